@@ -0,0 +1,305 @@
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+use caustic_core::{Color, TransferFunction, render::Denoiser};
+use thiserror::Error;
+
+use crate::{aovs::AovBuffer, color_to_image_rgb, parse_transfer_function};
+
+/// First bytes of an accumulation file
+const MAGIC: &[u8; 8] = b"CAUSTACC";
+
+/// Version of the accumulation file layout
+const VERSION: u32 = 1;
+
+#[derive(Error, Debug)]
+pub enum AccumulationError {
+    #[error("{path}: {source}")]
+    Io { path: String, source: io::Error },
+    #[error("{0}: not an accumulation file")]
+    NotAccumulation(String),
+    #[error("{path}: unsupported accumulation version {version}")]
+    UnsupportedVersion { path: String, version: u32 },
+    #[error("{path}: rendered {found}, expected {expected} like the other parts")]
+    SizeMismatch {
+        path: String,
+        found: String,
+        expected: String,
+    },
+}
+
+/// Sums of the linear colors rendered for every pixel along with the number
+/// of passes summed, so renders of the same scene made on several machines,
+/// or over several runs, can be merged into one less noisy image.
+///
+/// Files start with a small header, the magic bytes, version, width and
+/// height, followed by the sums and pass counts of each pixel in row order,
+/// all little endian.
+#[derive(Debug, Clone)]
+pub struct AccumulationBuffer {
+    width: u32,
+    height: u32,
+    sums: Vec<Color>,
+    passes: Vec<u32>,
+}
+
+impl AccumulationBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = width as usize * height as usize;
+        Self {
+            width,
+            height,
+            sums: vec![Color::BLACK; len],
+            passes: vec![0; len],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Adds one pass of a pixel, as returned by `Camera::render_linear`.
+    pub fn add(&mut self, x: u32, y: u32, color: Color) {
+        let i = self.index(x, y);
+        self.sums[i] += color;
+        self.passes[i] += 1;
+    }
+
+    /// Returns the most passes rendered into any pixel, the number of the
+    /// next pass when rendering continues.
+    pub fn pass_count(&self) -> u32 {
+        self.passes.iter().copied().max().unwrap_or(0)
+    }
+
+    /// Adds the passes of another render of the same scene.
+    pub fn merge(&mut self, other: &AccumulationBuffer) {
+        for (sum, other) in self.sums.iter_mut().zip(&other.sums) {
+            *sum += *other;
+        }
+        for (passes, other) in self.passes.iter_mut().zip(&other.passes) {
+            *passes += other;
+        }
+    }
+
+    /// Returns the averaged linear color of a pixel, black if it was never rendered.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Color {
+        let i = self.index(x, y);
+        match self.passes[i] {
+            0 => Color::BLACK,
+            passes => self.sums[i] / passes as f64,
+        }
+    }
+
+    /// Returns the 8-bit image of the averaged colors, encoded with `transfer`.
+    pub fn to_image(&self, transfer: TransferFunction) -> image::RgbImage {
+        image::RgbImage::from_fn(self.width, self.height, |x, y| {
+            color_to_image_rgb(self.get_pixel(x, y).encode(transfer))
+        })
+    }
+
+    /// Returns the 8-bit image of the averaged colors with their noise removed
+    /// by a [`Denoiser`] guided by `aovs`, encoded with `transfer`.
+    pub fn to_denoised_image(
+        &self,
+        aovs: &AovBuffer,
+        transfer: TransferFunction,
+    ) -> image::RgbImage {
+        let colors: Vec<Color> = (0..self.height)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.get_pixel(x, y))
+            .collect();
+        let denoised = Denoiser::new().denoise(self.width, self.height, &colors, aovs.pixels());
+        image::RgbImage::from_fn(self.width, self.height, |x, y| {
+            color_to_image_rgb(denoised[(y * self.width + x) as usize].encode(transfer))
+        })
+    }
+
+    /// Returns the floating point image of the averaged linear colors, for
+    /// outputs keeping the full range such as HDR and EXR files.
+    pub fn to_linear_image(&self) -> image::Rgb32FImage {
+        image::Rgb32FImage::from_fn(self.width, self.height, |x, y| {
+            let color = self.get_pixel(x, y);
+            image::Rgb([color.r as f32, color.g as f32, color.b as f32])
+        })
+    }
+
+    /// Errors if `other` was not rendered at the same size, `path` naming it.
+    pub fn check_size(
+        &self,
+        other: &AccumulationBuffer,
+        path: &str,
+    ) -> Result<(), AccumulationError> {
+        if (self.width, self.height) != (other.width, other.height) {
+            return Err(AccumulationError::SizeMismatch {
+                path: path.to_owned(),
+                found: format!("{}x{}", other.width, other.height),
+                expected: format!("{}x{}", self.width, self.height),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, AccumulationError> {
+        let io_error = |source| AccumulationError::Io {
+            path: path.display().to_string(),
+            source,
+        };
+        let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic).map_err(io_error)?;
+        if &magic != MAGIC {
+            return Err(AccumulationError::NotAccumulation(
+                path.display().to_string(),
+            ));
+        }
+        let version = read_u32(&mut reader).map_err(io_error)?;
+        if version != VERSION {
+            return Err(AccumulationError::UnsupportedVersion {
+                path: path.display().to_string(),
+                version,
+            });
+        }
+
+        let width = read_u32(&mut reader).map_err(io_error)?;
+        let height = read_u32(&mut reader).map_err(io_error)?;
+        let mut buffer = Self::new(width, height);
+        for i in 0..buffer.sums.len() {
+            let r = read_f64(&mut reader).map_err(io_error)?;
+            let g = read_f64(&mut reader).map_err(io_error)?;
+            let b = read_f64(&mut reader).map_err(io_error)?;
+            buffer.sums[i] = Color::new(r, g, b);
+            buffer.passes[i] = read_u32(&mut reader).map_err(io_error)?;
+        }
+        Ok(buffer)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AccumulationError> {
+        let io_error = |source| AccumulationError::Io {
+            path: path.display().to_string(),
+            source,
+        };
+        let mut writer = BufWriter::new(File::create(path).map_err(io_error)?);
+
+        let mut header = MAGIC.to_vec();
+        header.extend(VERSION.to_le_bytes());
+        header.extend(self.width.to_le_bytes());
+        header.extend(self.height.to_le_bytes());
+        writer.write_all(&header).map_err(io_error)?;
+
+        for (sum, passes) in self.sums.iter().zip(&self.passes) {
+            for v in [sum.r, sum.g, sum.b] {
+                writer.write_all(&v.to_le_bytes()).map_err(io_error)?;
+            }
+            writer.write_all(&passes.to_le_bytes()).map_err(io_error)?;
+        }
+        writer.flush().map_err(io_error)
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_f64(reader: &mut impl Read) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+/// Runs `caustic merge <part.accum>... -o <output>`, writing a PNG, or another
+/// accumulation file when the output ends in `.accum`. HDR and EXR outputs
+/// keep the linear colors, `--transfer` selects the encoding of other images.
+pub fn run_merge(args: &[String]) -> ExitCode {
+    let mut parts = vec![];
+    let mut output = None;
+    let mut transfer = TransferFunction::default();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--output" => output = args.next().cloned(),
+            "--transfer" => {
+                let value = args.next().map(String::as_str).unwrap_or_default();
+                match parse_transfer_function(value) {
+                    Some(value) => transfer = value,
+                    None => {
+                        eprintln!("invalid value for --transfer: {value}");
+                        return ExitCode::from(1);
+                    }
+                }
+            }
+            _ if arg.starts_with('-') => {
+                eprintln!("unknown option: {arg}");
+                return ExitCode::from(1);
+            }
+            _ => parts.push(arg.clone()),
+        }
+    }
+
+    let (Some(output), false) = (output, parts.is_empty()) else {
+        eprintln!(
+            "usage: caustic merge <part.accum>... -o <output.png|output.hdr|output.exr|output.accum> [--transfer <srgb|gamma2.2|gamma2|rec709|linear>]"
+        );
+        return ExitCode::from(1);
+    };
+
+    match merge_parts(&parts, Path::new(&output), transfer) {
+        Ok(()) => {
+            println!("merged {} parts into {output}", parts.len());
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{err}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn merge_parts(parts: &[String], output: &Path, transfer: TransferFunction) -> Result<(), String> {
+    let mut merged: Option<AccumulationBuffer> = None;
+    for part in parts {
+        let buffer = AccumulationBuffer::load(Path::new(part)).map_err(|err| err.to_string())?;
+        match &mut merged {
+            Some(merged) => {
+                merged
+                    .check_size(&buffer, part)
+                    .map_err(|err| err.to_string())?;
+                merged.merge(&buffer);
+            }
+            None => merged = Some(buffer),
+        }
+    }
+    let Some(merged) = merged else {
+        return Err("nothing to merge".to_owned());
+    };
+
+    let extension = output
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+    match extension.as_deref() {
+        Some("accum") => merged.save(output).map_err(|err| err.to_string()),
+        Some("hdr" | "exr") => merged
+            .to_linear_image()
+            .save(output)
+            .map_err(|err| format!("{}: {err}", output.display())),
+        _ => merged
+            .to_image(transfer)
+            .save(output)
+            .map_err(|err| format!("{}: {err}", output.display())),
+    }
+}
@@ -0,0 +1,97 @@
+use std::{path::PathBuf, process::ExitCode, sync::Arc};
+
+use caustic_core::{RenderContext, random_new};
+use caustic_openscad::{animation::frame_time, library::LibraryPath};
+
+use crate::{estimate::parse_positive, parse_scene_name, render_image, scene::get_scene_at_time};
+
+/// Frames rendered when `--frames` is not given
+const DEFAULT_FRAMES: u32 = 24;
+
+/// Options for `caustic animate <scene> [--frames N] [--output DIR]`.
+struct AnimateOptions {
+    scene_name: String,
+    frames: u32,
+    output: PathBuf,
+}
+
+/// Renders every frame of an animated scene, with `$t` going from 0 towards 1,
+/// to numbered PNG files which can be put together with any video tool.
+pub fn run(args: &[String], library_path: &LibraryPath) -> ExitCode {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!(
+                "usage: caustic animate <scene> [--frames N] [--output DIR] [--library-path DIR]"
+            );
+            return ExitCode::from(1);
+        }
+    };
+
+    if let Err(err) = std::fs::create_dir_all(&options.output) {
+        eprintln!("failed to create {}: {err}", options.output.display());
+        return ExitCode::from(1);
+    }
+
+    let ctx = Arc::new(RenderContext {
+        random: random_new(),
+    });
+
+    for frame in 0..options.frames {
+        let Some(scene) = parse_scene_name(&options.scene_name) else {
+            eprintln!("invalid scene name: {}", options.scene_name);
+            return ExitCode::from(1);
+        };
+        let time = frame_time(frame, options.frames);
+        let scene = match get_scene_at_time(&ctx, scene, library_path, time) {
+            Ok(scene) => scene,
+            Err(err) => {
+                eprintln!("failed to get scene at $t = {time}: {err}");
+                return ExitCode::from(1);
+            }
+        };
+
+        eprintln!("frame {}/{} ($t = {time})", frame + 1, options.frames);
+        let path = options.output.join(format!("frame_{frame:04}.png"));
+        if let Err(err) = render_image(&ctx, &scene).save(&path) {
+            eprintln!("failed to write {}: {err}", path.display());
+            return ExitCode::from(1);
+        }
+    }
+
+    println!(
+        "{} frames written to {}",
+        options.frames,
+        options.output.display()
+    );
+    ExitCode::SUCCESS
+}
+
+fn parse_args(args: &[String]) -> Result<AnimateOptions, String> {
+    let mut scene_name = None;
+    let mut frames = DEFAULT_FRAMES;
+    let mut output = PathBuf::from("../../target/animation");
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--frames" => frames = parse_positive(arg, args.next())?,
+            "--output" => {
+                output = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for {arg}"))?
+                    .into()
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {arg}")),
+            _ if scene_name.is_none() => scene_name = Some(arg.to_owned()),
+            _ => return Err(format!("unexpected argument: {arg}")),
+        }
+    }
+
+    Ok(AnimateOptions {
+        scene_name: scene_name.ok_or("missing scene name")?,
+        frames,
+        output,
+    })
+}
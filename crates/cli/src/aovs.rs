@@ -0,0 +1,138 @@
+use std::{
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicU32, Ordering},
+    },
+};
+
+use caustic_core::{Aovs, Color, RenderContext, SceneData, TransferFunction};
+
+use crate::color_to_image_rgb;
+
+/// The [`Aovs`] of every pixel of a render, in row order.
+pub struct AovBuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<Aovs>,
+    /// Number of pixels set since the buffer was created empty
+    set_count: usize,
+}
+
+impl AovBuffer {
+    /// Creates a buffer to be filled with [`AovBuffer::set`] while rendering.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Aovs::default(); width as usize * height as usize],
+            set_count: 0,
+        }
+    }
+
+    /// Sets the AOVs of a pixel, each pixel once.
+    pub fn set(&mut self, x: u32, y: u32, aovs: Aovs) {
+        self.pixels[(y * self.width + x) as usize] = aovs;
+        self.set_count += 1;
+    }
+
+    /// Returns whether every pixel was set.
+    pub fn is_complete(&self) -> bool {
+        self.set_count == self.pixels.len()
+    }
+
+    /// Computes the AOVs of every pixel of the scene on all cores, a row at a
+    /// time, for renders which did not fill a buffer as they went.
+    pub fn render(ctx: &Arc<RenderContext>, scene: &SceneData) -> Self {
+        let width = scene.camera.image_width();
+        let height = scene.camera.image_height();
+        let next_row = AtomicU32::new(0);
+
+        let mut rows: Vec<(u32, Vec<Aovs>)> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..num_cpus::get())
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut rows = vec![];
+                        loop {
+                            let y = next_row.fetch_add(1, Ordering::Relaxed);
+                            if y >= height {
+                                break rows;
+                            }
+                            let row = (0..width)
+                                .map(|x| scene.camera.aovs(ctx, x, y, &*scene.world))
+                                .collect();
+                            rows.push((y, row));
+                        }
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap())
+                .collect()
+        });
+        rows.sort_by_key(|(y, _)| *y);
+
+        let pixels: Vec<Aovs> = rows.into_iter().flat_map(|(_, row)| row).collect();
+        Self {
+            width,
+            height,
+            set_count: pixels.len(),
+            pixels,
+        }
+    }
+
+    /// Returns the AOVs of every pixel in row order.
+    pub fn pixels(&self) -> &[Aovs] {
+        &self.pixels
+    }
+
+    fn get(&self, x: u32, y: u32) -> &Aovs {
+        &self.pixels[(y * self.width + x) as usize]
+    }
+
+    /// Writes `<prefix>_albedo.png`, `<prefix>_normal.png`, `<prefix>_depth.exr`
+    /// and `<prefix>_object_id.png` to `dir`.
+    ///
+    /// Normals are mapped from -1..1 to 0..1 per axis and depth is kept in
+    /// scene units, with the background at 0.
+    pub fn save(&self, dir: &Path, prefix: &str) -> Result<(), String> {
+        let save = |name: &str, result: image::ImageResult<()>| {
+            result.map_err(|err| format!("{}: {err}", dir.join(name).display()))
+        };
+
+        let name = format!("{prefix}_albedo.png");
+        let albedo = image::RgbImage::from_fn(self.width, self.height, |x, y| {
+            color_to_image_rgb(self.get(x, y).albedo.encode(TransferFunction::Srgb))
+        });
+        save(&name, albedo.save(dir.join(&name)))?;
+
+        let name = format!("{prefix}_normal.png");
+        let normal = image::RgbImage::from_fn(self.width, self.height, |x, y| {
+            let n = self.get(x, y).normal;
+            color_to_image_rgb(Color::new(
+                (n.x * 0.5 + 0.5).clamp(0.0, 1.0),
+                (n.y * 0.5 + 0.5).clamp(0.0, 1.0),
+                (n.z * 0.5 + 0.5).clamp(0.0, 1.0),
+            ))
+        });
+        save(&name, normal.save(dir.join(&name)))?;
+
+        let name = format!("{prefix}_depth.exr");
+        let depth = image::Rgb32FImage::from_fn(self.width, self.height, |x, y| {
+            let depth = self.get(x, y).depth;
+            let depth = if depth.is_finite() { depth as f32 } else { 0.0 };
+            image::Rgb([depth, depth, depth])
+        });
+        save(&name, depth.save(dir.join(&name)))?;
+
+        let name = format!("{prefix}_object_id.png");
+        let object_id = image::RgbImage::from_fn(self.width, self.height, |x, y| {
+            let [r, g, b, _] = self.get(x, y).object_id.to_le_bytes();
+            image::Rgb([r, g, b])
+        });
+        save(&name, object_id.save(dir.join(&name)))?;
+
+        Ok(())
+    }
+}
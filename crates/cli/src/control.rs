@@ -0,0 +1,172 @@
+use std::{
+    io::{self, IsTerminal},
+    sync::{
+        Arc, Condvar, Mutex,
+        atomic::{AtomicBool, AtomicU32, Ordering},
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    terminal,
+};
+
+/// Lets a render be paused, resumed, cancelled or extended while it runs. The
+/// render workers check it before every block they render.
+#[derive(Debug)]
+pub struct RenderControl {
+    paused: Mutex<bool>,
+    resumed: Condvar,
+    cancelled: AtomicBool,
+    target_passes: AtomicU32,
+    snapshot_requested: AtomicBool,
+}
+
+impl RenderControl {
+    pub fn new(target_passes: u32) -> Self {
+        Self {
+            paused: Mutex::new(false),
+            resumed: Condvar::new(),
+            cancelled: AtomicBool::new(false),
+            target_passes: AtomicU32::new(target_passes.max(1)),
+            snapshot_requested: AtomicBool::new(false),
+        }
+    }
+
+    /// Pauses the render if it is running and resumes it otherwise, returning
+    /// whether it is now paused.
+    pub fn toggle_pause(&self) -> bool {
+        let mut paused = self.paused.lock().unwrap();
+        *paused = !*paused;
+        if !*paused {
+            self.resumed.notify_all();
+        }
+        *paused
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.paused.lock().unwrap()
+    }
+
+    /// Blocks while the render is paused. Returns false once it is cancelled.
+    pub fn wait_while_paused(&self) -> bool {
+        let mut paused = self.paused.lock().unwrap();
+        while *paused && !self.is_cancelled() {
+            paused = self.resumed.wait(paused).unwrap();
+        }
+        !self.is_cancelled()
+    }
+
+    /// Stops the render, blocks already being rendered are still finished.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        let _paused = self.paused.lock().unwrap();
+        self.resumed.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of passes over the image to render, each pass adding
+    /// the camera's samples per pixel.
+    pub fn get_target_passes(&self) -> u32 {
+        self.target_passes.load(Ordering::SeqCst)
+    }
+
+    /// Sets the number of passes to render, at least 1. Lowering it below the
+    /// passes already started finishes the render after the current pass.
+    pub fn set_target_passes(&self, target_passes: u32) {
+        self.target_passes
+            .store(target_passes.max(1), Ordering::SeqCst);
+    }
+
+    /// Asks the render to save an image of the passes rendered so far.
+    pub fn request_snapshot(&self) {
+        self.snapshot_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Returns whether a snapshot was requested since the last call.
+    pub fn take_snapshot_request(&self) -> bool {
+        self.snapshot_requested.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// Steers a render from the keyboard while it runs, until dropped:
+///
+/// - space pauses and resumes the render workers
+/// - `s` saves a snapshot of the image rendered so far
+/// - `+` and `-` add or remove a pass
+/// - `q`, escape or ctrl-c stop the render, keeping what was rendered
+pub struct KeyboardControl {
+    done: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl KeyboardControl {
+    /// Reads keys in the background, or returns `None` if standard input is
+    /// not a terminal.
+    pub fn start(control: Arc<RenderControl>) -> io::Result<Option<Self>> {
+        if !io::stdin().is_terminal() {
+            return Ok(None);
+        }
+        terminal::enable_raw_mode()?;
+
+        let done = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::Builder::new()
+            .name("KeyboardControl".to_owned())
+            .spawn({
+                let done = done.clone();
+                move || read_keys(&control, &done)
+            })?;
+        Ok(Some(Self {
+            done,
+            thread: Some(thread),
+        }))
+    }
+}
+
+impl Drop for KeyboardControl {
+    fn drop(&mut self) {
+        self.done.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn read_keys(control: &RenderControl, done: &AtomicBool) {
+    while !done.load(Ordering::SeqCst) {
+        // Poll rather than block so the thread notices when the render is over
+        match event::poll(Duration::from_millis(100)) {
+            Ok(true) => {}
+            Ok(false) => continue,
+            Err(_) => return,
+        }
+        let Ok(Event::Key(key)) = event::read() else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char(' ') => {
+                control.toggle_pause();
+            }
+            KeyCode::Char('s') | KeyCode::Char('S') => control.request_snapshot(),
+            KeyCode::Char('+') | KeyCode::Char('=') => {
+                control.set_target_passes(control.get_target_passes() + 1)
+            }
+            KeyCode::Char('-') => {
+                control.set_target_passes(control.get_target_passes().saturating_sub(1))
+            }
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => control.cancel(),
+            KeyCode::Char('q') | KeyCode::Esc => control.cancel(),
+            _ => {}
+        }
+    }
+}
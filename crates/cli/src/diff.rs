@@ -0,0 +1,230 @@
+use std::{path::Path, process::ExitCode, sync::Arc};
+
+use caustic_core::{Color, RenderContext, random_new};
+use caustic_openscad::library::LibraryPath;
+use image::{ImageFormat, RgbImage};
+
+use crate::{parse_scene_name, render_image, scene::get_scene};
+
+/// Side of the square windows SSIM is computed over
+const SSIM_WINDOW: u32 = 8;
+
+/// Offset between consecutive SSIM windows
+const SSIM_STRIDE: u32 = 4;
+
+/// ΔE below which a difference is not noticeable
+const JUST_NOTICEABLE_DIFFERENCE: f64 = 2.3;
+
+/// ΔE drawn at full brightness in the difference image
+const MAX_DISPLAYED_DIFFERENCE: f64 = 20.0;
+
+/// Options for `caustic diff <a> <b> [--output FILE]`.
+struct DiffOptions {
+    a: String,
+    b: String,
+    output: String,
+}
+
+/// Compares two renders, given as scenes to render or image files, writing an
+/// image highlighting where they differ and printing similarity metrics.
+///
+/// Rendered scenes are noisy, so even identical scenes only match exactly when
+/// comparing saved images, use enough samples per pixel when rendering.
+pub fn run(args: &[String], library_path: &LibraryPath) -> ExitCode {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!(
+                "usage: caustic diff <scene|image> <scene|image> [--output FILE] [--library-path DIR]"
+            );
+            return ExitCode::from(1);
+        }
+    };
+
+    let images = load_image(&options.a, library_path)
+        .and_then(|a| Ok((a, load_image(&options.b, library_path)?)));
+    let (a, b) = match images {
+        Ok(images) => images,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    if a.dimensions() != b.dimensions() {
+        eprintln!(
+            "image sizes differ: {}x{} and {}x{}",
+            a.width(),
+            a.height(),
+            b.width(),
+            b.height()
+        );
+        return ExitCode::from(1);
+    }
+
+    let delta_e = delta_e_per_pixel(&a, &b);
+    let max_delta_e = delta_e.iter().copied().fold(0.0, f64::max);
+    let mean_delta_e = delta_e.iter().sum::<f64>() / delta_e.len() as f64;
+    let noticeable = delta_e
+        .iter()
+        .filter(|delta_e| **delta_e > JUST_NOTICEABLE_DIFFERENCE)
+        .count();
+
+    println!("SSIM:        {:.4}", ssim(&a, &b));
+    println!("max ΔE:      {max_delta_e:.2}");
+    println!("mean ΔE:     {mean_delta_e:.2}");
+    println!(
+        "noticeable:  {noticeable} pixels ({:.2}%)",
+        100.0 * noticeable as f64 / delta_e.len() as f64
+    );
+
+    if let Err(err) = difference_image(&a, &delta_e).save(&options.output) {
+        eprintln!("failed to write {}: {err}", options.output);
+        return ExitCode::from(1);
+    }
+    println!("difference image written to {}", options.output);
+
+    ExitCode::SUCCESS
+}
+
+fn parse_args(args: &[String]) -> Result<DiffOptions, String> {
+    let mut inputs = vec![];
+    let mut output = "diff.png".to_owned();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" => {
+                output = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for {arg}"))?
+                    .to_owned()
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {arg}")),
+            _ if inputs.len() < 2 => inputs.push(arg.to_owned()),
+            _ => return Err(format!("unexpected argument: {arg}")),
+        }
+    }
+
+    let [a, b]: [String; 2] = inputs
+        .try_into()
+        .map_err(|_| "expected two scenes or images to compare")?;
+    Ok(DiffOptions { a, b, output })
+}
+
+/// Opens an image file, or renders the scene when the input is not an image.
+fn load_image(input: &str, library_path: &LibraryPath) -> Result<RgbImage, String> {
+    if ImageFormat::from_path(input).is_ok() {
+        return image::open(input)
+            .map(|image| image.to_rgb8())
+            .map_err(|err| format!("failed to open {input}: {err}"));
+    }
+
+    let scene = parse_scene_name(input).ok_or_else(|| format!("invalid scene name: {input}"))?;
+    let ctx = Arc::new(RenderContext {
+        random: random_new(),
+    });
+    let scene = get_scene(&ctx, scene, library_path)
+        .map_err(|err| format!("failed to get scene {input}: {err}"))?;
+    println!("rendering {}", Path::new(input).display());
+    Ok(render_image(&ctx, &scene))
+}
+
+/// Mean structural similarity of the luma of both images, 1 when identical.
+fn ssim(a: &RgbImage, b: &RgbImage) -> f64 {
+    const C1: f64 = 0.01 * 0.01;
+    const C2: f64 = 0.03 * 0.03;
+
+    let luma = |image: &RgbImage, x: u32, y: u32| pixel_color(image, x, y).luminance();
+    let window_w = SSIM_WINDOW.min(a.width());
+    let window_h = SSIM_WINDOW.min(a.height());
+    let n = (window_w * window_h) as f64;
+
+    let mut total = 0.0;
+    let mut windows = 0;
+    for y0 in (0..=a.height() - window_h).step_by(SSIM_STRIDE as usize) {
+        for x0 in (0..=a.width() - window_w).step_by(SSIM_STRIDE as usize) {
+            let (mut sum_a, mut sum_b, mut sum_aa, mut sum_bb, mut sum_ab) =
+                (0.0, 0.0, 0.0, 0.0, 0.0);
+            for y in y0..y0 + window_h {
+                for x in x0..x0 + window_w {
+                    let la = luma(a, x, y);
+                    let lb = luma(b, x, y);
+                    sum_a += la;
+                    sum_b += lb;
+                    sum_aa += la * la;
+                    sum_bb += lb * lb;
+                    sum_ab += la * lb;
+                }
+            }
+
+            let mean_a = sum_a / n;
+            let mean_b = sum_b / n;
+            let var_a = sum_aa / n - mean_a * mean_a;
+            let var_b = sum_bb / n - mean_b * mean_b;
+            let covariance = sum_ab / n - mean_a * mean_b;
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+    total / windows as f64
+}
+
+/// CIE76 color difference of each pixel, in row order.
+fn delta_e_per_pixel(a: &RgbImage, b: &RgbImage) -> Vec<f64> {
+    a.enumerate_pixels()
+        .map(|(x, y, _)| {
+            let lab_a = to_lab(pixel_color(a, x, y));
+            let lab_b = to_lab(pixel_color(b, x, y));
+            lab_a
+                .iter()
+                .zip(lab_b)
+                .map(|(a, b)| (a - b).powi(2))
+                .sum::<f64>()
+                .sqrt()
+        })
+        .collect()
+}
+
+/// Dims the first image and draws noticeable differences over it in red,
+/// brighter for larger differences.
+fn difference_image(a: &RgbImage, delta_e: &[f64]) -> RgbImage {
+    let mut img = RgbImage::new(a.width(), a.height());
+    for ((x, y, pixel), delta_e) in img.enumerate_pixels_mut().zip(delta_e) {
+        let background = (pixel_color(a, x, y).luminance() * 64.0) as u8;
+        *pixel = if *delta_e > JUST_NOTICEABLE_DIFFERENCE {
+            let intensity = (delta_e / MAX_DISPLAYED_DIFFERENCE).min(1.0);
+            image::Rgb([(128.0 + 127.0 * intensity) as u8, background, background])
+        } else {
+            image::Rgb([background, background, background])
+        };
+    }
+    img
+}
+
+/// Color of a pixel, still sRGB encoded.
+fn pixel_color(image: &RgbImage, x: u32, y: u32) -> Color {
+    let [r, g, b] = image.get_pixel(x, y).0;
+    Color::new(r as f64 / 255.0, g as f64 / 255.0, b as f64 / 255.0)
+}
+
+/// Converts an sRGB encoded color to CIELAB, using the D65 white point.
+fn to_lab(color: Color) -> [f64; 3] {
+    let c = color.srgb_to_linear();
+    let x = (0.4124 * c.r + 0.3576 * c.g + 0.1805 * c.b) / 0.95047;
+    let y = 0.2126 * c.r + 0.7152 * c.g + 0.0722 * c.b;
+    let z = (0.0193 * c.r + 0.1192 * c.g + 0.9505 * c.b) / 1.08883;
+
+    let f = |t: f64| {
+        if t > 216.0 / 24389.0 {
+            t.cbrt()
+        } else {
+            (24389.0 / 27.0 * t + 16.0) / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
@@ -0,0 +1,182 @@
+use std::{
+    process::ExitCode,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use caustic_core::{RenderContext, SceneData, random_new};
+use caustic_openscad::library::LibraryPath;
+
+use crate::{parse_scene_name, scene::get_scene};
+
+/// Number of probe pixels along each axis of the image
+const PROBE_GRID_SIZE: u32 = 16;
+
+/// z-score of a 95% confidence interval
+const CONFIDENCE_Z: f64 = 1.96;
+
+/// Options for `caustic estimate <scene> [--samples N] [--width W]`.
+struct EstimateOptions {
+    scene_name: String,
+    samples_per_pixel: Option<u32>,
+    image_width: Option<u32>,
+}
+
+/// Estimated render time with a 95% confidence interval.
+struct Estimate {
+    total: Duration,
+    low: Duration,
+    high: Duration,
+}
+
+/// Renders a sparse grid of probe pixels and extrapolates the time a full render
+/// would take at the requested sample count and image width.
+pub fn run(args: &[String], library_path: &LibraryPath) -> ExitCode {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!(
+                "usage: caustic estimate <scene> [--samples N] [--width W] [--library-path DIR]"
+            );
+            return ExitCode::from(1);
+        }
+    };
+
+    let Some(scene) = parse_scene_name(&options.scene_name) else {
+        eprintln!("invalid scene name: {}", options.scene_name);
+        return ExitCode::from(1);
+    };
+
+    let ctx = Arc::new(RenderContext {
+        random: random_new(),
+    });
+
+    let scene = match get_scene(&ctx, scene, library_path) {
+        Ok(scene) => scene,
+        Err(err) => {
+            eprintln!("failed to get scene: {err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let camera = &scene.camera;
+    let samples_per_pixel = options
+        .samples_per_pixel
+        .unwrap_or(camera.samples_per_pixel());
+    let image_width = options.image_width.unwrap_or(camera.image_width());
+    // Keep the aspect ratio of the scene camera
+    let image_height = ((image_width as u64 * camera.image_height() as u64
+        / camera.image_width() as u64) as u32)
+        .max(1);
+    let threads = num_cpus::get();
+
+    let sample_times = probe_sample_times(&ctx, &scene);
+    let estimate = extrapolate(
+        &sample_times,
+        image_width as f64 * image_height as f64 * samples_per_pixel as f64 / threads as f64,
+    );
+
+    println!(
+        "image:    {image_width}x{image_height}, {samples_per_pixel} samples per pixel, {threads} threads"
+    );
+    println!(
+        "probes:   {} pixels at {} samples per pixel",
+        sample_times.len(),
+        camera.samples_per_pixel()
+    );
+    println!("estimate: {}", format_duration(estimate.total));
+    println!(
+        "95% CI:   {} - {}",
+        format_duration(estimate.low),
+        format_duration(estimate.high)
+    );
+
+    ExitCode::SUCCESS
+}
+
+fn parse_args(args: &[String]) -> Result<EstimateOptions, String> {
+    let mut scene_name = None;
+    let mut samples_per_pixel = None;
+    let mut image_width = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--samples" => samples_per_pixel = Some(parse_positive(arg, args.next())?),
+            "--width" => image_width = Some(parse_positive(arg, args.next())?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {arg}")),
+            _ if scene_name.is_none() => scene_name = Some(arg.to_owned()),
+            _ => return Err(format!("unexpected argument: {arg}")),
+        }
+    }
+
+    Ok(EstimateOptions {
+        scene_name: scene_name.ok_or("missing scene name")?,
+        samples_per_pixel,
+        image_width,
+    })
+}
+
+pub(crate) fn parse_positive(option: &str, value: Option<&String>) -> Result<u32, String> {
+    let value = value.ok_or_else(|| format!("missing value for {option}"))?;
+    match value.parse::<u32>() {
+        Ok(value) if value > 0 => Ok(value),
+        _ => Err(format!(
+            "{option} must be a positive integer, found \"{value}\""
+        )),
+    }
+}
+
+/// Renders an evenly spaced grid of pixels with the scene camera and returns the
+/// time taken per sample, in seconds, for each of them.
+fn probe_sample_times(ctx: &RenderContext, scene: &SceneData) -> Vec<f64> {
+    let camera = &scene.camera;
+    let samples = camera.samples_per_pixel().max(1) as f64;
+    let columns = PROBE_GRID_SIZE.min(camera.image_width());
+    let rows = PROBE_GRID_SIZE.min(camera.image_height());
+
+    let mut sample_times = Vec::with_capacity((columns * rows) as usize);
+    for row in 0..rows {
+        for column in 0..columns {
+            // Sample the middle of each grid cell
+            let x = ((2 * column + 1) * camera.image_width()) / (2 * columns);
+            let y = ((2 * row + 1) * camera.image_height()) / (2 * rows);
+
+            let start = Instant::now();
+            camera.render(ctx, x, y, &*scene.world, scene.lights.clone());
+            sample_times.push(start.elapsed().as_secs_f64() / samples);
+        }
+    }
+    sample_times
+}
+
+/// Scales the mean probe time by the number of samples each thread renders.
+/// The interval is the standard error of the mean scaled the same way.
+fn extrapolate(sample_times: &[f64], samples_per_thread: f64) -> Estimate {
+    let n = sample_times.len() as f64;
+    let mean = sample_times.iter().sum::<f64>() / n;
+    let variance = if sample_times.len() > 1 {
+        sample_times.iter().map(|t| (t - mean).powi(2)).sum::<f64>() / (n - 1.0)
+    } else {
+        0.0
+    };
+    let margin = CONFIDENCE_Z * (variance / n).sqrt();
+
+    Estimate {
+        total: Duration::from_secs_f64(mean * samples_per_thread),
+        low: Duration::from_secs_f64((mean - margin).max(0.0) * samples_per_thread),
+        high: Duration::from_secs_f64((mean + margin) * samples_per_thread),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds >= 3600 {
+        format!("{}h {:02}m", seconds / 3600, (seconds % 3600) / 60)
+    } else if seconds >= 60 {
+        format!("{}m {:02}s", seconds / 60, seconds % 60)
+    } else {
+        format!("{:.1}s", duration.as_secs_f64())
+    }
+}
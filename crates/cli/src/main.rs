@@ -1,20 +1,44 @@
 use thread_priority::ThreadBuilderExt;
 use thread_priority::*;
 
+pub mod accumulation;
+pub mod animate;
+pub mod aovs;
+pub mod control;
+pub mod diff;
+pub mod estimate;
+pub mod profile;
+pub mod rng_bench;
 pub mod scene;
+pub mod tiling;
 
 use std::{
     env,
+    path::{Path, PathBuf},
     process::ExitCode,
-    sync::{Arc, Mutex, mpsc},
+    sync::{
+        Arc, Mutex,
+        mpsc::{self, RecvTimeoutError},
+    },
+    time::Duration,
 };
 
-use caustic_core::{Camera, Color, Node, RenderContext, random_new};
+use caustic_core::{
+    Aovs, Camera, Color, CropWindow, Exposure, GuideLine, Guides, Light, Node, RenderContext,
+    RenderRegion, SceneData, TransferFunction, random::RandomGenerator, render::TileOrder,
+};
+use caustic_openscad::library::LibraryPath;
 use indicatif::{ProgressBar, ProgressStyle};
 use scene::Scene;
 use thiserror::Error;
 
-use crate::scene::get_scene;
+use crate::{
+    accumulation::AccumulationBuffer,
+    aovs::AovBuffer,
+    control::{KeyboardControl, RenderControl},
+    scene::get_scene,
+    tiling::{TileSize, Tiling},
+};
 
 #[derive(Error, Debug)]
 pub enum CliError {
@@ -24,124 +48,432 @@ pub enum CliError {
 
 pub type Result<T> = core::result::Result<T, CliError>;
 
-const BLOCK_SIZE: u32 = 10;
+/// Where snapshots requested from the keyboard are written
+const SNAPSHOT_PATH: &str = "../../target/snapshot.png";
 
 fn main() -> ExitCode {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let library_path = match take_library_path(&mut args) {
+        Ok(library_path) => library_path,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    if args.get(1).is_some_and(|arg| arg == "estimate") {
+        return estimate::run(&args[2..], &library_path);
+    }
+
+    if args.get(1).is_some_and(|arg| arg == "diff") {
+        return diff::run(&args[2..], &library_path);
+    }
+
+    if args.get(1).is_some_and(|arg| arg == "profile") {
+        return profile::run(&args[2..], &library_path);
+    }
+
+    if args.get(1).is_some_and(|arg| arg == "rng-bench") {
+        return rng_bench::run(&args[2..], &library_path);
+    }
+
+    if args.get(1).is_some_and(|arg| arg == "animate") {
+        return animate::run(&args[2..], &library_path);
+    }
+
+    if args.get(1).is_some_and(|arg| arg == "merge") {
+        return accumulation::run_merge(&args[2..]);
+    }
+
+    let accumulation_path = match take_accumulation_path(&mut args) {
+        Ok(accumulation_path) => accumulation_path,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let interactive = take_interactive(&mut args);
+    let aovs = take_aovs(&mut args);
+    let denoise = take_denoise(&mut args);
+
+    let camera_name = match take_camera_name(&mut args) {
+        Ok(camera_name) => camera_name,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let render_region = match take_render_region(&mut args) {
+        Ok(render_region) => render_region,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let crop_window = match take_crop_window(&mut args) {
+        Ok(crop_window) => crop_window,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let overscan = match take_overscan(&mut args) {
+        Ok(overscan) => overscan,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let exposure = match take_exposure(&mut args) {
+        Ok(exposure) => exposure,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let transfer = match take_transfer_function(&mut args) {
+        Ok(transfer) => transfer,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let tile_order = match take_tile_order(&mut args) {
+        Ok(tile_order) => tile_order,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let hardest_first = take_hardest_first(&mut args);
+
+    let tile_size = match take_tile_size(&mut args) {
+        Ok(tile_size) => tile_size,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let guides = match take_guides(&mut args) {
+        Ok(guides) => guides,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let seed = match take_seed(&mut args) {
+        Ok(seed) => seed,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let generator = match take_random_generator(&mut args) {
+        Ok(generator) => generator,
+        Err(err) => {
+            eprintln!("{err}");
+            return ExitCode::from(1);
+        }
+    };
+    if seed.is_some() && generator.is_some() {
+        eprintln!("--rng cannot be used with --seed, seeded renders use their own generator");
+        return ExitCode::from(1);
+    }
 
     let mut scene = Scene::ThreeSpheres;
     if let Some(scene_name) = args.get(1) {
-        scene = if scene_name == "ThreeSpheres" {
-            Scene::ThreeSpheres
-        } else if scene_name == "RandomSpheres" {
-            Scene::RandomSpheres
-        } else if scene_name == "CheckeredSpheres" {
-            Scene::CheckeredSpheres
-        } else if scene_name == "Earth" {
-            Scene::Earth
-        } else if scene_name == "PerlinSpheres" {
-            Scene::PerlinSpheres
-        } else if scene_name == "Quads" {
-            Scene::Quads
-        } else if scene_name == "LightedSphere" {
-            Scene::LightedSphere
-        } else if scene_name == "LightedConeFrustum" {
-            Scene::LightedConeFrustum
-        } else if scene_name == "CornellBox" {
-            Scene::CornellBox
-        } else if scene_name == "CornellBoxSmoke" {
-            Scene::CornellBoxSmoke
-        } else if scene_name == "Final" {
-            Scene::Final
-        } else if scene_name.to_lowercase().ends_with(".scad") {
-            Scene::OpenScad(scene_name.to_owned())
-        } else {
-            eprintln!("invalid scene name: {scene_name}");
-            return ExitCode::from(1);
+        scene = match parse_scene_name(scene_name) {
+            Some(scene) => scene,
+            None => {
+                eprintln!("invalid scene name: {scene_name}");
+                return ExitCode::from(1);
+            }
         }
     }
 
-    let ctx = Arc::new(RenderContext {
-        random: random_new(),
+    let ctx = Arc::new(match seed {
+        Some(seed) => RenderContext::new_seeded(seed),
+        None => RenderContext::new_with_generator(generator.unwrap_or_default()),
     });
 
-    let scene = match get_scene(&ctx, scene) {
+    let mut scene = match get_scene(&ctx, scene, &library_path) {
         Ok(scene) => scene,
         Err(err) => {
             eprintln!("failed to get scene: {err}");
             return ExitCode::from(1);
         }
     };
+    if let Some(camera_name) = camera_name
+        && !scene.select_camera(&camera_name)
+    {
+        eprintln!(
+            "no camera named \"{camera_name}\", the scene has: {}",
+            scene.camera_names().join(", ")
+        );
+        return ExitCode::from(1);
+    }
+    if render_region.is_some() {
+        scene.camera = Arc::new(scene.camera.with_render_region(render_region));
+    }
+    if crop_window.is_some() {
+        scene.camera = Arc::new(scene.camera.with_crop_window(crop_window));
+    }
+    if let Some(overscan) = overscan {
+        scene.camera = Arc::new(scene.camera.with_overscan(overscan));
+    }
+    if let Some(exposure) = exposure {
+        scene.camera = Arc::new(scene.camera.with_exposure(exposure));
+    }
+    let guide_lines = guides.lines(&scene.camera);
 
-    // render image
-    let mut img: image::ImageBuffer<
-        image::Rgb<u8>,
-        Vec<<image::Rgb<u8> as image::Pixel>::Subpixel>,
-    > = image::ImageBuffer::new(scene.camera.image_width(), scene.camera.image_height());
-
-    // generate work
-    let mut work: Vec<Work> = vec![];
-    let mut y = 0;
-    loop {
-        let mut x = 0;
-        loop {
-            work.push(Work {
-                camera: scene.camera.clone(),
-                world: scene.world.clone(),
-                lights: scene.lights.clone(),
-                xmin: x,
-                xmax: (x + BLOCK_SIZE).min(img.width()),
-                ymin: y,
-                ymax: (y + BLOCK_SIZE).min(img.height()),
-            });
-            if x > img.width() {
-                break;
+    // Resume from the passes already rendered into the file, checked before
+    // rendering so a mismatched file does not waste a render
+    let mut previous =
+        AccumulationBuffer::new(scene.camera.image_width(), scene.camera.image_height());
+    if let Some(path) = &accumulation_path
+        && path.exists()
+    {
+        let result = AccumulationBuffer::load(path).and_then(|loaded| {
+            previous.check_size(&loaded, &path.display().to_string())?;
+            Ok(loaded)
+        });
+        match result {
+            Ok(loaded) => previous = loaded,
+            Err(err) => {
+                eprintln!("{err}");
+                return ExitCode::from(1);
             }
-            x += BLOCK_SIZE;
         }
-        if y > img.height() {
-            break;
+    }
+
+    let control = Arc::new(RenderControl::new(1));
+    let keyboard = if interactive {
+        eprintln!("space: pause/resume, s: snapshot, +/-: more/fewer passes, q: stop");
+        match KeyboardControl::start(control.clone()) {
+            Ok(keyboard) => keyboard,
+            Err(err) => {
+                eprintln!("failed to read the keyboard: {err}");
+                return ExitCode::from(1);
+            }
         }
-        y += BLOCK_SIZE;
+    } else {
+        None
+    };
+    let snapshot = Snapshot {
+        path: Path::new(SNAPSHOT_PATH),
+        guides: &guide_lines,
+        transfer,
+    };
+    let tiling = Tiling {
+        order: tile_order,
+        size: tile_size,
+        hardest_first,
+    };
+    // AOVs come from the first pass, which a cancelled render may not finish
+    let mut aov_buffer = (aovs || denoise)
+        .then(|| AovBuffer::new(scene.camera.image_width(), scene.camera.image_height()));
+    let accumulation = render_accumulation_with_control(
+        &ctx,
+        &scene,
+        &tiling,
+        &control,
+        Some(&snapshot),
+        previous,
+        aov_buffer.as_mut(),
+    );
+    drop(keyboard);
+
+    if let Some(path) = accumulation_path
+        && let Err(err) = accumulation.save(&path)
+    {
+        eprintln!("{err}");
+        return ExitCode::from(1);
     }
-    let work_count = work.len();
+
+    let aov_buffer = aov_buffer.map(|aov_buffer| {
+        if aov_buffer.is_complete() {
+            aov_buffer
+        } else {
+            AovBuffer::render(&ctx, &scene)
+        }
+    });
+    let mut image = match &aov_buffer {
+        Some(aov_buffer) if denoise => accumulation.to_denoised_image(aov_buffer, transfer),
+        _ => accumulation.to_image(transfer),
+    };
+    draw_guides(&mut image, &guide_lines);
+    image.save("../../target/out.png").unwrap();
+
+    if aovs
+        && let Some(aov_buffer) = aov_buffer
+        && let Err(err) = aov_buffer.save(Path::new("../../target"), "out")
+    {
+        eprintln!("{err}");
+        return ExitCode::from(1);
+    }
+    ExitCode::SUCCESS
+}
+
+/// Renders every pixel of the scene on all cores, showing a progress bar.
+pub fn render_image(ctx: &Arc<RenderContext>, scene: &SceneData) -> image::RgbImage {
+    render_accumulation(ctx, scene).to_image(TransferFunction::default())
+}
+
+/// Renders one pass of every pixel of the scene on all cores, showing a
+/// progress bar.
+pub fn render_accumulation(ctx: &Arc<RenderContext>, scene: &SceneData) -> AccumulationBuffer {
+    render_accumulation_with_control(
+        ctx,
+        scene,
+        &Tiling::default(),
+        &Arc::new(RenderControl::new(1)),
+        None,
+        AccumulationBuffer::new(scene.camera.image_width(), scene.camera.image_height()),
+        None,
+    )
+}
+
+/// Renders passes of every pixel of the scene on all cores, block by block as
+/// laid out by `tiling`, until `control` reaches its target passes or is
+/// cancelled, showing a progress bar weighted by the estimated cost of the
+/// blocks. Requested snapshots are written as described by `snapshot`.
+///
+/// Passes are added to `accumulation`, numbered on from the passes it holds
+/// so a resumed render draws new samples rather than repeating them. The AOVs
+/// of the first pass are written to `aovs` when given.
+pub fn render_accumulation_with_control(
+    ctx: &Arc<RenderContext>,
+    scene: &SceneData,
+    tiling: &Tiling,
+    control: &Arc<RenderControl>,
+    snapshot: Option<&Snapshot>,
+    mut accumulation: AccumulationBuffer,
+    mut aovs: Option<&mut AovBuffer>,
+) -> AccumulationBuffer {
+    let first_pass = accumulation.pass_count();
+
+    // generate work, reversed as workers take blocks from the end
+    let mut work: Vec<Work> = tiling
+        .tiles(ctx, scene, num_cpus::get() as u32)
+        .into_iter()
+        .map(|(tile, cost)| Work {
+            camera: scene.camera.clone(),
+            world: scene.world.clone(),
+            lights: scene.lights.clone(),
+            xmin: tile.xmin,
+            xmax: tile.xmax,
+            ymin: tile.ymin,
+            ymax: tile.ymax,
+            cost: (cost.round() as u64).max(1),
+        })
+        .collect();
+    work.reverse();
+    let pass_cost: u64 = work.iter().map(|item| item.cost).sum();
 
     // Setup progress bar
-    let pb = ProgressBar::new(work_count as u64);
+    let pb = ProgressBar::new(pass_cost * control.get_target_passes() as u64);
     pb.set_style(
         ProgressStyle::default_bar()
             .template(
-                "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} ({eta})",
+                "{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} ({eta}) {msg}",
             )
             .unwrap(),
     );
 
+    let mut pass = 0;
+    while pass < control.get_target_passes() && !control.is_cancelled() {
+        render_pass(
+            ctx,
+            &work,
+            control,
+            &pb,
+            first_pass + pass,
+            &mut accumulation,
+            aovs.take(),
+            snapshot,
+        );
+        pass += 1;
+    }
+
+    pb.finish_with_message(if control.is_cancelled() {
+        "Cancelled"
+    } else {
+        "Done!"
+    });
+    accumulation
+}
+
+/// Renders every block of `work` once, adding the pixels to `accumulation`
+/// and their AOVs to `aovs` when given.
+#[allow(clippy::too_many_arguments)]
+fn render_pass(
+    ctx: &Arc<RenderContext>,
+    work: &[Work],
+    control: &Arc<RenderControl>,
+    pb: &ProgressBar,
+    pass: u32,
+    accumulation: &mut AccumulationBuffer,
+    mut aovs: Option<&mut AovBuffer>,
+    snapshot: Option<&Snapshot>,
+) {
+    let with_aovs = aovs.is_some();
+    let (width, height) = (accumulation.width(), accumulation.height());
+
     // start work
     let threads = num_cpus::get();
-    let work = Arc::new(Mutex::new(work));
+    let work_count = work.len();
+    let pass_cost: u64 = work.iter().map(|item| item.cost).sum();
+    let work = Arc::new(Mutex::new(work.to_vec()));
     let (results_send, results_recv) = mpsc::channel();
     let mut handles = Vec::with_capacity(threads);
     for i in 0..threads {
         let work = work.clone();
         let results_send = results_send.clone();
         let ctx = ctx.clone();
+        let control = control.clone();
         let thread = std::thread::Builder::new()
             .name(format!("RenderThread-{i}"))
             .spawn_with_priority(ThreadPriority::Min, move |_| {
-                loop {
+                while control.wait_while_paused() {
                     let item = { work.lock().unwrap().pop() };
                     match item {
                         Some(item) => {
                             let mut pixels = vec![];
+                            let mut pixel_aovs = vec![];
                             for y in item.ymin..item.ymax {
                                 for x in item.xmin..item.xmax {
-                                    let pixel_color = item.camera.render(
-                                        &ctx,
-                                        x,
-                                        y,
-                                        &*item.world,
-                                        item.lights.clone(),
-                                    );
+                                    let pixel_color = if with_aovs {
+                                        let (pixel_color, aovs) = item.camera.render_pass_aovs(
+                                            &ctx,
+                                            x,
+                                            y,
+                                            pass,
+                                            &*item.world,
+                                            item.lights.clone(),
+                                        );
+                                        pixel_aovs.push(aovs);
+                                        pixel_color
+                                    } else {
+                                        item.camera.render_pass(
+                                            &ctx,
+                                            x,
+                                            y,
+                                            pass,
+                                            &*item.world,
+                                            item.lights.clone(),
+                                        )
+                                    };
                                     pixels.push(pixel_color);
                                 }
                             }
@@ -151,7 +483,9 @@ fn main() -> ExitCode {
                                     xmax: item.xmax,
                                     ymin: item.ymin,
                                     ymax: item.ymax,
+                                    cost: item.cost,
                                     pixels,
+                                    aovs: pixel_aovs,
                                 }))
                                 .unwrap();
                         }
@@ -161,50 +495,447 @@ fn main() -> ExitCode {
             });
         handles.push(thread.unwrap());
     }
+    // Only the workers hold senders now, so receiving fails once they all stop
+    drop(results_send);
 
-    for _ in 0..work_count {
-        let result = results_recv.recv().unwrap();
-        match result {
-            WorkResult::DataWorkResult(result) => {
+    let mut received = 0;
+    while received < work_count {
+        // Wake up regularly to follow the controls while workers are paused
+        match results_recv.recv_timeout(Duration::from_millis(100)) {
+            Ok(WorkResult::DataWorkResult(result)) => {
                 let mut i = 0;
                 for y in result.ymin..result.ymax {
                     for x in result.xmin..result.xmax {
-                        if let Some(pixel) = img.get_pixel_mut_checked(x, y) {
-                            let pixel_color = result.pixels[i];
-                            *pixel = color_to_image_rgb(pixel_color);
+                        if x < width && y < height {
+                            accumulation.add(x, y, result.pixels[i]);
+                            if let Some(aovs) = aovs.as_deref_mut() {
+                                aovs.set(x, y, result.aovs[i]);
+                            }
                             i += 1;
                         }
                     }
                 }
-                pb.inc(1);
+                received += 1;
+                pb.inc(result.cost);
             }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        let target_passes = control.get_target_passes();
+        pb.set_length(pass_cost * target_passes.max(pass + 1) as u64);
+        pb.set_message(if control.is_paused() {
+            format!("pass {}/{target_passes}, paused", pass + 1)
+        } else {
+            format!("pass {}/{target_passes}", pass + 1)
+        });
+
+        if let Some(snapshot) = snapshot
+            && control.take_snapshot_request()
+        {
+            let snapshot_path = snapshot.path;
+            let mut image = accumulation.to_image(snapshot.transfer);
+            draw_guides(&mut image, snapshot.guides);
+            let result = image.save(snapshot_path);
+            // Carriage returns keep lines aligned while the terminal is in raw mode
+            pb.suspend(|| match result {
+                Ok(()) => eprint!("snapshot written to {}\r\n", snapshot_path.display()),
+                Err(err) => eprint!("failed to write {}: {err}\r\n", snapshot_path.display()),
+            });
         }
     }
 
     for h in handles {
         h.join().unwrap();
     }
+}
 
-    img.save("../../target/out.png").unwrap();
-    pb.finish_with_message("Done!");
-    ExitCode::SUCCESS
+/// Removes every `--library-path <dir>` option from the arguments. The
+/// directories are searched in order, before the ones in `OPENSCADPATH`.
+fn take_library_path(args: &mut Vec<String>) -> core::result::Result<LibraryPath, String> {
+    let mut library_path = LibraryPath::new();
+    while let Some(i) = args.iter().position(|arg| arg == "--library-path") {
+        if i + 1 >= args.len() {
+            return Err("missing value for --library-path".to_owned());
+        }
+        library_path = library_path.with_directory(args.remove(i + 1));
+        args.remove(i);
+    }
+    Ok(library_path.with_library_path(LibraryPath::from_env()))
 }
 
-fn color_to_image_rgb(color: Color) -> image::Rgb<u8> {
+/// Removes the `--accum <file>` option from the arguments. Renders are added to
+/// the passes already in the file, which can later be merged with renders from
+/// other machines by `caustic merge`.
+fn take_accumulation_path(args: &mut Vec<String>) -> core::result::Result<Option<PathBuf>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--accum") else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --accum".to_owned());
+    }
+    let path = PathBuf::from(args.remove(i + 1));
+    args.remove(i);
+    Ok(Some(path))
+}
+
+/// Removes the `--interactive` option from the arguments, which lets the
+/// render be steered from the keyboard, see [`KeyboardControl`].
+fn take_interactive(args: &mut Vec<String>) -> bool {
+    let Some(i) = args.iter().position(|arg| arg == "--interactive") else {
+        return false;
+    };
+    args.remove(i);
+    true
+}
+
+/// Removes the `--aovs` option from the arguments, which also writes the
+/// albedo, normal, depth and object id of every pixel next to the image.
+fn take_aovs(args: &mut Vec<String>) -> bool {
+    let Some(i) = args.iter().position(|arg| arg == "--aovs") else {
+        return false;
+    };
+    args.remove(i);
+    true
+}
+
+/// Removes the `--denoise` option from the arguments, which removes the noise
+/// of the output image guided by the AOVs of the scene.
+fn take_denoise(args: &mut Vec<String>) -> bool {
+    let Some(i) = args.iter().position(|arg| arg == "--denoise") else {
+        return false;
+    };
+    args.remove(i);
+    true
+}
+
+/// Removes the `--camera <name>` option from the arguments, which renders the
+/// scene's camera with that name instead of its default camera.
+fn take_camera_name(args: &mut Vec<String>) -> core::result::Result<Option<String>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--camera") else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --camera".to_owned());
+    }
+    let name = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(name))
+}
+
+/// Removes the `--crop x,y,w,h` option from the arguments, which renders only
+/// that rectangle of the image, or `--crop-out x,y,w,h` which renders everything
+/// but it. The rest of the image is black.
+fn take_render_region(
+    args: &mut Vec<String>,
+) -> core::result::Result<Option<RenderRegion>, String> {
+    let Some(i) = args
+        .iter()
+        .position(|arg| arg == "--crop" || arg == "--crop-out")
+    else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err(format!("missing value for {}", args[i]));
+    }
+    let value = args.remove(i + 1);
+    let option = args.remove(i);
+
+    let numbers = value
+        .split(',')
+        .map(|n| n.trim().parse::<u32>())
+        .collect::<core::result::Result<Vec<_>, _>>();
+    let region = match numbers.as_deref() {
+        Ok([x, y, width, height]) => RenderRegion::new(*x, *y, *width, *height),
+        _ => {
+            return Err(format!(
+                "invalid value for {option}, expected x,y,w,h: {value}"
+            ));
+        }
+    };
+    Ok(Some(if option == "--crop-out" {
+        region.inverted()
+    } else {
+        region
+    }))
+}
+
+/// Removes the `--crop-window xmin,xmax,ymin,ymax` option from the arguments,
+/// which renders only that part of the image given as fractions of its size,
+/// so a region picked on a preview can be re-rendered at any resolution.
+fn take_crop_window(args: &mut Vec<String>) -> core::result::Result<Option<CropWindow>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--crop-window") else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --crop-window".to_owned());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+
+    let numbers = value
+        .split(',')
+        .map(|n| n.trim().parse::<f64>())
+        .collect::<core::result::Result<Vec<_>, _>>();
+    match numbers.as_deref() {
+        Ok([xmin, xmax, ymin, ymax]) if xmin < xmax && ymin < ymax => {
+            Ok(Some(CropWindow::new(*xmin, *xmax, *ymin, *ymax)))
+        }
+        _ => Err(format!(
+            "invalid value for --crop-window, expected xmin,xmax,ymin,ymax: {value}"
+        )),
+    }
+}
+
+/// Removes the `--overscan <fraction>` option from the arguments, which renders
+/// a margin of that fraction of the image size around it, see
+/// [`Camera::with_overscan`].
+fn take_overscan(args: &mut Vec<String>) -> core::result::Result<Option<f64>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--overscan") else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --overscan".to_owned());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    match value.parse::<f64>() {
+        Ok(overscan) if overscan >= 0.0 => Ok(Some(overscan)),
+        _ => Err(format!("invalid value for --overscan: {value}")),
+    }
+}
+
+/// Removes the `--seed <n>` option from the arguments, which renders the same
+/// image on every run and whatever the number of threads. Passes resumed with
+/// `--accum` or merged from several machines need different seeds, or they
+/// add the same samples again.
+fn take_seed(args: &mut Vec<String>) -> core::result::Result<Option<u64>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--seed") else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --seed".to_owned());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    match value.parse::<u64>() {
+        Ok(seed) => Ok(Some(seed)),
+        Err(_) => Err(format!("invalid value for --seed: {value}")),
+    }
+}
+
+/// Removes the `--rng <system|pcg32>` option from the arguments, selecting the
+/// generator random numbers are drawn from, see `caustic rng-bench`. Seeded
+/// renders always use their own generator, so it cannot be combined with
+/// `--seed`.
+fn take_random_generator(
+    args: &mut Vec<String>,
+) -> core::result::Result<Option<RandomGenerator>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--rng") else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --rng".to_owned());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    value.parse().map(Some)
+}
+
+/// Removes the `--exposure <ev>` option from the arguments, which replaces the
+/// exposure of the scene camera with that many stops of compensation.
+fn take_exposure(args: &mut Vec<String>) -> core::result::Result<Option<Exposure>, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--exposure") else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --exposure".to_owned());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    match value.parse::<f64>() {
+        Ok(ev) if ev.is_finite() => Ok(Some(Exposure::Ev(ev))),
+        _ => Err(format!("invalid value for --exposure: {value}")),
+    }
+}
+
+/// Removes the `--transfer <name>` option from the arguments, selecting how the
+/// linear colors are encoded in the output image and snapshots, sRGB when not
+/// given.
+fn take_transfer_function(
+    args: &mut Vec<String>,
+) -> core::result::Result<TransferFunction, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--transfer") else {
+        return Ok(TransferFunction::default());
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --transfer".to_owned());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    parse_transfer_function(&value).ok_or_else(|| {
+        format!(
+            "invalid value for --transfer \"{value}\", expected \"srgb\", \"gamma2.2\", \"gamma2\", \"rec709\" or \"linear\""
+        )
+    })
+}
+
+pub(crate) fn parse_transfer_function(name: &str) -> Option<TransferFunction> {
+    match name {
+        "srgb" => Some(TransferFunction::Srgb),
+        "gamma2.2" => Some(TransferFunction::Gamma22),
+        "gamma2" => Some(TransferFunction::Gamma2),
+        "rec709" => Some(TransferFunction::Rec709),
+        "linear" => Some(TransferFunction::Linear),
+        _ => None,
+    }
+}
+
+/// Removes the `--tile-order <name>` option from the arguments, selecting the
+/// order blocks are rendered in, scanline when not given.
+fn take_tile_order(args: &mut Vec<String>) -> core::result::Result<TileOrder, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--tile-order") else {
+        return Ok(TileOrder::default());
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --tile-order".to_owned());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    match value.as_str() {
+        "scanline" => Ok(TileOrder::Scanline),
+        "spiral" => Ok(TileOrder::Spiral),
+        "hilbert" => Ok(TileOrder::Hilbert),
+        _ => Err(format!(
+            "invalid value for --tile-order \"{value}\", expected \"scanline\", \"spiral\" or \"hilbert\""
+        )),
+    }
+}
+
+/// Removes the `--hardest-first` option from the arguments, which renders the
+/// blocks estimated to cost the most first.
+fn take_hardest_first(args: &mut Vec<String>) -> bool {
+    let Some(i) = args.iter().position(|arg| arg == "--hardest-first") else {
+        return false;
+    };
+    args.remove(i);
+    true
+}
+
+/// Removes the `--tile-size <auto|strip|size|WxH>` option from the arguments,
+/// selecting the size of the blocks handed to render threads. Blocks are sized
+/// automatically when not given.
+fn take_tile_size(args: &mut Vec<String>) -> core::result::Result<TileSize, String> {
+    let Some(i) = args.iter().position(|arg| arg == "--tile-size") else {
+        return Ok(TileSize::default());
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --tile-size".to_owned());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    TileSize::parse(&value).ok_or_else(|| {
+        format!(
+            "invalid value for --tile-size \"{value}\", expected \"auto\", \"strip\", a size or <width>x<height>"
+        )
+    })
+}
+
+/// Removes the `--guides <thirds,safe>` option from the arguments, selecting the
+/// composition guides drawn over the output image and snapshots.
+fn take_guides(args: &mut Vec<String>) -> core::result::Result<Guides, String> {
+    let mut guides = Guides::default();
+    let Some(i) = args.iter().position(|arg| arg == "--guides") else {
+        return Ok(guides);
+    };
+    if i + 1 >= args.len() {
+        return Err("missing value for --guides".to_owned());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    for guide in value.split(',') {
+        match guide.trim() {
+            "thirds" => guides.rule_of_thirds = true,
+            "safe" => guides.safe_areas = true,
+            other => {
+                return Err(format!(
+                    "invalid guide \"{other}\", expected \"thirds\" or \"safe\""
+                ));
+            }
+        }
+    }
+    Ok(guides)
+}
+
+fn parse_scene_name(scene_name: &str) -> Option<Scene> {
+    let scene = if scene_name == "ThreeSpheres" {
+        Scene::ThreeSpheres
+    } else if scene_name == "RandomSpheres" {
+        Scene::RandomSpheres
+    } else if scene_name == "CheckeredSpheres" {
+        Scene::CheckeredSpheres
+    } else if scene_name == "Earth" {
+        Scene::Earth
+    } else if scene_name == "PerlinSpheres" {
+        Scene::PerlinSpheres
+    } else if scene_name == "Quads" {
+        Scene::Quads
+    } else if scene_name == "LightedSphere" {
+        Scene::LightedSphere
+    } else if scene_name == "LightedConeFrustum" {
+        Scene::LightedConeFrustum
+    } else if scene_name == "CornellBox" {
+        Scene::CornellBox
+    } else if scene_name == "CornellBoxSmoke" {
+        Scene::CornellBoxSmoke
+    } else if scene_name == "Final" {
+        Scene::Final
+    } else if scene_name.to_lowercase().ends_with(".scad") {
+        Scene::OpenScad(scene_name.to_owned())
+    } else {
+        return None;
+    };
+    Some(scene)
+}
+
+pub(crate) fn color_to_image_rgb(color: Color) -> image::Rgb<u8> {
     let r = (color.r * 255.999) as u8;
     let g = (color.g * 255.999) as u8;
     let b = (color.b * 255.999) as u8;
     image::Rgb([r, g, b])
 }
 
+/// Draws guide lines over an image, leaving out parts outside of it.
+pub(crate) fn draw_guides(image: &mut image::RgbImage, guides: &[GuideLine]) {
+    for guide in guides {
+        let color = color_to_image_rgb(guide.kind.color());
+        for y in guide.y0..=guide.y1.min(image.height().saturating_sub(1)) {
+            for x in guide.x0..=guide.x1.min(image.width().saturating_sub(1)) {
+                image.put_pixel(x, y, color);
+            }
+        }
+    }
+}
+
+/// Where images of a render in progress are written, and the guides drawn
+/// over them.
+pub struct Snapshot<'a> {
+    pub path: &'a Path,
+    pub guides: &'a [GuideLine],
+    pub transfer: TransferFunction,
+}
+
+#[derive(Clone)]
 pub struct Work {
     pub camera: Arc<Camera>,
     pub world: Arc<dyn Node>,
-    pub lights: Option<Arc<dyn Node>>,
+    pub lights: Option<Arc<dyn Light>>,
     pub xmin: u32,
     pub xmax: u32,
     pub ymin: u32,
     pub ymax: u32,
+    /// Estimated cost of the block, see [`Tiling::tiles`]
+    pub cost: u64,
 }
 
 pub enum WorkResult {
@@ -216,5 +947,8 @@ pub struct DataWorkResult {
     pub xmax: u32,
     pub ymin: u32,
     pub ymax: u32,
+    pub cost: u64,
     pub pixels: Vec<Color>,
+    /// AOVs of the pixels, empty unless requested
+    pub aovs: Vec<Aovs>,
 }
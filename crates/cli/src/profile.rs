@@ -0,0 +1,179 @@
+use std::{
+    process::ExitCode,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use caustic_core::{RenderContext, SceneData, random_new};
+use caustic_openscad::library::LibraryPath;
+
+use crate::{estimate::parse_positive, parse_scene_name, scene::get_scene};
+
+/// Seconds spent rendering when `--seconds` is not given
+const DEFAULT_SECONDS: u32 = 10;
+
+/// Samples per second taken by the profiler when `--frequency` is not given
+const DEFAULT_FREQUENCY: u32 = 1000;
+
+/// Options for `caustic profile <scene> [--seconds S] [--frequency HZ] [--output FILE]`.
+struct ProfileOptions {
+    scene_name: String,
+    seconds: u32,
+    frequency: u32,
+    output: String,
+}
+
+/// Renders the scene for a fixed amount of time under a sampling profiler and
+/// writes the collected stacks as a flamegraph SVG, to attach to performance
+/// issues.
+pub fn run(args: &[String], library_path: &LibraryPath) -> ExitCode {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!(
+                "usage: caustic profile <scene> [--seconds S] [--frequency HZ] [--output FILE] [--library-path DIR]"
+            );
+            return ExitCode::from(1);
+        }
+    };
+
+    let Some(scene) = parse_scene_name(&options.scene_name) else {
+        eprintln!("invalid scene name: {}", options.scene_name);
+        return ExitCode::from(1);
+    };
+
+    let ctx = Arc::new(RenderContext {
+        random: random_new(),
+    });
+
+    let scene = match get_scene(&ctx, scene, library_path) {
+        Ok(scene) => scene,
+        Err(err) => {
+            eprintln!("failed to get scene: {err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let budget = Duration::from_secs(options.seconds as u64);
+    match profile(ctx, Arc::new(scene), budget, &options) {
+        Ok(pixels) => {
+            println!(
+                "rendered {pixels} pixels in {}s, flamegraph written to {}",
+                options.seconds, options.output
+            );
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("failed to profile scene: {err}");
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn parse_args(args: &[String]) -> Result<ProfileOptions, String> {
+    let mut scene_name = None;
+    let mut seconds = DEFAULT_SECONDS;
+    let mut frequency = DEFAULT_FREQUENCY;
+    let mut output = "profile.svg".to_owned();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seconds" => seconds = parse_positive(arg, args.next())?,
+            "--frequency" => frequency = parse_positive(arg, args.next())?,
+            "--output" => {
+                output = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for {arg}"))?
+                    .to_owned()
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {arg}")),
+            _ if scene_name.is_none() => scene_name = Some(arg.to_owned()),
+            _ => return Err(format!("unexpected argument: {arg}")),
+        }
+    }
+
+    Ok(ProfileOptions {
+        scene_name: scene_name.ok_or("missing scene name")?,
+        seconds,
+        frequency,
+        output,
+    })
+}
+
+/// Renders pixels on every core until the budget runs out, returning the
+/// number of pixels rendered.
+pub(crate) fn render_for(ctx: Arc<RenderContext>, scene: Arc<SceneData>, budget: Duration) -> u64 {
+    let camera = &scene.camera;
+    let pixel_count = camera.image_width() as u64 * camera.image_height() as u64;
+    let next_pixel = Arc::new(AtomicU64::new(0));
+    let deadline = Instant::now() + budget;
+
+    let handles: Vec<_> = (0..num_cpus::get())
+        .map(|i| {
+            let ctx = ctx.clone();
+            let scene = scene.clone();
+            let next_pixel = next_pixel.clone();
+            std::thread::Builder::new()
+                .name(format!("RenderThread-{i}"))
+                .spawn(move || {
+                    let camera = &scene.camera;
+                    while Instant::now() < deadline {
+                        // Wrap around so small scenes keep rendering for the whole budget
+                        let pixel = next_pixel.fetch_add(1, Ordering::Relaxed) % pixel_count;
+                        let x = (pixel % camera.image_width() as u64) as u32;
+                        let y = (pixel / camera.image_width() as u64) as u32;
+                        camera.render(&ctx, x, y, &*scene.world, scene.lights.clone());
+                    }
+                })
+                .unwrap()
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    next_pixel.load(Ordering::Relaxed)
+}
+
+#[cfg(unix)]
+fn profile(
+    ctx: Arc<RenderContext>,
+    scene: Arc<SceneData>,
+    budget: Duration,
+    options: &ProfileOptions,
+) -> Result<u64, String> {
+    let guard = pprof::ProfilerGuardBuilder::default()
+        .frequency(options.frequency as i32)
+        .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+        .build()
+        .map_err(|err| err.to_string())?;
+
+    let pixels = render_for(ctx, scene, budget);
+
+    let report = guard
+        .report()
+        // Merge the render threads into a single flamegraph
+        .frames_post_processor(|frames| frames.thread_name = "render".to_owned())
+        .build()
+        .map_err(|err| err.to_string())?;
+    let file = std::fs::File::create(&options.output)
+        .map_err(|err| format!("{}: {err}", options.output))?;
+    report.flamegraph(file).map_err(|err| err.to_string())?;
+
+    Ok(pixels)
+}
+
+#[cfg(not(unix))]
+fn profile(
+    _ctx: Arc<RenderContext>,
+    _scene: Arc<SceneData>,
+    _budget: Duration,
+    _options: &ProfileOptions,
+) -> Result<u64, String> {
+    Err("profiling is only supported on unix platforms".to_owned())
+}
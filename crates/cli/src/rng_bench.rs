@@ -0,0 +1,93 @@
+use std::{
+    hint::black_box,
+    process::ExitCode,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use caustic_core::{RenderContext, SceneData, random::RandomGenerator};
+use caustic_openscad::library::LibraryPath;
+
+use crate::{estimate::parse_positive, parse_scene_name, profile::render_for, scene::get_scene};
+
+/// Seconds each generator renders for when `--seconds` is not given
+const DEFAULT_SECONDS: u32 = 5;
+
+/// Numbers drawn on one thread to time each generator alone
+const DRAWS: u32 = 10_000_000;
+
+/// Options for `caustic rng-bench <scene> [--seconds S]`.
+struct RngBenchOptions {
+    scene_name: String,
+    seconds: u32,
+}
+
+/// Times every random generator drawing numbers on one thread, then rendering
+/// the scene on every core for a fixed amount of time, so a generator is only
+/// made the default once it pays off in renders.
+pub fn run(args: &[String], library_path: &LibraryPath) -> ExitCode {
+    let options = match parse_args(args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            eprintln!("usage: caustic rng-bench <scene> [--seconds S] [--library-path DIR]");
+            return ExitCode::from(1);
+        }
+    };
+
+    let Some(scene) = parse_scene_name(&options.scene_name) else {
+        eprintln!("invalid scene name: {}", options.scene_name);
+        return ExitCode::from(1);
+    };
+
+    // Build the scene once so every generator renders the same one
+    let ctx = RenderContext::new_with_generator(RandomGenerator::default());
+    let scene: Arc<SceneData> = match get_scene(&ctx, scene, library_path) {
+        Ok(scene) => Arc::new(scene),
+        Err(err) => {
+            eprintln!("failed to get scene: {err}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let budget = Duration::from_secs(options.seconds as u64);
+    println!("generator  Mdraws/s  pixels/s");
+    for generator in RandomGenerator::ALL {
+        let ctx = Arc::new(RenderContext::new_with_generator(generator));
+
+        let start = Instant::now();
+        for _ in 0..DRAWS {
+            black_box(ctx.random.rand());
+        }
+        let draws_per_second = DRAWS as f64 / start.elapsed().as_secs_f64();
+
+        let pixels = render_for(ctx, scene.clone(), budget);
+        println!(
+            "{:<9} {:>9.1} {:>9.1}",
+            generator.to_string(),
+            draws_per_second / 1e6,
+            pixels as f64 / options.seconds as f64
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+fn parse_args(args: &[String]) -> Result<RngBenchOptions, String> {
+    let mut scene_name = None;
+    let mut seconds = DEFAULT_SECONDS;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seconds" => seconds = parse_positive(arg, args.next())?,
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {arg}")),
+            _ if scene_name.is_none() => scene_name = Some(arg.to_owned()),
+            _ => return Err(format!("unexpected argument: {arg}")),
+        }
+    }
+
+    Ok(RngBenchOptions {
+        scene_name: scene_name.ok_or("missing scene name")?,
+        seconds,
+    })
+}
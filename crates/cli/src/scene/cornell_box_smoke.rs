@@ -1,11 +1,10 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use caustic_core::{
     CameraBuilder, Color, Node, RenderContext, Vector3,
+    light::LightList,
     material::{DiffuseLight, EmptyMaterial, Lambertian},
-    object::{
-        BoundingVolumeHierarchy, BoxPrimitive, ConstantMedium, Group, Quad, Rotate, Translate,
-    },
+    object::{BoundingVolumeHierarchy, BoxPrimitive, ConstantMedium, Quad, Rotate, Translate},
 };
 
 use crate::scene::SceneData;
@@ -96,7 +95,7 @@ pub fn create_cornell_box_smoke_scene(_ctx: &RenderContext) -> SceneData {
         Arc::new(EmptyMaterial::new()),
     ));
     let lights: Vec<Arc<dyn Node>> = vec![light1];
-    let lights = Arc::new(Group::from_list(&lights));
+    let lights = Arc::new(LightList::from_nodes(&lights));
 
     // Camera
     let mut camera_builder = CameraBuilder::new();
@@ -108,13 +107,15 @@ pub fn create_cornell_box_smoke_scene(_ctx: &RenderContext) -> SceneData {
     camera_builder.look_from = Vector3::new(278.0, 278.0, -800.0);
     camera_builder.look_at = Vector3::new(278.0, 278.0, 0.0);
     camera_builder.up = Vector3::new(0.0, 1.0, 0.0);
-    camera_builder.background = Color::BLACK;
+    camera_builder.background = Arc::new(Color::BLACK);
     camera_builder.defocus_angle = 0.0;
     let camera = Arc::new(camera_builder.build());
 
     SceneData {
         camera,
+        cameras: HashMap::new(),
         world,
         lights: Some(lights),
+        caustic_targets: vec![],
     }
 }
@@ -1,11 +1,12 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use caustic_core::{
     CameraBuilder, Color, RenderContext, Vector3,
     image::ImageImage,
+    light::LightList,
     material::{Dielectric, DiffuseLight, EmptyMaterial, Lambertian, Metal},
     object::{
-        BoundingVolumeHierarchy, BoxPrimitive, ConstantMedium, Group, Node, Quad, Rotate, Sphere,
+        BoundingVolumeHierarchy, BoxPrimitive, ConstantMedium, Node, Quad, Rotate, Sphere,
         Translate,
     },
     texture::{ImageTexture, PerlinNoiseTexture},
@@ -141,7 +142,7 @@ pub fn create_final_scene(ctx: &RenderContext) -> SceneData {
         Arc::new(EmptyMaterial::new()),
     ));
     let lights: Vec<Arc<dyn Node>> = vec![light1];
-    let lights = Arc::new(Group::from_list(&lights));
+    let lights = Arc::new(LightList::from_nodes(&lights));
 
     // Camera
     let image_width = 400;
@@ -166,7 +167,9 @@ pub fn create_final_scene(ctx: &RenderContext) -> SceneData {
 
     SceneData {
         camera,
+        cameras: HashMap::new(),
         world,
         lights: Some(lights),
+        caustic_targets: vec![],
     }
 }
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use caustic_core::{
     CameraBuilder, Color, RenderContext, Vector3,
@@ -59,12 +59,14 @@ pub fn create_lighted_sphere_scene(ctx: &RenderContext) -> SceneData {
     camera_builder.look_at = Vector3::new(0.0, 2.0, 0.0);
     camera_builder.up = Vector3::new(0.0, 1.0, 0.0);
     camera_builder.defocus_angle = 0.0;
-    camera_builder.background = Color::new(0.0, 0.0, 0.0);
+    camera_builder.background = Arc::new(Color::new(0.0, 0.0, 0.0));
     let camera = Arc::new(camera_builder.build());
 
     SceneData {
         camera,
+        cameras: HashMap::new(),
         world,
         lights: None,
+        caustic_targets: vec![],
     }
 }
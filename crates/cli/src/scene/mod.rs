@@ -14,9 +14,11 @@ pub mod three_spheres;
 use std::{path::Path, sync::Arc};
 
 use ariadne::{Label, Report, ReportKind, Source as AriadneSource};
-use caustic_core::{RenderContext, SceneData};
+use caustic_core::{RenderContext, SceneData, render::PhotonMap};
 use caustic_openscad::{
-    Message, MessageLevel, run_openscad,
+    Message, MessageLevel, find_missing_assets,
+    library::LibraryPath,
+    run_openscad_at_time,
     source::{FileSource, Source},
 };
 
@@ -47,7 +49,22 @@ pub enum Scene {
     OpenScad(String),
 }
 
-pub fn get_scene(ctx: &RenderContext, scene: Scene) -> Result<SceneData> {
+pub fn get_scene(
+    ctx: &RenderContext,
+    scene: Scene,
+    library_path: &LibraryPath,
+) -> Result<SceneData> {
+    get_scene_at_time(ctx, scene, library_path, 0.0)
+}
+
+/// Returns the scene with OpenSCAD's `$t` set to `time`, to render a frame of
+/// an animation. Built-in scenes do not move.
+pub fn get_scene_at_time(
+    ctx: &RenderContext,
+    scene: Scene,
+    library_path: &LibraryPath,
+    time: f64,
+) -> Result<SceneData> {
     match scene {
         Scene::ThreeSpheres => Ok(create_three_spheres_scene(ctx)),
         Scene::RandomSpheres => Ok(create_random_spheres_scene(ctx)),
@@ -67,12 +84,20 @@ pub fn get_scene(ctx: &RenderContext, scene: Scene) -> Result<SceneData> {
             })?;
 
             let source: Arc<Box<dyn Source>> = Arc::new(Box::new(source));
-            let results = run_openscad(source, ctx.random.clone());
+            let results =
+                run_openscad_at_time(source, ctx.random.clone(), library_path.clone(), time);
             for message in results.messages {
                 print_message(&message);
             }
+            let missing_count = find_missing_assets(&results.assets).len();
+            if missing_count > 0 {
+                eprintln!("{missing_count} referenced file(s) not found, not rendering");
+            }
             match results.scene_data {
-                Some(scene_data) => Ok(scene_data),
+                Some(mut scene_data) => {
+                    scene_data.trace_caustics(ctx, PhotonMap::MAX_PHOTONS);
+                    Ok(scene_data)
+                }
                 None => Err(CliError::OpenscadError),
             }
         }
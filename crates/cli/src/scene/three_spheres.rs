@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use caustic_core::{
     CameraBuilder, Color, RenderContext, Vector3,
@@ -59,12 +59,14 @@ pub fn create_three_spheres_scene(_ctx: &RenderContext) -> SceneData {
     camera_builder.max_depth = 50;
     camera_builder.defocus_angle = 0.6;
     camera_builder.focus_distance = 1.0;
-    camera_builder.background = Color::new(0.7, 0.8, 1.0);
+    camera_builder.background = Arc::new(Color::new(0.7, 0.8, 1.0));
     let camera = Arc::new(camera_builder.build());
 
     SceneData {
         camera,
+        cameras: HashMap::new(),
         world,
         lights: None,
+        caustic_targets: vec![],
     }
 }
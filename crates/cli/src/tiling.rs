@@ -0,0 +1,156 @@
+use std::time::Instant;
+
+use caustic_core::{
+    RenderContext, SceneData,
+    render::{
+        DEFAULT_COST_BLOCK_SIZE, Tile, TileOrder, TileScheduler, TileShape, estimate_tile_costs,
+    },
+};
+
+/// Most pixels rendered by the warmup measuring the cost of a pixel
+const WARMUP_PIXELS: u32 = 256;
+
+/// Most samples per pixel of the warmup, its time is scaled up to the scene's
+/// samples per pixel
+const WARMUP_SAMPLES_PER_PIXEL: u32 = 4;
+
+/// How the CLI splits the image in blocks handed to render threads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tiling {
+    pub order: TileOrder,
+    pub size: TileSize,
+    /// Renders the blocks estimated to cost the most first, in `order` among
+    /// blocks of the same cost, so no slow block is left for the end of a pass
+    pub hardest_first: bool,
+}
+
+impl Tiling {
+    /// Returns the blocks of the scene's image showing rendered pixels, in
+    /// render order, with their cost estimated by a pre-pass of one ray per
+    /// block of [`DEFAULT_COST_BLOCK_SIZE`] pixels.
+    pub fn tiles(&self, ctx: &RenderContext, scene: &SceneData, threads: u32) -> Vec<(Tile, f64)> {
+        let tiles: Vec<Tile> = self
+            .size
+            .scheduler(ctx, scene, self.order, threads)
+            .tiles()
+            .into_iter()
+            // Blocks outside of the render region stay black
+            .filter(|tile| tile.is_rendered_by(&scene.camera))
+            .collect();
+        let costs = estimate_tile_costs(
+            ctx,
+            &scene.camera,
+            &*scene.world,
+            &tiles,
+            DEFAULT_COST_BLOCK_SIZE,
+        );
+
+        let mut tiles: Vec<(Tile, f64)> = tiles.into_iter().zip(costs).collect();
+        if self.hardest_first {
+            // Stable, so blocks of the same cost keep their order
+            tiles.sort_by(|a, b| b.1.total_cmp(&a.1));
+        }
+        tiles
+    }
+}
+
+/// How the CLI sizes the blocks handed to render threads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileSize {
+    /// Sized from the image, the thread count and a warmup render
+    Auto(TileShape),
+    /// Fixed width and height in pixels
+    Fixed { width: u32, height: u32 },
+}
+
+impl Default for TileSize {
+    fn default() -> Self {
+        Self::Auto(TileShape::Square)
+    }
+}
+
+impl TileSize {
+    /// Parses `auto`, `strip`, `<size>` or `<width>x<height>`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "auto" => Some(Self::Auto(TileShape::Square)),
+            "strip" => Some(Self::Auto(TileShape::Strip)),
+            _ => {
+                let (width, height) = match value.split_once('x') {
+                    Some((width, height)) => (width.parse().ok()?, height.parse().ok()?),
+                    None => {
+                        let size = value.parse().ok()?;
+                        (size, size)
+                    }
+                };
+                (width > 0 && height > 0).then_some(Self::Fixed { width, height })
+            }
+        }
+    }
+
+    /// Returns the scheduler splitting the scene's image for `threads` threads,
+    /// running the warmup first when sizing tiles automatically.
+    pub fn scheduler(
+        &self,
+        ctx: &RenderContext,
+        scene: &SceneData,
+        order: TileOrder,
+        threads: u32,
+    ) -> TileScheduler {
+        let scheduler = TileScheduler::new(scene.camera.image_width(), scene.camera.image_height())
+            .with_order(order);
+        match *self {
+            Self::Auto(shape) => {
+                let seconds_per_pixel = measure_seconds_per_pixel(ctx, scene, threads);
+                scheduler.with_auto_tile_size(shape, threads, seconds_per_pixel)
+            }
+            Self::Fixed { width, height } => scheduler.with_tile_dimensions(width, height),
+        }
+    }
+}
+
+/// Renders up to [`WARMUP_PIXELS`] pixels spread over the image with up to
+/// [`WARMUP_SAMPLES_PER_PIXEL`] samples each, split over `threads` threads,
+/// and returns the time a thread takes for one pixel at the scene's samples
+/// per pixel, or `None` when no pixel is rendered. The pixels are thrown
+/// away, they only warm up the caches and measure the scene.
+fn measure_seconds_per_pixel(ctx: &RenderContext, scene: &SceneData, threads: u32) -> Option<f64> {
+    let samples_per_pixel = scene.camera.samples_per_pixel();
+    let camera = scene
+        .camera
+        .with_samples_per_pixel(samples_per_pixel.min(WARMUP_SAMPLES_PER_PIXEL));
+    let (width, height) = (camera.image_width(), camera.image_height());
+    let step = ((width as f64 * height as f64 / WARMUP_PIXELS as f64).sqrt() as usize).max(1);
+    let pixels: Vec<(u32, u32)> = (step as u32 / 2..height)
+        .step_by(step)
+        .flat_map(|y| (step as u32 / 2..width).step_by(step).map(move |x| (x, y)))
+        .filter(|(x, y)| camera.is_rendered(*x, *y))
+        .collect();
+    if pixels.is_empty() {
+        return None;
+    }
+
+    // Summed over the threads, each rendering every `threads`th pixel, so
+    // the time is that of one thread however many render at once
+    let threads = threads.max(1) as usize;
+    let seconds: f64 = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|i| {
+                let (camera, pixels) = (&camera, &pixels);
+                scope.spawn(move || {
+                    let start = Instant::now();
+                    for (x, y) in pixels.iter().skip(i).step_by(threads) {
+                        camera.render_linear(ctx, *x, *y, &*scene.world, scene.lights.clone());
+                    }
+                    start.elapsed().as_secs_f64()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .sum()
+    });
+    let scale = samples_per_pixel as f64 / camera.samples_per_pixel() as f64;
+    Some(seconds / pixels.len() as f64 * scale)
+}
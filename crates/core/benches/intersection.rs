@@ -0,0 +1,146 @@
+//! Intersection kernels run for every BVH node and primitive a ray visits.
+//!
+//! Run with `cargo bench -p caustic-core --bench intersection`, on aarch64 to
+//! check the NEON code generated for the portable kernels.
+
+use std::{hint::black_box, sync::Arc};
+
+use caustic_core::{
+    Axis, AxisAlignedBoundingBox, Color, Interval, Node, Ray, RenderContext, Vector3,
+    material::Lambertian,
+    object::{BoundingVolumeHierarchy, Sphere},
+    random::SeededRandom,
+};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+const COUNT: usize = 1024;
+
+/// Slab test of `AxisAlignedBoundingBox::hit` before it was made branch free
+fn hit_branchy(bbox: &AxisAlignedBoundingBox, ray: &Ray, ray_t: Interval) -> bool {
+    let ray_orig = ray.origin;
+    let ray_dir = ray.direction;
+    let mut ray_t = ray_t;
+
+    for axis in Axis::iter() {
+        let ax = bbox.axis_interval(axis);
+        let adinv = 1.0 / ray_dir.axis_value(axis);
+
+        let t0 = (ax.min - ray_orig.axis_value(axis)) * adinv;
+        let t1 = (ax.max - ray_orig.axis_value(axis)) * adinv;
+
+        if t0 < t1 {
+            if t0 > ray_t.min {
+                ray_t.min = t0;
+            }
+            if t1 < ray_t.max {
+                ray_t.max = t1;
+            }
+        } else {
+            if t1 > ray_t.min {
+                ray_t.min = t1;
+            }
+            if t0 < ray_t.max {
+                ray_t.max = t0;
+            }
+        }
+
+        if ray_t.max <= ray_t.min {
+            return false;
+        }
+    }
+    true
+}
+
+/// Boxes and rays around the origin, about half of the rays hitting their box
+fn boxes_and_rays() -> (Vec<AxisAlignedBoundingBox>, Vec<Ray>) {
+    let random = SeededRandom::new(1);
+    let boxes = (0..COUNT)
+        .map(|_| {
+            let a = Vector3::random_interval(&random, -2.0, 2.0);
+            let b = Vector3::random_interval(&random, -2.0, 2.0);
+            AxisAlignedBoundingBox::new_from_points(a, b)
+        })
+        .collect();
+    let rays = (0..COUNT)
+        .map(|_| {
+            let origin = Vector3::random_interval(&random, -10.0, 10.0);
+            let target = Vector3::random_interval(&random, -1.0, 1.0);
+            Ray::new(origin, target - origin)
+        })
+        .collect();
+    (boxes, rays)
+}
+
+fn aabb_hit(c: &mut Criterion) {
+    let (boxes, rays) = boxes_and_rays();
+    let ray_t = Interval::new(0.001, f64::INFINITY);
+
+    let mut group = c.benchmark_group("aabb_hit");
+    group.bench_function("branch_free", |b| {
+        b.iter(|| {
+            boxes
+                .iter()
+                .zip(&rays)
+                .filter(|(bbox, ray)| black_box(bbox).hit(black_box(ray), ray_t))
+                .count()
+        })
+    });
+    group.bench_function("branchy", |b| {
+        b.iter(|| {
+            boxes
+                .iter()
+                .zip(&rays)
+                .filter(|(bbox, ray)| hit_branchy(black_box(bbox), black_box(ray), ray_t))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+fn sphere_hit(c: &mut Criterion) {
+    let (boxes, rays) = boxes_and_rays();
+    let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+    let spheres: Vec<Sphere> = boxes
+        .iter()
+        .map(|bbox| Sphere::new(bbox.centroid(), 1.0, material.clone()))
+        .collect();
+    let ctx = RenderContext::new_seeded(1);
+    let ray_t = Interval::new(0.001, f64::INFINITY);
+
+    c.bench_function("sphere_hit", |b| {
+        b.iter(|| {
+            spheres
+                .iter()
+                .zip(&rays)
+                .filter_map(|(sphere, ray)| black_box(sphere).hit(&ctx, black_box(ray), ray_t))
+                .count()
+        })
+    });
+}
+
+/// Rays through a hierarchy of small spheres, mostly box tests
+fn bvh_hit(c: &mut Criterion) {
+    let random = SeededRandom::new(2);
+    let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+    let spheres: Vec<Arc<dyn Node>> = (0..COUNT * 4)
+        .map(|_| {
+            let center = Vector3::random_interval(&random, -10.0, 10.0);
+            Arc::new(Sphere::new(center, 0.2, material.clone())) as Arc<dyn Node>
+        })
+        .collect();
+    let world = BoundingVolumeHierarchy::new(&spheres);
+    let (_, rays) = boxes_and_rays();
+    let ctx = RenderContext::new_seeded(1);
+    let ray_t = Interval::new(0.001, f64::INFINITY);
+
+    c.bench_function("bvh_hit", |b| {
+        b.iter(|| {
+            rays.iter()
+                .filter_map(|ray| world.hit(&ctx, black_box(ray), ray_t))
+                .count()
+        })
+    });
+}
+
+criterion_group!(benches, aabb_hit, sphere_hit, bvh_hit);
+criterion_main!(benches);
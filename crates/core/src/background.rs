@@ -0,0 +1,515 @@
+use std::{f64::consts::PI, fmt::Debug, sync::Arc};
+
+use crate::{Color, Image, RenderContext, Vector3, utils::OrthonormalBasis, utils::math};
+
+/// The light arriving from far away along rays that miss every object.
+pub trait Background: Send + Sync + Debug {
+    /// Returns the light arriving from `direction`, a unit vector pointing away
+    /// from the scene.
+    fn value(&self, direction: &Vector3) -> Color;
+
+    /// Returns true if the background lights the scene enough to be sampled
+    /// directly, using `pdf_value` and `random`.
+    fn is_sampled(&self) -> bool {
+        false
+    }
+
+    /// Probability density of `random` returning the unit vector `direction`.
+    fn pdf_value(&self, _direction: &Vector3) -> f64 {
+        0.0
+    }
+
+    /// Returns a direction towards the background, preferring bright areas.
+    fn random(&self, _ctx: &RenderContext) -> Vector3 {
+        Vector3::new(0.0, 1.0, 0.0)
+    }
+}
+
+/// A background of a single color.
+impl Background for Color {
+    fn value(&self, _direction: &Vector3) -> Color {
+        *self
+    }
+}
+
+/// A procedural sky, blending from the horizon color to the zenith color as
+/// directions point further up, over a ground of a single color.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Background, Color, Vector3, background::SkyGradient};
+///
+/// let sky = SkyGradient::new(Color::WHITE, Color::new(0.5, 0.7, 1.0))
+///     .with_ground(Color::new(0.3, 0.3, 0.3));
+/// assert_eq!(sky.value(&Vector3::new(0.0, 1.0, 0.0)), Color::new(0.5, 0.7, 1.0));
+/// assert_eq!(sky.value(&Vector3::new(1.0, 0.0, 0.0)), Color::WHITE);
+/// assert_eq!(sky.value(&Vector3::new(0.0, -1.0, 0.0)), Color::new(0.3, 0.3, 0.3));
+/// ```
+#[derive(Debug)]
+pub struct SkyGradient {
+    horizon: Color,
+    zenith: Color,
+    ground: Color,
+}
+
+impl SkyGradient {
+    pub fn new(horizon: Color, zenith: Color) -> Self {
+        Self {
+            horizon,
+            zenith,
+            ground: horizon,
+        }
+    }
+
+    /// Sets the color below the horizon, the horizon color by default.
+    pub fn with_ground(mut self, ground: Color) -> Self {
+        self.ground = ground;
+        self
+    }
+}
+
+impl Background for SkyGradient {
+    fn value(&self, direction: &Vector3) -> Color {
+        let up = direction.unit().y;
+        if up < 0.0 {
+            self.ground
+        } else {
+            self.horizon * (1.0 - up) + self.zenith * up
+        }
+    }
+}
+
+/// A panorama surrounding the scene, stored as an equirectangular image such
+/// as the HDR environment maps used for outdoor lighting.
+///
+/// The top row of the image is straight up and the left and right edges meet
+/// behind `-X`, like the texture coordinates of a
+/// [`crate::object::Sphere`]. Directions are sampled in proportion to the
+/// brightness of the image, so small bright areas like the sun light the
+/// scene without much noise.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Background, Color, Image, RenderContext, Vector3, background::EnvironmentMap,
+/// };
+///
+/// // A dark sky with a bright pixel at the zenith
+/// #[derive(Debug)]
+/// struct Sky;
+/// impl Image for Sky {
+///     fn width(&self) -> u32 { 4 }
+///     fn height(&self) -> u32 { 4 }
+///     fn get_pixel(&self, x: u32, y: u32) -> Option<Color> {
+///         Some(if x == 0 && y == 0 { Color::new(100.0, 100.0, 100.0) } else { Color::new(0.1, 0.1, 0.1) })
+///     }
+/// }
+///
+/// let environment = EnvironmentMap::new(Arc::new(Sky)).with_intensity(2.0);
+/// assert_eq!(environment.value(&Vector3::new(0.0, -1.0, 0.0)), Color::new(0.2, 0.2, 0.2));
+///
+/// // Nearly all samples head for the bright pixel
+/// let ctx = RenderContext::new_seeded(1);
+/// let upward = (0..1000)
+///     .map(|_| environment.random(&ctx))
+///     .filter(|direction| direction.y > 0.0 && environment.pdf_value(direction) > 1.0)
+///     .count();
+/// assert!(upward > 900, "{upward} samples upward");
+/// ```
+#[derive(Debug)]
+pub struct EnvironmentMap {
+    image: Arc<dyn Image>,
+    intensity: f64,
+    /// Rotation around the Y axis in radians
+    rotation: f64,
+    /// Cumulative distribution of picking each row, `height + 1` values
+    row_cdf: Vec<f64>,
+    /// Cumulative distribution of picking each column of a row, `width + 1`
+    /// values per row
+    column_cdf: Vec<f64>,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: Arc<dyn Image>) -> Self {
+        let (width, height) = (image.width() as usize, image.height() as usize);
+
+        // Rows near the poles cover less of the sphere, weight by sin(θ)
+        let mut row_cdf = vec![0.0; height + 1];
+        let mut column_cdf = vec![0.0; height * (width + 1)];
+        for y in 0..height {
+            let sin_theta = math::sin(PI * (y as f64 + 0.5) / height as f64);
+            let row = &mut column_cdf[y * (width + 1)..(y + 1) * (width + 1)];
+            for x in 0..width {
+                let luminance = image
+                    .get_pixel(x as u32, y as u32)
+                    .map(|c| c.luminance().max(0.0))
+                    .unwrap_or(0.0);
+                row[x + 1] = row[x] + luminance * sin_theta;
+            }
+            row_cdf[y + 1] = row_cdf[y] + row[width];
+        }
+
+        Self {
+            image,
+            intensity: 1.0,
+            rotation: 0.0,
+            row_cdf,
+            column_cdf,
+        }
+    }
+
+    /// Scales the brightness of the image.
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Turns the panorama around the Y axis, in degrees.
+    pub fn with_rotation(mut self, degrees: f64) -> Self {
+        self.rotation = degrees.to_radians();
+        self
+    }
+
+    pub fn get_intensity(&self) -> f64 {
+        self.intensity
+    }
+
+    pub fn get_rotation(&self) -> f64 {
+        self.rotation.to_degrees()
+    }
+
+    fn width(&self) -> usize {
+        self.image.width() as usize
+    }
+
+    fn height(&self) -> usize {
+        self.image.height() as usize
+    }
+
+    fn total(&self) -> f64 {
+        self.row_cdf.last().copied().unwrap_or(0.0)
+    }
+
+    /// Returns the image coordinates in [0, 1] of a unit direction.
+    fn direction_to_uv(&self, direction: &Vector3) -> (f64, f64) {
+        let phi = math::atan2(-direction.z, direction.x) + PI - self.rotation;
+        let u = (phi / (2.0 * PI)).rem_euclid(1.0);
+        let v = math::acos(direction.y.clamp(-1.0, 1.0)) / PI;
+        (u, v)
+    }
+
+    fn uv_to_direction(&self, u: f64, v: f64) -> Vector3 {
+        let phi = 2.0 * PI * u - PI + self.rotation;
+        let theta = PI * v;
+        Vector3::new(
+            math::cos(phi) * math::sin(theta),
+            math::cos(theta),
+            -math::sin(phi) * math::sin(theta),
+        )
+    }
+
+    fn pixel(&self, u: f64, v: f64) -> (usize, usize) {
+        let x = ((u * self.width() as f64) as usize).min(self.width() - 1);
+        let y = ((v * self.height() as f64) as usize).min(self.height() - 1);
+        (x, y)
+    }
+}
+
+/// Returns the index of the interval of `cdf` containing `value`.
+fn sample_cdf(cdf: &[f64], value: f64) -> usize {
+    cdf.partition_point(|c| *c <= value)
+        .saturating_sub(1)
+        .min(cdf.len() - 2)
+}
+
+impl Background for EnvironmentMap {
+    fn value(&self, direction: &Vector3) -> Color {
+        if self.width() == 0 || self.height() == 0 {
+            return Color::BLACK;
+        }
+        let (u, v) = self.direction_to_uv(&direction.unit());
+        let (x, y) = self.pixel(u, v);
+        self.image
+            .get_pixel(x as u32, y as u32)
+            .unwrap_or(Color::BLACK)
+            * self.intensity
+    }
+
+    fn is_sampled(&self) -> bool {
+        self.total() > 0.0
+    }
+
+    fn pdf_value(&self, direction: &Vector3) -> f64 {
+        if !self.is_sampled() {
+            return 0.0;
+        }
+        let direction = direction.unit();
+        let sin_theta = (1.0 - direction.y * direction.y).max(0.0).sqrt();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+
+        let (u, v) = self.direction_to_uv(&direction);
+        let (x, y) = self.pixel(u, v);
+        let row = &self.column_cdf[y * (self.width() + 1)..];
+        let probability = (row[x + 1] - row[x]) / self.total();
+
+        // From the density over the image to the density over the sphere
+        let pixels = (self.width() * self.height()) as f64;
+        probability * pixels / (2.0 * PI * PI * sin_theta)
+    }
+
+    fn random(&self, ctx: &RenderContext) -> Vector3 {
+        if !self.is_sampled() {
+            return Vector3::new(0.0, 1.0, 0.0);
+        }
+        let y = sample_cdf(&self.row_cdf, ctx.random.rand() * self.total());
+        let row = &self.column_cdf[y * (self.width() + 1)..(y + 1) * (self.width() + 1)];
+        let x = sample_cdf(row, ctx.random.rand() * row[self.width()]);
+
+        let u = (x as f64 + ctx.random.rand()) / self.width() as f64;
+        let v = (y as f64 + ctx.random.rand()) / self.height() as f64;
+        self.uv_to_direction(u, v)
+    }
+}
+
+/// Scale from the sky model's luminance, in kcd/m², to scene brightness
+const SKY_SCALE: f64 = 0.05;
+
+/// Brightness of the sun disc relative to a luminance of 1 in the sky
+const SUN_RADIANCE: f64 = 40000.0;
+
+/// Angular radius of the sun seen from the earth
+const SUN_ANGULAR_RADIUS: f64 = 0.00465;
+
+/// Physically based daylight from the Preetham, Shirley and Smits analytic sky
+/// model, with a sun disc in the sun direction which is sampled directly, like
+/// a directional light.
+///
+/// Turbidity is the haziness of the air, 2 for a clear sky up to about 10 for
+/// a hazy one. The sky reddens as the sun approaches the horizon. Below the
+/// horizon the ground reflects a fraction of the sky at the horizon.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Background, Vector3, background::PreethamSky};
+///
+/// let noon = PreethamSky::new(Vector3::new(0.2, 1.0, 0.3), 3.0);
+/// let zenith = noon.value(&Vector3::new(0.0, 1.0, 0.0));
+/// assert!(zenith.b > zenith.r);
+///
+/// // Looking at the sun is much brighter than the sky
+/// let sun = noon.value(&noon.get_sun_direction());
+/// assert!(sun.luminance() > 100.0 * zenith.luminance());
+/// ```
+#[derive(Debug)]
+pub struct PreethamSky {
+    sun_direction: Vector3,
+    turbidity: f64,
+    intensity: f64,
+    ground_albedo: f64,
+    /// Sun zenith angle in radians, kept above the horizon
+    theta_sun: f64,
+    /// Perez coefficients A to E of the luminance and x and y chromaticities
+    perez: [[f64; 5]; 3],
+    /// Luminance and chromaticity at the zenith
+    zenith: [f64; 3],
+    sun_color: Color,
+}
+
+impl PreethamSky {
+    /// Creates a sky lit by a sun in the direction `sun_direction`.
+    pub fn new(sun_direction: Vector3, turbidity: f64) -> Self {
+        let sun_direction = sun_direction.unit();
+        let t = turbidity.clamp(1.0, 20.0);
+        // The model only holds while the sun is above the horizon
+        let theta_sun = math::acos(sun_direction.y.clamp(-1.0, 1.0)).min(PI / 2.0 - 0.01);
+
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_sun);
+        let zenith_luminance = (4.0453 * t - 4.9710) * math::tan(chi) - 0.2155 * t + 2.4192;
+        let chromaticity = |m: [[f64; 4]; 3]| {
+            let thetas = [
+                math::powi(theta_sun, 3),
+                math::powi(theta_sun, 2),
+                theta_sun,
+                1.0,
+            ];
+            let ts = [t * t, t, 1.0];
+            (0..3)
+                .map(|i| ts[i] * (0..4).map(|j| m[i][j] * thetas[j]).sum::<f64>())
+                .sum::<f64>()
+        };
+        let zenith_x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_y = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        let mut sky = Self {
+            sun_direction,
+            turbidity: t,
+            intensity: 1.0,
+            ground_albedo: 0.3,
+            theta_sun,
+            perez,
+            zenith: [zenith_luminance.max(0.0), zenith_x, zenith_y],
+            sun_color: Color::BLACK,
+        };
+
+        // The sun takes the hue of the sky around it, normalized to a luminance of 1
+        let around_sun = sky.sky_color(&sun_direction);
+        let luminance = around_sun.luminance();
+        if luminance > 0.0 {
+            sky.sun_color = around_sun / luminance;
+        }
+        sky
+    }
+
+    /// Scales the brightness of both the sky and the sun.
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Sets the fraction of the light at the horizon reflected by the ground.
+    pub fn with_ground_albedo(mut self, ground_albedo: f64) -> Self {
+        self.ground_albedo = ground_albedo.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn get_sun_direction(&self) -> Vector3 {
+        self.sun_direction
+    }
+
+    pub fn get_turbidity(&self) -> f64 {
+        self.turbidity
+    }
+
+    pub fn get_intensity(&self) -> f64 {
+        self.intensity
+    }
+
+    /// Perez sky distribution function for the angle to the zenith `theta` and
+    /// the angle to the sun `gamma`.
+    fn perez(coefficients: &[f64; 5], theta: f64, gamma: f64) -> f64 {
+        let [a, b, c, d, e] = *coefficients;
+        (1.0 + a * math::exp(b / math::cos(theta)))
+            * (1.0 + c * math::exp(d * gamma) + e * math::powi(math::cos(gamma), 2))
+    }
+
+    /// Linear RGB of the sky, without the sun, in a unit direction above the horizon.
+    fn sky_color(&self, direction: &Vector3) -> Color {
+        let theta = math::acos(direction.y.clamp(0.001, 1.0));
+        let gamma = math::acos(direction.dot(&self.sun_direction).clamp(-1.0, 1.0));
+
+        let [luminance, x, y] = [0, 1, 2].map(|i| {
+            self.zenith[i] * Self::perez(&self.perez[i], theta, gamma)
+                / Self::perez(&self.perez[i], 0.0, self.theta_sun)
+        });
+        if y <= 0.0 {
+            return Color::BLACK;
+        }
+
+        // xyY to XYZ to linear sRGB
+        let big_x = x / y * luminance;
+        let big_z = (1.0 - x - y) / y * luminance;
+        Color::from_xyz(big_x, luminance, big_z) * (SKY_SCALE * self.intensity)
+    }
+
+    fn is_sun_visible(&self) -> bool {
+        self.sun_direction.y > -SUN_ANGULAR_RADIUS
+    }
+}
+
+impl Background for PreethamSky {
+    fn value(&self, direction: &Vector3) -> Color {
+        let direction = direction.unit();
+        if direction.y < 0.0 {
+            let horizon = Vector3::new(direction.x, 0.0, direction.z);
+            if horizon.is_near_zero() {
+                return self.sky_color(&Vector3::new(1.0, 0.0, 0.0)) * self.ground_albedo;
+            }
+            return self.sky_color(&horizon.unit()) * self.ground_albedo;
+        }
+
+        let sky = self.sky_color(&direction);
+        if self.is_sun_visible()
+            && direction.dot(&self.sun_direction) > math::cos(SUN_ANGULAR_RADIUS)
+        {
+            sky + self.sun_color * (SUN_RADIANCE * SKY_SCALE * self.intensity)
+        } else {
+            sky
+        }
+    }
+
+    fn is_sampled(&self) -> bool {
+        self.is_sun_visible()
+    }
+
+    /// Half of the directions are sampled towards the sun and half uniformly
+    /// over the sphere for the rest of the sky.
+    fn pdf_value(&self, direction: &Vector3) -> f64 {
+        let cos_max = math::cos(SUN_ANGULAR_RADIUS);
+        let uniform = 1.0 / (4.0 * PI);
+        let sun = if direction.unit().dot(&self.sun_direction) > cos_max {
+            1.0 / (2.0 * PI * (1.0 - cos_max))
+        } else {
+            0.0
+        };
+        0.5 * uniform + 0.5 * sun
+    }
+
+    fn random(&self, ctx: &RenderContext) -> Vector3 {
+        if ctx.random.rand() < 0.5 {
+            // Uniform direction in the cone of the sun disc
+            let cos_max = math::cos(SUN_ANGULAR_RADIUS);
+            let cos_theta = 1.0 - ctx.random.rand() * (1.0 - cos_max);
+            let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+            let phi = 2.0 * PI * ctx.random.rand();
+            let basis = OrthonormalBasis::new(self.sun_direction);
+            basis.transform_to_local(Vector3::new(
+                math::cos(phi) * sin_theta,
+                math::sin(phi) * sin_theta,
+                cos_theta,
+            ))
+        } else {
+            let z = 1.0 - 2.0 * ctx.random.rand();
+            let r = (1.0 - z * z).max(0.0).sqrt();
+            let phi = 2.0 * PI * ctx.random.rand();
+            Vector3::new(r * math::cos(phi), r * math::sin(phi), z)
+        }
+    }
+}
@@ -1,10 +1,237 @@
 use std::{f64, sync::Arc};
 
 use crate::{
-    Color, HittablePdf, Interval, Random, Ray, RenderContext, Vector3, material::PdfOrRay,
-    object::Node, probability_density_function::MixturePdf,
+    Background, BackgroundPdf, Color, Interval, Light, LightPdf, ProbabilityDensityFunction,
+    Quaternion, Random, Ray, RenderContext, TransferFunction, Vector3,
+    material::PdfOrRay,
+    object::{HitRecord, Node},
+    probability_density_function::{MixturePdf, power_heuristic},
+    render::PhotonMap,
+    utils::math,
 };
 
+/// A rectangle of the image, in pixels from the top left corner, limiting which
+/// pixels are rendered. Pixels which are not rendered are black, so a detail of
+/// an expensive scene can be iterated on at full quality.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::RenderRegion;
+///
+/// let detail = RenderRegion::new(10, 20, 30, 40);
+/// assert!(detail.contains(10, 20));
+/// assert!(!detail.contains(40, 20));
+///
+/// // Renders everything except the detail
+/// let rest = detail.inverted();
+/// assert!(!rest.contains(10, 20));
+/// assert!(rest.contains(40, 20));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RenderRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Renders the pixels outside of the rectangle instead of inside.
+    pub invert: bool,
+}
+
+impl RenderRegion {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+            invert: false,
+        }
+    }
+
+    /// Returns the region rendering the pixels this region leaves out.
+    pub fn inverted(self) -> Self {
+        Self {
+            invert: !self.invert,
+            ..self
+        }
+    }
+
+    /// Returns whether the pixel at (x, y) is rendered.
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        let inside =
+            x >= self.x && x - self.x < self.width && y >= self.y && y - self.y < self.height;
+        inside != self.invert
+    }
+}
+
+/// A part of the image given as fractions of its width and height from the top
+/// left corner, so the same window can be re-rendered at any resolution.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{CropWindow, RenderRegion};
+///
+/// let center = CropWindow::new(0.25, 0.75, 0.25, 0.75);
+/// assert_eq!(center.to_render_region(200, 100), RenderRegion::new(50, 25, 100, 50));
+///
+/// // Partly covered pixels are rendered, the window is clamped to the image
+/// let edge = CropWindow::new(0.901, 1.5, -1.0, 0.1);
+/// assert_eq!(edge.to_render_region(10, 10), RenderRegion::new(9, 0, 1, 1));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CropWindow {
+    pub xmin: f64,
+    pub xmax: f64,
+    pub ymin: f64,
+    pub ymax: f64,
+}
+
+impl CropWindow {
+    pub fn new(xmin: f64, xmax: f64, ymin: f64, ymax: f64) -> Self {
+        Self {
+            xmin,
+            xmax,
+            ymin,
+            ymax,
+        }
+    }
+
+    /// Returns the pixels of a `width` by `height` image covered by the window.
+    pub fn to_render_region(&self, width: u32, height: u32) -> RenderRegion {
+        let span = |min: f64, max: f64, size: u32| {
+            let start = (min.clamp(0.0, 1.0) * size as f64).floor() as u32;
+            let end = (max.clamp(0.0, 1.0) * size as f64).ceil() as u32;
+            (start, end.saturating_sub(start))
+        };
+        let (x, width) = span(self.xmin, self.xmax, width);
+        let (y, height) = span(self.ymin, self.ymax, height);
+        RenderRegion::new(x, y, width, height)
+    }
+}
+
+/// How a [`Camera`] projects the scene onto the image.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{CameraBuilder, Projection, Vector3};
+///
+/// let mut camera_builder = CameraBuilder::new();
+/// camera_builder.look_from = Vector3::new(0.0, 0.0, 10.0);
+/// camera_builder.look_at = Vector3::ZERO;
+/// camera_builder.projection = Projection::Orthographic { view_height: 4.0 };
+/// let camera = camera_builder.build();
+///
+/// // Every ray points straight at the scene, the top edge is 2 units up
+/// let top_left = camera.get_pick_ray(0.0, 0.0);
+/// let center = camera.get_pick_ray(0.5, 0.5);
+/// assert_eq!(top_left.direction.unit(), center.direction.unit());
+/// assert!((top_left.origin.y - 2.0).abs() < 1e-9);
+/// assert_eq!(center.origin, Vector3::new(0.0, 0.0, 10.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Projection {
+    /// Rays spread out from the camera position, distant objects look smaller.
+    #[default]
+    Perspective,
+    /// Rays are parallel to the view direction, so objects keep their size at
+    /// any distance, as in technical drawings and isometric views.
+    Orthographic {
+        /// Height of the visible part of the scene in world units
+        view_height: f64,
+    },
+}
+
+/// How much light reaches the image, so scenes with bright light sources can
+/// be rendered without rescaling every emitter.
+///
+/// Colors are scaled by the exposure before the transfer function. Camera
+/// settings are relative to ISO 100, f/1 and a 1 second shutter, which leave
+/// colors unchanged, and must be positive.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::Exposure;
+///
+/// assert_eq!(Exposure::default().scale(), 1.0);
+///
+/// // Each stop doubles or halves the light
+/// assert_eq!(Exposure::Ev(2.0).scale(), 4.0);
+/// assert_eq!(Exposure::Ev(-1.0).scale(), 0.5);
+///
+/// // Closing the aperture two stops and doubling the ISO
+/// let settings = Exposure::Camera { iso: 200.0, f_number: 2.0, shutter: 1.0 };
+/// assert_eq!(settings.scale(), 0.5);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Exposure {
+    /// Exposure compensation in stops, positive values brighten the image
+    Ev(f64),
+    /// Settings of a physical camera
+    Camera {
+        /// Sensor sensitivity
+        iso: f64,
+        /// Focal length divided by the aperture diameter
+        f_number: f64,
+        /// Shutter time in seconds
+        shutter: f64,
+    },
+}
+
+impl Exposure {
+    /// Returns the factor colors are multiplied by.
+    pub fn scale(&self) -> f64 {
+        match *self {
+            Exposure::Ev(ev) => math::exp2(ev),
+            Exposure::Camera {
+                iso,
+                f_number,
+                shutter,
+            } => iso / 100.0 * shutter / (f_number * f_number),
+        }
+    }
+}
+
+impl Default for Exposure {
+    fn default() -> Self {
+        Exposure::Ev(0.0)
+    }
+}
+
+/// Outputs of a pixel besides its color, for denoisers and compositing.
+///
+/// Albedo, normal and depth are averaged over the samples of the pixel, so
+/// their edges are antialiased like the rendered color.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aovs {
+    /// Color of the surfaces without lighting, the background where nothing
+    /// is hit
+    pub albedo: Color,
+    /// Shading normal in world space facing the camera, zero where nothing is
+    /// hit
+    pub normal: Vector3,
+    /// Distance from the camera to the surfaces, infinite where nothing is hit
+    pub depth: f64,
+    /// Id of the object seen by the first sample, 0 for the background.
+    /// Objects are the nodes of the scene, see [`HitRecord::object_id`], ids
+    /// stay the same while the scene is loaded.
+    pub object_id: u32,
+}
+
+impl Default for Aovs {
+    fn default() -> Self {
+        Self {
+            albedo: Color::BLACK,
+            normal: Vector3::ZERO,
+            depth: f64::INFINITY,
+            object_id: 0,
+        }
+    }
+}
+
 /// Builder for configuring and constructing a [`Camera`].
 ///
 /// The `CameraBuilder` uses the builder pattern to configure camera parameters
@@ -14,6 +241,7 @@ use crate::{
 /// # Examples
 ///
 /// ```
+/// use std::sync::Arc;
 /// use caustic_core::{CameraBuilder, Vector3, Color};
 ///
 /// let mut camera_builder = CameraBuilder::new();
@@ -27,10 +255,10 @@ use crate::{
 /// camera_builder.up = Vector3::new(0.0, 1.0, 0.0);
 /// camera_builder.defocus_angle = 0.6;
 /// camera_builder.focus_distance = 10.0;
-/// camera_builder.background = Color::new(0.7, 0.8, 1.0);
+/// camera_builder.background = Arc::new(Color::new(0.7, 0.8, 1.0));
 /// let camera = camera_builder.build();
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CameraBuilder {
     /// Vertical view angle (field of view) in degrees.
     ///
@@ -82,10 +310,73 @@ pub struct CameraBuilder {
     /// Higher values allow more light bounces but increase computation.
     pub max_depth: u32,
 
-    /// Scene background color.
+    /// Scene background.
+    ///
+    /// Light returned when a ray doesn't hit any objects in the scene, a
+    /// [`Color`], a [`crate::background::SkyGradient`] or an
+    /// [`crate::background::EnvironmentMap`].
+    pub background: Arc<dyn Background>,
+
+    /// Radial lens distortion coefficient.
+    ///
+    /// Positive values bow straight lines outwards (barrel distortion), negative
+    /// values pinch them inwards (pincushion distortion). 0 is a perfect lens.
+    pub lens_distortion: f64,
+
+    /// Part of the image to render.
+    ///
+    /// Pixels outside of the region are black. `None` renders the whole image.
+    /// The region stays on the same pixels of the shot when the camera has
+    /// [overscan](Camera::with_overscan).
+    pub render_region: Option<RenderRegion>,
+
+    /// Part of the image to render as fractions of its size.
+    ///
+    /// Replaces `render_region` when set.
+    pub crop_window: Option<CropWindow>,
+
+    /// Strength of lateral chromatic aberration.
     ///
-    /// Color returned when a ray doesn't hit any objects in the scene.
-    pub background: Color,
+    /// Red and blue are distorted by this much more and less than green, producing
+    /// color fringes towards the edges of the image. 0 disables it.
+    pub chromatic_aberration: f64,
+
+    /// Perspective or orthographic projection.
+    ///
+    /// An orthographic camera ignores `vertical_fov`, its rays start on the plane
+    /// through `look_from` facing `look_at`.
+    pub projection: Projection,
+
+    /// Scale applied to colors before the transfer function.
+    pub exposure: Exposure,
+
+    /// Highest value of each color channel of a traced path, `None` to never
+    /// clamp.
+    ///
+    /// Rare paths finding a bright light through a small PDF leave single very
+    /// bright pixels (fireflies) which take many samples to average out.
+    /// Clamping removes them but darkens bright highlights, caustics and
+    /// lights seen in reflections, so the render no longer converges to the
+    /// exact image. Raise it or turn it off for reference renders.
+    pub firefly_clamp: Option<f64>,
+
+    /// Smallest PDF value a scattered ray may have, below which the path ends
+    /// with only the emitted light.
+    ///
+    /// Dividing by tiny PDFs produces fireflies, dropping those paths avoids
+    /// them but loses the light they carry, darkening grazing angles and
+    /// glossy reflections. 0 keeps every path with a valid PDF.
+    pub min_pdf: f64,
+
+    /// Photons traced from the lights for caustics, 0 to find caustics by
+    /// path tracing alone.
+    ///
+    /// Caustics seen by the camera on diffuse surfaces are then read from a
+    /// [`PhotonMap`] traced by [`crate::SceneData::trace_caustics`] when
+    /// rendering starts, or given by [`Camera::with_caustics`], which
+    /// converges much faster than finding small lights through glass, at the
+    /// cost of some blur. More photons give sharper caustics.
+    pub caustic_photons: u32,
 }
 
 impl CameraBuilder {
@@ -103,19 +394,37 @@ impl CameraBuilder {
     /// - up: (0, 1, 0)
     /// - defocus_angle: 0 (no depth of field)
     /// - focus_distance: 10
+    /// - lens_distortion: 0 (no distortion)
+    /// - chromatic_aberration: 0 (no color fringes)
+    /// - render_region: None (the whole image)
+    /// - crop_window: None (the whole image)
+    /// - projection: perspective
+    /// - exposure: 0 EV (colors unchanged)
+    /// - firefly_clamp: 10
+    /// - min_pdf: 0.05
+    /// - caustic_photons: 0 (no photon map)
     pub fn new() -> Self {
         CameraBuilder {
             aspect_ratio: 1.0,
             image_width: 100,
             samples_per_pixel: 10,
             max_depth: 10,
-            background: Color::new(0.0, 0.0, 0.0),
+            background: Arc::new(Color::BLACK),
             vertical_fov: 90.0,
             look_from: Vector3::new(0.0, 0.0, 0.0),
             look_at: Vector3::new(0.0, 0.0, -1.0),
             up: Vector3::new(0.0, 1.0, 0.0),
             defocus_angle: 0.0,
             focus_distance: 10.0,
+            lens_distortion: 0.0,
+            chromatic_aberration: 0.0,
+            render_region: None,
+            crop_window: None,
+            projection: Projection::Perspective,
+            exposure: Exposure::default(),
+            firefly_clamp: Some(10.0),
+            min_pdf: 0.05,
+            caustic_photons: 0,
         }
     }
 
@@ -135,9 +444,14 @@ impl CameraBuilder {
         let center = self.look_from;
 
         // Calculate viewport dimensions based on field of view
-        let theta = self.vertical_fov.to_radians();
-        let h = (theta / 2.0).tan();
-        let viewport_height = 2.0 * h * self.focus_distance;
+        let viewport_height = match self.projection {
+            Projection::Perspective => {
+                let theta = self.vertical_fov.to_radians();
+                let h = math::tan(theta / 2.0);
+                2.0 * h * self.focus_distance
+            }
+            Projection::Orthographic { view_height } => view_height,
+        };
         let viewport_width: f64 = viewport_height * (self.image_width as f64 / image_height as f64);
 
         // Calculate the u,v,w unit basis vectors for the camera coordinate frame.
@@ -157,12 +471,20 @@ impl CameraBuilder {
         let viewport_upper_left =
             center - (self.focus_distance * w) - viewport_u / 2.0 - viewport_v / 2.0;
         let pixel00_loc = viewport_upper_left + 0.5 * (pixel_delta_u + pixel_delta_v);
+        let viewport_center = center - (self.focus_distance * w);
 
         // Calculate the camera defocus disk basis vectors.
-        let defocus_radius = self.focus_distance * ((self.defocus_angle / 2.0).to_radians()).tan();
+        let defocus_radius =
+            self.focus_distance * math::tan((self.defocus_angle / 2.0).to_radians());
         let defocus_disk_u = u * defocus_radius;
         let defocus_disk_v = v * defocus_radius;
 
+        // Orthographic rays start on the camera plane, behind their pixel
+        let orthographic_offset = match self.projection {
+            Projection::Perspective => None,
+            Projection::Orthographic { .. } => Some(self.focus_distance * w),
+        };
+
         Camera {
             image_width: self.image_width,
             image_height,
@@ -174,12 +496,131 @@ impl CameraBuilder {
             defocus_angle: self.defocus_angle,
             defocus_disk_u,
             defocus_disk_v,
-            background: self.background,
+            background: self.background.clone(),
             sqrt_spp,
             reciprocal_sqrt_spp,
             pixel_samples_scale,
+            viewport_center,
+            viewport_half_height: viewport_height / 2.0,
+            lens_distortion: self.lens_distortion,
+            chromatic_aberration: self.chromatic_aberration,
+            render_region: match self.crop_window {
+                Some(crop_window) => {
+                    Some(crop_window.to_render_region(self.image_width, image_height))
+                }
+                None => self.render_region,
+            },
+            orthographic_offset,
+            exposure_scale: self.exposure.scale(),
+            firefly_clamp: self.firefly_clamp,
+            min_pdf: self.min_pdf,
+            caustics: None,
+            frame_x: 0,
+            frame_y: 0,
+            builder: self.clone(),
+        }
+    }
+
+    /// Moves `look_from` around `look_at` by `yaw` degrees about `up` and
+    /// `pitch` degrees towards `up`, keeping its distance, as when dragging
+    /// in a model viewer. The pitch stops a degree short of looking along
+    /// `up`, where the view would flip.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{CameraBuilder, Vector3};
+    ///
+    /// let mut camera_builder = CameraBuilder::new();
+    /// camera_builder.look_from = Vector3::new(0.0, 0.0, 10.0);
+    /// camera_builder.look_at = Vector3::new(0.0, 0.0, 0.0);
+    ///
+    /// camera_builder.orbit(90.0, 0.0);
+    /// assert!((camera_builder.look_from - Vector3::new(10.0, 0.0, 0.0)).length() < 1e-9);
+    ///
+    /// // Looking from straight above is as far as it goes
+    /// camera_builder.orbit(0.0, 120.0);
+    /// assert!(camera_builder.look_from.y > 9.99);
+    /// assert!((camera_builder.look_from.length() - 10.0).abs() < 1e-9);
+    /// ```
+    pub fn orbit(&mut self, yaw: f64, pitch: f64) {
+        let up = self.up.unit();
+        let offset = Quaternion::from_axis_angle(up, yaw).rotate(self.look_from - self.look_at);
+
+        let axis = offset.cross(&up);
+        if !axis.is_near_zero() {
+            // Angle between the offset and up, kept within 1 and 179 degrees
+            let elevation = math::acos(offset.unit().dot(&up).clamp(-1.0, 1.0)).to_degrees();
+            let pitch = pitch.clamp(elevation - 179.0, elevation - 1.0);
+            self.look_from = self.look_at + Quaternion::from_axis_angle(axis, pitch).rotate(offset);
+        } else {
+            self.look_from = self.look_at + offset;
+        }
+    }
+
+    /// Moves `look_from` towards `look_at`, multiplying their distance by
+    /// `factor`, so values below 1 zoom in. The focus distance follows, and
+    /// orthographic cameras scale their view height instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{CameraBuilder, Vector3};
+    ///
+    /// let mut camera_builder = CameraBuilder::new();
+    /// camera_builder.look_from = Vector3::new(0.0, 0.0, 10.0);
+    /// camera_builder.look_at = Vector3::new(0.0, 0.0, 0.0);
+    /// camera_builder.focus_distance = 10.0;
+    ///
+    /// camera_builder.dolly(0.5);
+    /// assert_eq!(camera_builder.look_from, Vector3::new(0.0, 0.0, 5.0));
+    /// assert_eq!(camera_builder.focus_distance, 5.0);
+    /// ```
+    pub fn dolly(&mut self, factor: f64) {
+        let factor = factor.max(1e-6);
+        match &mut self.projection {
+            Projection::Perspective => {
+                self.look_from = self.look_at + (self.look_from - self.look_at) * factor;
+                self.focus_distance *= factor;
+            }
+            Projection::Orthographic { view_height } => *view_height *= factor,
         }
     }
+
+    /// Moves `look_from` and `look_at` together across the view, by `right`
+    /// and `up` times the height of the view at `look_at`, so a drag across
+    /// the image can move the scene by the same amount.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{CameraBuilder, Vector3};
+    ///
+    /// let mut camera_builder = CameraBuilder::new();
+    /// camera_builder.look_from = Vector3::new(0.0, 0.0, 10.0);
+    /// camera_builder.look_at = Vector3::new(0.0, 0.0, 0.0);
+    /// camera_builder.vertical_fov = 90.0;
+    ///
+    /// // The view is 20 units high at look_at
+    /// camera_builder.pan(0.5, 0.0);
+    /// assert!((camera_builder.look_at - Vector3::new(10.0, 0.0, 0.0)).length() < 1e-9);
+    /// assert!((camera_builder.look_from - Vector3::new(10.0, 0.0, 10.0)).length() < 1e-9);
+    /// ```
+    pub fn pan(&mut self, right: f64, up: f64) {
+        let offset = self.look_from - self.look_at;
+        let view_height = match self.projection {
+            Projection::Perspective => {
+                2.0 * math::tan(self.vertical_fov.to_radians() / 2.0) * offset.length()
+            }
+            Projection::Orthographic { view_height } => view_height,
+        };
+        let w = offset.unit();
+        let u = self.up.cross(&w).unit();
+        let v = w.cross(&u);
+        let shift = (right * u + up * v) * view_height;
+        self.look_from = self.look_from + shift;
+        self.look_at = self.look_at + shift;
+    }
 }
 
 impl Default for CameraBuilder {
@@ -188,6 +629,18 @@ impl Default for CameraBuilder {
     }
 }
 
+/// The bounces a path took before the ray being traced.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PathKind {
+    /// Only specular bounces since the camera
+    Camera,
+    /// Just scattered by a diffuse or glossy surface
+    Diffuse,
+    /// Specular bounces since the last diffuse or glossy surface, the light
+    /// found is a caustic on that surface
+    Caustic,
+}
+
 /// A camera that renders 3D scenes.
 ///
 /// The `Camera` struct represents a configured camera ready to render images.
@@ -198,7 +651,7 @@ impl Default for CameraBuilder {
 /// - Path tracing with importance sampling
 ///
 /// Use [`CameraBuilder`] to construct a `Camera` instance.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Camera {
     /// Rendered image width in pixels
     image_width: u32,
@@ -222,12 +675,39 @@ pub struct Camera {
     defocus_disk_u: Vector3,
     /// Defocus disk vertical radius vector
     defocus_disk_v: Vector3,
-    /// Scene background color for rays that miss all objects
-    background: Color,
+    /// Scene background for rays that miss all objects
+    background: Arc<dyn Background>,
     /// Square root of number of samples per pixel
     sqrt_spp: u32,
     /// Reciprocal of sqrt_spp (1 / sqrt_spp)
     reciprocal_sqrt_spp: f64,
+    /// Center of the viewport in world space, the optical axis of the lens
+    viewport_center: Vector3,
+    /// Half of the viewport height, used to normalize the lens radius
+    viewport_half_height: f64,
+    /// Radial lens distortion coefficient
+    lens_distortion: f64,
+    /// Lateral chromatic aberration strength
+    chromatic_aberration: f64,
+    /// Part of the image to render, all of it when `None`
+    render_region: Option<RenderRegion>,
+    /// Offset from a point on the viewport back to the camera plane for
+    /// orthographic cameras, `None` for perspective cameras
+    orthographic_offset: Option<Vector3>,
+    /// Factor colors are multiplied by, from the exposure
+    exposure_scale: f64,
+    /// Highest value of each color channel of a path, unclamped when `None`
+    firefly_clamp: Option<f64>,
+    /// Smallest PDF value of a scattered ray
+    min_pdf: f64,
+    /// Caustics read at diffuse surfaces rather than path traced
+    caustics: Option<Arc<PhotonMap>>,
+    /// Pixels of overscan left and right of the frame
+    frame_x: u32,
+    /// Pixels of overscan above and below the frame
+    frame_y: u32,
+    /// Settings the camera was built from
+    builder: CameraBuilder,
 }
 
 impl Camera {
@@ -235,7 +715,8 @@ impl Camera {
     ///
     /// This method recursively traces rays through the scene, accumulating color
     /// from emissive materials and scattered light. It uses importance sampling
-    /// with a mixture of material and light PDFs for efficient rendering.
+    /// of both the material and the lights, combined by multiple importance
+    /// sampling with the power heuristic.
     ///
     /// # Parameters
     /// - `ctx`: Rendering context containing random number generator
@@ -243,70 +724,213 @@ impl Camera {
     /// - `depth`: Remaining recursion depth
     /// - `world`: The scene geometry to test for intersections
     /// - `lights`: Light sources for importance sampling
+    /// - `path`: The bounces before the ray
     ///
     /// # Returns
     /// The color seen along the ray direction.
-    #[allow(clippy::only_used_in_recursion)]
     fn ray_color(
         &self,
         ctx: &RenderContext,
         ray: Ray,
         depth: u32,
         world: &dyn Node,
-        lights: Option<Arc<dyn Node>>,
+        lights: Option<Arc<dyn Light>>,
+        path: PathKind,
     ) -> Color {
         // Recursion limit reached
         if depth == 0 {
             return Color::BLACK;
         }
 
-        // If the ray hits nothing, return the background color.
-        let Some(hit) = world.hit(ctx, &ray, Interval::new(0.001, f64::INFINITY)) else {
-            return self.background;
+        let hit = Self::first_hit(ctx, &ray, world);
+        self.hit_color(ctx, ray, hit, depth, world, lights, path)
+    }
+
+    /// Returns the color seen along `ray` like [`Camera::ray_color`], with the
+    /// nearest hit along the ray already found.
+    #[allow(clippy::too_many_arguments)]
+    fn hit_color(
+        &self,
+        ctx: &RenderContext,
+        ray: Ray,
+        hit: Option<HitRecord>,
+        depth: u32,
+        world: &dyn Node,
+        lights: Option<Arc<dyn Light>>,
+        path: PathKind,
+    ) -> Color {
+        // If the ray hits nothing, return the background.
+        let Some(hit) = hit else {
+            return self.background.value(&ray.direction.unit());
         };
 
-        let color_from_emission = hit.material.emitted(&ray, &hit, hit.u, hit.v, hit.pt);
+        // Light reaching a diffuse surface through specular bounces is read
+        // from the photon map instead, for the lights it covers
+        let color_from_emission = match &self.caustics {
+            Some(caustics) if path == PathKind::Caustic && caustics.covers(&hit.pt) => Color::BLACK,
+            _ => hit.material.emitted(&ray, &hit, hit.u, hit.v, hit.pt),
+        };
 
         match hit.material.scatter(ctx, &ray, &hit) {
             None => color_from_emission,
             Some(scatter_results) => match scatter_results.pdf_or_ray {
                 // Specular reflection (delta distribution)
                 PdfOrRay::Ray(ray) => {
-                    scatter_results.attenuation * self.ray_color(ctx, ray, depth - 1, world, lights)
+                    let path = match path {
+                        PathKind::Camera => PathKind::Camera,
+                        PathKind::Diffuse | PathKind::Caustic => PathKind::Caustic,
+                    };
+                    scatter_results.attenuation
+                        * self.ray_color(ctx, ray, depth - 1, world, lights, path)
                 }
                 // Diffuse/glossy reflection (use importance sampling)
                 PdfOrRay::Pdf(material_pdf) => {
-                    let pdf = match &lights {
+                    // Lights at a point or in a single direction are never
+                    // found by scattered rays, so they are sampled directly
+                    let color_from_emission = match &lights {
                         Some(lights) => {
-                            let light_pdf = Arc::new(HittablePdf::new(lights.clone(), hit.pt));
-                            Arc::new(MixturePdf::new(light_pdf, material_pdf))
+                            color_from_emission
+                                + self.delta_light_color(
+                                    ctx,
+                                    &ray,
+                                    &hit,
+                                    scatter_results.attenuation,
+                                    world,
+                                    lights.as_ref(),
+                                )
                         }
-                        None => material_pdf,
+                        None => color_from_emission,
                     };
-
-                    let scattered = Ray::new_with_time(hit.pt, pdf.generate(ctx), ray.time);
-                    let pdf_value = pdf.value(ctx, &scattered.direction);
+                    let color_from_emission = match &self.caustics {
+                        Some(caustics) => {
+                            color_from_emission
+                                + caustics.radiance(ctx, &ray, &hit, scatter_results.attenuation)
+                        }
+                        None => color_from_emission,
+                    };
+                    let lights_pdf =
+                        lights
+                            .as_ref()
+                            .filter(|lights| lights.has_area())
+                            .map(|lights| {
+                                Arc::new(LightPdf::new(lights.clone(), hit.pt))
+                                    as Arc<dyn ProbabilityDensityFunction>
+                            });
+                    // A bright background is sampled like another light
+                    let background_pdf = self.background.is_sampled().then(|| {
+                        Arc::new(BackgroundPdf::new(self.background.clone()))
+                            as Arc<dyn ProbabilityDensityFunction>
+                    });
+                    let light_pdf = match (lights_pdf, background_pdf) {
+                        (Some(lights_pdf), Some(background_pdf)) => {
+                            Some(Arc::new(MixturePdf::new(lights_pdf, background_pdf))
+                                as Arc<dyn ProbabilityDensityFunction>)
+                        }
+                        (lights_pdf, background_pdf) => lights_pdf.or(background_pdf),
+                    };
+                    // Sample the lights or the material with equal odds and weigh
+                    // the sample by the power heuristic, folded into the PDF the
+                    // scattered color is divided by
+                    let (direction, pdf_value) = match light_pdf {
+                        Some(light_pdf) => {
+                            let sample_light = ctx.random.rand() < 0.5;
+                            let direction = if sample_light {
+                                light_pdf.generate(ctx)
+                            } else {
+                                material_pdf.generate(ctx)
+                            };
+                            let light_value = light_pdf.value(ctx, &direction);
+                            let material_value = material_pdf.value(ctx, &direction);
+                            let (sampled_value, other_value) = if sample_light {
+                                (light_value, material_value)
+                            } else {
+                                (material_value, light_value)
+                            };
+                            let weight = power_heuristic(sampled_value, other_value);
+                            (direction, 0.5 * sampled_value / weight)
+                        }
+                        None => {
+                            let direction = material_pdf.generate(ctx);
+                            let pdf_value = material_pdf.value(ctx, &direction);
+                            (direction, pdf_value)
+                        }
+                    };
+                    let scattered = Ray::new_with_time(hit.pt, direction, ray.time);
 
                     // Guard against small or invalid PDF values which can cause over exposure
-                    if pdf_value < 0.05 {
+                    if pdf_value.is_nan() || pdf_value <= 0.0 || pdf_value < self.min_pdf {
                         return color_from_emission;
                     }
 
-                    let scattering_pdf = hit.material.scattering_pdf(ctx, &ray, &hit, &scattered);
+                    let scattering_color = hit.material.scattering_color(
+                        ctx,
+                        &ray,
+                        &hit,
+                        &scattered,
+                        scatter_results.attenuation,
+                    );
 
-                    let sample_color = self.ray_color(ctx, scattered, depth - 1, world, lights);
-                    let color_from_scatter =
-                        (scatter_results.attenuation * scattering_pdf * sample_color) / pdf_value;
+                    let sample_color =
+                        self.ray_color(ctx, scattered, depth - 1, world, lights, PathKind::Diffuse);
+                    let color_from_scatter = (scattering_color * sample_color) / pdf_value;
 
                     let color = color_from_emission + color_from_scatter;
 
                     // Clamp to prevent fireflies
-                    color.clamp(0.0, 10.0)
+                    match self.firefly_clamp {
+                        Some(max) => color.clamp(0.0, max),
+                        None => color,
+                    }
                 }
             },
         }
     }
 
+    /// Returns the light reflected along `ray` at `hit` from the lights
+    /// concentrated at a point or in a single direction which are not
+    /// shadowed.
+    fn delta_light_color(
+        &self,
+        ctx: &RenderContext,
+        ray: &Ray,
+        hit: &HitRecord,
+        attenuation: Color,
+        world: &dyn Node,
+        lights: &dyn Light,
+    ) -> Color {
+        let mut color = Color::BLACK;
+        lights.sample_delta(ctx, &hit.pt, &mut |sample| {
+            let shadow_ray = Ray::new_with_time(hit.pt, sample.direction, ray.time);
+            if Self::is_occluded(ctx, &shadow_ray, sample.distance, world) {
+                return;
+            }
+            let scattering_color =
+                hit.material
+                    .scattering_color(ctx, ray, hit, &shadow_ray, attenuation);
+            color += scattering_color * sample.irradiance;
+        });
+        color
+    }
+
+    /// Returns true when something other than a cutout is hit along the unit
+    /// direction of `ray` closer than `distance`.
+    pub(crate) fn is_occluded(
+        ctx: &RenderContext,
+        ray: &Ray,
+        distance: f64,
+        world: &dyn Node,
+    ) -> bool {
+        let max = distance - 0.001;
+        let mut ray_t = Interval::new(0.001, max);
+        while let Some(hit) = world.hit(ctx, ray, ray_t) {
+            if !hit.material.is_cutout(&hit) {
+                return true;
+            }
+            ray_t = Interval::new(hit.t + 0.001, max);
+        }
+        false
+    }
+
     /// Renders a single pixel at the given coordinates.
     ///
     /// This method performs stratified sampling over the pixel area, tracing
@@ -320,28 +944,316 @@ impl Camera {
     /// - `lights`: Light sources for importance sampling
     ///
     /// # Returns
-    /// The final gamma-corrected color for the pixel.
+    /// The final color for the pixel, sRGB encoded for an 8-bit image.
     pub fn render(
         &self,
         ctx: &RenderContext,
         x: u32,
         y: u32,
         world: &dyn Node,
-        lights: Option<Arc<dyn Node>>,
+        lights: Option<Arc<dyn Light>>,
+    ) -> Color {
+        self.render_linear(ctx, x, y, world, lights)
+            .encode(TransferFunction::Srgb)
+    }
+
+    /// Renders a single pixel like [`Camera::render`], but returns the averaged
+    /// linear color before gamma correction. The exposure is already applied.
+    ///
+    /// Every call takes a fresh set of samples, so the results of several calls
+    /// can be averaged to progressively refine a pixel. With a seeded context
+    /// every call takes the samples of the first pass, use
+    /// [`Camera::render_pass`] to refine a seeded render.
+    pub fn render_linear(
+        &self,
+        ctx: &RenderContext,
+        x: u32,
+        y: u32,
+        world: &dyn Node,
+        lights: Option<Arc<dyn Light>>,
+    ) -> Color {
+        self.render_pass(ctx, x, y, 0, world, lights)
+    }
+
+    /// Renders pass `pass` of a single pixel like [`Camera::render_linear`].
+    /// With a context from [`RenderContext::new_seeded`], the samples of each
+    /// pass are drawn from streams derived from the seed, the pixel and the
+    /// sample, so a pass renders the same colors on every run, whichever
+    /// thread renders it and in whatever order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use caustic_core::{
+    ///     CameraBuilder, Color, RenderContext, Vector3, material::Lambertian, object::Sphere,
+    /// };
+    ///
+    /// let mut camera_builder = CameraBuilder::new();
+    /// camera_builder.image_width = 10;
+    /// camera_builder.background = Arc::new(Color::new(0.5, 0.7, 1.0));
+    /// let camera = camera_builder.build();
+    /// let gray = Arc::new(Lambertian::new_from_color(Color::new(0.5, 0.5, 0.5)));
+    /// let world = Sphere::new(Vector3::new(0.0, 0.0, -3.0), 1.0, gray);
+    ///
+    /// let a = RenderContext::new_seeded(42);
+    /// let b = RenderContext::new_seeded(42);
+    /// // Pixels rendered in another order still match
+    /// let first = camera.render_pass(&a, 5, 5, 3, &world, None);
+    /// camera.render_pass(&b, 6, 5, 0, &world, None);
+    /// assert_eq!(first, camera.render_pass(&b, 5, 5, 3, &world, None));
+    /// ```
+    pub fn render_pass(
+        &self,
+        ctx: &RenderContext,
+        x: u32,
+        y: u32,
+        pass: u32,
+        world: &dyn Node,
+        lights: Option<Arc<dyn Light>>,
+    ) -> Color {
+        self.render_samples(ctx, x, y, pass, world, lights, None)
+    }
+
+    /// Renders pass `pass` of a single pixel like [`Camera::render_pass`] and
+    /// returns its color along with its [`Aovs`], taken from the first hits
+    /// of the rays traced for the color.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use caustic_core::{
+    ///     CameraBuilder, Color, RenderContext, Vector3, material::Lambertian, object::Sphere,
+    /// };
+    ///
+    /// let mut camera_builder = CameraBuilder::new();
+    /// camera_builder.image_width = 10;
+    /// let camera = camera_builder.build();
+    /// let gray = Arc::new(Lambertian::new_from_color(Color::new(0.5, 0.5, 0.5)));
+    /// let world = Sphere::new(Vector3::new(0.0, 0.0, -3.0), 1.0, gray);
+    ///
+    /// let ctx = RenderContext::new_seeded(42);
+    /// let (color, aovs) = camera.render_pass_aovs(&ctx, 5, 5, 0, &world, None);
+    /// // The same samples as the color alone
+    /// assert_eq!(color, camera.render_pass(&ctx, 5, 5, 0, &world, None));
+    /// assert_eq!(aovs.albedo, Color::new(0.5, 0.5, 0.5));
+    /// ```
+    pub fn render_pass_aovs(
+        &self,
+        ctx: &RenderContext,
+        x: u32,
+        y: u32,
+        pass: u32,
+        world: &dyn Node,
+        lights: Option<Arc<dyn Light>>,
+    ) -> (Color, Aovs) {
+        let mut samples = AovSamples::default();
+        let color = self.render_samples(ctx, x, y, pass, world, lights, Some(&mut samples));
+        (color, samples.average(self.pixel_samples_scale))
+    }
+
+    /// Renders pass `pass` of a single pixel, adding the first hits of its
+    /// rays to `aovs` when given.
+    #[allow(clippy::too_many_arguments)]
+    fn render_samples(
+        &self,
+        ctx: &RenderContext,
+        x: u32,
+        y: u32,
+        pass: u32,
+        world: &dyn Node,
+        lights: Option<Arc<dyn Light>>,
+        mut aovs: Option<&mut AovSamples>,
     ) -> Color {
+        if !self.is_rendered(x, y) {
+            return Color::BLACK;
+        }
+
         let mut pixel_color = Color::new(0.0, 0.0, 0.0);
 
         // Stratified sampling: divide pixel into sqrt_spp x sqrt_spp grid
+        let first_sample = pass as u64 * (self.sqrt_spp as u64 * self.sqrt_spp as u64);
+        for s_y in 0..self.sqrt_spp {
+            for s_x in 0..self.sqrt_spp {
+                let index = first_sample + (s_y * self.sqrt_spp + s_x) as u64;
+                let sample_ctx = ctx.for_sample(x, y, index);
+                let ctx = sample_ctx.as_ref().unwrap_or(ctx);
+                if self.chromatic_aberration == 0.0 {
+                    let r = self.get_ray(ctx, x, y, s_x, s_y, self.lens_distortion);
+                    let sample =
+                        self.camera_ray_color(ctx, r, world, lights.clone(), aovs.as_deref_mut());
+                    pixel_color += sample;
+                } else {
+                    // Each color channel is refracted differently, so trace a single
+                    // randomly chosen channel and weight it to keep the average
+                    let channel = ctx.random.rand_int_interval(0, 3);
+                    let distortion =
+                        self.lens_distortion + self.chromatic_aberration * (channel - 1) as f64;
+                    let r = self.get_ray(ctx, x, y, s_x, s_y, distortion);
+                    let sample =
+                        self.camera_ray_color(ctx, r, world, lights.clone(), aovs.as_deref_mut());
+                    pixel_color += match channel {
+                        0 => Color::new(3.0 * sample.r, 0.0, 0.0),
+                        1 => Color::new(0.0, 3.0 * sample.g, 0.0),
+                        _ => Color::new(0.0, 0.0, 3.0 * sample.b),
+                    };
+                }
+            }
+        }
+
+        self.exposure_scale * self.pixel_samples_scale * pixel_color.nan_to_zero()
+    }
+
+    /// Returns the color seen along a ray from the camera, adding its first
+    /// hit to `aovs` when given.
+    fn camera_ray_color(
+        &self,
+        ctx: &RenderContext,
+        ray: Ray,
+        world: &dyn Node,
+        lights: Option<Arc<dyn Light>>,
+        aovs: Option<&mut AovSamples>,
+    ) -> Color {
+        let Some(aovs) = aovs else {
+            return self.ray_color(ctx, ray, self.max_depth, world, lights, PathKind::Camera);
+        };
+
+        let hit = Self::first_hit(ctx, &ray, world);
+        aovs.add(ctx, &ray, hit.as_ref(), &*self.background);
+        if self.max_depth == 0 {
+            return Color::BLACK;
+        }
+        self.hit_color(
+            ctx,
+            ray,
+            hit,
+            self.max_depth,
+            world,
+            lights,
+            PathKind::Camera,
+        )
+    }
+
+    /// Returns the nearest hit along the ray, continuing past holes cut in
+    /// surfaces.
+    pub(crate) fn first_hit(ctx: &RenderContext, ray: &Ray, world: &dyn Node) -> Option<HitRecord> {
+        let mut ray_t = Interval::new(0.001, f64::INFINITY);
+        loop {
+            let hit = world.hit(ctx, ray, ray_t)?;
+            if !hit.material.is_cutout(&hit) {
+                return Some(hit);
+            }
+            ray_t = Interval::new(hit.t + 0.001, f64::INFINITY);
+        }
+    }
+
+    /// Renders a single pixel like [`Camera::render_linear`] and returns its
+    /// color along with its [`Aovs`], see [`Camera::render_pass_aovs`].
+    pub fn render_aovs(
+        &self,
+        ctx: &RenderContext,
+        x: u32,
+        y: u32,
+        world: &dyn Node,
+        lights: Option<Arc<dyn Light>>,
+    ) -> (Color, Aovs) {
+        self.render_pass_aovs(ctx, x, y, 0, world, lights)
+    }
+
+    /// Returns the [`Aovs`] of a single pixel, tracing only the rays from the
+    /// camera, for callers which render the color separately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use caustic_core::{
+    ///     CameraBuilder, Color, RenderContext, Vector3, material::Lambertian, object::Sphere,
+    ///     random_new,
+    /// };
+    ///
+    /// let mut camera_builder = CameraBuilder::new();
+    /// camera_builder.image_width = 100;
+    /// camera_builder.background = Arc::new(Color::new(0.5, 0.7, 1.0));
+    /// let camera = camera_builder.build();
+    /// let red = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.1, 0.1)));
+    /// let world = Sphere::new(Vector3::new(0.0, 0.0, -3.0), 1.0, red);
+    /// let ctx = RenderContext { random: random_new() };
+    ///
+    /// // The sphere facing the camera at the center of the image
+    /// let center = camera.aovs(&ctx, 50, 50, &world);
+    /// assert_eq!(center.albedo, Color::new(0.8, 0.1, 0.1));
+    /// assert!(center.normal.z > 0.9);
+    /// assert!((center.depth - 2.0).abs() < 0.1);
+    /// assert_ne!(center.object_id, 0);
+    ///
+    /// // The background in a corner
+    /// let corner = camera.aovs(&ctx, 0, 0, &world);
+    /// assert_eq!(corner.albedo, Color::new(0.5, 0.7, 1.0));
+    /// assert_eq!(corner.depth, f64::INFINITY);
+    /// assert_eq!(corner.object_id, 0);
+    /// ```
+    pub fn aovs(&self, ctx: &RenderContext, x: u32, y: u32, world: &dyn Node) -> Aovs {
+        if !self.is_rendered(x, y) {
+            return Aovs::default();
+        }
+
+        let mut samples = AovSamples::default();
+
         for s_y in 0..self.sqrt_spp {
             for s_x in 0..self.sqrt_spp {
-                let r = self.get_ray(ctx, x, y, s_x, s_y);
-                let sample = self.ray_color(ctx, r, self.max_depth, world, lights.clone());
-                pixel_color += sample;
+                // The rays of the first pass when seeded
+                let sample_ctx = ctx.for_sample(x, y, (s_y * self.sqrt_spp + s_x) as u64);
+                let ctx = sample_ctx.as_ref().unwrap_or(ctx);
+                let ray = self.get_ray(ctx, x, y, s_x, s_y, self.lens_distortion);
+                let hit = Self::first_hit(ctx, &ray, world);
+                samples.add(ctx, &ray, hit.as_ref(), &*self.background);
             }
         }
+        samples.average(self.pixel_samples_scale)
+    }
+
+    /// Estimates the relative cost of rendering a pixel from a single ray
+    /// through its center, followed to its first hit only. Misses cost 1,
+    /// every hole passed through adds 1 and hits add more the deeper the paths
+    /// leaving them usually go: surfaces which absorb or only emit add 1,
+    /// diffuse ones 2 and specular ones, such as glass and metal, 3.
+    ///
+    /// Pixels outside of the render region cost 0.
+    pub fn estimate_pixel_cost(
+        &self,
+        ctx: &RenderContext,
+        x: u32,
+        y: u32,
+        world: &dyn Node,
+    ) -> f64 {
+        if !self.is_rendered(x, y) {
+            return 0.0;
+        }
+
+        let center = self.sqrt_spp / 2;
+        let ray = self.get_ray(ctx, x, y, center, center, self.lens_distortion);
+        let mut cost = 1.0;
+        let mut ray_t = Interval::new(0.001, f64::INFINITY);
+        let hit = loop {
+            let Some(hit) = world.hit(ctx, &ray, ray_t) else {
+                return cost;
+            };
+            if !hit.material.is_cutout(&hit) {
+                break hit;
+            }
+            cost += 1.0;
+            ray_t = Interval::new(hit.t + 0.001, f64::INFINITY);
+        };
 
-        let pixel_color = self.pixel_samples_scale * pixel_color.nan_to_zero();
-        pixel_color.linear_to_gamma()
+        cost + match hit.material.scatter(ctx, &ray, &hit) {
+            None => 1.0,
+            Some(scatter_result) => match scatter_result.pdf_or_ray {
+                PdfOrRay::Pdf(_) => 2.0,
+                PdfOrRay::Ray(_) => 3.0,
+            },
+        }
     }
 
     /// Constructs a camera ray originating from the defocus disk and directed at a randomly
@@ -353,19 +1265,30 @@ impl Camera {
     /// - `y`: Pixel y-coordinate
     /// - `s_x`: Stratification grid x-index
     /// - `s_y`: Stratification grid y-index
+    /// - `distortion`: Radial lens distortion coefficient for the traced color channel
     ///
     /// # Returns
     /// A ray from the camera through the specified pixel sample.
-    fn get_ray(&self, ctx: &RenderContext, x: u32, y: u32, s_x: u32, s_y: u32) -> Ray {
+    fn get_ray(
+        &self,
+        ctx: &RenderContext,
+        x: u32,
+        y: u32,
+        s_x: u32,
+        s_y: u32,
+        distortion: f64,
+    ) -> Ray {
         let offset = self.sample_square_stratified(&*ctx.random, s_x, s_y);
         let pixel_sample = self.pixel00_loc
             + ((x as f64 + offset.x) * self.pixel_delta_u)
             + ((y as f64 + offset.y) * self.pixel_delta_v);
+        let pixel_sample = self.apply_lens_distortion(pixel_sample, distortion);
 
+        let lens_center = self.lens_center(pixel_sample);
         let ray_origin = if self.defocus_angle <= 0.0 {
-            self.center
+            lens_center
         } else {
-            self.defocus_disk_sample(&*ctx.random)
+            self.defocus_disk_sample(&*ctx.random, lens_center)
         };
         let ray_direction = pixel_sample - ray_origin;
         let ray_time = ctx.random.rand();
@@ -373,6 +1296,54 @@ impl Camera {
         Ray::new_with_time(ray_origin, ray_direction, ray_time)
     }
 
+    /// Constructs a deterministic ray through a point on the image plane, ignoring
+    /// defocus blur and sub-pixel jitter. Used for picking objects in the scene.
+    ///
+    /// # Parameters
+    /// - `s`: Normalized horizontal screen coordinate (0.0 = left edge, 1.0 = right edge)
+    /// - `t`: Normalized vertical screen coordinate (0.0 = top edge, 1.0 = bottom edge)
+    ///
+    /// # Returns
+    /// A ray from the camera center through the given screen location.
+    pub fn get_pick_ray(&self, s: f64, t: f64) -> Ray {
+        let viewport_upper_left =
+            self.pixel00_loc - 0.5 * (self.pixel_delta_u + self.pixel_delta_v);
+        let target = viewport_upper_left
+            + (s * self.image_width as f64) * self.pixel_delta_u
+            + (t * self.image_height as f64) * self.pixel_delta_v;
+        let target = self.apply_lens_distortion(target, self.lens_distortion);
+        let origin = self.lens_center(target);
+
+        Ray::new(origin, target - origin)
+    }
+
+    /// Returns the point rays through a point on the viewport start from
+    /// without defocus blur, the camera center unless the camera is
+    /// orthographic.
+    fn lens_center(&self, viewport_pt: Vector3) -> Vector3 {
+        match self.orthographic_offset {
+            Some(offset) => viewport_pt + offset,
+            None => self.center,
+        }
+    }
+
+    /// Moves a point on the viewport radially to simulate lens distortion, using
+    /// the first term of the Brown–Conrady model. The radius is normalized so that
+    /// the top and bottom edges of the image are at 1.
+    ///
+    /// # Parameters
+    /// - `pt`: Point on the viewport in world space
+    /// - `distortion`: Radial distortion coefficient, 0 leaves the point unchanged
+    fn apply_lens_distortion(&self, pt: Vector3, distortion: f64) -> Vector3 {
+        if distortion == 0.0 {
+            return pt;
+        }
+        let offset = pt - self.viewport_center;
+        let r_squared =
+            offset.length_squared() / (self.viewport_half_height * self.viewport_half_height);
+        self.viewport_center + (1.0 + distortion * r_squared) * offset
+    }
+
     /// Returns the vector to a random point in the square sub-pixel specified by grid
     /// indices s_x and s_y, for an idealized unit square pixel [-.5,-.5] to [+.5,+.5].
     ///
@@ -403,6 +1374,236 @@ impl Camera {
         self.image_height
     }
 
+    /// Returns the number of samples taken per pixel. Stratified sampling uses a
+    /// square grid, so this is `samples_per_pixel` rounded down to a square number.
+    pub fn samples_per_pixel(&self) -> u32 {
+        self.sqrt_spp * self.sqrt_spp
+    }
+
+    /// Returns the part of the image rendered, `None` for all of it.
+    pub fn render_region(&self) -> Option<RenderRegion> {
+        self.render_region
+    }
+
+    /// Returns a copy of the camera rendering only the given part of the image.
+    pub fn with_render_region(&self, render_region: Option<RenderRegion>) -> Self {
+        Self {
+            render_region,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the factor colors are multiplied by, from the exposure.
+    pub fn exposure_scale(&self) -> f64 {
+        self.exposure_scale
+    }
+
+    /// Returns the highest value of each color channel of a path, `None` when
+    /// paths are not clamped.
+    pub fn firefly_clamp(&self) -> Option<f64> {
+        self.firefly_clamp
+    }
+
+    /// Returns the smallest PDF value a scattered ray may have.
+    pub fn min_pdf(&self) -> f64 {
+        self.min_pdf
+    }
+
+    /// Returns a copy of the camera with a different exposure, for example to
+    /// override the exposure of a scene from the command line.
+    pub fn with_exposure(&self, exposure: Exposure) -> Self {
+        Self {
+            exposure_scale: exposure.scale(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the camera taking the same shot at another resolution,
+    /// keeping the aspect ratio as closely as whole pixels allow. Any render
+    /// region and overscan are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::CameraBuilder;
+    ///
+    /// let mut camera_builder = CameraBuilder::new();
+    /// camera_builder.image_width = 600;
+    /// camera_builder.aspect_ratio = 1.5;
+    /// let camera = camera_builder.build();
+    /// let small = camera.with_image_width(150);
+    /// assert_eq!((small.image_width(), small.image_height()), (150, 100));
+    ///
+    /// // The corners of the shot do not move
+    /// let corner = camera.get_pick_ray(1.0, 1.0).direction.unit();
+    /// let small_corner = small.get_pick_ray(1.0, 1.0).direction.unit();
+    /// assert!((corner - small_corner).length() < 1e-9);
+    /// ```
+    pub fn with_image_width(&self, image_width: u32) -> Self {
+        let frame = self.with_overscan(0.0);
+        let image_width = image_width.max(1);
+        let image_height = ((frame.image_height as f64 * image_width as f64
+            / frame.image_width as f64)
+            .round() as u32)
+            .max(1);
+
+        let viewport_u = frame.pixel_delta_u * frame.image_width as f64;
+        let viewport_v = frame.pixel_delta_v * frame.image_height as f64;
+        let viewport_upper_left =
+            frame.pixel00_loc - 0.5 * (frame.pixel_delta_u + frame.pixel_delta_v);
+        let pixel_delta_u = viewport_u / image_width as f64;
+        let pixel_delta_v = viewport_v / image_height as f64;
+        Self {
+            image_width,
+            image_height,
+            pixel00_loc: viewport_upper_left + 0.5 * (pixel_delta_u + pixel_delta_v),
+            pixel_delta_u,
+            pixel_delta_v,
+            render_region: None,
+            ..frame
+        }
+    }
+
+    /// Returns a copy of the camera tracing another number of samples per
+    /// pixel, rounded down to a square number like [`CameraBuilder`] does.
+    pub fn with_samples_per_pixel(&self, samples_per_pixel: u32) -> Self {
+        let sqrt_spp = ((samples_per_pixel as f64).sqrt() as u32).max(1);
+        Self {
+            sqrt_spp,
+            reciprocal_sqrt_spp: 1.0 / sqrt_spp as f64,
+            pixel_samples_scale: 1.0 / (sqrt_spp * sqrt_spp) as f64,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the camera following paths for at most `max_depth`
+    /// bounces.
+    pub fn with_max_depth(&self, max_depth: u32) -> Self {
+        Self {
+            max_depth,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the most bounces a path is followed for.
+    pub fn max_depth(&self) -> u32 {
+        self.max_depth
+    }
+
+    /// Returns a copy of the camera reading caustics from `caustics`, traced
+    /// for the scene it renders, rather than path tracing them.
+    pub fn with_caustics(&self, caustics: Option<Arc<PhotonMap>>) -> Self {
+        Self {
+            caustics,
+            ..self.clone()
+        }
+    }
+
+    pub fn caustics(&self) -> Option<&Arc<PhotonMap>> {
+        self.caustics.as_ref()
+    }
+
+    /// Returns a copy of the camera rendering only the part of the shot in the
+    /// window, all of it when `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{CameraBuilder, CropWindow, RenderRegion};
+    ///
+    /// let mut camera_builder = CameraBuilder::new();
+    /// camera_builder.image_width = 200;
+    /// let camera = camera_builder
+    ///     .build()
+    ///     .with_crop_window(Some(CropWindow::new(0.5, 1.0, 0.0, 0.5)));
+    /// assert_eq!(camera.render_region(), Some(RenderRegion::new(100, 0, 100, 100)));
+    /// assert!(camera.is_rendered(150, 50));
+    /// assert!(!camera.is_rendered(50, 50));
+    /// ```
+    pub fn with_crop_window(&self, crop_window: Option<CropWindow>) -> Self {
+        let frame = self.frame();
+        self.with_render_region(
+            crop_window.map(|crop_window| crop_window.to_render_region(frame.width, frame.height)),
+        )
+    }
+
+    /// Returns whether the pixel at (x, y) is inside the render region, pixels
+    /// outside of it are rendered black.
+    pub fn is_rendered(&self, x: u32, y: u32) -> bool {
+        let Some(region) = self.render_region else {
+            return true;
+        };
+        // The region is relative to the frame, the overscan is outside of it
+        match (x.checked_sub(self.frame_x), y.checked_sub(self.frame_y)) {
+            (Some(x), Some(y)) => region.contains(x, y),
+            _ => region.invert,
+        }
+    }
+
+    /// Returns a copy of the camera which also renders a margin around the
+    /// image, `overscan` times its width and height on each side, to show what
+    /// lies just outside of the shot when composing it. The original image is
+    /// then [`Camera::frame`] and the pixels keep their size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{CameraBuilder, RenderRegion};
+    ///
+    /// let mut camera_builder = CameraBuilder::new();
+    /// camera_builder.image_width = 200;
+    /// camera_builder.aspect_ratio = 2.0;
+    /// let camera = camera_builder.build().with_overscan(0.1);
+    /// assert_eq!((camera.image_width(), camera.image_height()), (240, 120));
+    /// assert_eq!(camera.frame(), RenderRegion::new(20, 10, 200, 100));
+    ///
+    /// // Overscan replaces any previous overscan
+    /// assert_eq!(camera.with_overscan(0.0).image_width(), 200);
+    /// ```
+    pub fn with_overscan(&self, overscan: f64) -> Self {
+        let frame = self.frame();
+        let overscan = overscan.max(0.0);
+        let frame_x = (frame.width as f64 * overscan).round() as u32;
+        let frame_y = (frame.height as f64 * overscan).round() as u32;
+
+        let frame_pixel00_loc = self.pixel00_loc
+            + self.frame_x as f64 * self.pixel_delta_u
+            + self.frame_y as f64 * self.pixel_delta_v;
+        Self {
+            image_width: frame.width + 2 * frame_x,
+            image_height: frame.height + 2 * frame_y,
+            pixel00_loc: frame_pixel00_loc
+                - frame_x as f64 * self.pixel_delta_u
+                - frame_y as f64 * self.pixel_delta_v,
+            frame_x,
+            frame_y,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the part of the rendered image which is the shot, all of it
+    /// unless the camera has [overscan](Camera::with_overscan).
+    pub fn frame(&self) -> RenderRegion {
+        RenderRegion::new(
+            self.frame_x,
+            self.frame_y,
+            self.image_width - 2 * self.frame_x,
+            self.image_height - 2 * self.frame_y,
+        )
+    }
+
+    /// Returns the settings the camera was built from, without later changes
+    /// such as [`Camera::with_overscan`], so a changed copy of them can be
+    /// built into a new camera.
+    pub fn builder(&self) -> &CameraBuilder {
+        &self.builder
+    }
+
+    /// Returns the background seen by rays that miss all objects.
+    pub fn background(&self) -> &Arc<dyn Background> {
+        &self.background
+    }
+
     /// Returns a random point in the camera defocus disk.
     ///
     /// This is used to create depth of field effects by varying the ray origin
@@ -410,11 +1611,193 @@ impl Camera {
     ///
     /// # Parameters
     /// - `random`: Random number generator
+    /// - `center`: Center of the disk, see [`Camera::lens_center`]
     ///
     /// # Returns
     /// A random point on the defocus disk in world space.
-    fn defocus_disk_sample(&self, random: &dyn Random) -> Vector3 {
+    fn defocus_disk_sample(&self, random: &dyn Random, center: Vector3) -> Vector3 {
         let pt = Vector3::random_in_unit_disk(random);
-        self.center + (pt.x * self.defocus_disk_u) + (pt.y * self.defocus_disk_v)
+        center + (pt.x * self.defocus_disk_u) + (pt.y * self.defocus_disk_v)
+    }
+}
+
+/// Sums of the [`Aovs`] of the samples of a pixel.
+struct AovSamples {
+    albedo: Color,
+    normal: Vector3,
+    depth: f64,
+    hits: u32,
+    /// Id of the object seen by the first sample, once there is one
+    object_id: Option<u32>,
+}
+
+impl Default for AovSamples {
+    fn default() -> Self {
+        Self {
+            albedo: Color::BLACK,
+            normal: Vector3::ZERO,
+            depth: 0.0,
+            hits: 0,
+            object_id: None,
+        }
+    }
+}
+
+impl AovSamples {
+    /// Adds a sample whose ray from the camera first hits `hit`.
+    fn add(
+        &mut self,
+        ctx: &RenderContext,
+        ray: &Ray,
+        hit: Option<&HitRecord>,
+        background: &dyn Background,
+    ) {
+        match hit {
+            Some(hit) => {
+                self.albedo += hit.material.albedo(ctx, ray, hit);
+                self.normal = self.normal + hit.normal;
+                self.depth += hit.t * ray.direction.length();
+                self.hits += 1;
+                // a world of a single object holds no node giving it an id
+                self.object_id.get_or_insert(hit.object_id.max(1));
+            }
+            None => {
+                self.albedo += background.value(&ray.direction.unit());
+                self.object_id.get_or_insert(0);
+            }
+        }
+    }
+
+    /// Returns the AOVs averaged over the samples, `scale` being one over
+    /// their number.
+    fn average(self, scale: f64) -> Aovs {
+        Aovs {
+            albedo: scale * self.albedo.nan_to_zero(),
+            normal: scale * self.normal,
+            depth: if self.hits > 0 {
+                self.depth / self.hits as f64
+            } else {
+                f64::INFINITY
+            },
+            object_id: self.object_id.unwrap_or(0),
+        }
+    }
+}
+
+// Renders only match the expected hash with the portable math functions
+#[cfg(all(test, not(feature = "fastmath")))]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{
+        CameraBuilder, Color, Light, Node, RenderContext, Vector3,
+        background::PreethamSky,
+        light::GeometryLight,
+        material::{Dielectric, DiffuseLight, Lambertian, Metal},
+        object::{BoundingVolumeHierarchy, Quad, Sphere},
+        random::SeededRandom,
+        texture::PerlinTurbulenceTexture,
+    };
+
+    /// Hashes the bits of every pixel of a small scene exercising noise,
+    /// refraction, reflection, area lights and the sky, rendered with a seed,
+    /// the last pixel first when `reversed`.
+    fn seeded_render_hash(reversed: bool) -> u64 {
+        let random = Arc::new(SeededRandom::new(7));
+        let ctx = RenderContext {
+            random: random.clone(),
+        };
+
+        let marble = Arc::new(Lambertian::new(Arc::new(PerlinTurbulenceTexture::new(
+            &*random, 4.0, 7,
+        ))));
+        let light = Arc::new(DiffuseLight::new_from_color(Color::new(4.0, 4.0, 4.0)));
+        let quad: Arc<dyn Node> = Arc::new(Quad::new(
+            Vector3::new(-1.0, 3.0, -3.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 2.0),
+            light,
+        ));
+        let world: Vec<Arc<dyn Node>> = vec![
+            Arc::new(Sphere::new(Vector3::new(0.0, -100.5, -1.0), 100.0, marble)),
+            Arc::new(Sphere::new(
+                Vector3::new(-1.0, 0.0, -2.0),
+                0.5,
+                Arc::new(Dielectric::new(1.5)),
+            )),
+            Arc::new(Sphere::new(
+                Vector3::new(1.0, 0.0, -2.0),
+                0.5,
+                Arc::new(Metal::new(Color::new(0.8, 0.6, 0.2), 0.3)),
+            )),
+            quad.clone(),
+        ];
+        let world = BoundingVolumeHierarchy::new(&world);
+
+        let mut camera_builder = CameraBuilder::new();
+        camera_builder.image_width = 12;
+        camera_builder.samples_per_pixel = 4;
+        camera_builder.max_depth = 8;
+        camera_builder.defocus_angle = 1.0;
+        camera_builder.focus_distance = 2.0;
+        camera_builder.background = Arc::new(PreethamSky::new(Vector3::new(1.0, 1.0, 0.5), 3.0));
+        let camera = camera_builder.build();
+        let lights: Arc<dyn Light> = Arc::new(GeometryLight::new(quad));
+
+        let width = camera.image_width();
+        let pixel_count = width * camera.image_height();
+        let mut colors = vec![Color::BLACK; pixel_count as usize];
+        for i in 0..pixel_count {
+            let i = if reversed { pixel_count - 1 - i } else { i };
+            colors[i as usize] =
+                camera.render_pass(&ctx, i % width, i / width, 1, &world, Some(lights.clone()));
+        }
+
+        // FNV-1a
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        for color in colors {
+            for value in [color.r, color.g, color.b] {
+                for byte in value.to_bits().to_le_bytes() {
+                    hash = (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3);
+                }
+            }
+        }
+        hash
+    }
+
+    /// Guards that seeded renders are bit identical on every platform, such as
+    /// x86_64, aarch64 and wasm32, so previews match final renders. A change
+    /// in this hash from a change to the renderer is expected, update it; a
+    /// difference between platforms is a bug. Run it in wasm32 with
+    /// `wasm-pack test --node crates/core -- --lib seeded_render`.
+    #[cfg_attr(not(target_arch = "wasm32"), test)]
+    #[cfg_attr(target_arch = "wasm32", wasm_bindgen_test::wasm_bindgen_test)]
+    fn seeded_render_is_reproducible() {
+        assert_eq!(seeded_render_hash(false), seeded_render_hash(true));
+        assert_eq!(seeded_render_hash(false), 0x04b6_fa6d_d871_e084);
+    }
+
+    #[test]
+    fn object_ids_tell_apart_objects_sharing_a_material() {
+        let gray = Arc::new(Lambertian::new_from_color(Color::new(0.5, 0.5, 0.5)));
+        let spheres: Vec<Arc<dyn Node>> = [-1.2, 1.2]
+            .into_iter()
+            .map(|x| {
+                Arc::new(Sphere::new(Vector3::new(x, 0.0, -4.0), 1.0, gray.clone()))
+                    as Arc<dyn Node>
+            })
+            .collect();
+        let world = BoundingVolumeHierarchy::new(&spheres);
+        let mut camera_builder = CameraBuilder::new();
+        camera_builder.image_width = 40;
+        let camera = camera_builder.build();
+        let ctx = RenderContext::new_seeded(3);
+
+        let (_, left) = camera.render_aovs(&ctx, 12, 20, &world, None);
+        let (_, right) = camera.render_aovs(&ctx, 28, 20, &world, None);
+        assert_ne!(left.object_id, 0);
+        assert_ne!(right.object_id, 0);
+        assert_ne!(left.object_id, right.object_id);
+        assert_eq!(left, camera.aovs(&ctx, 12, 20, &world));
     }
 }
@@ -1,4 +1,5 @@
 use crate::Random;
+use crate::utils::math;
 use std::ops::{Add, AddAssign, Div, Mul};
 
 /// Represents an RGB color with floating-point components in the range [0.0, 1.0].
@@ -110,8 +111,8 @@ impl Color {
     /// Converts linear color space to gamma-corrected color space and clamps to [0.0, 0.999].
     ///
     /// This applies gamma correction using a square root transformation (gamma = 2.0),
-    /// which is commonly used to convert linear light intensity values to values suitable
-    /// for display on standard monitors.
+    /// an approximation of sRGB kept to reproduce earlier renders. New outputs should
+    /// use [`Color::encode`].
     ///
     /// Components are clamped to [0.0, 0.999] to ensure they map to valid pixel values
     /// (typically 0-255 for 8-bit color).
@@ -126,10 +127,50 @@ impl Color {
     /// // gamma.r ≈ 0.5, gamma.g ≈ 0.707, gamma.b ≈ 0.999
     /// ```
     pub fn linear_to_gamma(&self) -> Self {
+        self.encode(TransferFunction::Gamma2)
+    }
+
+    /// Encodes a linear color for an 8-bit output with the given transfer
+    /// function and clamps it to [0.0, 0.999].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{Color, TransferFunction};
+    ///
+    /// let linear = Color::new(0.0, 0.214, 2.0);
+    /// let srgb = linear.encode(TransferFunction::Srgb);
+    /// assert_eq!(srgb.r, 0.0);
+    /// assert!((srgb.g - 0.5).abs() < 0.001);
+    /// assert_eq!(srgb.b, 0.999);
+    /// ```
+    pub fn encode(&self, transfer: TransferFunction) -> Self {
         Self {
-            r: linear_to_gamma(self.r).clamp(0.0, 0.999),
-            g: linear_to_gamma(self.g).clamp(0.0, 0.999),
-            b: linear_to_gamma(self.b).clamp(0.0, 0.999),
+            r: transfer.encode(self.r).clamp(0.0, 0.999),
+            g: transfer.encode(self.g).clamp(0.0, 0.999),
+            b: transfer.encode(self.b).clamp(0.0, 0.999),
+        }
+    }
+
+    /// Decodes an sRGB encoded color to linear values using the exact sRGB
+    /// transfer function.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::Color;
+    ///
+    /// let srgb = Color::new(0.0, 0.5, 1.0);
+    /// let linear = srgb.srgb_to_linear();
+    /// assert_eq!(linear.r, 0.0);
+    /// assert!((linear.g - 0.214).abs() < 0.001);
+    /// assert_eq!(linear.b, 1.0);
+    /// ```
+    pub fn srgb_to_linear(&self) -> Self {
+        Self {
+            r: srgb_to_linear(self.r),
+            g: srgb_to_linear(self.g),
+            b: srgb_to_linear(self.b),
         }
     }
 
@@ -159,6 +200,20 @@ impl Color {
         }
     }
 
+    /// Returns the relative luminance using the Rec. 709 weights.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::Color;
+    ///
+    /// assert_eq!(Color::new(1.0, 1.0, 1.0).luminance(), 1.0);
+    /// assert_eq!(Color::new(0.0, 0.0, 0.0).luminance(), 0.0);
+    /// ```
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
     pub fn clamp(&self, min: f64, max: f64) -> Color {
         Color::new(
             self.r.clamp(min, max),
@@ -166,14 +221,148 @@ impl Color {
             self.b.clamp(min, max),
         )
     }
+
+    /// Converts CIE XYZ tristimulus values to linear sRGB. Colors outside the
+    /// sRGB gamut have their negative components set to 0.
+    pub fn from_xyz(x: f64, y: f64, z: f64) -> Self {
+        let r = 3.2406 * x - 1.5372 * y - 0.4986 * z;
+        let g = -0.9689 * x + 1.8758 * y + 0.0415 * z;
+        let b = 0.0557 * x - 0.2040 * y + 1.0570 * z;
+        Color::new(r.max(0.0), g.max(0.0), b.max(0.0))
+    }
+
+    /// Returns the color of the light emitted by a black body at the given
+    /// temperature in Kelvin, in linear sRGB with a luminance of 1.
+    ///
+    /// Candles are around 1900K, tungsten bulbs 2700K to 3200K, daylight
+    /// 5500K to 6500K and an overcast sky 7000K and above.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::Color;
+    ///
+    /// let tungsten = Color::from_temperature(3200.0);
+    /// assert!(tungsten.r > tungsten.g && tungsten.g > tungsten.b);
+    /// assert!((tungsten.luminance() - 1.0).abs() < 1e-9);
+    ///
+    /// let overcast = Color::from_temperature(10000.0);
+    /// assert!(overcast.b > overcast.r);
+    /// ```
+    pub fn from_temperature(kelvin: f64) -> Self {
+        let kelvin = kelvin.clamp(500.0, 40000.0);
+
+        // Integrate Planck's law against the CIE 1931 color matching functions
+        let (mut x, mut y, mut z) = (0.0, 0.0, 0.0);
+        for step in 0..=80 {
+            let wavelength = 380.0 + 5.0 * step as f64;
+            let radiance = planck(wavelength, kelvin);
+            let [cx, cy, cz] = cie_1931(wavelength);
+            x += radiance * cx;
+            y += radiance * cy;
+            z += radiance * cz;
+        }
+
+        let color = Color::from_xyz(x, y, z);
+        color / color.luminance()
+    }
 }
 
-/// Converts a linear color component to gamma-corrected space.
-///
-/// Uses square root (gamma = 2.0) for the transformation. Negative values
-/// are clamped to 0.0.
-fn linear_to_gamma(v: f64) -> f64 {
-    if v > 0.0 { v.sqrt() } else { 0.0 }
+/// Spectral radiance of a black body, up to a constant factor, for a wavelength
+/// in nanometers.
+fn planck(wavelength: f64, kelvin: f64) -> f64 {
+    // Second radiation constant h·c/k in nm·K
+    const C2: f64 = 1.438_776_877e7;
+    let wavelength_um = wavelength * 1e-3;
+    1.0 / (math::powi(wavelength_um, 5) * (math::exp(C2 / (wavelength * kelvin)) - 1.0))
+}
+
+/// CIE 1931 2° color matching functions, using the multi-lobe Gaussian fit from
+/// Wyman, Sloan and Shirley, for a wavelength in nanometers.
+fn cie_1931(wavelength: f64) -> [f64; 3] {
+    fn gaussian(wavelength: f64, mean: f64, sigma_low: f64, sigma_high: f64) -> f64 {
+        let sigma = if wavelength < mean {
+            sigma_low
+        } else {
+            sigma_high
+        };
+        let t = (wavelength - mean) / sigma;
+        math::exp(-0.5 * t * t)
+    }
+
+    let x = 1.056 * gaussian(wavelength, 599.8, 37.9, 31.0)
+        + 0.362 * gaussian(wavelength, 442.0, 16.0, 26.7)
+        - 0.065 * gaussian(wavelength, 501.1, 20.4, 26.2);
+    let y = 0.821 * gaussian(wavelength, 568.8, 46.9, 40.5)
+        + 0.286 * gaussian(wavelength, 530.9, 16.3, 31.1);
+    let z = 1.217 * gaussian(wavelength, 437.0, 11.8, 36.0)
+        + 0.681 * gaussian(wavelength, 459.0, 26.0, 13.8);
+    [x, y, z]
+}
+
+/// Converts an sRGB encoded color component to linear space.
+fn srgb_to_linear(v: f64) -> f64 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        math::powf((v + 0.055) / 1.055, 2.4)
+    }
+}
+
+/// How linear light values are encoded for an output image. Renders are
+/// accumulated in linear values and only encoded when written out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TransferFunction {
+    /// The sRGB curve, expected by PNG and JPEG files and browsers
+    #[default]
+    Srgb,
+    /// A pure power curve with gamma 2.2, close to sRGB except in the shadows
+    Gamma22,
+    /// A pure power curve with gamma 2, the square root used by earlier renders
+    Gamma2,
+    /// The Rec. 709 curve of HD video
+    Rec709,
+    /// No encoding, for floating point outputs such as HDR and EXR files
+    Linear,
+}
+
+impl TransferFunction {
+    /// Encodes a linear component, negative values become 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::TransferFunction;
+    ///
+    /// assert_eq!(TransferFunction::Gamma2.encode(0.25), 0.5);
+    /// assert_eq!(TransferFunction::Linear.encode(0.25), 0.25);
+    /// assert_eq!(TransferFunction::Srgb.encode(-1.0), 0.0);
+    /// assert!((TransferFunction::Rec709.encode(0.01) - 0.045).abs() < 1e-9);
+    /// ```
+    pub fn encode(&self, v: f64) -> f64 {
+        if v <= 0.0 {
+            return 0.0;
+        }
+        match self {
+            TransferFunction::Srgb => {
+                if v <= 0.0031308 {
+                    12.92 * v
+                } else {
+                    1.055 * math::powf(v, 1.0 / 2.4) - 0.055
+                }
+            }
+            TransferFunction::Gamma22 => math::powf(v, 1.0 / 2.2),
+            TransferFunction::Gamma2 => v.sqrt(),
+            TransferFunction::Rec709 => {
+                if v < 0.018 {
+                    4.5 * v
+                } else {
+                    1.099 * math::powf(v, 0.45) - 0.099
+                }
+            }
+            TransferFunction::Linear => v,
+        }
+    }
 }
 
 // Operator Implementations
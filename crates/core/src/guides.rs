@@ -0,0 +1,159 @@
+use crate::{Camera, Color};
+
+/// Which guide a [`GuideLine`] belongs to, so previews can draw them differently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuideKind {
+    /// Border of the shot inside an image rendered with overscan
+    Frame,
+    /// Lines splitting the shot in thirds
+    Thirds,
+    /// Border inside which the important action should stay
+    ActionSafe,
+    /// Border inside which text should stay
+    TitleSafe,
+}
+
+impl GuideKind {
+    /// Returns the color previews draw the guide with.
+    pub fn color(&self) -> Color {
+        match self {
+            GuideKind::Frame => Color::new(1.0, 1.0, 1.0),
+            GuideKind::Thirds => Color::new(0.9, 0.9, 0.9),
+            GuideKind::ActionSafe => Color::new(0.2, 0.8, 1.0),
+            GuideKind::TitleSafe => Color::new(1.0, 0.8, 0.2),
+        }
+    }
+}
+
+/// A horizontal or vertical guide line, in pixels from the top left corner of
+/// the rendered image. The line covers the pixels from (x0, y0) to (x1, y1),
+/// both included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GuideLine {
+    pub x0: u32,
+    pub y0: u32,
+    pub x1: u32,
+    pub y1: u32,
+    pub kind: GuideKind,
+}
+
+/// Composition guides drawn over previews, on top of the rendered pixels
+/// rather than into them, so they never end up in accumulated renders.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{CameraBuilder, GuideKind, Guides};
+///
+/// let mut camera_builder = CameraBuilder::new();
+/// camera_builder.image_width = 300;
+/// camera_builder.aspect_ratio = 1.5;
+/// let camera = camera_builder.build();
+///
+/// let guides = Guides {
+///     rule_of_thirds: true,
+///     safe_areas: false,
+/// };
+/// let lines = guides.lines(&camera);
+/// assert_eq!(lines.len(), 4);
+/// assert!(lines.iter().all(|line| line.kind == GuideKind::Thirds));
+/// assert_eq!((lines[0].x0, lines[0].x1), (100, 100));
+///
+/// // With overscan the guides are relative to the shot, which gets a border
+/// let lines = guides.lines(&camera.with_overscan(0.1));
+/// assert_eq!(lines.len(), 8);
+/// assert_eq!((lines[4].x0, lines[4].x1), (130, 130));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Guides {
+    /// Splits the shot in thirds horizontally and vertically
+    pub rule_of_thirds: bool,
+    /// Outlines the action safe and title safe areas
+    pub safe_areas: bool,
+}
+
+impl Guides {
+    /// Part of the shot which is action safe, per EBU R 95
+    pub const ACTION_SAFE: f64 = 0.93;
+    /// Part of the shot which is title safe, per EBU R 95
+    pub const TITLE_SAFE: f64 = 0.9;
+
+    /// Returns the lines to draw over an image rendered by `camera`. The frame
+    /// is outlined whenever the camera has overscan.
+    pub fn lines(&self, camera: &Camera) -> Vec<GuideLine> {
+        let frame = camera.frame();
+        let (x, y) = (frame.x as f64, frame.y as f64);
+        let (width, height) = (frame.width as f64, frame.height as f64);
+        if frame.width == 0 || frame.height == 0 {
+            return vec![];
+        }
+
+        let mut lines = vec![];
+        if frame.width != camera.image_width() || frame.height != camera.image_height() {
+            rectangle(&mut lines, x, y, width, height, GuideKind::Frame);
+        }
+
+        if self.rule_of_thirds {
+            for third in [1.0, 2.0] {
+                let line_x = (x + width * third / 3.0).round() as u32;
+                let line_y = (y + height * third / 3.0).round() as u32;
+                lines.push(GuideLine {
+                    x0: line_x,
+                    y0: frame.y,
+                    x1: line_x,
+                    y1: frame.y + frame.height - 1,
+                    kind: GuideKind::Thirds,
+                });
+                lines.push(GuideLine {
+                    x0: frame.x,
+                    y0: line_y,
+                    x1: frame.x + frame.width - 1,
+                    y1: line_y,
+                    kind: GuideKind::Thirds,
+                });
+            }
+        }
+
+        if self.safe_areas {
+            for (size, kind) in [
+                (Self::ACTION_SAFE, GuideKind::ActionSafe),
+                (Self::TITLE_SAFE, GuideKind::TitleSafe),
+            ] {
+                let margin_x = width * (1.0 - size) / 2.0;
+                let margin_y = height * (1.0 - size) / 2.0;
+                rectangle(
+                    &mut lines,
+                    x + margin_x,
+                    y + margin_y,
+                    width * size,
+                    height * size,
+                    kind,
+                );
+            }
+        }
+
+        lines
+    }
+}
+
+/// Adds the outline of a rectangle, drawn on the pixels just inside of it.
+fn rectangle(lines: &mut Vec<GuideLine>, x: f64, y: f64, width: f64, height: f64, kind: GuideKind) {
+    let left = x.round() as u32;
+    let top = y.round() as u32;
+    let right = ((x + width).round() as u32).saturating_sub(1).max(left);
+    let bottom = ((y + height).round() as u32).saturating_sub(1).max(top);
+    for (x0, y0, x1, y1) in [
+        (left, top, right, top),
+        (left, bottom, right, bottom),
+        (left, top, left, bottom),
+        (right, top, right, bottom),
+    ] {
+        lines.push(GuideLine {
+            x0,
+            y0,
+            x1,
+            y1,
+            kind,
+        });
+    }
+}
@@ -1,31 +1,59 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
 use crate::Color;
 
 #[derive(Debug)]
 pub enum ImageError {
+    /// The image file does not exist
+    NotFound(String),
+    /// The image data is not in a format that can be decoded
+    UnsupportedFormat(String),
     Io(String),
     Decode(String),
     Other(String),
 }
 
+impl Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::NotFound(message) => write!(f, "image not found: {message}"),
+            ImageError::UnsupportedFormat(message) => {
+                write!(f, "unsupported image format: {message}")
+            }
+            ImageError::Io(message) => write!(f, "image io error: {message}"),
+            ImageError::Decode(message) => write!(f, "image decode error: {message}"),
+            ImageError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
 pub trait Image: Send + Sync + Debug {
     fn width(&self) -> u32;
     fn height(&self) -> u32;
     fn get_pixel(&self, x: u32, y: u32) -> Option<Color>;
 }
 
-#[cfg(not(target_arch = "wasm32"))]
 pub use image_crate::ImageImage;
 
-#[cfg(not(target_arch = "wasm32"))]
 pub mod image_crate {
-    use std::{path::Path, sync::Arc};
+    use std::{
+        io::{self, BufRead, Cursor, Seek},
+        path::Path,
+        sync::Arc,
+    };
 
     use image::{DynamicImage, GenericImageView, ImageReader, Pixel};
 
     use crate::{Color, Image, image::ImageError};
 
+    /// An image decoded with the `image` crate.
+    ///
+    /// Any format supported by the crate can be loaded, including high dynamic
+    /// range formats such as Radiance HDR and OpenEXR. Floating point images keep
+    /// their full range so they can be used as environment maps, all other images
+    /// are converted to colors in [0, 1].
     #[derive(Debug)]
     pub struct ImageImage {
         image: DynamicImage,
@@ -36,14 +64,41 @@ pub mod image_crate {
         where
             P: AsRef<Path>,
         {
+            let filename = filename.as_ref();
             match ImageReader::open(filename) {
-                Ok(image) => match image.decode() {
-                    Ok(image) => Ok(Arc::new(ImageImage { image })),
-                    Err(err) => Err(ImageError::Decode(format!("Failed to decode image: {err}"))),
-                },
+                Ok(reader) => Self::decode(reader),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    Err(ImageError::NotFound(format!("{}", filename.display())))
+                }
                 Err(err) => Err(ImageError::Io(format!("Failed to load image: {err}"))),
             }
         }
+
+        /// Decodes an image from an in-memory buffer, guessing the format from its contents.
+        pub fn load_from_memory(bytes: &[u8]) -> Result<Arc<dyn Image>, ImageError> {
+            let reader = ImageReader::new(Cursor::new(bytes))
+                .with_guessed_format()
+                .map_err(|err| ImageError::Io(format!("Failed to read image: {err}")))?;
+            Self::decode(reader)
+        }
+
+        fn decode<R: BufRead + Seek>(reader: ImageReader<R>) -> Result<Arc<dyn Image>, ImageError> {
+            if reader.format().is_none() {
+                return Err(ImageError::UnsupportedFormat(
+                    "could not determine image format".to_owned(),
+                ));
+            }
+            match reader.decode() {
+                Ok(image) => Ok(Arc::new(ImageImage { image })),
+                Err(image::ImageError::Unsupported(err)) => {
+                    Err(ImageError::UnsupportedFormat(format!("{err}")))
+                }
+                Err(image::ImageError::IoError(err)) => {
+                    Err(ImageError::Io(format!("Failed to load image: {err}")))
+                }
+                Err(err) => Err(ImageError::Decode(format!("Failed to decode image: {err}"))),
+            }
+        }
     }
 
     impl Image for ImageImage {
@@ -59,11 +114,58 @@ pub mod image_crate {
             if !self.image.in_bounds(x, y) {
                 return None;
             }
-            let p = self.image.get_pixel(x, y).to_rgb();
-            let r = p.0[0] as f64 / 255.0;
-            let g = p.0[1] as f64 / 255.0;
-            let b = p.0[2] as f64 / 255.0;
-            Some(Color::new(r, g, b))
+            match &self.image {
+                DynamicImage::ImageRgb32F(image) => {
+                    let p = image.get_pixel(x, y);
+                    Some(Color::new(p.0[0] as f64, p.0[1] as f64, p.0[2] as f64))
+                }
+                DynamicImage::ImageRgba32F(image) => {
+                    let p = image.get_pixel(x, y);
+                    Some(Color::new(p.0[0] as f64, p.0[1] as f64, p.0[2] as f64))
+                }
+                image => {
+                    let p = image.get_pixel(x, y).to_rgb();
+                    let r = p.0[0] as f64 / 255.0;
+                    let g = p.0[1] as f64 / 255.0;
+                    let b = p.0[2] as f64 / 255.0;
+                    Some(Color::new(r, g, b))
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::ImageImage;
+        use crate::image::ImageError;
+
+        #[test]
+        fn load_file_not_found() {
+            let err = ImageImage::load_file("does-not-exist.png").unwrap_err();
+            assert!(matches!(err, ImageError::NotFound(_)));
+        }
+
+        #[test]
+        fn load_from_memory_unsupported() {
+            let err = ImageImage::load_from_memory(b"not an image").unwrap_err();
+            assert!(matches!(err, ImageError::UnsupportedFormat(_)));
+        }
+
+        #[test]
+        fn load_from_memory_hdr() {
+            let mut bytes = vec![];
+            let image = image::Rgb32FImage::from_pixel(2, 1, image::Rgb([4.0, 0.5, 0.0]));
+            image::DynamicImage::ImageRgb32F(image)
+                .write_to(
+                    &mut std::io::Cursor::new(&mut bytes),
+                    image::ImageFormat::Hdr,
+                )
+                .unwrap();
+
+            let image = ImageImage::load_from_memory(&bytes).unwrap();
+            assert_eq!(image.width(), 2);
+            let color = image.get_pixel(1, 0).unwrap();
+            assert!(color.r > 1.0);
         }
     }
 }
@@ -7,6 +7,13 @@ use std::ops::Add;
 /// It provides utilities for checking containment, expanding ranges, and performing
 /// arithmetic operations.
 ///
+/// Both endpoints are stored as inclusive, but callers pick the semantics they
+/// need: [`Interval::contains`] includes the endpoints while [`Interval::surrounds`]
+/// excludes them. Ray hit tests use `surrounds` on the `ray_t` interval, so a hit
+/// exactly at `ray_t.min` is rejected (avoiding self-intersection at the surface a
+/// ray was scattered from) and a hit exactly at `ray_t.max` never replaces a hit
+/// that was already found at that distance.
+///
 /// # Examples
 ///
 /// ```
@@ -16,7 +23,7 @@ use std::ops::Add;
 /// assert!(interval.contains(5.0));
 /// assert!(!interval.contains(15.0));
 /// ```
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Interval {
     /// The minimum value of the interval (inclusive)
     pub min: f64,
@@ -27,10 +34,13 @@ pub struct Interval {
 impl Interval {
     /// An empty interval where min > max.
     ///
-    /// This represents an invalid or empty range that contains no values.
+    /// This represents an invalid or empty range that contains no values. It is
+    /// the identity for [`Interval::union`].
     pub const EMPTY: Interval = Interval::new(f64::INFINITY, -f64::INFINITY);
 
     /// An interval spanning all possible values from negative to positive infinity.
+    ///
+    /// It is the identity for [`Interval::intersection`].
     pub const UNIVERSE: Interval = Interval::new(-f64::INFINITY, f64::INFINITY);
 
     /// Creates a new interval with the specified minimum and maximum values.
@@ -81,6 +91,42 @@ impl Interval {
         }
     }
 
+    /// Returns the smallest interval containing both `self` and `other`.
+    ///
+    /// Same as [`Interval::new_from_intervals`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::Interval;
+    ///
+    /// let a = Interval::new(0.0, 5.0);
+    /// let b = Interval::new(3.0, 8.0);
+    /// assert_eq!(a.union(&b), Interval::new(0.0, 8.0));
+    /// assert_eq!(a.union(&Interval::EMPTY), a);
+    /// ```
+    pub const fn union(&self, other: &Interval) -> Interval {
+        Interval::new_from_intervals(*self, *other)
+    }
+
+    /// Returns the range shared by `self` and `other`. If they do not overlap the
+    /// result has `min > max` and is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::Interval;
+    ///
+    /// let a = Interval::new(0.0, 5.0);
+    /// let b = Interval::new(3.0, 8.0);
+    /// assert_eq!(a.intersection(&b), Interval::new(3.0, 5.0));
+    /// assert_eq!(a.intersection(&Interval::UNIVERSE), a);
+    /// assert!(a.intersection(&Interval::new(6.0, 7.0)).is_empty());
+    /// ```
+    pub const fn intersection(&self, other: &Interval) -> Interval {
+        Interval::new(self.min.max(other.min), self.max.min(other.max))
+    }
+
     /// Checks if a value is contained within the interval (inclusive).
     ///
     /// Returns `true` if `min <= x <= max`.
@@ -126,6 +172,31 @@ impl Interval {
         self.min < x && x < self.max
     }
 
+    /// Clamps a value to lie within the interval (inclusive).
+    ///
+    /// Unlike [`f64::clamp`] this does not panic on an empty interval, it
+    /// returns `min` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::Interval;
+    ///
+    /// let interval = Interval::new(0.0, 1.0);
+    /// assert_eq!(interval.clamp(-0.5), 0.0);
+    /// assert_eq!(interval.clamp(0.25), 0.25);
+    /// assert_eq!(interval.clamp(1.5), 1.0);
+    /// ```
+    pub fn clamp(&self, x: f64) -> f64 {
+        if x < self.min {
+            self.min
+        } else if x > self.max {
+            self.max
+        } else {
+            x
+        }
+    }
+
     /// Creates a new interval expanded by the specified delta.
     ///
     /// The expansion is symmetric: half of `delta` is subtracted from `min`
@@ -166,8 +237,10 @@ impl Interval {
 
     /// Checks if the interval is empty.
     ///
-    /// An interval is considered empty if `max <= min`, meaning it contains no values.
-    /// This occurs when the interval is invalid or represents an empty range.
+    /// An interval is considered empty if `max <= min`, meaning it surrounds no values.
+    /// This occurs when the interval is invalid or represents an empty range. Note
+    /// that a single-point interval still [`contains`](Interval::contains) its point
+    /// but has no extent, so it is reported as empty.
     ///
     /// # Examples
     ///
@@ -1,32 +1,43 @@
 pub mod axis;
 pub mod axis_aligned_bounding_box;
+pub mod background;
 pub mod camera;
 pub mod color;
+pub mod guides;
 pub mod image;
 pub mod interval;
+pub mod light;
 pub mod material;
 pub mod matrix;
 pub mod object;
 pub mod probability_density_function;
+pub mod progressive_renderer;
+pub mod quaternion;
 pub mod random;
 pub mod ray;
+pub mod render;
 pub mod texture;
 pub mod utils;
 pub mod vector;
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 pub use axis::Axis;
 pub use axis_aligned_bounding_box::AxisAlignedBoundingBox;
-pub use camera::{Camera, CameraBuilder};
-pub use color::Color;
+pub use background::Background;
+pub use camera::{Aovs, Camera, CameraBuilder, CropWindow, Exposure, Projection, RenderRegion};
+pub use color::{Color, TransferFunction};
+pub use guides::{GuideKind, GuideLine, Guides};
 pub use image::Image;
 pub use interval::Interval;
+pub use light::Light;
 pub use matrix::Matrix3x3;
 pub use object::Node;
 pub use probability_density_function::{
-    CosinePdf, HittablePdf, ProbabilityDensityFunction, SpherePdf,
+    BackgroundPdf, CosinePdf, GgxPdf, HittablePdf, LightPdf, ProbabilityDensityFunction, SpherePdf,
 };
+pub use progressive_renderer::ProgressiveRenderer;
+pub use quaternion::Quaternion;
 pub use random::{Random, random_new};
 pub use ray::Ray;
 pub use vector::Vector3;
@@ -35,11 +46,109 @@ pub struct RenderContext {
     pub random: Arc<dyn Random>,
 }
 
+impl RenderContext {
+    /// Returns a context rendering the same image for the same seed, on every
+    /// run and whatever the number of threads, see [`random::SeededRandom`].
+    pub fn new_seeded(seed: u64) -> Self {
+        Self {
+            random: Arc::new(random::SeededRandom::new(seed)),
+        }
+    }
+
+    /// Returns a context drawing from a new `generator`.
+    pub fn new_with_generator(generator: random::RandomGenerator) -> Self {
+        Self {
+            random: generator.create(),
+        }
+    }
+
+    /// Returns the context to render `sample` of pixel (x, y) with, drawing
+    /// from its own [`Random::stream`], or `None` when not seeded and samples
+    /// draw from this context.
+    pub fn for_sample(&self, x: u32, y: u32, sample: u64) -> Option<RenderContext> {
+        self.random
+            .stream(x, y, sample)
+            .map(|random| RenderContext { random })
+    }
+}
+
 #[derive(Debug)]
 pub struct SceneData {
+    /// Camera rendered unless another one is selected
     pub camera: Arc<Camera>,
+    /// Cameras the scene defines by name, see [`SceneData::select_camera`]
+    pub cameras: HashMap<String, Arc<Camera>>,
     pub world: Arc<dyn Node>,
-    pub lights: Option<Arc<dyn Node>>,
+    /// Lights sampled directly, usually a [`light::LightList`]
+    pub lights: Option<Arc<dyn Light>>,
+    /// Boxes of the objects with mirror or glass materials, which caustic
+    /// photons are sent towards by [`SceneData::trace_caustics`]
+    pub caustic_targets: Vec<AxisAlignedBoundingBox>,
+}
+
+impl SceneData {
+    /// Makes the named camera the one rendered. Returns false, leaving the
+    /// camera unchanged, when the scene has no camera with that name.
+    pub fn select_camera(&mut self, name: &str) -> bool {
+        match self.cameras.get(name) {
+            Some(camera) => {
+                self.camera = camera.clone();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Traces the photon map of the cameras asking for
+    /// [caustic photons](CameraBuilder::caustic_photons), with the most
+    /// photons any of them asks for but at most `max_photons`, so renders can
+    /// bound the time spent tracing them. One map serves every camera.
+    /// Cameras render caustics by path tracing until this is called.
+    pub fn trace_caustics(&mut self, ctx: &RenderContext, max_photons: u32) {
+        let cameras = || std::iter::once(&self.camera).chain(self.cameras.values());
+        let photon_count = cameras()
+            .map(|camera| camera.builder().caustic_photons)
+            .max()
+            .unwrap_or(0)
+            .min(max_photons);
+        let max_depth = cameras()
+            .map(|camera| camera.max_depth())
+            .max()
+            .unwrap_or(0);
+        let Some(lights) = &self.lights else {
+            return;
+        };
+        if photon_count == 0 {
+            return;
+        }
+
+        let caustics = Some(Arc::new(render::PhotonMap::trace_caustics(
+            ctx,
+            &*self.world,
+            &**lights,
+            &self.caustic_targets,
+            photon_count,
+            max_depth,
+        )));
+        let with_caustics = |camera: &Arc<Camera>| {
+            if camera.builder().caustic_photons > 0 {
+                Arc::new(camera.with_caustics(caustics.clone()))
+            } else {
+                camera.clone()
+            }
+        };
+        self.camera = with_caustics(&self.camera);
+        for camera in self.cameras.values_mut() {
+            *camera = with_caustics(camera);
+        }
+    }
+
+    /// Returns the names of the scene's cameras in alphabetical order.
+    pub fn camera_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.cameras.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
 }
 
 pub fn line_number_at_offset(text: &str, offset: usize) -> usize {
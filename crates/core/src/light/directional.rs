@@ -0,0 +1,85 @@
+use std::any::Any;
+
+use crate::{
+    Color, RenderContext, Vector3,
+    light::{DeltaSample, Light},
+};
+
+/// A light so far away its rays are parallel, such as the sun, lighting every
+/// point of the scene equally from one direction.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{
+///     Color, RenderContext, Vector3,
+///     light::{DirectionalLight, Light},
+///     random_new,
+/// };
+///
+/// let sun = DirectionalLight::new(Vector3::new(0.0, 2.0, 0.0), Color::new(3.0, 3.0, 3.0));
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let origin = Vector3::new(100.0, 0.0, -40.0);
+/// let mut samples = vec![];
+/// sun.sample_delta(&ctx, &origin, &mut |sample| samples.push(sample));
+/// assert_eq!(samples[0].direction, Vector3::new(0.0, 1.0, 0.0));
+/// assert_eq!(samples[0].distance, f64::INFINITY);
+/// assert_eq!(samples[0].irradiance, Color::new(3.0, 3.0, 3.0));
+/// ```
+#[derive(Debug)]
+pub struct DirectionalLight {
+    /// Unit direction towards the light
+    direction: Vector3,
+    irradiance: Color,
+}
+
+impl DirectionalLight {
+    /// Creates a light coming from `direction`, with `irradiance` the light
+    /// arriving on a surface facing it.
+    pub fn new(direction: Vector3, irradiance: Color) -> Self {
+        Self {
+            direction: direction.unit(),
+            irradiance,
+        }
+    }
+
+    pub fn get_direction(&self) -> Vector3 {
+        self.direction
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample_direction(&self, _ctx: &RenderContext, _origin: &Vector3) -> Vector3 {
+        self.direction
+    }
+
+    fn pdf_value(&self, _ctx: &RenderContext, _origin: &Vector3, _direction: &Vector3) -> f64 {
+        0.0
+    }
+
+    fn emitted(&self, _ctx: &RenderContext, _origin: &Vector3, _direction: &Vector3) -> Color {
+        Color::BLACK
+    }
+
+    fn has_area(&self) -> bool {
+        false
+    }
+
+    fn sample_delta(
+        &self,
+        _ctx: &RenderContext,
+        _origin: &Vector3,
+        sample: &mut dyn FnMut(DeltaSample),
+    ) {
+        sample(DeltaSample {
+            direction: self.direction,
+            distance: f64::INFINITY,
+            irradiance: self.irradiance,
+        });
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,119 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    Axis, AxisAlignedBoundingBox, Color, Interval, Node, Ray, RenderContext, Vector3, light::Light,
+};
+
+/// Adapts an emissive [`Node`], such as a [`crate::object::Quad`] with a
+/// [`crate::material::DiffuseLight`], to a [`Light`] sampled by its
+/// `random` and `pdf_value`.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, RenderContext, Vector3,
+///     light::{GeometryLight, Light},
+///     material::DiffuseLight,
+///     object::Quad,
+///     random_new,
+/// };
+///
+/// let lamp = Arc::new(DiffuseLight::new_from_color(Color::new(4.0, 4.0, 4.0)));
+/// let quad = Quad::new(
+///     Vector3::new(-1.0, 1.0, -1.0),
+///     Vector3::new(2.0, 0.0, 0.0),
+///     Vector3::new(0.0, 0.0, 2.0),
+///     lamp,
+/// );
+/// let light = GeometryLight::new(Arc::new(quad));
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let origin = Vector3::new(0.0, 0.0, 0.0);
+/// let direction = light.sample_direction(&ctx, &origin);
+/// assert!(direction.y > 0.0);
+/// assert!(light.pdf_value(&ctx, &origin, &direction) > 0.0);
+///
+/// let up = Vector3::new(0.0, 1.0, 0.0);
+/// assert_eq!(light.emitted(&ctx, &origin, &up), Color::new(4.0, 4.0, 4.0));
+/// let down = Vector3::new(0.0, -1.0, 0.0);
+/// assert_eq!(light.pdf_value(&ctx, &origin, &down), 0.0);
+/// assert_eq!(light.emitted(&ctx, &origin, &down), Color::BLACK);
+/// ```
+#[derive(Debug)]
+pub struct GeometryLight {
+    node: Arc<dyn Node>,
+}
+
+impl GeometryLight {
+    pub fn new(node: Arc<dyn Node>) -> Self {
+        Self { node }
+    }
+
+    pub fn get_node(&self) -> &Arc<dyn Node> {
+        &self.node
+    }
+}
+
+impl Light for GeometryLight {
+    fn sample_direction(&self, ctx: &RenderContext, origin: &Vector3) -> Vector3 {
+        self.node.random(ctx, origin)
+    }
+
+    fn pdf_value(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> f64 {
+        self.node.pdf_value(ctx, origin, direction)
+    }
+
+    fn emitted(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> Color {
+        let ray = Ray::new(*origin, *direction);
+        match self
+            .node
+            .hit(ctx, &ray, Interval::new(0.001, f64::INFINITY))
+        {
+            Some(hit) => hit.material.emitted(&ray, &hit, hit.u, hit.v, hit.pt),
+            None => Color::BLACK,
+        }
+    }
+
+    fn bounding_box(&self) -> Option<AxisAlignedBoundingBox> {
+        let bbox = self.node.bounding_box();
+        (!bbox.is_unbounded()).then_some(*bbox)
+    }
+
+    /// Looks at the light from outside each face of its box, as surfaces may
+    /// only emit on one side, and scales the brightest radiance seen by an
+    /// area estimated from the box.
+    fn estimate_power(&self, ctx: &RenderContext) -> f64 {
+        let Some(bbox) = Light::bounding_box(self) else {
+            return 1.0;
+        };
+        let center = bbox.centroid();
+        let size = Vector3::new(
+            bbox.axis_interval(Axis::X).size(),
+            bbox.axis_interval(Axis::Y).size(),
+            bbox.axis_interval(Axis::Z).size(),
+        );
+        let reach = size.length();
+        let brightest = [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, -1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        ]
+        .into_iter()
+        .map(|side| {
+            let origin = center + side * reach;
+            let direction = self.node.random(ctx, &origin);
+            self.emitted(ctx, &origin, &direction).luminance()
+        })
+        .fold(0.0, f64::max);
+        brightest * bbox.surface_area() / 2.0
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,207 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    Color, Node, RenderContext, Vector3,
+    light::{DeltaSample, GeometryLight, Light, LightTree},
+};
+
+/// The lights of a scene. Lights with an area are sampled by picking one of
+/// them at random, every other light is sampled by [`Light::sample_delta`].
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Node, RenderContext, Vector3,
+///     light::{Light, LightList},
+///     material::DiffuseLight,
+///     object::Sphere,
+///     random_new,
+/// };
+///
+/// let lamp = Arc::new(DiffuseLight::new_from_color(Color::new(1.0, 1.0, 1.0)));
+/// let above: Arc<dyn Node> = Arc::new(Sphere::new(Vector3::new(0.0, 5.0, 0.0), 1.0, lamp.clone()));
+/// let below: Arc<dyn Node> = Arc::new(Sphere::new(Vector3::new(0.0, -5.0, 0.0), 1.0, lamp));
+/// let lights = LightList::from_nodes(&[above, below]);
+/// assert_eq!(lights.len(), 2);
+///
+/// // Each light is picked half of the time
+/// let ctx = RenderContext { random: random_new() };
+/// let origin = Vector3::new(0.0, 0.0, 0.0);
+/// let up = Vector3::new(0.0, 1.0, 0.0);
+/// let one = Sphere::new(Vector3::new(0.0, 5.0, 0.0), 1.0, Arc::new(DiffuseLight::new_from_color(Color::BLACK)));
+/// let expected = 0.5 * one.pdf_value(&ctx, &origin, &up);
+/// assert!((lights.pdf_value(&ctx, &origin, &up) - expected).abs() < 1e-12);
+/// ```
+#[derive(Debug, Default)]
+pub struct LightList {
+    lights: Vec<Arc<dyn Light>>,
+    /// The lights with an area, the ones `sample_direction` picks from
+    area_lights: Vec<Arc<dyn Light>>,
+}
+
+impl LightList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a list sampling each of the emissive `nodes` through a
+    /// [`GeometryLight`].
+    pub fn from_nodes(nodes: &[Arc<dyn Node>]) -> Self {
+        let mut results = Self::new();
+        for node in nodes {
+            results.push(Arc::new(GeometryLight::new(node.clone())));
+        }
+        results
+    }
+
+    pub fn push(&mut self, light: Arc<dyn Light>) {
+        if light.has_area() {
+            self.area_lights.push(light.clone());
+        }
+        self.lights.push(light);
+    }
+
+    /// Samples the area lights with bounding boxes through a [`LightTree`],
+    /// by their contribution rather than uniformly, which pays off once a
+    /// scene has many lights. Lights pushed afterwards are picked uniformly
+    /// alongside the tree.
+    pub fn build_tree(&mut self, ctx: &RenderContext) {
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = self
+            .area_lights
+            .iter()
+            .cloned()
+            .partition(|light| light.bounding_box().is_some());
+        if bounded.len() < 2 {
+            return;
+        }
+        self.area_lights = vec![Arc::new(LightTree::new(ctx, &bounded)) as Arc<dyn Light>];
+        self.area_lights.extend(unbounded);
+    }
+
+    pub fn get_lights(&self) -> &[Arc<dyn Light>] {
+        &self.lights
+    }
+
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+}
+
+impl Light for LightList {
+    fn sample_direction(&self, ctx: &RenderContext, origin: &Vector3) -> Vector3 {
+        if self.area_lights.is_empty() {
+            Vector3::new(0.0, 1.0, 0.0)
+        } else {
+            let r = ctx
+                .random
+                .rand_int_interval(0, self.area_lights.len() as i64) as usize;
+            self.area_lights[r].sample_direction(ctx, origin)
+        }
+    }
+
+    fn pdf_value(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> f64 {
+        let weight = 1.0 / (self.area_lights.len() as f64);
+        self.area_lights
+            .iter()
+            .map(|light| weight * light.pdf_value(ctx, origin, direction))
+            .sum()
+    }
+
+    /// Returns the sum of the radiance of every light along `direction`,
+    /// without lights hiding each other.
+    fn emitted(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> Color {
+        self.lights.iter().fold(Color::BLACK, |sum, light| {
+            sum + light.emitted(ctx, origin, direction)
+        })
+    }
+
+    fn has_area(&self) -> bool {
+        !self.area_lights.is_empty()
+    }
+
+    fn sample_delta(
+        &self,
+        ctx: &RenderContext,
+        origin: &Vector3,
+        sample: &mut dyn FnMut(DeltaSample),
+    ) {
+        for light in &self.lights {
+            light.sample_delta(ctx, origin, sample);
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use crate::{
+        Color, Interval, Node, Ray, RenderContext, Vector3,
+        light::{Light, LightList},
+        material::DiffuseLight,
+        object::Sphere,
+    };
+
+    /// Estimates the solid angle of each sphere seen from the origin, as the
+    /// mean of `1 / pdf` over the sampled directions hitting it, against the
+    /// exact `2π (1 - cos θ)` of a sphere of radius `r` at distance `d`, where
+    /// `sin θ = r / d`. The dim far light is picked least and is the noisiest.
+    #[test]
+    fn light_tree_pdf_matches_sampled_directions() {
+        let ctx = RenderContext::new_seeded(1);
+        let spheres: Vec<Arc<dyn Node>> = [
+            (Vector3::new(0.0, 5.0, 0.0), 1.0),
+            (Vector3::new(4.0, 0.0, 3.0), 20.0),
+            (Vector3::new(-8.0, 0.0, 0.0), 5.0),
+            (Vector3::new(0.0, -2.0, -10.0), 0.5),
+        ]
+        .into_iter()
+        .map(|(center, emission)| {
+            let lamp = Arc::new(DiffuseLight::new_from_color(Color::new(
+                emission, emission, emission,
+            )));
+            Arc::new(Sphere::new(center, 1.0, lamp)) as Arc<dyn Node>
+        })
+        .collect();
+        let mut lights = LightList::from_nodes(&spheres);
+        lights.build_tree(&ctx);
+        assert_eq!(lights.area_lights.len(), 1);
+
+        let origin = Vector3::ZERO;
+        let samples = 200_000;
+        let mut estimates = vec![0.0; spheres.len()];
+        for _ in 0..samples {
+            let direction = lights.sample_direction(&ctx, &origin);
+            let pdf = lights.pdf_value(&ctx, &origin, &direction);
+            assert!(pdf > 0.0);
+            let ray = Ray::new(origin, direction);
+            for (estimate, sphere) in estimates.iter_mut().zip(&spheres) {
+                if sphere
+                    .hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY))
+                    .is_some()
+                {
+                    *estimate += 1.0 / pdf / samples as f64;
+                }
+            }
+        }
+
+        for (estimate, sphere) in estimates.iter().zip(&spheres) {
+            let distance = sphere.bounding_box().centroid().length();
+            let exact = 2.0 * PI * (1.0 - (1.0 - 1.0 / (distance * distance)).sqrt());
+            assert!(
+                (estimate - exact).abs() < 0.05 * exact,
+                "estimated {estimate}, exact {exact}"
+            );
+        }
+    }
+}
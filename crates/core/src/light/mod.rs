@@ -0,0 +1,92 @@
+pub mod directional;
+pub mod geometry;
+pub mod list;
+pub mod point;
+pub mod spot;
+pub mod tree;
+
+pub use directional::DirectionalLight;
+pub use geometry::GeometryLight;
+pub use list::LightList;
+pub use point::PointLight;
+pub use spot::SpotLight;
+pub use tree::LightTree;
+
+use std::{any::Any, fmt::Debug};
+
+use crate::{AxisAlignedBoundingBox, Color, RenderContext, Vector3};
+
+/// A source of light sampled directly when shading, so paths find small
+/// lights rather than waiting to bounce into them.
+///
+/// Lights are separate from the [`crate::Node`]s of the world: emissive
+/// geometry is sampled through [`GeometryLight`], and lights without a
+/// surface, such as point or directional lights, can implement this trait
+/// alone.
+///
+/// Lights concentrated at a point or in a single direction, such as
+/// [`PointLight`], cannot be found by random directions. They return false
+/// from [`Light::has_area`] and are sampled by [`Light::sample_delta`]
+/// instead, with a shadow ray towards each of them.
+pub trait Light: Send + Sync + Debug {
+    /// Returns a random direction from `origin` towards the light.
+    fn sample_direction(&self, ctx: &RenderContext, origin: &Vector3) -> Vector3;
+
+    /// Returns the density, over solid angle, of [`Light::sample_direction`]
+    /// returning `direction` from `origin`.
+    fn pdf_value(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> f64;
+
+    /// Returns the radiance arriving at `origin` from the light along
+    /// `direction`, black when the light is not in that direction. Anything
+    /// between `origin` and the light is not taken into account.
+    fn emitted(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> Color;
+
+    /// Returns true when the light has an area which
+    /// [`Light::sample_direction`] finds.
+    fn has_area(&self) -> bool {
+        true
+    }
+
+    /// Returns the box the light's area lies in, `None` when it has no area or
+    /// is unbounded. Lights with a box can be sampled by a [`LightTree`].
+    fn bounding_box(&self) -> Option<AxisAlignedBoundingBox> {
+        None
+    }
+
+    /// Returns a rough estimate of the light emitted in total, only compared
+    /// between lights so brighter ones are sampled more often.
+    fn estimate_power(&self, _ctx: &RenderContext) -> f64 {
+        1.0
+    }
+
+    /// Calls `sample` with the light arriving at `origin` from each part of
+    /// the light concentrated at a point or in a single direction. Anything
+    /// between `origin` and the light is not taken into account.
+    fn sample_delta(
+        &self,
+        _ctx: &RenderContext,
+        _origin: &Vector3,
+        _sample: &mut dyn FnMut(DeltaSample),
+    ) {
+    }
+
+    /// Returns the point a light concentrated at a point shines from, `None`
+    /// for other lights.
+    fn position(&self) -> Option<Vector3> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Light arriving at a point from a light concentrated at a point or in a
+/// single direction, see [`Light::sample_delta`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DeltaSample {
+    /// Unit direction towards the light
+    pub direction: Vector3,
+    /// Distance to the light, infinite for directional lights
+    pub distance: f64,
+    /// Light arriving on a surface facing the light, after falloff
+    pub irradiance: Color,
+}
@@ -0,0 +1,100 @@
+use std::any::Any;
+
+use crate::{
+    Color, RenderContext, Vector3,
+    light::{DeltaSample, Light},
+};
+
+/// A light shining equally in every direction from a single point, falling
+/// off with the square of the distance. Its shadows have hard edges.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{
+///     Color, RenderContext, Vector3,
+///     light::{Light, PointLight},
+///     random_new,
+/// };
+///
+/// let light = PointLight::new(Vector3::new(0.0, 2.0, 0.0), Color::new(8.0, 8.0, 8.0));
+/// assert!(!light.has_area());
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let mut samples = vec![];
+/// light.sample_delta(&ctx, &Vector3::ZERO, &mut |sample| samples.push(sample));
+/// assert_eq!(samples.len(), 1);
+/// assert_eq!(samples[0].direction, Vector3::new(0.0, 1.0, 0.0));
+/// assert_eq!(samples[0].distance, 2.0);
+/// // 8 / 2²
+/// assert_eq!(samples[0].irradiance, Color::new(2.0, 2.0, 2.0));
+/// ```
+#[derive(Debug)]
+pub struct PointLight {
+    position: Vector3,
+    intensity: Color,
+}
+
+impl PointLight {
+    /// Creates a light at `position`, `intensity` being the light arriving one
+    /// unit away.
+    pub fn new(position: Vector3, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+        }
+    }
+
+    pub fn get_position(&self) -> Vector3 {
+        self.position
+    }
+
+    pub fn get_intensity(&self) -> Color {
+        self.intensity
+    }
+}
+
+impl Light for PointLight {
+    fn sample_direction(&self, _ctx: &RenderContext, origin: &Vector3) -> Vector3 {
+        self.position - *origin
+    }
+
+    fn pdf_value(&self, _ctx: &RenderContext, _origin: &Vector3, _direction: &Vector3) -> f64 {
+        0.0
+    }
+
+    fn emitted(&self, _ctx: &RenderContext, _origin: &Vector3, _direction: &Vector3) -> Color {
+        Color::BLACK
+    }
+
+    fn has_area(&self) -> bool {
+        false
+    }
+
+    fn position(&self) -> Option<Vector3> {
+        Some(self.position)
+    }
+
+    fn sample_delta(
+        &self,
+        _ctx: &RenderContext,
+        origin: &Vector3,
+        sample: &mut dyn FnMut(DeltaSample),
+    ) {
+        let to_light = self.position - *origin;
+        let distance_squared = to_light.length_squared();
+        if distance_squared == 0.0 {
+            return;
+        }
+        let distance = distance_squared.sqrt();
+        sample(DeltaSample {
+            direction: to_light / distance,
+            distance,
+            irradiance: self.intensity / distance_squared,
+        });
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,150 @@
+use std::any::Any;
+
+use crate::{
+    Color, RenderContext, Vector3,
+    light::{DeltaSample, Light},
+    utils::math,
+};
+
+/// A point light shining in a cone, full within `inner_angle` of its
+/// direction and fading out smoothly to nothing at `outer_angle`.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{
+///     Color, RenderContext, Vector3,
+///     light::{Light, SpotLight},
+///     random_new,
+/// };
+///
+/// let light = SpotLight::new(
+///     Vector3::new(0.0, 1.0, 0.0),
+///     Vector3::new(0.0, -1.0, 0.0),
+///     Color::new(1.0, 1.0, 1.0),
+/// )
+/// .with_cone(20.0, 30.0);
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let irradiance_at = |x: f64| {
+///     let mut irradiance = Color::BLACK;
+///     let origin = Vector3::new(x, 0.0, 0.0);
+///     light.sample_delta(&ctx, &origin, &mut |sample| irradiance = sample.irradiance);
+///     irradiance
+/// };
+/// // Below the light, 10 and 45 degrees off its direction
+/// assert_eq!(irradiance_at(0.0), Color::new(1.0, 1.0, 1.0));
+/// assert!(irradiance_at(0.18).r > 0.9);
+/// assert_eq!(irradiance_at(1.0), Color::BLACK);
+/// ```
+#[derive(Debug)]
+pub struct SpotLight {
+    position: Vector3,
+    /// Unit direction the light shines in
+    direction: Vector3,
+    intensity: Color,
+    cos_inner: f64,
+    cos_outer: f64,
+}
+
+impl SpotLight {
+    /// Default angle, in degrees, between the direction and the edge of the cone
+    pub const DEFAULT_OUTER_ANGLE: f64 = 30.0;
+
+    /// Creates a light at `position` shining towards `direction`, with
+    /// `intensity` the light arriving one unit away along `direction`.
+    pub fn new(position: Vector3, direction: Vector3, intensity: Color) -> Self {
+        Self {
+            position,
+            direction: direction.unit(),
+            intensity,
+            cos_inner: 1.0,
+            cos_outer: 1.0,
+        }
+        .with_cone(Self::DEFAULT_OUTER_ANGLE * 0.8, Self::DEFAULT_OUTER_ANGLE)
+    }
+
+    /// Sets the angles, in degrees from the direction, within which the light
+    /// is full and beyond which it is dark. `inner_angle` is clamped to
+    /// `outer_angle`.
+    pub fn with_cone(self, inner_angle: f64, outer_angle: f64) -> Self {
+        let outer_angle = outer_angle.clamp(0.0, 180.0);
+        let inner_angle = inner_angle.clamp(0.0, outer_angle);
+        Self {
+            cos_inner: math::cos(inner_angle.to_radians()),
+            cos_outer: math::cos(outer_angle.to_radians()),
+            ..self
+        }
+    }
+
+    pub fn get_position(&self) -> Vector3 {
+        self.position
+    }
+
+    pub fn get_direction(&self) -> Vector3 {
+        self.direction
+    }
+
+    /// Returns the fraction of the light shining in the unit `direction`.
+    fn cone_falloff(&self, direction: Vector3) -> f64 {
+        let cos_theta = self.direction.dot(&direction);
+        if cos_theta >= self.cos_inner {
+            1.0
+        } else if cos_theta <= self.cos_outer {
+            0.0
+        } else {
+            let t = (cos_theta - self.cos_outer) / (self.cos_inner - self.cos_outer);
+            t * t * (3.0 - 2.0 * t)
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn sample_direction(&self, _ctx: &RenderContext, origin: &Vector3) -> Vector3 {
+        self.position - *origin
+    }
+
+    fn pdf_value(&self, _ctx: &RenderContext, _origin: &Vector3, _direction: &Vector3) -> f64 {
+        0.0
+    }
+
+    fn emitted(&self, _ctx: &RenderContext, _origin: &Vector3, _direction: &Vector3) -> Color {
+        Color::BLACK
+    }
+
+    fn has_area(&self) -> bool {
+        false
+    }
+
+    fn position(&self) -> Option<Vector3> {
+        Some(self.position)
+    }
+
+    fn sample_delta(
+        &self,
+        _ctx: &RenderContext,
+        origin: &Vector3,
+        sample: &mut dyn FnMut(DeltaSample),
+    ) {
+        let to_light = self.position - *origin;
+        let distance_squared = to_light.length_squared();
+        if distance_squared == 0.0 {
+            return;
+        }
+        let distance = distance_squared.sqrt();
+        let direction = to_light / distance;
+        let falloff = self.cone_falloff(-direction);
+        if falloff == 0.0 {
+            return;
+        }
+        sample(DeltaSample {
+            direction,
+            distance,
+            irradiance: self.intensity * (falloff / distance_squared),
+        });
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,294 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    Axis, AxisAlignedBoundingBox, Color, Interval, Ray, RenderContext, Vector3, light::Light,
+};
+
+/// A hierarchy of lights with bounding boxes, picking a light with odds
+/// following its estimated contribution at the shaded point rather than
+/// uniformly, so scenes with hundreds of lights, such as the windows of a
+/// city, spend their samples on the lights which matter nearby.
+///
+/// Each step down the tree chooses a side by the power of its lights over
+/// the squared distance to their box. Lights without a box cannot be placed
+/// in the tree and are left out.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Node, RenderContext, Vector3,
+///     light::{GeometryLight, Light, LightTree},
+///     material::DiffuseLight,
+///     object::Sphere,
+///     random_new,
+/// };
+///
+/// let lamp = Arc::new(DiffuseLight::new_from_color(Color::new(1.0, 1.0, 1.0)));
+/// let lights: Vec<Arc<dyn Light>> = (0..100)
+///     .map(|i| {
+///         let center = Vector3::new(i as f64 * 10.0, 5.0, 0.0);
+///         let sphere: Arc<dyn Node> = Arc::new(Sphere::new(center, 1.0, lamp.clone()));
+///         Arc::new(GeometryLight::new(sphere)) as Arc<dyn Light>
+///     })
+///     .collect();
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let tree = LightTree::new(&ctx, &lights);
+/// assert_eq!(tree.len(), 100);
+///
+/// // Under the first light, it is picked far more often than uniformly
+/// let origin = Vector3::new(0.0, 0.0, 0.0);
+/// let up = Vector3::new(0.0, 1.0, 0.0);
+/// let one = lights[0].pdf_value(&ctx, &origin, &up);
+/// assert!(tree.pdf_value(&ctx, &origin, &up) > 0.5 * one);
+///
+/// // Sampled directions always have a density
+/// for _ in 0..10 {
+///     let direction = tree.sample_direction(&ctx, &origin);
+///     assert!(tree.pdf_value(&ctx, &origin, &direction) > 0.0);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct LightTree {
+    lights: Vec<Arc<dyn Light>>,
+    /// Nodes of the tree, the root first
+    nodes: Vec<LightTreeNode>,
+}
+
+#[derive(Debug)]
+struct LightTreeNode {
+    bbox: AxisAlignedBoundingBox,
+    power: f64,
+    kind: LightTreeNodeKind,
+}
+
+#[derive(Debug)]
+enum LightTreeNodeKind {
+    /// Index of the light
+    Leaf(usize),
+    /// Indices of the two child nodes
+    Interior(usize, usize),
+}
+
+impl LightTree {
+    /// Builds a tree of the `lights` with bounding boxes, estimating their
+    /// power once.
+    pub fn new(ctx: &RenderContext, lights: &[Arc<dyn Light>]) -> Self {
+        let lights: Vec<Arc<dyn Light>> = lights
+            .iter()
+            .filter(|light| light.bounding_box().is_some())
+            .cloned()
+            .collect();
+        let mut leaves: Vec<LightTreeNode> = lights
+            .iter()
+            .enumerate()
+            .map(|(index, light)| LightTreeNode {
+                bbox: light.bounding_box().unwrap(),
+                power: light.estimate_power(ctx).max(0.0),
+                kind: LightTreeNodeKind::Leaf(index),
+            })
+            .collect();
+
+        let mut tree = Self {
+            lights,
+            nodes: vec![],
+        };
+        if !leaves.is_empty() {
+            // Reserve the root so it comes first
+            tree.nodes.push(LightTreeNode {
+                bbox: AxisAlignedBoundingBox::new(),
+                power: 0.0,
+                kind: LightTreeNodeKind::Leaf(0),
+            });
+            tree.nodes[0] = tree.build(&mut leaves);
+        }
+        tree
+    }
+
+    /// Returns the node over `leaves`, split in halves along the longest axis
+    /// of their centers, adding the nodes below it to the tree.
+    fn build(&mut self, leaves: &mut [LightTreeNode]) -> LightTreeNode {
+        if let [leaf] = leaves {
+            return LightTreeNode {
+                bbox: leaf.bbox,
+                power: leaf.power,
+                kind: LightTreeNodeKind::Leaf(match leaf.kind {
+                    LightTreeNodeKind::Leaf(index) => index,
+                    LightTreeNodeKind::Interior(..) => unreachable!("only leaves are built"),
+                }),
+            };
+        }
+
+        let centers = leaves
+            .iter()
+            .map(|leaf| {
+                let center = leaf.bbox.centroid();
+                AxisAlignedBoundingBox::new_from_points(center, center)
+            })
+            .reduce(|a, b| a.union(&b))
+            .unwrap();
+        let axis = centers.longest_axis();
+        leaves.sort_by(|a, b| {
+            let a = a.bbox.centroid().axis_value(axis);
+            let b = b.bbox.centroid().axis_value(axis);
+            a.total_cmp(&b)
+        });
+
+        let (left, right) = leaves.split_at_mut(leaves.len() / 2);
+        let left = self.build(left);
+        let right = self.build(right);
+        let node = LightTreeNode {
+            bbox: left.bbox.union(&right.bbox),
+            power: left.power + right.power,
+            kind: LightTreeNodeKind::Interior(self.nodes.len(), self.nodes.len() + 1),
+        };
+        self.nodes.push(left);
+        self.nodes.push(right);
+        node
+    }
+
+    pub fn len(&self) -> usize {
+        self.lights.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lights.is_empty()
+    }
+
+    /// Returns the estimated contribution of the lights below a node at
+    /// `origin`, their power over the squared distance to the center of their
+    /// box, no closer than the edge of the box.
+    fn importance(&self, index: usize, origin: &Vector3) -> f64 {
+        let node = &self.nodes[index];
+        let half_diagonal_squared = Axis::iter()
+            .map(|axis| {
+                let half = node.bbox.axis_interval(axis).size() / 2.0;
+                half * half
+            })
+            .sum::<f64>();
+        let distance_squared = (node.bbox.centroid() - *origin).length_squared();
+        node.power / distance_squared.max(half_diagonal_squared).max(1e-12)
+    }
+
+    /// Returns the odds of choosing the left child of an interior node.
+    fn left_probability(&self, left: usize, right: usize, origin: &Vector3) -> f64 {
+        let left = self.importance(left, origin);
+        let right = self.importance(right, origin);
+        if left + right > 0.0 {
+            left / (left + right)
+        } else {
+            0.5
+        }
+    }
+
+    fn pdf_below(
+        &self,
+        index: usize,
+        ctx: &RenderContext,
+        ray: &Ray,
+        origin: &Vector3,
+        direction: &Vector3,
+    ) -> f64 {
+        // Lights whose box is not in the direction cannot be sampled there
+        if !self.nodes[index]
+            .bbox
+            .hit(ray, Interval::new(0.0, f64::INFINITY))
+        {
+            return 0.0;
+        }
+        match self.nodes[index].kind {
+            LightTreeNodeKind::Leaf(light) => self.lights[light].pdf_value(ctx, origin, direction),
+            LightTreeNodeKind::Interior(left, right) => {
+                let p = self.left_probability(left, right, origin);
+                let mut pdf = 0.0;
+                if p > 0.0 {
+                    pdf += p * self.pdf_below(left, ctx, ray, origin, direction);
+                }
+                if p < 1.0 {
+                    pdf += (1.0 - p) * self.pdf_below(right, ctx, ray, origin, direction);
+                }
+                pdf
+            }
+        }
+    }
+
+    fn emitted_below(
+        &self,
+        index: usize,
+        ctx: &RenderContext,
+        ray: &Ray,
+        origin: &Vector3,
+        direction: &Vector3,
+    ) -> Color {
+        if !self.nodes[index]
+            .bbox
+            .hit(ray, Interval::new(0.0, f64::INFINITY))
+        {
+            return Color::BLACK;
+        }
+        match self.nodes[index].kind {
+            LightTreeNodeKind::Leaf(light) => self.lights[light].emitted(ctx, origin, direction),
+            LightTreeNodeKind::Interior(left, right) => {
+                self.emitted_below(left, ctx, ray, origin, direction)
+                    + self.emitted_below(right, ctx, ray, origin, direction)
+            }
+        }
+    }
+}
+
+impl Light for LightTree {
+    fn sample_direction(&self, ctx: &RenderContext, origin: &Vector3) -> Vector3 {
+        if self.nodes.is_empty() {
+            return Vector3::new(0.0, 1.0, 0.0);
+        }
+        let mut index = 0;
+        loop {
+            match self.nodes[index].kind {
+                LightTreeNodeKind::Leaf(light) => {
+                    return self.lights[light].sample_direction(ctx, origin);
+                }
+                LightTreeNodeKind::Interior(left, right) => {
+                    index = if ctx.random.rand() < self.left_probability(left, right, origin) {
+                        left
+                    } else {
+                        right
+                    };
+                }
+            }
+        }
+    }
+
+    fn pdf_value(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> f64 {
+        if self.nodes.is_empty() {
+            return 0.0;
+        }
+        let ray = Ray::new(*origin, *direction);
+        self.pdf_below(0, ctx, &ray, origin, direction)
+    }
+
+    fn emitted(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> Color {
+        if self.nodes.is_empty() {
+            return Color::BLACK;
+        }
+        let ray = Ray::new(*origin, *direction);
+        self.emitted_below(0, ctx, &ray, origin, direction)
+    }
+
+    fn has_area(&self) -> bool {
+        !self.lights.is_empty()
+    }
+
+    fn bounding_box(&self) -> Option<AxisAlignedBoundingBox> {
+        self.nodes.first().map(|root| root.bbox)
+    }
+
+    fn estimate_power(&self, _ctx: &RenderContext) -> f64 {
+        self.nodes.first().map_or(0.0, |root| root.power)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use crate::{
+    Color, Ray, RenderContext, Vector3,
+    material::{Material, ScatterResult},
+    object::HitRecord,
+    texture::Texture,
+};
+
+/// Wraps another material and cuts holes in the surface with an opacity
+/// texture, so a single quad can show the outline of a leaf or a fence.
+///
+/// The opacity is the luminance of the texture. Where it falls below the
+/// threshold rays pass straight through, as if the surface was not there.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Vector3,
+///     material::{AlphaMask, Lambertian, Material},
+///     object::HitRecord,
+///     texture::{CheckerTexture, SolidColor},
+/// };
+///
+/// let leaf = Arc::new(Lambertian::new_from_color(Color::new(0.2, 0.6, 0.1)));
+/// let mask = Arc::new(CheckerTexture::new(
+///     1.0,
+///     Arc::new(SolidColor::new(Color::BLACK)),
+///     Arc::new(SolidColor::new(Color::WHITE)),
+/// ));
+/// let cutout = AlphaMask::new(leaf.clone(), mask);
+///
+/// let mut hit = HitRecord {
+///     pt: Vector3::new(0.5, 0.5, 0.5),
+///     normal: Vector3::new(0.0, 0.0, 1.0),
+///     tangent: Vector3::new(1.0, 0.0, 0.0),
+///     t: 1.0,
+///     u: 0.5,
+///     v: 0.5,
+///     front_face: true,
+///     material: leaf,
+///     object_id: 0,
+/// };
+/// let first = cutout.is_cutout(&hit);
+/// hit.pt = Vector3::new(1.5, 0.5, 0.5);
+/// assert_ne!(cutout.is_cutout(&hit), first);
+/// ```
+#[derive(Debug)]
+pub struct AlphaMask {
+    material: Arc<dyn Material>,
+    opacity: Arc<dyn Texture>,
+    threshold: f64,
+}
+
+impl AlphaMask {
+    pub fn new(material: Arc<dyn Material>, opacity: Arc<dyn Texture>) -> Self {
+        Self {
+            material,
+            opacity,
+            threshold: 0.5,
+        }
+    }
+
+    /// Sets the opacity below which the surface is cut away.
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn get_threshold(&self) -> f64 {
+        self.threshold
+    }
+}
+
+impl Material for AlphaMask {
+    fn scatter(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
+        self.material.scatter(ctx, r_in, hit)
+    }
+
+    fn emitted(&self, r_in: &Ray, hit: &HitRecord, u: f64, v: f64, pt: Vector3) -> Color {
+        self.material.emitted(r_in, hit, u, v, pt)
+    }
+
+    fn scattering_pdf(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+    ) -> f64 {
+        self.material.scattering_pdf(ctx, r_in, hit, scattered)
+    }
+
+    fn scattering_color(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+        attenuation: Color,
+    ) -> Color {
+        self.material
+            .scattering_color(ctx, r_in, hit, scattered, attenuation)
+    }
+
+    fn is_cutout(&self, hit: &HitRecord) -> bool {
+        self.opacity.value_at(hit).luminance() < self.threshold || self.material.is_cutout(hit)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn is_specular(&self) -> bool {
+        self.material.is_specular()
+    }
+}
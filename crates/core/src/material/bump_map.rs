@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use crate::{
+    Color, Ray, RenderContext, Vector3,
+    material::{Material, ScatterResult},
+    object::HitRecord,
+    texture::Texture,
+};
+
+/// Wraps another material and bends its shading normal by the slope of a
+/// grayscale height texture, so scratches, dimples and grain catch the light
+/// without extra geometry.
+///
+/// The height is the luminance of the texture. Its slope is found with central
+/// differences, stepping `delta` in both the UV coordinates and along the
+/// surface, so image textures and solid textures such as Perlin noise both work.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Vector3,
+///     material::{BumpMap, Lambertian},
+///     object::HitRecord,
+///     texture::{SolidColor, PerlinNoiseTexture},
+///     random_new,
+/// };
+///
+/// let base = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.8, 0.8)));
+/// let hit = HitRecord {
+///     pt: Vector3::new(0.3, 0.0, 0.7),
+///     normal: Vector3::new(0.0, 1.0, 0.0),
+///     tangent: Vector3::new(1.0, 0.0, 0.0),
+///     t: 1.0,
+///     u: 0.3,
+///     v: 0.7,
+///     front_face: true,
+///     material: base.clone(),
+///     object_id: 0,
+/// };
+///
+/// // A flat height texture leaves the surface unchanged
+/// let flat = BumpMap::new(base.clone(), Arc::new(SolidColor::new(Color::new(0.5, 0.5, 0.5))));
+/// assert_eq!(flat.shading_normal(&hit), hit.normal);
+///
+/// // Noise tilts it
+/// let random = random_new();
+/// let bumpy = BumpMap::new(base, Arc::new(PerlinNoiseTexture::new(&*random, 20.0)))
+///     .with_strength(0.05);
+/// assert_ne!(bumpy.shading_normal(&hit), hit.normal);
+/// ```
+#[derive(Debug)]
+pub struct BumpMap {
+    material: Arc<dyn Material>,
+    texture: Arc<dyn Texture>,
+    strength: f64,
+    delta: f64,
+}
+
+impl BumpMap {
+    pub fn new(material: Arc<dyn Material>, texture: Arc<dyn Texture>) -> Self {
+        Self {
+            material,
+            texture,
+            strength: 1.0,
+            delta: 1e-3,
+        }
+    }
+
+    /// Scales the height of the bumps, negative values turn bumps into dents.
+    pub fn with_strength(mut self, strength: f64) -> Self {
+        self.strength = strength;
+        self
+    }
+
+    /// Sets the step used to measure the slope of the height texture. Larger
+    /// steps smooth out detail finer than the step.
+    pub fn with_delta(mut self, delta: f64) -> Self {
+        self.delta = delta.abs().max(f64::EPSILON);
+        self
+    }
+
+    /// Returns the normal of `hit` bent by the slope of the height texture.
+    pub fn shading_normal(&self, hit: &HitRecord) -> Vector3 {
+        let (tangent, bitangent) = hit.tangent_frame();
+        // Looked up at a hit moved along the surface, so textures which
+        // depend on the surface, such as triplanar projections, bump as they look
+        let mut moved = hit.clone();
+        let mut height = |du: f64, dv: f64| {
+            moved.pt = hit.pt + tangent * du + bitangent * dv;
+            moved.u = hit.u + du;
+            moved.v = hit.v + dv;
+            self.texture.value_at(&moved).luminance()
+        };
+
+        let d = self.delta;
+        let dh_du = (height(d, 0.0) - height(-d, 0.0)) / (2.0 * d);
+        let dh_dv = (height(0.0, d) - height(0.0, -d)) / (2.0 * d);
+
+        let perturbed = hit.normal - (tangent * dh_du + bitangent * dh_dv) * self.strength;
+        if perturbed.is_near_zero() {
+            hit.normal
+        } else {
+            perturbed.unit()
+        }
+    }
+
+    fn perturb(&self, hit: &HitRecord) -> HitRecord {
+        let mut hit = hit.clone();
+        hit.normal = self.shading_normal(&hit);
+        hit
+    }
+}
+
+impl Material for BumpMap {
+    fn scatter(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
+        self.material.scatter(ctx, r_in, &self.perturb(hit))
+    }
+
+    fn emitted(&self, r_in: &Ray, hit: &HitRecord, u: f64, v: f64, pt: Vector3) -> Color {
+        self.material.emitted(r_in, hit, u, v, pt)
+    }
+
+    fn scattering_pdf(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+    ) -> f64 {
+        self.material
+            .scattering_pdf(ctx, r_in, &self.perturb(hit), scattered)
+    }
+
+    fn scattering_color(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+        attenuation: Color,
+    ) -> Color {
+        self.material
+            .scattering_color(ctx, r_in, &self.perturb(hit), scattered, attenuation)
+    }
+
+    fn is_cutout(&self, hit: &HitRecord) -> bool {
+        self.material.is_cutout(hit)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn is_specular(&self) -> bool {
+        self.material.is_specular()
+    }
+}
@@ -1,28 +1,84 @@
 use core::f64;
+use std::sync::Arc;
 
 use crate::{
     Color, Ray, RenderContext,
-    material::{Material, PdfOrRay, ScatterResult},
+    material::{Material, MaterialParameters, PdfOrRay, ScatterResult},
     object::HitRecord,
+    utils::math,
 };
 
+/// A glass-like material which reflects or refracts every ray.
+///
+/// Colored glass absorbs light following the Beer–Lambert law, so the color
+/// deepens with the distance traveled inside, thick glass being darker than
+/// thin glass of the same material.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Color, material::Dielectric};
+///
+/// // light keeps 80% of its red after traveling 2 units through the glass
+/// let glass = Dielectric::new(1.5).with_absorption(Color::new(0.8, 0.2, 0.2), 2.0);
+/// let absorption = glass.get_absorption();
+/// assert!(absorption.r < absorption.g);
+/// assert_eq!(absorption.g, absorption.b);
+///
+/// assert_eq!(Dielectric::new(1.5).get_absorption(), Color::BLACK);
+/// ```
 #[derive(Debug)]
 pub struct Dielectric {
     /// Refractive index in vacuum or air, or the ratio of the material's refractive index over
     /// the refractive index of the enclosing media
     refraction_index: f64,
+    /// Fraction of each color channel absorbed per unit of distance traveled inside
+    absorption: Color,
 }
 
 impl Dielectric {
     pub fn new(refraction_index: f64) -> Self {
-        Self { refraction_index }
+        Self {
+            refraction_index,
+            absorption: Color::BLACK,
+        }
+    }
+
+    /// Tints the glass so that light traveling `distance` inside it is left
+    /// with `color`, a channel of 1 is never absorbed.
+    pub fn with_absorption(mut self, color: Color, distance: f64) -> Self {
+        let coefficient = |c: f64| -math::ln(c.clamp(1e-6, 1.0)) / distance.max(1e-6);
+        self.absorption = Color::new(
+            coefficient(color.r),
+            coefficient(color.g),
+            coefficient(color.b),
+        );
+        self
+    }
+
+    pub fn get_refraction_index(&self) -> f64 {
+        self.refraction_index
+    }
+
+    /// Returns the absorption coefficient of each color channel, per unit of distance.
+    pub fn get_absorption(&self) -> Color {
+        self.absorption
+    }
+
+    /// Light remaining after traveling `distance` through the glass.
+    fn transmittance(&self, distance: f64) -> Color {
+        Color::new(
+            math::exp(-self.absorption.r * distance),
+            math::exp(-self.absorption.g * distance),
+            math::exp(-self.absorption.b * distance),
+        )
     }
 
     /// Use Schlick's approximation for reflectance.
     fn reflectance(&self, cosine: f64, refraction_index: f64) -> f64 {
         let r0 = (1.0 - refraction_index) / (1.0 + refraction_index);
         let r0 = r0 * r0;
-        r0 + (1.0 - r0) * (1.0 - cosine).powi(5)
+        r0 + (1.0 - r0) * math::powi(1.0 - cosine, 5)
     }
 }
 
@@ -45,9 +101,34 @@ impl Material for Dielectric {
             unit_direction.refract(hit.normal, ri)
         };
 
+        // Hitting the back face ends a path through the inside of the glass
+        let attenuation = if hit.front_face {
+            Color::WHITE
+        } else {
+            self.transmittance(hit.t * r_in.direction.length())
+        };
+
         Some(ScatterResult {
-            attenuation: Color::WHITE,
+            attenuation,
             pdf_or_ray: PdfOrRay::Ray(Ray::new_with_time(hit.pt, direction, r_in.time)),
         })
     }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+
+    fn parameters(&self) -> MaterialParameters {
+        MaterialParameters {
+            ior: Some(self.refraction_index),
+            ..Default::default()
+        }
+    }
+
+    fn with_parameters(&self, parameters: &MaterialParameters) -> Option<Arc<dyn Material>> {
+        Some(Arc::new(Dielectric {
+            refraction_index: parameters.ior.unwrap_or(self.refraction_index),
+            absorption: self.absorption,
+        }))
+    }
 }
@@ -2,25 +2,83 @@ use std::sync::Arc;
 
 use crate::{
     Color, Ray, RenderContext, Vector3,
-    material::{Material, ScatterResult},
+    material::{Material, MaterialParameters, ScatterResult},
     object::HitRecord,
     texture::{SolidColor, Texture},
 };
 
+/// Emits light from the front face of a surface. The emission comes from a
+/// texture, so an image can light a scene like a TV screen or a softbox with a
+/// printed pattern, and is scaled by an intensity multiplier.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Ray, Vector3,
+///     material::{DiffuseLight, Lambertian, Material},
+///     object::HitRecord,
+///     texture::SolidColor,
+/// };
+///
+/// let screen = DiffuseLight::new(Arc::new(SolidColor::new(Color::new(0.5, 0.25, 1.0))))
+///     .with_intensity(4.0);
+///
+/// let hit = HitRecord {
+///     pt: Vector3::new(0.5, 0.5, 0.5),
+///     normal: Vector3::new(0.0, 0.0, 1.0),
+///     tangent: Vector3::ZERO,
+///     t: 1.0,
+///     u: 0.5,
+///     v: 0.5,
+///     front_face: true,
+///     material: Arc::new(Lambertian::new_from_color(Color::BLACK)),
+///     object_id: 0,
+/// };
+/// let ray = Ray::new(Vector3::new(0.5, 0.5, 2.0), Vector3::new(0.0, 0.0, -1.0));
+/// assert_eq!(screen.emitted(&ray, &hit, hit.u, hit.v, hit.pt), Color::new(2.0, 1.0, 4.0));
+/// ```
 #[derive(Debug)]
 pub struct DiffuseLight {
     texture: Arc<dyn Texture>,
+    intensity: f64,
+    tint: Color,
 }
 
 impl DiffuseLight {
     pub fn new(texture: Arc<dyn Texture>) -> Self {
-        Self { texture }
+        Self {
+            texture,
+            intensity: 1.0,
+            tint: Color::WHITE,
+        }
     }
 
     pub fn new_from_color(emit: Color) -> Self {
-        Self {
-            texture: Arc::new(SolidColor::new(emit)),
-        }
+        Self::new(Arc::new(SolidColor::new(emit)))
+    }
+
+    /// Multiplies the emitted light, so textures with colors in [0, 1] can
+    /// light a scene.
+    pub fn with_intensity(mut self, intensity: f64) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    /// Tints the emitted light with the color of a black body at the given
+    /// temperature in Kelvin, see [`Color::from_temperature`].
+    pub fn with_temperature(mut self, kelvin: f64) -> Self {
+        self.tint = Color::from_temperature(kelvin);
+        self
+    }
+
+    pub fn get_intensity(&self) -> f64 {
+        self.intensity
+    }
+
+    pub fn get_tint(&self) -> Color {
+        self.tint
     }
 }
 
@@ -34,11 +92,33 @@ impl Material for DiffuseLight {
         None
     }
 
-    fn emitted(&self, _r_in: &Ray, hit: &HitRecord, u: f64, v: f64, pt: Vector3) -> Color {
+    fn emitted(&self, _r_in: &Ray, hit: &HitRecord, _u: f64, _v: f64, _pt: Vector3) -> Color {
         if hit.front_face {
-            self.texture.value(u, v, pt)
+            // Looked up at the hit, so textures which depend on the surface,
+            // such as triplanar projections, light the scene as they look
+            self.texture.value_at(hit) * self.tint * self.intensity
         } else {
             Color::BLACK
         }
     }
+
+    fn is_emissive(&self) -> bool {
+        self.intensity > 0.0
+    }
+
+    fn parameters(&self) -> MaterialParameters {
+        MaterialParameters {
+            color: self.texture.solid_color(),
+            ..Default::default()
+        }
+    }
+
+    fn with_parameters(&self, parameters: &MaterialParameters) -> Option<Arc<dyn Material>> {
+        let color = self.texture.solid_color()?;
+        Some(Arc::new(DiffuseLight {
+            texture: Arc::new(SolidColor::new(parameters.color.unwrap_or(color))),
+            intensity: self.intensity,
+            tint: self.tint,
+        }))
+    }
 }
@@ -0,0 +1,122 @@
+use std::sync::{Arc, RwLock};
+
+use crate::{
+    Color, Ray, RenderContext, Vector3,
+    material::{Material, MaterialParameters, ScatterResult},
+    object::HitRecord,
+};
+
+/// A material whose parameters can be changed after the scene is built, so a
+/// user interface can tweak colors and finishes and render again without
+/// building the scene again. Every surface sharing the material sees the
+/// change.
+///
+/// Reading the material takes a lock on every ray, so scenes are only built
+/// with editable materials when they are going to be edited.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color,
+///     material::{EditableMaterial, Material, MaterialParameters, Metal},
+/// };
+///
+/// let material = EditableMaterial::new(Arc::new(Metal::new(Color::WHITE, 0.1)));
+/// assert_eq!(material.parameters().fuzz, Some(0.1));
+///
+/// assert!(material.set_parameters(&MaterialParameters {
+///     fuzz: Some(0.4),
+///     ..Default::default()
+/// }));
+/// assert_eq!(material.parameters().fuzz, Some(0.4));
+/// assert_eq!(material.parameters().color, Some(Color::WHITE));
+/// ```
+#[derive(Debug)]
+pub struct EditableMaterial {
+    material: RwLock<Arc<dyn Material>>,
+}
+
+impl EditableMaterial {
+    pub fn new(material: Arc<dyn Material>) -> Self {
+        Self {
+            material: RwLock::new(material),
+        }
+    }
+
+    /// Returns the material currently rendered.
+    pub fn get_material(&self) -> Arc<dyn Material> {
+        self.material.read().unwrap().clone()
+    }
+
+    /// Changes the given parameters, returning false when the material has no
+    /// editable parameters, such as textured materials.
+    pub fn set_parameters(&self, parameters: &MaterialParameters) -> bool {
+        let mut material = self.material.write().unwrap();
+        match material.with_parameters(parameters) {
+            Some(edited) => {
+                *material = edited;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl Material for EditableMaterial {
+    fn scatter(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
+        self.get_material().scatter(ctx, r_in, hit)
+    }
+
+    fn emitted(&self, r_in: &Ray, hit: &HitRecord, u: f64, v: f64, pt: Vector3) -> Color {
+        self.get_material().emitted(r_in, hit, u, v, pt)
+    }
+
+    fn scattering_pdf(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+    ) -> f64 {
+        self.get_material()
+            .scattering_pdf(ctx, r_in, hit, scattered)
+    }
+
+    fn scattering_color(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+        attenuation: Color,
+    ) -> Color {
+        self.get_material()
+            .scattering_color(ctx, r_in, hit, scattered, attenuation)
+    }
+
+    fn is_cutout(&self, hit: &HitRecord) -> bool {
+        self.get_material().is_cutout(hit)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.get_material().is_emissive()
+    }
+
+    fn is_specular(&self) -> bool {
+        self.get_material().is_specular()
+    }
+
+    fn albedo(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Color {
+        self.get_material().albedo(ctx, r_in, hit)
+    }
+
+    fn parameters(&self) -> MaterialParameters {
+        self.get_material().parameters()
+    }
+
+    fn with_parameters(&self, parameters: &MaterialParameters) -> Option<Arc<dyn Material>> {
+        self.get_material().with_parameters(parameters)
+    }
+}
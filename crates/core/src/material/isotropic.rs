@@ -28,7 +28,7 @@ impl Isotropic {
 impl Material for Isotropic {
     fn scatter(&self, _ctx: &RenderContext, _r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
         Some(ScatterResult {
-            attenuation: self.texture.value(hit.u, hit.v, hit.pt),
+            attenuation: self.texture.value_at(hit),
             pdf_or_ray: PdfOrRay::Pdf(Arc::new(SpherePdf::new())),
         })
     }
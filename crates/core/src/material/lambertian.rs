@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use crate::{
     Color, CosinePdf, Ray, RenderContext,
-    material::{Material, PdfOrRay, ScatterResult},
+    material::{Material, MaterialParameters, PdfOrRay, ScatterResult},
     object::HitRecord,
     texture::{SolidColor, Texture},
 };
@@ -28,7 +28,7 @@ impl Lambertian {
 impl Material for Lambertian {
     fn scatter(&self, _ctx: &RenderContext, _r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
         Some(ScatterResult {
-            attenuation: self.texture.value(hit.u, hit.v, hit.pt),
+            attenuation: self.texture.value_at(hit),
             pdf_or_ray: PdfOrRay::Pdf(Arc::new(CosinePdf::new(hit.normal))),
         })
     }
@@ -47,4 +47,18 @@ impl Material for Lambertian {
             cos_theta / f64::consts::PI
         }
     }
+
+    fn parameters(&self) -> MaterialParameters {
+        MaterialParameters {
+            color: self.texture.solid_color(),
+            ..Default::default()
+        }
+    }
+
+    fn with_parameters(&self, parameters: &MaterialParameters) -> Option<Arc<dyn Material>> {
+        let color = self.texture.solid_color()?;
+        Some(Arc::new(Lambertian::new_from_color(
+            parameters.color.unwrap_or(color),
+        )))
+    }
 }
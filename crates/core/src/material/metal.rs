@@ -1,6 +1,8 @@
+use std::sync::Arc;
+
 use crate::{
     Color, Ray, RenderContext, Vector3,
-    material::{Material, PdfOrRay, ScatterResult},
+    material::{Material, MaterialParameters, PdfOrRay, ScatterResult},
     object::HitRecord,
 };
 
@@ -26,4 +28,23 @@ impl Material for Metal {
             pdf_or_ray: PdfOrRay::Ray(Ray::new_with_time(hit.pt, reflected, r_in.time)),
         })
     }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+
+    fn parameters(&self) -> MaterialParameters {
+        MaterialParameters {
+            color: Some(self.albedo),
+            fuzz: Some(self.fuzz),
+            ior: None,
+        }
+    }
+
+    fn with_parameters(&self, parameters: &MaterialParameters) -> Option<Arc<dyn Material>> {
+        Some(Arc::new(Metal::new(
+            parameters.color.unwrap_or(self.albedo),
+            parameters.fuzz.unwrap_or(self.fuzz),
+        )))
+    }
 }
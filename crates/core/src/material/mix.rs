@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use crate::{
+    Color, Ray, RenderContext, Vector3,
+    material::{Material, ScatterResult},
+    object::HitRecord,
+    texture::{SolidColor, Texture},
+};
+
+/// Blends two materials, for effects like patches of rust on metal driven by a
+/// noise texture.
+///
+/// The luminance of the mask at the hit point is the weight of the second
+/// material, so black shows only the first material and white only the second.
+/// Each ray scatters off one of the two materials, picked with a probability
+/// equal to its weight, which averages to the blend over many samples. Where
+/// only one of the materials is cut away, the surface is cut away with that
+/// material's weight.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Vector3,
+///     material::{AlphaMask, Lambertian, Material, Metal, MixMaterial},
+///     object::HitRecord,
+///     texture::SolidColor,
+/// };
+///
+/// let metal = Arc::new(Metal::new(Color::new(0.7, 0.7, 0.8), 0.1));
+/// let rust = Arc::new(Lambertian::new_from_color(Color::new(0.5, 0.2, 0.1)));
+/// let mix = MixMaterial::new_from_factor(metal.clone(), rust.clone(), 0.25);
+///
+/// let hit = HitRecord {
+///     pt: Vector3::ZERO,
+///     normal: Vector3::new(0.0, 0.0, 1.0),
+///     tangent: Vector3::new(1.0, 0.0, 0.0),
+///     t: 1.0,
+///     u: 0.5,
+///     v: 0.5,
+///     front_face: true,
+///     material: rust,
+///     object_id: 0,
+/// };
+/// assert_eq!(mix.factor(&hit), 0.25);
+///
+/// let hole = Arc::new(AlphaMask::new(metal.clone(), Arc::new(SolidColor::new(Color::BLACK))));
+/// assert!(MixMaterial::new_from_factor(metal.clone(), hole.clone(), 1.0).is_cutout(&hit));
+/// assert!(!MixMaterial::new_from_factor(metal, hole, 0.0).is_cutout(&hit));
+/// ```
+#[derive(Debug)]
+pub struct MixMaterial {
+    a: Arc<dyn Material>,
+    b: Arc<dyn Material>,
+    mask: Arc<dyn Texture>,
+}
+
+impl MixMaterial {
+    pub fn new(a: Arc<dyn Material>, b: Arc<dyn Material>, mask: Arc<dyn Texture>) -> Self {
+        Self { a, b, mask }
+    }
+
+    /// Blends the materials evenly over the surface, `factor` being the weight of `b`.
+    pub fn new_from_factor(a: Arc<dyn Material>, b: Arc<dyn Material>, factor: f64) -> Self {
+        let factor = factor.clamp(0.0, 1.0);
+        Self::new(
+            a,
+            b,
+            Arc::new(SolidColor::new(Color::new(factor, factor, factor))),
+        )
+    }
+
+    /// Returns the weight of the second material at the hit point, in [0, 1].
+    pub fn factor(&self, hit: &HitRecord) -> f64 {
+        self.mask.value_at(hit).luminance().clamp(0.0, 1.0)
+    }
+
+    /// Picks the material a ray scatters off. The choice must be the same in
+    /// `scatter` and `scattering_color`, so rather than drawing a random number
+    /// it hashes the hit point and ray direction, which vary between samples.
+    fn select(&self, r_in: &Ray, hit: &HitRecord) -> &Arc<dyn Material> {
+        let seed = [
+            hit.pt.x,
+            hit.pt.y,
+            hit.pt.z,
+            r_in.direction.x,
+            r_in.direction.y,
+            r_in.direction.z,
+        ];
+        if self.picks_b(hit, &seed) {
+            &self.b
+        } else {
+            &self.a
+        }
+    }
+
+    /// Returns whether `b` is picked at the hit, with a probability equal to
+    /// its weight, by hashing `seed`.
+    fn picks_b(&self, hit: &HitRecord, seed: &[f64]) -> bool {
+        let factor = self.factor(hit);
+        if factor <= 0.0 {
+            return false;
+        }
+        if factor >= 1.0 {
+            return true;
+        }
+
+        let mut hash = 0x9E37_79B9_7F4A_7C15_u64;
+        for v in seed {
+            hash = splitmix64(hash ^ v.to_bits());
+        }
+        let rand = (hash >> 11) as f64 / (1u64 << 53) as f64;
+        rand < factor
+    }
+}
+
+/// Scrambles the bits of `x`, from the SplitMix64 generator.
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+impl Material for MixMaterial {
+    fn scatter(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
+        self.select(r_in, hit).scatter(ctx, r_in, hit)
+    }
+
+    fn emitted(&self, r_in: &Ray, hit: &HitRecord, u: f64, v: f64, pt: Vector3) -> Color {
+        let factor = self.factor(hit);
+        self.a.emitted(r_in, hit, u, v, pt) * (1.0 - factor)
+            + self.b.emitted(r_in, hit, u, v, pt) * factor
+    }
+
+    fn scattering_pdf(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+    ) -> f64 {
+        self.select(r_in, hit)
+            .scattering_pdf(ctx, r_in, hit, scattered)
+    }
+
+    fn scattering_color(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+        attenuation: Color,
+    ) -> Color {
+        self.select(r_in, hit)
+            .scattering_color(ctx, r_in, hit, scattered, attenuation)
+    }
+
+    fn is_cutout(&self, hit: &HitRecord) -> bool {
+        let a = self.a.is_cutout(hit);
+        let b = self.b.is_cutout(hit);
+        if a == b {
+            return a;
+        }
+        // No ray is given here, the hit point still varies between samples
+        if self.picks_b(hit, &[hit.pt.x, hit.pt.y, hit.pt.z]) {
+            b
+        } else {
+            a
+        }
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.a.is_emissive() || self.b.is_emissive()
+    }
+
+    fn is_specular(&self) -> bool {
+        self.a.is_specular() || self.b.is_specular()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::MixMaterial;
+    use crate::{
+        Color, Vector3,
+        material::{AlphaMask, Lambertian, Material},
+        object::HitRecord,
+        texture::SolidColor,
+    };
+
+    #[test]
+    fn cut_away_with_the_weight_of_the_cut_material() {
+        let leaf = Arc::new(Lambertian::new_from_color(Color::new(0.2, 0.6, 0.1)));
+        let hole = Arc::new(AlphaMask::new(
+            leaf.clone(),
+            Arc::new(SolidColor::new(Color::BLACK)),
+        ));
+        let mix = MixMaterial::new_from_factor(leaf.clone(), hole, 0.25);
+
+        let count = 10_000;
+        let cut = (0..count)
+            .filter(|i| {
+                let hit = HitRecord {
+                    pt: Vector3::new(*i as f64 * 0.01, 0.5, 0.0),
+                    normal: Vector3::new(0.0, 0.0, 1.0),
+                    tangent: Vector3::new(1.0, 0.0, 0.0),
+                    t: 1.0,
+                    u: 0.5,
+                    v: 0.5,
+                    front_face: true,
+                    material: leaf.clone(),
+                    object_id: 0,
+                };
+                mix.is_cutout(&hit)
+            })
+            .count();
+        let fraction = cut as f64 / count as f64;
+        assert!((fraction - 0.25).abs() < 0.02, "{fraction}");
+    }
+}
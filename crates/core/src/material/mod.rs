@@ -2,19 +2,37 @@ use std::{fmt::Debug, sync::Arc};
 
 use crate::{Color, ProbabilityDensityFunction, Ray, RenderContext, Vector3, object::HitRecord};
 
+pub mod alpha_mask;
+pub mod bump_map;
 pub mod dielectric;
 pub mod diffuse_light;
+pub mod editable;
 pub mod empty;
+pub mod ggx_metal;
 pub mod isotropic;
 pub mod lambertian;
 pub mod metal;
+pub mod mix;
+pub mod normal_map;
+pub mod principled;
+pub mod sheen;
+pub mod toon;
 
+pub use alpha_mask::AlphaMask;
+pub use bump_map::BumpMap;
 pub use dielectric::Dielectric;
 pub use diffuse_light::DiffuseLight;
+pub use editable::EditableMaterial;
 pub use empty::EmptyMaterial;
+pub use ggx_metal::GgxMetal;
 pub use isotropic::Isotropic;
 pub use lambertian::Lambertian;
 pub use metal::Metal;
+pub use mix::MixMaterial;
+pub use normal_map::NormalMap;
+pub use principled::Principled;
+pub use sheen::Sheen;
+pub use toon::Toon;
 
 pub trait Material: Debug + Send + Sync {
     fn scatter(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult>;
@@ -32,6 +50,85 @@ pub trait Material: Debug + Send + Sync {
     ) -> f64 {
         0.0
     }
+
+    /// Returns the light reflected into `scattered` per unit of incoming light,
+    /// the BRDF times the cosine of the scattered direction. The default is the
+    /// attenuation from `scatter` times `scattering_pdf`, materials whose lobes
+    /// have different colors override this instead.
+    fn scattering_color(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+        attenuation: Color,
+    ) -> Color {
+        attenuation * self.scattering_pdf(ctx, r_in, hit, scattered)
+    }
+
+    /// Returns true where the surface is cut away, rays hitting there continue
+    /// as if nothing was hit.
+    fn is_cutout(&self, _hit: &HitRecord) -> bool {
+        false
+    }
+
+    /// Returns true if surfaces using the material emit light, so they are
+    /// sampled directly as lights.
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    /// Returns true if the material reflects or refracts light in a single
+    /// direction, like mirrors and glass, so it can focus light into
+    /// caustics.
+    fn is_specular(&self) -> bool {
+        false
+    }
+
+    /// Returns the color of the surface without lighting, the albedo output
+    /// given to denoisers. The default is the attenuation of a scattered ray
+    /// plus the emitted light.
+    fn albedo(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Color {
+        let emitted = self.emitted(r_in, hit, hit.u, hit.v, hit.pt);
+        match self.scatter(ctx, r_in, hit) {
+            Some(scatter_result) => scatter_result.attenuation + emitted,
+            None => emitted,
+        }
+    }
+
+    /// Returns the parameters of the material which can be edited after the
+    /// scene is loaded, see [`EditableMaterial`].
+    fn parameters(&self) -> MaterialParameters {
+        MaterialParameters::default()
+    }
+
+    /// Returns a copy of the material with the given parameters changed, or
+    /// `None` when the material has no editable parameters. Parameters the
+    /// material does not have are ignored.
+    fn with_parameters(&self, _parameters: &MaterialParameters) -> Option<Arc<dyn Material>> {
+        None
+    }
+}
+
+/// Parameters shared by several materials, each `None` when the material does
+/// not have it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MaterialParameters {
+    /// Color of the surface or of the emitted light, only for materials with a
+    /// solid color rather than a texture
+    pub color: Option<Color>,
+    /// Blur of reflections, the fuzz of metals or the roughness of principled
+    /// materials
+    pub fuzz: Option<f64>,
+    /// Index of refraction
+    pub ior: Option<f64>,
+}
+
+impl PartialEq for dyn Material {
+    /// Materials are equal when they are the same instance.
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
 }
 
 pub enum PdfOrRay {
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use crate::{
+    Color, Ray, RenderContext, Vector3,
+    material::{Material, ScatterResult},
+    object::HitRecord,
+    texture::Texture,
+};
+
+/// Wraps another material and bends its shading normal with a tangent-space
+/// normal map, adding surface detail without extra geometry.
+///
+/// Each texel's red, green and blue channels map from [0, 1] to the [-1, 1]
+/// components of a normal along the surface tangent, bitangent and normal, so
+/// the flat color (0.5, 0.5, 1.0) leaves the surface unchanged. The tangent is
+/// the direction of increasing `u` reported by the primitive. Load image normal
+/// maps with [`crate::texture::ColorSpace::Data`] so they are not gamma decoded.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Vector3,
+///     material::{Lambertian, NormalMap},
+///     object::HitRecord,
+///     texture::SolidColor,
+/// };
+///
+/// let base = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.8, 0.8)));
+/// // Tilt the normal all the way towards the tangent
+/// let normal_map = NormalMap::new(base.clone(), Arc::new(SolidColor::new(Color::new(1.0, 0.5, 0.5))));
+///
+/// let hit = HitRecord {
+///     pt: Vector3::ZERO,
+///     normal: Vector3::new(0.0, 0.0, 1.0),
+///     tangent: Vector3::new(1.0, 0.0, 0.0),
+///     t: 1.0,
+///     u: 0.5,
+///     v: 0.5,
+///     front_face: true,
+///     material: base,
+///     object_id: 0,
+/// };
+/// assert_eq!(normal_map.shading_normal(&hit), Vector3::new(1.0, 0.0, 0.0));
+/// ```
+#[derive(Debug)]
+pub struct NormalMap {
+    material: Arc<dyn Material>,
+    texture: Arc<dyn Texture>,
+    strength: f64,
+}
+
+impl NormalMap {
+    pub fn new(material: Arc<dyn Material>, texture: Arc<dyn Texture>) -> Self {
+        Self {
+            material,
+            texture,
+            strength: 1.0,
+        }
+    }
+
+    /// Scales how far the map tilts the normal, 0 leaves the surface flat.
+    pub fn with_strength(mut self, strength: f64) -> Self {
+        self.strength = strength;
+        self
+    }
+
+    /// Returns the normal of `hit` bent by the normal map, on the same side of
+    /// the surface as `hit.normal`.
+    pub fn shading_normal(&self, hit: &HitRecord) -> Vector3 {
+        let normal = hit.normal;
+        let (tangent, bitangent) = hit.tangent_frame();
+
+        let Color { r, g, b } = self.texture.value_at(hit);
+        let x = (2.0 * r - 1.0) * self.strength;
+        let y = (2.0 * g - 1.0) * self.strength;
+        let z = (2.0 * b - 1.0).max(0.0);
+
+        let perturbed = tangent * x + bitangent * y + normal * z;
+        if perturbed.is_near_zero() {
+            normal
+        } else {
+            perturbed.unit()
+        }
+    }
+
+    fn perturb(&self, hit: &HitRecord) -> HitRecord {
+        let mut hit = hit.clone();
+        hit.normal = self.shading_normal(&hit);
+        hit
+    }
+}
+
+impl Material for NormalMap {
+    fn scatter(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
+        self.material.scatter(ctx, r_in, &self.perturb(hit))
+    }
+
+    fn emitted(&self, r_in: &Ray, hit: &HitRecord, u: f64, v: f64, pt: Vector3) -> Color {
+        self.material.emitted(r_in, hit, u, v, pt)
+    }
+
+    fn scattering_pdf(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+    ) -> f64 {
+        self.material
+            .scattering_pdf(ctx, r_in, &self.perturb(hit), scattered)
+    }
+
+    fn scattering_color(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+        attenuation: Color,
+    ) -> Color {
+        self.material
+            .scattering_color(ctx, r_in, &self.perturb(hit), scattered, attenuation)
+    }
+
+    fn is_cutout(&self, hit: &HitRecord) -> bool {
+        self.material.is_cutout(hit)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn is_specular(&self) -> bool {
+        self.material.is_specular()
+    }
+}
@@ -0,0 +1,348 @@
+use std::sync::Arc;
+
+use crate::{
+    Color, CosinePdf, GgxPdf, ProbabilityDensityFunction, Ray, RenderContext, Vector3,
+    material::{Material, MaterialParameters, PdfOrRay, ScatterResult, sheen::sheen_brdf_cos},
+    object::HitRecord,
+    texture::{SolidColor, Texture},
+    utils::math,
+};
+
+/// A Disney style principled material combining a diffuse base, a GGX
+/// specular layer, glass-like transmission and emission, controlled by a few
+/// artist friendly parameters in [0, 1]. It is the natural target for PBR
+/// materials from formats such as glTF.
+///
+/// - `metallic` blends from a dielectric, with a white specular highlight over a
+///   diffuse base, to a metal that reflects tinted by the base color.
+/// - `roughness` widens the specular highlight.
+/// - `specular` scales the reflectance of dielectrics at normal incidence, 0.5
+///   is the common 4%.
+/// - `transmission` turns the dielectric part into glass refracting with `ior`.
+/// - `sheen` adds a layer of fibers over the diffuse base which brightens
+///   grazing angles like cloth, see [`crate::material::Sheen`].
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Color, material::Principled};
+///
+/// let gold = Principled::new_from_color(Color::new(1.0, 0.78, 0.34))
+///     .with_metallic(1.0)
+///     .with_roughness(0.3);
+/// assert_eq!(gold.get_metallic(), 1.0);
+///
+/// let glass = Principled::new_from_color(Color::WHITE)
+///     .with_roughness(0.0)
+///     .with_transmission(1.0)
+///     .with_ior(1.5);
+/// assert_eq!(glass.get_transmission(), 1.0);
+///
+/// let satin = Principled::new_from_color(Color::new(0.1, 0.2, 0.6))
+///     .with_sheen(1.0)
+///     .with_sheen_tint(Color::new(0.6, 0.7, 1.0));
+/// assert_eq!(satin.get_sheen(), 1.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Principled {
+    base_color: Arc<dyn Texture>,
+    metallic: f64,
+    roughness: f64,
+    specular: f64,
+    transmission: f64,
+    ior: f64,
+    emission: Color,
+    sheen: f64,
+    sheen_tint: Color,
+    sheen_roughness: f64,
+}
+
+impl Principled {
+    pub fn new(base_color: Arc<dyn Texture>) -> Self {
+        Self {
+            base_color,
+            metallic: 0.0,
+            roughness: 0.5,
+            specular: 0.5,
+            transmission: 0.0,
+            ior: 1.5,
+            emission: Color::BLACK,
+            sheen: 0.0,
+            sheen_tint: Color::WHITE,
+            sheen_roughness: 0.5,
+        }
+    }
+
+    pub fn new_from_color(base_color: Color) -> Self {
+        Self::new(Arc::new(SolidColor::new(base_color)))
+    }
+
+    pub fn with_metallic(mut self, metallic: f64) -> Self {
+        self.metallic = metallic.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.roughness = roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_specular(mut self, specular: f64) -> Self {
+        self.specular = specular.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_transmission(mut self, transmission: f64) -> Self {
+        self.transmission = transmission.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the index of refraction used for transmission.
+    pub fn with_ior(mut self, ior: f64) -> Self {
+        self.ior = ior.max(1.0);
+        self
+    }
+
+    /// Sets the light emitted from the front face, on top of any reflected light.
+    pub fn with_emission(mut self, emission: Color) -> Self {
+        self.emission = emission;
+        self
+    }
+
+    pub fn with_sheen(mut self, sheen: f64) -> Self {
+        self.sheen = sheen.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the color of the light reflected by the sheen.
+    pub fn with_sheen_tint(mut self, sheen_tint: Color) -> Self {
+        self.sheen_tint = sheen_tint;
+        self
+    }
+
+    pub fn with_sheen_roughness(mut self, sheen_roughness: f64) -> Self {
+        self.sheen_roughness = sheen_roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn get_metallic(&self) -> f64 {
+        self.metallic
+    }
+
+    pub fn get_roughness(&self) -> f64 {
+        self.roughness
+    }
+
+    pub fn get_specular(&self) -> f64 {
+        self.specular
+    }
+
+    pub fn get_transmission(&self) -> f64 {
+        self.transmission
+    }
+
+    pub fn get_ior(&self) -> f64 {
+        self.ior
+    }
+
+    pub fn get_emission(&self) -> Color {
+        self.emission
+    }
+
+    pub fn get_sheen(&self) -> f64 {
+        self.sheen
+    }
+
+    pub fn get_sheen_tint(&self) -> Color {
+        self.sheen_tint
+    }
+
+    pub fn get_sheen_roughness(&self) -> f64 {
+        self.sheen_roughness
+    }
+
+    /// Probability of refracting through the surface instead of reflecting.
+    fn transmission_probability(&self) -> f64 {
+        (1.0 - self.metallic) * self.transmission
+    }
+
+    /// Probability of sampling the specular lobe rather than the diffuse one.
+    fn specular_probability(&self) -> f64 {
+        0.5 + 0.5 * self.metallic
+    }
+
+    fn specular_pdf(&self, r_in: &Ray, hit: &HitRecord) -> GgxPdf {
+        let (tangent, bitangent) = hit.tangent_frame();
+        let alpha = self.roughness * self.roughness;
+        GgxPdf::new(
+            tangent,
+            bitangent,
+            hit.normal,
+            -r_in.direction.unit(),
+            alpha,
+            alpha,
+        )
+    }
+
+    /// Reflectance at normal incidence, white for dielectrics and the base
+    /// color for metals.
+    fn f0(&self, base_color: Color) -> Color {
+        let dielectric = Color::WHITE * (0.08 * self.specular);
+        dielectric * (1.0 - self.metallic) + base_color * self.metallic
+    }
+
+    fn refract(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Vector3 {
+        let ri = if hit.front_face {
+            1.0 / self.ior
+        } else {
+            self.ior
+        };
+
+        let unit_direction = r_in.direction.unit();
+        let cos_theta = (-unit_direction).dot(&hit.normal).min(1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+        // Schlick's approximation for the share of light reflected off the glass
+        let r0 = math::powi((1.0 - ri) / (1.0 + ri), 2);
+        let reflectance = r0 + (1.0 - r0) * math::powi(1.0 - cos_theta, 5);
+
+        if ri * sin_theta > 1.0 || reflectance > ctx.random.rand() {
+            unit_direction.reflect(hit.normal)
+        } else {
+            unit_direction.refract(hit.normal, ri)
+        }
+    }
+}
+
+/// Picks the specular lobe with probability `specular_probability` and the
+/// cosine weighted diffuse lobe otherwise.
+struct LobePdf {
+    diffuse: CosinePdf,
+    specular: GgxPdf,
+    specular_probability: f64,
+}
+
+impl ProbabilityDensityFunction for LobePdf {
+    fn value(&self, ctx: &RenderContext, direction: &Vector3) -> f64 {
+        self.specular_probability * self.specular.value(ctx, direction)
+            + (1.0 - self.specular_probability) * self.diffuse.value(ctx, direction)
+    }
+
+    fn generate(&self, ctx: &RenderContext) -> Vector3 {
+        if ctx.random.rand() < self.specular_probability {
+            self.specular.generate(ctx)
+        } else {
+            self.diffuse.generate(ctx)
+        }
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, ctx: &RenderContext, r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
+        let base_color = self.base_color.value_at(hit);
+
+        // Choosing transmission with the probability of its weight cancels the
+        // weight, so neither branch scales its attenuation
+        if ctx.random.rand() < self.transmission_probability() {
+            let direction = self.refract(ctx, r_in, hit);
+            return Some(ScatterResult {
+                attenuation: base_color,
+                pdf_or_ray: PdfOrRay::Ray(Ray::new_with_time(hit.pt, direction, r_in.time)),
+            });
+        }
+
+        Some(ScatterResult {
+            attenuation: Color::WHITE,
+            pdf_or_ray: PdfOrRay::Pdf(Arc::new(LobePdf {
+                diffuse: CosinePdf::new(hit.normal),
+                specular: self.specular_pdf(r_in, hit),
+                specular_probability: self.specular_probability(),
+            })),
+        })
+    }
+
+    fn emitted(&self, _r_in: &Ray, hit: &HitRecord, _u: f64, _v: f64, _pt: Vector3) -> Color {
+        if hit.front_face {
+            self.emission
+        } else {
+            Color::BLACK
+        }
+    }
+
+    /// The diffuse and specular lobes have different colors, so the reflected
+    /// light is evaluated here rather than through `scattering_pdf`.
+    fn scattering_color(
+        &self,
+        _ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+        attenuation: Color,
+    ) -> Color {
+        let out = scattered.direction.unit();
+        let cos_out = out.dot(&hit.normal);
+        if cos_out <= 0.0 {
+            return Color::BLACK;
+        }
+
+        let base_color = self.base_color.value_at(hit);
+        let view = -r_in.direction.unit();
+        let pdf = self.specular_pdf(r_in, hit);
+
+        let (specular, fresnel) = match pdf.half_vector(&out) {
+            Some(h) => {
+                // Schlick's approximation, using the angle to the microfacet normal
+                let cos_h = (view + out).unit().dot(&view).max(0.0);
+                let s = math::powi(1.0 - cos_h, 5);
+                let fresnel = self.f0(base_color) * (1.0 - s) + Color::WHITE * s;
+                let brdf_cos =
+                    pdf.distribution(h) * pdf.masking_shadowing(&out) / (4.0 * pdf.view_cosine());
+                (fresnel * brdf_cos, fresnel)
+            }
+            None => (Color::BLACK, Color::BLACK),
+        };
+
+        // Light reflected by the specular layer never reaches the diffuse base
+        let transmitted = Color::new(1.0 - fresnel.r, 1.0 - fresnel.g, 1.0 - fresnel.b);
+        let diffuse =
+            base_color * transmitted * ((1.0 - self.metallic) * cos_out / std::f64::consts::PI);
+
+        // Like the diffuse base, metals have no sheen
+        let sheen = self.sheen_tint
+            * ((1.0 - self.metallic)
+                * self.sheen
+                * sheen_brdf_cos(self.sheen_roughness, hit.normal, view, out));
+
+        attenuation * (diffuse + specular + sheen)
+    }
+
+    fn is_emissive(&self) -> bool {
+        self.emission.r > 0.0 || self.emission.g > 0.0 || self.emission.b > 0.0
+    }
+
+    fn is_specular(&self) -> bool {
+        self.transmission > 0.0
+    }
+
+    fn parameters(&self) -> MaterialParameters {
+        MaterialParameters {
+            color: self.base_color.solid_color(),
+            fuzz: Some(self.roughness),
+            ior: Some(self.ior),
+        }
+    }
+
+    fn with_parameters(&self, parameters: &MaterialParameters) -> Option<Arc<dyn Material>> {
+        let mut material = self.clone();
+        if let (Some(color), Some(_)) = (parameters.color, self.base_color.solid_color()) {
+            material.base_color = Arc::new(SolidColor::new(color));
+        }
+        if let Some(roughness) = parameters.fuzz {
+            material = material.with_roughness(roughness);
+        }
+        if let Some(ior) = parameters.ior {
+            material = material.with_ior(ior);
+        }
+        Some(Arc::new(material))
+    }
+}
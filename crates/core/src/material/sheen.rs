@@ -0,0 +1,131 @@
+use core::f64;
+use std::sync::Arc;
+
+use crate::{
+    Color, CosinePdf, Ray, RenderContext, Vector3,
+    material::{Material, PdfOrRay, ScatterResult},
+    object::HitRecord,
+    texture::{SolidColor, Texture},
+    utils::math,
+};
+
+/// A velvet-like material for cloth, a diffuse base under a layer of fibers
+/// which catch the light at grazing angles, brightening the silhouette.
+///
+/// The sheen lobe uses the "Charlie" distribution from Estevez and Kulla,
+/// which is also available on [`crate::material::Principled`].
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Color, material::Sheen};
+///
+/// let velvet = Sheen::new_from_color(Color::new(0.4, 0.05, 0.1))
+///     .with_sheen_color(Color::new(1.0, 0.6, 0.7))
+///     .with_roughness(0.3);
+/// assert_eq!(velvet.get_roughness(), 0.3);
+/// ```
+#[derive(Debug)]
+pub struct Sheen {
+    texture: Arc<dyn Texture>,
+    sheen_color: Color,
+    roughness: f64,
+}
+
+impl Sheen {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self {
+            texture,
+            sheen_color: Color::WHITE,
+            roughness: 0.5,
+        }
+    }
+
+    pub fn new_from_color(color: Color) -> Self {
+        Self::new(Arc::new(SolidColor::new(color)))
+    }
+
+    /// Sets the color of the light reflected by the fibers.
+    pub fn with_sheen_color(mut self, sheen_color: Color) -> Self {
+        self.sheen_color = sheen_color;
+        self
+    }
+
+    /// Sets how far from grazing angles the sheen reaches, 1 spreading it the most.
+    pub fn with_roughness(mut self, roughness: f64) -> Self {
+        self.roughness = roughness.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn get_sheen_color(&self) -> Color {
+        self.sheen_color
+    }
+
+    pub fn get_roughness(&self) -> f64 {
+        self.roughness
+    }
+}
+
+/// Sheen BRDF times the cosine of the outgoing direction, for unit vectors
+/// `view` and `out` on the side of `normal`.
+pub(crate) fn sheen_brdf_cos(roughness: f64, normal: Vector3, view: Vector3, out: Vector3) -> f64 {
+    let cos_view = normal.dot(&view);
+    let cos_out = normal.dot(&out);
+    if cos_view <= 0.0 || cos_out <= 0.0 {
+        return 0.0;
+    }
+
+    // Charlie distribution, sin(θh)^(1/α) normalized over the hemisphere
+    let inv_alpha = 1.0 / (roughness * roughness).max(1e-3);
+    let cos_h = normal.dot(&(view + out).unit());
+    let sin_h = (1.0 - cos_h * cos_h).max(0.0).sqrt();
+    let distribution = (2.0 + inv_alpha) * math::powf(sin_h, inv_alpha) / (2.0 * f64::consts::PI);
+
+    // Neubelt and Pettineo's visibility term
+    let visibility = 1.0 / (4.0 * (cos_out + cos_view - cos_out * cos_view));
+
+    distribution * visibility * cos_out
+}
+
+impl Material for Sheen {
+    fn scatter(&self, _ctx: &RenderContext, _r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
+        Some(ScatterResult {
+            attenuation: self.texture.value_at(hit),
+            pdf_or_ray: PdfOrRay::Pdf(Arc::new(CosinePdf::new(hit.normal))),
+        })
+    }
+
+    fn scattering_pdf(
+        &self,
+        _ctx: &RenderContext,
+        _r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+    ) -> f64 {
+        let cos_theta = hit.normal.dot(&scattered.direction.unit());
+        if cos_theta < 0.0 {
+            0.0
+        } else {
+            cos_theta / f64::consts::PI
+        }
+    }
+
+    /// The base and the sheen have different colors, so the reflected light is
+    /// evaluated here rather than through `scattering_pdf`.
+    fn scattering_color(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+        attenuation: Color,
+    ) -> Color {
+        let sheen = sheen_brdf_cos(
+            self.roughness,
+            hit.normal,
+            -r_in.direction.unit(),
+            scattered.direction.unit(),
+        );
+        attenuation * self.scattering_pdf(ctx, r_in, hit, scattered) + self.sheen_color * sheen
+    }
+}
@@ -0,0 +1,115 @@
+use core::f64;
+use std::sync::Arc;
+
+use crate::{
+    Color, CosinePdf, Ray, RenderContext,
+    material::{Material, PdfOrRay, ScatterResult},
+    object::HitRecord,
+    texture::{SolidColor, Texture},
+};
+
+/// A cel shaded material for illustrative renders. Light falls off in a few
+/// flat bands instead of smoothly, and the silhouette can be outlined in black.
+///
+/// It scatters like [`crate::material::Lambertian`], so lights are still
+/// sampled directly, only the cosine term is quantized.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Color, material::Toon};
+///
+/// let toon = Toon::new_from_color(Color::new(0.9, 0.3, 0.2))
+///     .with_bands(4)
+///     .with_outline(0.3);
+/// assert_eq!(toon.get_bands(), 4);
+/// assert_eq!(toon.band(0.1), 0.125);
+/// assert_eq!(toon.band(0.99), 0.875);
+/// ```
+#[derive(Debug)]
+pub struct Toon {
+    texture: Arc<dyn Texture>,
+    bands: u32,
+    outline: f64,
+}
+
+impl Toon {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self {
+            texture,
+            bands: 3,
+            outline: 0.0,
+        }
+    }
+
+    pub fn new_from_color(color: Color) -> Self {
+        Self::new(Arc::new(SolidColor::new(color)))
+    }
+
+    /// Sets the number of brightness levels, at least 1.
+    pub fn with_bands(mut self, bands: u32) -> Self {
+        self.bands = bands.max(1);
+        self
+    }
+
+    /// Draws the surface black where the cosine between the normal and the view
+    /// direction is below `outline`, 0 disables the outline.
+    pub fn with_outline(mut self, outline: f64) -> Self {
+        self.outline = outline.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn get_bands(&self) -> u32 {
+        self.bands
+    }
+
+    pub fn get_outline(&self) -> f64 {
+        self.outline
+    }
+
+    /// Quantizes a cosine in [0, 1] to the middle of its band.
+    pub fn band(&self, cos_theta: f64) -> f64 {
+        let bands = self.bands as f64;
+        (((cos_theta * bands).floor() + 0.5) / bands).min(1.0)
+    }
+}
+
+impl Material for Toon {
+    fn scatter(&self, _ctx: &RenderContext, _r_in: &Ray, hit: &HitRecord) -> Option<ScatterResult> {
+        Some(ScatterResult {
+            attenuation: self.texture.value_at(hit),
+            pdf_or_ray: PdfOrRay::Pdf(Arc::new(CosinePdf::new(hit.normal))),
+        })
+    }
+
+    fn scattering_pdf(
+        &self,
+        _ctx: &RenderContext,
+        _r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+    ) -> f64 {
+        let cos_theta = hit.normal.dot(&scattered.direction.unit());
+        if cos_theta < 0.0 {
+            0.0
+        } else {
+            self.band(cos_theta) / f64::consts::PI
+        }
+    }
+
+    fn scattering_color(
+        &self,
+        ctx: &RenderContext,
+        r_in: &Ray,
+        hit: &HitRecord,
+        scattered: &Ray,
+        attenuation: Color,
+    ) -> Color {
+        // Surfaces seen edge on form the silhouette
+        let facing = hit.normal.dot(&-r_in.direction.unit());
+        if facing < self.outline {
+            return Color::BLACK;
+        }
+        attenuation * self.scattering_pdf(ctx, r_in, hit, scattered)
+    }
+}
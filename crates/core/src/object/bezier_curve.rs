@@ -0,0 +1,309 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    AxisAlignedBoundingBox, Interval, Ray, RenderContext, Vector3,
+    material::Material,
+    object::{HitRecord, Node},
+};
+
+/// Deepest level of subdivision, at most `2^MAX_DEPTH` segments
+const MAX_DEPTH: u32 = 10;
+
+/// A straight piece of the curve, rendered as a capsule.
+#[derive(Debug)]
+struct Segment {
+    start: Vector3,
+    end: Vector3,
+    /// Curve parameter at `start`
+    u_start: f64,
+    /// Curve parameter at `end`
+    u_end: f64,
+    bbox: AxisAlignedBoundingBox,
+}
+
+/// A tube of constant radius swept along a cubic Bezier curve, useful for
+/// wires, strands of hair and pipes.
+///
+/// The curve is adaptively subdivided into straight segments until each is
+/// within a small tolerance of the true curve, so tight bends get more
+/// segments than straight runs. Each segment is intersected as a capsule
+/// (a cylinder with hemispherical caps), which keeps the joints between
+/// segments smooth.
+///
+/// The hit `u` coordinate is the curve parameter, from 0 at the first control
+/// point to 1 at the last.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Interval, Node, Ray, RenderContext, Vector3, random_new,
+///     material::Lambertian,
+///     object::BezierCurve,
+/// };
+///
+/// let wire = BezierCurve::new(
+///     [
+///         Vector3::new(-2.0, 0.0, 0.0),
+///         Vector3::new(-1.0, 2.0, 0.0),
+///         Vector3::new(1.0, 2.0, 0.0),
+///         Vector3::new(2.0, 0.0, 0.0),
+///     ],
+///     0.1,
+///     Arc::new(Lambertian::new_from_color(Color::new(0.7, 0.5, 0.2))),
+/// );
+///
+/// let ctx = RenderContext { random: random_new() };
+/// // The middle of the curve is at (0, 1.5, 0)
+/// let ray = Ray::new(Vector3::new(0.0, 1.5, 5.0), Vector3::new(0.0, 0.0, -1.0));
+/// let hit = wire.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+/// assert!((hit.t - 4.9).abs() < 1e-3);
+/// assert!((hit.u - 0.5).abs() < 1e-2);
+/// ```
+#[derive(Debug)]
+pub struct BezierCurve {
+    control_points: [Vector3; 4],
+    radius: f64,
+    pub material: Arc<dyn Material>,
+    segments: Vec<Segment>,
+    bbox: AxisAlignedBoundingBox,
+}
+
+impl BezierCurve {
+    /// Creates a tube of the given radius along the curve defined by four
+    /// control points. The curve starts at the first point and ends at the last,
+    /// the middle two points pull the curve towards them.
+    pub fn new(control_points: [Vector3; 4], radius: f64, material: Arc<dyn Material>) -> Self {
+        let radius = radius.abs();
+        // A segment may stray from the curve by a small fraction of the radius
+        // before the difference is visible
+        let tolerance = (radius * 0.05).max(1e-6);
+
+        let mut segments = vec![];
+        Self::subdivide(
+            control_points,
+            0.0,
+            1.0,
+            tolerance,
+            0,
+            radius,
+            &mut segments,
+        );
+
+        let bbox = segments
+            .iter()
+            .map(|segment| segment.bbox)
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_default();
+
+        Self {
+            control_points,
+            radius,
+            material,
+            segments,
+            bbox,
+        }
+    }
+
+    pub fn get_control_points(&self) -> [Vector3; 4] {
+        self.control_points
+    }
+
+    pub fn get_radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Returns the number of straight segments the curve was divided into.
+    pub fn get_segment_count(&self) -> usize {
+        self.segments.len()
+    }
+
+    /// Returns the point on the curve at parameter `u` in the range [0, 1].
+    pub fn point_at(&self, u: f64) -> Vector3 {
+        let [p0, p1, p2, p3] = self.control_points;
+        let v = 1.0 - u;
+        v * v * v * p0 + 3.0 * v * v * u * p1 + 3.0 * v * u * u * p2 + u * u * u * p3
+    }
+
+    /// Splits the curve in half with de Casteljau's algorithm until the inner
+    /// control points are within `tolerance` of the chord.
+    fn subdivide(
+        points: [Vector3; 4],
+        u_start: f64,
+        u_end: f64,
+        tolerance: f64,
+        depth: u32,
+        radius: f64,
+        segments: &mut Vec<Segment>,
+    ) {
+        let [p0, p1, p2, p3] = points;
+        if depth >= MAX_DEPTH
+            || (Self::distance_to_line(p1, p0, p3) <= tolerance
+                && Self::distance_to_line(p2, p0, p3) <= tolerance)
+        {
+            segments.push(Segment {
+                start: p0,
+                end: p3,
+                u_start,
+                u_end,
+                bbox: AxisAlignedBoundingBox::new_from_points(p0, p3).expand(2.0 * radius),
+            });
+            return;
+        }
+
+        let p01 = 0.5 * (p0 + p1);
+        let p12 = 0.5 * (p1 + p2);
+        let p23 = 0.5 * (p2 + p3);
+        let p012 = 0.5 * (p01 + p12);
+        let p123 = 0.5 * (p12 + p23);
+        let mid = 0.5 * (p012 + p123);
+        let u_mid = 0.5 * (u_start + u_end);
+
+        Self::subdivide(
+            [p0, p01, p012, mid],
+            u_start,
+            u_mid,
+            tolerance,
+            depth + 1,
+            radius,
+            segments,
+        );
+        Self::subdivide(
+            [mid, p123, p23, p3],
+            u_mid,
+            u_end,
+            tolerance,
+            depth + 1,
+            radius,
+            segments,
+        );
+    }
+
+    /// Distance from `pt` to the segment from `a` to `b`.
+    fn distance_to_line(pt: Vector3, a: Vector3, b: Vector3) -> f64 {
+        let ab = b - a;
+        let length_squared = ab.length_squared();
+        if length_squared == 0.0 {
+            return (pt - a).length();
+        }
+        let h = ((pt - a).dot(&ab) / length_squared).clamp(0.0, 1.0);
+        (pt - (a + h * ab)).length()
+    }
+
+    /// Intersects a capsule around `segment`. Returns the ray parameter and the
+    /// position of the closest point on the segment axis, 0 at the start and 1
+    /// at the end.
+    fn hit_capsule(&self, segment: &Segment, ray: &Ray, ray_t: Interval) -> Option<(f64, f64)> {
+        let r_squared = self.radius * self.radius;
+        let ba = segment.end - segment.start;
+        let oa = ray.origin - segment.start;
+        let baba = ba.length_squared();
+        let bard = ba.dot(&ray.direction);
+        let baoa = ba.dot(&oa);
+        let rdrd = ray.direction.length_squared();
+
+        let mut closest: Option<f64> = None;
+        let mut consider = |t: f64| {
+            if ray_t.surrounds(t) && closest.is_none_or(|c| t < c) {
+                closest = Some(t);
+            }
+        };
+
+        // Both roots of a quadratic with a halved linear term
+        let roots = |a: f64, half_b: f64, c: f64| -> Option<(f64, f64)> {
+            let discriminant = half_b * half_b - a * c;
+            if a == 0.0 || discriminant < 0.0 {
+                return None;
+            }
+            let sqrtd = discriminant.sqrt();
+            Some(((-half_b - sqrtd) / a, (-half_b + sqrtd) / a))
+        };
+
+        // Cylinder body, only the part between the two end caps
+        if baba > 0.0 {
+            let a = baba * rdrd - bard * bard;
+            let half_b = baba * oa.dot(&ray.direction) - baoa * bard;
+            let c = baba * oa.length_squared() - baoa * baoa - r_squared * baba;
+            if let Some((t1, t2)) = roots(a, half_b, c) {
+                for t in [t1, t2] {
+                    let y = baoa + t * bard;
+                    if y > 0.0 && y < baba {
+                        consider(t);
+                    }
+                }
+            }
+        }
+
+        // Hemispherical caps
+        for (center, is_start) in [(segment.start, true), (segment.end, false)] {
+            let oc = ray.origin - center;
+            if let Some((t1, t2)) = roots(
+                rdrd,
+                oc.dot(&ray.direction),
+                oc.length_squared() - r_squared,
+            ) {
+                for t in [t1, t2] {
+                    let y = baoa + t * bard;
+                    if (is_start && y <= 0.0) || (!is_start && y >= baba) {
+                        consider(t);
+                    }
+                }
+            }
+        }
+
+        let t = closest?;
+        let h = if baba > 0.0 {
+            ((baoa + t * bard) / baba).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        Some((t, h))
+    }
+}
+
+impl Node for BezierCurve {
+    fn hit(&self, _ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if !self.bbox.hit(ray, ray_t) {
+            return None;
+        }
+
+        let mut closest_so_far = ray_t.max;
+        let mut closest: Option<(&Segment, f64, f64)> = None;
+        for segment in &self.segments {
+            let ray_t = Interval::new(ray_t.min, closest_so_far);
+            if !segment.bbox.hit(ray, ray_t) {
+                continue;
+            }
+            if let Some((t, h)) = self.hit_capsule(segment, ray, ray_t) {
+                closest_so_far = t;
+                closest = Some((segment, t, h));
+            }
+        }
+
+        let (segment, t, h) = closest?;
+        let pt = ray.at(t);
+        let axis_pt = segment.start + h * (segment.end - segment.start);
+        let mut rec = HitRecord {
+            pt,
+            normal: Vector3::ZERO, // set by set_face_normal
+            tangent: segment.end - segment.start,
+            t,
+            u: segment.u_start + h * (segment.u_end - segment.u_start),
+            v: 0.0,
+            front_face: false,
+            material: self.material.clone(),
+            object_id: 0,
+        };
+        rec.set_face_normal(ray, (pt - axis_pt) / self.radius);
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        &self.bbox
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -1,33 +1,185 @@
-use std::{any::Any, cmp::Ordering, sync::Arc};
+use std::{any::Any, cmp::Ordering, collections::HashSet, sync::Arc};
 
 use crate::{
     Axis, AxisAlignedBoundingBox, Interval, Ray, RenderContext,
-    object::{Group, HitRecord, Node},
+    object::{Group, HitRecord, Node, node_id},
 };
 
+/// Counts of primitives which make a bounding volume hierarchy slow or
+/// incorrect, found by [`BoundingVolumeHierarchy::diagnose`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BvhDiagnostics {
+    /// Nodes whose bounding box is empty or not a number, which can never be
+    /// hit and are left out of the hierarchy
+    pub degenerate: usize,
+    /// Nodes with exactly the same bounding box as an earlier node, usually
+    /// the same primitive placed several times. No split can separate them,
+    /// so rays test each of them.
+    pub coincident: usize,
+}
+
+/// Gives the node replacing a node of a hierarchy, if any
+type NodeUpdate<'a> = dyn FnMut(&Arc<dyn Node>) -> Option<Arc<dyn Node>> + 'a;
+
+/// What a child of a [`BoundingVolumeHierarchy`] holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChildKind {
+    /// One of the nodes the hierarchy was built from
+    Primitive,
+    /// A [`Group`] of nodes the hierarchy was built from, tested in turn
+    Group,
+    /// A hierarchy below this one
+    Hierarchy,
+}
+
+/// Binary tree of bounding boxes, so rays only test the nodes whose boxes
+/// they cross.
+///
+/// The tree is split at the median along the longest axis. Spans deeper than
+/// the maximum depth, or whose nodes all share one bounding box, become
+/// leaves testing their nodes in turn, which keeps traversal bounded for
+/// pathological scenes such as thousands of coincident primitives.
+///
+/// Nodes which move a little, such as animated or dragged objects, are
+/// replaced with [`BoundingVolumeHierarchy::refit`], which keeps the tree and
+/// grows its boxes rather than building it again.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Vector3,
+///     material::Lambertian,
+///     object::{BoundingVolumeHierarchy, BvhDiagnostics, Node, Sphere},
+/// };
+///
+/// let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+/// let spheres: Vec<Arc<dyn Node>> = (0..1000)
+///     .map(|_| Arc::new(Sphere::new(Vector3::ZERO, 1.0, material.clone())) as Arc<dyn Node>)
+///     .collect();
+///
+/// assert_eq!(
+///     BoundingVolumeHierarchy::diagnose(&spheres),
+///     BvhDiagnostics { degenerate: 0, coincident: 999 }
+/// );
+///
+/// // Coincident spheres are kept in a single leaf
+/// let bvh = BoundingVolumeHierarchy::new(&spheres);
+/// assert_eq!(bvh.depth(), 1);
+///
+/// let bvh = BoundingVolumeHierarchy::new_with_max_depth(&spheres[..1], 4);
+/// assert_eq!(bvh.depth(), 1);
+/// ```
 #[derive(Debug)]
 pub struct BoundingVolumeHierarchy {
     left: Arc<dyn Node>,
     right: Arc<dyn Node>,
     bbox: AxisAlignedBoundingBox,
+    kinds: [ChildKind; 2],
+    /// Set when `right` holds the unbounded nodes and `left` the tree of the
+    /// others
+    split_unbounded: bool,
+    /// Depth the hierarchy was built to at most
+    max_depth: usize,
+    /// Sum of the surface areas of the boxes of this hierarchy and those
+    /// below it
+    area_sum: f64,
+    /// [`BoundingVolumeHierarchy::cost`] when built
+    built_cost: f64,
 }
 
 impl BoundingVolumeHierarchy {
+    /// Depth below which spans are not split further. Median splits reach it
+    /// only with billions of nodes, it bounds traversal should that change.
+    pub const DEFAULT_MAX_DEPTH: usize = 32;
+
+    /// Degradation above which [`BoundingVolumeHierarchy::refit`] builds the
+    /// hierarchy again.
+    pub const DEFAULT_MAX_DEGRADATION: f64 = 2.0;
+
     pub fn new(nodes: &[Arc<dyn Node>]) -> Self {
+        Self::new_with_max_depth(nodes, Self::DEFAULT_MAX_DEPTH)
+    }
+
+    /// Builds a hierarchy at most `max_depth` levels deep, nodes left below
+    /// that are tested in turn.
+    pub fn new_with_max_depth(nodes: &[Arc<dyn Node>], max_depth: usize) -> Self {
+        let nodes: Vec<Arc<dyn Node>> = nodes
+            .iter()
+            .filter(|node| !is_degenerate(node.bounding_box()))
+            .cloned()
+            .collect();
+        Self::build(&nodes, max_depth.max(1))
+    }
+
+    /// Finds degenerate and coincident nodes, so callers can warn about them
+    /// before building a hierarchy.
+    pub fn diagnose(nodes: &[Arc<dyn Node>]) -> BvhDiagnostics {
+        let mut diagnostics = BvhDiagnostics::default();
+        let mut seen = HashSet::new();
+        for node in nodes {
+            let bbox = node.bounding_box();
+            if is_degenerate(bbox) {
+                diagnostics.degenerate += 1;
+                continue;
+            }
+            let key = Axis::iter().map(|axis| {
+                let interval = bbox.axis_interval(axis);
+                (interval.min.to_bits(), interval.max.to_bits())
+            });
+            if !seen.insert(key.collect::<Vec<_>>()) {
+                diagnostics.coincident += 1;
+            }
+        }
+        diagnostics
+    }
+
+    fn build(nodes: &[Arc<dyn Node>], max_depth: usize) -> Self {
+        // Unbounded nodes (e.g. infinite planes) would make every box above them
+        // infinite, so split them off and only build the tree from bounded nodes.
+        let (bounded, unbounded): (Vec<_>, Vec<_>) = nodes
+            .iter()
+            .cloned()
+            .partition(|node| !node.bounding_box().is_unbounded());
+        if !unbounded.is_empty() {
+            let (left, left_kind): (Arc<dyn Node>, _) = if bounded.is_empty() {
+                (Arc::new(Group::new()), ChildKind::Group)
+            } else {
+                (
+                    Arc::new(BoundingVolumeHierarchy::build(&bounded, max_depth)),
+                    ChildKind::Hierarchy,
+                )
+            };
+            let right: Arc<dyn Node> = Arc::new(Group::from_list(&unbounded));
+            return Self::from_children(
+                left,
+                right,
+                [left_kind, ChildKind::Group],
+                true,
+                max_depth,
+            );
+        }
+
         // Build the bounding box of the span of source objects.
         let mut bbox = AxisAlignedBoundingBox::new();
         for obj in nodes {
             bbox = AxisAlignedBoundingBox::new_from_bbox(bbox, *obj.bounding_box());
         }
 
-        let (left, right) = if nodes.is_empty() {
+        let (left, right, kind) = if nodes.is_empty() {
             let left: Arc<dyn Node> = Arc::new(Group::new());
             let right: Arc<dyn Node> = Arc::new(Group::new());
-            (left, right)
+            (left, right, ChildKind::Group)
         } else if nodes.len() == 1 {
-            (nodes[0].clone(), nodes[0].clone())
+            (nodes[0].clone(), nodes[0].clone(), ChildKind::Primitive)
         } else if nodes.len() == 2 {
-            (nodes[0].clone(), nodes[1].clone())
+            (nodes[0].clone(), nodes[1].clone(), ChildKind::Primitive)
+        } else if max_depth == 1 || all_coincident(nodes) {
+            let mid = nodes.len() / 2;
+            let left: Arc<dyn Node> = Arc::new(Group::from_list(&nodes[..mid]));
+            let right: Arc<dyn Node> = Arc::new(Group::from_list(&nodes[mid..]));
+            (left, right, ChildKind::Group)
         } else {
             let axis = bbox.longest_axis();
 
@@ -35,14 +187,205 @@ impl BoundingVolumeHierarchy {
             nodes.sort_by(|a, b| bbox_compare(a, b, axis));
 
             let mid = nodes.len() / 2;
-            let left: Arc<dyn Node> = Arc::new(BoundingVolumeHierarchy::new(&nodes[..mid]));
-            let right: Arc<dyn Node> = Arc::new(BoundingVolumeHierarchy::new(&nodes[mid..]));
-            (left, right)
+            let left: Arc<dyn Node> =
+                Arc::new(BoundingVolumeHierarchy::build(&nodes[..mid], max_depth - 1));
+            let right: Arc<dyn Node> =
+                Arc::new(BoundingVolumeHierarchy::build(&nodes[mid..], max_depth - 1));
+            (left, right, ChildKind::Hierarchy)
         };
 
+        Self::from_children(left, right, [kind, kind], false, max_depth)
+    }
+
+    fn from_children(
+        left: Arc<dyn Node>,
+        right: Arc<dyn Node>,
+        kinds: [ChildKind; 2],
+        split_unbounded: bool,
+        max_depth: usize,
+    ) -> Self {
         let bbox =
             AxisAlignedBoundingBox::new_from_bbox(*left.bounding_box(), *right.bounding_box());
-        Self { left, right, bbox }
+        let child_area_sum = |node: &Arc<dyn Node>| {
+            node.as_any()
+                .downcast_ref::<BoundingVolumeHierarchy>()
+                .map_or(0.0, |bvh| bvh.area_sum)
+        };
+        let own_area = if split_unbounded {
+            0.0
+        } else {
+            box_area(&bbox)
+        };
+        let area_sum = own_area + child_area_sum(&left) + child_area_sum(&right);
+        let mut bvh = Self {
+            left,
+            right,
+            bbox,
+            kinds,
+            split_unbounded,
+            max_depth,
+            area_sum,
+            built_cost: 1.0,
+        };
+        bvh.built_cost = bvh.cost();
+        bvh
+    }
+
+    /// Returns the expected number of boxes tested by a ray crossing the
+    /// hierarchy's box, the sum of the surface areas of its boxes over the
+    /// area of the outermost one. Unbounded nodes are left out.
+    pub fn cost(&self) -> f64 {
+        if self.split_unbounded {
+            return self
+                .left
+                .as_any()
+                .downcast_ref::<BoundingVolumeHierarchy>()
+                .map_or(1.0, |bvh| bvh.cost());
+        }
+        let area = box_area(&self.bbox);
+        if area == 0.0 {
+            1.0
+        } else {
+            self.area_sum / area
+        }
+    }
+
+    /// Returns how many times the hierarchy's [`BoundingVolumeHierarchy::cost`]
+    /// has grown since it was built, 1 for a freshly built hierarchy. Boxes
+    /// refitted around moved nodes overlap more and more, so rays test more
+    /// of them.
+    pub fn degradation(&self) -> f64 {
+        self.cost() / self.built_cost
+    }
+
+    /// Replaces the nodes for which `update` returns a new node, usually the
+    /// same object moved a little, and updates the boxes above them. The
+    /// hierarchy is built again when its
+    /// [`BoundingVolumeHierarchy::degradation`] passes
+    /// [`BoundingVolumeHierarchy::DEFAULT_MAX_DEGRADATION`]. Returns whether
+    /// it was built again.
+    ///
+    /// `update` is called once for every node the hierarchy was built from.
+    /// Only the boxes above replaced nodes are created again, so hierarchies
+    /// shared from [`BoundingVolumeHierarchy::get_left`] keep the nodes they
+    /// had.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use caustic_core::{
+    ///     Color, Interval, Ray, RenderContext, Vector3, random_new,
+    ///     material::Lambertian,
+    ///     object::{BoundingVolumeHierarchy, Node, Sphere, Translate},
+    /// };
+    ///
+    /// let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+    /// let spheres: Vec<Arc<dyn Node>> = (0..100)
+    ///     .map(|i| {
+    ///         let center = Vector3::new(i as f64 * 3.0, 0.0, 0.0);
+    ///         Arc::new(Sphere::new(center, 1.0, material.clone())) as Arc<dyn Node>
+    ///     })
+    ///     .collect();
+    /// let mut bvh = BoundingVolumeHierarchy::new(&spheres);
+    ///
+    /// // Nudge the first sphere up
+    /// let first = spheres[0].clone();
+    /// let rebuilt = bvh.refit(|node| {
+    ///     Arc::ptr_eq(node, &first).then(|| {
+    ///         Arc::new(Translate::new(node.clone(), Vector3::new(0.0, 0.5, 0.0))) as Arc<dyn Node>
+    ///     })
+    /// });
+    /// assert!(!rebuilt);
+    /// assert!(bvh.degradation() < 1.01);
+    ///
+    /// let ctx = RenderContext { random: random_new() };
+    /// let ray = Ray::new(Vector3::new(0.0, 10.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+    /// let hit = bvh.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+    /// assert!((hit.pt.y - 1.5).abs() < 1e-9);
+    /// ```
+    pub fn refit(&mut self, update: impl FnMut(&Arc<dyn Node>) -> Option<Arc<dyn Node>>) -> bool {
+        self.refit_with_max_degradation(update, Self::DEFAULT_MAX_DEGRADATION)
+    }
+
+    /// Refits the hierarchy as [`BoundingVolumeHierarchy::refit`] does,
+    /// building it again when its degradation passes `max_degradation`.
+    pub fn refit_with_max_degradation(
+        &mut self,
+        mut update: impl FnMut(&Arc<dyn Node>) -> Option<Arc<dyn Node>>,
+        max_degradation: f64,
+    ) -> bool {
+        if let Some(refitted) = self.refitted(&mut update) {
+            *self = refitted;
+        }
+        // Unbounded or invalid boxes make the degradation not a number
+        if self.degradation() <= max_degradation {
+            return false;
+        }
+
+        let mut nodes = vec![];
+        self.collect_nodes(&mut nodes);
+        *self = Self::new_with_max_depth(&nodes, self.max_depth);
+        true
+    }
+
+    /// Returns the hierarchy with the nodes `update` replaces, or `None` when
+    /// it replaces none.
+    fn refitted(&self, update: &mut NodeUpdate<'_>) -> Option<Self> {
+        let left = refit_child(&self.left, self.kinds[0], update);
+        let right = if Arc::ptr_eq(&self.left, &self.right) {
+            left.clone()
+        } else {
+            refit_child(&self.right, self.kinds[1], update)
+        };
+        if left.is_none() && right.is_none() {
+            return None;
+        }
+
+        let mut bvh = Self::from_children(
+            left.unwrap_or_else(|| self.left.clone()),
+            right.unwrap_or_else(|| self.right.clone()),
+            self.kinds,
+            self.split_unbounded,
+            self.max_depth,
+        );
+        bvh.built_cost = self.built_cost;
+        Some(bvh)
+    }
+
+    /// Adds the nodes the hierarchy was built from to `nodes`.
+    fn collect_nodes(&self, nodes: &mut Vec<Arc<dyn Node>>) {
+        let children = if Arc::ptr_eq(&self.left, &self.right) {
+            &[&self.left][..]
+        } else {
+            &[&self.left, &self.right][..]
+        };
+        for (child, kind) in children.iter().zip(self.kinds) {
+            match kind {
+                ChildKind::Primitive => nodes.push((*child).clone()),
+                ChildKind::Group => {
+                    if let Some(group) = child.as_any().downcast_ref::<Group>() {
+                        nodes.extend(group.get_nodes().iter().cloned());
+                    }
+                }
+                ChildKind::Hierarchy => {
+                    if let Some(bvh) = child.as_any().downcast_ref::<BoundingVolumeHierarchy>() {
+                        bvh.collect_nodes(nodes);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the number of levels of the hierarchy, 1 when both children
+    /// are primitives or leaves.
+    pub fn depth(&self) -> usize {
+        let child_depth = |node: &Arc<dyn Node>| {
+            node.as_any()
+                .downcast_ref::<BoundingVolumeHierarchy>()
+                .map_or(0, |bvh| bvh.depth())
+        };
+        1 + child_depth(&self.left).max(child_depth(&self.right))
     }
 
     pub fn get_left(&self) -> Arc<dyn Node> {
@@ -67,12 +410,15 @@ impl Node for BoundingVolumeHierarchy {
         if let Some(hit_left) = &hit_left {
             t = hit_left.t;
         }
-        let hit_right = self.right.hit(ctx, ray, Interval::new(ray_t.min, t));
-        if hit_right.is_some() {
-            return hit_right;
+        let (mut hit, child, kind) = match self.right.hit(ctx, ray, Interval::new(ray_t.min, t)) {
+            Some(hit_right) => (hit_right, &self.right, self.kinds[1]),
+            None => (hit_left?, &self.left, self.kinds[0]),
+        };
+        // groups and hierarchies below set the ids of the nodes they hold
+        if kind == ChildKind::Primitive {
+            hit.object_id = node_id(child);
         }
-
-        hit_left
+        Some(hit)
     }
 
     fn bounding_box(&self) -> &AxisAlignedBoundingBox {
@@ -84,8 +430,214 @@ impl Node for BoundingVolumeHierarchy {
     }
 }
 
+/// Returns the child with the nodes `update` replaces, or `None` when it
+/// replaces none.
+fn refit_child(
+    child: &Arc<dyn Node>,
+    kind: ChildKind,
+    update: &mut NodeUpdate<'_>,
+) -> Option<Arc<dyn Node>> {
+    match kind {
+        ChildKind::Primitive => update(child),
+        ChildKind::Group => {
+            let group = child.as_any().downcast_ref::<Group>()?;
+            let mut replaced = false;
+            let nodes: Vec<Arc<dyn Node>> = group
+                .get_nodes()
+                .iter()
+                .map(|node| match update(node) {
+                    Some(node) => {
+                        replaced = true;
+                        node
+                    }
+                    None => node.clone(),
+                })
+                .collect();
+            replaced.then(|| Arc::new(Group::from_list(&nodes)) as Arc<dyn Node>)
+        }
+        ChildKind::Hierarchy => {
+            let bvh = child.as_any().downcast_ref::<BoundingVolumeHierarchy>()?;
+            bvh.refitted(update)
+                .map(|bvh| Arc::new(bvh) as Arc<dyn Node>)
+        }
+    }
+}
+
+/// Returns the surface area of a box, 0 when it is empty.
+fn box_area(bbox: &AxisAlignedBoundingBox) -> f64 {
+    if is_degenerate(bbox) {
+        0.0
+    } else {
+        bbox.surface_area()
+    }
+}
+
 fn bbox_compare(a: &Arc<dyn Node>, b: &Arc<dyn Node>, axis: Axis) -> Ordering {
     let a_axis_interval = a.bounding_box().axis_interval(axis);
     let b_axis_interval = b.bounding_box().axis_interval(axis);
     a_axis_interval.min.total_cmp(&b_axis_interval.min)
 }
+
+/// Returns whether a bounding box is empty or not a number along an axis.
+fn is_degenerate(bbox: &AxisAlignedBoundingBox) -> bool {
+    Axis::iter().any(|axis| {
+        let interval = bbox.axis_interval(axis);
+        interval.min.is_nan() || interval.max.is_nan() || interval.min > interval.max
+    })
+}
+
+fn all_coincident(nodes: &[Arc<dyn Node>]) -> bool {
+    let first = nodes[0].bounding_box();
+    nodes[1..].iter().all(|node| {
+        let bbox = node.bounding_box();
+        Axis::iter().all(|axis| bbox.axis_interval(axis) == first.axis_interval(axis))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{BoundingVolumeHierarchy, BvhDiagnostics};
+    use crate::{
+        Axis, Color, Interval, Ray, RenderContext, Vector3,
+        material::Lambertian,
+        object::{Group, Node, Plane, Sphere, Translate},
+        random_new,
+    };
+
+    fn spheres(centers: impl Iterator<Item = Vector3>) -> Vec<Arc<dyn Node>> {
+        let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+        centers
+            .map(|center| Arc::new(Sphere::new(center, 1.0, material.clone())) as Arc<dyn Node>)
+            .collect()
+    }
+
+    #[test]
+    fn coincident_primitives_make_a_shallow_tree() {
+        let nodes = spheres((0..10_000).map(|_| Vector3::new(0.0, 0.0, -5.0)));
+        assert_eq!(
+            BoundingVolumeHierarchy::diagnose(&nodes),
+            BvhDiagnostics {
+                degenerate: 0,
+                coincident: 9_999
+            }
+        );
+
+        let bvh = BoundingVolumeHierarchy::new(&nodes);
+        assert_eq!(bvh.depth(), 1);
+
+        let ctx = RenderContext {
+            random: random_new(),
+        };
+        let ray = Ray::new(Vector3::ZERO, Vector3::new(0.0, 0.0, -1.0));
+        let hit = bvh.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY));
+        assert!((hit.unwrap().t - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn depth_never_exceeds_max_depth() {
+        let nodes = spheres((0..5_000).map(|i| Vector3::new(i as f64 * 3.0, 0.0, 0.0)));
+        assert_eq!(
+            BoundingVolumeHierarchy::diagnose(&nodes),
+            BvhDiagnostics::default()
+        );
+
+        for max_depth in [0, 1, 4, 8] {
+            let bvh = BoundingVolumeHierarchy::new_with_max_depth(&nodes, max_depth);
+            assert!(bvh.depth() <= max_depth.max(1));
+        }
+        assert!(BoundingVolumeHierarchy::new(&nodes).depth() <= 14);
+
+        let ctx = RenderContext {
+            random: random_new(),
+        };
+        let bvh = BoundingVolumeHierarchy::new_with_max_depth(&nodes, 4);
+        let ray = Ray::new(Vector3::new(300.0, 0.0, 10.0), Vector3::new(0.0, 0.0, -1.0));
+        let hit = bvh.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY));
+        assert!((hit.unwrap().t - 9.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn degenerate_primitives_are_skipped() {
+        let mut nodes = spheres([Vector3::ZERO, Vector3::new(f64::NAN, 0.0, 0.0)].into_iter());
+        nodes.push(Arc::new(Group::new()));
+        assert_eq!(
+            BoundingVolumeHierarchy::diagnose(&nodes),
+            BvhDiagnostics {
+                degenerate: 2,
+                coincident: 0
+            }
+        );
+
+        let bvh = BoundingVolumeHierarchy::new(&nodes);
+        assert_eq!(bvh.depth(), 1);
+        for axis in Axis::iter() {
+            assert_eq!(
+                bvh.bounding_box().axis_interval(axis),
+                Interval::new(-1.0, 1.0)
+            );
+        }
+    }
+
+    #[test]
+    fn refit_rebuilds_once_degraded() {
+        let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+        let mut nodes = spheres((0..200).map(|i| Vector3::new(i as f64 * 3.0, 0.0, 0.0)));
+        nodes.push(Arc::new(Plane::new(
+            Vector3::new(0.0, -5.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            material,
+        )));
+        let mut bvh = BoundingVolumeHierarchy::new(&nodes);
+        assert_eq!(bvh.degradation(), 1.0);
+        assert!(bvh.cost() > 1.0);
+
+        let ctx = RenderContext {
+            random: random_new(),
+        };
+        let ray_t = Interval::new(0.001, f64::INFINITY);
+        let check_hits = |bvh: &BoundingVolumeHierarchy, expected: &Group| {
+            for x in -10..620 {
+                let ray = Ray::new(
+                    Vector3::new(x as f64, 20.0, 0.5),
+                    Vector3::new(0.0, -1.0, 0.0),
+                );
+                let hit = bvh.hit(&ctx, &ray, ray_t).map(|hit| hit.t);
+                assert_eq!(hit, expected.hit(&ctx, &ray, ray_t).map(|hit| hit.t));
+            }
+        };
+
+        // Small moves keep the tree
+        let mut moved = nodes.clone();
+        let nudged = [3, 50, 51, 199];
+        let rebuilt = bvh.refit(|node| {
+            let index = nodes.iter().position(|n| Arc::ptr_eq(n, node))?;
+            nudged.contains(&index).then(|| {
+                moved[index] = Arc::new(Translate::new(node.clone(), Vector3::new(0.5, 1.0, 0.0)));
+                moved[index].clone()
+            })
+        });
+        assert!(!rebuilt);
+        assert!(bvh.degradation() < 1.1);
+        check_hits(&bvh, &Group::from_list(&moved));
+        let nodes = moved;
+
+        // Swapping the ends of the row makes every box above them span it
+        let mut moved = nodes.clone();
+        let rebuilt = bvh.refit(|node| {
+            let index = nodes.iter().position(|n| Arc::ptr_eq(n, node))?;
+            (index < 20 || (180..200).contains(&index)).then(|| {
+                let offset = if index < 20 { 540.0 } else { -540.0 };
+                moved[index] = Arc::new(Translate::new(
+                    nodes[index].clone(),
+                    Vector3::new(offset, 0.0, 0.0),
+                ));
+                moved[index].clone()
+            })
+        });
+        assert!(rebuilt);
+        assert_eq!(bvh.degradation(), 1.0);
+        check_hits(&bvh, &Group::from_list(&moved));
+    }
+}
@@ -5,11 +5,53 @@ use crate::{
     AxisAlignedBoundingBox, Interval, Node, Ray, RenderContext, Vector3,
     material::Material,
     object::{Disc, Group, HitRecord},
+    utils::math,
 };
 
+/// A cylinder, cone or frustum standing on the XZ plane.
+///
+/// Both ends are closed by default, [`ConeFrustum::with_caps`] opens them to
+/// make tubes and lampshades. The side wall is shaded smoothly unless
+/// [`ConeFrustum::with_facets`] sets a number of flat faces, matching the
+/// polygons OpenSCAD draws. Textures wrap around the side once, with `u`
+/// following the angle around the axis and `v` the height, while the caps map
+/// their plane onto the texture.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Interval, Node, Ray, RenderContext, Vector3, random_new,
+///     material::Lambertian,
+///     object::ConeFrustum,
+/// };
+///
+/// let material = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.8, 0.8)));
+/// let tube = ConeFrustum::new(Vector3::ZERO, 2.0, 1.0, 1.0, material).with_caps(false, false);
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let ray_t = Interval::new(0.001, f64::INFINITY);
+/// let down = Ray::new(Vector3::new(0.0, 5.0, 0.0), Vector3::new(0.0, -1.0, 0.0));
+/// // Looking down the open tube
+/// assert!(tube.hit(&ctx, &down, ray_t).is_none());
+///
+/// let side = Ray::new(Vector3::new(5.0, 1.0, 0.0), Vector3::new(-1.0, 0.0, 0.0));
+/// let hit = tube.hit(&ctx, &side, ray_t).unwrap();
+/// assert_eq!(hit.normal, Vector3::new(1.0, 0.0, 0.0));
+/// assert_eq!(hit.v, 0.5);
+/// ```
 #[derive(Debug)]
 pub struct ConeFrustum {
     pub object_node: Group,
+    base: Vector3,
+    height: f64,
+    top_radius: f64,
+    bottom_radius: f64,
+    material: Arc<dyn Material>,
+    top_cap: bool,
+    bottom_cap: bool,
+    facets: u32,
 }
 
 impl ConeFrustum {
@@ -23,31 +65,87 @@ impl ConeFrustum {
         bottom_radius: f64,
         material: Arc<dyn Material>,
     ) -> Self {
+        Self {
+            object_node: Group::new(),
+            base,
+            height,
+            top_radius,
+            bottom_radius,
+            material,
+            top_cap: true,
+            bottom_cap: true,
+            facets: 0,
+        }
+        .build()
+    }
+
+    /// Sets whether the top and bottom ends are closed.
+    pub fn with_caps(mut self, top_cap: bool, bottom_cap: bool) -> Self {
+        self.top_cap = top_cap;
+        self.bottom_cap = bottom_cap;
+        self.build()
+    }
+
+    /// Shades the side wall as `facets` flat faces around the axis, 0 shades
+    /// it smoothly.
+    pub fn with_facets(mut self, facets: u32) -> Self {
+        self.facets = facets;
+        self.build()
+    }
+
+    pub fn get_top_radius(&self) -> f64 {
+        self.top_radius
+    }
+
+    pub fn get_bottom_radius(&self) -> f64 {
+        self.bottom_radius
+    }
+
+    pub fn has_top_cap(&self) -> bool {
+        self.top_cap
+    }
+
+    pub fn has_bottom_cap(&self) -> bool {
+        self.bottom_cap
+    }
+
+    pub fn get_facets(&self) -> u32 {
+        self.facets
+    }
+
+    fn build(mut self) -> Self {
+        let base = self.base;
+
         // Y-coordinates for the caps
         let y_base = base.y; // Bottom Y-coordinate
-        let y_top = base.y + height; // Top Y-coordinate
+        let y_top = base.y + self.height; // Top Y-coordinate
 
         let mut nodes: Vec<Arc<dyn Node>> = Vec::new();
 
         // --- 1. Top Cap (Disc) ---
         // Normal points UP (+Y)
-        if top_radius > 1e-4 {
+        if self.top_cap && self.top_radius > 1e-4 {
             let top_center = Vector3::new(base.x, y_top, base.z);
             let top_normal = Vector3::new(0.0, 1.0, 0.0);
-            let top_disc = Disc::new(top_center, top_radius, top_normal, material.clone());
+            let top_disc = Disc::new(
+                top_center,
+                self.top_radius,
+                top_normal,
+                self.material.clone(),
+            );
             nodes.push(Arc::new(top_disc));
         }
 
         // --- 2. Bottom Cap (Disc) ---
         // Normal points DOWN (-Y)
-        if bottom_radius > 1e-4 {
+        if self.bottom_cap && self.bottom_radius > 1e-4 {
             let bottom_center = Vector3::new(base.x, y_base, base.z);
             let bottom_normal = Vector3::new(0.0, -1.0, 0.0);
             let bottom_disc = Disc::new(
                 bottom_center,
-                bottom_radius,
+                self.bottom_radius,
                 bottom_normal,
-                material.clone(),
+                self.material.clone(),
             );
             nodes.push(Arc::new(bottom_disc));
         }
@@ -56,16 +154,16 @@ impl ConeFrustum {
         // base is used as the reference point for the frustum wall
         let side_wall = ConeFrustumWall::new(
             base,
-            height,
-            top_radius,    // r1
-            bottom_radius, // r0
-            material.clone(),
+            self.height,
+            self.top_radius,    // r1
+            self.bottom_radius, // r0
+            self.facets,
+            self.material.clone(),
         );
         nodes.push(Arc::new(side_wall));
 
-        Self {
-            object_node: Group::from_list(&nodes),
-        }
+        self.object_node = Group::from_list(&nodes);
+        self
     }
 }
 
@@ -97,6 +195,8 @@ struct ConeFrustumWall {
     height: f64,
     r0: f64, // Bottom radius
     r1: f64, // Top radius
+    /// Number of flat faces the normals are snapped to, 0 for smooth shading
+    facets: u32,
     pub material: Arc<dyn Material>,
     bbox: AxisAlignedBoundingBox,
 }
@@ -111,6 +211,7 @@ impl ConeFrustumWall {
         height: f64,
         r1: f64, // top radius
         r0: f64, // bottom radius
+        facets: u32,
         material: Arc<dyn Material>,
     ) -> Self {
         // Assume min radius is 0 for bounding box calculation
@@ -134,6 +235,7 @@ impl ConeFrustumWall {
             height,
             r0,
             r1,
+            facets,
             material,
             bbox: AxisAlignedBoundingBox::new_from_points(min_p, max_p),
         }
@@ -143,9 +245,9 @@ impl ConeFrustumWall {
     /// Maps azimuth (angle around Y) to U, and height (Y-coordinate) to V.
     pub fn get_uv(pt: Vector3, base_y: f64, height: f64) -> (f64, f64) {
         // Calculate U (azimuth)
-        // atan2(z, x) gives angle in [-pi, pi]. Add PI to get [0, 2pi].
-        // Normalize to [0, 1].
-        let phi = pt.z.atan2(pt.x);
+        // Like a sphere, u = 0 at -X and increases counterclockwise viewed
+        // from above, so the texture is not mirrored seen from outside.
+        let phi = math::atan2(-pt.z, pt.x);
         let u = (phi + f64::consts::PI) / (2.0 * f64::consts::PI);
 
         // Calculate V (height)
@@ -254,23 +356,28 @@ impl Node for ConeFrustumWall {
 
         let pt = ray.at(t);
 
-        // Calculate the hit point's local Y coordinate (y' in [0, h])
-        let hit_y_local = pt.y - base_y;
-
-        // Calculate the radius at the hit point
-        let hit_radius = r0 + k * hit_y_local;
-
         // The normal calculation must also be done relative to the central axis (base.x, base.z).
         let pt_local = pt - base;
 
-        // Normal N is proportional to (X_local, k * R(y), Z_local)
+        // Snap the angle around the axis to the middle of its facet
+        let mut phi = math::atan2(pt_local.z, pt_local.x);
+        if self.facets > 0 {
+            let facet_angle = 2.0 * f64::consts::PI / self.facets as f64;
+            phi = ((phi / facet_angle).floor() + 0.5) * facet_angle;
+        }
+
+        // Normal N is proportional to the gradient of x^2 + z^2 - R(y)^2, that
+        // is R(y) * (cos(phi), -k, sin(phi)), tilting up when the radius shrinks
         let outward_normal = Vector3::new(
-            pt_local.x,
-            k * hit_radius, // This component accounts for the cone/frustum slope
-            pt_local.z,
+            math::cos(phi),
+            -k, // This component accounts for the cone/frustum slope
+            math::sin(phi),
         )
         .unit();
 
+        // Direction of increasing u, clockwise in the angle phi from atan2(z, x)
+        let tangent = Vector3::new(math::sin(phi), 0.0, -math::cos(phi));
+
         // UV calculation still uses the global hit point's Y and Z/X relative to the base.
         // The azimuth calculation is based on the local X and Z:
         let (u, v) = ConeFrustumWall::get_uv(pt_local, 0.0, h); // We pass 0.0 as base_y because pt_local is already relative to the base.
@@ -278,11 +385,13 @@ impl Node for ConeFrustumWall {
         let mut rec = HitRecord {
             pt, // Store global hit point
             normal: Vector3::ZERO,
+            tangent,
             t,
             u,
             v,
             front_face: false,
             material: self.material.clone(),
+            object_id: 0,
         };
         rec.set_face_normal(ray, outward_normal);
 
@@ -340,7 +449,7 @@ impl Node for ConeFrustumWall {
         };
 
         // 3. Construct Local Point (P_local)
-        let p_local = Vector3::new(r_rand * phi.cos(), y_local, r_rand * phi.sin());
+        let p_local = Vector3::new(r_rand * math::cos(phi), y_local, r_rand * math::sin(phi));
 
         // 4. Translate back to Global Space
         p_local + self.base
@@ -350,3 +459,94 @@ impl Node for ConeFrustumWall {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::ConeFrustum;
+    use crate::{
+        Color, Interval, Node, Ray, RenderContext, Vector3, material::Lambertian, object::HitRecord,
+    };
+
+    fn hit(cone: &ConeFrustum, origin: Vector3, direction: Vector3) -> HitRecord {
+        let ctx = RenderContext::new_seeded(1);
+        cone.hit(
+            &ctx,
+            &Ray::new(origin, direction),
+            Interval::new(0.001, f64::INFINITY),
+        )
+        .unwrap()
+    }
+
+    fn assert_uv(rec: &HitRecord, u: f64, v: f64) {
+        assert!(
+            (rec.u - u).abs() < 1e-9 && (rec.v - v).abs() < 1e-9,
+            "expected ({u}, {v}), got ({}, {})",
+            rec.u,
+            rec.v
+        );
+    }
+
+    #[test]
+    fn side_wraps_the_texture_counterclockwise() {
+        let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+        let cone = ConeFrustum::new(Vector3::new(1.0, 2.0, 3.0), 4.0, 1.0, 2.0, material)
+            .with_caps(false, false);
+
+        // -X at a quarter of the height, where the radius is 1.75
+        let rec = hit(
+            &cone,
+            Vector3::new(-5.0, 3.0, 3.0),
+            Vector3::new(1.0, 0.0, 0.0),
+        );
+        assert!((rec.pt.x + 0.75).abs() < 1e-9);
+        assert_uv(&rec, 0.0, 0.25);
+        // +Z is a quarter of the way round
+        let rec = hit(
+            &cone,
+            Vector3::new(1.0, 3.0, 10.0),
+            Vector3::new(0.0, 0.0, -1.0),
+        );
+        assert_uv(&rec, 0.25, 0.25);
+        // +X half way round
+        let rec = hit(
+            &cone,
+            Vector3::new(10.0, 5.0, 3.0),
+            Vector3::new(-1.0, 0.0, 0.0),
+        );
+        assert_uv(&rec, 0.5, 0.75);
+
+        // u grows along the tangent
+        let next = hit(
+            &cone,
+            Vector3::new(10.0, 5.0, 3.0) + rec.tangent * 0.01,
+            Vector3::new(-1.0, 0.0, 0.0),
+        );
+        assert!(next.u > rec.u);
+        // v grows along normal x tangent, so the texture is not mirrored
+        assert!(rec.normal.cross(&rec.tangent).y > 0.0);
+    }
+
+    #[test]
+    fn caps_map_their_plane_facing_out() {
+        let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+        let cone = ConeFrustum::new(Vector3::ZERO, 2.0, 1.0, 1.0, material);
+
+        for (origin, direction) in [
+            (Vector3::new(0.0, 5.0, 0.0), Vector3::new(0.0, -1.0, 0.0)),
+            (Vector3::new(0.0, -5.0, 0.0), Vector3::new(0.0, 1.0, 0.0)),
+        ] {
+            let center = hit(&cone, origin, direction);
+            assert_uv(&center, 0.5, 0.5);
+
+            // u grows along the tangent and v along normal x tangent, so the
+            // texture is not mirrored seen from outside
+            let bitangent = center.normal.cross(&center.tangent);
+            let rec = hit(&cone, origin + center.tangent * 0.5, direction);
+            assert_uv(&rec, 0.75, 0.5);
+            let rec = hit(&cone, origin - bitangent * 0.5, direction);
+            assert_uv(&rec, 0.5, 0.25);
+        }
+    }
+}
@@ -6,6 +6,7 @@ use crate::{
     material::{Isotropic, Material},
     object::HitRecord,
     texture::Texture,
+    utils::math,
 };
 
 #[derive(Debug)]
@@ -39,43 +40,37 @@ impl ConstantMedium {
 
 impl Node for ConstantMedium {
     fn hit(&self, ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
-        let mut hit1 = self.boundary.hit(ctx, ray, Interval::UNIVERSE)?;
-        let mut hit2 =
-            self.boundary
-                .hit(ctx, ray, Interval::new(hit1.t + 0.0001, f64::INFINITY))?;
+        let hit1 = self.boundary.hit(ctx, ray, Interval::UNIVERSE)?;
+        let hit2 = self
+            .boundary
+            .hit(ctx, ray, Interval::new(hit1.t + 0.0001, f64::INFINITY))?;
 
-        if hit1.t < ray_t.min {
-            hit1.t = ray_t.min;
-        }
-        if hit2.t > ray_t.max {
-            hit2.t = ray_t.max;
-        }
-
-        if hit1.t >= hit2.t {
+        // The part of the ray inside the boundary that is also within ray_t
+        let mut inside = Interval::new(hit1.t, hit2.t).intersection(&ray_t);
+        if inside.is_empty() {
             return None;
         }
-
-        if hit1.t < 0.0 {
-            hit1.t = 0.0;
-        }
+        inside.min = inside.min.max(0.0);
 
         let ray_length = ray.direction.length();
-        let distance_inside_boundary = (hit2.t - hit1.t) * ray_length;
-        let hit_distance = self.neg_inv_density * ctx.random.rand().ln();
+        let distance_inside_boundary = inside.size() * ray_length;
+        let hit_distance = self.neg_inv_density * math::ln(ctx.random.rand());
 
         if hit_distance > distance_inside_boundary {
             return None;
         }
 
-        let t = hit1.t + hit_distance / ray_length;
+        let t = inside.min + hit_distance / ray_length;
         Some(HitRecord {
             pt: ray.at(t),
             normal: Vector3::new(1.0, 0.0, 0.0), // arbitrary
+            tangent: Vector3::ZERO,
             t,
             u: 0.0,
             v: 0.0,
             front_face: true, // also arbitrary
             material: self.phase_function.clone(),
+            object_id: 0,
         })
     }
 
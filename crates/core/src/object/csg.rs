@@ -0,0 +1,176 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    Axis, AxisAlignedBoundingBox, Interval, Ray, RenderContext,
+    object::{HitRecord, Node},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOperation {
+    /// Points inside either child
+    Union,
+    /// Points inside the left child but not the right child
+    Difference,
+    /// Points inside both children
+    Intersection,
+}
+
+impl CsgOperation {
+    fn is_inside(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Difference => inside_left && !inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+        }
+    }
+}
+
+/// Combines two closed solids with a boolean operation.
+///
+/// The ray is walked through the surfaces of both children in order, tracking
+/// whether it is inside each one. The first surface where the combined
+/// inside/outside state changes is the surface of the result.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Interval, Ray, RenderContext, Vector3, Node, random_new,
+///     material::Lambertian,
+///     object::{Csg, CsgOperation, Sphere},
+/// };
+///
+/// let material = Arc::new(Lambertian::new_from_color(Color::new(0.5, 0.5, 0.5)));
+/// let big = Arc::new(Sphere::new(Vector3::ZERO, 2.0, material.clone()));
+/// let small = Arc::new(Sphere::new(Vector3::new(0.0, 0.0, 2.0), 1.0, material));
+/// let bitten = Csg::new(CsgOperation::Difference, big, small);
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+/// let hit = bitten.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+/// // The ray passes through the bite and hits the far side of the small sphere
+/// assert_eq!(hit.pt, Vector3::new(0.0, 0.0, 1.0));
+/// ```
+#[derive(Debug)]
+pub struct Csg {
+    operation: CsgOperation,
+    left: Arc<dyn Node>,
+    right: Arc<dyn Node>,
+    bbox: AxisAlignedBoundingBox,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Arc<dyn Node>, right: Arc<dyn Node>) -> Self {
+        let left_bbox = left.bounding_box();
+        let right_bbox = right.bounding_box();
+        let bbox = match operation {
+            CsgOperation::Union => left_bbox.union(right_bbox),
+            // Nothing outside the left child survives a difference
+            CsgOperation::Difference => *left_bbox,
+            CsgOperation::Intersection => AxisAlignedBoundingBox::new_from_intervals(
+                left_bbox
+                    .axis_interval(Axis::X)
+                    .intersection(&right_bbox.axis_interval(Axis::X)),
+                left_bbox
+                    .axis_interval(Axis::Y)
+                    .intersection(&right_bbox.axis_interval(Axis::Y)),
+                left_bbox
+                    .axis_interval(Axis::Z)
+                    .intersection(&right_bbox.axis_interval(Axis::Z)),
+            ),
+        };
+        Self {
+            operation,
+            left,
+            right,
+            bbox,
+        }
+    }
+
+    pub fn get_operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    pub fn get_left(&self) -> Arc<dyn Node> {
+        self.left.clone()
+    }
+
+    pub fn get_right(&self) -> Arc<dyn Node> {
+        self.right.clone()
+    }
+
+    /// Finds the next surface of `node` along the ray after `t_min`.
+    fn next_hit(
+        ctx: &RenderContext,
+        node: &Arc<dyn Node>,
+        ray: &Ray,
+        t_min: f64,
+    ) -> Option<HitRecord> {
+        node.hit(ctx, ray, Interval::new(t_min, f64::INFINITY))
+    }
+}
+
+impl Node for Csg {
+    fn hit(&self, ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if !self.bbox.hit(ray, ray_t) {
+            return None;
+        }
+
+        let mut hit_left = Self::next_hit(ctx, &self.left, ray, ray_t.min);
+        let mut hit_right = Self::next_hit(ctx, &self.right, ray, ray_t.min);
+
+        // If the next surface is hit from the inside, the ray starts inside that child
+        let mut inside_left = hit_left.as_ref().is_some_and(|hit| !hit.front_face);
+        let mut inside_right = hit_right.as_ref().is_some_and(|hit| !hit.front_face);
+
+        loop {
+            let is_left = match (&hit_left, &hit_right) {
+                (None, None) => return None,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some(l), Some(r)) => l.t <= r.t,
+            };
+
+            let (hit, node) = if is_left {
+                (hit_left.take()?, &self.left)
+            } else {
+                (hit_right.take()?, &self.right)
+            };
+            if hit.t >= ray_t.max {
+                return None;
+            }
+
+            let was_inside = self.operation.is_inside(inside_left, inside_right);
+            if is_left {
+                inside_left = !inside_left;
+            } else {
+                inside_right = !inside_right;
+            }
+            let is_inside = self.operation.is_inside(inside_left, inside_right);
+
+            if was_inside != is_inside {
+                // The normal already faces against the ray, only whether the ray
+                // is entering the combined solid needs to be updated
+                let mut hit = hit;
+                hit.front_face = is_inside;
+                return Some(hit);
+            }
+
+            let next = Self::next_hit(ctx, node, ray, hit.t);
+            if is_left {
+                hit_left = next;
+            } else {
+                hit_right = next;
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        &self.bbox
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -7,15 +7,47 @@ use crate::{
     object::{HitRecord, Node},
     ray::Ray,
     utils::OrthonormalBasis,
+    utils::math,
 };
 
 /// Represents a circular disk, defined by its center, radius, and normal.
 /// This will be used for the cylinder's top and bottom caps.
+///
+/// An inner radius turns the disc into a ring (annulus), and a sweep angle
+/// limits it to a sector, so washers and dials don't need CSG.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Interval, Node, Ray, RenderContext, Vector3, random_new,
+///     material::Lambertian,
+///     object::Disc,
+/// };
+///
+/// let material = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.8, 0.8)));
+/// let washer = Disc::new(Vector3::ZERO, 2.0, Vector3::new(0.0, 1.0, 0.0), material)
+///     .with_inner_radius(1.0);
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let ray_t = Interval::new(0.001, f64::INFINITY);
+/// let down = Vector3::new(0.0, -1.0, 0.0);
+/// // The hole in the middle
+/// assert!(washer.hit(&ctx, &Ray::new(Vector3::new(0.0, 1.0, 0.0), down), ray_t).is_none());
+/// // The ring
+/// assert!(washer.hit(&ctx, &Ray::new(Vector3::new(1.5, 1.0, 0.0), down), ray_t).is_some());
+/// ```
 #[derive(Debug)]
 pub struct Disc {
     center: Vector3,
     radius: f64,
+    inner_radius: f64,
+    /// Sweep angle in radians, starting at `tangent` and turning towards `bitangent`
+    sweep: f64,
     normal: Vector3, // Normal vector pointing outward from the cylinder
+    tangent: Vector3,
+    bitangent: Vector3,
     pub material: Arc<dyn Material>,
     bbox: AxisAlignedBoundingBox,
 }
@@ -34,10 +66,18 @@ impl Disc {
             radius_z + normal.z.abs() * delta,
         );
 
+        let basis = OrthonormalBasis::new(normal);
+        let tangent = basis.u;
+        let bitangent = basis.w.cross(&tangent);
+
         Self {
             center,
             radius,
+            inner_radius: 0.0,
+            sweep: 2.0 * f64::consts::PI,
             normal,
+            tangent,
+            bitangent,
             material,
             // A Disc's BBox should be calculated based on its plane orientation.
             bbox: AxisAlignedBoundingBox::new_from_points(
@@ -47,45 +87,54 @@ impl Disc {
         }
     }
 
+    /// Cuts a hole of the given radius out of the middle of the disc.
+    pub fn with_inner_radius(mut self, inner_radius: f64) -> Self {
+        self.inner_radius = inner_radius.clamp(0.0, self.radius);
+        self
+    }
+
+    /// Limits the disc to a sector of `angle` degrees. The sector starts along
+    /// the first axis of the disc's [`OrthonormalBasis`] and turns counterclockwise
+    /// around the normal. For a disc facing +Y that is the world -X axis,
+    /// turning towards +Z.
+    pub fn with_sweep_angle(mut self, angle: f64) -> Self {
+        self.sweep = angle.to_radians().clamp(0.0, 2.0 * f64::consts::PI);
+        self
+    }
+
     /// UV mapping for a circular disk (flat cap).
-    /// Maps the projection of the point onto the disk plane to [0, 1]x[0, 1].
-    pub fn get_uv(pt: Vector3, center: Vector3, radius: f64) -> (f64, f64) {
-        // Assume Y-aligned disk (Normal = +/-Y) for simplicity in UV calculation
-        let local_pt = pt - center;
-
-        // Map X and Z (the plane coordinates) relative to the radius
-        let u = (local_pt.x / radius + 1.0) * 0.5;
-        let v = (local_pt.z / radius + 1.0) * 0.5;
+    /// Maps the square around the disc onto [0, 1]x[0, 1], with `u` along the
+    /// disc's tangent and `v` along its bitangent, so the texture reads the
+    /// right way round when looking at the disc against its normal.
+    pub fn get_uv(&self, pt: Vector3) -> (f64, f64) {
+        let local_pt = pt - self.center;
+        let u = (local_pt.dot(&self.tangent) / self.radius + 1.0) * 0.5;
+        let v = (local_pt.dot(&self.bitangent) / self.radius + 1.0) * 0.5;
         (u, v)
     }
 
     /// Generates a random point on the disc's surface.
     /// Uses an OrthonormalBasis to transform a 2D random point into 3D space
     /// on the plane defined by the disc's normal.
-    fn random_on_disc(
-        random: &dyn Random,
-        center: Vector3,
-        radius: f64,
-        normal: Vector3,
-    ) -> Vector3 {
-        // 1. Generate a random point in a unit square and map it to a unit disc.
-        // We use random_in_unit_disc() from a common library or implement it:
-        // A simple way is to use a polar coordinate approach for uniform sampling
-        let r_sq = random.rand().sqrt(); // Radius from center: r in [0, 1]
-        let phi = 2.0 * f64::consts::PI * random.rand(); // Angle: phi in [0, 2pi)
-
-        let x = r_sq * phi.cos() * radius;
-        let y = r_sq * phi.sin() * radius;
-
-        // 2. Define a coordinate system for the disc's plane.
-        // OrthonormalBasis helps define u and v vectors that span the plane.
-        let uvw = OrthonormalBasis::new(normal);
-
-        // 3. Convert the 2D random point (x, y) into a 3D point on the disc's plane.
-        let random_local_pt = uvw.u * x + uvw.v * y;
+    fn random_on_disc(&self, random: &dyn Random) -> Vector3 {
+        // 1. Pick a radius so points are spread uniformly over the area of the
+        // ring, then an angle within the sweep.
+        let inner_sq = self.inner_radius * self.inner_radius;
+        let outer_sq = self.radius * self.radius;
+        let r = (inner_sq + random.rand() * (outer_sq - inner_sq)).sqrt();
+        let phi = self.sweep * random.rand();
+
+        // 2. Convert the polar point into a 3D point on the disc's plane.
+        let random_local_pt =
+            self.tangent * (r * math::cos(phi)) + self.bitangent * (r * math::sin(phi));
+
+        // 3. Translate to the disc's actual center.
+        self.center + random_local_pt
+    }
 
-        // 4. Translate to the disc's actual center.
-        center + random_local_pt
+    /// Area of the disc, excluding the hole and the part outside the sweep.
+    fn area(&self) -> f64 {
+        0.5 * self.sweep * (self.radius * self.radius - self.inner_radius * self.inner_radius)
     }
 
     pub fn get_center(&self) -> &Vector3 {
@@ -99,6 +148,15 @@ impl Disc {
     pub fn get_normal(&self) -> &Vector3 {
         &self.normal
     }
+
+    pub fn get_inner_radius(&self) -> f64 {
+        self.inner_radius
+    }
+
+    /// Returns the sweep angle in degrees.
+    pub fn get_sweep_angle(&self) -> f64 {
+        self.sweep.to_degrees()
+    }
 }
 
 impl Node for Disc {
@@ -123,22 +181,38 @@ impl Node for Disc {
         let v = pt - self.center; // Vector from center to hit point
 
         // Check distance squared against radius squared
-        if v.length_squared() > self.radius * self.radius {
+        let distance_squared = v.length_squared();
+        if distance_squared > self.radius * self.radius
+            || distance_squared < self.inner_radius * self.inner_radius
+        {
             return None;
         }
 
+        // Check the angle around the normal against the sweep
+        if self.sweep < 2.0 * f64::consts::PI {
+            let mut angle = math::atan2(v.dot(&self.bitangent), v.dot(&self.tangent));
+            if angle < 0.0 {
+                angle += 2.0 * f64::consts::PI;
+            }
+            if angle > self.sweep {
+                return None;
+            }
+        }
+
         // 3. Create HitRecord
         let outward_normal = self.normal;
-        let (u, v_uv) = Disc::get_uv(pt, self.center, self.radius);
+        let (u, v_uv) = self.get_uv(pt);
 
         let mut rec = HitRecord {
             pt,
             normal: Vector3::ZERO,
+            tangent: self.tangent, // matches get_uv
             t,
             u,
             v: v_uv,
             front_face: false,
             material: self.material.clone(),
+            object_id: 0,
         };
         rec.set_face_normal(ray, outward_normal);
 
@@ -178,7 +252,7 @@ impl Node for Disc {
                 }
 
                 // 4. Calculate the Disc's Area
-                let area = f64::consts::PI * self.radius * self.radius;
+                let area = self.area();
 
                 // 5. Calculate the PDF value
                 // PDF = (r^2) / (|N . D| * Area)
@@ -189,12 +263,16 @@ impl Node for Disc {
 
     fn random(&self, ctx: &RenderContext, origin: &Vector3) -> Vector3 {
         // Get a random point on the disc's surface
-        let target = Disc::random_on_disc(&*ctx.random, self.center, self.radius, self.normal);
+        let target = self.random_on_disc(&*ctx.random);
 
         // Return the direction vector from the origin to that random point
         target - *origin
     }
 
+    fn is_light(&self) -> bool {
+        self.material.is_emissive()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
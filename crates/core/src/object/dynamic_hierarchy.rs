@@ -0,0 +1,247 @@
+use std::{any::Any, collections::HashSet, sync::Arc};
+
+use crate::{
+    AxisAlignedBoundingBox, Interval, Quaternion, Ray, RenderContext, Vector3,
+    object::{BoundingVolumeHierarchy, HitRecord, Node, Rotate, Translate},
+};
+
+/// Placement of an object in a [`DynamicHierarchy`], rotated about its own
+/// origin and then moved by `offset`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InstanceTransform {
+    pub offset: Vector3,
+    pub rotation: Quaternion,
+}
+
+impl Default for InstanceTransform {
+    fn default() -> Self {
+        Self {
+            offset: Vector3::ZERO,
+            rotation: Quaternion::IDENTITY,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Instance {
+    object: Arc<dyn Node>,
+    transform: InstanceTransform,
+    /// The object at its transform, the node held by the hierarchy
+    placed: Arc<dyn Node>,
+}
+
+/// Objects whose transforms change between frames, such as objects dragged
+/// around by an interactive front-end.
+///
+/// Changing a transform replaces the object's node in a
+/// [`BoundingVolumeHierarchy`] through [`BoundingVolumeHierarchy::refit`],
+/// which grows the boxes above it rather than building the hierarchy again,
+/// until the boxes have grown loose enough that building it again pays off.
+/// The objects themselves, with their own hierarchies, are shared rather
+/// than rebuilt.
+///
+/// Lights are found when the scene is built, so moving emissive objects
+/// needs the scene's lights to be found again.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Interval, Quaternion, Ray, RenderContext, Vector3, random_new,
+///     material::Lambertian,
+///     object::{DynamicHierarchy, InstanceTransform, Node, Sphere},
+/// };
+///
+/// let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+/// let objects: Vec<Arc<dyn Node>> = (0..4)
+///     .map(|i| {
+///         let center = Vector3::new(i as f64 * 3.0, 0.0, 0.0);
+///         Arc::new(Sphere::new(center, 1.0, material.clone())) as Arc<dyn Node>
+///     })
+///     .collect();
+/// let mut hierarchy = DynamicHierarchy::new(&objects);
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let ray = Ray::new(Vector3::new(0.0, 10.0, 5.0), Vector3::new(0.0, -1.0, 0.0));
+/// assert!(hierarchy.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY)).is_none());
+///
+/// // Move the first sphere under the ray
+/// hierarchy.set_transform(0, InstanceTransform {
+///     offset: Vector3::new(0.0, 0.0, 5.0),
+///     rotation: Quaternion::IDENTITY,
+/// });
+/// let hit = hierarchy.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+/// assert!((hit.pt - Vector3::new(0.0, 1.0, 5.0)).length() < 1e-9);
+/// ```
+#[derive(Debug)]
+pub struct DynamicHierarchy {
+    instances: Vec<Instance>,
+    bvh: BoundingVolumeHierarchy,
+}
+
+impl DynamicHierarchy {
+    pub fn new(objects: &[Arc<dyn Node>]) -> Self {
+        let mut seen = HashSet::new();
+        let instances: Vec<Instance> = objects
+            .iter()
+            .map(|object| {
+                // The hierarchy finds instances by their node, so an object
+                // given twice is placed through a node of its own
+                let placed = if seen.insert(Arc::as_ptr(object) as *const ()) {
+                    object.clone()
+                } else {
+                    Arc::new(Translate::new(object.clone(), Vector3::ZERO)) as Arc<dyn Node>
+                };
+                Instance {
+                    object: object.clone(),
+                    transform: InstanceTransform::default(),
+                    placed,
+                }
+            })
+            .collect();
+        let placed: Vec<Arc<dyn Node>> = instances.iter().map(|i| i.placed.clone()).collect();
+        Self {
+            instances,
+            bvh: BoundingVolumeHierarchy::new(&placed),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    /// Returns the placement of the object at `index`, in the order given to
+    /// [`DynamicHierarchy::new`].
+    pub fn get_transform(&self, index: usize) -> InstanceTransform {
+        self.instances[index].transform
+    }
+
+    /// Moves the object at `index`, refitting the hierarchy around it.
+    /// Returns whether the hierarchy was built again.
+    pub fn set_transform(&mut self, index: usize, transform: InstanceTransform) -> bool {
+        let instance = &mut self.instances[index];
+        let placed: Arc<dyn Node> = Arc::new(Translate::new(
+            Arc::new(Rotate::new_from_quaternion(
+                instance.object.clone(),
+                transform.rotation,
+            )),
+            transform.offset,
+        ));
+        let previous = std::mem::replace(&mut instance.placed, placed.clone());
+        instance.transform = transform;
+        self.bvh
+            .refit(|node| Arc::ptr_eq(node, &previous).then(|| placed.clone()))
+    }
+
+    /// Returns how many times the cost of tracing the hierarchy has grown
+    /// since it was last built, see [`BoundingVolumeHierarchy::degradation`].
+    pub fn degradation(&self) -> f64 {
+        self.bvh.degradation()
+    }
+}
+
+impl Node for DynamicHierarchy {
+    fn hit(&self, ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        self.bvh.hit(ctx, ray, ray_t)
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        self.bvh.bounding_box()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::{DynamicHierarchy, InstanceTransform};
+    use crate::{
+        Color, Interval, Quaternion, Ray, RenderContext, Vector3,
+        material::Lambertian,
+        object::{Group, Node, Rotate, Sphere, Translate},
+    };
+
+    #[test]
+    fn moved_instances_hit_where_they_were_moved() {
+        let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+        let objects: Vec<Arc<dyn Node>> = (0..50)
+            .map(|i| {
+                let center = Vector3::new((i % 10) as f64 * 3.0, (i / 10) as f64 * 3.0, 1.0);
+                Arc::new(Sphere::new(center, 1.0, material.clone())) as Arc<dyn Node>
+            })
+            .collect();
+        let mut hierarchy = DynamicHierarchy::new(&objects);
+        let ctx = RenderContext::new_seeded(1);
+        let ray_t = Interval::new(0.001, f64::INFINITY);
+        let rays: Vec<Ray> = (-10..60)
+            .flat_map(|x| (-20..40).map(move |y| (x, y)))
+            .map(|(x, y)| {
+                let origin = Vector3::new(x as f64 * 0.5, y as f64 * 0.5, 20.0);
+                Ray::new(origin, Vector3::new(0.01, 0.02, -1.0))
+            })
+            .collect();
+        let hits = |node: &dyn Node| -> Vec<Option<f64>> {
+            rays.iter()
+                .map(|ray| node.hit(&ctx, ray, ray_t).map(|hit| hit.t))
+                .collect()
+        };
+        let before = hits(&hierarchy);
+
+        let mut moved = objects.clone();
+        for index in [0, 7, 23, 49] {
+            let transform = InstanceTransform {
+                offset: Vector3::new(index as f64 * 0.5, -4.0, 2.0),
+                rotation: Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), 30.0),
+            };
+            hierarchy.set_transform(index, transform);
+            assert_eq!(hierarchy.get_transform(index), transform);
+            moved[index] = Arc::new(Translate::new(
+                Arc::new(Rotate::new_from_quaternion(
+                    objects[index].clone(),
+                    transform.rotation,
+                )),
+                transform.offset,
+            ));
+        }
+
+        let after = hits(&hierarchy);
+        assert_ne!(after, before);
+        assert_eq!(after, hits(&Group::from_list(&moved)));
+    }
+
+    #[test]
+    fn moving_far_builds_the_hierarchy_again() {
+        let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+        let objects: Vec<Arc<dyn Node>> = (0..16)
+            .map(|i| {
+                let center = Vector3::new(i as f64 * 3.0, 0.0, 0.0);
+                Arc::new(Sphere::new(center, 1.0, material.clone())) as Arc<dyn Node>
+            })
+            .collect();
+        let mut hierarchy = DynamicHierarchy::new(&objects);
+
+        let mut rebuilt = false;
+        // Shuffle the row of spheres, so each box stretches across the row
+        for index in 0..16 {
+            let shuffled = (index * 7) % 16;
+            rebuilt |= hierarchy.set_transform(
+                index,
+                InstanceTransform {
+                    offset: Vector3::new(3.0 * (shuffled as f64 - index as f64), 0.0, 0.0),
+                    ..Default::default()
+                },
+            );
+        }
+        assert!(rebuilt);
+        assert!(hierarchy.degradation() <= 2.0);
+    }
+}
@@ -0,0 +1,211 @@
+use std::{any::Any, f64::consts::PI, sync::Arc};
+
+use crate::{
+    AxisAlignedBoundingBox, Interval, RenderContext, Vector3,
+    material::Material,
+    object::{HitRecord, Node, Sphere},
+    ray::Ray,
+    utils::OrthonormalBasis,
+};
+
+/// An axis-aligned ellipsoid with analytic normals.
+///
+/// Scaling a [`Sphere`] non-uniformly through a generic transform only has the
+/// hit point to work with, so the ellipsoid solves the intersection in the
+/// space of the unit sphere and derives the normal from the implicit surface
+/// gradient instead.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{Color, Vector3, material::Lambertian, object::Ellipsoid};
+///
+/// let egg = Ellipsoid::new(
+///     Vector3::new(0.0, 1.0, 0.0),
+///     Vector3::new(1.0, 1.5, 1.0),
+///     Arc::new(Lambertian::new_from_color(Color::new(0.9, 0.9, 0.8))),
+/// );
+/// ```
+#[derive(Debug)]
+pub struct Ellipsoid {
+    center: Vector3,
+    /// Semi-axis lengths along x, y and z
+    radii: Vector3,
+    pub material: Arc<dyn Material>,
+    bbox: AxisAlignedBoundingBox,
+}
+
+impl Ellipsoid {
+    /// Creates a new ellipsoid. Negative radii are treated as their absolute value.
+    pub fn new(center: Vector3, radii: Vector3, material: Arc<dyn Material>) -> Self {
+        let radii = Vector3::new(radii.x.abs(), radii.y.abs(), radii.z.abs());
+        Self {
+            center,
+            radii,
+            material,
+            bbox: AxisAlignedBoundingBox::new_from_points(center - radii, center + radii),
+        }
+    }
+
+    pub fn get_center(&self) -> &Vector3 {
+        &self.center
+    }
+
+    pub fn get_radii(&self) -> &Vector3 {
+        &self.radii
+    }
+
+    fn to_unit_space(&self, v: Vector3) -> Vector3 {
+        Vector3::new(v.x / self.radii.x, v.y / self.radii.y, v.z / self.radii.z)
+    }
+
+    fn to_ellipsoid_space(&self, v: Vector3) -> Vector3 {
+        Vector3::new(v.x * self.radii.x, v.y * self.radii.y, v.z * self.radii.z)
+    }
+}
+
+impl Node for Ellipsoid {
+    fn hit(&self, _ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        // Map the ray into the space where the ellipsoid is a unit sphere at the origin.
+        // The mapping is linear so the ray parameter t is the same in both spaces.
+        let origin = self.to_unit_space(ray.origin - self.center);
+        let direction = self.to_unit_space(ray.direction);
+
+        let a = direction.length_squared();
+        let h = direction.dot(&-origin);
+        let c = origin.length_squared() - 1.0;
+        let discriminant = h * h - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+
+        let sqrt_discriminant = discriminant.sqrt();
+
+        // Find the nearest root that lies in the acceptable range.
+        let mut root = (h - sqrt_discriminant) / a;
+        if !ray_t.surrounds(root) {
+            root = (h + sqrt_discriminant) / a;
+            if !ray_t.surrounds(root) {
+                return None;
+            }
+        }
+
+        let t = root;
+        let pt = ray.at(t);
+        let unit_pt = origin + t * direction;
+
+        // The gradient of (x/a)^2 + (y/b)^2 + (z/c)^2 gives the surface normal.
+        let outward_normal = self.to_unit_space(unit_pt).unit();
+        let (u, v) = Sphere::get_uv(unit_pt);
+        let mut rec = HitRecord {
+            pt,
+            normal: Vector3::ZERO, // set by set_face_normal
+            tangent: Vector3::ZERO,
+            t,
+            u,
+            v,
+            front_face: false,
+            material: self.material.clone(),
+            object_id: 0,
+        };
+        rec.set_face_normal(ray, outward_normal);
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        &self.bbox
+    }
+
+    /// Directions are sampled towards the unit sphere the ellipsoid is
+    /// stretched from, spread evenly over its cone, and stretched with it.
+    /// Stretching a direction changes the solid angle around it by
+    /// `det(M) / |M d|^3`, for the stretch `M` and the unit sphere's
+    /// direction `d`.
+    fn pdf_value(&self, _ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> f64 {
+        let det = self.radii.x * self.radii.y * self.radii.z;
+        if det == 0.0 {
+            return 0.0;
+        }
+        let unit_origin = self.to_unit_space(*origin - self.center);
+        let distance_squared = unit_origin.length_squared();
+        let unit_direction = self.to_unit_space(*direction).unit();
+        let unit_pdf = if distance_squared <= 1.0 {
+            // Every direction from within the ellipsoid finds it
+            1.0 / (4.0 * PI)
+        } else {
+            let cos_theta_max = (1.0 - 1.0 / distance_squared).sqrt();
+            let cos_theta = -unit_origin.dot(&unit_direction) / distance_squared.sqrt();
+            if cos_theta < cos_theta_max {
+                return 0.0;
+            }
+            1.0 / (2.0 * PI * (1.0 - cos_theta_max))
+        };
+        let stretch = self.to_ellipsoid_space(unit_direction).length();
+        unit_pdf * stretch * stretch * stretch / det
+    }
+
+    fn random(&self, ctx: &RenderContext, origin: &Vector3) -> Vector3 {
+        let unit_origin = self.to_unit_space(*origin - self.center);
+        let distance_squared = unit_origin.length_squared();
+        let unit_direction = if distance_squared <= 1.0 {
+            Vector3::random_unit(&*ctx.random)
+        } else {
+            let uvw = OrthonormalBasis::new(-unit_origin);
+            uvw.transform_to_local(Sphere::random_to_sphere(
+                &*ctx.random,
+                1.0,
+                distance_squared,
+            ))
+        };
+        self.to_ellipsoid_space(unit_direction)
+    }
+
+    fn is_light(&self) -> bool {
+        self.material.is_emissive()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Color, material::DiffuseLight};
+
+    #[test]
+    fn test_pdf_value_integrates_to_one() {
+        let ctx = RenderContext::new_seeded(1);
+        let ellipsoid = Ellipsoid::new(
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(2.0, 3.0, 1.0),
+            Arc::new(DiffuseLight::new_from_color(Color::WHITE)),
+        );
+        let ray_t = Interval::new(0.001, f64::INFINITY);
+        for origin in [Vector3::new(0.0, -4.0, 0.0), Vector3::new(3.0, 2.0, 1.0)] {
+            // Uniform sphere samples, every direction has density 1/(4π)
+            let n = 200_000;
+            let sum: f64 = (0..n)
+                .map(|_| {
+                    let direction = Vector3::random_unit(&*ctx.random);
+                    ellipsoid.pdf_value(&ctx, &origin, &direction)
+                })
+                .sum();
+            let integral = sum / n as f64 * 4.0 * PI;
+            assert!((integral - 1.0).abs() < 0.03, "integral {integral}");
+
+            // Every sampled direction finds the ellipsoid
+            for _ in 0..1000 {
+                let direction = ellipsoid.random(&ctx, &origin);
+                assert!(
+                    ellipsoid
+                        .hit(&ctx, &Ray::new(origin, direction), ray_t)
+                        .is_some()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use std::{any::Any, fmt::Display, sync::Arc};
+
+use crate::{
+    Axis, AxisAlignedBoundingBox, Color, Interval, Node, Ray, RenderContext, Vector3,
+    material::{Isotropic, Material},
+    object::HitRecord,
+    texture::Texture,
+    utils::math,
+};
+
+#[derive(Debug)]
+pub enum VolumeError {
+    UnsupportedFormat(String),
+    Decode(String),
+}
+
+impl Display for VolumeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VolumeError::UnsupportedFormat(message) => {
+                write!(f, "unsupported volume format: {message}")
+            }
+            VolumeError::Decode(message) => write!(f, "volume decode error: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for VolumeError {}
+
+/// A 3D grid of density values.
+///
+/// Values are stored with X varying fastest, then Y, then Z. Each value is the
+/// density at the center of its voxel, densities in between are interpolated.
+#[derive(Debug, Clone)]
+pub struct VoxelGrid {
+    width: usize,
+    height: usize,
+    depth: usize,
+    data: Vec<f64>,
+}
+
+impl VoxelGrid {
+    /// Creates a grid of `width * height * depth` values. Returns `None` if the
+    /// data has the wrong length or the grid is empty.
+    pub fn new(width: usize, height: usize, depth: usize, data: Vec<f64>) -> Option<Self> {
+        let count = width.checked_mul(height).and_then(|n| n.checked_mul(depth));
+        if width == 0 || height == 0 || depth == 0 || count != Some(data.len()) {
+            return None;
+        }
+        Some(Self {
+            width,
+            height,
+            depth,
+            data,
+        })
+    }
+
+    /// Loads a grid from the Mitsuba `.vol` format, returning the grid and the
+    /// bounding box stored in the file. Only single channel float32 grids are
+    /// supported, multi-channel grids use their first channel.
+    ///
+    /// The header is the bytes `VOL` followed by the version (3), then
+    /// little-endian int32 encoding (1 = float32), X, Y and Z resolution and
+    /// channel count, then six float32 values for the bounding box min and max.
+    pub fn load_mitsuba_vol(bytes: &[u8]) -> Result<(Self, AxisAlignedBoundingBox), VolumeError> {
+        if bytes.len() < 4 || &bytes[0..3] != b"VOL" {
+            return Err(VolumeError::UnsupportedFormat(
+                "missing VOL header".to_owned(),
+            ));
+        }
+        if bytes[3] != 3 {
+            return Err(VolumeError::UnsupportedFormat(format!(
+                "version {}",
+                bytes[3]
+            )));
+        }
+
+        let mut offset = 4;
+        let mut read_i32 = || -> Result<i32, VolumeError> {
+            let value = bytes
+                .get(offset..offset + 4)
+                .ok_or_else(|| VolumeError::Decode("unexpected end of header".to_owned()))?;
+            offset += 4;
+            Ok(i32::from_le_bytes(value.try_into().unwrap()))
+        };
+        let encoding = read_i32()?;
+        let width = read_i32()?;
+        let height = read_i32()?;
+        let depth = read_i32()?;
+        let channels = read_i32()?;
+        if encoding != 1 {
+            return Err(VolumeError::UnsupportedFormat(format!(
+                "encoding {encoding}, only float32 (1) is supported"
+            )));
+        }
+        if width <= 0 || height <= 0 || depth <= 0 || channels <= 0 {
+            return Err(VolumeError::Decode(format!(
+                "invalid dimensions {width}x{height}x{depth} with {channels} channels"
+            )));
+        }
+        let (width, height, depth, channels) = (
+            width as usize,
+            height as usize,
+            depth as usize,
+            channels as usize,
+        );
+
+        // Checked against the file's length before allocating, so a crafted
+        // header can not overflow or ask for more memory than the file holds
+        let found = (bytes.len() - offset) / 4;
+        let value_count = width
+            .checked_mul(height)
+            .and_then(|n| n.checked_mul(depth))
+            .and_then(|n| n.checked_mul(channels))
+            .filter(|n| n.checked_add(6).is_some_and(|n| n <= found))
+            .ok_or_else(|| {
+                VolumeError::Decode(format!(
+                    "expected {width}x{height}x{depth}x{channels} values, found {}",
+                    found.saturating_sub(6)
+                ))
+            })?;
+
+        let floats = bytes[offset..offset + (6 + value_count) * 4]
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()) as f64)
+            .collect::<Vec<f64>>();
+
+        let bbox = AxisAlignedBoundingBox::new_from_points(
+            Vector3::new(floats[0], floats[1], floats[2]),
+            Vector3::new(floats[3], floats[4], floats[5]),
+        );
+        let data = floats[6..6 + value_count]
+            .iter()
+            .step_by(channels)
+            .copied()
+            .collect();
+
+        Ok((Self::new(width, height, depth, data).unwrap(), bbox))
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the density of the voxel at the given index.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> f64 {
+        self.data[(z * self.height + y) * self.width + x]
+    }
+
+    /// Returns the largest density in the grid.
+    pub fn max_value(&self) -> f64 {
+        self.data.iter().copied().fold(0.0, f64::max)
+    }
+
+    /// Returns the trilinearly interpolated density at `pt`, where each
+    /// coordinate is in the range [0, 1] across the grid.
+    pub fn sample(&self, pt: Vector3) -> f64 {
+        // Voxel centers are at half integer positions
+        let fx = pt.x * self.width as f64 - 0.5;
+        let fy = pt.y * self.height as f64 - 0.5;
+        let fz = pt.z * self.depth as f64 - 0.5;
+
+        let corner = |f: f64, size: usize| -> (usize, usize, f64) {
+            let f = f.clamp(0.0, (size - 1) as f64);
+            let i = f.floor() as usize;
+            (i, (i + 1).min(size - 1), f - i as f64)
+        };
+        let (x0, x1, tx) = corner(fx, self.width);
+        let (y0, y1, ty) = corner(fy, self.height);
+        let (z0, z1, tz) = corner(fz, self.depth);
+
+        let lerp = |a: f64, b: f64, t: f64| a + t * (b - a);
+        let c00 = lerp(self.get(x0, y0, z0), self.get(x1, y0, z0), tx);
+        let c10 = lerp(self.get(x0, y1, z0), self.get(x1, y1, z0), tx);
+        let c01 = lerp(self.get(x0, y0, z1), self.get(x1, y0, z1), tx);
+        let c11 = lerp(self.get(x0, y1, z1), self.get(x1, y1, z1), tx);
+        lerp(lerp(c00, c10, ty), lerp(c01, c11, ty), tz)
+    }
+}
+
+/// A participating medium, like smoke or clouds, whose density varies across a
+/// [`VoxelGrid`] stretched over a bounding box.
+///
+/// Unlike [`ConstantMedium`](crate::object::ConstantMedium), the scattering
+/// distance is found with delta tracking: tentative collisions are sampled as if
+/// the whole box had the maximum density, and each is accepted with probability
+/// `density / max_density`.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{
+///     AxisAlignedBoundingBox, Color, Interval, Node, Ray, RenderContext, Vector3, random_new,
+///     object::{GridMedium, VoxelGrid},
+/// };
+///
+/// // A 2x1x1 grid, empty on the left and dense on the right
+/// let grid = VoxelGrid::new(2, 1, 1, vec![0.0, 1.0]).unwrap();
+/// let bbox = AxisAlignedBoundingBox::new_from_points(
+///     Vector3::new(-1.0, -1.0, -1.0),
+///     Vector3::new(1.0, 1.0, 1.0),
+/// );
+/// let smoke = GridMedium::new_from_color(grid, bbox, 1000.0, Color::new(0.8, 0.8, 0.8));
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let ray = Ray::new(Vector3::new(-5.0, 0.0, 0.0), Vector3::new(1.0, 0.0, 0.0));
+/// let hit = smoke.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+/// assert!(hit.pt.x > -0.5 && hit.pt.x < 1.0);
+/// ```
+#[derive(Debug)]
+pub struct GridMedium {
+    grid: VoxelGrid,
+    bbox: AxisAlignedBoundingBox,
+    /// Lowest corner of the bounding box
+    origin: Vector3,
+    /// Size of the bounding box along each axis
+    size: Vector3,
+    density_scale: f64,
+    max_density: f64,
+    phase_function: Arc<dyn Material>,
+}
+
+impl GridMedium {
+    /// Creates a medium whose density at each point is the grid value times
+    /// `density_scale`.
+    pub fn new_from_texture(
+        grid: VoxelGrid,
+        bbox: AxisAlignedBoundingBox,
+        density_scale: f64,
+        texture: Arc<dyn Texture>,
+    ) -> Self {
+        Self::new(
+            grid,
+            bbox,
+            density_scale,
+            Arc::new(Isotropic::new_from_texture(texture)),
+        )
+    }
+
+    pub fn new_from_color(
+        grid: VoxelGrid,
+        bbox: AxisAlignedBoundingBox,
+        density_scale: f64,
+        albedo: Color,
+    ) -> Self {
+        Self::new(
+            grid,
+            bbox,
+            density_scale,
+            Arc::new(Isotropic::new_from_color(albedo)),
+        )
+    }
+
+    fn new(
+        grid: VoxelGrid,
+        bbox: AxisAlignedBoundingBox,
+        density_scale: f64,
+        phase_function: Arc<dyn Material>,
+    ) -> Self {
+        let x = bbox.axis_interval(Axis::X);
+        let y = bbox.axis_interval(Axis::Y);
+        let z = bbox.axis_interval(Axis::Z);
+        let max_density = grid.max_value() * density_scale;
+        Self {
+            grid,
+            bbox,
+            origin: Vector3::new(x.min, y.min, z.min),
+            size: Vector3::new(x.size(), y.size(), z.size()),
+            density_scale,
+            max_density,
+            phase_function,
+        }
+    }
+
+    pub fn get_grid(&self) -> &VoxelGrid {
+        &self.grid
+    }
+
+    /// Returns the density at a point in world space.
+    pub fn density(&self, pt: Vector3) -> f64 {
+        let local = pt - self.origin;
+        let local = Vector3::new(
+            local.x / self.size.x,
+            local.y / self.size.y,
+            local.z / self.size.z,
+        );
+        self.grid.sample(local) * self.density_scale
+    }
+}
+
+impl Node for GridMedium {
+    fn hit(&self, ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if self.max_density <= 0.0 {
+            return None;
+        }
+        let inside = self.bbox.hit_interval(ray, ray_t)?;
+
+        let ray_length = ray.direction.length();
+        let mut t = inside.min;
+        loop {
+            t -= math::ln(ctx.random.rand()) / (self.max_density * ray_length);
+            if t >= inside.max {
+                return None;
+            }
+
+            let pt = ray.at(t);
+            if ctx.random.rand() * self.max_density < self.density(pt) {
+                return Some(HitRecord {
+                    pt,
+                    normal: Vector3::new(1.0, 0.0, 0.0), // arbitrary
+                    tangent: Vector3::ZERO,
+                    t,
+                    u: 0.0,
+                    v: 0.0,
+                    front_face: true, // also arbitrary
+                    material: self.phase_function.clone(),
+                    object_id: 0,
+                });
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        &self.bbox
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{VolumeError, VoxelGrid};
+    use crate::Vector3;
+
+    fn vol_bytes(encoding: i32, dims: [i32; 4], bbox: [f32; 6], values: &[f32]) -> Vec<u8> {
+        let mut bytes = b"VOL\x03".to_vec();
+        bytes.extend(encoding.to_le_bytes());
+        for dim in dims {
+            bytes.extend(dim.to_le_bytes());
+        }
+        for value in bbox.iter().chain(values) {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn load_mitsuba_vol() {
+        let bytes = vol_bytes(
+            1,
+            [2, 1, 1, 1],
+            [0.0, 0.0, 0.0, 2.0, 1.0, 1.0],
+            &[0.25, 0.75],
+        );
+        let (grid, bbox) = VoxelGrid::load_mitsuba_vol(&bytes).unwrap();
+        assert_eq!(grid.get_width(), 2);
+        assert_eq!(grid.get(1, 0, 0), 0.75);
+        assert_eq!(bbox.centroid(), Vector3::new(1.0, 0.5, 0.5));
+        assert_eq!(grid.sample(Vector3::new(0.5, 0.5, 0.5)), 0.5);
+    }
+
+    #[test]
+    fn load_mitsuba_vol_errors() {
+        let err = VoxelGrid::load_mitsuba_vol(b"NVDB").unwrap_err();
+        assert!(matches!(err, VolumeError::UnsupportedFormat(_)));
+
+        let bytes = vol_bytes(2, [1, 1, 1, 1], [0.0; 6], &[]);
+        let err = VoxelGrid::load_mitsuba_vol(&bytes).unwrap_err();
+        assert!(matches!(err, VolumeError::UnsupportedFormat(_)));
+
+        let bytes = vol_bytes(1, [2, 2, 2, 1], [0.0; 6], &[1.0]);
+        let err = VoxelGrid::load_mitsuba_vol(&bytes).unwrap_err();
+        assert!(matches!(err, VolumeError::Decode(_)));
+
+        // Dimensions whose product overflows
+        let bytes = vol_bytes(1, [i32::MAX; 4], [0.0; 6], &[1.0]);
+        let err = VoxelGrid::load_mitsuba_vol(&bytes).unwrap_err();
+        assert!(matches!(err, VolumeError::Decode(_)));
+        assert!(VoxelGrid::new(usize::MAX, 2, 1, vec![]).is_none());
+    }
+}
@@ -2,7 +2,7 @@ use std::{any::Any, sync::Arc};
 
 use crate::{
     AxisAlignedBoundingBox, Interval, Ray, RenderContext, Vector3,
-    object::{HitRecord, Node},
+    object::{HitRecord, Node, node_id},
 };
 
 #[derive(Debug)]
@@ -32,6 +32,10 @@ impl Group {
         self.nodes.push(node);
         self.bbox = AxisAlignedBoundingBox::new_from_bbox(self.bbox, node_bbox);
     }
+
+    pub fn get_nodes(&self) -> &[Arc<dyn Node>] {
+        &self.nodes
+    }
 }
 
 impl Default for Group {
@@ -45,8 +49,9 @@ impl Node for Group {
         let mut closest_hit: Option<HitRecord> = None;
 
         for node in &self.nodes {
-            if let Some(hit) = node.hit(ctx, ray, ray_t) {
+            if let Some(mut hit) = node.hit(ctx, ray, ray_t) {
                 ray_t.max = hit.t;
+                hit.object_id = node_id(node);
                 closest_hit = Some(hit);
             }
         }
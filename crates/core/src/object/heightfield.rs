@@ -0,0 +1,263 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    AxisAlignedBoundingBox, Image, Interval, Ray, RenderContext, Vector3,
+    material::Material,
+    object::{HitRecord, Node},
+};
+
+/// A terrain surface defined by a regular grid of heights.
+///
+/// Heights are sampled at the grid vertices, `heights[row][col]` being the
+/// height at `(col * scale.x, height * scale.y, row * scale.z)`. Every grid cell
+/// is split into two triangles. Rays walk the cells they cross in order, so
+/// only the triangles of a few cells are tested per ray.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Interval, Node, Ray, RenderContext, Vector3, random_new,
+///     material::Lambertian,
+///     object::Heightfield,
+/// };
+///
+/// let terrain = Heightfield::new(
+///     vec![vec![0.0, 1.0, 0.0], vec![1.0, 2.0, 1.0], vec![0.0, 1.0, 0.0]],
+///     Vector3::new(1.0, 1.0, 1.0),
+///     Arc::new(Lambertian::new_from_color(Color::new(0.4, 0.6, 0.3))),
+/// );
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let ray = Ray::new(Vector3::new(1.0, 10.0, 1.0), Vector3::new(0.0, -1.0, 0.0));
+/// let hit = terrain.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+/// assert_eq!(hit.pt, Vector3::new(1.0, 2.0, 1.0));
+/// ```
+#[derive(Debug)]
+pub struct Heightfield {
+    /// Heights in row major order, `columns * rows` values
+    heights: Vec<f64>,
+    columns: usize,
+    rows: usize,
+    scale: Vector3,
+    pub material: Arc<dyn Material>,
+    bbox: AxisAlignedBoundingBox,
+}
+
+impl Heightfield {
+    /// Creates a heightfield from rows of heights. Rows longer than the shortest
+    /// row are truncated. Negative scale factors are treated as their absolute value.
+    pub fn new(heights: Vec<Vec<f64>>, scale: Vector3, material: Arc<dyn Material>) -> Self {
+        let rows = heights.len();
+        let columns = heights.iter().map(|row| row.len()).min().unwrap_or(0);
+        let heights: Vec<f64> = heights
+            .into_iter()
+            .flat_map(|row| row.into_iter().take(columns))
+            .collect();
+        let scale = Vector3::new(scale.x.abs(), scale.y.abs(), scale.z.abs());
+
+        let bbox = if columns < 2 || rows < 2 {
+            AxisAlignedBoundingBox::new()
+        } else {
+            let min_height = heights.iter().copied().fold(f64::INFINITY, f64::min);
+            let max_height = heights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            AxisAlignedBoundingBox::new_from_points(
+                Vector3::new(0.0, min_height * scale.y, 0.0),
+                Vector3::new(
+                    (columns - 1) as f64 * scale.x,
+                    max_height * scale.y,
+                    (rows - 1) as f64 * scale.z,
+                ),
+            )
+        };
+
+        Self {
+            heights,
+            columns,
+            rows,
+            scale,
+            material,
+            bbox,
+        }
+    }
+
+    /// Creates a heightfield from the luminance of an image, where black is a
+    /// height of 0 and white a height of 1 before scaling. The first image row is
+    /// grid row 0.
+    pub fn new_from_image(image: &dyn Image, scale: Vector3, material: Arc<dyn Material>) -> Self {
+        let heights = (0..image.height())
+            .map(|y| {
+                (0..image.width())
+                    .map(|x| image.get_pixel(x, y).map(|c| c.luminance()).unwrap_or(0.0))
+                    .collect()
+            })
+            .collect();
+        Self::new(heights, scale, material)
+    }
+
+    pub fn get_columns(&self) -> usize {
+        self.columns
+    }
+
+    pub fn get_rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns the unscaled height at the given grid vertex.
+    pub fn get_height(&self, column: usize, row: usize) -> f64 {
+        self.heights[row * self.columns + column]
+    }
+
+    fn vertex(&self, column: usize, row: usize) -> Vector3 {
+        Vector3::new(
+            column as f64 * self.scale.x,
+            self.get_height(column, row) * self.scale.y,
+            row as f64 * self.scale.z,
+        )
+    }
+
+    /// Intersects the two triangles of the cell whose lowest corner is `(column, row)`.
+    fn hit_cell(
+        &self,
+        ray: &Ray,
+        ray_t: Interval,
+        column: usize,
+        row: usize,
+    ) -> Option<(f64, Vector3)> {
+        let a = self.vertex(column, row);
+        let b = self.vertex(column + 1, row);
+        let c = self.vertex(column, row + 1);
+        let d = self.vertex(column + 1, row + 1);
+
+        let first = Self::hit_triangle(ray, ray_t, a, b, c);
+        let ray_t = match &first {
+            Some((t, _)) => Interval::new(ray_t.min, *t),
+            None => ray_t,
+        };
+        Self::hit_triangle(ray, ray_t, b, d, c).or(first)
+    }
+
+    /// Möller–Trumbore ray/triangle intersection. Returns the ray parameter and
+    /// the upward facing normal of the triangle.
+    fn hit_triangle(
+        ray: &Ray,
+        ray_t: Interval,
+        a: Vector3,
+        b: Vector3,
+        c: Vector3,
+    ) -> Option<(f64, Vector3)> {
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = ray.direction.cross(&edge2);
+        let det = edge1.dot(&p);
+        if det.abs() < 1e-12 {
+            return None;
+        }
+        let inv_det = 1.0 / det;
+
+        let s = ray.origin - a;
+        let u = s.dot(&p) * inv_det;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = s.cross(&edge1);
+        let v = ray.direction.dot(&q) * inv_det;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(&q) * inv_det;
+        if !ray_t.surrounds(t) {
+            return None;
+        }
+
+        Some((t, edge2.cross(&edge1).unit()))
+    }
+}
+
+impl Node for Heightfield {
+    fn hit(&self, _ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if self.columns < 2 || self.rows < 2 {
+            return None;
+        }
+        let clipped = self.bbox.hit_interval(ray, ray_t)?;
+
+        let cells_x = self.columns - 1;
+        let cells_z = self.rows - 1;
+        let cell_index = |value: f64, size: f64, cells: usize| -> usize {
+            ((value / size).floor().max(0.0) as usize).min(cells - 1)
+        };
+
+        // Walk the grid cells under the ray in order (2D DDA), the first cell with
+        // a hit contains the closest hit
+        let start = ray.at(clipped.min);
+        let mut column = cell_index(start.x, self.scale.x, cells_x);
+        let mut row = cell_index(start.z, self.scale.z, cells_z);
+
+        let step = |direction: f64, cell: usize, size: f64, origin: f64| -> (i64, f64, f64) {
+            if direction > 0.0 {
+                let boundary = (cell + 1) as f64 * size;
+                (1, (boundary - origin) / direction, size / direction)
+            } else if direction < 0.0 {
+                let boundary = cell as f64 * size;
+                (-1, (boundary - origin) / direction, -size / direction)
+            } else {
+                (0, f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (step_x, mut t_max_x, t_delta_x) =
+            step(ray.direction.x, column, self.scale.x, ray.origin.x);
+        let (step_z, mut t_max_z, t_delta_z) =
+            step(ray.direction.z, row, self.scale.z, ray.origin.z);
+
+        loop {
+            let t_exit = t_max_x.min(t_max_z).min(clipped.max);
+            if let Some((t, outward_normal)) = self.hit_cell(ray, ray_t, column, row) {
+                let pt = ray.at(t);
+                let mut rec = HitRecord {
+                    pt,
+                    normal: Vector3::ZERO, // set by set_face_normal
+                    tangent: Vector3::ZERO,
+                    t,
+                    u: pt.x / (cells_x as f64 * self.scale.x),
+                    v: pt.z / (cells_z as f64 * self.scale.z),
+                    front_face: false,
+                    material: self.material.clone(),
+                    object_id: 0,
+                };
+                rec.set_face_normal(ray, outward_normal);
+                return Some(rec);
+            }
+
+            if t_exit >= clipped.max {
+                return None;
+            }
+
+            if t_max_x < t_max_z {
+                let next = column as i64 + step_x;
+                if next < 0 || next >= cells_x as i64 {
+                    return None;
+                }
+                column = next as usize;
+                t_max_x += t_delta_x;
+            } else {
+                let next = row as i64 + step_z;
+                if next < 0 || next >= cells_z as i64 {
+                    return None;
+                }
+                row = next as usize;
+                t_max_z += t_delta_z;
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        &self.bbox
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
@@ -1,41 +1,80 @@
 use std::{any::Any, fmt::Debug, sync::Arc};
 
 use crate::{
-    AxisAlignedBoundingBox, Interval, RenderContext, material::Material, ray::Ray, vector::Vector3,
+    AxisAlignedBoundingBox, Interval, RenderContext, material::Material, ray::Ray,
+    utils::OrthonormalBasis, vector::Vector3,
 };
 
+pub mod bezier_curve;
 pub mod bounding_volume_hierarchy;
 pub mod box_node;
 pub mod cone;
 pub mod constant_medium;
+pub mod csg;
 pub mod disc;
+pub mod dynamic_hierarchy;
+pub mod ellipsoid;
+pub mod grid_medium;
 pub mod group;
+pub mod heightfield;
+pub mod plane;
 pub mod quad;
 pub mod rotate;
 pub mod scale;
+pub mod sdf;
 pub mod sphere;
 pub mod translate;
+pub mod triangle_mesh;
 
-pub use bounding_volume_hierarchy::BoundingVolumeHierarchy;
+pub use bezier_curve::BezierCurve;
+pub use bounding_volume_hierarchy::{BoundingVolumeHierarchy, BvhDiagnostics};
 pub use box_node::BoxPrimitive;
 pub use cone::ConeFrustum;
 pub use constant_medium::ConstantMedium;
+pub use csg::{Csg, CsgOperation};
 pub use disc::Disc;
+pub use dynamic_hierarchy::{DynamicHierarchy, InstanceTransform};
+pub use ellipsoid::Ellipsoid;
+pub use grid_medium::{GridMedium, VolumeError, VoxelGrid};
 pub use group::Group;
+pub use heightfield::Heightfield;
+pub use plane::Plane;
 pub use quad::Quad;
 pub use rotate::Rotate;
 pub use scale::Scale;
+pub use sdf::SdfNode;
 pub use sphere::Sphere;
 pub use translate::Translate;
+pub use triangle_mesh::{ConvertedMesh, TriangleMesh};
 
+#[derive(Clone)]
 pub struct HitRecord {
     pub pt: Vector3,
     pub normal: Vector3,
+    /// Direction along the surface in which `u` increases, used to orient
+    /// tangent-space normal maps. Not necessarily unit length, and zero for
+    /// primitives without a meaningful UV mapping.
+    pub tangent: Vector3,
     pub t: f64,
     pub u: f64,
     pub v: f64,
     pub front_face: bool,
     pub material: Arc<dyn Material>,
+    /// Id of the object hit, set from the node holding it by the [`Group`]
+    /// or [`BoundingVolumeHierarchy`] it is in, the outermost one last, so
+    /// hits in the scene are told apart by the objects of the scene. 0 for
+    /// primitives outside of any.
+    pub object_id: u32,
+}
+
+/// Returns a nonzero id for a node while it is loaded, its address mixed so
+/// the ids of nodes allocated one after the other are far apart.
+pub(crate) fn node_id(node: &Arc<dyn Node>) -> u32 {
+    let mut id = Arc::as_ptr(node) as *const () as usize as u64;
+    id = (id ^ (id >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    id = (id ^ (id >> 27)).wrapping_mul(0x94d049bb133111eb);
+    id ^= id >> 31;
+    (id as u32).max(1)
 }
 
 impl HitRecord {
@@ -49,6 +88,19 @@ impl HitRecord {
             -outward_normal
         };
     }
+
+    /// Returns a unit tangent and bitangent perpendicular to the normal, with
+    /// the tangent as close to `tangent` as possible. Primitives without a UV
+    /// mapping get an arbitrary tangent.
+    pub fn tangent_frame(&self) -> (Vector3, Vector3) {
+        let tangent = self.tangent - self.normal * self.normal.dot(&self.tangent);
+        let tangent = if tangent.is_near_zero() {
+            OrthonormalBasis::new(self.normal).u
+        } else {
+            tangent.unit()
+        };
+        (tangent, self.normal.cross(&tangent))
+    }
 }
 
 pub trait Node: Send + Sync + Debug {
@@ -64,5 +116,34 @@ pub trait Node: Send + Sync + Debug {
         Vector3::new(1.0, 0.0, 0.0)
     }
 
+    /// Returns true for emissive surfaces which implement `pdf_value` and
+    /// `random`, so they can be added to the lights sampled directly.
+    fn is_light(&self) -> bool {
+        false
+    }
+
     fn as_any(&self) -> &dyn Any;
+
+    /// Returns the name of the node's type without its module path or
+    /// generic parameters, such as `Sphere`, to tell users what they picked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use caustic_core::{
+    ///     Color, Node, Vector3,
+    ///     material::Lambertian,
+    ///     object::Sphere,
+    /// };
+    ///
+    /// let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+    /// let sphere: Arc<dyn Node> = Arc::new(Sphere::new(Vector3::ZERO, 1.0, material));
+    /// assert_eq!(sphere.type_name(), "Sphere");
+    /// ```
+    fn type_name(&self) -> &'static str {
+        let name = std::any::type_name::<Self>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name)
+    }
 }
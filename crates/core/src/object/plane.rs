@@ -0,0 +1,154 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    AxisAlignedBoundingBox, Interval, Ray, RenderContext, Vector3,
+    material::Material,
+    object::{HitRecord, Node},
+    utils::OrthonormalBasis,
+};
+
+/// An infinite plane defined by a point on the plane and a normal.
+///
+/// Useful for ground planes and backdrops without having to size a giant quad.
+/// Because the plane has no finite extent, its bounding box is unbounded along
+/// every axis the plane is not perpendicular to. [`crate::object::BoundingVolumeHierarchy`]
+/// keeps such nodes out of the tree so they do not inflate every other bounding box.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{Color, Vector3, material::Lambertian, object::Plane};
+///
+/// let ground = Plane::new(
+///     Vector3::new(0.0, 0.0, 0.0),
+///     Vector3::new(0.0, 1.0, 0.0),
+///     Arc::new(Lambertian::new_from_color(Color::new(0.5, 0.5, 0.5))),
+/// )
+/// .with_uv_scale(2.0);
+/// ```
+#[derive(Debug)]
+pub struct Plane {
+    /// A point lying on the plane
+    point: Vector3,
+    /// Unit normal vector of the plane
+    normal: Vector3,
+    /// First tangent vector spanning the plane, used for UV mapping
+    tangent_u: Vector3,
+    /// Second tangent vector spanning the plane, used for UV mapping
+    tangent_v: Vector3,
+    /// Size of one UV tile in world units. When `None` the UVs are the raw plane coordinates.
+    uv_scale: Option<f64>,
+    /// Surface material for rendering
+    material: Arc<dyn Material>,
+    bbox: AxisAlignedBoundingBox,
+}
+
+impl Plane {
+    /// Creates a new infinite plane.
+    ///
+    /// # Arguments
+    ///
+    /// * `point` - Any point on the plane
+    /// * `normal` - Normal of the plane. Does not need to be normalized.
+    /// * `material` - Material for the plane's surface
+    pub fn new(point: Vector3, normal: Vector3, material: Arc<dyn Material>) -> Self {
+        let normal = normal.unit();
+        let basis = OrthonormalBasis::new(normal);
+
+        Self {
+            point,
+            normal,
+            tangent_u: basis.u,
+            tangent_v: basis.v,
+            uv_scale: None,
+            material,
+            bbox: Plane::calculate_bbox(point, normal),
+        }
+    }
+
+    /// Maps the plane into repeating UV tiles of `scale` world units, so that
+    /// image and checker textures tile across the plane.
+    pub fn with_uv_scale(mut self, scale: f64) -> Self {
+        self.uv_scale = Some(scale);
+        self
+    }
+
+    pub fn get_point(&self) -> &Vector3 {
+        &self.point
+    }
+
+    pub fn get_normal(&self) -> &Vector3 {
+        &self.normal
+    }
+
+    /// An axis-aligned plane is thin along its normal axis and unbounded along the
+    /// other two. Any other orientation is unbounded along all three axes.
+    fn calculate_bbox(point: Vector3, normal: Vector3) -> AxisAlignedBoundingBox {
+        let delta = 1e-4;
+        let axis_interval = |n: f64, p: f64| {
+            if (n.abs() - 1.0).abs() < 1e-8 {
+                Interval::new(p - delta, p + delta)
+            } else {
+                Interval::UNIVERSE
+            }
+        };
+        AxisAlignedBoundingBox::new_from_intervals(
+            axis_interval(normal.x, point.x),
+            axis_interval(normal.y, point.y),
+            axis_interval(normal.z, point.z),
+        )
+    }
+
+    fn get_uv(&self, pt: Vector3) -> (f64, f64) {
+        let local_pt = pt - self.point;
+        let u = local_pt.dot(&self.tangent_u);
+        let v = local_pt.dot(&self.tangent_v);
+        match self.uv_scale {
+            Some(scale) => ((u / scale).rem_euclid(1.0), (v / scale).rem_euclid(1.0)),
+            None => (u, v),
+        }
+    }
+}
+
+impl Node for Plane {
+    fn hit(&self, _ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let denominator = ray.direction.dot(&self.normal);
+
+        // Ray is parallel to the plane.
+        if denominator.abs() < 1e-8 {
+            return None;
+        }
+
+        let t = (self.point - ray.origin).dot(&self.normal) / denominator;
+        if !ray_t.contains(t) {
+            return None;
+        }
+
+        let pt = ray.at(t);
+        let (u, v) = self.get_uv(pt);
+
+        let mut rec = HitRecord {
+            pt,
+            normal: Vector3::ZERO,
+            tangent: self.tangent_u,
+            t,
+            u,
+            v,
+            front_face: false,
+            material: self.material.clone(),
+            object_id: 0,
+        };
+        rec.set_face_normal(ray, self.normal);
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        &self.bbox
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
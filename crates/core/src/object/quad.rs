@@ -172,11 +172,13 @@ impl Node for Quad {
         let mut hit = HitRecord {
             pt: intersection,
             normal: Vector3::ZERO,
+            tangent: self.u,
             t,
             u,
             v,
             front_face: false,
             material: self.material.clone(),
+            object_id: 0,
         };
         hit.set_face_normal(ray, self.normal);
         Some(hit)
@@ -249,6 +251,10 @@ impl Node for Quad {
     /// # Returns
     ///
     /// A reference to self as `&dyn Any`.
+    fn is_light(&self) -> bool {
+        self.material.is_emissive()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -2,82 +2,162 @@ use core::f64;
 use std::{any::Any, sync::Arc};
 
 use crate::{
-    Axis, AxisAlignedBoundingBox, Interval, Matrix3x3, Node, Ray, RenderContext, Vector3,
-    object::HitRecord,
+    Axis, AxisAlignedBoundingBox, Interval, Matrix3x3, Node, Quaternion, Ray, RenderContext,
+    Vector3, object::HitRecord, utils::math,
 };
 
 #[derive(Debug)]
 pub struct Rotate {
     object: Arc<dyn Node>,
+    /// Rotation at time 0.0
+    rotation: Quaternion,
+    /// Rotation at time 1.0, equal to `rotation` unless the rotation is animated
+    rotation_end: Quaternion,
     rotation_matrix: Matrix3x3,
     inverse_rotation_matrix: Matrix3x3,
+    /// Point the object turns around
+    pivot: Vector3,
     bbox: AxisAlignedBoundingBox,
 }
 
 impl Rotate {
     /// Creates a rotation around an arbitrary axis
     pub fn new(object: Arc<dyn Node>, axis: Vector3, angle: f64) -> Self {
-        let radians = angle.to_radians();
-        let sin_theta = radians.sin();
-        let cos_theta = radians.cos();
-
-        // Normalize the axis
-        let axis = axis.unit();
-        let x = axis.x;
-        let y = axis.y;
-        let z = axis.z;
-
-        // Rodrigues' rotation formula to build rotation matrix
-        let one_minus_cos = 1.0 - cos_theta;
-
-        let rotation_matrix = Matrix3x3::new([
-            [
-                cos_theta + x * x * one_minus_cos,
-                x * y * one_minus_cos - z * sin_theta,
-                x * z * one_minus_cos + y * sin_theta,
-            ],
-            [
-                y * x * one_minus_cos + z * sin_theta,
-                cos_theta + y * y * one_minus_cos,
-                y * z * one_minus_cos - x * sin_theta,
-            ],
-            [
-                z * x * one_minus_cos - y * sin_theta,
-                z * y * one_minus_cos + x * sin_theta,
-                cos_theta + z * z * one_minus_cos,
-            ],
-        ]);
-
-        // The inverse rotation is just the transpose for rotation matrices
-        let inverse_rotation_matrix = Matrix3x3::new([
-            [
-                rotation_matrix[0][0],
-                rotation_matrix[1][0],
-                rotation_matrix[2][0],
-            ],
-            [
-                rotation_matrix[0][1],
-                rotation_matrix[1][1],
-                rotation_matrix[2][1],
-            ],
-            [
-                rotation_matrix[0][2],
-                rotation_matrix[1][2],
-                rotation_matrix[2][2],
-            ],
-        ]);
+        Self::new_from_quaternion(object, Quaternion::from_axis_angle(axis, angle))
+    }
 
-        let obj_bbox = object.bounding_box();
-        let bbox = Self::compute_bounding_box(obj_bbox, &rotation_matrix);
+    /// Creates a rotation from a quaternion. Rotating another stationary [`Rotate`]
+    /// composes both rotations into a single node rather than nesting them.
+    pub fn new_from_quaternion(object: Arc<dyn Node>, rotation: Quaternion) -> Self {
+        let (object, rotation) = match object.as_any().downcast_ref::<Rotate>() {
+            Some(inner) if !inner.is_moving() && inner.pivot == Vector3::ZERO => {
+                (inner.object.clone(), rotation * inner.rotation)
+            }
+            _ => (object, rotation),
+        };
+        Self::new_about(object, rotation, Vector3::ZERO)
+    }
+
+    /// Creates a rotation around `pivot` rather than the origin, the same as
+    /// translating by `-pivot`, rotating, then translating back.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use caustic_core::{
+    ///     Color, Interval, Node, Quaternion, Ray, RenderContext, Vector3, random_new,
+    ///     material::Lambertian,
+    ///     object::{Rotate, Sphere},
+    /// };
+    ///
+    /// let material = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.8, 0.8)));
+    /// let sphere = Arc::new(Sphere::new(Vector3::new(2.0, 0.0, 0.0), 0.5, material));
+    /// // A quarter turn around the Y axis through (1, 0, 0) moves the sphere to (1, 0, -1)
+    /// let rotation = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 90.0);
+    /// let rotated = Rotate::new_about(sphere, rotation, Vector3::new(1.0, 0.0, 0.0));
+    ///
+    /// let ctx = RenderContext { random: random_new() };
+    /// let ray = Ray::new(Vector3::new(1.0, 5.0, -1.0), Vector3::new(0.0, -1.0, 0.0));
+    /// let hit = rotated.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+    /// assert!((hit.pt - Vector3::new(1.0, 0.5, -1.0)).length() < 1e-9);
+    /// ```
+    pub fn new_about(object: Arc<dyn Node>, rotation: Quaternion, pivot: Vector3) -> Self {
+        let rotation = rotation.normalize();
+
+        let rotation_matrix = rotation.to_matrix();
+        // The inverse of a unit quaternion is its conjugate
+        let inverse_rotation_matrix = rotation.conjugate().to_matrix();
+
+        let bbox = (*object.bounding_box() + -pivot).transformed(&rotation_matrix) + pivot;
 
         Self {
             object,
+            rotation,
+            rotation_end: rotation,
             rotation_matrix,
             inverse_rotation_matrix,
+            pivot,
             bbox,
         }
     }
 
+    /// Creates a rotation that turns from `rotation_start` at time 0.0 to
+    /// `rotation_end` at time 1.0 along the shortest arc, producing motion blur.
+    ///
+    /// The object may be at any orientation in between, so the bounding box is
+    /// the box around the sphere swept by the object's furthest corner.
+    pub fn new_moving(
+        object: Arc<dyn Node>,
+        rotation_start: Quaternion,
+        rotation_end: Quaternion,
+    ) -> Self {
+        let rotation_start = rotation_start.normalize();
+        let rotation_end = rotation_end.normalize();
+        if rotation_start == rotation_end {
+            return Self::new_from_quaternion(object, rotation_start);
+        }
+
+        let obj_bbox = object.bounding_box();
+        let radius = Axis::iter()
+            .map(|axis| {
+                let interval = obj_bbox.axis_interval(axis);
+                math::powi(interval.min.abs().max(interval.max.abs()), 2)
+            })
+            .sum::<f64>()
+            .sqrt();
+        let radius_vec = Vector3::new(radius, radius, radius);
+
+        Self {
+            object,
+            rotation: rotation_start,
+            rotation_end,
+            rotation_matrix: rotation_start.to_matrix(),
+            inverse_rotation_matrix: rotation_start.conjugate().to_matrix(),
+            pivot: Vector3::ZERO,
+            bbox: AxisAlignedBoundingBox::new_from_points(-radius_vec, radius_vec),
+        }
+    }
+
+    /// Creates a rotation from Euler angles in degrees, applied X first, then Y, then Z
+    pub fn new_from_euler(object: Arc<dyn Node>, angles: Vector3) -> Self {
+        Self::new_from_quaternion(object, Quaternion::from_euler(angles.x, angles.y, angles.z))
+    }
+
+    pub fn get_rotation(&self) -> &Quaternion {
+        &self.rotation
+    }
+
+    /// Returns the rotation at the given time.
+    pub fn get_rotation_at(&self, time: f64) -> Quaternion {
+        if self.is_moving() {
+            Quaternion::slerp(&self.rotation, &self.rotation_end, time)
+        } else {
+            self.rotation
+        }
+    }
+
+    pub fn get_pivot(&self) -> Vector3 {
+        self.pivot
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.rotation != self.rotation_end
+    }
+
+    pub fn get_object(&self) -> &Arc<dyn Node> {
+        &self.object
+    }
+
+    /// Returns the same rotation applied to another object.
+    pub fn with_object(&self, object: Arc<dyn Node>) -> Self {
+        if self.is_moving() {
+            Self::new_moving(object, self.rotation, self.rotation_end)
+        } else {
+            Self::new_about(object, self.rotation, self.pivot)
+        }
+    }
+
     /// Helper function to rotate around the X axis
     pub fn rotate_x(object: Arc<dyn Node>, angle: f64) -> Self {
         Self::new(object, Vector3::new(1.0, 0.0, 0.0), angle)
@@ -92,49 +172,26 @@ impl Rotate {
     pub fn rotate_z(object: Arc<dyn Node>, angle: f64) -> Self {
         Self::new(object, Vector3::new(0.0, 0.0, 1.0), angle)
     }
-
-    fn compute_bounding_box(
-        original_bbox: &AxisAlignedBoundingBox,
-        rotation_matrix: &Matrix3x3,
-    ) -> AxisAlignedBoundingBox {
-        let mut min = Vector3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
-        let mut max = Vector3::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY);
-
-        for i in 0..2 {
-            for j in 0..2 {
-                for k in 0..2 {
-                    let i_f = i as f64;
-                    let j_f = j as f64;
-                    let k_f = k as f64;
-
-                    let x = i_f * original_bbox.axis_interval(Axis::X).max
-                        + (1.0 - i_f) * original_bbox.axis_interval(Axis::X).min;
-                    let y = j_f * original_bbox.axis_interval(Axis::Y).max
-                        + (1.0 - j_f) * original_bbox.axis_interval(Axis::Y).min;
-                    let z = k_f * original_bbox.axis_interval(Axis::Z).max
-                        + (1.0 - k_f) * original_bbox.axis_interval(Axis::Z).min;
-
-                    let corner = Vector3::new(x, y, z);
-                    let rotated = rotation_matrix * corner;
-
-                    for axis in Axis::iter() {
-                        *min.axis_value_mut(axis) =
-                            min.axis_value(axis).min(rotated.axis_value(axis));
-                        *max.axis_value_mut(axis) =
-                            max.axis_value(axis).max(rotated.axis_value(axis));
-                    }
-                }
-            }
-        }
-
-        AxisAlignedBoundingBox::new_from_points(min, max)
-    }
 }
 
 impl Node for Rotate {
     fn hit(&self, ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        if self.is_moving() {
+            let rotation = self.get_rotation_at(ray.time);
+            let inverse_rotation = rotation.conjugate();
+            let origin = inverse_rotation.rotate(ray.origin - self.pivot) + self.pivot;
+            let direction = inverse_rotation.rotate(ray.direction);
+            let rotated_r = Ray::new_with_time(origin, direction, ray.time);
+
+            let mut hit = self.object.hit(ctx, &rotated_r, ray_t)?;
+            hit.pt = rotation.rotate(hit.pt - self.pivot) + self.pivot;
+            hit.normal = rotation.rotate(hit.normal);
+            hit.tangent = rotation.rotate(hit.tangent);
+            return Some(hit);
+        }
+
         // Transform the ray from world space to object space using inverse rotation
-        let origin = &self.inverse_rotation_matrix * ray.origin;
+        let origin = &self.inverse_rotation_matrix * (ray.origin - self.pivot) + self.pivot;
         let direction = &self.inverse_rotation_matrix * ray.direction;
         let rotated_r = Ray::new_with_time(origin, direction, ray.time);
 
@@ -142,8 +199,9 @@ impl Node for Rotate {
         let mut hit = self.object.hit(ctx, &rotated_r, ray_t)?;
 
         // Transform the intersection from object space back to world space
-        hit.pt = &self.rotation_matrix * hit.pt;
+        hit.pt = &self.rotation_matrix * (hit.pt - self.pivot) + self.pivot;
         hit.normal = &self.rotation_matrix * hit.normal;
+        hit.tangent = &self.rotation_matrix * hit.tangent;
 
         Some(hit)
     }
@@ -152,6 +210,23 @@ impl Node for Rotate {
         &self.bbox
     }
 
+    // Lights are sampled in their orientation at time 0.0. Rotations preserve
+    // solid angles, so the object's pdf needs no correction.
+    fn pdf_value(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> f64 {
+        let origin = &self.inverse_rotation_matrix * (*origin - self.pivot) + self.pivot;
+        let direction = &self.inverse_rotation_matrix * *direction;
+        self.object.pdf_value(ctx, &origin, &direction)
+    }
+
+    fn random(&self, ctx: &RenderContext, origin: &Vector3) -> Vector3 {
+        let origin = &self.inverse_rotation_matrix * (*origin - self.pivot) + self.pivot;
+        &self.rotation_matrix * self.object.random(ctx, &origin)
+    }
+
+    fn is_light(&self) -> bool {
+        self.object.is_light()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
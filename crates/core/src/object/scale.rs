@@ -2,12 +2,14 @@ use core::f64;
 use std::{any::Any, sync::Arc};
 
 use crate::{
-    Axis, AxisAlignedBoundingBox, Interval, Matrix3x3, Node, Ray, RenderContext, object::HitRecord,
+    AxisAlignedBoundingBox, Interval, Matrix3x3, Node, Ray, RenderContext, Vector3,
+    object::HitRecord, utils::math,
 };
 
 #[derive(Debug)]
 pub struct Scale {
     object: Arc<dyn Node>,
+    scale: Vector3,
     scale_matrix: Matrix3x3,
     inverse_scale_matrix: Matrix3x3,
     bbox: AxisAlignedBoundingBox,
@@ -47,46 +49,28 @@ impl Scale {
             Matrix3x3::new([[inv_x, 0.0, 0.0], [0.0, inv_y, 0.0], [0.0, 0.0, inv_z]]);
 
         // 3. Compute the new bounding box
-        let obj_bbox = object.bounding_box();
-        let bbox = Self::compute_bounding_box(obj_bbox, scale_x, scale_y, scale_z);
+        let bbox = object.bounding_box().transformed(&scale_matrix);
 
         Self {
             object,
+            scale: Vector3::new(scale_x, scale_y, scale_z),
             scale_matrix,
             inverse_scale_matrix,
             bbox,
         }
     }
 
-    fn compute_bounding_box(
-        original_bbox: &AxisAlignedBoundingBox,
-        scale_x: f64,
-        scale_y: f64,
-        scale_z: f64,
-    ) -> AxisAlignedBoundingBox {
-        // Scaling an AABB by positive scale factors simply scales the min/max of the intervals.
-        // If a scale factor is negative (mirroring), the min/max might swap.
-        let min_x = original_bbox.axis_interval(Axis::X).min;
-        let max_x = original_bbox.axis_interval(Axis::X).max;
-        let min_y = original_bbox.axis_interval(Axis::Y).min;
-        let max_y = original_bbox.axis_interval(Axis::Y).max;
-        let min_z = original_bbox.axis_interval(Axis::Z).min;
-        let max_z = original_bbox.axis_interval(Axis::Z).max;
-
-        // Calculate new bounds after scaling, ensuring min <= max
-        let new_min_x = (min_x * scale_x).min(max_x * scale_x);
-        let new_max_x = (min_x * scale_x).max(max_x * scale_x);
-        let new_min_y = (min_y * scale_y).min(max_y * scale_y);
-        let new_max_y = (min_y * scale_y).max(max_y * scale_y);
-        let new_min_z = (min_z * scale_z).min(max_z * scale_z);
-        let new_max_z = (min_z * scale_z).max(max_z * scale_z);
-
-        // Create the new AxisAlignedBoundingBox
-        AxisAlignedBoundingBox::new_from_intervals(
-            Interval::new(new_min_x, new_max_x),
-            Interval::new(new_min_y, new_max_y),
-            Interval::new(new_min_z, new_max_z),
-        )
+    pub fn get_scale(&self) -> Vector3 {
+        self.scale
+    }
+
+    pub fn get_object(&self) -> &Arc<dyn Node> {
+        &self.object
+    }
+
+    /// Returns the same scaling applied to another object.
+    pub fn with_object(&self, object: Arc<dyn Node>) -> Self {
+        Self::new(object, self.scale.x, self.scale.y, self.scale.z)
     }
 }
 
@@ -113,6 +97,9 @@ impl Node for Scale {
         // Normals also need to be re-normalized after transformation
         hit.normal = hit.normal.unit();
 
+        // c. The tangent lies along the surface, so it scales like the hit point
+        hit.tangent = &self.scale_matrix * hit.tangent;
+
         Some(hit)
     }
 
@@ -120,6 +107,26 @@ impl Node for Scale {
         &self.bbox
     }
 
+    fn pdf_value(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> f64 {
+        let origin = &self.inverse_scale_matrix * *origin;
+        let direction = &self.inverse_scale_matrix * direction.unit();
+        let pdf = self.object.pdf_value(ctx, &origin, &direction);
+
+        // Scaling stretches solid angles, a unit direction d maps to the object
+        // space direction M⁻¹d whose solid angle changes by |det M⁻¹| / |M⁻¹d|³
+        let det = (self.scale.x * self.scale.y * self.scale.z).abs();
+        pdf / (det * math::powi(direction.length(), 3))
+    }
+
+    fn random(&self, ctx: &RenderContext, origin: &Vector3) -> Vector3 {
+        let origin = &self.inverse_scale_matrix * *origin;
+        &self.scale_matrix * self.object.random(ctx, &origin)
+    }
+
+    fn is_light(&self) -> bool {
+        self.object.is_light()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -0,0 +1,228 @@
+use std::{any::Any, fmt::Debug, sync::Arc};
+
+use crate::{
+    AxisAlignedBoundingBox, Interval, Ray, RenderContext, Vector3,
+    material::Material,
+    object::{HitRecord, Node},
+    utils::math,
+};
+
+/// A signed distance function, negative inside the shape and positive outside.
+pub type SdfFunction = dyn Fn(Vector3) -> f64 + Send + Sync;
+
+/// Renders a shape described by a signed distance function using sphere tracing.
+///
+/// The ray is marched through the bounding box, stepping by the distance to the
+/// closest surface each time, until it gets within a small tolerance of the
+/// surface. Functions that only bound the distance (such as [`gyroid`]) should
+/// use a step scale below 1.0 so the march does not overshoot thin features.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     AxisAlignedBoundingBox, Color, Interval, Node, Ray, RenderContext, Vector3, random_new,
+///     material::Lambertian,
+///     object::{SdfNode, sdf},
+/// };
+///
+/// let material = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.3, 0.3)));
+/// let half_size = Vector3::new(1.0, 1.0, 1.0);
+/// let node = SdfNode::new(
+///     Arc::new(sdf::rounded_box(half_size, 0.25)),
+///     AxisAlignedBoundingBox::new_from_points(-half_size, half_size),
+///     material,
+/// );
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let ray = Ray::new(Vector3::new(0.0, 0.0, 5.0), Vector3::new(0.0, 0.0, -1.0));
+/// let hit = node.hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY)).unwrap();
+/// assert!((hit.t - 4.0).abs() < 1e-3);
+/// assert_eq!(hit.normal, Vector3::new(0.0, 0.0, 1.0));
+/// ```
+pub struct SdfNode {
+    sdf: Arc<SdfFunction>,
+    bbox: AxisAlignedBoundingBox,
+    pub material: Arc<dyn Material>,
+    step_scale: f64,
+    max_steps: usize,
+    epsilon: f64,
+}
+
+impl SdfNode {
+    /// Creates a new node. The shape must fit within `bbox`, rays are only
+    /// marched inside it.
+    pub fn new(
+        sdf: Arc<SdfFunction>,
+        bbox: AxisAlignedBoundingBox,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        Self {
+            sdf,
+            bbox,
+            material,
+            step_scale: 1.0,
+            max_steps: 256,
+            epsilon: 1e-4,
+        }
+    }
+
+    /// Scales every marching step, use values below 1.0 for functions that
+    /// overestimate the distance.
+    pub fn with_step_scale(mut self, step_scale: f64) -> Self {
+        self.step_scale = step_scale;
+        self
+    }
+
+    /// Sets the maximum number of steps before a ray is considered a miss.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Sets how close to the surface a ray must get to count as a hit.
+    pub fn with_epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Returns the signed distance from `pt` to the surface.
+    pub fn distance(&self, pt: Vector3) -> f64 {
+        (self.sdf)(pt)
+    }
+
+    /// Estimates the surface normal from the gradient of the distance function
+    /// using central differences.
+    fn normal(&self, pt: Vector3) -> Vector3 {
+        let h = self.epsilon;
+        let dx = Vector3::new(h, 0.0, 0.0);
+        let dy = Vector3::new(0.0, h, 0.0);
+        let dz = Vector3::new(0.0, 0.0, h);
+        Vector3::new(
+            self.distance(pt + dx) - self.distance(pt - dx),
+            self.distance(pt + dy) - self.distance(pt - dy),
+            self.distance(pt + dz) - self.distance(pt - dz),
+        )
+        .unit()
+    }
+}
+
+impl Debug for SdfNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SdfNode")
+            .field("bbox", &self.bbox)
+            .field("material", &self.material)
+            .field("step_scale", &self.step_scale)
+            .field("max_steps", &self.max_steps)
+            .field("epsilon", &self.epsilon)
+            .finish()
+    }
+}
+
+impl Node for SdfNode {
+    fn hit(&self, _ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let clipped = self.bbox.hit_interval(ray, ray_t)?;
+        let direction_length = ray.direction.length();
+
+        // March on the absolute distance so rays that start inside the shape
+        // (e.g. refracted into it) find the surface on the far side
+        let mut t = clipped.min;
+        for _ in 0..self.max_steps {
+            if t > clipped.max {
+                return None;
+            }
+
+            let pt = ray.at(t);
+            let distance = self.distance(pt).abs();
+            if distance < self.epsilon && ray_t.surrounds(t) {
+                let mut rec = HitRecord {
+                    pt,
+                    normal: Vector3::ZERO, // set by set_face_normal
+                    tangent: Vector3::ZERO,
+                    t,
+                    u: 0.0,
+                    v: 0.0,
+                    front_face: false,
+                    material: self.material.clone(),
+                    object_id: 0,
+                };
+                rec.set_face_normal(ray, self.normal(pt));
+                return Some(rec);
+            }
+
+            t += distance.max(self.epsilon) * self.step_scale / direction_length;
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        &self.bbox
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// A sphere of the given radius centered at the origin.
+pub fn sphere(radius: f64) -> impl Fn(Vector3) -> f64 + Send + Sync {
+    move |pt| pt.length() - radius
+}
+
+/// A box centered at the origin with rounded edges. `half_size` is the distance
+/// from the center to each face, including the rounding.
+pub fn rounded_box(half_size: Vector3, radius: f64) -> impl Fn(Vector3) -> f64 + Send + Sync {
+    move |pt| {
+        let q = Vector3::new(
+            pt.x.abs() - half_size.x + radius,
+            pt.y.abs() - half_size.y + radius,
+            pt.z.abs() - half_size.z + radius,
+        );
+        let outside = Vector3::new(q.x.max(0.0), q.y.max(0.0), q.z.max(0.0)).length();
+        let inside = q.x.max(q.y).max(q.z).min(0.0);
+        outside + inside - radius
+    }
+}
+
+/// A torus around the Y axis centered at the origin.
+pub fn torus(major_radius: f64, minor_radius: f64) -> impl Fn(Vector3) -> f64 + Send + Sync {
+    move |pt| {
+        let ring = (pt.x * pt.x + pt.z * pt.z).sqrt() - major_radius;
+        (ring * ring + pt.y * pt.y).sqrt() - minor_radius
+    }
+}
+
+/// A gyroid sheet of the given thickness, repeating every `2π / scale` units.
+///
+/// The gyroid is infinite, so intersect it with a bounding shape or box. The
+/// function is not an exact distance, use a step scale of about 0.5.
+pub fn gyroid(scale: f64, thickness: f64) -> impl Fn(Vector3) -> f64 + Send + Sync {
+    move |pt| {
+        let p = scale * pt;
+        let value = math::sin(p.x) * math::cos(p.y)
+            + math::sin(p.y) * math::cos(p.z)
+            + math::sin(p.z) * math::cos(p.x);
+        value.abs() / scale - thickness / 2.0
+    }
+}
+
+/// The volume inside both `a` and `b`.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Vector3, object::sdf};
+///
+/// // A gyroid lattice trimmed to a sphere
+/// let lattice = sdf::intersection(sdf::gyroid(4.0, 0.1), sdf::sphere(1.0));
+/// assert!(lattice(Vector3::new(2.0, 0.0, 0.0)) > 0.0);
+/// ```
+pub fn intersection<A, B>(a: A, b: B) -> impl Fn(Vector3) -> f64 + Send + Sync
+where
+    A: Fn(Vector3) -> f64 + Send + Sync,
+    B: Fn(Vector3) -> f64 + Send + Sync,
+{
+    move |pt| a(pt).max(b(pt))
+}
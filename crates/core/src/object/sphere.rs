@@ -7,6 +7,7 @@ use crate::{
     object::{HitRecord, Node},
     ray::Ray,
     utils::OrthonormalBasis,
+    utils::math,
 };
 
 #[derive(Debug)]
@@ -33,6 +34,15 @@ impl Sphere {
         self.update_bbox();
     }
 
+    /// Returns the center of the sphere at the given time.
+    pub fn get_center(&self, time: f64) -> Vector3 {
+        self.center.at(time)
+    }
+
+    pub fn get_radius(&self) -> f64 {
+        self.radius
+    }
+
     fn update_bbox(&mut self) {
         let rvec = Vector3::new(self.radius, self.radius, self.radius);
         let box1 = AxisAlignedBoundingBox::new_from_points(
@@ -70,26 +80,33 @@ impl Sphere {
     pub fn get_uv(pt: Vector3) -> (f64, f64) {
         // produces a polar angle where the south pole maps to 0 and the north
         // pole maps to 1 after normalization.
-        let theta = (-pt.y).acos();
+        let theta = math::acos(-pt.y);
 
         // yields an azimuth that wraps `[0, 2π)` with `u = 0` at `(-1, 0, 0)`
         // and increasing counterclockwise when viewed from above the positive
         // Y axis.
-        let phi = (-pt.z).atan2(pt.x) + PI;
+        let phi = math::atan2(-pt.z, pt.x) + PI;
 
         let u = phi / (2.0 * PI);
         let v = theta / PI;
         (u, v)
     }
 
-    fn random_to_sphere(random: &dyn Random, radius: f64, distance_squared: f64) -> Vector3 {
+    /// Returns a random direction, about the z axis, towards a sphere of
+    /// `radius` whose center is `distance_squared` away along z, spread
+    /// evenly over the cone the sphere fills.
+    pub(crate) fn random_to_sphere(
+        random: &dyn Random,
+        radius: f64,
+        distance_squared: f64,
+    ) -> Vector3 {
         let r1 = random.rand();
         let r2 = random.rand();
         let z = 1.0 + r2 * ((1.0 - radius * radius / distance_squared).sqrt() - 1.0);
 
         let phi = 2.0 * f64::consts::PI * r1;
-        let x = phi.cos() * (1.0 - z * z).sqrt();
-        let y = phi.sin() * (1.0 - z * z).sqrt();
+        let x = math::cos(phi) * (1.0 - z * z).sqrt();
+        let y = math::sin(phi) * (1.0 - z * z).sqrt();
 
         Vector3::new(x, y, z)
     }
@@ -125,11 +142,13 @@ impl Node for Sphere {
         let mut rec = HitRecord {
             pt,
             normal: Vector3::ZERO, // set by set_face_normal
+            tangent: Vector3::new(outward_normal.z, 0.0, -outward_normal.x),
             t,
             u,
             v,
             front_face: false,
             material: self.material.clone(),
+            object_id: 0,
         };
         rec.set_face_normal(ray, outward_normal);
 
@@ -169,6 +188,10 @@ impl Node for Sphere {
         ))
     }
 
+    fn is_light(&self) -> bool {
+        self.material.is_emissive()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -7,7 +7,9 @@ use crate::{
 #[derive(Debug)]
 pub struct Translate {
     object: Arc<dyn Node>,
-    offset: Vector3,
+    /// Offset at time 0.0 as the origin, moving along the direction, the
+    /// change over one unit of ray time
+    offset: Ray,
     bbox: AxisAlignedBoundingBox,
 }
 
@@ -16,22 +18,50 @@ impl Translate {
         let bbox = *object.bounding_box() + offset;
         Self {
             object,
-            offset,
+            offset: Ray::new(offset, Vector3::ZERO),
             bbox,
         }
     }
+
+    /// Creates a translation that moves linearly from `offset_start` at time 0.0
+    /// to `offset_end` at time 1.0, producing motion blur.
+    pub fn new_moving(object: Arc<dyn Node>, offset_start: Vector3, offset_end: Vector3) -> Self {
+        let bbox = AxisAlignedBoundingBox::new_from_bbox(
+            *object.bounding_box() + offset_start,
+            *object.bounding_box() + offset_end,
+        );
+        Self {
+            object,
+            offset: Ray::new(offset_start, offset_end - offset_start),
+            bbox,
+        }
+    }
+
+    pub fn get_offset(&self, time: f64) -> Vector3 {
+        self.offset.at(time)
+    }
+
+    pub fn get_object(&self) -> &Arc<dyn Node> {
+        &self.object
+    }
+
+    /// Returns the same translation applied to another object.
+    pub fn with_object(&self, object: Arc<dyn Node>) -> Self {
+        Self::new_moving(object, self.get_offset(0.0), self.get_offset(1.0))
+    }
 }
 
 impl Node for Translate {
     fn hit(&self, ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
         // Move the ray backwards by the offset
-        let offset_r = Ray::new_with_time(ray.origin - self.offset, ray.direction, ray.time);
+        let offset = self.offset.at(ray.time);
+        let offset_r = Ray::new_with_time(ray.origin - offset, ray.direction, ray.time);
 
         // Determine whether an intersection exists along the offset ray (and if so, where)
         let mut hit = self.object.hit(ctx, &offset_r, ray_t)?;
 
         // Move the intersection point forwards by the offset
-        hit.pt = hit.pt + self.offset;
+        hit.pt = hit.pt + offset;
 
         Some(hit)
     }
@@ -40,6 +70,20 @@ impl Node for Translate {
         &self.bbox
     }
 
+    // Lights are sampled where they are at time 0.0
+    fn pdf_value(&self, ctx: &RenderContext, origin: &Vector3, direction: &Vector3) -> f64 {
+        self.object
+            .pdf_value(ctx, &(*origin - self.offset.origin), direction)
+    }
+
+    fn random(&self, ctx: &RenderContext, origin: &Vector3) -> Vector3 {
+        self.object.random(ctx, &(*origin - self.offset.origin))
+    }
+
+    fn is_light(&self) -> bool {
+        self.object.is_light()
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -0,0 +1,333 @@
+use std::{any::Any, sync::Arc};
+
+use crate::{
+    AxisAlignedBoundingBox, Interval, Ray, RenderContext, Vector3,
+    material::Material,
+    object::{BoundingVolumeHierarchy, HitRecord, Node},
+};
+
+/// A mesh of triangles sharing a list of vertices, as loaded from model files.
+///
+/// Every triangle has an index into the mesh's list of materials, so models
+/// made of several materials are still a single node. The triangles are kept
+/// in a bounding volume hierarchy internal to the mesh.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Interval, Node, Ray, RenderContext, Vector3, random_new,
+///     material::{Lambertian, Material},
+///     object::TriangleMesh,
+/// };
+///
+/// let red: Arc<dyn Material> = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.1, 0.1)));
+/// let blue: Arc<dyn Material> = Arc::new(Lambertian::new_from_color(Color::new(0.1, 0.1, 0.8)));
+///
+/// // A unit square in the XZ plane, the left half red and the right half blue
+/// let square = TriangleMesh::new_with_materials(
+///     vec![
+///         Vector3::new(0.0, 0.0, 0.0),
+///         Vector3::new(1.0, 0.0, 0.0),
+///         Vector3::new(1.0, 0.0, 1.0),
+///         Vector3::new(0.0, 0.0, 1.0),
+///     ],
+///     vec![[0, 1, 2], [0, 2, 3]],
+///     vec![red.clone(), blue.clone()],
+///     vec![0, 1],
+/// );
+/// assert_eq!(square.get_material_index(1), 1);
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let down = Vector3::new(0.0, -1.0, 0.0);
+/// let hit = square
+///     .hit(&ctx, &Ray::new(Vector3::new(0.2, 1.0, 0.8), down), Interval::new(0.001, f64::INFINITY))
+///     .unwrap();
+/// assert!(Arc::ptr_eq(&hit.material, &blue));
+/// ```
+#[derive(Debug)]
+pub struct TriangleMesh {
+    mesh: Arc<MeshData>,
+    bvh: BoundingVolumeHierarchy,
+}
+
+#[derive(Debug)]
+struct MeshData {
+    vertices: Vec<Vector3>,
+    triangles: Vec<[usize; 3]>,
+    materials: Vec<Arc<dyn Material>>,
+    /// Index into `materials` of every triangle
+    material_indices: Vec<usize>,
+}
+
+impl TriangleMesh {
+    /// Creates a mesh whose triangles all use the same material.
+    pub fn new(
+        vertices: Vec<Vector3>,
+        triangles: Vec<[usize; 3]>,
+        material: Arc<dyn Material>,
+    ) -> Self {
+        let material_indices = vec![0; triangles.len()];
+        Self::new_with_materials(vertices, triangles, vec![material], material_indices)
+    }
+
+    /// Creates a mesh where triangle `i` uses `materials[material_indices[i]]`.
+    /// Triangles referring to missing vertices are left out, and triangles
+    /// without a valid material index use the first material.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `materials` is empty.
+    pub fn new_with_materials(
+        vertices: Vec<Vector3>,
+        triangles: Vec<[usize; 3]>,
+        materials: Vec<Arc<dyn Material>>,
+        material_indices: Vec<usize>,
+    ) -> Self {
+        assert!(!materials.is_empty(), "a mesh needs at least one material");
+
+        let material_indices = (0..triangles.len())
+            .map(|i| {
+                material_indices
+                    .get(i)
+                    .copied()
+                    .filter(|index| *index < materials.len())
+                    .unwrap_or(0)
+            })
+            .collect();
+        let mesh = Arc::new(MeshData {
+            vertices,
+            triangles,
+            materials,
+            material_indices,
+        });
+
+        let nodes: Vec<Arc<dyn Node>> = mesh
+            .triangles
+            .iter()
+            .enumerate()
+            .filter(|(_, triangle)| triangle.iter().all(|i| *i < mesh.vertices.len()))
+            .map(|(index, _)| Arc::new(MeshTriangle::new(mesh.clone(), index)) as Arc<dyn Node>)
+            .collect();
+        let bvh = BoundingVolumeHierarchy::new(&nodes);
+
+        Self { mesh, bvh }
+    }
+
+    pub fn get_vertices(&self) -> &[Vector3] {
+        &self.mesh.vertices
+    }
+
+    pub fn get_triangles(&self) -> &[[usize; 3]] {
+        &self.mesh.triangles
+    }
+
+    pub fn get_materials(&self) -> &[Arc<dyn Material>] {
+        &self.mesh.materials
+    }
+
+    /// Returns the index into the materials of the given triangle.
+    pub fn get_material_index(&self, triangle: usize) -> usize {
+        self.mesh.material_indices[triangle]
+    }
+}
+
+impl Node for TriangleMesh {
+    fn hit(&self, ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        self.bvh.hit(ctx, ray, ray_t)
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        self.bvh.bounding_box()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Vertices and triangles of a mesh converted by the web app from an uploaded
+/// STL or OBJ file, with vertices welded, degenerate triangles dropped and
+/// every triangle counter clockwise seen from outside.
+///
+/// The format is little endian: [`ConvertedMesh::MAGIC`], the vertex, triangle
+/// and hierarchy node counts as `u32`, the vertices as three `f64`, the
+/// triangles as three `u32` vertex indices, then the hierarchy nodes of 56
+/// bytes each. The nodes are skipped, a [`TriangleMesh`] builds its own.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Vector3, object::ConvertedMesh};
+///
+/// let mut data = ConvertedMesh::MAGIC.to_vec();
+/// for count in [3u32, 1, 0] {
+///     data.extend_from_slice(&count.to_le_bytes());
+/// }
+/// for v in [0.0f64, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+///     data.extend_from_slice(&v.to_le_bytes());
+/// }
+/// for i in [0u32, 1, 2] {
+///     data.extend_from_slice(&i.to_le_bytes());
+/// }
+///
+/// let mesh = ConvertedMesh::decode(&data).unwrap();
+/// assert_eq!(mesh.vertices[1], Vector3::new(1.0, 0.0, 0.0));
+/// assert_eq!(mesh.triangles, vec![[0, 1, 2]]);
+/// assert!(ConvertedMesh::decode(&data[..data.len() - 1]).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertedMesh {
+    pub vertices: Vec<Vector3>,
+    pub triangles: Vec<[usize; 3]>,
+}
+
+impl ConvertedMesh {
+    /// Start of every converted mesh, the last byte is the format version
+    pub const MAGIC: &[u8; 8] = b"CAUMESH1";
+
+    const HEADER_SIZE: usize = 20;
+
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        let header = data
+            .get(..Self::HEADER_SIZE)
+            .ok_or("converted mesh is too short")?;
+        if !header.starts_with(Self::MAGIC) {
+            return Err("not a converted mesh".to_owned());
+        }
+        let count =
+            |i: usize| u32::from_le_bytes(header[8 + i * 4..12 + i * 4].try_into().unwrap()) as u64;
+        let (vertex_count, triangle_count, node_count) = (count(0), count(1), count(2));
+        // Counted in u64, which the u32 counts cannot overflow, so once the
+        // size matches the file every offset fits in a usize
+        let size =
+            Self::HEADER_SIZE as u64 + vertex_count * 24 + triangle_count * 12 + node_count * 56;
+        if data.len() as u64 != size {
+            return Err(format!(
+                "converted mesh should be {size} bytes, found {}",
+                data.len()
+            ));
+        }
+
+        let (vertex_data, rest) = data[Self::HEADER_SIZE..].split_at(vertex_count as usize * 24);
+        let vertices = vertex_data
+            .chunks_exact(24)
+            .map(|vertex| {
+                let coordinate =
+                    |i: usize| f64::from_le_bytes(vertex[i * 8..i * 8 + 8].try_into().unwrap());
+                Vector3::new(coordinate(0), coordinate(1), coordinate(2))
+            })
+            .collect();
+        let triangles: Vec<[usize; 3]> = rest[..triangle_count as usize * 12]
+            .chunks_exact(12)
+            .map(|triangle| {
+                std::array::from_fn(|i| {
+                    u32::from_le_bytes(triangle[i * 4..i * 4 + 4].try_into().unwrap()) as usize
+                })
+            })
+            .collect();
+        if triangles
+            .iter()
+            .flatten()
+            .any(|i| *i as u64 >= vertex_count)
+        {
+            return Err("converted mesh refers to a missing vertex".to_owned());
+        }
+
+        Ok(Self {
+            vertices,
+            triangles,
+        })
+    }
+}
+
+/// One triangle of a mesh, referring to the shared mesh data by index.
+#[derive(Debug)]
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    index: usize,
+    bbox: AxisAlignedBoundingBox,
+}
+
+impl MeshTriangle {
+    fn new(mesh: Arc<MeshData>, index: usize) -> Self {
+        let [a, b, c] = mesh.triangles[index].map(|i| mesh.vertices[i]);
+        let bbox = AxisAlignedBoundingBox::new_from_bbox(
+            AxisAlignedBoundingBox::new_from_points(a, b),
+            AxisAlignedBoundingBox::new_from_points(c, c),
+        );
+        Self { mesh, index, bbox }
+    }
+}
+
+impl Node for MeshTriangle {
+    fn hit(&self, _ctx: &RenderContext, ray: &Ray, ray_t: Interval) -> Option<HitRecord> {
+        let [a, b, c] = self.mesh.triangles[self.index].map(|i| self.mesh.vertices[i]);
+        let (t, u, v) = moller_trumbore(a, b, c, ray, ray_t)?;
+        let edge1 = b - a;
+        let edge2 = c - a;
+
+        let material_index = self.mesh.material_indices[self.index];
+        let mut rec = HitRecord {
+            pt: ray.at(t),
+            normal: Vector3::ZERO,
+            tangent: edge1,
+            t,
+            u,
+            v,
+            front_face: false,
+            material: self.mesh.materials[material_index].clone(),
+            object_id: 0,
+        };
+        // Counter-clockwise triangles face the viewer
+        rec.set_face_normal(ray, edge1.cross(&edge2).unit());
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> &AxisAlignedBoundingBox {
+        &self.bbox
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Möller–Trumbore ray/triangle intersection, returning the distance
+/// along the ray and the barycentric coordinates of the hit.
+#[inline]
+fn moller_trumbore(
+    a: Vector3,
+    b: Vector3,
+    c: Vector3,
+    ray: &Ray,
+    ray_t: Interval,
+) -> Option<(f64, f64, f64)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+    let p = ray.direction.cross(&edge2);
+    let det = edge1.dot(&p);
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let s = ray.origin - a;
+    let u = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let v = ray.direction.dot(&q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(&q) * inv_det;
+    if !ray_t.surrounds(t) {
+        return None;
+    }
+    Some((t, u, v))
+}
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use crate::{Background, ProbabilityDensityFunction, RenderContext, Vector3};
+
+/// Samples directions towards the bright areas of the background.
+pub struct BackgroundPdf {
+    background: Arc<dyn Background>,
+}
+
+impl BackgroundPdf {
+    pub fn new(background: Arc<dyn Background>) -> Self {
+        Self { background }
+    }
+}
+
+impl ProbabilityDensityFunction for BackgroundPdf {
+    fn value(&self, _ctx: &RenderContext, direction: &Vector3) -> f64 {
+        self.background.pdf_value(&direction.unit())
+    }
+
+    fn generate(&self, ctx: &RenderContext) -> Vector3 {
+        self.background.random(ctx)
+    }
+}
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use crate::{ProbabilityDensityFunction, RenderContext, Vector3, light::Light};
+
+/// Samples directions from `origin` towards a [`Light`].
+pub struct LightPdf {
+    light: Arc<dyn Light>,
+    origin: Vector3,
+}
+
+impl LightPdf {
+    pub fn new(light: Arc<dyn Light>, origin: Vector3) -> Self {
+        Self { light, origin }
+    }
+}
+
+impl ProbabilityDensityFunction for LightPdf {
+    fn value(&self, ctx: &RenderContext, direction: &Vector3) -> f64 {
+        self.light.pdf_value(ctx, &self.origin, direction)
+    }
+
+    fn generate(&self, ctx: &RenderContext) -> Vector3 {
+        self.light.sample_direction(ctx, &self.origin)
+    }
+}
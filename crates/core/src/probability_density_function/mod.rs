@@ -1,10 +1,16 @@
+pub mod background;
 pub mod cosine;
+pub mod ggx;
 pub mod hittable;
+pub mod light;
 pub mod mixture;
 pub mod sphere;
 
+pub use background::BackgroundPdf;
 pub use cosine::CosinePdf;
+pub use ggx::GgxPdf;
 pub use hittable::HittablePdf;
+pub use light::LightPdf;
 pub use mixture::MixturePdf;
 pub use sphere::SpherePdf;
 
@@ -16,3 +22,27 @@ pub trait ProbabilityDensityFunction: Send + Sync {
     fn value(&self, ctx: &RenderContext, direction: &Vector3) -> f64;
     fn generate(&self, ctx: &RenderContext) -> Vector3;
 }
+
+/// Weight of a sample drawn with one of two sampling strategies, by the power
+/// heuristic (exponent 2). `pdf_f` is the density of the strategy the sample
+/// was drawn with, `pdf_g` the density of the other strategy for the same
+/// direction.
+///
+/// Compared to a plain average of both densities, the power heuristic leans
+/// harder on whichever strategy is better at a direction, which cuts the noise
+/// of glossy surfaces lit by small lights.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::probability_density_function::power_heuristic;
+///
+/// assert_eq!(power_heuristic(1.0, 1.0), 0.5);
+/// assert_eq!(power_heuristic(3.0, 1.0), 0.9);
+/// assert_eq!(power_heuristic(0.0, 0.0), 0.0);
+/// ```
+pub fn power_heuristic(pdf_f: f64, pdf_g: f64) -> f64 {
+    let f = pdf_f * pdf_f;
+    let g = pdf_g * pdf_g;
+    if f + g > 0.0 { f / (f + g) } else { 0.0 }
+}
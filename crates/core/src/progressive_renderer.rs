@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use crate::{Camera, Color, Light, Node, RenderContext, SceneData, TransferFunction};
+
+/// Renders a scene one pass of samples at a time, keeping the sum of the linear
+/// colors of every pass, so a preview can show an image of the whole frame that
+/// refines over time instead of filling in block by block.
+///
+/// Passes can be rendered whole with [`ProgressiveRenderer::add_sample_pass`]
+/// or pixel by pixel with [`ProgressiveRenderer::render_next_pixel`], for
+/// callers which must return control regularly.
+///
+/// # Examples
+///
+/// ```
+/// use std::{collections::HashMap, sync::Arc};
+/// use caustic_core::{
+///     CameraBuilder, Color, Node, ProgressiveRenderer, RenderContext, SceneData,
+///     TransferFunction, Vector3, material::Lambertian, object::Sphere, random_new,
+/// };
+///
+/// let mut camera_builder = CameraBuilder::new();
+/// camera_builder.image_width = 4;
+/// camera_builder.samples_per_pixel = 1;
+/// camera_builder.background = Arc::new(Color::new(0.5, 0.7, 1.0));
+/// let material = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.2, 0.2)));
+/// let scene = SceneData {
+///     camera: Arc::new(camera_builder.build()),
+///     cameras: HashMap::new(),
+///     world: Arc::new(Sphere::new(Vector3::new(0.0, 0.0, -2.0), 1.0, material)),
+///     lights: None,
+///     caustic_targets: vec![],
+/// };
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let mut renderer = ProgressiveRenderer::new(&scene);
+/// renderer.add_sample_pass(&ctx);
+/// renderer.add_sample_pass(&ctx);
+/// assert_eq!(renderer.passes(), 2);
+///
+/// let image = renderer.current_image(TransferFunction::Srgb);
+/// assert_eq!(image.len(), 16);
+/// assert!(image.iter().all(|color| color.r < 1.0));
+///
+/// // Half way through a pass, the remaining pixels keep their earlier passes
+/// renderer.render_next_pixel(&ctx);
+/// assert_eq!(renderer.passes(), 2);
+/// assert_eq!(renderer.samples_at(0, 0), 3);
+/// assert_eq!(renderer.samples_at(1, 0), 2);
+/// ```
+#[derive(Debug)]
+pub struct ProgressiveRenderer {
+    camera: Arc<Camera>,
+    world: Arc<dyn Node>,
+    lights: Option<Arc<dyn Light>>,
+    width: u32,
+    height: u32,
+    /// Passes completed over every pixel
+    passes: u32,
+    /// Index of the next pixel to render in the current pass
+    next_pixel: u32,
+    /// Sum of the linear colors of every pass, per pixel
+    sums: Vec<Color>,
+}
+
+impl ProgressiveRenderer {
+    /// Creates a renderer for the camera of `scene`, with no passes rendered.
+    pub fn new(scene: &SceneData) -> Self {
+        let width = scene.camera.image_width();
+        let height = scene.camera.image_height();
+        Self {
+            camera: scene.camera.clone(),
+            world: scene.world.clone(),
+            lights: scene.lights.clone(),
+            width,
+            height,
+            passes: 0,
+            next_pixel: 0,
+            sums: vec![Color::BLACK; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the number of passes completed over every pixel.
+    pub fn passes(&self) -> u32 {
+        self.passes
+    }
+
+    /// Returns the index, in row order, of the next pixel of the current pass.
+    pub fn next_pixel(&self) -> u32 {
+        self.next_pixel
+    }
+
+    pub fn pixel_count(&self) -> u32 {
+        self.width * self.height
+    }
+
+    /// Renders the rest of the current pass, every pixel when none of it was
+    /// rendered yet.
+    pub fn add_sample_pass(&mut self, ctx: &RenderContext) {
+        let passes = self.passes;
+        while self.passes == passes {
+            self.render_next_pixel(ctx);
+        }
+    }
+
+    /// Renders one pixel of the current pass, starting the next pass after the
+    /// last pixel.
+    pub fn render_next_pixel(&mut self, ctx: &RenderContext) {
+        // An empty image has nothing to render, each pass is done at once
+        if self.pixel_count() == 0 {
+            self.passes += 1;
+            return;
+        }
+
+        let x = self.next_pixel % self.width;
+        let y = self.next_pixel / self.width;
+        self.sums[self.next_pixel as usize] +=
+            self.camera
+                .render_pass(ctx, x, y, self.passes, &*self.world, self.lights.clone());
+
+        self.next_pixel += 1;
+        if self.next_pixel >= self.pixel_count() {
+            self.passes += 1;
+            self.next_pixel = 0;
+        }
+    }
+
+    /// Returns the number of passes summed for a pixel.
+    pub fn samples_at(&self, x: u32, y: u32) -> u32 {
+        if y * self.width + x < self.next_pixel {
+            self.passes + 1
+        } else {
+            self.passes
+        }
+    }
+
+    /// Returns the averaged linear color of a pixel, black before its first pass.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Color {
+        match self.samples_at(x, y) {
+            0 => Color::BLACK,
+            samples => self.sums[(y * self.width + x) as usize] / samples as f64,
+        }
+    }
+
+    /// Returns the averaged colors of rows `ymin..ymax` in row order, encoded
+    /// with `transfer` for display.
+    pub fn rows(&self, ymin: u32, ymax: u32, transfer: TransferFunction) -> Vec<Color> {
+        (ymin..ymax)
+            .flat_map(|y| (0..self.width).map(move |x| (x, y)))
+            .map(|(x, y)| self.get_pixel(x, y).encode(transfer))
+            .collect()
+    }
+
+    /// Returns the averaged colors of the whole image in row order, encoded
+    /// with `transfer` for display.
+    pub fn current_image(&self, transfer: TransferFunction) -> Vec<Color> {
+        self.rows(0, self.height, transfer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use super::ProgressiveRenderer;
+    use crate::{
+        CameraBuilder, Color, RenderContext, SceneData, TransferFunction, Vector3,
+        material::Lambertian, object::Sphere,
+    };
+
+    #[test]
+    fn empty_images_complete_their_passes() {
+        let mut camera_builder = CameraBuilder::new();
+        camera_builder.image_width = 0;
+        let material = Arc::new(Lambertian::new_from_color(Color::WHITE));
+        let scene = SceneData {
+            camera: Arc::new(camera_builder.build()),
+            cameras: HashMap::new(),
+            world: Arc::new(Sphere::new(Vector3::ZERO, 1.0, material)),
+            lights: None,
+            caustic_targets: vec![],
+        };
+
+        let ctx = RenderContext::new_seeded(1);
+        let mut renderer = ProgressiveRenderer::new(&scene);
+        assert_eq!(renderer.pixel_count(), 0);
+        renderer.add_sample_pass(&ctx);
+        renderer.render_next_pixel(&ctx);
+        assert_eq!(renderer.passes(), 2);
+        assert!(renderer.current_image(TransferFunction::Srgb).is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+use crate::{Matrix3x3, Vector3, utils::math};
+use std::ops::Mul;
+
+/// A quaternion `w + xi + yj + zk`, used to represent rotations in 3D space.
+///
+/// Unit quaternions compose with multiplication, where `a * b` applies `b`
+/// first and then `a`, the same as multiplying rotation matrices. Unlike
+/// Euler angles they interpolate smoothly with [`Quaternion::slerp`].
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Quaternion, Vector3};
+///
+/// let q = Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), 90.0);
+/// let v = q.rotate(Vector3::new(1.0, 0.0, 0.0));
+/// assert_eq!(v, Vector3::new(0.0, 1.0, 0.0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    /// The identity rotation.
+    pub const IDENTITY: Quaternion = Quaternion::new(1.0, 0.0, 0.0, 0.0);
+
+    /// Creates a new quaternion from its scalar part `w` and vector part `(x, y, z)`.
+    pub const fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z }
+    }
+
+    /// Creates a rotation of `angle` degrees around `axis`. The axis does not
+    /// need to be normalized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{Quaternion, Vector3};
+    ///
+    /// let q = Quaternion::from_axis_angle(Vector3::new(0.0, 2.0, 0.0), 180.0);
+    /// assert_eq!(q.rotate(Vector3::new(1.0, 0.0, 0.0)), Vector3::new(-1.0, 0.0, 0.0));
+    /// ```
+    pub fn from_axis_angle(axis: Vector3, angle: f64) -> Self {
+        let half = angle.to_radians() / 2.0;
+        let axis = axis.unit();
+        let s = math::sin(half);
+        Self::new(math::cos(half), axis.x * s, axis.y * s, axis.z * s)
+    }
+
+    /// Returns the rotation axis and angle in degrees. The identity rotation
+    /// returns the X axis and an angle of zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{Quaternion, Vector3};
+    ///
+    /// let q = Quaternion::from_axis_angle(Vector3::new(1.0, 1.0, 0.0), 60.0);
+    /// let (axis, angle) = q.to_axis_angle();
+    /// assert_eq!(axis, Vector3::new(1.0, 1.0, 0.0).unit());
+    /// assert!((angle - 60.0).abs() < 1e-9);
+    /// ```
+    pub fn to_axis_angle(&self) -> (Vector3, f64) {
+        let q = self.normalize();
+        let s = (1.0 - q.w * q.w).max(0.0).sqrt();
+        if s < 1e-12 {
+            return (Vector3::new(1.0, 0.0, 0.0), 0.0);
+        }
+        let angle = 2.0 * math::acos(q.w.clamp(-1.0, 1.0));
+        (Vector3::new(q.x / s, q.y / s, q.z / s), angle.to_degrees())
+    }
+
+    /// Creates a rotation from Euler angles in degrees. The rotation around X is
+    /// applied first, then Y, then Z, matching OpenSCAD's `rotate([x, y, z])`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{Quaternion, Vector3};
+    ///
+    /// let q = Quaternion::from_euler(90.0, 0.0, 90.0);
+    /// // X takes +Y to +Z, then Z leaves +Z alone
+    /// assert_eq!(q.rotate(Vector3::new(0.0, 1.0, 0.0)), Vector3::new(0.0, 0.0, 1.0));
+    /// ```
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Self {
+        Self::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), z)
+            * Self::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), y)
+            * Self::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), x)
+    }
+
+    /// Returns the Euler angles in degrees, in the same convention as
+    /// [`Quaternion::from_euler`]. The Y angle is in the range [-90, 90].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{Quaternion, Vector3};
+    ///
+    /// let q = Quaternion::from_euler(10.0, 20.0, 30.0);
+    /// assert_eq!(q.to_euler(), Vector3::new(10.0, 20.0, 30.0));
+    /// ```
+    pub fn to_euler(&self) -> Vector3 {
+        let q = self.normalize();
+        let sin_y = (2.0 * (q.w * q.y - q.z * q.x)).clamp(-1.0, 1.0);
+        let x = math::atan2(
+            2.0 * (q.w * q.x + q.y * q.z),
+            1.0 - 2.0 * (q.x * q.x + q.y * q.y),
+        );
+        let y = math::asin(sin_y);
+        let z = math::atan2(
+            2.0 * (q.w * q.z + q.x * q.y),
+            1.0 - 2.0 * (q.y * q.y + q.z * q.z),
+        );
+        Vector3::new(x.to_degrees(), y.to_degrees(), z.to_degrees())
+    }
+
+    pub fn dot(&self, other: &Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn length(&self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    /// Returns this quaternion scaled to unit length.
+    pub fn normalize(&self) -> Self {
+        let len = self.length();
+        Self::new(self.w / len, self.x / len, self.y / len, self.z / len)
+    }
+
+    /// Returns the conjugate, which is the inverse rotation for unit quaternions.
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Rotates a vector by this quaternion, which is assumed to be of unit length.
+    pub fn rotate(&self, v: Vector3) -> Vector3 {
+        // v' = v + 2w(u x v) + 2u x (u x v), where u is the vector part
+        let u = Vector3::new(self.x, self.y, self.z);
+        let t = 2.0 * u.cross(&v);
+        v + self.w * t + u.cross(&t)
+    }
+
+    /// Converts this quaternion, which is assumed to be of unit length, to a rotation matrix.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{Quaternion, Vector3};
+    ///
+    /// let q = Quaternion::from_euler(30.0, 45.0, 60.0);
+    /// let v = Vector3::new(1.0, 2.0, 3.0);
+    /// assert_eq!(&q.to_matrix() * v, q.rotate(v));
+    /// ```
+    pub fn to_matrix(&self) -> Matrix3x3 {
+        let Self { w, x, y, z } = *self;
+        Matrix3x3::new([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y - w * z),
+                2.0 * (x * z + w * y),
+            ],
+            [
+                2.0 * (x * y + w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z - w * x),
+            ],
+            [
+                2.0 * (x * z - w * y),
+                2.0 * (y * z + w * x),
+                1.0 - 2.0 * (x * x + y * y),
+            ],
+        ])
+    }
+
+    /// Spherical linear interpolation between two unit quaternions, following
+    /// the shortest path. `t = 0` returns `a` and `t = 1` returns `b`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{Quaternion, Vector3};
+    ///
+    /// let a = Quaternion::IDENTITY;
+    /// let b = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 90.0);
+    /// let half = Quaternion::slerp(&a, &b, 0.5);
+    /// assert_eq!(
+    ///     half.rotate(Vector3::new(1.0, 0.0, 0.0)),
+    ///     Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 45.0)
+    ///         .rotate(Vector3::new(1.0, 0.0, 0.0))
+    /// );
+    /// ```
+    pub fn slerp(a: &Quaternion, b: &Quaternion, t: f64) -> Quaternion {
+        let mut b = *b;
+        let mut cos_theta = a.dot(&b);
+
+        // q and -q are the same rotation, pick the one that takes the short way around
+        if cos_theta < 0.0 {
+            b = Self::new(-b.w, -b.x, -b.y, -b.z);
+            cos_theta = -cos_theta;
+        }
+
+        // Nearly parallel, fall back to linear interpolation to avoid dividing by ~0
+        if cos_theta > 0.9995 {
+            return Self::new(
+                a.w + t * (b.w - a.w),
+                a.x + t * (b.x - a.x),
+                a.y + t * (b.y - a.y),
+                a.z + t * (b.z - a.z),
+            )
+            .normalize();
+        }
+
+        let theta = math::acos(cos_theta);
+        let sin_theta = math::sin(theta);
+        let wa = math::sin((1.0 - t) * theta) / sin_theta;
+        let wb = math::sin(t * theta) / sin_theta;
+        Self::new(
+            wa * a.w + wb * b.w,
+            wa * a.x + wb * b.x,
+            wa * a.y + wb * b.y,
+            wa * a.z + wb * b.z,
+        )
+    }
+}
+
+/// Composes two rotations, `a * b` applies `b` first and then `a`.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Quaternion, Vector3};
+///
+/// let a = Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), 30.0);
+/// let b = Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), 60.0);
+/// let v = (a * b).rotate(Vector3::new(1.0, 0.0, 0.0));
+/// assert_eq!(v, Vector3::new(0.0, 1.0, 0.0));
+/// ```
+impl Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, o: Quaternion) -> Self::Output {
+        Quaternion::new(
+            self.w * o.w - self.x * o.x - self.y * o.y - self.z * o.z,
+            self.w * o.x + self.x * o.w + self.y * o.z - self.z * o.y,
+            self.w * o.y - self.x * o.z + self.y * o.w + self.z * o.x,
+            self.w * o.z + self.x * o.y - self.y * o.x + self.z * o.w,
+        )
+    }
+}
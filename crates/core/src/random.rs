@@ -1,9 +1,23 @@
-use std::sync::Arc;
+use std::{
+    cell::Cell,
+    fmt,
+    str::FromStr,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
 
 pub trait Random: Send + Sync {
     fn rand(&self) -> f64;
     fn rand_int_interval(&self, min: i64, max: i64) -> i64;
     fn rand_interval(&self, min: f64, max: f64) -> f64;
+
+    /// Returns the numbers used for `sample` of pixel (x, y), independent of
+    /// the numbers drawn before, or `None` when the generator is not seeded.
+    fn stream(&self, _x: u32, _y: u32, _sample: u64) -> Option<Arc<dyn Random>> {
+        None
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -56,7 +70,7 @@ pub mod rand {
             let random = RandRandom::new();
             for _ in 0..1000 {
                 let v = random.rand();
-                assert!(v >= 0.0 && v < 1.0);
+                assert!((0.0..1.0).contains(&v));
             }
         }
 
@@ -65,7 +79,7 @@ pub mod rand {
             let random = RandRandom::new();
             for _ in 0..1000 {
                 let v = random.rand_interval(4.2, 8.9);
-                assert!(v >= 4.2 && v < 8.9);
+                assert!((4.2..8.9).contains(&v));
             }
         }
 
@@ -74,7 +88,7 @@ pub mod rand {
             let random = RandRandom::new();
             for _ in 0..1000 {
                 let v = random.rand_int_interval(4, 42);
-                assert!(v >= 4 && v < 42);
+                assert!((4..42).contains(&v));
             }
         }
     }
@@ -117,8 +131,9 @@ pub mod wasm {
         }
 
         fn rand_int_interval(&self, min: i64, max: i64) -> i64 {
-            let delta = max - min + 1; // inclusive range
-            (self.rand() * delta as f64).floor() as i64 + min
+            // exclusive of max, like the native implementation
+            let delta = max - min;
+            ((self.rand() * delta as f64).floor() as i64 + min).min(max - 1)
         }
     }
 
@@ -129,11 +144,271 @@ pub mod wasm {
     }
 }
 
+/// Pseudo-random numbers from a seed (SplitMix64), the same on every target,
+/// for reproducible renders and for comparing renders across platforms.
+///
+/// Threads drawing from one generator get the numbers in whatever order they
+/// run, so every sample of every pixel draws from its own
+/// [`Random::stream`], derived from the seed, the pixel and the sample. The
+/// render is then the same whatever the number of threads and the order
+/// pixels are rendered in.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Random, random::SeededRandom};
+///
+/// let a = SeededRandom::new(42);
+/// let b = SeededRandom::new(42);
+/// for _ in 0..100 {
+///     assert_eq!(a.rand(), b.rand());
+/// }
+/// assert!((0..3).contains(&a.rand_int_interval(0, 3)));
+///
+/// // Streams depend only on the seed, the pixel and the sample
+/// let stream = a.stream(4, 2, 7).unwrap();
+/// assert_eq!(stream.rand(), b.stream(4, 2, 7).unwrap().rand());
+/// assert_ne!(stream.rand(), b.stream(4, 2, 8).unwrap().rand());
+/// ```
+#[derive(Debug)]
+pub struct SeededRandom {
+    seed: u64,
+    state: AtomicU64,
+}
+
+impl SeededRandom {
+    const GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            state: AtomicU64::new(seed),
+        }
+    }
+
+    fn next_u64(&self) -> u64 {
+        mix(self
+            .state
+            .fetch_add(Self::GAMMA, Ordering::Relaxed)
+            .wrapping_add(Self::GAMMA))
+    }
+}
+
+/// SplitMix64 finalizer, spreading every bit of `z` over the result
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Random for SeededRandom {
+    fn rand(&self) -> f64 {
+        // 53 random bits, uniform in [0, 1)
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn rand_interval(&self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.rand()
+    }
+
+    fn rand_int_interval(&self, min: i64, max: i64) -> i64 {
+        let range = (max - min) as f64;
+        (min + (self.rand() * range) as i64).min(max - 1)
+    }
+
+    fn stream(&self, x: u32, y: u32, sample: u64) -> Option<Arc<dyn Random>> {
+        let seed = [x as u64, y as u64, sample]
+            .into_iter()
+            .fold(self.seed, |seed, value| {
+                mix(seed ^ value.wrapping_mul(Self::GAMMA))
+            });
+        Some(Arc::new(SeededRandom::new(seed)))
+    }
+}
+
+/// Generators a [`crate::RenderContext`] can draw its random numbers from.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{RenderContext, random::RandomGenerator};
+///
+/// let generator: RandomGenerator = "pcg32".parse().unwrap();
+/// let ctx = RenderContext::new_with_generator(generator);
+/// assert!((0.0..1.0).contains(&ctx.random.rand()));
+/// assert!("mt19937".parse::<RandomGenerator>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RandomGenerator {
+    /// [`random_new`], the `rand` crate natively and `Math.random` in the
+    /// browser
+    #[default]
+    System,
+    /// [`Pcg32Random`], as fast natively and much faster in the browser, where
+    /// it does not call into JavaScript for every number
+    Pcg32,
+}
+
+impl RandomGenerator {
+    pub const ALL: [RandomGenerator; 2] = [RandomGenerator::System, RandomGenerator::Pcg32];
+
+    pub fn create(&self) -> Arc<dyn Random> {
+        match self {
+            RandomGenerator::System => random_new(),
+            RandomGenerator::Pcg32 => Arc::new(Pcg32Random::new()),
+        }
+    }
+}
+
+impl fmt::Display for RandomGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RandomGenerator::System => write!(f, "system"),
+            RandomGenerator::Pcg32 => write!(f, "pcg32"),
+        }
+    }
+}
+
+impl FromStr for RandomGenerator {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "system" => Ok(RandomGenerator::System),
+            "pcg32" => Ok(RandomGenerator::Pcg32),
+            _ => Err(format!(
+                "unknown random generator \"{s}\", expected system or pcg32"
+            )),
+        }
+    }
+}
+
+thread_local! {
+    /// State and stream of the PCG32 generator of each thread, seeded on its
+    /// first use
+    static PCG32: Cell<Option<(u64, u64)>> = const { Cell::new(None) };
+}
+
+/// PCG32 (XSH RR) generator, a few instructions per number.
+///
+/// Every thread draws from its own generator, seeded from [`random_new`] the
+/// first time the thread draws, so render threads never wait on each other
+/// and renders differ from run to run. Use [`SeededRandom`] for reproducible
+/// renders.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Random, random::Pcg32Random};
+///
+/// let random = Pcg32Random::new();
+/// for _ in 0..1000 {
+///     assert!((0.0..1.0).contains(&random.rand()));
+///     assert!((4..42).contains(&random.rand_int_interval(4, 42)));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Pcg32Random {}
+
+impl Pcg32Random {
+    const MULTIPLIER: u64 = 6_364_136_223_846_793_005;
+
+    /// Returns a handle on the generator of the calling thread.
+    ///
+    /// The generator state lives in a thread local rather than in the
+    /// instance, so every `Pcg32Random` used on a thread draws from the same
+    /// stream and two instances never repeat each other's numbers.
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Returns two numbers of the thread's generator, looking it up once.
+    fn next_u64(&self) -> u64 {
+        PCG32.with(|generator| {
+            let (state, stream) = generator.get().unwrap_or_else(|| {
+                let entropy = random_new();
+                let draw = || (entropy.rand() * (1u64 << 53) as f64) as u64;
+                // The stream must be odd
+                (draw() ^ (draw() << 11), (draw() << 1) | 1)
+            });
+            let (value, state) = Self::next_pair(state, stream);
+            generator.set(Some((state, stream)));
+            value
+        })
+    }
+
+    /// Returns the next two outputs of the generator at `state`, the first in
+    /// the high bits, and its state after them.
+    fn next_pair(state: u64, stream: u64) -> (u64, u64) {
+        let next = state.wrapping_mul(Self::MULTIPLIER).wrapping_add(stream);
+        (
+            ((output(state) as u64) << 32) | output(next) as u64,
+            next.wrapping_mul(Self::MULTIPLIER).wrapping_add(stream),
+        )
+    }
+}
+
+/// PCG32 output function, a permutation of the high bits of `state`
+fn output(state: u64) -> u32 {
+    let xorshifted = (((state >> 18) ^ state) >> 27) as u32;
+    xorshifted.rotate_right((state >> 59) as u32)
+}
+
+impl Default for Pcg32Random {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Random for Pcg32Random {
+    fn rand(&self) -> f64 {
+        // 53 random bits, uniform in [0, 1)
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn rand_interval(&self, min: f64, max: f64) -> f64 {
+        min + (max - min) * self.rand()
+    }
+
+    fn rand_int_interval(&self, min: i64, max: i64) -> i64 {
+        let range = (max - min) as f64;
+        (min + (self.rand() * range) as i64).min(max - 1)
+    }
+}
+
 #[cfg(test)]
 pub mod test {
     use std::{fmt::Debug, sync::Mutex};
 
-    use crate::Random;
+    use crate::{
+        Random,
+        random::{Pcg32Random, RandomGenerator},
+    };
+
+    #[test]
+    fn random_generator_names_round_trip() {
+        for generator in RandomGenerator::ALL {
+            assert_eq!(generator.to_string().parse(), Ok(generator));
+        }
+        assert_eq!(RandomGenerator::System.to_string(), "system");
+        assert_eq!(RandomGenerator::Pcg32.to_string(), "pcg32");
+        assert!("PCG32".parse::<RandomGenerator>().is_err());
+        assert!("".parse::<RandomGenerator>().is_err());
+    }
+
+    #[test]
+    fn pcg32_matches_reference_outputs() {
+        // State of the reference pcg32_srandom_r(42, 54), whose first outputs
+        // are listed by the pcg32-demo of the PCG C library
+        let stream = (54 << 1) | 1;
+        let state = 0x1857_06b8_2c2e_03f8;
+        let (first, state) = Pcg32Random::next_pair(state, stream);
+        let (second, state) = Pcg32Random::next_pair(state, stream);
+        let (third, _) = Pcg32Random::next_pair(state, stream);
+        assert_eq!(first, 0xa15c_02b7_7b47_f409);
+        assert_eq!(second, 0xba1d_3330_83d2_f293);
+        assert_eq!(third, 0xbfa4_784b_cbed_606e);
+    }
 
     #[derive(Debug)]
     pub struct MockRandom {
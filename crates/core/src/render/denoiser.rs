@@ -0,0 +1,221 @@
+use crate::{Aovs, Color, utils::math};
+
+/// How much depths may differ, relative to the depth of the filtered pixel,
+/// before neighbors stop counting
+const DEPTH_SIGMA: f64 = 0.1;
+
+/// Removes the noise of a render with a joint bilateral filter, averaging
+/// every pixel with the neighbors showing the same surface.
+///
+/// Neighbors are weighted by their distance and by how close their color,
+/// normal, albedo and depth are to those of the filtered pixel, so edges of
+/// objects, creases and texture details seen in the [`Aovs`] stay sharp while
+/// noise on flat parts of the image is smoothed out.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{Aovs, Color, Vector3, random_new, render::Denoiser};
+///
+/// // A noisy gray wall facing the camera on the left, a brighter one facing
+/// // to the side on the right
+/// let (width, height) = (16, 16);
+/// let random = random_new();
+/// let mut colors = vec![];
+/// let mut aovs = vec![];
+/// for _y in 0..height {
+///     for x in 0..width {
+///         let (base, normal) = if x < 8 {
+///             (0.2, Vector3::new(0.0, 0.0, 1.0))
+///         } else {
+///             (0.8, Vector3::new(1.0, 0.0, 0.0))
+///         };
+///         let noise = random.rand_interval(-0.1, 0.1);
+///         colors.push(Color::new(base + noise, base + noise, base + noise));
+///         aovs.push(Aovs { albedo: Color::WHITE, normal, depth: 5.0, object_id: 1 });
+///     }
+/// }
+///
+/// let denoised = Denoiser::new().denoise(width, height, &colors, &aovs);
+///
+/// let error = |colors: &[Color]| {
+///     colors.iter().enumerate()
+///         .map(|(i, c)| (c.r - if i % 16 < 8 { 0.2 } else { 0.8 }).abs())
+///         .sum::<f64>()
+/// };
+/// assert!(error(&denoised) < error(&colors) / 2.0);
+/// // The walls do not bleed into each other
+/// assert!((denoised[7].r - 0.2).abs() < 0.05);
+/// assert!((denoised[8].r - 0.8).abs() < 0.05);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Denoiser {
+    radius: u32,
+    color_sigma: f64,
+    normal_sigma: f64,
+    albedo_sigma: f64,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self {
+            radius: 5,
+            color_sigma: 0.3,
+            normal_sigma: 0.1,
+            albedo_sigma: 0.1,
+        }
+    }
+}
+
+impl Denoiser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many pixels away neighbors are averaged, larger radii remove
+    /// more noise and take longer.
+    pub fn with_radius(mut self, radius: u32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Sets how much the brightness of neighbors, mapped to 0..1, may differ
+    /// before they stop counting. Smaller values keep more shading detail and
+    /// remove less noise.
+    pub fn with_color_sigma(mut self, color_sigma: f64) -> Self {
+        self.color_sigma = color_sigma;
+        self
+    }
+
+    /// Sets how much normals may differ, one minus the cosine of the angle
+    /// between them, before neighbors stop counting.
+    pub fn with_normal_sigma(mut self, normal_sigma: f64) -> Self {
+        self.normal_sigma = normal_sigma;
+        self
+    }
+
+    /// Sets how much albedos may differ before neighbors stop counting.
+    pub fn with_albedo_sigma(mut self, albedo_sigma: f64) -> Self {
+        self.albedo_sigma = albedo_sigma;
+        self
+    }
+
+    pub fn get_radius(&self) -> u32 {
+        self.radius
+    }
+
+    /// Returns the denoised linear colors of a `width` by `height` image given
+    /// its linear `colors` and `aovs` in row order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `colors` or `aovs` do not hold one entry per pixel.
+    pub fn denoise(&self, width: u32, height: u32, colors: &[Color], aovs: &[Aovs]) -> Vec<Color> {
+        let pixel_count = width as usize * height as usize;
+        assert_eq!(colors.len(), pixel_count, "one color per pixel");
+        assert_eq!(aovs.len(), pixel_count, "one set of AOVs per pixel");
+
+        let radius = self.radius as i64;
+        let spatial_sigma = (self.radius as f64 / 2.0).max(0.5);
+        let index = |x: i64, y: i64| (y * width as i64 + x) as usize;
+
+        let mut denoised = Vec::with_capacity(pixel_count);
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                let center = index(x, y);
+                let mut sum = Color::BLACK;
+                let mut total_weight = 0.0;
+                for ny in (y - radius).max(0)..=(y + radius).min(height as i64 - 1) {
+                    for nx in (x - radius).max(0)..=(x + radius).min(width as i64 - 1) {
+                        let neighbor = index(nx, ny);
+                        let distance_squared = ((nx - x).pow(2) + (ny - y).pow(2)) as f64;
+                        let weight = gaussian(distance_squared.sqrt(), spatial_sigma)
+                            * self.edge_weight(
+                                &colors[center],
+                                &aovs[center],
+                                &colors[neighbor],
+                                &aovs[neighbor],
+                            );
+                        sum += weight * colors[neighbor];
+                        total_weight += weight;
+                    }
+                }
+                // The pixel itself has a weight of 1, up to rounding, so the
+                // total is never 0
+                denoised.push(sum / total_weight);
+            }
+        }
+        denoised
+    }
+
+    /// Returns how much a neighbor counts for the filtered pixel given how
+    /// alike they are, from 0 to 1.
+    fn edge_weight(&self, color: &Color, aovs: &Aovs, other_color: &Color, other: &Aovs) -> f64 {
+        let color_weight = gaussian(
+            tonemap(color.luminance()) - tonemap(other_color.luminance()),
+            self.color_sigma,
+        );
+        // Surfaces and the background are never averaged together
+        match (aovs.depth.is_finite(), other.depth.is_finite()) {
+            (true, true) => {}
+            (false, false) => return color_weight,
+            _ => return 0.0,
+        }
+
+        // Normals averaged over the samples of a pixel are shorter where the
+        // samples hit different surfaces, so only their directions are compared
+        let normal_weight = if aovs.normal.is_near_zero() || other.normal.is_near_zero() {
+            1.0
+        } else {
+            let cosine = aovs.normal.unit().dot(&other.normal.unit());
+            gaussian(1.0 - cosine.clamp(-1.0, 1.0), self.normal_sigma)
+        };
+        let albedo_difference = ((aovs.albedo.r - other.albedo.r).powi(2)
+            + (aovs.albedo.g - other.albedo.g).powi(2)
+            + (aovs.albedo.b - other.albedo.b).powi(2))
+        .sqrt();
+        let albedo_weight = gaussian(albedo_difference, self.albedo_sigma);
+        let depth_difference = (aovs.depth - other.depth) / aovs.depth.max(f64::EPSILON);
+        let depth_weight = gaussian(depth_difference, DEPTH_SIGMA);
+
+        color_weight * normal_weight * albedo_weight * depth_weight
+    }
+}
+
+fn gaussian(x: f64, sigma: f64) -> f64 {
+    math::exp(-(x * x) / (2.0 * sigma * sigma))
+}
+
+/// Maps a linear brightness to 0..1, so differences between bright pixels
+/// count as much as differences between dark ones.
+fn tonemap(luminance: f64) -> f64 {
+    let luminance = luminance.max(0.0);
+    luminance / (1.0 + luminance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Denoiser;
+    use crate::{Aovs, Color, Vector3};
+
+    #[test]
+    fn pixels_weigh_themselves_fully() {
+        let denoiser = Denoiser::new();
+        let color = Color::new(0.4, 0.5, 0.6);
+        for normal in [
+            Vector3::new(0.0, 0.0, 1.0),
+            // Averaged over samples hitting the surface and the background
+            Vector3::new(0.0, 0.3, 0.4),
+            Vector3::ZERO,
+        ] {
+            let aovs = Aovs {
+                albedo: Color::WHITE,
+                normal,
+                depth: 5.0,
+                object_id: 1,
+            };
+            let weight = denoiser.edge_weight(&color, &aovs, &color, &aovs);
+            assert!((weight - 1.0).abs() < 1e-12, "{normal:?}: {weight}");
+        }
+    }
+}
@@ -0,0 +1,9 @@
+pub mod denoiser;
+pub mod photon_map;
+pub mod tile_cost;
+pub mod tile_scheduler;
+
+pub use denoiser::Denoiser;
+pub use photon_map::PhotonMap;
+pub use tile_cost::{DEFAULT_COST_BLOCK_SIZE, estimate_tile_costs, find_tiles_showing};
+pub use tile_scheduler::{Tile, TileOrder, TileScheduler, TileShape};
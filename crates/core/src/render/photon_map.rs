@@ -0,0 +1,457 @@
+use std::{collections::HashMap, f64};
+
+use crate::{
+    Axis, AxisAlignedBoundingBox, Camera, Color, Light, Ray, RenderContext, Vector3,
+    light::LightList,
+    material::PdfOrRay,
+    object::{HitRecord, Node},
+};
+
+/// Photons expected within the gather radius when the photons entering the
+/// targets spread evenly over twice their size
+const PHOTONS_PER_GATHER: f64 = 64.0;
+
+/// Smallest cosine of the angle photons arrive at, so photons skimming a
+/// surface do not dominate the estimate
+const MIN_COSINE: f64 = 0.05;
+
+/// Distance the boxes of uncovered lights are grown by, so points on their
+/// surfaces are within them
+const COVER_PADDING: f64 = 1e-6;
+
+#[derive(Debug, Clone, Copy)]
+struct Photon {
+    position: Vector3,
+    /// Unit direction the photon traveled in
+    direction: Vector3,
+    power: Color,
+}
+
+/// Light which reached diffuse surfaces through mirrors and glass, traced
+/// from the lights ahead of rendering.
+///
+/// Paths traced from the camera rarely find a small light through glass, so
+/// caustics, the bright patterns focused by glass and mirrors, take very many
+/// samples to converge. Photons are instead sent from the lights towards the
+/// objects which can focus light, followed through their specular bounces and
+/// stored where they land on a diffuse surface. Rendering reads the light at
+/// a diffuse surface from the photons around it.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Node, RenderContext, Vector3, random_new,
+///     light::{LightList, PointLight},
+///     material::{Dielectric, Lambertian},
+///     object::{BoundingVolumeHierarchy, Quad, Sphere},
+///     render::PhotonMap,
+/// };
+///
+/// let glass = Arc::new(Sphere::new(
+///     Vector3::new(0.0, 2.0, 0.0),
+///     1.0,
+///     Arc::new(Dielectric::new(1.5)),
+/// )) as Arc<dyn Node>;
+/// let floor = Arc::new(Quad::new(
+///     Vector3::new(-5.0, 0.0, -5.0),
+///     Vector3::new(10.0, 0.0, 0.0),
+///     Vector3::new(0.0, 0.0, 10.0),
+///     Arc::new(Lambertian::new_from_color(Color::WHITE)),
+/// )) as Arc<dyn Node>;
+/// let world = BoundingVolumeHierarchy::new(&[glass.clone(), floor]);
+///
+/// let mut lights = LightList::new();
+/// lights.push(Arc::new(PointLight::new(
+///     Vector3::new(0.0, 6.0, 0.0),
+///     Color::new(10.0, 10.0, 10.0),
+/// )));
+///
+/// let ctx = RenderContext { random: random_new() };
+/// let photons = PhotonMap::trace_caustics(
+///     &ctx,
+///     &world,
+///     &lights,
+///     &[*glass.bounding_box()],
+///     10_000,
+///     10,
+/// );
+/// // Light focused by the sphere lands on the floor below it
+/// assert!(!photons.is_empty());
+/// ```
+#[derive(Debug)]
+pub struct PhotonMap {
+    /// Distance photons are gathered from
+    radius: f64,
+    /// Photons in cubes `radius` wide
+    cells: HashMap<(i64, i64, i64), Vec<Photon>>,
+    len: usize,
+    /// Boxes of the lights with an area which photons were not sent from
+    uncovered: Vec<AxisAlignedBoundingBox>,
+}
+
+impl PhotonMap {
+    /// Most photons traced for one map, which holds about a hundred bytes
+    /// for each photon stored.
+    pub const MAX_PHOTONS: u32 = 10_000_000;
+
+    /// Sends `photon_count` photons from `lights` towards the objects within
+    /// `targets`, the objects with mirror or glass materials, and keeps those
+    /// which land on a diffuse surface after at least one specular bounce.
+    /// Photons are followed for at most `max_depth` bounces, and at most
+    /// [`PhotonMap::MAX_PHOTONS`] are sent.
+    ///
+    /// Photons start on a sphere around the targets, heading inwards from
+    /// each light visible from there, so only light which can reach the
+    /// targets is traced. Lights at a point within the sphere send photons
+    /// in every direction from the point instead. Lights with an area within
+    /// the sphere cannot be reached from it, their caustics are left to path
+    /// tracing, see [`PhotonMap::covers`].
+    pub fn trace_caustics(
+        ctx: &RenderContext,
+        world: &dyn Node,
+        lights: &dyn Light,
+        targets: &[AxisAlignedBoundingBox],
+        photon_count: u32,
+        max_depth: u32,
+    ) -> Self {
+        let photon_count = photon_count.min(Self::MAX_PHOTONS);
+        let bbox = targets
+            .iter()
+            .fold(AxisAlignedBoundingBox::new(), |bbox, target| {
+                bbox.union(target)
+            });
+        if targets.is_empty() || photon_count == 0 || bbox.is_unbounded() {
+            return Self::new(vec![], 1.0, vec![]);
+        }
+
+        let center = bbox.centroid();
+        let size = Vector3::new(
+            bbox.axis_interval(Axis::X).size(),
+            bbox.axis_interval(Axis::Y).size(),
+            bbox.axis_interval(Axis::Z).size(),
+        );
+        let sphere_radius = size.length() / 2.0;
+        let radius = 2.0 * sphere_radius * (PHOTONS_PER_GATHER / photon_count as f64).sqrt();
+        // Each photon stands for the light crossing the sphere's area
+        let area = 4.0 * f64::consts::PI * sphere_radius * sphere_radius / photon_count as f64;
+        let inside = |pt: Vector3| (pt - center).length() < sphere_radius;
+
+        let light_list = match lights.as_any().downcast_ref::<LightList>() {
+            Some(light_list) => light_list.get_lights().iter().map(|l| &**l).collect(),
+            None => vec![lights],
+        };
+        let inner_points: Vec<&dyn Light> = light_list
+            .iter()
+            .copied()
+            .filter(|light| light.position().is_some_and(inside))
+            .collect();
+        let uncovered: Vec<AxisAlignedBoundingBox> = light_list
+            .iter()
+            .filter_map(|light| light.bounding_box())
+            .filter(|light_bbox| distance_to_box(light_bbox, center) < sphere_radius)
+            .map(|light_bbox| light_bbox.expand(COVER_PADDING))
+            .collect();
+        let is_uncovered = |pt: Vector3| uncovered.iter().any(|b| contains(b, pt));
+
+        let mut photons = vec![];
+        for _ in 0..photon_count {
+            let normal = Vector3::random_unit(&*ctx.random);
+            let origin = center + sphere_radius * normal;
+
+            if lights.has_area() {
+                let direction = lights.sample_direction(ctx, &origin).unit();
+                let cosine = normal.dot(&direction);
+                let pdf = lights.pdf_value(ctx, &origin, &direction);
+                let ray = Ray::new(origin, direction);
+                let visible = Camera::first_hit(ctx, &ray, world)
+                    .is_some_and(|hit| hit.material.is_emissive() && !is_uncovered(hit.pt));
+                if cosine > 0.0 && pdf > 0.0 && visible {
+                    let power = lights.emitted(ctx, &origin, &direction) * (cosine * area / pdf);
+                    trace(
+                        ctx,
+                        world,
+                        Ray::new(origin, -direction),
+                        power,
+                        max_depth,
+                        &mut photons,
+                    );
+                }
+            }
+
+            lights.sample_delta(ctx, &origin, &mut |sample| {
+                let cosine = normal.dot(&sample.direction);
+                let ray = Ray::new(origin, sample.direction);
+                if cosine <= 0.0
+                    || inside(origin + sample.direction * sample.distance)
+                    || Camera::is_occluded(ctx, &ray, sample.distance, world)
+                {
+                    return;
+                }
+                trace(
+                    ctx,
+                    world,
+                    Ray::new(origin, -sample.direction),
+                    sample.irradiance * (cosine * area),
+                    max_depth,
+                    &mut photons,
+                );
+            });
+
+            // Lights at a point within the sphere send photons from the
+            // point, in directions spread evenly over the whole sphere
+            for light in &inner_points {
+                let Some(position) = light.position() else {
+                    continue;
+                };
+                let direction = Vector3::random_unit(&*ctx.random);
+                // The light arriving one unit away is the light's intensity
+                // in that direction
+                light.sample_delta(ctx, &(position + direction), &mut |sample| {
+                    trace(
+                        ctx,
+                        world,
+                        Ray::new(position, direction),
+                        sample.irradiance * (4.0 * f64::consts::PI / photon_count as f64),
+                        max_depth,
+                        &mut photons,
+                    );
+                });
+            }
+        }
+
+        Self::new(photons, radius, uncovered)
+    }
+
+    fn new(photons: Vec<Photon>, radius: f64, uncovered: Vec<AxisAlignedBoundingBox>) -> Self {
+        let len = photons.len();
+        let mut cells: HashMap<(i64, i64, i64), Vec<Photon>> = HashMap::new();
+        for photon in photons {
+            cells
+                .entry(cell(photon.position, radius))
+                .or_default()
+                .push(photon);
+        }
+        Self {
+            radius,
+            cells,
+            len,
+            uncovered,
+        }
+    }
+
+    /// Returns the number of photons stored.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether the caustics of light emitted at `pt` are held by the
+    /// map, false on lights too close to the targets for photons to be sent
+    /// from them, whose caustics are path traced instead.
+    pub fn covers(&self, pt: &Vector3) -> bool {
+        !self.uncovered.iter().any(|bbox| contains(bbox, *pt))
+    }
+
+    /// Returns the distance photons are gathered from.
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Returns the light of the photons around `hit` reflected back along
+    /// `ray`, with the `attenuation` of the hit's material.
+    pub fn radiance(
+        &self,
+        ctx: &RenderContext,
+        ray: &Ray,
+        hit: &HitRecord,
+        attenuation: Color,
+    ) -> Color {
+        if self.is_empty() {
+            return Color::BLACK;
+        }
+
+        let (x, y, z) = cell(hit.pt, self.radius);
+        let mut color = Color::BLACK;
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let Some(photons) = self.cells.get(&(x + dx, y + dy, z + dz)) else {
+                        continue;
+                    };
+                    for photon in photons {
+                        // Photons arriving on the other side of the surface
+                        // do not light it
+                        let cosine = -photon.direction.dot(&hit.normal);
+                        if cosine <= 0.0 || (photon.position - hit.pt).length() > self.radius {
+                            continue;
+                        }
+                        let towards_light = Ray::new_with_time(hit.pt, -photon.direction, ray.time);
+                        let brdf = hit.material.scattering_color(
+                            ctx,
+                            ray,
+                            hit,
+                            &towards_light,
+                            attenuation,
+                        ) / cosine.max(MIN_COSINE);
+                        color += brdf * photon.power;
+                    }
+                }
+            }
+        }
+        color / (f64::consts::PI * self.radius * self.radius)
+    }
+}
+
+/// Follows a photon through specular bounces, storing it where it lands on a
+/// diffuse surface after at least one of them.
+fn trace(
+    ctx: &RenderContext,
+    world: &dyn Node,
+    mut ray: Ray,
+    mut power: Color,
+    max_depth: u32,
+    photons: &mut Vec<Photon>,
+) {
+    let mut specular = false;
+    for _ in 0..max_depth {
+        let Some(hit) = Camera::first_hit(ctx, &ray, world) else {
+            return;
+        };
+        let Some(scatter_result) = hit.material.scatter(ctx, &ray, &hit) else {
+            return;
+        };
+        match scatter_result.pdf_or_ray {
+            PdfOrRay::Ray(scattered) => {
+                power = power * scatter_result.attenuation;
+                ray = scattered;
+                specular = true;
+            }
+            PdfOrRay::Pdf(_) => {
+                if specular {
+                    photons.push(Photon {
+                        position: hit.pt,
+                        direction: ray.direction.unit(),
+                        power,
+                    });
+                }
+                return;
+            }
+        }
+    }
+}
+
+/// Returns the distance from `pt` to the closest point of `bbox`, 0 within it.
+fn distance_to_box(bbox: &AxisAlignedBoundingBox, pt: Vector3) -> f64 {
+    let closest = Vector3::new(
+        bbox.axis_interval(Axis::X).clamp(pt.x),
+        bbox.axis_interval(Axis::Y).clamp(pt.y),
+        bbox.axis_interval(Axis::Z).clamp(pt.z),
+    );
+    (closest - pt).length()
+}
+
+fn contains(bbox: &AxisAlignedBoundingBox, pt: Vector3) -> bool {
+    distance_to_box(bbox, pt) == 0.0
+}
+
+fn cell(pt: Vector3, size: f64) -> (i64, i64, i64) {
+    (
+        (pt.x / size).floor() as i64,
+        (pt.y / size).floor() as i64,
+        (pt.z / size).floor() as i64,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::PI, sync::Arc};
+
+    use super::PhotonMap;
+    use crate::{
+        AxisAlignedBoundingBox, Color, Interval, Node, Ray, RenderContext, Vector3,
+        light::{LightList, PointLight},
+        material::{DiffuseLight, Lambertian, Metal},
+        object::{BoundingVolumeHierarchy, Quad, Sphere},
+    };
+
+    #[test]
+    fn light_within_the_targets_matches_its_mirror_image() {
+        let floor: Arc<dyn Node> = Arc::new(Quad::new(
+            Vector3::new(-5.0, 0.0, -5.0),
+            Vector3::new(10.0, 0.0, 0.0),
+            Vector3::new(0.0, 0.0, 10.0),
+            Arc::new(Lambertian::new_from_color(Color::WHITE)),
+        ));
+        let mirror: Arc<dyn Node> = Arc::new(Quad::new(
+            Vector3::new(1.0, 0.0, -2.0),
+            Vector3::new(0.0, 2.0, 0.0),
+            Vector3::new(0.0, 0.0, 4.0),
+            Arc::new(Metal::new(Color::WHITE, 0.0)),
+        ));
+        let world = BoundingVolumeHierarchy::new(&[floor, mirror.clone()]);
+        // The light is within the sphere around the mirror photons start from
+        let intensity = 10.0;
+        let mut lights = LightList::new();
+        lights.push(Arc::new(PointLight::new(
+            Vector3::new(0.0, 1.0, 0.0),
+            Color::new(intensity, intensity, intensity),
+        )));
+
+        let ctx = RenderContext::new_seeded(1);
+        let photons =
+            PhotonMap::trace_caustics(&ctx, &world, &lights, &[*mirror.bounding_box()], 800_000, 4);
+
+        // The light reflected by the mirror is that of the light's mirror
+        // image, reflected by the white floor. Each estimate gathers few
+        // photons, so they are compared over a grid of points.
+        let image = Vector3::new(2.0, 1.0, 0.0);
+        let (mut sum, mut expected_sum) = (0.0, 0.0);
+        for i in 0..400 {
+            let pt = Vector3::new(
+                -1.5 + 0.1 * (i % 20) as f64,
+                0.0,
+                -1.0 + 0.1 * (i / 20) as f64,
+            );
+            let ray = Ray::new(
+                pt + Vector3::new(0.0, 1.0, 0.0),
+                Vector3::new(0.0, -1.0, 0.0),
+            );
+            let hit = world
+                .hit(&ctx, &ray, Interval::new(0.001, f64::INFINITY))
+                .unwrap();
+            let distance = (image - pt).length();
+            let irradiance = intensity * (image.y / distance) / (distance * distance);
+            expected_sum += irradiance / PI;
+            sum += photons.radiance(&ctx, &ray, &hit, Color::WHITE).g;
+        }
+        assert!(
+            (sum - expected_sum).abs() < 0.05 * expected_sum,
+            "{sum}, expected {expected_sum}"
+        );
+    }
+
+    #[test]
+    fn area_light_within_the_targets_is_left_to_path_tracing() {
+        let glass_box = AxisAlignedBoundingBox::new_from_points(
+            Vector3::new(-2.0, -2.0, -2.0),
+            Vector3::new(2.0, 2.0, 2.0),
+        );
+        let lamp: Arc<dyn Node> = Arc::new(Sphere::new(
+            Vector3::new(0.0, 0.0, 0.0),
+            0.5,
+            Arc::new(DiffuseLight::new_from_color(Color::WHITE)),
+        ));
+        let world = BoundingVolumeHierarchy::new(std::slice::from_ref(&lamp));
+        let lights = LightList::from_nodes(&[lamp]);
+
+        let ctx = RenderContext::new_seeded(1);
+        let photons = PhotonMap::trace_caustics(&ctx, &world, &lights, &[glass_box], 1000, 4);
+        assert!(!photons.covers(&Vector3::new(0.0, 0.5, 0.0)));
+        assert!(photons.covers(&Vector3::new(0.0, 5.0, 0.0)));
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{Camera, Interval, Node, RenderContext, object::HitRecord, render::Tile};
+
+/// Side of the blocks estimated with one ray when none is given
+pub const DEFAULT_COST_BLOCK_SIZE: u32 = 8;
+
+/// Estimates the relative cost of rendering each tile before the render
+/// starts, casting one ray per `block_size` by `block_size` block of pixels
+/// with [`Camera::estimate_pixel_cost`] and counting it for every pixel of the
+/// block.
+///
+/// Costs have no unit, they only compare tiles of the same scene, to render
+/// the hardest tiles first and to weigh the progress of a render by how much
+/// work each tile is.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     CameraBuilder, Color, RenderContext, Vector3, material::Lambertian, object::Sphere,
+///     random_new,
+///     render::{Tile, estimate_tile_costs},
+/// };
+///
+/// let mut camera_builder = CameraBuilder::new();
+/// camera_builder.image_width = 40;
+/// let camera = camera_builder.build();
+/// let material = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.2, 0.2)));
+/// let world = Sphere::new(Vector3::new(0.0, 0.0, -3.0), 1.0, material);
+/// let ctx = RenderContext { random: random_new() };
+///
+/// let sky = Tile { xmin: 0, xmax: 10, ymin: 0, ymax: 10 };
+/// let sphere = Tile { xmin: 15, xmax: 25, ymin: 15, ymax: 25 };
+/// let costs = estimate_tile_costs(&ctx, &camera, &world, &[sky, sphere], 5);
+/// assert!(costs[1] > costs[0]);
+/// ```
+pub fn estimate_tile_costs(
+    ctx: &RenderContext,
+    camera: &Camera,
+    world: &dyn Node,
+    tiles: &[Tile],
+    block_size: u32,
+) -> Vec<f64> {
+    let block_size = block_size.max(1);
+    tiles
+        .iter()
+        .map(|tile| {
+            let mut cost = 0.0;
+            for ymin in (tile.ymin..tile.ymax).step_by(block_size as usize) {
+                let ymax = (ymin + block_size).min(tile.ymax);
+                for xmin in (tile.xmin..tile.xmax).step_by(block_size as usize) {
+                    let xmax = (xmin + block_size).min(tile.xmax);
+                    let pixels = ((xmax - xmin) * (ymax - ymin)) as f64;
+                    let x = (xmin + xmax) / 2;
+                    let y = (ymin + ymax) / 2;
+                    cost += pixels * camera.estimate_pixel_cost(ctx, x, y, world);
+                }
+            }
+            cost
+        })
+        .collect()
+}
+
+/// Finds the tiles which show a surface for which `shows` holds, casting one
+/// ray through the center of each `block_size` by `block_size` block of
+/// pixels, such as the tiles to render again after a material changed.
+///
+/// Only surfaces seen directly are found, not their reflections, refractions
+/// or shadows, and surfaces smaller than a block can fall between the rays.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     CameraBuilder, Color, RenderContext, Vector3, material::Lambertian, object::Sphere,
+///     random_new,
+///     render::{Tile, find_tiles_showing},
+/// };
+///
+/// let mut camera_builder = CameraBuilder::new();
+/// camera_builder.image_width = 40;
+/// let camera = camera_builder.build();
+/// let material = Arc::new(Lambertian::new_from_color(Color::new(0.8, 0.2, 0.2)));
+/// let world = Sphere::new(Vector3::new(0.0, 0.0, -3.0), 1.0, material);
+/// let ctx = RenderContext { random: random_new() };
+///
+/// let sky = Tile { xmin: 0, xmax: 10, ymin: 0, ymax: 10 };
+/// let sphere = Tile { xmin: 15, xmax: 25, ymin: 15, ymax: 25 };
+/// let showing = find_tiles_showing(&ctx, &camera, &world, &[sky, sphere], 5, |_| true);
+/// assert_eq!(showing, [false, true]);
+/// ```
+pub fn find_tiles_showing(
+    ctx: &RenderContext,
+    camera: &Camera,
+    world: &dyn Node,
+    tiles: &[Tile],
+    block_size: u32,
+    shows: impl Fn(&HitRecord) -> bool,
+) -> Vec<bool> {
+    let block_size = block_size.max(1);
+    let width = camera.image_width() as f64;
+    let height = camera.image_height() as f64;
+    let block_shows = |x: u32, y: u32| {
+        let ray = camera.get_pick_ray((x as f64 + 0.5) / width, (y as f64 + 0.5) / height);
+        let mut ray_t = Interval::new(0.001, f64::INFINITY);
+        while let Some(hit) = world.hit(ctx, &ray, ray_t) {
+            if !hit.material.is_cutout(&hit) {
+                return shows(&hit);
+            }
+            ray_t = Interval::new(hit.t + 0.001, f64::INFINITY);
+        }
+        false
+    };
+
+    tiles
+        .iter()
+        .map(|tile| {
+            (tile.ymin..tile.ymax)
+                .step_by(block_size as usize)
+                .any(|ymin| {
+                    let ymax = (ymin + block_size).min(tile.ymax);
+                    (tile.xmin..tile.xmax)
+                        .step_by(block_size as usize)
+                        .any(|xmin| {
+                            let xmax = (xmin + block_size).min(tile.xmax);
+                            block_shows((xmin + xmax) / 2, (ymin + ymax) / 2)
+                        })
+                })
+        })
+        .collect()
+}
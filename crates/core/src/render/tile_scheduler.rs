@@ -0,0 +1,268 @@
+use crate::{Camera, utils::math};
+
+/// Tile size used when none is given
+pub const DEFAULT_TILE_SIZE: u32 = 32;
+
+/// Tiles handed to every thread when tiles are sized automatically, enough
+/// that threads finishing early can take work from slower parts of the image
+const TILES_PER_THREAD: u32 = 16;
+
+/// Shortest time in seconds a tile should take to render when sized
+/// automatically, so handing it out costs little next to rendering it
+const MIN_TILE_SECONDS: f64 = 0.005;
+
+/// Bounds of the side of automatically sized tiles, strips hold as many pixels
+/// as squares of these sizes
+const MIN_AUTO_TILE_SIZE: u32 = 8;
+const MAX_AUTO_TILE_SIZE: u32 = 256;
+
+/// A rectangle of pixels rendered as one unit of work, `xmin..xmax` by
+/// `ymin..ymax`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub xmin: u32,
+    pub xmax: u32,
+    pub ymin: u32,
+    pub ymax: u32,
+}
+
+impl Tile {
+    /// Returns whether the camera renders any pixel of the tile, tiles outside
+    /// of its render region stay black and need no work.
+    pub fn is_rendered_by(&self, camera: &Camera) -> bool {
+        (self.ymin..self.ymax).any(|y| (self.xmin..self.xmax).any(|x| camera.is_rendered(x, y)))
+    }
+}
+
+/// Order tiles are rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileOrder {
+    /// Row by row from the top left
+    #[default]
+    Scanline,
+    /// Outwards from the center, where the subject usually is
+    Spiral,
+    /// Along a Hilbert curve, neighboring tiles are rendered one after the
+    /// other, which keeps the parts of the scene they hit in the caches
+    Hilbert,
+}
+
+/// Shape of the tiles picked by [`TileScheduler::with_auto_tile_size`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TileShape {
+    /// Squares, which hit the fewest distinct parts of the scene per pixel
+    #[default]
+    Square,
+    /// Rows across the whole image, written to the output without gaps
+    Strip,
+}
+
+/// Splits an image in tiles and orders them, so the CLI, the wasm worker pool
+/// and other renderers share how work is handed out.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::render::{Tile, TileOrder, TileScheduler};
+///
+/// let tiles = TileScheduler::new(25, 10).with_tile_size(10).tiles();
+/// assert_eq!(tiles.len(), 3);
+/// // Tiles at the edges are cut to the image
+/// assert_eq!(tiles[2], Tile { xmin: 20, xmax: 25, ymin: 0, ymax: 10 });
+///
+/// // The spiral starts with the tile at the center
+/// let spiral = TileScheduler::new(30, 30)
+///     .with_tile_size(10)
+///     .with_order(TileOrder::Spiral)
+///     .tiles();
+/// assert_eq!(spiral[0], Tile { xmin: 10, xmax: 20, ymin: 10, ymax: 20 });
+///
+/// // Strips span the whole width of the image
+/// let strips = TileScheduler::new(25, 10).with_tile_dimensions(25, 4).tiles();
+/// assert_eq!(strips.len(), 3);
+/// assert_eq!(strips[2], Tile { xmin: 0, xmax: 25, ymin: 8, ymax: 10 });
+///
+/// // Every tile of the Hilbert curve touches the one before it
+/// let hilbert = TileScheduler::new(40, 40)
+///     .with_tile_size(10)
+///     .with_order(TileOrder::Hilbert)
+///     .tiles();
+/// assert_eq!(hilbert.len(), 16);
+/// for pair in hilbert.windows(2) {
+///     let dx = pair[0].xmin.abs_diff(pair[1].xmin);
+///     let dy = pair[0].ymin.abs_diff(pair[1].ymin);
+///     assert_eq!(dx + dy, 10);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TileScheduler {
+    width: u32,
+    height: u32,
+    tile_width: u32,
+    tile_height: u32,
+    order: TileOrder,
+}
+
+impl TileScheduler {
+    /// Creates a scheduler for an image of `width` by `height` pixels with
+    /// [`DEFAULT_TILE_SIZE`] tiles in scanline order.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            tile_width: DEFAULT_TILE_SIZE,
+            tile_height: DEFAULT_TILE_SIZE,
+            order: TileOrder::default(),
+        }
+    }
+
+    /// Sets the width and height of square tiles in pixels, at least 1.
+    pub fn with_tile_size(self, tile_size: u32) -> Self {
+        self.with_tile_dimensions(tile_size, tile_size)
+    }
+
+    /// Sets the width and height of rectangular tiles in pixels, at least 1.
+    /// Tiles as wide as the image render it in strips.
+    pub fn with_tile_dimensions(mut self, tile_width: u32, tile_height: u32) -> Self {
+        self.tile_width = tile_width.max(1);
+        self.tile_height = tile_height.max(1);
+        self
+    }
+
+    /// Sizes tiles for rendering on `threads` threads, with enough tiles per
+    /// thread to balance the work and, when `seconds_per_pixel` was measured,
+    /// few enough that each tile is worth handing out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::render::{TileScheduler, TileShape};
+    ///
+    /// // Large images get larger tiles
+    /// let small = TileScheduler::new(320, 240).with_auto_tile_size(TileShape::Square, 8, None);
+    /// let large = TileScheduler::new(3840, 2160).with_auto_tile_size(TileShape::Square, 8, None);
+    /// assert!(small.get_tile_width() < large.get_tile_width());
+    ///
+    /// // Cheap pixels get larger tiles to keep the scheduling overhead low
+    /// let cheap = TileScheduler::new(1920, 1080)
+    ///     .with_auto_tile_size(TileShape::Square, 8, Some(1e-7));
+    /// let costly = TileScheduler::new(1920, 1080)
+    ///     .with_auto_tile_size(TileShape::Square, 8, Some(1e-4));
+    /// assert!(cheap.get_tile_width() > costly.get_tile_width());
+    ///
+    /// // Strips span the image
+    /// let strips = TileScheduler::new(1920, 1080).with_auto_tile_size(TileShape::Strip, 8, None);
+    /// assert_eq!(strips.get_tile_width(), 1920);
+    /// ```
+    pub fn with_auto_tile_size(
+        self,
+        shape: TileShape,
+        threads: u32,
+        seconds_per_pixel: Option<f64>,
+    ) -> Self {
+        let threads = threads.max(1) as f64;
+        let area = self.width as f64 * self.height as f64;
+
+        let mut pixels = area / (threads * TILES_PER_THREAD as f64);
+        if let Some(seconds_per_pixel) = seconds_per_pixel.filter(|s| *s > 0.0) {
+            pixels = pixels.max(MIN_TILE_SECONDS / seconds_per_pixel);
+        }
+        // Every thread still gets a tile
+        pixels = pixels.min(area / threads);
+
+        let min_pixels = (MIN_AUTO_TILE_SIZE * MIN_AUTO_TILE_SIZE) as f64;
+        let max_pixels = (MAX_AUTO_TILE_SIZE * MAX_AUTO_TILE_SIZE) as f64;
+        let pixels = pixels.clamp(min_pixels, max_pixels);
+        match shape {
+            TileShape::Square => self.with_tile_size(pixels.sqrt().round() as u32),
+            TileShape::Strip => {
+                let width = self.width.max(1);
+                self.with_tile_dimensions(width, (pixels / width as f64).ceil() as u32)
+            }
+        }
+    }
+
+    pub fn with_order(mut self, order: TileOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn get_tile_width(&self) -> u32 {
+        self.tile_width
+    }
+
+    pub fn get_tile_height(&self) -> u32 {
+        self.tile_height
+    }
+
+    pub fn get_order(&self) -> TileOrder {
+        self.order
+    }
+
+    /// Returns every tile of the image in render order.
+    pub fn tiles(&self) -> Vec<Tile> {
+        let columns = self.width.div_ceil(self.tile_width);
+        let rows = self.height.div_ceil(self.tile_height);
+        let mut cells: Vec<(u32, u32)> = (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .collect();
+
+        match self.order {
+            TileOrder::Scanline => {}
+            TileOrder::Spiral => {
+                // By ring around the center tile, then by angle within a ring
+                let center_x = (columns as f64 - 1.0) / 2.0;
+                let center_y = (rows as f64 - 1.0) / 2.0;
+                let key = |&(column, row): &(u32, u32)| {
+                    let dx = column as f64 - center_x;
+                    let dy = row as f64 - center_y;
+                    (dx.abs().max(dy.abs()).round() as u32, math::atan2(dy, dx))
+                };
+                cells.sort_by(|a, b| {
+                    let (a, b) = (key(a), key(b));
+                    a.0.cmp(&b.0).then(a.1.total_cmp(&b.1))
+                });
+            }
+            TileOrder::Hilbert => {
+                let n = columns.max(rows).next_power_of_two();
+                cells.sort_by_key(|&(column, row)| hilbert_index(n, column, row));
+            }
+        }
+
+        cells
+            .into_iter()
+            .map(|(column, row)| {
+                let xmin = column * self.tile_width;
+                let ymin = row * self.tile_height;
+                Tile {
+                    xmin,
+                    xmax: (xmin + self.tile_width).min(self.width),
+                    ymin,
+                    ymax: (ymin + self.tile_height).min(self.height),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Returns the distance along a Hilbert curve filling an `n` by `n` grid, `n`
+/// a power of two, to the cell at `x`, `y`.
+fn hilbert_index(n: u32, mut x: u32, mut y: u32) -> u64 {
+    let mut index = 0;
+    let mut s = n / 2;
+    while s > 0 {
+        let rx = u32::from(x & s > 0);
+        let ry = u32::from(y & s > 0);
+        index += s as u64 * s as u64 * ((3 * rx) ^ ry) as u64;
+        // Rotate the quadrant so the curve continues where the last one ended
+        if ry == 0 {
+            if rx == 1 {
+                x = n - 1 - x;
+                y = n - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s /= 2;
+    }
+    index
+}
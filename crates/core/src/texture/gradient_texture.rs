@@ -0,0 +1,105 @@
+use crate::{Color, Vector3, texture::Texture};
+
+/// The shape along which a [`GradientTexture`] blends its colors.
+#[derive(Debug, Clone, Copy)]
+pub enum GradientShape {
+    /// Blends along the line from `start` to `end`, constant on planes
+    /// perpendicular to it.
+    Linear { start: Vector3, end: Vector3 },
+    /// Blends outward from `center`, reaching the last stop at `radius`.
+    Radial { center: Vector3, radius: f64 },
+}
+
+/// Blends between colors placed at positions in [0, 1], for backdrops and
+/// stylized materials. The gradient is evaluated on the hit point, before the
+/// first stop and after the last one the color stays constant.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{
+///     Color, Vector3,
+///     texture::{GradientShape, GradientTexture, Texture},
+/// };
+///
+/// let sunset = GradientTexture::new(
+///     GradientShape::Linear {
+///         start: Vector3::new(0.0, 0.0, 0.0),
+///         end: Vector3::new(0.0, 10.0, 0.0),
+///     },
+///     vec![
+///         (0.0, Color::new(1.0, 0.5, 0.0)),
+///         (0.5, Color::new(1.0, 0.0, 0.5)),
+///         (1.0, Color::new(0.0, 0.0, 0.5)),
+///     ],
+/// );
+/// assert_eq!(sunset.value(0.0, 0.0, Vector3::new(3.0, 5.0, 0.0)), Color::new(1.0, 0.0, 0.5));
+/// assert_eq!(sunset.value(0.0, 0.0, Vector3::new(0.0, 2.5, 0.0)), Color::new(1.0, 0.25, 0.25));
+/// assert_eq!(sunset.value(0.0, 0.0, Vector3::new(0.0, 20.0, 0.0)), Color::new(0.0, 0.0, 0.5));
+/// ```
+#[derive(Debug)]
+pub struct GradientTexture {
+    shape: GradientShape,
+    stops: Vec<(f64, Color)>,
+}
+
+impl GradientTexture {
+    /// Creates a gradient from `(position, color)` stops, which are sorted by
+    /// position. Without stops the gradient is black.
+    pub fn new(shape: GradientShape, mut stops: Vec<(f64, Color)>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { shape, stops }
+    }
+
+    pub fn get_shape(&self) -> GradientShape {
+        self.shape
+    }
+
+    pub fn get_stops(&self) -> &[(f64, Color)] {
+        &self.stops
+    }
+
+    /// Returns the position of a point along the gradient, 0 at the start.
+    fn position(&self, pt: Vector3) -> f64 {
+        match self.shape {
+            GradientShape::Linear { start, end } => {
+                let axis = end - start;
+                let length_squared = axis.length_squared();
+                if length_squared == 0.0 {
+                    0.0
+                } else {
+                    (pt - start).dot(&axis) / length_squared
+                }
+            }
+            GradientShape::Radial { center, radius } => {
+                if radius == 0.0 {
+                    0.0
+                } else {
+                    (pt - center).length() / radius
+                }
+            }
+        }
+    }
+}
+
+impl Texture for GradientTexture {
+    fn value(&self, _u: f64, _v: f64, pt: Vector3) -> Color {
+        let (Some(first), Some(last)) = (self.stops.first(), self.stops.last()) else {
+            return Color::BLACK;
+        };
+
+        let t = self.position(pt);
+        if t <= first.0 {
+            return first.1;
+        }
+        if t >= last.0 {
+            return last.1;
+        }
+
+        let next = self.stops.partition_point(|(position, _)| *position <= t);
+        let (start, start_color) = self.stops[next - 1];
+        let (end, end_color) = self.stops[next];
+        let f = (t - start) / (end - start);
+        start_color * (1.0 - f) + end_color * f
+    }
+}
@@ -1,30 +1,319 @@
 use std::sync::Arc;
 
-use crate::{Color, Image, Vector3, texture::Texture};
+use crate::{
+    Color, Image, Vector3,
+    image::{ImageError, ImageImage},
+    object::HitRecord,
+    texture::Texture,
+    utils::math,
+};
 
+/// How the stored pixel values of an image should be interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ColorSpace {
+    /// sRGB encoded colors, the norm for photos and painted albedo maps. Values
+    /// are decoded to linear before shading.
+    #[default]
+    Srgb,
+    /// Colors that are already linear, such as HDR environment maps.
+    Linear,
+    /// Non-color data such as normal, roughness or height maps, used as is.
+    Data,
+}
+
+impl ColorSpace {
+    /// Converts a stored pixel value to the linear value used for shading.
+    pub fn to_linear(&self, color: Color) -> Color {
+        match self {
+            ColorSpace::Srgb => color.srgb_to_linear(),
+            ColorSpace::Linear | ColorSpace::Data => color,
+        }
+    }
+}
+
+/// How an [`ImageTexture`] reads between the pixels of its image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextureFilter {
+    /// Uses the pixel under the lookup, giving blocky magnified textures.
+    Nearest,
+    /// Interpolates between the four pixels around the lookup.
+    #[default]
+    Bilinear,
+}
+
+/// An image downsampled to half its size, or a copy of the image at level 0.
+/// Pixels are stored as linear colors, row by row.
+#[derive(Debug)]
+struct MipLevel {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl MipLevel {
+    fn half(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = Color::BLACK;
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (x * 2 + dx).min(self.width - 1);
+                    let sy = (y * 2 + dy).min(self.height - 1);
+                    sum += self.pixels[(sy * self.width + sx) as usize];
+                }
+                pixels.push(sum * 0.25);
+            }
+        }
+        MipLevel {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// Textures a surface with an image, using the texture coordinates of the hit.
+///
+/// Lookups are bilinearly filtered by default. Distant surfaces can still
+/// alias, since many pixels of the image fall within one rendered pixel, which
+/// mipmaps avoid by reading from prefiltered smaller copies of the image the
+/// further the surface is from the ray origin. Without ray differentials the
+/// level is estimated from the hit distance alone: within the mipmap distance
+/// the full image is used and each doubling of the distance halves its size.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Image, Vector3,
+///     material::Lambertian,
+///     object::HitRecord,
+///     texture::{ColorSpace, ImageTexture, Texture, TextureFilter},
+/// };
+///
+/// // Black and white columns
+/// #[derive(Debug)]
+/// struct Stripes;
+/// impl Image for Stripes {
+///     fn width(&self) -> u32 { 4 }
+///     fn height(&self) -> u32 { 4 }
+///     fn get_pixel(&self, x: u32, _y: u32) -> Option<Color> {
+///         Some(if x % 2 == 0 { Color::BLACK } else { Color::WHITE })
+///     }
+/// }
+///
+/// let nearest = ImageTexture::new(Arc::new(Stripes))
+///     .with_color_space(ColorSpace::Linear)
+///     .with_filter(TextureFilter::Nearest);
+/// assert_eq!(nearest.value(0.3, 0.5, Vector3::ZERO), Color::WHITE);
+///
+/// // Halfway between the centers of the first two columns
+/// let bilinear = ImageTexture::new(Arc::new(Stripes)).with_color_space(ColorSpace::Linear);
+/// assert_eq!(bilinear.value(0.25, 0.5, Vector3::ZERO), Color::new(0.5, 0.5, 0.5));
+///
+/// // Far away the stripes average out instead of flickering
+/// let mipmapped = ImageTexture::new(Arc::new(Stripes))
+///     .with_color_space(ColorSpace::Linear)
+///     .with_mipmaps(10.0);
+/// let hit = HitRecord {
+///     pt: Vector3::ZERO,
+///     normal: Vector3::new(0.0, 0.0, 1.0),
+///     tangent: Vector3::ZERO,
+///     t: 100.0,
+///     u: 0.125,
+///     v: 0.5,
+///     front_face: true,
+///     material: Arc::new(Lambertian::new_from_color(Color::WHITE)),
+///     object_id: 0,
+/// };
+/// assert_eq!(mipmapped.value_at(&hit), Color::new(0.5, 0.5, 0.5));
+/// ```
 #[derive(Debug)]
 pub struct ImageTexture {
     image: Arc<dyn Image>,
+    color_space: ColorSpace,
+    filter: TextureFilter,
+    /// Distance from the ray origin up to which the full image is used
+    mipmap_distance: Option<f64>,
+    /// Mipmap chain from the full image down to a single pixel, empty without
+    /// mipmaps
+    mip_levels: Vec<MipLevel>,
 }
 
 impl ImageTexture {
+    /// Creates a texture from an sRGB encoded image.
     pub fn new(image: Arc<dyn Image>) -> Self {
-        Self { image }
+        Self {
+            image,
+            color_space: ColorSpace::default(),
+            filter: TextureFilter::default(),
+            mipmap_distance: None,
+            mip_levels: vec![],
+        }
     }
-}
 
-impl Texture for ImageTexture {
-    fn value(&self, u: f64, v: f64, _pt: Vector3) -> Color {
+    /// Creates a texture from an encoded image, such as a PNG or JPEG file
+    /// fetched over the network, guessing the format from its contents.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{Color, Vector3, texture::{ImageTexture, Texture}};
+    ///
+    /// // 1x1 Radiance HDR image with a single white pixel
+    /// let mut bytes = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 1\n".to_vec();
+    /// bytes.extend([128, 128, 128, 129]);
+    /// let texture = ImageTexture::from_bytes(&bytes).unwrap();
+    /// assert_eq!(texture.value(0.5, 0.5, Vector3::ZERO), Color::WHITE);
+    ///
+    /// assert!(ImageTexture::from_bytes(b"not an image").is_err());
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+        Ok(Self::new(ImageImage::load_from_memory(bytes)?))
+    }
+
+    /// Overrides how the image pixels are interpreted.
+    pub fn with_color_space(mut self, color_space: ColorSpace) -> Self {
+        self.color_space = color_space;
+        self.build_mip_levels();
+        self
+    }
+
+    /// Overrides how lookups read between pixels.
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Builds a mipmap chain, used for surfaces further than `distance` from
+    /// the ray origin.
+    pub fn with_mipmaps(mut self, distance: f64) -> Self {
+        self.mipmap_distance = Some(distance);
+        self.build_mip_levels();
+        self
+    }
+
+    pub fn get_color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    pub fn get_filter(&self) -> TextureFilter {
+        self.filter
+    }
+
+    pub fn get_mipmap_distance(&self) -> Option<f64> {
+        self.mipmap_distance
+    }
+
+    fn build_mip_levels(&mut self) {
+        self.mip_levels.clear();
+        let (width, height) = (self.image.width(), self.image.height());
+        if self.mipmap_distance.is_none() || width == 0 || height == 0 {
+            return;
+        }
+
+        let mut pixels = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(self.image_pixel(x, y));
+            }
+        }
+        let mut level = MipLevel {
+            width,
+            height,
+            pixels,
+        };
+        while level.width > 1 || level.height > 1 {
+            let next = level.half();
+            self.mip_levels.push(level);
+            level = next;
+        }
+        self.mip_levels.push(level);
+    }
+
+    fn image_pixel(&self, x: u32, y: u32) -> Color {
+        if let Some(color) = self.image.get_pixel(x, y) {
+            self.color_space.to_linear(color)
+        } else {
+            Color::new(0.0, 1.0, 1.0)
+        }
+    }
+
+    fn level_size(&self, level: usize) -> (u32, u32) {
+        match self.mip_levels.get(level) {
+            Some(level) => (level.width, level.height),
+            None => (self.image.width(), self.image.height()),
+        }
+    }
+
+    fn texel(&self, level: usize, x: u32, y: u32) -> Color {
+        match self.mip_levels.get(level) {
+            Some(level) => level.pixels[(y * level.width + x) as usize],
+            None => self.image_pixel(x, y),
+        }
+    }
+
+    /// Looks up a mipmap level, level 0 being the full image.
+    fn sample(&self, level: usize, u: f64, v: f64) -> Color {
+        let (width, height) = self.level_size(level);
+        if width == 0 || height == 0 {
+            return Color::new(0.0, 1.0, 1.0);
+        }
+
         // Clamp input texture coordinates to [0,1] x [1,0]
         let u = u.clamp(0.0, 1.0);
         let v = 1.0 - v.clamp(0.0, 1.0); // Flip V to image coordinates
 
-        let i = (u * self.image.width() as f64) as u32;
-        let j = (v * self.image.height() as f64) as u32;
-        if let Some(color) = self.image.get_pixel(i, j) {
+        match self.filter {
+            TextureFilter::Nearest => {
+                let i = ((u * width as f64) as u32).min(width - 1);
+                let j = ((v * height as f64) as u32).min(height - 1);
+                self.texel(level, i, j)
+            }
+            TextureFilter::Bilinear => {
+                // Pixel centers are at half coordinates
+                let x = u * width as f64 - 0.5;
+                let y = v * height as f64 - 0.5;
+                let (fx, fy) = (x - x.floor(), y - y.floor());
+                let x0 = (x.floor().max(0.0) as u32).min(width - 1);
+                let y0 = (y.floor().max(0.0) as u32).min(height - 1);
+                let x1 = ((x.floor() + 1.0).max(0.0) as u32).min(width - 1);
+                let y1 = ((y.floor() + 1.0).max(0.0) as u32).min(height - 1);
+
+                let top = self.texel(level, x0, y0) * (1.0 - fx) + self.texel(level, x1, y0) * fx;
+                let bottom =
+                    self.texel(level, x0, y1) * (1.0 - fx) + self.texel(level, x1, y1) * fx;
+                top * (1.0 - fy) + bottom * fy
+            }
+        }
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _pt: Vector3) -> Color {
+        self.sample(0, u, v)
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Color {
+        let Some(distance) = self.mipmap_distance else {
+            return self.sample(0, hit.u, hit.v);
+        };
+        if self.mip_levels.is_empty() || distance <= 0.0 || hit.t <= distance {
+            return self.sample(0, hit.u, hit.v);
+        }
+
+        let max_level = (self.mip_levels.len() - 1) as f64;
+        let lod = math::log2(hit.t / distance).min(max_level);
+        let level = lod.floor();
+        let f = lod - level;
+        let color = self.sample(level as usize, hit.u, hit.v);
+        if f == 0.0 {
             color
         } else {
-            Color::new(0.0, 1.0, 1.0)
+            color * (1.0 - f) + self.sample(level as usize + 1, hit.u, hit.v) * f
         }
     }
 }
@@ -1,21 +1,42 @@
 use std::fmt::Debug;
 
-use crate::{Color, Vector3};
+use crate::{Color, Vector3, object::HitRecord};
 
 pub mod checker_texture;
+pub mod gradient_texture;
 pub mod image_texture;
 pub mod perlin_noise;
 pub mod perlin_turbulence;
 pub mod solid_color;
+pub mod texture_registry;
+pub mod triplanar_texture;
+pub mod voronoi_texture;
 
 pub use checker_texture::CheckerTexture;
-pub use image_texture::ImageTexture;
+pub use gradient_texture::{GradientShape, GradientTexture};
+pub use image_texture::{ColorSpace, ImageTexture, TextureFilter};
 pub use perlin_noise::PerlinNoiseTexture;
 pub use perlin_turbulence::PerlinTurbulenceTexture;
 pub use solid_color::SolidColor;
+pub use texture_registry::{TextureRegistry, TextureRegistryStats};
+pub use triplanar_texture::TriplanarTexture;
+pub use voronoi_texture::{VoronoiFeature, VoronoiMetric, VoronoiTexture};
 
 pub trait Texture: Debug + Send + Sync {
     fn value(&self, u: f64, v: f64, pt: Vector3) -> Color;
+
+    /// Returns the color at a surface hit. Textures which depend on the
+    /// orientation of the surface override this, the others look up `value`
+    /// with the texture coordinates of the hit.
+    fn value_at(&self, hit: &HitRecord) -> Color {
+        self.value(hit.u, hit.v, hit.pt)
+    }
+
+    /// Returns the color of textures which are the same everywhere, so it can
+    /// be shown and edited as a single color.
+    fn solid_color(&self) -> Option<Color> {
+        None
+    }
 }
 
 impl PartialEq for dyn Texture {
@@ -1,4 +1,8 @@
-use crate::{Color, Random, Vector3, texture::Texture, utils::Perlin};
+use crate::{
+    Color, Random, Vector3,
+    texture::Texture,
+    utils::{Perlin, math},
+};
 
 #[derive(Debug)]
 pub struct PerlinTurbulenceTexture {
@@ -21,7 +25,8 @@ impl Texture for PerlinTurbulenceTexture {
     fn value(&self, _u: f64, _v: f64, pt: Vector3) -> Color {
         Color::new(0.5, 0.5, 0.5)
             * (1.0
-                + (self.scale * pt.z + 10.0 * self.noise.turbulence(pt, self.turbulence_depth))
-                    .sin())
+                + math::sin(
+                    self.scale * pt.z + 10.0 * self.noise.turbulence(pt, self.turbulence_depth),
+                ))
     }
 }
@@ -15,4 +15,8 @@ impl Texture for SolidColor {
     fn value(&self, _u: f64, _v: f64, _pt: crate::Vector3) -> crate::Color {
         self.albedo
     }
+
+    fn solid_color(&self) -> Option<Color> {
+        Some(self.albedo)
+    }
 }
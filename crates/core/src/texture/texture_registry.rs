@@ -0,0 +1,160 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{
+    Image,
+    texture::{ColorSpace, ImageTexture, TextureFilter},
+};
+
+/// Counts of what a [`TextureRegistry`] holds and how often it was used.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TextureRegistryStats {
+    /// Distinct images held
+    pub images: usize,
+    /// Distinct image textures held, one per image and set of options
+    pub textures: usize,
+    /// Lookups answered with a cached image or texture
+    pub hits: usize,
+    /// Images loaded because no earlier lookup did
+    pub misses: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct ImageTextureKey {
+    path: String,
+    color_space: ColorSpace,
+    filter: TextureFilter,
+    /// Bits of the mipmap distance, floats are not hashable
+    mipmap_distance: Option<u64>,
+}
+
+/// Cache of images and image textures keyed by path, so a scene using the
+/// same image in many materials loads and stores it once.
+///
+/// Textures with the same image and options are shared as well, which avoids
+/// building a mipmap chain per material.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Image,
+///     texture::{ColorSpace, TextureFilter, TextureRegistry, TextureRegistryStats},
+/// };
+///
+/// #[derive(Debug)]
+/// struct White;
+/// impl Image for White {
+///     fn width(&self) -> u32 { 2 }
+///     fn height(&self) -> u32 { 2 }
+///     fn get_pixel(&self, _x: u32, _y: u32) -> Option<Color> {
+///         Some(Color::WHITE)
+///     }
+/// }
+///
+/// let mut registry = TextureRegistry::new();
+/// let mut loads = 0;
+/// let mut textures = vec![];
+/// for _ in 0..50 {
+///     let texture = registry
+///         .get_or_load_texture("wood.png", ColorSpace::Srgb, TextureFilter::Bilinear, None, || {
+///             loads += 1;
+///             Ok::<_, String>(Arc::new(White) as Arc<dyn Image>)
+///         })
+///         .unwrap();
+///     textures.push(texture);
+/// }
+///
+/// assert_eq!(loads, 1);
+/// assert!(Arc::ptr_eq(&textures[0], &textures[49]));
+/// assert_eq!(
+///     registry.stats(),
+///     TextureRegistryStats { images: 1, textures: 1, hits: 49, misses: 1 }
+/// );
+///
+/// registry.clear();
+/// assert_eq!(registry.stats(), TextureRegistryStats::default());
+/// ```
+#[derive(Debug, Default)]
+pub struct TextureRegistry {
+    images: HashMap<String, Arc<dyn Image>>,
+    textures: HashMap<ImageTextureKey, Arc<ImageTexture>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl TextureRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the image stored for `path`, calling `load` only the first time
+    /// a path is requested. Failed loads are not cached.
+    pub fn get_or_load_image<E>(
+        &mut self,
+        path: &str,
+        load: impl FnOnce() -> Result<Arc<dyn Image>, E>,
+    ) -> Result<Arc<dyn Image>, E> {
+        if let Some(image) = self.images.get(path) {
+            self.hits += 1;
+            return Ok(image.clone());
+        }
+
+        let image = load()?;
+        self.misses += 1;
+        self.images.insert(path.to_owned(), image.clone());
+        Ok(image)
+    }
+
+    /// Returns the texture of the image at `path` with the given options,
+    /// loading the image and building the texture only when no earlier
+    /// lookup did.
+    pub fn get_or_load_texture<E>(
+        &mut self,
+        path: &str,
+        color_space: ColorSpace,
+        filter: TextureFilter,
+        mipmap_distance: Option<f64>,
+        load: impl FnOnce() -> Result<Arc<dyn Image>, E>,
+    ) -> Result<Arc<ImageTexture>, E> {
+        let key = ImageTextureKey {
+            path: path.to_owned(),
+            color_space,
+            filter,
+            mipmap_distance: mipmap_distance.map(f64::to_bits),
+        };
+        if let Some(texture) = self.textures.get(&key) {
+            self.hits += 1;
+            return Ok(texture.clone());
+        }
+
+        let image = self.get_or_load_image(path, load)?;
+        let mut texture = ImageTexture::new(image)
+            .with_color_space(color_space)
+            .with_filter(filter);
+        if let Some(mipmap_distance) = mipmap_distance {
+            texture = texture.with_mipmaps(mipmap_distance);
+        }
+        let texture = Arc::new(texture);
+        self.textures.insert(key, texture.clone());
+        Ok(texture)
+    }
+
+    pub fn stats(&self) -> TextureRegistryStats {
+        TextureRegistryStats {
+            images: self.images.len(),
+            textures: self.textures.len(),
+            hits: self.hits,
+            misses: self.misses,
+        }
+    }
+
+    /// Drops every cached image and texture and resets the counts. Textures
+    /// still used by a scene stay alive until the scene is dropped.
+    pub fn clear(&mut self) {
+        self.images.clear();
+        self.textures.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+}
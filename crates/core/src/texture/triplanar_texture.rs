@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use crate::{Color, Vector3, object::HitRecord, texture::Texture, utils::math};
+
+/// Projects a texture along the X, Y and Z axes and blends the three lookups by
+/// how much the surface faces each axis, for primitives with stretched or
+/// missing UVs such as boxes and meshes without texture coordinates.
+///
+/// The projection uses the hit point in world space, with the texture repeating
+/// every `scale` units. Looked up without a surface hit, through
+/// [`Texture::value`], the wrapped texture is used with the given coordinates.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use caustic_core::{
+///     Color, Ray, Vector3,
+///     material::{DiffuseLight, Lambertian, Material},
+///     object::HitRecord,
+///     texture::{Texture, TriplanarTexture},
+/// };
+///
+/// /// Shows the texture coordinates as red and green
+/// #[derive(Debug)]
+/// struct Uv;
+///
+/// impl Texture for Uv {
+///     fn value(&self, u: f64, v: f64, _pt: Vector3) -> Color {
+///         Color::new(u, v, 0.0)
+///     }
+/// }
+///
+/// let triplanar = TriplanarTexture::new(Arc::new(Uv)).with_scale(2.0);
+///
+/// // The top of a box, far from any UVs, is projected along Y
+/// let hit = HitRecord {
+///     pt: Vector3::new(0.5, 3.0, 5.0),
+///     normal: Vector3::new(0.0, 1.0, 0.0),
+///     tangent: Vector3::ZERO,
+///     t: 1.0,
+///     u: 0.0,
+///     v: 0.0,
+///     front_face: true,
+///     material: Arc::new(Lambertian::new_from_color(Color::WHITE)),
+///     object_id: 0,
+/// };
+/// assert_eq!(triplanar.value_at(&hit), Color::new(0.25, 0.5, 0.0));
+///
+/// // Lights project their emission the same way
+/// let light = DiffuseLight::new(Arc::new(triplanar));
+/// let ray = Ray::new(Vector3::new(0.5, 5.0, 5.0), Vector3::new(0.0, -1.0, 0.0));
+/// assert_eq!(light.emitted(&ray, &hit, hit.u, hit.v, hit.pt), Color::new(0.25, 0.5, 0.0));
+/// ```
+#[derive(Debug)]
+pub struct TriplanarTexture {
+    texture: Arc<dyn Texture>,
+    scale: f64,
+    sharpness: f64,
+}
+
+impl TriplanarTexture {
+    pub fn new(texture: Arc<dyn Texture>) -> Self {
+        Self {
+            texture,
+            scale: 1.0,
+            sharpness: 4.0,
+        }
+    }
+
+    /// Sets the size, in world units, covered by one repeat of the texture.
+    pub fn with_scale(mut self, scale: f64) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Sets how quickly the projections fade into each other where the surface
+    /// turns, higher values give narrower seams.
+    pub fn with_sharpness(mut self, sharpness: f64) -> Self {
+        self.sharpness = sharpness;
+        self
+    }
+
+    pub fn get_scale(&self) -> f64 {
+        self.scale
+    }
+
+    pub fn get_sharpness(&self) -> f64 {
+        self.sharpness
+    }
+
+    fn lookup(&self, a: f64, b: f64, pt: Vector3) -> Color {
+        let u = (a / self.scale).rem_euclid(1.0);
+        let v = (b / self.scale).rem_euclid(1.0);
+        self.texture.value(u, v, pt)
+    }
+}
+
+impl Texture for TriplanarTexture {
+    fn value(&self, u: f64, v: f64, pt: Vector3) -> Color {
+        self.texture.value(u, v, pt)
+    }
+
+    fn value_at(&self, hit: &HitRecord) -> Color {
+        let pt = hit.pt;
+        let weight_x = math::powf(hit.normal.x.abs(), self.sharpness);
+        let weight_y = math::powf(hit.normal.y.abs(), self.sharpness);
+        let weight_z = math::powf(hit.normal.z.abs(), self.sharpness);
+        let total = weight_x + weight_y + weight_z;
+        if total == 0.0 {
+            return self.lookup(pt.x, pt.y, pt);
+        }
+
+        let mut color = Color::BLACK;
+        for (weight, a, b) in [
+            (weight_x, pt.z, pt.y),
+            (weight_y, pt.x, pt.z),
+            (weight_z, pt.x, pt.y),
+        ] {
+            if weight > 0.0 {
+                color += self.lookup(a, b, pt) * (weight / total);
+            }
+        }
+        color
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{Color, Random, Vector3, texture::Texture, utils::Perlin};
+
+/// Which distance to a feature point a [`VoronoiTexture`] shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VoronoiFeature {
+    /// Distance to the nearest feature point, dark spots at the cell centers
+    #[default]
+    F1,
+    /// Distance to the second nearest feature point, rounded cells
+    F2,
+    /// Difference between the two nearest distances, dark lines along the
+    /// cell borders as in cracked ground or stone
+    F2MinusF1,
+}
+
+/// How distances to feature points are measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VoronoiMetric {
+    /// Straight line distance, round cells
+    #[default]
+    Euclidean,
+    /// Sum of the distances along each axis, diamond shaped cells
+    Manhattan,
+    /// Largest distance along an axis, square cells
+    Chebyshev,
+}
+
+impl VoronoiMetric {
+    fn distance(&self, d: Vector3) -> f64 {
+        match self {
+            VoronoiMetric::Euclidean => d.length(),
+            VoronoiMetric::Manhattan => d.x.abs() + d.y.abs() + d.z.abs(),
+            VoronoiMetric::Chebyshev => d.x.abs().max(d.y.abs()).max(d.z.abs()),
+        }
+    }
+}
+
+/// Worley (cellular) noise: space is split in cells around pseudo-random
+/// feature points, one per unit lattice cell, and the texture blends from
+/// `low` to `high` with the distance to them. Distances are clamped to 1.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::{
+///     Color, Vector3, random_new,
+///     texture::{Texture, VoronoiFeature, VoronoiTexture},
+/// };
+///
+/// let random = random_new();
+/// let cells = VoronoiTexture::new(&*random, 1.0);
+///
+/// // Black right on a feature point
+/// let feature_point = cells.get_noise().cell_point(2, 0, 1) + Vector3::new(2.0, 0.0, 1.0);
+/// assert_eq!(cells.value(0.0, 0.0, feature_point), Color::BLACK);
+///
+/// // Stone with dark mortar along the cell borders
+/// let stone = VoronoiTexture::new(&*random, 0.5)
+///     .with_feature(VoronoiFeature::F2MinusF1)
+///     .with_colors(Color::new(0.1, 0.1, 0.1), Color::new(0.6, 0.55, 0.5));
+/// let color = stone.value(0.0, 0.0, Vector3::new(0.3, 1.7, 2.2));
+/// assert!(color.r >= 0.1 && color.r <= 0.6);
+/// ```
+#[derive(Debug)]
+pub struct VoronoiTexture {
+    noise: Perlin,
+    scale: f64,
+    feature: VoronoiFeature,
+    metric: VoronoiMetric,
+    low: Color,
+    high: Color,
+}
+
+impl VoronoiTexture {
+    /// Creates a texture with `scale` cells per unit, showing the distance to
+    /// the nearest feature point from black to white.
+    pub fn new(random: &dyn Random, scale: f64) -> Self {
+        Self {
+            noise: Perlin::new(random),
+            scale,
+            feature: VoronoiFeature::default(),
+            metric: VoronoiMetric::default(),
+            low: Color::BLACK,
+            high: Color::WHITE,
+        }
+    }
+
+    pub fn with_feature(mut self, feature: VoronoiFeature) -> Self {
+        self.feature = feature;
+        self
+    }
+
+    pub fn with_metric(mut self, metric: VoronoiMetric) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    /// Sets the colors at distance 0 and at distance 1 or more.
+    pub fn with_colors(mut self, low: Color, high: Color) -> Self {
+        self.low = low;
+        self.high = high;
+        self
+    }
+
+    /// Returns the lattice the feature points are taken from.
+    pub fn get_noise(&self) -> &Perlin {
+        &self.noise
+    }
+
+    pub fn get_feature(&self) -> VoronoiFeature {
+        self.feature
+    }
+
+    pub fn get_metric(&self) -> VoronoiMetric {
+        self.metric
+    }
+
+    /// Returns the distances to the nearest and second nearest feature points.
+    fn distances(&self, pt: Vector3) -> (f64, f64) {
+        let i = pt.x.floor() as isize;
+        let j = pt.y.floor() as isize;
+        let k = pt.z.floor() as isize;
+
+        let mut f1 = f64::INFINITY;
+        let mut f2 = f64::INFINITY;
+        for di in -1..=1 {
+            for dj in -1..=1 {
+                for dk in -1..=1 {
+                    let (ci, cj, ck) = (i + di, j + dj, k + dk);
+                    let corner = Vector3::new(ci as f64, cj as f64, ck as f64);
+                    let feature_point = corner + self.noise.cell_point(ci, cj, ck);
+                    let distance = self.metric.distance(feature_point - pt);
+                    if distance < f1 {
+                        f2 = f1;
+                        f1 = distance;
+                    } else if distance < f2 {
+                        f2 = distance;
+                    }
+                }
+            }
+        }
+        (f1, f2)
+    }
+}
+
+impl Texture for VoronoiTexture {
+    fn value(&self, _u: f64, _v: f64, pt: Vector3) -> Color {
+        let (f1, f2) = self.distances(self.scale * pt);
+        let t = match self.feature {
+            VoronoiFeature::F1 => f1,
+            VoronoiFeature::F2 => f2,
+            VoronoiFeature::F2MinusF1 => f2 - f1,
+        }
+        .clamp(0.0, 1.0);
+        self.low * (1.0 - t) + self.high * t
+    }
+}
@@ -0,0 +1,93 @@
+//! Transcendental functions used while rendering.
+//!
+//! The standard library calls the platform's math library for these, which
+//! rounds differently on Linux, macOS, Windows and in the browser, so the same
+//! seeded render would not give identical pixels everywhere. By default they
+//! are computed with `libm`, in software and identically on every target, so
+//! webapp previews match the final renders of the server. The `fastmath`
+//! feature switches back to the platform functions, which are faster on some
+//! targets. Square roots are exactly rounded everywhere and need no wrapper.
+
+macro_rules! unary {
+    ($($(#[$doc:meta])* $name:ident => $libm:ident;)*) => {$(
+        $(#[$doc])*
+        #[inline]
+        pub fn $name(x: f64) -> f64 {
+            #[cfg(feature = "fastmath")]
+            {
+                x.$name()
+            }
+            #[cfg(not(feature = "fastmath"))]
+            {
+                libm::$libm(x)
+            }
+        }
+    )*};
+}
+
+unary! {
+    sin => sin;
+    cos => cos;
+    tan => tan;
+    asin => asin;
+    acos => acos;
+    atan => atan;
+    exp => exp;
+    /// Two raised to the power of `x`
+    exp2 => exp2;
+    /// Natural logarithm
+    ln => log;
+    log2 => log2;
+    log10 => log10;
+}
+
+#[inline]
+pub fn atan2(y: f64, x: f64) -> f64 {
+    #[cfg(feature = "fastmath")]
+    {
+        y.atan2(x)
+    }
+    #[cfg(not(feature = "fastmath"))]
+    {
+        libm::atan2(y, x)
+    }
+}
+
+/// Returns the sine and cosine of `x`.
+#[inline]
+pub fn sin_cos(x: f64) -> (f64, f64) {
+    #[cfg(feature = "fastmath")]
+    {
+        x.sin_cos()
+    }
+    #[cfg(not(feature = "fastmath"))]
+    {
+        libm::sincos(x)
+    }
+}
+
+#[inline]
+pub fn powf(x: f64, y: f64) -> f64 {
+    #[cfg(feature = "fastmath")]
+    {
+        x.powf(y)
+    }
+    #[cfg(not(feature = "fastmath"))]
+    {
+        libm::pow(x, y)
+    }
+}
+
+/// Raises `x` to an integer power. The precision of [`f64::powi`] is not
+/// specified, it is computed like [`powf`].
+#[inline]
+pub fn powi(x: f64, n: i32) -> f64 {
+    #[cfg(feature = "fastmath")]
+    {
+        x.powi(n)
+    }
+    #[cfg(not(feature = "fastmath"))]
+    {
+        libm::pow(x, n as f64)
+    }
+}
@@ -1,8 +1,20 @@
+pub mod math;
 pub mod orthonormal_basis;
 pub mod perlin;
+#[cfg(all(feature = "simd128", target_feature = "simd128"))]
+pub mod simd128;
+pub mod solar_position;
 
 pub use orthonormal_basis::OrthonormalBasis;
 pub use perlin::Perlin;
+pub use solar_position::SolarPosition;
+
+#[cfg(all(
+    feature = "simd128",
+    target_arch = "wasm32",
+    not(target_feature = "simd128")
+))]
+compile_error!("the simd128 feature needs RUSTFLAGS=\"-C target-feature=+simd128\"");
 
 #[cfg(not(target_arch = "wasm32"))]
 pub fn to_absolute(path: &str) -> std::io::Result<std::path::PathBuf> {
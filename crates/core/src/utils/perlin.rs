@@ -112,10 +112,8 @@ impl Perlin {
         for di in 0..2 {
             for dj in 0..2 {
                 for dk in 0..2 {
-                    let i = self.perm_x[((i + di) & 255) as usize]
-                        ^ self.perm_y[((j + dj) & 255) as usize]
-                        ^ self.perm_z[((k + dk) & 255) as usize];
-                    c[di as usize][dj as usize][dk as usize] = self.rand_vec[i];
+                    c[di as usize][dj as usize][dk as usize] =
+                        self.rand_vec[self.hash(i + di, j + dj, k + dk)];
                 }
             }
         }
@@ -162,6 +160,39 @@ impl Perlin {
         acc.abs()
     }
 
+    /// Returns a pseudo-random point inside the unit lattice cell with corner
+    /// (i, j, k), relative to that corner. The same cell always gives the same
+    /// point, which makes it suitable as a feature point for cellular noise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use caustic_core::{utils::Perlin, random_new};
+    ///
+    /// let random = random_new();
+    /// let perlin = Perlin::new(&*random);
+    /// let pt = perlin.cell_point(3, -2, 7);
+    /// assert!((0.0..1.0).contains(&pt.x) && (0.0..1.0).contains(&pt.y) && (0.0..1.0).contains(&pt.z));
+    /// assert_eq!(pt, perlin.cell_point(3, -2, 7));
+    /// ```
+    pub fn cell_point(&self, i: isize, j: isize, k: isize) -> Vector3 {
+        let h = self.hash(i, j, k);
+        let scale = 1.0 / Perlin::POINT_COUNT as f64;
+        // Each table is a different permutation, so the components vary independently
+        Vector3::new(
+            (self.perm_x[h] as f64 + 0.5) * scale,
+            (self.perm_y[h] as f64 + 0.5) * scale,
+            (self.perm_z[h] as f64 + 0.5) * scale,
+        )
+    }
+
+    /// Hashes lattice coordinates to an index into the lattice tables.
+    fn hash(&self, i: isize, j: isize, k: isize) -> usize {
+        self.perm_x[(i & 255) as usize]
+            ^ self.perm_y[(j & 255) as usize]
+            ^ self.perm_z[(k & 255) as usize]
+    }
+
     /// Performs trilinear interpolation with Hermite smoothing on a 2x2x2 cube of gradient vectors.
     ///
     /// This is the core of Perlin noise, using dot products between random gradients and
@@ -0,0 +1,80 @@
+//! Hand-written WebAssembly SIMD128 kernels for the hottest vector and
+//! bounding box operations, enabled by the `simd128` feature on wasm32 builds
+//! compiled with `-C target-feature=+simd128`.
+//!
+//! Each kernel does the same floating point operations in the same order as
+//! its scalar version and ignores NaN operands like `f64::min` and `f64::max`,
+//! so the browser renders bit-identical images with or without SIMD.
+
+use std::arch::wasm32::{
+    f64x2, f64x2_div, f64x2_extract_lane, f64x2_mul, f64x2_ne, f64x2_pmax, f64x2_pmin, f64x2_splat,
+    f64x2_sub, v128, v128_bitselect,
+};
+
+use crate::{Axis, AxisAlignedBoundingBox, Interval, Ray, Vector3};
+
+/// Lane-wise `f64::min`, returning the other lane where one is NaN.
+#[inline]
+fn min(a: v128, b: v128) -> v128 {
+    // pmin returns `a` where either is NaN, so take `b` where `a` is NaN
+    v128_bitselect(b, f64x2_pmin(a, b), f64x2_ne(a, a))
+}
+
+/// Lane-wise `f64::max`, returning the other lane where one is NaN.
+#[inline]
+fn max(a: v128, b: v128) -> v128 {
+    v128_bitselect(b, f64x2_pmax(a, b), f64x2_ne(a, a))
+}
+
+/// Slab test of [`AxisAlignedBoundingBox::hit`], the x and y slabs side by
+/// side in one vector.
+#[inline]
+pub fn slab_hit(bbox: &AxisAlignedBoundingBox, ray: &Ray, ray_t: Interval) -> bool {
+    let x = bbox.axis_interval(Axis::X);
+    let y = bbox.axis_interval(Axis::Y);
+    let z = bbox.axis_interval(Axis::Z);
+
+    let inv = f64x2_div(f64x2_splat(1.0), f64x2(ray.direction.x, ray.direction.y));
+    let origin = f64x2(ray.origin.x, ray.origin.y);
+    let t0 = f64x2_mul(f64x2_sub(f64x2(x.min, y.min), origin), inv);
+    let t1 = f64x2_mul(f64x2_sub(f64x2(x.max, y.max), origin), inv);
+    let near = min(t0, t1);
+    let far = max(t0, t1);
+
+    let inv_z = 1.0 / ray.direction.z;
+    let tz0 = (z.min - ray.origin.z) * inv_z;
+    let tz1 = (z.max - ray.origin.z) * inv_z;
+
+    let t_min = ray_t
+        .min
+        .max(f64x2_extract_lane::<0>(near))
+        .max(f64x2_extract_lane::<1>(near))
+        .max(tz0.min(tz1));
+    let t_max = ray_t
+        .max
+        .min(f64x2_extract_lane::<0>(far))
+        .min(f64x2_extract_lane::<1>(far))
+        .min(tz0.max(tz1));
+    t_min < t_max
+}
+
+/// [`Vector3::dot`] with the x and y products in one multiplication.
+#[inline]
+pub fn dot(a: &Vector3, b: &Vector3) -> f64 {
+    let products = f64x2_mul(f64x2(a.x, a.y), f64x2(b.x, b.y));
+    f64x2_extract_lane::<0>(products) + f64x2_extract_lane::<1>(products) + a.z * b.z
+}
+
+/// [`Vector3::cross`] with the x and y components computed side by side.
+#[inline]
+pub fn cross(a: &Vector3, b: &Vector3) -> Vector3 {
+    let xy = f64x2_sub(
+        f64x2_mul(f64x2(a.y, a.z), f64x2(b.z, b.x)),
+        f64x2_mul(f64x2(a.z, a.x), f64x2(b.y, b.z)),
+    );
+    Vector3::new(
+        f64x2_extract_lane::<0>(xy),
+        f64x2_extract_lane::<1>(xy),
+        a.x * b.y - a.y * b.x,
+    )
+}
@@ -0,0 +1,91 @@
+use crate::utils::math;
+
+/// Position of the sun in the sky seen from a place on earth at a given time,
+/// from the low precision formulas of the Astronomical Almanac, accurate to
+/// about a hundredth of a degree between 1950 and 2050.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_core::utils::SolarPosition;
+///
+/// // Summer solstice at noon on the tropic of cancer, the sun is overhead
+/// let overhead = SolarPosition::new(23.44, 0.0, 2024, 6, 20, 12.0);
+/// assert!(overhead.elevation > 89.0);
+///
+/// // An afternoon in Paris, the sun is in the south west
+/// let paris = SolarPosition::new(48.86, 2.35, 2024, 10, 16, 14.0);
+/// assert!(paris.azimuth > 180.0 && paris.azimuth < 270.0);
+/// assert!(paris.elevation > 20.0 && paris.elevation < 40.0);
+///
+/// // At night the sun is below the horizon
+/// let night = SolarPosition::new(48.86, 2.35, 2024, 10, 16, 23.0);
+/// assert!(night.elevation < 0.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SolarPosition {
+    /// Compass direction of the sun in degrees, clockwise from north
+    pub azimuth: f64,
+    /// Angle of the sun above the horizon in degrees, negative at night
+    pub elevation: f64,
+}
+
+impl SolarPosition {
+    /// Computes the sun position at `latitude` and `longitude` in degrees,
+    /// positive north and east, on a Gregorian calendar date at `hours` UTC.
+    pub fn new(latitude: f64, longitude: f64, year: i32, month: u32, day: u32, hours: f64) -> Self {
+        // Days since noon UTC on January 1st 2000
+        let n = julian_day(year, month, day) + hours / 24.0 - 2451545.0;
+
+        // Ecliptic longitude of the sun from its mean longitude and anomaly
+        let mean_longitude = 280.460 + 0.9856474 * n;
+        let mean_anomaly = (357.528 + 0.9856003 * n).to_radians();
+        let ecliptic_longitude = (mean_longitude
+            + 1.915 * math::sin(mean_anomaly)
+            + 0.020 * math::sin(2.0 * mean_anomaly))
+        .to_radians();
+        let obliquity = (23.439 - 0.0000004 * n).to_radians();
+
+        let (sin_longitude, cos_longitude) = math::sin_cos(ecliptic_longitude);
+        let right_ascension =
+            math::atan2(math::cos(obliquity) * sin_longitude, cos_longitude).to_degrees();
+        let declination = math::asin(math::sin(obliquity) * sin_longitude);
+
+        // Angle the earth turned since the sun crossed the local meridian
+        let sidereal_time = 280.46061837 + 360.98564736629 * n;
+        let hour_angle = (sidereal_time + longitude - right_ascension).to_radians();
+
+        let latitude = latitude.to_radians();
+        let (sin_latitude, cos_latitude) = math::sin_cos(latitude);
+        let (sin_hour_angle, cos_hour_angle) = math::sin_cos(hour_angle);
+        let elevation = math::asin(
+            sin_latitude * math::sin(declination)
+                + cos_latitude * math::cos(declination) * cos_hour_angle,
+        );
+        let azimuth = math::atan2(
+            -sin_hour_angle,
+            cos_latitude * math::tan(declination) - sin_latitude * cos_hour_angle,
+        );
+
+        Self {
+            azimuth: azimuth.to_degrees().rem_euclid(360.0),
+            elevation: elevation.to_degrees(),
+        }
+    }
+}
+
+/// Returns the Julian day at midnight UTC starting a Gregorian calendar date.
+fn julian_day(year: i32, month: u32, day: u32) -> f64 {
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 12)
+    } else {
+        (year, month)
+    };
+    let century = (year as f64 / 100.0).floor();
+    let leap_correction = 2.0 - century + (century / 4.0).floor();
+    (365.25 * (year as f64 + 4716.0)).floor()
+        + (30.6001 * (month as f64 + 1.0)).floor()
+        + day as f64
+        + leap_correction
+        - 1524.5
+}
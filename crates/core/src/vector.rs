@@ -1,7 +1,7 @@
 use core::f64;
 use std::ops::{Add, Div, Mul, Neg, Sub};
 
-use crate::{Axis, Random};
+use crate::{Axis, Random, utils::math};
 
 /// A 3-dimensional vector with x, y, and z components.
 ///
@@ -138,8 +138,8 @@ impl Vector3 {
         let r2 = random.rand();
 
         let phi = 2.0 * f64::consts::PI * r1;
-        let x = phi.cos() * r2.sqrt();
-        let y = phi.sin() * r2.sqrt();
+        let x = math::cos(phi) * r2.sqrt();
+        let y = math::sin(phi) * r2.sqrt();
         let z = (1.0 - r2).sqrt();
 
         Vector3::new(x, y, z)
@@ -209,7 +209,14 @@ impl Vector3 {
     /// assert_eq_float!(v1.dot(&v2), 32.0);
     /// ```
     pub fn dot(&self, other: &Vector3) -> f64 {
-        self.x * other.x + self.y * other.y + self.z * other.z
+        #[cfg(all(feature = "simd128", target_feature = "simd128"))]
+        {
+            crate::utils::simd128::dot(self, other)
+        }
+        #[cfg(not(all(feature = "simd128", target_feature = "simd128")))]
+        {
+            self.x * other.x + self.y * other.y + self.z * other.z
+        }
     }
 
     /// Computes the cross product (vector product) of this vector with another.
@@ -232,10 +239,17 @@ impl Vector3 {
     /// assert_eq_float!(result.z, 1.0);
     /// ```
     pub fn cross(&self, other: &Vector3) -> Vector3 {
-        Vector3 {
-            x: self.y * other.z - self.z * other.y,
-            y: self.z * other.x - self.x * other.z,
-            z: self.x * other.y - self.y * other.x,
+        #[cfg(all(feature = "simd128", target_feature = "simd128"))]
+        {
+            crate::utils::simd128::cross(self, other)
+        }
+        #[cfg(not(all(feature = "simd128", target_feature = "simd128")))]
+        {
+            Vector3 {
+                x: self.y * other.z - self.z * other.y,
+                y: self.z * other.x - self.x * other.z,
+                z: self.x * other.y - self.y * other.x,
+            }
         }
     }
 
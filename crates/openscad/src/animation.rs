@@ -0,0 +1,80 @@
+use crate::value::Value;
+
+/// A value changing over the time of an animation, such as the offset of a
+/// `translate()`, given at keyframes and interpolated in between.
+///
+/// Numbers and vectors of numbers are interpolated linearly, other values
+/// hold until the next keyframe. Before the first and after the last
+/// keyframe the track keeps their values.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_openscad::{animation::Track, value::Value};
+///
+/// let point = |y: f64| Value::Vector {
+///     items: vec![Value::Number(0.0), Value::Number(y), Value::Number(0.0)],
+/// };
+/// let track = Track::new(vec![(0.0, point(0.0)), (1.0, point(10.0))]).unwrap();
+/// assert_eq!(track.value_at(0.25), point(2.5));
+/// assert_eq!(track.value_at(2.0), point(10.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    keyframes: Vec<(f64, Value)>,
+}
+
+impl Track {
+    /// Creates a track from `(time, value)` keyframes sorted by time.
+    pub fn new(keyframes: Vec<(f64, Value)>) -> Result<Self, String> {
+        if keyframes.is_empty() {
+            return Err("a track needs at least one keyframe".to_owned());
+        }
+        if keyframes.windows(2).any(|pair| pair[1].0 < pair[0].0) {
+            return Err("keyframes must be sorted by time".to_owned());
+        }
+        Ok(Self { keyframes })
+    }
+
+    /// Returns the value of the track at time `t`.
+    pub fn value_at(&self, t: f64) -> Value {
+        // Index of the first keyframe after t
+        let next = self.keyframes.partition_point(|(time, _)| *time <= t);
+        if next == 0 {
+            return self.keyframes[0].1.clone();
+        }
+        let (start_time, start) = &self.keyframes[next - 1];
+        let Some((end_time, end)) = self.keyframes.get(next) else {
+            return start.clone();
+        };
+
+        let fraction = (t - start_time) / (end_time - start_time);
+        interpolate(start, end, fraction).unwrap_or_else(|| start.clone())
+    }
+}
+
+/// Returns the time `$t` of frame `frame` of an animation of `frame_count`
+/// frames, from 0 for the first frame to just under 1 for the last, like
+/// OpenSCAD, so looping animations do not show the same pose twice.
+pub fn frame_time(frame: u32, frame_count: u32) -> f64 {
+    frame as f64 / frame_count.max(1) as f64
+}
+
+fn interpolate(start: &Value, end: &Value, fraction: f64) -> Option<Value> {
+    match (start, end) {
+        (Value::Number(start), Value::Number(end)) => {
+            Some(Value::Number(start + (end - start) * fraction))
+        }
+        (Value::Vector { items: start }, Value::Vector { items: end })
+            if start.len() == end.len() =>
+        {
+            let items = start
+                .iter()
+                .zip(end)
+                .map(|(start, end)| interpolate(start, end, fraction))
+                .collect::<Option<Vec<_>>>()?;
+            Some(Value::Vector { items })
+        }
+        _ => None,
+    }
+}
@@ -18,7 +18,6 @@ pub(crate) static BUILTIN_MODULE_DOCS: LazyLock<HashMap<&'static str, ModuleDocs
         // TODO Other - render
         // TODO Other - children
         // TODO Other - assert
-        // TODO Other - assign
         // TODO functions - concat
         // TODO functions - lookup
         // TODO functions - str
@@ -129,6 +128,97 @@ pub(crate) static BUILTIN_MODULE_DOCS: LazyLock<HashMap<&'static str, ModuleDocs
                         description: "Background color as [r, g, b] (values 0-1).".to_owned(),
                         default: Some("[0, 0, 0]".to_owned()),
                     },
+                    ModuleDocsArguments {
+                        name: "lens_distortion".to_owned(),
+                        description:
+                            "Radial lens distortion, positive for barrel and negative for pincushion (0 for a perfect lens)."
+                                .to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "chromatic_aberration".to_owned(),
+                        description:
+                            "Extra distortion of red and less of blue, producing color fringes near the edges (0 to disable)."
+                                .to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "environment".to_owned(),
+                        description:
+                            "Equirectangular image surrounding the scene, such as an HDR environment map, replacing the background color. Bright areas are sampled as lights."
+                                .to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "environment_intensity".to_owned(),
+                        description: "Brightness multiplier of the environment.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "environment_rotation".to_owned(),
+                        description: "Rotation of the environment around the vertical axis in degrees.".to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "render_region".to_owned(),
+                        description: "Rectangle [x, y, width, height] in pixels from the top left of the image to render, the rest stays black. Useful to iterate on a detail of a slow scene.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "name".to_owned(),
+                        description: "Name to define several cameras in a scene and choose which one to render. An unnamed camera is rendered by default, otherwise the first named one.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "crop_window".to_owned(),
+                        description: "Part of the image [xmin, xmax, ymin, ymax] to render as fractions of its width and height from the top left, the rest stays black. Unlike render_region it covers the same part of the scene at any image_width. Replaces render_region.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "projection".to_owned(),
+                        description: "\"perspective\", or \"orthographic\" for parallel rays where objects keep their size at any distance, as in technical drawings and isometric views.".to_owned(),
+                        default: Some("\"perspective\"".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "view_height".to_owned(),
+                        description: "Height of the visible part of the scene with an orthographic projection. Defaults to what vertical_fov shows at the distance of look_at.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "exposure".to_owned(),
+                        description: "Exposure compensation in stops, each stop doubles the brightness. Cannot be combined with iso, f_number and shutter.".to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "iso".to_owned(),
+                        description: "Sensor sensitivity of a physical exposure. The defaults of iso, f_number and shutter leave colors unchanged, so settings of real cameras such as f/8 and 1/60 s render much darker than a photo.".to_owned(),
+                        default: Some("100".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "f_number".to_owned(),
+                        description: "Aperture f-number of a physical exposure, each doubling lets in a quarter of the light. Does not change the depth of field.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "shutter".to_owned(),
+                        description: "Shutter time in seconds of a physical exposure.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "firefly_clamp".to_owned(),
+                        description: "Highest brightness of each color channel of a traced path, or false to never clamp. Clamping removes isolated bright pixels but darkens highlights and caustics.".to_owned(),
+                        default: Some("10".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "min_pdf".to_owned(),
+                        description: "Paths scattering in directions less likely than this end early, which avoids bright pixels but loses some light at grazing angles. 0 keeps every path.".to_owned(),
+                        default: Some("0.05".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "caustic_photons".to_owned(),
+                        description: "Photons traced from the lights through mirrors and glass, so caustics converge in few samples. More photons give sharper caustics, up to 10000000; 0 leaves them to path tracing.".to_owned(),
+                        default: Some("0".to_owned()),
+                    },
                 ],
                 examples: vec![
                     "camera();".to_owned(),
@@ -137,144 +227,726 @@ pub(crate) static BUILTIN_MODULE_DOCS: LazyLock<HashMap<&'static str, ModuleDocs
                     "camera(samples_per_pixel=100, max_depth=50, defocus_angle=0.6);".to_owned(),
                     "camera(background=[0, 0, 0], look_from=[3, 3, 2], look_at=[0, 0, -1]);"
                         .to_owned(),
+                    "camera(lens_distortion=0.1, chromatic_aberration=0.01);".to_owned(),
+                    "camera(projection=\"orthographic\", view_height=20, look_from=[30, 30, 30]);"
+                        .to_owned(),
+                    "camera(environment=\"sky.hdr\", environment_rotation=90);".to_owned(),
+                    "camera(image_width=800, render_region=[300, 200, 100, 100]);".to_owned(),
+                    "camera(crop_window=[0.25, 0.75, 0.4, 0.6]);".to_owned(),
+                    "camera(exposure=-2);".to_owned(),
+                    "camera(iso=200, f_number=1.4, shutter=1);".to_owned(),
+                    "camera(caustic_photons=200000);".to_owned(),
+                    "camera(name=\"front\", look_from=[0, 0, 50]); camera(name=\"top\", look_from=[0, 50, 0]);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "sky",
+            ModuleDocs {
+                description: "Lights the scene with physically based daylight, a sky whose color depends on the sun position and a sun which is sampled like a light. Replaces the camera background."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "sun".to_owned(),
+                        description: "direction towards the sun [x, y, z], the sky reddens as it nears the horizon.".to_owned(),
+                        default: Some("[1, 1, 2]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "turbidity".to_owned(),
+                        description: "haziness of the air, 2 for a clear sky up to 10 for a hazy one.".to_owned(),
+                        default: Some("3".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "intensity".to_owned(),
+                        description: "brightness multiplier of the sky and sun.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "sky();".to_owned(),
+                    "sky(sun=[1, 0, 0.1], turbidity=5);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "sunlight",
+            ModuleDocs {
+                description: "Lights the scene like sky() with the sun where it stands at a place and time, for example to check the shadows of a building. North is +y and east is +x. Replaces the camera background."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "lat".to_owned(),
+                        description: "latitude in degrees, positive north of the equator.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "lon".to_owned(),
+                        description: "longitude in degrees, positive east of Greenwich.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "date".to_owned(),
+                        description: "date as [year, month, day].".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "time".to_owned(),
+                        description: "time of day as hours or [hours, minutes], in the time zone `timezone`.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "timezone".to_owned(),
+                        description: "offset of the time zone from UTC in hours, including daylight saving time.".to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "turbidity".to_owned(),
+                        description: "haziness of the air, 2 for a clear sky up to 10 for a hazy one.".to_owned(),
+                        default: Some("3".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "intensity".to_owned(),
+                        description: "brightness multiplier of the sky and sun.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "sunlight(lat=48.86, lon=2.35, date=[2024, 6, 21], time=[16, 30], timezone=2);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "point_light",
+            ModuleDocs {
+                description: "Adds a light shining in every direction from a point, falling off with the square of the distance and casting hard shadows. Transforms around it are ignored."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "position".to_owned(),
+                        description: "position of the light [x, y, z].".to_owned(),
+                        default: Some("[0, 0, 0]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "c".to_owned(),
+                        description: "color of the light.".to_owned(),
+                        default: Some("[1, 1, 1]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "intensity".to_owned(),
+                        description: "brightness of the light one unit away, so lights far from the scene need large values.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "point_light(position=[0, 0, 20], intensity=400);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "spot_light",
+            ModuleDocs {
+                description: "Adds a point light shining in a cone towards a target, fading out smoothly at the edge of the cone. Transforms around it are ignored."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "position".to_owned(),
+                        description: "position of the light [x, y, z].".to_owned(),
+                        default: Some("[0, 0, 0]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "target".to_owned(),
+                        description: "point the light shines at [x, y, z], which must differ from position.".to_owned(),
+                        default: Some("[0, 0, 0]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "c".to_owned(),
+                        description: "color of the light.".to_owned(),
+                        default: Some("[1, 1, 1]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "intensity".to_owned(),
+                        description: "brightness of the light one unit away.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "angle".to_owned(),
+                        description: "angle in degrees between the direction of the light and the edge of the cone.".to_owned(),
+                        default: Some("30".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "inner_angle".to_owned(),
+                        description: "angle in degrees within which the light is full, at most angle.".to_owned(),
+                        default: Some("0.8 * angle".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "spot_light(position=[0, 0, 30], target=[0, 0, 0], intensity=900, angle=20);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "sun",
+            ModuleDocs {
+                description: "Adds a light from so far away that its rays are parallel, lighting the whole scene from one direction with hard shadows. Unlike sky(), the background is left alone."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "direction".to_owned(),
+                        description: "direction towards the light [x, y, z].".to_owned(),
+                        default: Some("[1, 1, 2]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "c".to_owned(),
+                        description: "color of the light.".to_owned(),
+                        default: Some("[1, 1, 1]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "intensity".to_owned(),
+                        description: "brightness of the light on a surface facing it.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "sun(direction=[1, -1, 1], intensity=3);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "lambertian",
+            ModuleDocs {
+                description: "Creates a Lambertian (diffuse) material with the specified texture."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "t".to_owned(),
+                        description: "texture for the Lambertian material. Can be a color value or texture object."
+                            .to_owned(),
+                        default: None,
+                    },
+                ],
+                examples: vec![
+                    "lambertian([0.5, 0.5, 0.5]);".to_owned(),
+                    "lambertian(checker_texture);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "metal",
+            ModuleDocs {
+                description: "Creates a metal material with configurable color and fuzziness for reflections."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "c".to_owned(),
+                        description: "metal color as RGB vector [r,g,b] with values 0-1, or single grayscale value."
+                            .to_owned(),
+                        default: Some("white".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "fuzz".to_owned(),
+                        description: "fuzziness factor for reflections (0=perfect mirror, 1=maximum diffusion)."
+                            .to_owned(),
+                        default: Some("0.2".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "metal([0.8, 0.8, 0.8]);".to_owned(),
+                    "metal([0.8, 0.6, 0.2], 0.3);".to_owned(),
+                    "metal([0.8, 0.8, 0.8], fuzz=0.1);".to_owned(),
+                    "metal(0.7);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "diffuse_light",
+            ModuleDocs {
+                description: "Creates a light emitting material. The emission can be a color or a texture, such as an image for a glowing screen."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "c".to_owned(),
+                        description: "emitted color as RGB vector [r,g,b], single grayscale value, or texture object."
+                            .to_owned(),
+                        default: Some("white".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "intensity".to_owned(),
+                        description: "multiplier applied to the emitted color.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "temperature".to_owned(),
+                        description: "color temperature in Kelvin tinting the emitted color, 2700 for a warm bulb and 6500 for daylight.".to_owned(),
+                        default: None,
+                    },
+                ],
+                examples: vec![
+                    "diffuse_light([4, 4, 4]);".to_owned(),
+                    "diffuse_light(4, temperature=3200);".to_owned(),
+                    "diffuse_light(image(\"tv.png\"), intensity=3);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "pbr",
+            ModuleDocs {
+                description: "Creates a physically based material combining a diffuse base, a glossy specular layer, glass-like transmission and emission. Parameters match the metallic-roughness model used by glTF."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "base_color".to_owned(),
+                        description: "base color as RGB vector [r,g,b], single grayscale value, or texture object.".to_owned(),
+                        default: Some("white".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "metallic".to_owned(),
+                        description: "0 for dielectrics such as plastic, 1 for metals which reflect tinted by the base color.".to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "roughness".to_owned(),
+                        description: "0 for a mirror-like finish, 1 for a fully rough one.".to_owned(),
+                        default: Some("0.5".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "specular".to_owned(),
+                        description: "strength of the reflection of dielectrics, 0.5 reflects 4% of the light at normal incidence.".to_owned(),
+                        default: Some("0.5".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "transmission".to_owned(),
+                        description: "0 for opaque, 1 for glass that refracts light through the surface.".to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "ior".to_owned(),
+                        description: "index of refraction used for transmission.".to_owned(),
+                        default: Some("1.5".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "emission".to_owned(),
+                        description: "emitted color as RGB vector [r,g,b].".to_owned(),
+                        default: Some("black".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "sheen".to_owned(),
+                        description: "strength of the cloth-like sheen brightening grazing angles.".to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "sheen_tint".to_owned(),
+                        description: "color of the sheen as RGB vector [r,g,b].".to_owned(),
+                        default: Some("white".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "sheen_roughness".to_owned(),
+                        description: "how far from grazing angles the sheen reaches.".to_owned(),
+                        default: Some("0.5".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "pbr(base_color=[1, 0.78, 0.34], metallic=1, roughness=0.3);".to_owned(),
+                    "pbr(base_color=[0.8, 0.1, 0.1], roughness=0.2);".to_owned(),
+                    "pbr(transmission=1, roughness=0, ior=1.5);".to_owned(),
+                    "pbr(base_color=[0.1, 0.2, 0.6], sheen=1);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "sheen",
+            ModuleDocs {
+                description: "Creates a velvet-like material for cloth, a diffuse base whose fibers catch the light at grazing angles."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "c".to_owned(),
+                        description: "base color as RGB vector [r,g,b], single grayscale value, or texture object.".to_owned(),
+                        default: Some("white".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "sheen_color".to_owned(),
+                        description: "color of the light reflected by the fibers as RGB vector [r,g,b].".to_owned(),
+                        default: Some("white".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "roughness".to_owned(),
+                        description: "how far from grazing angles the sheen reaches.".to_owned(),
+                        default: Some("0.5".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "sheen([0.4, 0.05, 0.1]);".to_owned(),
+                    "sheen(c=[0.4, 0.05, 0.1], sheen_color=[1, 0.6, 0.7], roughness=0.3);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "toon",
+            ModuleDocs {
+                description: "Creates a cel shaded material for illustrative renders, with light falling off in flat bands and an optional black outline around the silhouette."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "c".to_owned(),
+                        description: "color as RGB vector [r,g,b], single grayscale value, or texture object.".to_owned(),
+                        default: Some("white".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "bands".to_owned(),
+                        description: "number of brightness levels.".to_owned(),
+                        default: Some("3".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "outline".to_owned(),
+                        description: "width of the outline from 0 to 1, as the cosine of the angle between the surface and the view below which it is drawn black.".to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "toon([0.9, 0.3, 0.2]);".to_owned(),
+                    "toon(c=[0.2, 0.5, 0.9], bands=4, outline=0.3);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "apply",
+            ModuleDocs {
+                description: "Applies a material stored in a variable to its children. The material modules can also be called as functions, which returns the material instead of applying it."
+                    .to_owned(),
+                arguments: vec![ModuleDocsArguments {
+                    name: "m".to_owned(),
+                    description: "material created by calling a material module as a function.".to_owned(),
+                    default: None,
+                }],
+                examples: vec![
+                    "m = metal([0.9, 0.9, 0.9], 0.05); apply(m) sphere(1);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "default_material",
+            ModuleDocs {
+                description: "Sets the material of objects without one for the rest of the file, replacing the yellow default."
+                    .to_owned(),
+                arguments: vec![ModuleDocsArguments {
+                    name: "m".to_owned(),
+                    description: "material created by calling a material module as a function.".to_owned(),
+                    default: None,
+                }],
+                examples: vec![
+                    "default_material(lambertian([0.8, 0.8, 0.8]));".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "reset_material",
+            ModuleDocs {
+                description: "Makes its children ignore the materials they are nested in and use the default material. Without children, restores the yellow default for the rest of the file."
+                    .to_owned(),
+                arguments: vec![],
+                examples: vec![
+                    "metal([0.9, 0.9, 0.9]) { sphere(1); reset_material() cube(1); }".to_owned(),
+                    "reset_material();".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "dielectric",
+            ModuleDocs {
+                description:
+                    "Creates a dielectric (glass-like) material with a given refractive index. Colored glass absorbs light as it travels inside, so thick glass is darker than thin glass."
+                        .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "n".to_owned(),
+                        description: "refractive index of the dielectric material.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "c".to_owned(),
+                        description: "color of the light left after traveling `distance` through the glass, as RGB vector [r,g,b].".to_owned(),
+                        default: Some("clear".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "distance".to_owned(),
+                        description: "distance at which the light is tinted to `c`.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "dielectric(1.5);".to_owned(),
+                    "dielectric(n=1.5);".to_owned(),
+                    "dielectric(1.5, c=[0.2, 0.8, 0.3], distance=2);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "checker",
+            ModuleDocs {
+                description:
+                    "Creates a checker pattern texture for materials. All parameters must be named."
+                        .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "scale".to_owned(),
+                        description: "size of the checker pattern squares.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "even".to_owned(),
+                        description: "color or texture for even squares.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "odd".to_owned(),
+                        description: "color or texture for odd squares.".to_owned(),
+                        default: None,
+                    },
+                ],
+                examples: vec![
+                    "checker(scale=2, even=[1,1,1], odd=[0,0,0]);".to_owned(),
+                    "checker(scale=0.5, even=white, odd=black);".to_owned(),
+                    "checker(scale=1, even=texture1, odd=texture2);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "gradient",
+            ModuleDocs {
+                description: "Creates a texture blending between colors along a line or outward from a point, for backdrops and stylized materials. All parameters must be named."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "stops".to_owned(),
+                        description: "list of [position, color] pairs, positions going from 0 at the start to 1 at the end.".to_owned(),
+                        default: Some("[[0, black], [1, white]]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "type".to_owned(),
+                        description: "\"linear\" or \"radial\".".to_owned(),
+                        default: Some("\"linear\"".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "start".to_owned(),
+                        description: "point where a linear gradient is at position 0.".to_owned(),
+                        default: Some("[0, 0, 0]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "end".to_owned(),
+                        description: "point where a linear gradient is at position 1.".to_owned(),
+                        default: Some("[0, 0, 1]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "center".to_owned(),
+                        description: "center of a radial gradient.".to_owned(),
+                        default: Some("[0, 0, 0]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "radius".to_owned(),
+                        description: "distance from the center where a radial gradient is at position 1.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "gradient(stops=[[0, [1, 0.5, 0]], [1, [0, 0, 0.5]]], end=[0, 0, 10]);".to_owned(),
+                    "gradient(type=\"radial\", radius=5, stops=[[0, [1, 1, 1]], [0.8, [0.2, 0.2, 0.2]]]);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "perlin_turbulence",
+            ModuleDocs {
+                description: "Creates a texture with Perlin turbulence noise effect.".to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "scale".to_owned(),
+                        description: "The scale factor for the Perlin noise pattern.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "turbulence_depth".to_owned(),
+                        description: "The depth/intensity of the turbulence effect.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "perlin_turbulence(1.0, 5);".to_owned(),
+                    "perlin_turbulence(scale=2.0, turbulence_depth=3);".to_owned(),
+                    "perlin_turbulence(0.5, 7);".to_owned(),
                 ],
             },
         );
 
         map.insert(
-            "lambertian",
+            "voronoi",
             ModuleDocs {
-                description: "Creates a Lambertian (diffuse) material with the specified texture."
-                    .to_owned(),
+                description: "Creates a cellular (Worley) noise texture from the distance to random points, one per cell, for cells, cracked ground and stone.".to_owned(),
                 arguments: vec![
                     ModuleDocsArguments {
-                        name: "t".to_owned(),
-                        description: "texture for the Lambertian material. Can be a color value or texture object."
-                            .to_owned(),
-                        default: None,
+                        name: "scale".to_owned(),
+                        description: "number of cells per unit.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "feature".to_owned(),
+                        description: "\"f1\" for the distance to the nearest point, \"f2\" for the second nearest, \"f2-f1\" for lines along the cell borders.".to_owned(),
+                        default: Some("\"f1\"".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "metric".to_owned(),
+                        description: "how distances are measured: \"euclidean\" for round cells, \"manhattan\" for diamonds, \"chebyshev\" for squares.".to_owned(),
+                        default: Some("\"euclidean\"".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "low".to_owned(),
+                        description: "color at distance 0.".to_owned(),
+                        default: Some("black".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "high".to_owned(),
+                        description: "color at distance 1 and beyond.".to_owned(),
+                        default: Some("white".to_owned()),
                     },
                 ],
                 examples: vec![
-                    "lambertian([0.5, 0.5, 0.5]);".to_owned(),
-                    "lambertian(checker_texture);".to_owned(),
+                    "voronoi(2);".to_owned(),
+                    "voronoi(0.5, feature=\"f2-f1\", low=[0.1, 0.1, 0.1], high=[0.6, 0.55, 0.5]);".to_owned(),
                 ],
             },
         );
 
         map.insert(
-            "metal",
+            "image",
             ModuleDocs {
-                description: "Creates a metal material with configurable color and fuzziness for reflections."
-                    .to_owned(),
+                description: "Creates a image texture from a file.".to_owned(),
                 arguments: vec![
                     ModuleDocsArguments {
-                        name: "c".to_owned(),
-                        description: "metal color as RGB vector [r,g,b] with values 0-1, or single grayscale value."
-                            .to_owned(),
-                        default: Some("white".to_owned()),
+                        name: "filename".to_owned(),
+                        description: "path to the image file to render.".to_owned(),
+                        default: None,
                     },
                     ModuleDocsArguments {
-                        name: "fuzz".to_owned(),
-                        description: "fuzziness factor for reflections (0=perfect mirror, 1=maximum diffusion)."
-                            .to_owned(),
-                        default: Some("0.2".to_owned()),
+                        name: "colorspace".to_owned(),
+                        description: "how pixel values are interpreted: \"srgb\" for ordinary color images, \"linear\" for images that are already linear such as HDR, \"data\" for normal and other non-color maps.".to_owned(),
+                        default: Some("\"srgb\"".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "filter".to_owned(),
+                        description: "\"bilinear\" to blend between neighboring pixels or \"nearest\" for blocky pixels.".to_owned(),
+                        default: Some("\"bilinear\"".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "mipmap_distance".to_owned(),
+                        description: "distance beyond which smaller, prefiltered copies of the image are used, halving in size each time the distance doubles, to avoid shimmering on distant surfaces. Unset to always use the full image.".to_owned(),
+                        default: None,
                     },
                 ],
                 examples: vec![
-                    "metal([0.8, 0.8, 0.8]);".to_owned(),
-                    "metal([0.8, 0.6, 0.2], 0.3);".to_owned(),
-                    "metal([0.8, 0.8, 0.8], fuzz=0.1);".to_owned(),
-                    "metal(0.7);".to_owned(),
+                    "image(\"photo.png\");".to_owned(),
+                    "image(\"n.png\", colorspace=\"linear\");".to_owned(),
+                    "image(\"pixel_art.png\", filter=\"nearest\");".to_owned(),
+                    "image(\"tiles.png\", mipmap_distance=20);".to_owned(),
                 ],
             },
         );
 
         map.insert(
-            "dielectric",
+            "triplanar",
             ModuleDocs {
-                description:
-                    "Creates a dielectric (glass-like) material with a given refractive index."
-                        .to_owned(),
-                arguments: vec![ModuleDocsArguments {
-                    name: "n".to_owned(),
-                    description: "refractive index of the dielectric material.".to_owned(),
-                    default: None,
-                }],
+                description: "Projects a texture along the X, Y and Z axes and blends the projections by the direction the surface faces, to texture boxes and meshes without stretched or missing UVs.".to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "t".to_owned(),
+                        description: "texture or color to project.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "scale".to_owned(),
+                        description: "size covered by one repeat of the texture, must be positive.".to_owned(),
+                        default: Some("1".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "sharpness".to_owned(),
+                        description: "how quickly the projections fade into each other where the surface turns, higher values give narrower seams.".to_owned(),
+                        default: Some("4".to_owned()),
+                    },
+                ],
                 examples: vec![
-                    "dielectric(1.5);".to_owned(),
-                    "dielectric(n=1.5);".to_owned(),
+                    "triplanar(image(\"bricks.png\"), scale=5);".to_owned(),
+                    "lambertian(t=triplanar(image(\"wood.jpg\"), sharpness=8)) cube(10);".to_owned(),
                 ],
             },
         );
 
         map.insert(
-            "checker",
+            "surface",
             ModuleDocs {
-                description:
-                    "Creates a checker pattern texture for materials. All parameters must be named."
-                        .to_owned(),
+                description: "Creates a height map surface from the brightness of an image. Each pixel is one unit apart and heights range from 0 (black) to 100 (white).".to_owned(),
                 arguments: vec![
                     ModuleDocsArguments {
-                        name: "scale".to_owned(),
-                        description: "size of the checker pattern squares.".to_owned(),
-                        default: Some("1".to_owned()),
+                        name: "file".to_owned(),
+                        description: "image file to read the heights from.".to_owned(),
+                        default: None,
                     },
                     ModuleDocsArguments {
-                        name: "even".to_owned(),
-                        description: "color or texture for even squares.".to_owned(),
-                        default: None,
+                        name: "center".to_owned(),
+                        description: "whether to center the surface on the x and y axes.".to_owned(),
+                        default: Some("false".to_owned()),
                     },
                     ModuleDocsArguments {
-                        name: "odd".to_owned(),
-                        description: "color or texture for odd squares.".to_owned(),
-                        default: None,
+                        name: "invert".to_owned(),
+                        description: "whether dark pixels are high and bright pixels low.".to_owned(),
+                        default: Some("false".to_owned()),
                     },
                 ],
                 examples: vec![
-                    "checker(scale=2, even=[1,1,1], odd=[0,0,0]);".to_owned(),
-                    "checker(scale=0.5, even=white, odd=black);".to_owned(),
-                    "checker(scale=1, even=texture1, odd=texture2);".to_owned(),
+                    "surface(file = \"terrain.png\");".to_owned(),
+                    "scale([1, 1, 0.1]) surface(file = \"terrain.png\", center = true);".to_owned(),
                 ],
             },
         );
 
         map.insert(
-            "perlin_turbulence",
+            "import",
             ModuleDocs {
-                description: "Creates a texture with Perlin turbulence noise effect.".to_owned(),
+                description: "Imports an STL or OBJ mesh uploaded to the project. Meshes are converted when uploaded, so importing them again is fast.".to_owned(),
                 arguments: vec![
                     ModuleDocsArguments {
-                        name: "scale".to_owned(),
-                        description: "The scale factor for the Perlin noise pattern.".to_owned(),
-                        default: Some("1".to_owned()),
+                        name: "file".to_owned(),
+                        description: "mesh file to import.".to_owned(),
+                        default: None,
                     },
                     ModuleDocsArguments {
-                        name: "turbulence_depth".to_owned(),
-                        description: "The depth/intensity of the turbulence effect.".to_owned(),
-                        default: Some("1".to_owned()),
+                        name: "convexity".to_owned(),
+                        description: "ignored, accepted for compatibility with OpenSCAD.".to_owned(),
+                        default: None,
                     },
                 ],
                 examples: vec![
-                    "perlin_turbulence(1.0, 5);".to_owned(),
-                    "perlin_turbulence(scale=2.0, turbulence_depth=3);".to_owned(),
-                    "perlin_turbulence(0.5, 7);".to_owned(),
+                    "import(\"teapot.stl\");".to_owned(),
+                    "metal([0.8, 0.8, 0.8], 0.2) import(file = \"part.obj\");".to_owned(),
                 ],
             },
         );
 
-        map.insert(
-            "image",
-            ModuleDocs {
-                description: "Creates a image texture from a file.".to_owned(),
-                arguments: vec![ModuleDocsArguments {
-                    name: "filename".to_owned(),
-                    description: "path to the image file to render.".to_owned(),
-                    default: None,
-                }],
-                examples: vec!["image(\"photo.png\");".to_owned()],
-            },
-        );
-
         map.insert(
             "quad",
             ModuleDocs {
@@ -326,11 +998,24 @@ pub(crate) static BUILTIN_MODULE_DOCS: LazyLock<HashMap<&'static str, ModuleDocs
                         description: "circle diameter.".to_owned(),
                         default: None,
                     },
+                    ModuleDocsArguments {
+                        name: "inner_r".to_owned(),
+                        description: "radius of a hole in the middle, making a ring.".to_owned(),
+                        default: Some("0".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "angle".to_owned(),
+                        description:
+                            "degrees of the circle to keep, starting at +X and turning towards +Y."
+                                .to_owned(),
+                        default: Some("360".to_owned()),
+                    },
                 ],
                 examples: vec![
                     "circle(10);".to_owned(),
                     "circle(r=10);".to_owned(),
                     "circle(d=20);".to_owned(),
+                    "circle(r=10, inner_r=6, angle=270);".to_owned(),
                 ],
             },
         );
@@ -471,6 +1156,38 @@ pub(crate) static BUILTIN_MODULE_DOCS: LazyLock<HashMap<&'static str, ModuleDocs
             },
         );
 
+        map.insert(
+            "moving_sphere",
+            ModuleDocs {
+                description: "Creates a sphere that moves from one point to another while the camera shutter is open, producing motion blur.".to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "from".to_owned(),
+                        description: "center of the sphere when the shutter opens.".to_owned(),
+                        default: Some("[0, 0, 0]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "to".to_owned(),
+                        description: "center of the sphere when the shutter closes.".to_owned(),
+                        default: Some("[0, 0, 0]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "r".to_owned(),
+                        description: "sphere radius.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "d".to_owned(),
+                        description: "sphere diameter.".to_owned(),
+                        default: None,
+                    },
+                ],
+                examples: vec![
+                    "moving_sphere(from=[0, 0, 0], to=[0, 0, 0.5], r=0.2);".to_owned(),
+                ],
+            },
+        );
+
         map.insert(
             "cylinder",
             ModuleDocs {
@@ -516,12 +1233,24 @@ pub(crate) static BUILTIN_MODULE_DOCS: LazyLock<HashMap<&'static str, ModuleDocs
                         description: "if true, centers cylinder vertically.".to_owned(),
                         default: Some("false".to_owned()),
                     },
+                    ModuleDocsArguments {
+                        name: "capped".to_owned(),
+                        description: "if false, leaves both ends open like a tube.".to_owned(),
+                        default: Some("true".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "smooth".to_owned(),
+                        description: "if false, shades the side as flat faces, as many as OpenSCAD draws from $fn, $fa and $fs.".to_owned(),
+                        default: Some("true".to_owned()),
+                    },
                 ],
                 examples: vec![
                     "cylinder(h=10, r=5);".to_owned(),
                     "cylinder(h=10, d=10);".to_owned(),
                     "cylinder(h=10, r1=5, r2=2);".to_owned(),
                     "cylinder(h=10, r=5, center=true);".to_owned(),
+                    "cylinder(h=10, r=5, capped=false);".to_owned(),
+                    "cylinder(h=10, r=5, smooth=false, $fn=6);".to_owned(),
                 ],
             },
         );
@@ -572,6 +1301,59 @@ pub(crate) static BUILTIN_MODULE_DOCS: LazyLock<HashMap<&'static str, ModuleDocs
             },
         );
 
+        map.insert(
+            "animate",
+            ModuleDocs {
+                description: "Moves and rotates its child elements while the camera shutter is open, producing motion blur.".to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "v".to_owned(),
+                        description: "distance moved during the exposure [x, y, z].".to_owned(),
+                        default: Some("[0, 0, 0]".to_owned()),
+                    },
+                    ModuleDocsArguments {
+                        name: "a".to_owned(),
+                        description: "rotation in degrees during the exposure [x, y, z], applied around the origin before moving.".to_owned(),
+                        default: None,
+                    },
+                ],
+                examples: vec![
+                    "animate([0, 0, 0.5]) sphere(r=0.2);".to_owned(),
+                    "animate(a = [0, 0, 15]) cube(1);".to_owned(),
+                    "animate(v = [x, y, z], a = [x, y, z]) { ... }".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "union",
+            ModuleDocs {
+                description: "Creates a union of all its child nodes, the sum of all children."
+                    .to_owned(),
+                arguments: vec![],
+                examples: vec!["union() { cube(10); sphere(7); }".to_owned()],
+            },
+        );
+
+        map.insert(
+            "difference",
+            ModuleDocs {
+                description: "Subtracts the 2nd (and all further) child nodes from the first one."
+                    .to_owned(),
+                arguments: vec![],
+                examples: vec!["difference() { cube(10, center=true); sphere(7); }".to_owned()],
+            },
+        );
+
+        map.insert(
+            "intersection",
+            ModuleDocs {
+                description: "Creates the intersection of all child nodes, keeping only the overlapping portion.".to_owned(),
+                arguments: vec![],
+                examples: vec!["intersection() { cube(10, center=true); sphere(7); }".to_owned()],
+            },
+        );
+
         map.insert(
             "rotate",
             ModuleDocs {
@@ -598,6 +1380,32 @@ pub(crate) static BUILTIN_MODULE_DOCS: LazyLock<HashMap<&'static str, ModuleDocs
             },
         );
 
+        map.insert(
+            "rotate_about",
+            ModuleDocs {
+                description: "Rotates its child elements around a point rather than the origin."
+                    .to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "a".to_owned(),
+                        description:
+                            "angle in degrees around the Z axis, or [x, y, z] for rotation around each axis."
+                                .to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "pt".to_owned(),
+                        description: "point [x, y, z] to rotate around.".to_owned(),
+                        default: Some("[0, 0, 0]".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "rotate_about([0, 0, 45], [10, 0, 0]) { ... }".to_owned(),
+                    "rotate_about(a=90, pt=[5, 5, 0]) { ... }".to_owned(),
+                ],
+            },
+        );
+
         map.insert(
             "scale",
             ModuleDocs {
@@ -884,6 +1692,81 @@ pub(crate) static BUILTIN_MODULE_DOCS: LazyLock<HashMap<&'static str, ModuleDocs
             },
         );
 
+        // Other
+        map.insert(
+            "let",
+            ModuleDocs {
+                description: "Sets variables for its children only. Each argument can use the ones before it. The deprecated assign() is read as let().".to_owned(),
+                arguments: vec![],
+                examples: vec![
+                    "let(r=5, h=r * 2) cylinder(r=r, h=h);".to_owned(),
+                    "for (i = [0:4]) let(x=i * 10) translate([x, 0, 0]) sphere(r=2);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "bbox",
+            ModuleDocs {
+                description: "Measures the bounding box of its children, which are rendered as usual, as [[xmin, ymin, zmin], [xmax, ymax, zmax]] rounded to thousandths. The box is stored in a variable for the statements after it, or echoed when no name is given.".to_owned(),
+                arguments: vec![ModuleDocsArguments {
+                    name: "name".to_owned(),
+                    description: "name of the variable to store the bounding box in.".to_owned(),
+                    default: None,
+                }],
+                examples: vec![
+                    "bbox() translate([0, 0, 5]) cube(10);".to_owned(),
+                    "bbox(\"part\") cylinder(r=5, h=20); echo(part[1][2] - part[0][2]);".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "measure_distance",
+            ModuleDocs {
+                description: "Function returning the distance between two points.".to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "a".to_owned(),
+                        description: "first point as [x, y] or [x, y, z].".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "b".to_owned(),
+                        description: "second point, with as many coordinates as a.".to_owned(),
+                        default: None,
+                    },
+                ],
+                examples: vec![
+                    "echo(measure_distance([0, 0, 0], [3, 4, 0]));".to_owned(),
+                    "bbox(\"b\") sphere(5); echo(measure_distance(b[0], b[1]));".to_owned(),
+                ],
+            },
+        );
+
+        map.insert(
+            "keyframes",
+            ModuleDocs {
+                description: "Function returning the value of an animation track at time t, interpolating linearly between the keyframes around it. Numbers and lists of numbers are interpolated, other values hold until the next keyframe.".to_owned(),
+                arguments: vec![
+                    ModuleDocsArguments {
+                        name: "frames".to_owned(),
+                        description: "list of [time, value] keyframes sorted by time.".to_owned(),
+                        default: None,
+                    },
+                    ModuleDocsArguments {
+                        name: "t".to_owned(),
+                        description: "time to evaluate the track at, values before the first and after the last keyframe hold.".to_owned(),
+                        default: Some("$t".to_owned()),
+                    },
+                ],
+                examples: vec![
+                    "translate(keyframes([[0, [0, 0, 0]], [1, [0, 10, 0]]])) sphere(1);".to_owned(),
+                    "rotate([0, 0, keyframes([[0, 0], [0.5, 90], [1, 0]])]) cube(5);".to_owned(),
+                ],
+            },
+        );
+
         map
     },
 );
@@ -1,5 +1,9 @@
-use crate::Result;
+use std::sync::Arc;
+
+use caustic_core::utils::math;
+
 use crate::interpreter::Interpreter;
+use crate::{Message, MessageLevel, Position, Result};
 
 use crate::{
     parser::{BinaryOperator, Expr, ExprWithPosition, UnaryOperator},
@@ -9,6 +13,7 @@ use crate::{
 impl Interpreter {
     pub(super) fn expr_to_value(&mut self, expr: &ExprWithPosition) -> Result<Value> {
         let position = &expr.position;
+        self.count_operation(position)?;
         Ok(match &expr.item {
             Expr::Number(number) => Value::Number(*number),
             Expr::String(str) => Value::String(str.clone()),
@@ -20,9 +25,11 @@ impl Interpreter {
             Expr::True => Value::Boolean(true),
             Expr::False => Value::Boolean(false),
             Expr::Binary { operator, lhs, rhs } => {
-                self.evaluate_binary_expression(operator, lhs, rhs)?
+                self.evaluate_binary_expression(operator, lhs, rhs, position)?
+            }
+            Expr::Unary { operator, rhs } => {
+                self.evaluate_unary_expression(operator, rhs, position)?
             }
-            Expr::Unary { operator, rhs } => self.evaluate_unary_expression(operator, rhs)?,
             Expr::FunctionCall { name, arguments } => {
                 self.evaluate_function_call(name, arguments, position)?
             }
@@ -68,10 +75,11 @@ impl Interpreter {
         operator: &BinaryOperator,
         lhs: &ExprWithPosition,
         rhs: &ExprWithPosition,
+        position: &Position,
     ) -> Result<Value> {
         let lhs = self.expr_to_value(lhs)?;
         let rhs = self.expr_to_value(rhs)?;
-        self.evaluate_binary_expression_values(operator, &lhs, &rhs)
+        self.evaluate_binary_expression_values(operator, &lhs, &rhs, position)
     }
 
     fn evaluate_binary_expression_values(
@@ -79,174 +87,76 @@ impl Interpreter {
         operator: &BinaryOperator,
         lhs: &Value,
         rhs: &Value,
+        position: &Position,
     ) -> Result<Value> {
         match operator {
-            BinaryOperator::Exponentiation => {
-                self.evaluate_binary_expression_exponentiation(lhs, rhs)
+            BinaryOperator::Exponentiation
+            | BinaryOperator::Modulus
+            | BinaryOperator::Add
+            | BinaryOperator::Subtract
+            | BinaryOperator::Multiply
+            | BinaryOperator::Divide => {
+                self.evaluate_binary_expression_arithmetic(operator, lhs, rhs, position)
+            }
+            BinaryOperator::LessThan
+            | BinaryOperator::LessThanEqual
+            | BinaryOperator::GreaterThan
+            | BinaryOperator::GreaterThanEqual => {
+                self.evaluate_binary_expression_comparison(operator, lhs, rhs, position)
             }
-            BinaryOperator::Modulus => self.evaluate_binary_expression_modulus(lhs, rhs),
-            BinaryOperator::Add => self.evaluate_binary_expression_add(lhs, rhs),
-            BinaryOperator::Subtract => self.evaluate_binary_expression_subtract(lhs, rhs),
-            BinaryOperator::Multiply => self.evaluate_binary_expression_multiply(lhs, rhs),
-            BinaryOperator::Divide => self.evaluate_binary_expression_divide(lhs, rhs),
-            BinaryOperator::LessThan => self.evaluate_binary_expression_less_than(lhs, rhs),
-            BinaryOperator::LessThanEqual => {
-                self.evaluate_binary_expression_less_than_equal(lhs, rhs)
+            BinaryOperator::EqualEqual => {
+                self.evaluate_binary_expression_equal_equal(lhs, rhs, position)
             }
-            BinaryOperator::GreaterThan => self.evaluate_binary_expression_greater_than(lhs, rhs),
-            BinaryOperator::GreaterThanEqual => {
-                self.evaluate_binary_expression_greater_than_equal(lhs, rhs)
+            BinaryOperator::NotEqual => {
+                self.evaluate_binary_expression_not_equals(lhs, rhs, position)
             }
-            BinaryOperator::EqualEqual => self.evaluate_binary_expression_equal_equal(lhs, rhs),
-            BinaryOperator::NotEqual => self.evaluate_binary_expression_not_equals(lhs, rhs),
             BinaryOperator::And => Ok(Value::Boolean(lhs.is_truthy() && rhs.is_truthy())),
             BinaryOperator::Or => Ok(Value::Boolean(lhs.is_truthy() || rhs.is_truthy())),
         }
     }
 
-    fn evaluate_binary_expression_exponentiation(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => {
-                self.evaluate_binary_expression_exponentiation_number_value(*lhs, rhs)
-            }
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_exponentiation_number_value(
-        &self,
-        lhs: f64,
-        rhs: &Value,
-    ) -> Result<Value> {
-        match rhs {
-            Value::Number(rhs) => Ok(Value::Number(lhs.powf(*rhs))),
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_modulus(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => self.evaluate_binary_expression_modulus_number_value(*lhs, rhs),
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_modulus_number_value(
-        &self,
-        lhs: f64,
-        rhs: &Value,
-    ) -> Result<Value> {
-        match rhs {
-            Value::Number(rhs) => Ok(Value::Number(lhs % rhs)),
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_add(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => self.evaluate_binary_expression_add_number_value(*lhs, rhs),
-            Value::Vector { items: lhs } => {
-                self.evaluate_binary_expression_vector_value(&BinaryOperator::Add, lhs, rhs)
-            }
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_add_number_value(&self, lhs: f64, rhs: &Value) -> Result<Value> {
-        match rhs {
-            Value::Number(rhs) => Ok(Value::Number(lhs + rhs)),
-            Value::Vector { items: rhs } => {
-                let items: Result<Vec<Value>> = rhs
-                    .iter()
-                    .map(|rhs_v| self.evaluate_binary_expression_add_number_value(lhs, rhs_v))
-                    .collect();
-                Ok(Value::Vector { items: items? })
-            }
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_subtract(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => self.evaluate_binary_expression_subtract_number_value(*lhs, rhs),
-            Value::Vector { items: lhs } => {
-                self.evaluate_binary_expression_vector_value(&BinaryOperator::Subtract, lhs, rhs)
-            }
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_subtract_number_value(
-        &self,
-        lhs: f64,
-        rhs: &Value,
-    ) -> Result<Value> {
-        match rhs {
-            Value::Number(rhs) => Ok(Value::Number(lhs - rhs)),
-            Value::Vector { items: rhs } => {
-                let items: Result<Vec<Value>> = rhs
-                    .iter()
-                    .map(|rhs_v| self.evaluate_binary_expression_subtract_number_value(lhs, rhs_v))
-                    .collect();
-                Ok(Value::Vector { items: items? })
-            }
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_multiply(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => self.evaluate_binary_expression_multiply_number_value(*lhs, rhs),
-            Value::Vector { items: lhs } => {
-                self.evaluate_binary_expression_vector_value(&BinaryOperator::Multiply, lhs, rhs)
-            }
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_multiply_number_value(
+    /// Applies an arithmetic operator to numbers, and item by item to vectors
+    /// and a vector and a number.
+    fn evaluate_binary_expression_arithmetic(
         &self,
-        lhs: f64,
+        operator: &BinaryOperator,
+        lhs: &Value,
         rhs: &Value,
+        position: &Position,
     ) -> Result<Value> {
-        match rhs {
-            Value::Number(rhs) => Ok(Value::Number(lhs * rhs)),
-            Value::Vector { items: rhs } => {
-                let items: Result<Vec<Value>> = rhs
-                    .iter()
-                    .map(|rhs_v| self.evaluate_binary_expression_multiply_number_value(lhs, rhs_v))
-                    .collect();
-                Ok(Value::Vector { items: items? })
-            }
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_divide(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => self.evaluate_binary_expression_divide_number_value(*lhs, rhs),
-            Value::Vector { items: lhs } => {
-                self.evaluate_binary_expression_vector_value(&BinaryOperator::Divide, lhs, rhs)
+        match (lhs, rhs) {
+            (Value::Number(a), Value::Number(b)) => Ok(Value::Number(match operator {
+                BinaryOperator::Exponentiation => math::powf(*a, *b),
+                BinaryOperator::Modulus => a % b,
+                BinaryOperator::Add => a + b,
+                BinaryOperator::Subtract => a - b,
+                BinaryOperator::Multiply => a * b,
+                BinaryOperator::Divide => a / b,
+                _ => return unsupported_operands(operator, lhs, rhs, position),
+            })),
+            (Value::Vector { items: lhs_items }, _)
+                if !matches!(
+                    operator,
+                    BinaryOperator::Exponentiation | BinaryOperator::Modulus
+                ) =>
+            {
+                self.evaluate_binary_expression_vector_value(operator, lhs_items, rhs, position)
             }
-            _ => todo!("unsupported"),
-        }
-    }
-
-    fn evaluate_binary_expression_divide_number_value(
-        &self,
-        lhs: f64,
-        rhs: &Value,
-    ) -> Result<Value> {
-        match rhs {
-            Value::Number(rhs) => Ok(Value::Number(lhs / rhs)),
-            Value::Vector { items: rhs } => {
-                let items: Result<Vec<Value>> = rhs
+            (Value::Number(_), Value::Vector { items: rhs_items })
+                if !matches!(
+                    operator,
+                    BinaryOperator::Exponentiation | BinaryOperator::Modulus
+                ) =>
+            {
+                let items: Result<Vec<Value>> = rhs_items
                     .iter()
-                    .map(|rhs_v| self.evaluate_binary_expression_divide_number_value(lhs, rhs_v))
+                    .map(|rhs_v| {
+                        self.evaluate_binary_expression_arithmetic(operator, lhs, rhs_v, position)
+                    })
                     .collect();
                 Ok(Value::Vector { items: items? })
             }
-            _ => todo!("unsupported"),
+            _ => unsupported_operands(operator, lhs, rhs, position),
         }
     }
 
@@ -255,6 +165,7 @@ impl Interpreter {
         operator: &BinaryOperator,
         lhs_items: &[Value],
         rhs: &Value,
+        position: &Position,
     ) -> Result<Value> {
         match rhs {
             Value::Number(rhs) => {
@@ -265,197 +176,105 @@ impl Interpreter {
                             operator,
                             lhs_v,
                             &Value::Number(*rhs),
+                            position,
                         )
                     })
                     .collect();
                 Ok(Value::Vector { items: items? })
             }
             Value::Vector { items: rhs_items } => {
-                self.eval_vector_vector(operator, lhs_items, rhs_items)
+                self.eval_vector_vector(operator, lhs_items, rhs_items, position)
             }
-            _ => todo!("unsupported"),
+            _ => unsupported_operands(
+                operator,
+                &Value::Vector {
+                    items: lhs_items.to_vec(),
+                },
+                rhs,
+                position,
+            ),
         }
     }
 
-    fn evaluate_binary_expression_less_than(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => {
-                if let Value::Number(rhs) = rhs {
-                    Ok(Value::Boolean(lhs < rhs))
-                } else {
-                    Ok(Value::Boolean(false))
-                }
-            }
-            Value::String(lhs) => todo!("{lhs} < {rhs}"),
-            Value::Vector { items: lhs_items } => {
-                if let Value::Vector { items: rhs_items } = rhs {
-                    self.eval_vector_vector(&BinaryOperator::LessThan, lhs_items, rhs_items)
-                } else {
-                    Ok(Value::Boolean(false))
-                }
-            }
-            Value::Boolean(lhs) => todo!("{lhs} < {rhs}"),
-            Value::Texture(lhs) => todo!("{lhs:?} < {rhs}"),
-            Value::Range {
-                start: lhs_start,
-                end: lhs_end,
-                increment: lhs_increment,
-            } => todo!("{lhs_start} {lhs_end} {lhs_increment:?} < {rhs}"),
-            Value::Undef => todo!("{lhs} < {rhs}"),
-            Value::FunctionRef { function_name } => todo!("{lhs} < {function_name}"),
-        }
-    }
-
-    fn evaluate_binary_expression_less_than_equal(
+    /// Orders numbers, strings, and vectors item by item. Comparing a number
+    /// or string with another type is false.
+    fn evaluate_binary_expression_comparison(
         &self,
+        operator: &BinaryOperator,
         lhs: &Value,
         rhs: &Value,
+        position: &Position,
     ) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => {
-                if let Value::Number(rhs) = rhs {
-                    Ok(Value::Boolean(lhs <= rhs))
-                } else {
-                    Ok(Value::Boolean(false))
-                }
-            }
-            Value::String(lhs) => todo!("{lhs} <= {rhs}"),
-            Value::Vector { items: lhs_items } => {
-                if let Value::Vector { items: rhs_items } = rhs {
-                    self.eval_vector_vector(&BinaryOperator::LessThanEqual, lhs_items, rhs_items)
-                } else {
-                    Ok(Value::Boolean(false))
-                }
+        let ordering = match (lhs, rhs) {
+            (Value::Number(lhs), Value::Number(rhs)) => lhs.partial_cmp(rhs),
+            (Value::String(lhs), Value::String(rhs)) => Some(lhs.cmp(rhs)),
+            (Value::Vector { items: lhs_items }, Value::Vector { items: rhs_items }) => {
+                return self.eval_vector_vector(operator, lhs_items, rhs_items, position);
             }
-            Value::Boolean(lhs) => todo!("{lhs} <= {rhs}"),
-            Value::Texture(lhs) => todo!("{lhs:?} <= {rhs}"),
-            Value::Range {
-                start: lhs_start,
-                end: lhs_end,
-                increment: lhs_increment,
-            } => todo!("{lhs_start} {lhs_end} {lhs_increment:?} <= {rhs}"),
-            Value::Undef => todo!("{lhs} <= {rhs}"),
-            Value::FunctionRef { function_name } => todo!("{lhs} <= {function_name}"),
-        }
-    }
-
-    fn evaluate_binary_expression_greater_than(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => {
-                if let Value::Number(rhs) = rhs {
-                    Ok(Value::Boolean(lhs > rhs))
-                } else {
-                    Ok(Value::Boolean(false))
-                }
-            }
-            Value::String(lhs) => todo!("{lhs} > {rhs}"),
-            Value::Vector { items: lhs_items } => {
-                if let Value::Vector { items: rhs_items } = rhs {
-                    self.eval_vector_vector(&BinaryOperator::GreaterThan, lhs_items, rhs_items)
-                } else {
-                    Ok(Value::Boolean(false))
-                }
-            }
-            Value::Boolean(lhs) => todo!("{lhs} > {rhs}"),
-            Value::Texture(lhs) => todo!("{lhs:?} > {rhs}"),
-            Value::Range {
-                start: lhs_start,
-                end: lhs_end,
-                increment: lhs_increment,
-            } => todo!("{lhs_start} {lhs_end} {lhs_increment:?} > {rhs}"),
-            Value::Undef => todo!("{lhs} > {rhs}"),
-            Value::FunctionRef { function_name } => todo!("{lhs} > {function_name}"),
-        }
+            (Value::Number(_) | Value::String(_) | Value::Vector { .. }, _) => None,
+            _ => return unsupported_operands(operator, lhs, rhs, position),
+        };
+        Ok(Value::Boolean(ordering.is_some_and(
+            |ordering| match operator {
+                BinaryOperator::LessThan => ordering.is_lt(),
+                BinaryOperator::LessThanEqual => ordering.is_le(),
+                BinaryOperator::GreaterThan => ordering.is_gt(),
+                _ => ordering.is_ge(),
+            },
+        )))
     }
 
-    fn evaluate_binary_expression_greater_than_equal(
+    fn evaluate_binary_expression_equal_equal(
         &self,
         lhs: &Value,
         rhs: &Value,
+        position: &Position,
     ) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => {
-                if let Value::Number(rhs) = rhs {
-                    Ok(Value::Boolean(lhs >= rhs))
-                } else {
-                    Ok(Value::Boolean(false))
-                }
+        let equal = match (lhs, rhs) {
+            (Value::Vector { items: lhs_items }, Value::Vector { items: rhs_items }) => {
+                return self.eval_vector_vector(
+                    &BinaryOperator::EqualEqual,
+                    lhs_items,
+                    rhs_items,
+                    position,
+                );
             }
-            Value::String(lhs) => todo!("{lhs} >= {rhs}"),
-            Value::Vector { items: lhs_items } => {
-                if let Value::Vector { items: rhs_items } = rhs {
-                    self.eval_vector_vector(&BinaryOperator::GreaterThanEqual, lhs_items, rhs_items)
-                } else {
-                    Ok(Value::Boolean(false))
-                }
+            (Value::Number(lhs), Value::Number(rhs)) => lhs == rhs,
+            (Value::String(lhs), Value::String(rhs)) => lhs == rhs,
+            (Value::Boolean(lhs), Value::Boolean(rhs)) => lhs == rhs,
+            (Value::Material(lhs), Value::Material(rhs)) => lhs == rhs,
+            (Value::Texture(lhs), Value::Texture(rhs)) => Arc::ptr_eq(lhs, rhs),
+            (Value::Undef, Value::Undef) => true,
+            (
+                Value::FunctionRef { function_name: lhs },
+                Value::FunctionRef { function_name: rhs },
+            ) => lhs == rhs,
+            (Value::Range { .. }, _) | (_, Value::Range { .. }) => {
+                return unsupported_operands(&BinaryOperator::EqualEqual, lhs, rhs, position);
             }
-            Value::Boolean(lhs) => todo!("{lhs} >= {rhs}"),
-            Value::Texture(lhs) => todo!("{lhs:?} >= {rhs}"),
-            Value::Range {
-                start: lhs_start,
-                end: lhs_end,
-                increment: lhs_increment,
-            } => todo!("{lhs_start} {lhs_end} {lhs_increment:?} >= {rhs}"),
-            Value::Undef => todo!("{lhs} >= {rhs}"),
-            Value::FunctionRef { function_name } => todo!("{lhs} >= {function_name}"),
-        }
-    }
-
-    fn evaluate_binary_expression_equal_equal(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => {
-                if let Value::Number(rhs) = rhs {
-                    Ok(Value::Boolean(lhs == rhs))
-                } else {
-                    Ok(Value::Boolean(false))
-                }
-            }
-            Value::String(lhs) => todo!("{lhs} == {rhs}"),
-            Value::Vector { items: lhs_items } => {
-                if let Value::Vector { items: rhs_items } = rhs {
-                    self.eval_vector_vector(&BinaryOperator::EqualEqual, lhs_items, rhs_items)
-                } else {
-                    Ok(Value::Boolean(false))
-                }
-            }
-            Value::Boolean(lhs) => todo!("{lhs} == {rhs}"),
-            Value::Texture(lhs) => todo!("{lhs:?} == {rhs}"),
-            Value::Range {
-                start: lhs_start,
-                end: lhs_end,
-                increment: lhs_increment,
-            } => todo!("{lhs_start} {lhs_end} {lhs_increment:?} == {rhs}"),
-            Value::Undef => todo!("{lhs} == {rhs}"),
-            Value::FunctionRef { function_name } => todo!("{lhs} == {function_name}"),
-        }
+            _ => false,
+        };
+        Ok(Value::Boolean(equal))
     }
 
-    fn evaluate_binary_expression_not_equals(&self, lhs: &Value, rhs: &Value) -> Result<Value> {
-        match lhs {
-            Value::Number(lhs) => {
-                if let Value::Number(rhs) = rhs {
-                    Ok(Value::Boolean(lhs != rhs))
-                } else {
-                    Ok(Value::Boolean(false))
-                }
+    fn evaluate_binary_expression_not_equals(
+        &self,
+        lhs: &Value,
+        rhs: &Value,
+        position: &Position,
+    ) -> Result<Value> {
+        match (lhs, rhs) {
+            (Value::Vector { items: lhs_items }, Value::Vector { items: rhs_items }) => {
+                self.eval_vector_vector(&BinaryOperator::NotEqual, lhs_items, rhs_items, position)
             }
-            Value::String(lhs) => todo!("{lhs} != {rhs}"),
-            Value::Vector { items: lhs_items } => {
-                if let Value::Vector { items: rhs_items } = rhs {
-                    self.eval_vector_vector(&BinaryOperator::NotEqual, lhs_items, rhs_items)
-                } else {
-                    Ok(Value::Boolean(false))
-                }
+            (Value::Range { .. }, _) | (_, Value::Range { .. }) => {
+                unsupported_operands(&BinaryOperator::NotEqual, lhs, rhs, position)
             }
-            Value::Boolean(lhs) => todo!("{lhs} != {rhs}"),
-            Value::Texture(lhs) => todo!("{lhs:?} != {rhs}"),
-            Value::Range {
-                start: lhs_start,
-                end: lhs_end,
-                increment: lhs_increment,
-            } => todo!("{lhs_start} {lhs_end} {lhs_increment:?} != {rhs}"),
-            Value::Undef => todo!("{lhs} != {rhs}"),
-            Value::FunctionRef { function_name } => todo!("{lhs} != {function_name}"),
+            _ => Ok(Value::Boolean(
+                !self
+                    .evaluate_binary_expression_equal_equal(lhs, rhs, position)?
+                    .is_truthy(),
+            )),
         }
     }
 
@@ -463,25 +282,18 @@ impl Interpreter {
         &mut self,
         operator: &UnaryOperator,
         rhs: &ExprWithPosition,
+        position: &Position,
     ) -> Result<Value> {
         let right = self.expr_to_value(rhs)?;
 
         match operator {
             UnaryOperator::Minus => match right {
                 Value::Number(right) => Ok(Value::Number(-right)),
-                Value::String(_) => todo!(),
-                Value::Vector { items: _items } => todo!(),
-                Value::Boolean(_) => todo!(),
-                Value::Texture(_texture) => todo!(),
-                Value::Range {
-                    start: _start,
-                    end: _end,
-                    increment: _increment,
-                } => todo!(),
-                Value::Undef => todo!(),
-                Value::FunctionRef {
-                    function_name: _function_name,
-                } => todo!(),
+                other => Err(Message {
+                    level: MessageLevel::Error,
+                    message: format!("cannot negate a {}", other.type_name()),
+                    position: position.clone(),
+                }),
             },
             UnaryOperator::Negation => Ok(Value::Boolean(!right.is_truthy())),
         }
@@ -492,6 +304,7 @@ impl Interpreter {
         operator: &BinaryOperator,
         lhs_items: &[Value],
         rhs_items: &[Value],
+        position: &Position,
     ) -> Result<Value> {
         let min_item_len = lhs_items.len().min(rhs_items.len());
         let mut results = vec![];
@@ -499,7 +312,7 @@ impl Interpreter {
         for i in 0..min_item_len {
             let lhs = &lhs_items[i];
             let rhs = &rhs_items[i];
-            let result = self.evaluate_binary_expression_values(operator, lhs, rhs)?;
+            let result = self.evaluate_binary_expression_values(operator, lhs, rhs, position)?;
             results.push(result);
         }
 
@@ -510,3 +323,38 @@ impl Interpreter {
         }
     }
 }
+
+/// Returns the error for an operator applied to values it does not support,
+/// such as adding a string or comparing materials.
+fn unsupported_operands(
+    operator: &BinaryOperator,
+    lhs: &Value,
+    rhs: &Value,
+    position: &Position,
+) -> Result<Value> {
+    let symbol = match operator {
+        BinaryOperator::Exponentiation => "^",
+        BinaryOperator::Modulus => "%",
+        BinaryOperator::Add => "+",
+        BinaryOperator::Subtract => "-",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::LessThan => "<",
+        BinaryOperator::LessThanEqual => "<=",
+        BinaryOperator::GreaterThan => ">",
+        BinaryOperator::GreaterThanEqual => ">=",
+        BinaryOperator::EqualEqual => "==",
+        BinaryOperator::NotEqual => "!=",
+        BinaryOperator::And => "&&",
+        BinaryOperator::Or => "||",
+    };
+    Err(Message {
+        level: MessageLevel::Error,
+        message: format!(
+            "operator {symbol} is not supported between a {} and a {}",
+            lhs.type_name(),
+            rhs.type_name()
+        ),
+        position: position.clone(),
+    })
+}
@@ -1,13 +1,19 @@
 use std::{mem::swap, sync::Arc};
 
 use caustic_core::{
-    Color,
-    texture::{CheckerTexture, ImageTexture, PerlinTurbulenceTexture, SolidColor, Texture},
+    Color, Vector3,
+    texture::{
+        CheckerTexture, ColorSpace, GradientShape, GradientTexture, PerlinTurbulenceTexture,
+        SolidColor, Texture, TextureFilter, TriplanarTexture, VoronoiFeature, VoronoiMetric,
+        VoronoiTexture,
+    },
+    utils::math,
 };
 
 use crate::{
     Message, MessageLevel, Position, Result,
-    interpreter::Interpreter,
+    animation::Track,
+    interpreter::{AssetKind, Interpreter, UNSUPPORTED_FUNCTIONS, image_key},
     parser::CallArgumentWithPosition,
     value::{Value, values_to_numbers},
 };
@@ -21,7 +27,31 @@ impl Interpreter {
     ) -> Result<Value> {
         match name {
             "checker" => self.evaluate_checker(arguments),
+            "gradient" => self.evaluate_gradient(arguments),
             "perlin_turbulence" => self.evaluate_perlin_turbulence(arguments),
+            "voronoi" => self.evaluate_voronoi(arguments),
+            "triplanar" => self.evaluate_triplanar(arguments, position),
+            "lambertian" => self
+                .create_lambertian(arguments)
+                .map(|m| Value::Material(self.scene_material(m, name, position))),
+            "dielectric" => self
+                .create_dielectric(arguments)
+                .map(|m| Value::Material(self.scene_material(m, name, position))),
+            "metal" => self
+                .create_metal(arguments)
+                .map(|m| Value::Material(self.scene_material(m, name, position))),
+            "diffuse_light" => self
+                .create_diffuse_light(arguments)
+                .map(|m| Value::Material(self.scene_material(m, name, position))),
+            "pbr" => self
+                .create_pbr(arguments)
+                .map(|m| Value::Material(self.scene_material(m, name, position))),
+            "sheen" => self
+                .create_sheen(arguments)
+                .map(|m| Value::Material(self.scene_material(m, name, position))),
+            "toon" => self
+                .create_toon(arguments)
+                .map(|m| Value::Material(self.scene_material(m, name, position))),
             "concat" => self.evaluate_concat(arguments),
             "lookup" => self.evaluate_lookup(arguments),
             "abs" => self.evaluate_abs(arguments),
@@ -45,6 +75,8 @@ impl Interpreter {
             "max" => self.evaluate_max(arguments),
             "norm" => self.evaluate_norm(arguments),
             "cross" => self.evaluate_cross(arguments, position),
+            "measure_distance" => self.evaluate_measure_distance(arguments, position),
+            "keyframes" => self.evaluate_keyframes(arguments, position),
             "rands" => self.evaluate_rands(arguments),
             "image" => self.evaluate_image(arguments),
             "is_undef" => self.evaluate_is_undef(arguments),
@@ -53,7 +85,14 @@ impl Interpreter {
             "is_string" => self.evaluate_is_string(arguments),
             "is_list" => self.evaluate_is_list(arguments),
             "is_function" => self.evaluate_is_function(arguments),
-            other => self.evaluate_non_built_in(other, arguments),
+            other
+                if UNSUPPORTED_FUNCTIONS.contains(&other)
+                    && !self.functions.contains_key(other) =>
+            {
+                self.record_unsupported(other, position);
+                Ok(Value::Undef)
+            }
+            other => self.evaluate_non_built_in(other, arguments, position),
         }
     }
 
@@ -157,31 +196,31 @@ impl Interpreter {
     }
 
     fn evaluate_sin(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func1(arguments, "degrees", |v| v.to_radians().sin())
+        self.evaluate_math_func1(arguments, "degrees", |v| math::sin(v.to_radians()))
     }
 
     fn evaluate_cos(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func1(arguments, "degrees", |v| v.to_radians().cos())
+        self.evaluate_math_func1(arguments, "degrees", |v| math::cos(v.to_radians()))
     }
 
     fn evaluate_tan(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func1(arguments, "degrees", |v| v.to_radians().tan())
+        self.evaluate_math_func1(arguments, "degrees", |v| math::tan(v.to_radians()))
     }
 
     fn evaluate_asin(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func1(arguments, "x", |v| v.asin().to_degrees())
+        self.evaluate_math_func1(arguments, "x", |v| math::asin(v).to_degrees())
     }
 
     fn evaluate_acos(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func1(arguments, "x", |v| v.acos().to_degrees())
+        self.evaluate_math_func1(arguments, "x", |v| math::acos(v).to_degrees())
     }
 
     fn evaluate_atan(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func1(arguments, "x", |v| v.atan().to_degrees())
+        self.evaluate_math_func1(arguments, "x", |v| math::atan(v).to_degrees())
     }
 
     fn evaluate_atan2(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func2(arguments, "y", "x", |y, x| y.atan2(x).to_degrees())
+        self.evaluate_math_func2(arguments, "y", "x", |y, x| math::atan2(y, x).to_degrees())
     }
 
     fn evaluate_floor(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
@@ -197,16 +236,16 @@ impl Interpreter {
     }
 
     fn evaluate_ln(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func1(arguments, "x", |v| v.ln())
+        self.evaluate_math_func1(arguments, "x", math::ln)
     }
 
     fn evaluate_log(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func1(arguments, "x", |v| v.log10())
+        self.evaluate_math_func1(arguments, "x", math::log10)
     }
 
     fn evaluate_pow(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
         self.evaluate_math_func2(arguments, "base", "exponent", |base, exponent| {
-            base.powf(exponent)
+            math::powf(base, exponent)
         })
     }
 
@@ -215,7 +254,7 @@ impl Interpreter {
     }
 
     fn evaluate_exp(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        self.evaluate_math_func1(arguments, "x", |v| v.exp())
+        self.evaluate_math_func1(arguments, "x", math::exp)
     }
 
     fn evaluate_min(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
@@ -281,7 +320,7 @@ impl Interpreter {
                     message: format!("failed to convert vector element to number: {err:?}"),
                     position: arguments[0].position.clone(),
                 })?;
-                let sum_squared: f64 = numbers.iter().map(|n| n.powf(2.0)).sum();
+                let sum_squared: f64 = numbers.iter().map(|n| math::powf(*n, 2.0)).sum();
                 Ok(Value::Number(sum_squared.sqrt()))
             }
             _ => {
@@ -291,6 +330,84 @@ impl Interpreter {
         })
     }
 
+    /// Returns the distance between two points given as vectors of the same length.
+    fn evaluate_measure_distance(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        position: &Position,
+    ) -> Result<Value> {
+        let arguments = self.convert_args(&["a", "b"], arguments)?;
+
+        let (Some(a), Some(b)) = (arguments.get("a"), arguments.get("b")) else {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "measure_distance requires two points".to_owned(),
+                position: position.clone(),
+            });
+        };
+
+        let points = match (&a.item, &b.item) {
+            (Value::Vector { items: a }, Value::Vector { items: b }) => {
+                Some((values_to_numbers(a)?, values_to_numbers(b)?))
+            }
+            _ => None,
+        };
+        match points {
+            Some((a, b)) if a.len() == b.len() => {
+                let sum_squared: f64 = a.iter().zip(&b).map(|(a, b)| math::powi(a - b, 2)).sum();
+                Ok(Value::Number(sum_squared.sqrt()))
+            }
+            _ => Err(Message {
+                level: MessageLevel::Error,
+                message: format!(
+                    "measure_distance expects two points of the same size, found {} and {}",
+                    a.item, b.item
+                ),
+                position: position.clone(),
+            }),
+        }
+    }
+
+    fn evaluate_keyframes(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        position: &Position,
+    ) -> Result<Value> {
+        let arguments = self.convert_args(&["frames", "t"], arguments)?;
+
+        let error = |message: String| Message {
+            level: MessageLevel::Error,
+            message,
+            position: position.clone(),
+        };
+        let Some(Value::Vector { items: frames }) = arguments.get("frames").map(|arg| &arg.item)
+        else {
+            return Err(error(
+                "keyframes requires a list of [time, value] keyframes".to_owned(),
+            ));
+        };
+        let keyframes = frames
+            .iter()
+            .map(|frame| match frame {
+                Value::Vector { items } if items.len() == 2 => {
+                    Ok((items[0].to_number()?, items[1].clone()))
+                }
+                _ => Err(error(format!(
+                    "keyframes expects [time, value] keyframes, found {frame}"
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let track = Track::new(keyframes).map_err(error)?;
+
+        let t = match arguments.get("t") {
+            Some(arg) => arg.item.to_number()?,
+            None => self
+                .get_variable("$t")
+                .map_or(Ok(0.0), |value| value.to_number())?,
+        };
+        Ok(track.value_at(t))
+    }
+
     fn evaluate_cross(
         &mut self,
         arguments: &[CallArgumentWithPosition],
@@ -486,6 +603,122 @@ impl Interpreter {
         ))))
     }
 
+    fn evaluate_gradient(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
+        let arguments = self.convert_args(
+            &["stops", "type", "start", "end", "center", "radius"],
+            arguments,
+        )?;
+
+        let mut stops = vec![(0.0, Color::BLACK), (1.0, Color::WHITE)];
+        if let Some(arg) = arguments.get("stops") {
+            let invalid_stop = || Message {
+                level: MessageLevel::Error,
+                message: "gradient stops must be a list of [position, color] pairs".to_owned(),
+                position: arg.position.clone(),
+            };
+            let Value::Vector { items } = &arg.item else {
+                return Err(invalid_stop());
+            };
+            stops = items
+                .iter()
+                .map(|item| match item {
+                    Value::Vector { items } => match &items[..] {
+                        [Value::Number(position), Value::Vector { items: color }]
+                            if color.len() == 3
+                                && color.iter().all(|c| matches!(c, Value::Number(_))) =>
+                        {
+                            Ok((*position, Value::values_to_color(color)?))
+                        }
+                        _ => Err(invalid_stop()),
+                    },
+                    _ => Err(invalid_stop()),
+                })
+                .collect::<Result<Vec<_>>>()?;
+        }
+
+        let mut radial = false;
+        if let Some(arg) = arguments.get("type") {
+            radial = match arg.item.to_unescaped_string()?.as_str() {
+                "linear" => false,
+                "radial" => true,
+                other => {
+                    return Err(Message {
+                        level: MessageLevel::Error,
+                        message: format!(
+                            "unknown gradient type \"{other}\", expected \"linear\" or \"radial\""
+                        ),
+                        position: arg.position.clone(),
+                    });
+                }
+            };
+        }
+
+        let shape = if radial {
+            let mut center = Vector3::new(0.0, 0.0, 0.0);
+            let mut radius = 1.0;
+            if let Some(arg) = arguments.get("center") {
+                center = arg.item.to_vector3()?;
+            }
+            if let Some(arg) = arguments.get("radius") {
+                radius = arg.item.to_number()?;
+            }
+            GradientShape::Radial { center, radius }
+        } else {
+            let mut start = Vector3::new(0.0, 0.0, 0.0);
+            // [0, 0, 1] in OpenSCAD coordinates, bottom to top
+            let mut end = Vector3::new(0.0, 1.0, 0.0);
+            if let Some(arg) = arguments.get("start") {
+                start = arg.item.to_vector3()?;
+            }
+            if let Some(arg) = arguments.get("end") {
+                end = arg.item.to_vector3()?;
+            }
+            GradientShape::Linear { start, end }
+        };
+
+        Ok(Value::Texture(Arc::new(GradientTexture::new(shape, stops))))
+    }
+
+    fn evaluate_triplanar(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        position: &Position,
+    ) -> Result<Value> {
+        let arguments = self.convert_args(&["t", "scale", "sharpness"], arguments)?;
+
+        let Some(arg) = arguments.get("t") else {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "triplanar requires a texture".to_owned(),
+                position: position.clone(),
+            });
+        };
+        let texture: Arc<dyn Texture> = match &arg.item {
+            Value::Texture(texture) => texture.clone(),
+            other => Arc::new(SolidColor::new(other.to_color()?)),
+        };
+
+        let mut triplanar = TriplanarTexture::new(texture);
+
+        if let Some(arg) = arguments.get("scale") {
+            let scale = arg.item.to_number()?;
+            if scale <= 0.0 {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: "triplanar scale must be a positive number".to_owned(),
+                    position: arg.position.clone(),
+                });
+            }
+            triplanar = triplanar.with_scale(scale);
+        }
+
+        if let Some(arg) = arguments.get("sharpness") {
+            triplanar = triplanar.with_sharpness(arg.item.to_number()?);
+        }
+
+        Ok(Value::Texture(Arc::new(triplanar)))
+    }
+
     fn evaluate_perlin_turbulence(
         &mut self,
         arguments: &[CallArgumentWithPosition],
@@ -510,25 +743,132 @@ impl Interpreter {
         ))))
     }
 
+    fn evaluate_voronoi(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
+        let arguments =
+            self.convert_args(&["scale", "feature", "metric", "low", "high"], arguments)?;
+
+        let mut scale: f64 = 1.0;
+        if let Some(arg) = arguments.get("scale") {
+            scale = arg.item.to_number()?;
+        }
+
+        let mut texture = VoronoiTexture::new(self.random.as_ref(), scale);
+
+        if let Some(arg) = arguments.get("feature") {
+            texture = texture.with_feature(match arg.item.to_unescaped_string()?.as_str() {
+                "f1" => VoronoiFeature::F1,
+                "f2" => VoronoiFeature::F2,
+                "f2-f1" => VoronoiFeature::F2MinusF1,
+                other => {
+                    return Err(Message {
+                        level: MessageLevel::Error,
+                        message: format!(
+                            "unknown voronoi feature \"{other}\", expected \"f1\", \"f2\" or \"f2-f1\""
+                        ),
+                        position: arg.position.clone(),
+                    });
+                }
+            });
+        }
+
+        if let Some(arg) = arguments.get("metric") {
+            texture = texture.with_metric(match arg.item.to_unescaped_string()?.as_str() {
+                "euclidean" => VoronoiMetric::Euclidean,
+                "manhattan" => VoronoiMetric::Manhattan,
+                "chebyshev" => VoronoiMetric::Chebyshev,
+                other => {
+                    return Err(Message {
+                        level: MessageLevel::Error,
+                        message: format!(
+                            "unknown voronoi metric \"{other}\", expected \"euclidean\", \"manhattan\" or \"chebyshev\""
+                        ),
+                        position: arg.position.clone(),
+                    });
+                }
+            });
+        }
+
+        let mut low = Color::BLACK;
+        let mut high = Color::WHITE;
+        if let Some(arg) = arguments.get("low") {
+            low = arg.item.to_color()?;
+        }
+        if let Some(arg) = arguments.get("high") {
+            high = arg.item.to_color()?;
+        }
+
+        Ok(Value::Texture(Arc::new(texture.with_colors(low, high))))
+    }
+
     fn evaluate_image(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
-        let arguments = self.convert_args(&["filename"], arguments)?;
+        let arguments = self.convert_args(
+            &["filename", "colorspace", "filter", "mipmap_distance"],
+            arguments,
+        )?;
+
+        let mut color_space = ColorSpace::Srgb;
+        if let Some(arg) = arguments.get("colorspace") {
+            color_space = match arg.item.to_unescaped_string()?.as_str() {
+                "srgb" => ColorSpace::Srgb,
+                "linear" => ColorSpace::Linear,
+                "data" => ColorSpace::Data,
+                other => {
+                    return Err(Message {
+                        level: MessageLevel::Error,
+                        message: format!(
+                            "unknown colorspace \"{other}\", expected \"srgb\", \"linear\" or \"data\""
+                        ),
+                        position: arg.position.clone(),
+                    });
+                }
+            };
+        }
+
+        let mut filter = TextureFilter::default();
+        if let Some(arg) = arguments.get("filter") {
+            filter = match arg.item.to_unescaped_string()?.as_str() {
+                "nearest" => TextureFilter::Nearest,
+                "bilinear" => TextureFilter::Bilinear,
+                other => {
+                    return Err(Message {
+                        level: MessageLevel::Error,
+                        message: format!(
+                            "unknown filter \"{other}\", expected \"nearest\" or \"bilinear\""
+                        ),
+                        position: arg.position.clone(),
+                    });
+                }
+            };
+        }
+
+        let mipmap_distance = if let Some(arg) = arguments.get("mipmap_distance") {
+            Some(arg.item.to_number()?)
+        } else {
+            None
+        };
 
-        let image = if let Some(arg) = arguments.get("filename") {
+        let texture = if let Some(arg) = arguments.get("filename") {
             let position = &arg.position;
             let filename = arg.item.to_unescaped_string()?;
-            arg.position
-                .source
-                .get_image(&filename)
+            self.record_asset(AssetKind::Image, &filename, position);
+            self.texture_registry
+                .get_or_load_texture(
+                    &image_key(position, &filename),
+                    color_space,
+                    filter,
+                    mipmap_distance,
+                    || position.source.get_image(&filename),
+                )
                 .map_err(|err| Message {
                     level: MessageLevel::Error,
-                    message: format!("failed to get image \"{filename}\": {err:?}"),
+                    message: format!("failed to get image \"{filename}\": {err}"),
                     position: position.clone(),
                 })?
         } else {
             todo!("filename required");
         };
 
-        Ok(Value::Texture(Arc::new(ImageTexture::new(image))))
+        Ok(Value::Texture(texture))
     }
 
     fn evaluate_rands(&mut self, arguments: &[CallArgumentWithPosition]) -> Result<Value> {
@@ -565,25 +905,34 @@ impl Interpreter {
             swap(&mut min_value, &mut max_value);
         }
 
+        // Like OpenSCAD, a seed restarts the generator and later calls without
+        // one continue from there
+        if let Some(seed_value) = seed_value {
+            self.rng.reseed(seed_value as u32);
+        }
+
         let mut items = vec![];
         for _ in 0..value_count {
-            let rand_value = if let Some(seed_value) = seed_value {
-                todo!("rands with seed {seed_value}");
-            } else {
-                self.rng.next_u64()
-            };
-
-            let normalized = rand_value as f64 / u64::MAX as f64;
-            let v = min_value + normalized * (max_value - min_value);
+            let v = min_value + self.next_canonical() * (max_value - min_value);
             items.push(Value::Number(v));
         }
         Ok(Value::Vector { items })
     }
 
+    /// Returns a number in [0, 1) built from two 32-bit outputs, the same way
+    /// `std::uniform_real_distribution` does in OpenSCAD so seeded sequences match.
+    fn next_canonical(&mut self) -> f64 {
+        let low = self.rng.next_u32() as f64;
+        let high = self.rng.next_u32() as f64;
+        let v = (low + high * 4294967296.0) / 18446744073709551616.0;
+        v.min(1.0 - f64::EPSILON / 2.0)
+    }
+
     fn evaluate_non_built_in(
         &mut self,
         name: &str,
         arguments: &[CallArgumentWithPosition],
+        position: &Position,
     ) -> Result<Value> {
         let (arg_names, expr) = if let Some(function) = self.functions.get(name) {
             let arg_names = function.get_argument_names();
@@ -595,14 +944,14 @@ impl Interpreter {
 
         let arguments = self.convert_args(&arg_names, arguments)?;
 
-        {
-            let _scope = self.create_scope();
+        self.enter_call(position, |it| {
+            let _scope = it.create_scope();
 
             for (name, value) in arguments {
-                self.set_variable(&name, value.item);
+                it.set_variable(&name, value.item);
             }
 
-            self.expr_to_value(&expr)
-        }
+            it.expr_to_value(&expr)
+        })
     }
 }
@@ -0,0 +1,300 @@
+use std::{collections::HashMap, sync::Arc};
+
+use caustic_core::{
+    Node, Vector3,
+    object::{BoxPrimitive, ConeFrustum, Group, Rotate, Sphere, Translate},
+};
+
+use crate::{
+    Message, MessageLevel, Position, Result,
+    interpreter::Interpreter,
+    library::{LibraryShim, LibraryValue},
+    parser::{CallArgument, CallArgumentWithPosition, ModuleIdWithPosition},
+    value::{Value, ValueWithPosition},
+};
+
+impl Interpreter {
+    /// Makes the modules of a library shim available, `include` also defines
+    /// the library constants while `use` only imports modules.
+    pub(super) fn include_library_shim(&mut self, shim: &'static LibraryShim, use_only: bool) {
+        self.library_modules.extend(shim.modules);
+        if use_only {
+            return;
+        }
+        for (name, value) in shim.variables {
+            let value = match value {
+                LibraryValue::Number(value) => Value::Number(*value),
+                LibraryValue::Vector(items) => Value::Vector {
+                    items: items.iter().map(|item| Value::Number(*item)).collect(),
+                },
+            };
+            self.set_variable(name, value);
+        }
+    }
+
+    pub(super) fn create_library_module(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<Arc<dyn Node>> {
+        match module_id.item.as_str() {
+            "cuboid" => self.create_cuboid(module_id, arguments),
+            "roundedBox" => self.create_rounded_box(module_id, arguments),
+            "cyl" => self.create_cyl(module_id, arguments),
+            "spheroid" => self.create_spheroid(module_id, arguments),
+            "up" => self.create_move(module_id, arguments, child_nodes, "z", [0.0, 0.0, 1.0]),
+            "down" => self.create_move(module_id, arguments, child_nodes, "z", [0.0, 0.0, -1.0]),
+            "right" => self.create_move(module_id, arguments, child_nodes, "x", [1.0, 0.0, 0.0]),
+            "left" => self.create_move(module_id, arguments, child_nodes, "x", [-1.0, 0.0, 0.0]),
+            "back" => self.create_move(module_id, arguments, child_nodes, "y", [0.0, 1.0, 0.0]),
+            "fwd" => self.create_move(module_id, arguments, child_nodes, "y", [0.0, -1.0, 0.0]),
+            "xrot" => self.create_axis_rotate(module_id, arguments, child_nodes, [1.0, 0.0, 0.0]),
+            "yrot" => self.create_axis_rotate(module_id, arguments, child_nodes, [0.0, 1.0, 0.0]),
+            "zrot" => self.create_axis_rotate(module_id, arguments, child_nodes, [0.0, 0.0, 1.0]),
+            other => Err(Message {
+                level: MessageLevel::Error,
+                message: format!("unsupported library feature: {other}()"),
+                position: module_id.position.clone(),
+            }),
+        }
+    }
+
+    /// Converts the arguments of a library module, named arguments the shim
+    /// does not implement are reported and ignored.
+    fn convert_library_args(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arg_names: &[&str],
+        arguments: &[CallArgumentWithPosition],
+    ) -> Result<HashMap<String, ValueWithPosition>> {
+        let mut supported = vec![];
+        for argument in arguments {
+            match &argument.item {
+                CallArgument::NamedArgument {
+                    identifier,
+                    expr: _,
+                } if !arg_names.contains(&identifier.as_str()) => {
+                    self.unsupported_library_feature(
+                        &format!("{}({identifier}=...) is ignored", module_id.item),
+                        &argument.position,
+                    );
+                }
+                _ => supported.push(argument.clone()),
+            }
+        }
+        self.convert_args(arg_names, &supported)
+    }
+
+    fn unsupported_library_feature(&mut self, feature: &str, position: &Position) {
+        self.messages.push(Message {
+            level: MessageLevel::Warning,
+            message: format!("unsupported library feature: {feature}"),
+            position: position.clone(),
+        });
+    }
+
+    /// Reports edge rounding or chamfers, shapes are drawn with sharp edges.
+    fn report_sharp_edges(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &HashMap<String, ValueWithPosition>,
+        edge_args: &[&str],
+    ) -> Result<()> {
+        for name in edge_args {
+            if let Some(arg) = arguments.get(*name)
+                && arg.item.to_number()? != 0.0
+            {
+                self.unsupported_library_feature(
+                    &format!("{}({name}=...) is drawn with sharp edges", module_id.item),
+                    &arg.position,
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Box centered on the origin, from BOSL2.
+    fn create_cuboid(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+    ) -> Result<Arc<dyn Node>> {
+        let arguments =
+            self.convert_library_args(module_id, &["size", "chamfer", "rounding"], arguments)?;
+        self.report_sharp_edges(module_id, &arguments, &["chamfer", "rounding"])?;
+
+        let mut size = Value::Number(1.0).to_vector3()?;
+        if let Some(arg) = arguments.get("size") {
+            size = arg.item.to_vector3()?;
+        }
+        Ok(self.create_centered_box(size))
+    }
+
+    /// Box centered on the origin, from MCAD.
+    fn create_rounded_box(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+    ) -> Result<Arc<dyn Node>> {
+        let arguments =
+            self.convert_library_args(module_id, &["size", "radius", "sidesonly"], arguments)?;
+        self.report_sharp_edges(module_id, &arguments, &["radius"])?;
+
+        let mut size = Value::Number(1.0).to_vector3()?;
+        if let Some(arg) = arguments.get("size") {
+            size = arg.item.to_vector3()?;
+        }
+        Ok(self.create_centered_box(size))
+    }
+
+    fn create_centered_box(&self, size: Vector3) -> Arc<dyn Node> {
+        Arc::new(BoxPrimitive::new(
+            Vector3::ZERO - (size / 2.0),
+            size / 2.0,
+            self.current_material(),
+        ))
+    }
+
+    /// Cylinder or cone, centered on the origin unless `center=false`, from BOSL2.
+    fn create_cyl(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+    ) -> Result<Arc<dyn Node>> {
+        let arguments = self.convert_library_args(
+            module_id,
+            &[
+                "h", "r", "center", "l", "r1", "r2", "d", "d1", "d2", "length", "height",
+                "chamfer", "rounding",
+            ],
+            arguments,
+        )?;
+        self.report_sharp_edges(module_id, &arguments, &["chamfer", "rounding"])?;
+
+        let mut height = 1.0;
+        for name in ["h", "l", "length", "height"] {
+            if let Some(arg) = arguments.get(name) {
+                height = arg.item.to_number()?;
+            }
+        }
+
+        let mut radius1 = 1.0;
+        let mut radius2 = 1.0;
+        if let Some(arg) = arguments.get("r") {
+            radius1 = arg.item.to_number()?;
+            radius2 = radius1;
+        }
+        if let Some(arg) = arguments.get("d") {
+            radius1 = arg.item.to_number()? / 2.0;
+            radius2 = radius1;
+        }
+        if let Some(arg) = arguments.get("r1") {
+            radius1 = arg.item.to_number()?;
+        }
+        if let Some(arg) = arguments.get("r2") {
+            radius2 = arg.item.to_number()?;
+        }
+        if let Some(arg) = arguments.get("d1") {
+            radius1 = arg.item.to_number()? / 2.0;
+        }
+        if let Some(arg) = arguments.get("d2") {
+            radius2 = arg.item.to_number()? / 2.0;
+        }
+
+        let mut center = true;
+        if let Some(arg) = arguments.get("center") {
+            center = arg.item.to_boolean()?;
+        }
+
+        let mut base = Vector3::ZERO;
+        if center {
+            base.y -= height / 2.0;
+        }
+
+        // r1 is the radius at the bottom, r2 at the top
+        Ok(Arc::new(ConeFrustum::new(
+            base,
+            height,
+            radius2,
+            radius1,
+            self.current_material(),
+        )))
+    }
+
+    /// Sphere, from BOSL2.
+    fn create_spheroid(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+    ) -> Result<Arc<dyn Node>> {
+        // style only changes how OpenSCAD tessellates the sphere
+        let arguments = self.convert_library_args(module_id, &["r", "style", "d"], arguments)?;
+
+        let mut radius = 1.0;
+        if let Some(arg) = arguments.get("r") {
+            radius = arg.item.to_number()?;
+        } else if let Some(arg) = arguments.get("d") {
+            radius = arg.item.to_number()? / 2.0;
+        }
+
+        Ok(Arc::new(Sphere::new(
+            Vector3::ZERO,
+            radius,
+            self.current_material(),
+        )))
+    }
+
+    /// Translation along a single axis, such as BOSL2's `up(z)`.
+    fn create_move(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+        arg_name: &str,
+        direction: [f64; 3],
+    ) -> Result<Arc<dyn Node>> {
+        let arguments = self.convert_library_args(module_id, &[arg_name], arguments)?;
+
+        let mut distance = 0.0;
+        if let Some(arg) = arguments.get(arg_name) {
+            distance = arg.item.to_number()?;
+        }
+
+        let offset = openscad_vector(direction.map(|d| d * distance))?;
+        let child = Arc::new(Group::from_list(&child_nodes));
+        Ok(Arc::new(Translate::new(child, offset)))
+    }
+
+    /// Rotation around a single axis, such as BOSL2's `zrot(a)`.
+    fn create_axis_rotate(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+        axis: [f64; 3],
+    ) -> Result<Arc<dyn Node>> {
+        let arguments = self.convert_library_args(module_id, &["a"], arguments)?;
+
+        let mut angle = 0.0;
+        if let Some(arg) = arguments.get("a") {
+            angle = arg.item.to_number()?;
+        }
+
+        let child: Arc<dyn Node> = match child_nodes.as_slice() {
+            [child] => child.clone(),
+            _ => Arc::new(Group::from_list(&child_nodes)),
+        };
+        if angle == 0.0 {
+            return Ok(child);
+        }
+
+        let euler = openscad_vector(axis.map(|a| a * angle))?;
+        Ok(Arc::new(Rotate::new_from_euler(child, euler)))
+    }
+}
+
+/// Converts OpenSCAD coordinates to the renderer's coordinates.
+fn openscad_vector(v: [f64; 3]) -> Result<Vector3> {
+    Ok(Value::values_to_vector3(&v.map(Value::Number))?)
+}
@@ -1,25 +1,38 @@
 pub mod expr;
 pub mod functions;
+pub mod library;
 pub mod modules;
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 pub mod tests;
 
 use core::f64;
-use std::{cell::RefCell, collections::HashMap, sync::Arc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use caustic_core::{
-    Camera, CameraBuilder, Color, Node, Random, SceneData, Vector3,
-    material::{Lambertian, Material},
-    object::BoundingVolumeHierarchy,
+    AxisAlignedBoundingBox, Background, CameraBuilder, Color, Light, Node, Random, RenderContext,
+    SceneData, Vector3,
+    light::LightList,
+    material::{EditableMaterial, Lambertian, Material},
+    object::{BoundingVolumeHierarchy, Group, Rotate, Scale, Translate},
+    texture::{TextureRegistry, TextureRegistryStats},
 };
-use rand_mt::Mt64;
+use rand_mt::Mt;
 
 use crate::{
     Message, MessageLevel, Position, Result,
+    library::{LibraryPath, find_library_shim, is_known_library, unsupported_library_message},
     parser::{
         CallArgument, CallArgumentWithPosition, DeclArgument, DeclArgumentWithPosition,
-        ExprWithPosition, Statement, StatementWithPosition,
+        ExprWithPosition, Statement, StatementWithPosition, openscad_parse,
     },
+    source::Source,
+    tokenizer::openscad_tokenize,
     value::{Value, ValueConversionError, ValueWithPosition},
 };
 
@@ -66,10 +79,114 @@ impl From<ValueConversionError> for Message {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetKind {
+    /// An image loaded with `image()` or `surface()`
+    Image,
+    /// A file pulled in with `include <...>`
+    Include,
+    /// A mesh loaded with `import()`
+    Mesh,
+}
+
+/// An external file referenced by the scene.
+#[derive(Debug, Clone)]
+pub struct AssetReference {
+    pub kind: AssetKind,
+    pub filename: String,
+    pub position: Position,
+}
+
+/// OpenSCAD modules which are not implemented yet. They are left out of the
+/// scene along with their children, the rest of the scene still renders.
+const UNSUPPORTED_MODULES: &[&str] = &[
+    "children",
+    "hull",
+    "intersection_for",
+    "linear_extrude",
+    "minkowski",
+    "mirror",
+    "multmatrix",
+    "offset",
+    "polygon",
+    "polyhedron",
+    "projection",
+    "resize",
+    "rotate_extrude",
+    "square",
+    "text",
+];
+
+/// Deprecated OpenSCAD module names and the module interpreting them instead,
+/// so scenes written for older versions load without edits.
+const MODULE_ALIASES: &[(&str, &str)] = &[
+    ("assign", "let"),
+    ("child", "children"),
+    ("dxf_linear_extrude", "linear_extrude"),
+    ("dxf_rotate_extrude", "rotate_extrude"),
+    ("import_dxf", "import"),
+    ("import_off", "import"),
+    ("import_stl", "import"),
+];
+
+/// OpenSCAD functions which are not implemented yet, they evaluate to undef.
+const UNSUPPORTED_FUNCTIONS: &[&str] = &[
+    "chr",
+    "fontmetrics",
+    "len",
+    "ord",
+    "parent_module",
+    "search",
+    "str",
+    "textmetrics",
+    "version",
+    "version_num",
+];
+
+/// An OpenSCAD module or function the scene uses which is not implemented and
+/// was skipped.
+#[derive(Debug, Clone)]
+pub struct UnsupportedFeature {
+    pub name: String,
+    /// Every place it is used, in the order they were first interpreted
+    pub positions: Vec<Position>,
+}
+
+/// Bounds on the work interpreting a scene may do, so scenes from untrusted
+/// users cannot hang or crash the process interpreting them.
+///
+/// The default has no limits.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InterpreterLimits {
+    /// Most statements, loop iterations and expressions evaluated
+    pub max_operations: Option<u64>,
+    /// Deepest nesting of calls to functions defined by the scene
+    pub max_call_depth: Option<u32>,
+}
+
 #[derive(Debug)]
 pub struct InterpreterResults {
     pub scene_data: Option<SceneData>,
     pub messages: Vec<Message>,
+    /// Every external file the scene referenced, in the order they were first used
+    pub assets: Vec<AssetReference>,
+    /// How often images used more than once were shared rather than reloaded
+    pub texture_stats: TextureRegistryStats,
+    /// Features which were skipped, in the order they were first used
+    pub unsupported_features: Vec<UnsupportedFeature>,
+    /// Materials created by the scene in the order they were created, only
+    /// when interpreted with editable materials
+    pub materials: Vec<SceneMaterial>,
+}
+
+/// A material created by a call such as `metal()` or `color()`, which can be
+/// edited after the scene is loaded.
+#[derive(Debug, Clone)]
+pub struct SceneMaterial {
+    /// Module or function which created the material
+    pub name: String,
+    pub position: Position,
+    pub material: Arc<EditableMaterial>,
 }
 
 #[derive(Debug)]
@@ -106,15 +223,47 @@ impl Function {
 struct Interpreter {
     _modules: HashMap<String, Module>,
 
-    camera: Option<Arc<Camera>>,
+    /// Camera without a name, the one rendered by default
+    camera: Option<CameraBuilder>,
+    /// Cameras given a name, in the order they were first defined
+    named_cameras: Vec<(String, CameraBuilder)>,
+    /// Background set by `sky()`, replacing the one of the camera
+    background: Option<Arc<dyn Background>>,
+    /// Lights without geometry, such as point_light()
+    delta_lights: Vec<Arc<dyn Light>>,
     world: Vec<Arc<dyn Node>>,
-    lights: Vec<Arc<dyn Node>>,
     material_stack: Vec<Arc<dyn Material>>,
+    /// Material set by `default_material()` for objects without one
+    default_material: Option<Arc<dyn Material>>,
     variables: RefCell<Vec<HashMap<String, Value>>>,
     functions: HashMap<String, Function>,
     random: Arc<dyn Random>,
-    rng: Mt64,
+    /// Generator behind `rands`, starting from the same state for every scene so
+    /// random placement is reproducible, reseeded by calls given a seed
+    rng: Mt,
     messages: Vec<Message>,
+    assets: Vec<AssetReference>,
+    unsupported_features: Vec<UnsupportedFeature>,
+    library_path: LibraryPath,
+    /// Modules provided by included library shims
+    library_modules: HashSet<&'static str>,
+    /// Files being included, innermost last, to detect files including themselves
+    include_stack: Vec<String>,
+    /// Images loaded by the scene, so each file is loaded and stored once
+    texture_registry: TextureRegistry,
+    limits: InterpreterLimits,
+    /// Operations counted against `limits.max_operations` so far
+    operations: u64,
+    /// Calls to functions defined by the scene being evaluated
+    call_depth: u32,
+    /// Materials created by the scene, when they are made editable
+    scene_materials: Option<Vec<SceneMaterial>>,
+    /// Set when an object of the statement being processed has a mirror or
+    /// glass material
+    specular_used: Cell<bool>,
+    /// Boxes of the top level objects with mirror or glass materials, which
+    /// caustic photons are sent towards
+    caustic_targets: Vec<AxisAlignedBoundingBox>,
 }
 
 impl Interpreter {
@@ -150,27 +299,173 @@ impl Interpreter {
             variables: RefCell::new(vec![variables]),
             functions: HashMap::new(),
             camera: None,
+            named_cameras: vec![],
+            background: None,
+            delta_lights: vec![],
             world: vec![],
-            lights: vec![],
             material_stack: vec![],
+            default_material: None,
             random,
-            rng: Mt64::new_unseeded(),
+            rng: Mt::new_unseeded(),
             messages: vec![],
+            assets: vec![],
+            unsupported_features: vec![],
+            library_path: LibraryPath::new(),
+            library_modules: HashSet::new(),
+            include_stack: vec![],
+            texture_registry: TextureRegistry::new(),
+            limits: InterpreterLimits::default(),
+            operations: 0,
+            call_depth: 0,
+            scene_materials: None,
+            specular_used: Cell::new(false),
+            caustic_targets: vec![],
+        }
+    }
+
+    pub fn with_library_path(mut self, library_path: LibraryPath) -> Self {
+        self.library_path = library_path;
+        self
+    }
+
+    /// Sets `$t`, the time of the animation frame being rendered from 0 to 1.
+    pub fn with_time(self, time: f64) -> Self {
+        self.set_variable("$t", Value::Number(time));
+        self
+    }
+
+    pub fn with_limits(mut self, limits: InterpreterLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Wraps every material the scene creates in an [`EditableMaterial`],
+    /// listed in the results, so they can be changed without interpreting the
+    /// scene again.
+    pub fn with_editable_materials(mut self) -> Self {
+        self.scene_materials = Some(vec![]);
+        self
+    }
+
+    /// Returns `material` created by `name` at `position`, made editable when
+    /// the scene's materials are.
+    fn scene_material(
+        &mut self,
+        material: Arc<dyn Material>,
+        name: &str,
+        position: &Position,
+    ) -> Arc<dyn Material> {
+        let Some(scene_materials) = &mut self.scene_materials else {
+            return material;
+        };
+        let material = Arc::new(EditableMaterial::new(material));
+        scene_materials.push(SceneMaterial {
+            name: name.to_owned(),
+            position: position.clone(),
+            material: material.clone(),
+        });
+        material
+    }
+
+    /// Counts an operation against the limits, failing once there were too
+    /// many.
+    fn count_operation(&mut self, position: &Position) -> Result<()> {
+        self.operations += 1;
+        match self.limits.max_operations {
+            Some(max) if self.operations > max => Err(Message {
+                level: MessageLevel::Error,
+                message: format!("scene is too complex, it exceeds {max} operations"),
+                position: position.clone(),
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn operations_exceeded(&self) -> bool {
+        self.limits
+            .max_operations
+            .is_some_and(|max| self.operations > max)
+    }
+
+    /// Evaluates a call to a function defined by the scene, failing when calls
+    /// nest deeper than the limits allow, such as a function calling itself
+    /// forever.
+    fn enter_call<T>(
+        &mut self,
+        position: &Position,
+        call: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        if let Some(max) = self.limits.max_call_depth
+            && self.call_depth >= max
+        {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: format!("function calls nest deeper than {max}"),
+                position: position.clone(),
+            });
+        }
+        self.call_depth += 1;
+        let result = call(self);
+        self.call_depth -= 1;
+        result
+    }
+
+    /// Warns about objects which slow down or are left out of the bounding
+    /// volume hierarchy, such as the same shape placed many times in a loop.
+    fn warn_pathological_nodes(&mut self, nodes: &[Arc<dyn Node>], position: &Position) {
+        let diagnostics = BoundingVolumeHierarchy::diagnose(nodes);
+        if diagnostics.degenerate > 0 {
+            self.messages.push(Message {
+                level: MessageLevel::Warning,
+                message: format!(
+                    "Ignoring {} object(s) with an empty or invalid bounding box",
+                    diagnostics.degenerate
+                ),
+                position: position.clone(),
+            });
+        }
+        if diagnostics.coincident > 0 {
+            self.messages.push(Message {
+                level: MessageLevel::Warning,
+                message: format!(
+                    "{} object(s) have the same bounding box as another object, duplicated objects slow down rendering",
+                    diagnostics.coincident
+                ),
+                position: position.clone(),
+            });
         }
     }
 
     fn interpret(mut self, statements: Vec<StatementWithPosition>) -> InterpreterResults {
         for statement in statements {
+            self.specular_used.set(false);
             match self.process_statement(&statement) {
                 Ok(mut nodes) => {
+                    self.warn_pathological_nodes(&nodes, &statement.position);
+                    if self.specular_used.get() {
+                        self.caustic_targets.extend(
+                            nodes
+                                .iter()
+                                .map(|node| *node.bounding_box())
+                                .filter(|bbox| !bbox.is_unbounded()),
+                        );
+                    }
                     self.world.append(&mut nodes);
                 }
                 Err(err) => self.messages.push(err),
             }
+            // Every later statement would fail the same way
+            if self.operations_exceeded() {
+                break;
+            }
         }
 
-        let camera = if let Some(camera) = self.camera {
-            camera
+        // Without an unnamed camera the first named one is rendered by default
+        let default_camera = self
+            .camera
+            .or_else(|| self.named_cameras.first().map(|(_, camera)| camera.clone()));
+        let mut camera_builder = if let Some(camera_builder) = default_camera {
+            camera_builder
         } else {
             let mut camera_builder = CameraBuilder::new();
             camera_builder.aspect_ratio = 1.0;
@@ -178,26 +473,64 @@ impl Interpreter {
             camera_builder.samples_per_pixel = 10;
             camera_builder.max_depth = 50;
             camera_builder.defocus_angle = 0.0;
-            camera_builder.background = Color::new(0.7, 0.8, 1.0);
+            camera_builder.background = Arc::new(Color::new(0.7, 0.8, 1.0));
             camera_builder.look_at = Vector3::new(0.0, 0.0, 0.0);
             camera_builder.look_from = Vector3::new(-50.0, 70.0, -50.0);
             camera_builder.up = Vector3::new(0.0, 1.0, 0.0);
-            Arc::new(camera_builder.build())
+            camera_builder
         };
+        if let Some(background) = &self.background {
+            camera_builder.background = background.clone();
+        }
+
+        // Lights are found once the tree is complete, so they pick up every
+        // transform above them
+        let mut lights = vec![];
+        for node in &self.world {
+            find_lights(node, &mut lights);
+        }
+
+        let mut light_list = LightList::from_nodes(&lights);
+        light_list.build_tree(&RenderContext {
+            random: self.random.clone(),
+        });
+        for light in self.delta_lights {
+            light_list.push(light);
+        }
+
+        let world = Arc::new(BoundingVolumeHierarchy::new(&self.world));
+
+        let camera = Arc::new(camera_builder.build());
+        let cameras = self
+            .named_cameras
+            .into_iter()
+            .map(|(name, mut camera_builder)| {
+                if let Some(background) = &self.background {
+                    camera_builder.background = background.clone();
+                }
+                (name, Arc::new(camera_builder.build()))
+            })
+            .collect();
 
         let scene_data = SceneData {
             camera,
-            world: Arc::new(BoundingVolumeHierarchy::new(&self.world)),
-            lights: if self.lights.is_empty() {
+            cameras,
+            world,
+            lights: if light_list.is_empty() {
                 None
             } else {
-                Some(Arc::new(BoundingVolumeHierarchy::new(&self.lights)))
+                Some(Arc::new(light_list) as Arc<dyn Light>)
             },
+            caustic_targets: self.caustic_targets,
         };
 
         InterpreterResults {
             scene_data: Some(scene_data),
             messages: self.messages,
+            assets: self.assets,
+            texture_stats: self.texture_registry.stats(),
+            unsupported_features: self.unsupported_features,
+            materials: self.scene_materials.unwrap_or_default(),
         }
     }
 
@@ -205,6 +538,7 @@ impl Interpreter {
         &mut self,
         statement: &StatementWithPosition,
     ) -> Result<Vec<Arc<dyn Node>>> {
+        self.count_operation(&statement.position)?;
         match &statement.item {
             Statement::Empty => Ok(vec![]),
             Statement::ModuleInstantiation {
@@ -215,11 +549,17 @@ impl Interpreter {
             Statement::Assignment { identifier, expr } => {
                 self.process_assignment(identifier, expr).map(|_| vec![])
             }
-            Statement::Include { filename } => self.process_include(filename),
+            Statement::Include { filename } => {
+                self.process_include(filename, &statement.position, false)
+            }
+            Statement::Use { filename } => {
+                self.process_include(filename, &statement.position, true)
+            }
             Statement::FunctionDecl {
                 function_name,
                 arguments,
                 expr,
+                doc: _,
             } => self
                 .process_function_decl(function_name, arguments, expr)
                 .map(|_| vec![]),
@@ -232,7 +572,21 @@ impl Interpreter {
     }
 
     fn current_material(&self) -> Arc<dyn Material> {
-        if let Some(mat) = self.material_stack.last() {
+        let material = if let Some(mat) = self.material_stack.last() {
+            mat.clone()
+        } else {
+            self.default_material()
+        };
+        if material.is_specular() {
+            self.specular_used.set(true);
+        }
+        material
+    }
+
+    /// Returns the material of objects without one, set by
+    /// `default_material()` or yellow.
+    fn default_material(&self) -> Arc<dyn Material> {
+        if let Some(mat) = &self.default_material {
             mat.clone()
         } else {
             Arc::new(Lambertian::new_from_color(Color::new(0.99, 0.85, 0.26)))
@@ -293,6 +647,7 @@ impl Interpreter {
                 break;
             }
 
+            self.count_operation(&arg.position)?;
             self.set_variable(name, Value::Number(i));
 
             let mut child_statement_nodes = self.process_child_statements(child_statements)?;
@@ -304,6 +659,42 @@ impl Interpreter {
         Ok(children)
     }
 
+    /// Interprets the children with the named arguments set as variables, which
+    /// are evaluated in order so later ones can use earlier ones.
+    fn process_let(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        child_statements: &[StatementWithPosition],
+    ) -> Result<Vec<Arc<dyn Node>>> {
+        self.variables.borrow_mut().push(HashMap::new());
+        let result = self.process_let_scope(arguments, child_statements);
+        self.variables.borrow_mut().pop();
+        result
+    }
+
+    fn process_let_scope(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        child_statements: &[StatementWithPosition],
+    ) -> Result<Vec<Arc<dyn Node>>> {
+        for arg in arguments {
+            match &arg.item {
+                CallArgument::NamedArgument { identifier, expr } => {
+                    let value = self.expr_to_value(expr)?;
+                    self.set_variable(identifier, value);
+                }
+                CallArgument::Expr { .. } => {
+                    return Err(Message {
+                        level: MessageLevel::Error,
+                        message: "let arguments must be named, e.g. let(a=1)".to_owned(),
+                        position: arg.position.clone(),
+                    });
+                }
+            }
+        }
+        self.process_child_statements(child_statements)
+    }
+
     fn process_child_statements(
         &mut self,
         child_statements: &[StatementWithPosition],
@@ -334,12 +725,144 @@ impl Interpreter {
         Ok(())
     }
 
-    fn process_include(&self, filename: &str) -> Result<Vec<Arc<dyn Node>>> {
+    /// Includes a file, searching next to the including file first and then
+    /// the library path. Widely used libraries are replaced by builtin shims.
+    /// `use` only imports the functions of the file.
+    fn process_include(
+        &mut self,
+        filename: &str,
+        position: &Position,
+        use_only: bool,
+    ) -> Result<Vec<Arc<dyn Node>>> {
         if filename.ends_with("caustic.scad") {
             return Ok(vec![]);
         }
 
-        todo!("include {filename}")
+        if let Some(shim) = find_library_shim(filename) {
+            self.include_library_shim(shim, use_only);
+            return Ok(vec![]);
+        }
+
+        // the parser cannot read the real libraries yet
+        if is_known_library(filename) {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: unsupported_library_message(filename),
+                position: position.clone(),
+            });
+        }
+
+        if position.source.has_file(filename) {
+            self.record_asset(AssetKind::Include, filename, position);
+            return match position.source.get_source(filename) {
+                Some(source) => self.process_include_source(source, position, use_only),
+                None => Err(Message {
+                    level: MessageLevel::Error,
+                    message: format!("cannot load \"{filename}\""),
+                    position: position.clone(),
+                }),
+            };
+        }
+
+        if let Some(source) = self.library_path.load(filename) {
+            return self.process_include_source(source, position, use_only);
+        }
+
+        // reported with the other missing assets
+        self.record_asset(AssetKind::Include, filename, position);
+        Ok(vec![])
+    }
+
+    fn process_include_source(
+        &mut self,
+        source: Arc<Box<dyn Source>>,
+        position: &Position,
+        use_only: bool,
+    ) -> Result<Vec<Arc<dyn Node>>> {
+        let filename = source.get_filename().to_owned();
+        if self.include_stack.contains(&filename) {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: format!("\"{filename}\" includes itself"),
+                position: position.clone(),
+            });
+        }
+
+        let mut tokenize_results = openscad_tokenize(source.clone());
+        self.messages.append(&mut tokenize_results.messages);
+        let Some(tokens) = tokenize_results.tokens else {
+            return Ok(vec![]);
+        };
+
+        let mut parse_results = openscad_parse(tokens, source);
+        self.messages.append(&mut parse_results.messages);
+        let Some(statements) = parse_results.statements else {
+            return Ok(vec![]);
+        };
+
+        self.include_stack.push(filename);
+        let mut nodes = vec![];
+        for statement in &statements {
+            if use_only && !matches!(statement.item, Statement::FunctionDecl { .. }) {
+                continue;
+            }
+            match self.process_statement(statement) {
+                Ok(mut statement_nodes) => nodes.append(&mut statement_nodes),
+                Err(err) => self.messages.push(err),
+            }
+        }
+        self.include_stack.pop();
+
+        Ok(nodes)
+    }
+
+    /// Adds a file to the scene's asset manifest. A call evaluated several times,
+    /// e.g. in a loop, is only recorded once.
+    fn record_asset(&mut self, kind: AssetKind, filename: &str, position: &Position) {
+        let recorded = self.assets.iter().any(|asset| {
+            asset.kind == kind
+                && asset.filename == filename
+                && asset.position.start == position.start
+                && asset.position.end == position.end
+        });
+        if !recorded {
+            self.assets.push(AssetReference {
+                kind,
+                filename: filename.to_owned(),
+                position: position.clone(),
+            });
+        }
+    }
+
+    /// Records the use of a feature which is not implemented and warns about
+    /// it, once per place it is used even when evaluated several times.
+    fn record_unsupported(&mut self, name: &str, position: &Position) {
+        let index = match self
+            .unsupported_features
+            .iter()
+            .position(|feature| feature.name == name)
+        {
+            Some(index) => index,
+            None => {
+                self.unsupported_features.push(UnsupportedFeature {
+                    name: name.to_owned(),
+                    positions: vec![],
+                });
+                self.unsupported_features.len() - 1
+            }
+        };
+        let feature = &mut self.unsupported_features[index];
+        if feature.positions.contains(position) {
+            return;
+        }
+        feature.positions.push(position.clone());
+        self.messages.push(Message {
+            level: MessageLevel::Warning,
+            message: format!(
+                "unsupported feature \"{name}\" is not implemented yet and was skipped"
+            ),
+            position: position.clone(),
+        });
     }
 
     fn convert_args(
@@ -466,6 +989,7 @@ impl Interpreter {
                 }
             }
             Value::Texture(texture) => todo!("evaluate_index {lhs:?} {texture:?}"),
+            Value::Material(material) => todo!("evaluate_index {lhs:?} {material:?}"),
             Value::Range {
                 start,
                 end,
@@ -499,6 +1023,7 @@ impl Interpreter {
             }
             Value::Boolean(_) => todo!(),
             Value::Texture(_texture) => todo!(),
+            Value::Material(_material) => todo!(),
             Value::Range {
                 start: _,
                 end: _,
@@ -584,10 +1109,116 @@ impl Interpreter {
     }
 }
 
+/// Collects the emissive surfaces under `node`, each wrapped in copies of the
+/// transforms above it so it is sampled where it is rendered. Lights inside
+/// CSG operations are left out, as parts of them may be cut away.
+fn find_lights(node: &Arc<dyn Node>, lights: &mut Vec<Arc<dyn Node>>) {
+    let any = node.as_any();
+    if let Some(group) = any.downcast_ref::<Group>() {
+        for child in group.get_nodes() {
+            find_lights(child, lights);
+        }
+    } else if let Some(bvh) = any.downcast_ref::<BoundingVolumeHierarchy>() {
+        let (left, right) = (bvh.get_left(), bvh.get_right());
+        find_lights(&left, lights);
+        // A tree of a single node holds it on both sides
+        if !Arc::ptr_eq(&left, &right) {
+            find_lights(&right, lights);
+        }
+    } else if let Some(translate) = any.downcast_ref::<Translate>() {
+        find_transformed_lights(translate.get_object(), lights, |light| {
+            Arc::new(translate.with_object(light))
+        });
+    } else if let Some(rotate) = any.downcast_ref::<Rotate>() {
+        find_transformed_lights(rotate.get_object(), lights, |light| {
+            Arc::new(rotate.with_object(light))
+        });
+    } else if let Some(scale) = any.downcast_ref::<Scale>() {
+        find_transformed_lights(scale.get_object(), lights, |light| {
+            Arc::new(scale.with_object(light))
+        });
+    } else if node.is_light() {
+        lights.push(node.clone());
+    }
+}
+
+fn find_transformed_lights(
+    object: &Arc<dyn Node>,
+    lights: &mut Vec<Arc<dyn Node>>,
+    transform: impl Fn(Arc<dyn Node>) -> Arc<dyn Node>,
+) {
+    let mut inner = vec![];
+    find_lights(object, &mut inner);
+    lights.extend(inner.into_iter().map(transform));
+}
+
+/// Returns the key an image file is cached under, its path relative to the
+/// directory of the source using it.
+fn image_key(position: &Position, filename: &str) -> String {
+    let source_filename = Path::new(position.source.get_filename());
+    source_filename
+        .parent()
+        .map_or_else(|| PathBuf::from(filename), |dir| dir.join(filename))
+        .to_string_lossy()
+        .into_owned()
+}
+
 pub fn openscad_interpret(
     statements: Vec<StatementWithPosition>,
     random: Arc<dyn Random>,
 ) -> InterpreterResults {
-    let it = Interpreter::new(random);
+    openscad_interpret_with_library_path(statements, random, LibraryPath::new())
+}
+
+pub fn openscad_interpret_with_library_path(
+    statements: Vec<StatementWithPosition>,
+    random: Arc<dyn Random>,
+    library_path: LibraryPath,
+) -> InterpreterResults {
+    openscad_interpret_at_time(statements, random, library_path, 0.0)
+}
+
+/// Interprets a scene with `$t` set to `time`, to render a frame of an
+/// animation.
+pub fn openscad_interpret_at_time(
+    statements: Vec<StatementWithPosition>,
+    random: Arc<dyn Random>,
+    library_path: LibraryPath,
+    time: f64,
+) -> InterpreterResults {
+    openscad_interpret_with_limits(
+        statements,
+        random,
+        library_path,
+        time,
+        InterpreterLimits::default(),
+    )
+}
+
+/// Interprets a scene within `limits`, for scenes from untrusted users.
+pub fn openscad_interpret_with_limits(
+    statements: Vec<StatementWithPosition>,
+    random: Arc<dyn Random>,
+    library_path: LibraryPath,
+    time: f64,
+    limits: InterpreterLimits,
+) -> InterpreterResults {
+    let it = Interpreter::new(random)
+        .with_library_path(library_path)
+        .with_time(time)
+        .with_limits(limits);
+    it.interpret(statements)
+}
+
+/// Interprets a scene with every material it creates made editable, see
+/// [`InterpreterResults::materials`].
+pub fn openscad_interpret_with_editable_materials(
+    statements: Vec<StatementWithPosition>,
+    random: Arc<dyn Random>,
+    library_path: LibraryPath,
+) -> InterpreterResults {
+    let it = Interpreter::new(random)
+        .with_library_path(library_path)
+        .with_editable_materials();
     it.interpret(statements)
 }
@@ -1,16 +1,24 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use caustic_core::{
-    CameraBuilder, Color, Node, Vector3,
-    material::{Dielectric, DiffuseLight, Lambertian, Material, Metal},
-    object::{BoxPrimitive, ConeFrustum, Disc, Group, Quad, Rotate, Scale, Sphere, Translate},
+    Axis, CameraBuilder, Color, CropWindow, Exposure, Node, Projection, Quaternion, RenderRegion,
+    Vector3,
+    background::{EnvironmentMap, PreethamSky},
+    light::{DirectionalLight, PointLight, SpotLight},
+    material::{Dielectric, DiffuseLight, Lambertian, Material, Metal, Principled, Sheen, Toon},
+    object::{
+        BoxPrimitive, ConeFrustum, ConvertedMesh, Csg, CsgOperation, Disc, Ellipsoid, Group,
+        Heightfield, Quad, Rotate, Scale, Sphere, Translate, TriangleMesh,
+    },
+    render::PhotonMap,
+    utils::{SolarPosition, math},
 };
 
 use crate::{
     Message, MessageLevel, Position, Result,
-    interpreter::Interpreter,
+    interpreter::{AssetKind, Interpreter, MODULE_ALIASES, UNSUPPORTED_MODULES, image_key},
     parser::{CallArgument, CallArgumentWithPosition, ModuleIdWithPosition, StatementWithPosition},
-    value::Value,
+    value::{Value, ValueWithPosition, values_to_numbers},
 };
 
 impl Interpreter {
@@ -22,23 +30,95 @@ impl Interpreter {
     ) -> Result<Vec<Arc<dyn Node>>> {
         let module_position = module_id.position.clone();
 
+        if let Some((_, target)) = MODULE_ALIASES
+            .iter()
+            .find(|(name, _)| *name == module_id.item)
+            && !self.library_modules.contains(module_id.item.as_str())
+        {
+            let warning = Message {
+                level: MessageLevel::Warning,
+                message: format!(
+                    "\"{}\" is deprecated, use \"{target}\" instead",
+                    module_id.item
+                ),
+                position: module_position.clone(),
+            };
+            // Warn once when the module is in a loop
+            if !self.messages.contains(&warning) {
+                self.messages.push(warning);
+            }
+            let module_id = ModuleIdWithPosition::new((*target).to_owned(), module_position);
+            return self.process_module_instantiation(&module_id, arguments, child_statements);
+        }
+
         if module_id.item == "color" {
             let m = self.create_color(arguments)?;
+            let m = self.scene_material(m, &module_id.item, &module_position);
             self.material_stack.push(m);
         } else if module_id.item == "lambertian" {
             let m = self.create_lambertian(arguments)?;
+            let m = self.scene_material(m, &module_id.item, &module_position);
             self.material_stack.push(m);
         } else if module_id.item == "dielectric" {
             let m = self.create_dielectric(arguments)?;
+            let m = self.scene_material(m, &module_id.item, &module_position);
             self.material_stack.push(m);
         } else if module_id.item == "metal" {
             let m = self.create_metal(arguments)?;
+            let m = self.scene_material(m, &module_id.item, &module_position);
             self.material_stack.push(m);
         } else if module_id.item == "diffuse_light" {
             let m = self.create_diffuse_light(arguments)?;
+            let m = self.scene_material(m, &module_id.item, &module_position);
+            self.material_stack.push(m);
+        } else if module_id.item == "pbr" {
+            let m = self.create_pbr(arguments)?;
+            let m = self.scene_material(m, &module_id.item, &module_position);
+            self.material_stack.push(m);
+        } else if module_id.item == "sheen" {
+            let m = self.create_sheen(arguments)?;
+            let m = self.scene_material(m, &module_id.item, &module_position);
+            self.material_stack.push(m);
+        } else if module_id.item == "toon" {
+            let m = self.create_toon(arguments)?;
+            let m = self.scene_material(m, &module_id.item, &module_position);
+            self.material_stack.push(m);
+        } else if module_id.item == "apply" {
+            let m = self.material_argument(module_id, arguments)?;
             self.material_stack.push(m);
+        } else if module_id.item == "default_material" {
+            let m = self.material_argument(module_id, arguments)?;
+            if !child_statements.is_empty() {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message:
+                        "default_material takes no children, use apply() for part of the scene"
+                            .to_owned(),
+                    position: module_position,
+                });
+            }
+            self.default_material = Some(m);
+            return Ok(vec![]);
+        } else if module_id.item == "reset_material" {
+            self.convert_args(&[], arguments)?;
+            if child_statements.is_empty() {
+                // Without children, restore the default for the rest of the file
+                self.default_material = None;
+                return Ok(vec![]);
+            }
+            // Children ignore the materials they are nested in
+            self.material_stack.push(self.default_material());
         } else if module_id.item == "for" {
             return self.process_for_loop(arguments, child_statements);
+        } else if module_id.item == "let" {
+            return self.process_let(arguments, child_statements);
+        }
+
+        if UNSUPPORTED_MODULES.contains(&module_id.item.as_str())
+            && !self.library_modules.contains(module_id.item.as_str())
+        {
+            self.record_unsupported(&module_id.item, &module_position);
+            return Ok(vec![]);
         }
 
         let child_nodes = self.process_child_statements(child_statements)?;
@@ -47,24 +127,58 @@ impl Interpreter {
             "circle" => self.create_circle(arguments, child_nodes).map(|n| vec![n]),
             "cube" => self.create_cube(arguments, child_nodes).map(|n| vec![n]),
             "sphere" => self.create_sphere(arguments, child_nodes).map(|n| vec![n]),
+            "moving_sphere" => self
+                .create_moving_sphere(arguments, child_nodes)
+                .map(|n| vec![n]),
             "cylinder" => self
                 .create_cylinder(arguments, child_nodes)
                 .map(|n| vec![n]),
             "quad" => self.create_quad(arguments, child_nodes).map(|n| vec![n]),
+            "surface" => self
+                .create_surface(module_id, arguments, child_nodes)
+                .map(|n| vec![n]),
+            "import" => self
+                .create_import(module_id, arguments, child_nodes)
+                .map(|n| vec![n]),
             "translate" => self
                 .create_translate(arguments, child_nodes)
                 .map(|n| vec![n]),
             "rotate" => self.create_rotate(arguments, child_nodes).map(|n| vec![n]),
+            "rotate_about" => self
+                .create_rotate_about(module_id, arguments, child_nodes)
+                .map(|n| vec![n]),
             "scale" => self.create_scale(arguments, child_nodes).map(|n| vec![n]),
-            "camera" => self.create_camera(arguments, child_nodes).map(|_| vec![]),
-            "color" | "lambertian" | "dielectric" | "metal" | "diffuse_light" => {
+            "animate" => self.create_animate(arguments, child_nodes).map(|n| vec![n]),
+            "bbox" => self.create_bbox(module_id, arguments, child_nodes),
+            "union" => Ok(Self::create_csg(CsgOperation::Union, child_nodes)),
+            "difference" => Ok(Self::create_csg(CsgOperation::Difference, child_nodes)),
+            "intersection" => Ok(Self::create_csg(CsgOperation::Intersection, child_nodes)),
+            "camera" => self
+                .create_camera(module_id, arguments, child_nodes)
+                .map(|_| vec![]),
+            "sky" => self.create_sky(arguments, child_nodes).map(|_| vec![]),
+            "sunlight" => self
+                .create_sunlight(module_id, arguments, child_nodes)
+                .map(|_| vec![]),
+            "point_light" => self
+                .create_point_light(arguments, child_nodes)
+                .map(|_| vec![]),
+            "spot_light" => self
+                .create_spot_light(module_id, arguments, child_nodes)
+                .map(|_| vec![]),
+            "sun" => self.create_sun(arguments, child_nodes).map(|_| vec![]),
+            "color" | "lambertian" | "dielectric" | "metal" | "diffuse_light" | "pbr" | "sheen"
+            | "toon" | "apply" | "reset_material" => {
                 self.material_stack.pop();
                 Ok(child_nodes)
             }
-            "for" => panic!("already handled"),
+            "for" | "let" => panic!("already handled"),
             "echo" => self
                 .evaluate_echo(arguments, child_nodes, module_position)
                 .map(|_| vec![]),
+            other if self.library_modules.contains(other) => self
+                .create_library_module(module_id, arguments, child_nodes)
+                .map(|n| vec![n]),
             other => Err(Message {
                 level: MessageLevel::Error,
                 message: format!("unknown identifier \"{other}\""),
@@ -86,7 +200,10 @@ impl Interpreter {
         let normal = Vector3::new(0.0, 1.0, 0.0);
         let mut radius = 1.0;
 
-        let arguments = self.convert_args(&["r", "d"], arguments)?;
+        let mut inner_radius = 0.0;
+        let mut angle = 360.0;
+
+        let arguments = self.convert_args(&["r", "d", "inner_r", "angle"], arguments)?;
 
         if let Some(arg) = arguments.get("r") {
             radius = arg.item.to_number()?;
@@ -96,12 +213,28 @@ impl Interpreter {
             radius = arg.item.to_number()? / 2.0;
         }
 
-        Ok(Arc::new(Disc::new(
-            center,
-            radius,
-            normal,
-            self.current_material(),
-        )))
+        if let Some(arg) = arguments.get("inner_r") {
+            inner_radius = arg.item.to_number()?;
+            if inner_radius < 0.0 || inner_radius >= radius {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: format!(
+                        "inner_r must be at least 0 and less than the radius {radius}, found {inner_radius}"
+                    ),
+                    position: arg.position.clone(),
+                });
+            }
+        }
+
+        if let Some(arg) = arguments.get("angle") {
+            angle = arg.item.to_number()?;
+        }
+
+        Ok(Arc::new(
+            Disc::new(center, radius, normal, self.current_material())
+                .with_inner_radius(inner_radius)
+                .with_sweep_angle(angle),
+        ))
     }
 
     fn create_cube(
@@ -162,6 +295,40 @@ impl Interpreter {
         )))
     }
 
+    fn create_moving_sphere(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<Arc<dyn Node>> {
+        if !child_nodes.is_empty() {
+            todo!("should not have children");
+        }
+
+        let mut from = Vector3::ZERO;
+        let mut to = Vector3::ZERO;
+        let mut radius = 1.0;
+
+        let arguments = self.convert_args(&["from", "to", "r", "d"], arguments)?;
+
+        if let Some(arg) = arguments.get("from") {
+            from = arg.item.to_vector3()?;
+        }
+
+        if let Some(arg) = arguments.get("to") {
+            to = arg.item.to_vector3()?;
+        }
+
+        if let Some(arg) = arguments.get("r") {
+            radius = arg.item.to_number()?;
+        } else if let Some(arg) = arguments.get("d") {
+            radius = arg.item.to_number()? / 2.0;
+        }
+
+        let mut sphere = Sphere::new(from, radius, self.current_material());
+        sphere.set_direction(to - from);
+        Ok(Arc::new(sphere))
+    }
+
     fn create_cylinder(
         &mut self,
         arguments: &[CallArgumentWithPosition],
@@ -177,7 +344,10 @@ impl Interpreter {
         let mut center = false;
 
         let arguments = self.convert_args(
-            &["h", "r1", "r2", "center", "r", "d", "d1", "d2"],
+            &[
+                "h", "r1", "r2", "center", "r", "d", "d1", "d2", "capped", "smooth", "$fn", "$fa",
+                "$fs",
+            ],
             arguments,
         )?;
 
@@ -217,18 +387,67 @@ impl Interpreter {
             center = arg.item.to_boolean()?;
         }
 
+        let mut capped = true;
+        if let Some(arg) = arguments.get("capped") {
+            capped = arg.item.to_boolean()?;
+        }
+
+        let mut facets = 0;
+        if let Some(arg) = arguments.get("smooth")
+            && !arg.item.to_boolean()?
+        {
+            let special = |name: &str| arguments.get(name).map(|arg| &arg.item);
+            facets = self.get_fragments(
+                radius1.max(radius2),
+                special("$fn"),
+                special("$fa"),
+                special("$fs"),
+            )?;
+        }
+
         let mut center_vec = Vector3::new(0.0, 0.0, 0.0);
         if center {
             center_vec.y -= height / 2.0;
         }
 
-        Ok(Arc::new(ConeFrustum::new(
-            center_vec,
-            height,
-            radius1,
-            radius2,
-            self.current_material(),
-        )))
+        // r1 is the radius at the bottom, r2 at the top
+        Ok(Arc::new(
+            ConeFrustum::new(
+                center_vec,
+                height,
+                radius2,
+                radius1,
+                self.current_material(),
+            )
+            .with_caps(capped, capped)
+            .with_facets(facets),
+        ))
+    }
+
+    /// Returns the number of sides OpenSCAD draws circles of radius `r` with,
+    /// from `$fn`, `$fa` and `$fs` given as arguments or else as variables.
+    fn get_fragments(
+        &self,
+        r: f64,
+        fn_arg: Option<&Value>,
+        fa_arg: Option<&Value>,
+        fs_arg: Option<&Value>,
+    ) -> Result<u32> {
+        let special = |arg: Option<&Value>, name: &str, default: f64| -> Result<f64> {
+            match arg.cloned().or_else(|| self.get_variable(name)) {
+                Some(value) => Ok(value.to_number()?),
+                None => Ok(default),
+            }
+        };
+        let fn_ = special(fn_arg, "$fn", 0.0)?;
+        let fa = special(fa_arg, "$fa", 12.0)?;
+        let fs = special(fs_arg, "$fs", 2.0)?;
+
+        if fn_ > 0.0 {
+            return Ok(fn_.max(3.0) as u32);
+        }
+        let fragments = (360.0 / fa).min(r * 2.0 * std::f64::consts::PI / fs);
+        Ok(fragments.max(5.0).ceil() as u32)
     }
 
     fn create_quad(
@@ -263,6 +482,142 @@ impl Interpreter {
         Ok(Arc::new(Quad::new(q, u, v, self.current_material())))
     }
 
+    fn create_surface(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<Arc<dyn Node>> {
+        if !child_nodes.is_empty() {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "surface does not take children".to_owned(),
+                position: module_id.position.clone(),
+            });
+        }
+
+        let mut center = false;
+        let mut invert = false;
+
+        let arguments = self.convert_args(&["file", "center", "invert"], arguments)?;
+
+        let image = if let Some(arg) = arguments.get("file") {
+            let position = &arg.position;
+            let filename = arg.item.to_unescaped_string()?;
+            self.record_asset(AssetKind::Image, &filename, position);
+            self.texture_registry
+                .get_or_load_image(&image_key(position, &filename), || {
+                    position.source.get_image(&filename)
+                })
+                .map_err(|err| Message {
+                    level: MessageLevel::Error,
+                    message: format!("failed to get image \"{filename}\": {err}"),
+                    position: position.clone(),
+                })?
+        } else {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "surface requires a file".to_owned(),
+                position: module_id.position.clone(),
+            });
+        };
+
+        if let Some(arg) = arguments.get("center") {
+            center = arg.item.to_boolean()?;
+        }
+
+        if let Some(arg) = arguments.get("invert") {
+            invert = arg.item.to_boolean()?;
+        }
+
+        // OpenSCAD puts the last image row at y = 0 and maps luminance to a height
+        // from 0 to 100. Rows and columns are reversed to match the flipped x axis.
+        let heights: Vec<Vec<f64>> = (0..image.height())
+            .rev()
+            .map(|y| {
+                (0..image.width())
+                    .rev()
+                    .map(|x| {
+                        let luminance = image.get_pixel(x, y).map(|c| c.luminance()).unwrap_or(0.0);
+                        if invert {
+                            (1.0 - luminance) * 100.0
+                        } else {
+                            luminance * 100.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let width = image.width().saturating_sub(1) as f64;
+        let depth = image.height().saturating_sub(1) as f64;
+        let mut offset = Vector3::new(-width, 0.0, 0.0);
+        if center {
+            offset = offset + Vector3::new(width / 2.0, 0.0, -depth / 2.0);
+        }
+
+        let heightfield = Heightfield::new(
+            heights,
+            Vector3::new(1.0, 1.0, 1.0),
+            self.current_material(),
+        );
+        Ok(Arc::new(Translate::new(Arc::new(heightfield), offset)))
+    }
+
+    fn create_import(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<Arc<dyn Node>> {
+        if !child_nodes.is_empty() {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "import does not take children".to_owned(),
+                position: module_id.position.clone(),
+            });
+        }
+
+        let arguments = self.convert_args(&["file", "convexity"], arguments)?;
+
+        let Some(arg) = arguments.get("file") else {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "import requires a file".to_owned(),
+                position: module_id.position.clone(),
+            });
+        };
+        let position = &arg.position;
+        let filename = arg.item.to_unescaped_string()?;
+        self.record_asset(AssetKind::Mesh, &filename, position);
+        let mesh = position
+            .source
+            .get_converted_mesh(&filename)
+            .and_then(|data| ConvertedMesh::decode(&data))
+            .map_err(|err| Message {
+                level: MessageLevel::Error,
+                message: format!("failed to import mesh \"{filename}\": {err}"),
+                position: position.clone(),
+            })?;
+
+        // Mirrored to match the flipped x axis, which reverses the winding
+        let vertices = mesh
+            .vertices
+            .iter()
+            .map(|v| Vector3::new(-v.x, v.y, v.z))
+            .collect();
+        let triangles = mesh
+            .triangles
+            .iter()
+            .map(|[a, b, c]| [*a, *c, *b])
+            .collect();
+        Ok(Arc::new(TriangleMesh::new(
+            vertices,
+            triangles,
+            self.current_material(),
+        )))
+    }
+
     fn create_translate(
         &mut self,
         arguments: &[CallArgumentWithPosition],
@@ -293,7 +648,11 @@ impl Interpreter {
         if child_nodes.is_empty() {
             todo!("should have children");
         }
-        let child = Arc::new(Group::from_list(&child_nodes));
+        // A lone child is rotated directly so nested rotations can compose into one node
+        let child: Arc<dyn Node> = match child_nodes.as_slice() {
+            [child] => child.clone(),
+            _ => Arc::new(Group::from_list(&child_nodes)),
+        };
 
         let arguments = self.convert_args(&["a", "v"], arguments)?;
 
@@ -302,17 +661,10 @@ impl Interpreter {
                 Value::Number(_deg_a) => todo!(),
                 Value::Vector { items } => {
                     let a = Value::values_to_vector3(items)?;
-                    let mut result: Arc<dyn Node> = child;
-                    if a.x != 0.0 {
-                        result = Arc::new(Rotate::rotate_x(result, a.x));
+                    if a == Vector3::ZERO {
+                        return Ok(child);
                     }
-                    if a.y != 0.0 {
-                        result = Arc::new(Rotate::rotate_y(result, a.y));
-                    }
-                    if a.z != 0.0 {
-                        result = Arc::new(Rotate::rotate_z(result, a.z));
-                    }
-                    return Ok(result);
+                    return Ok(Arc::new(Rotate::new_from_euler(child, a)));
                 }
                 _ => todo!("add error"),
             }
@@ -325,6 +677,85 @@ impl Interpreter {
         todo!();
     }
 
+    /// Rotates the children around a point instead of the origin, saving the
+    /// usual translate, rotate, translate back.
+    fn create_rotate_about(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<Arc<dyn Node>> {
+        let child: Arc<dyn Node> = match child_nodes.as_slice() {
+            [child] => child.clone(),
+            _ => Arc::new(Group::from_list(&child_nodes)),
+        };
+
+        let arguments = self.convert_args(&["a", "pt"], arguments)?;
+
+        let angles = match arguments.get("a").map(|arg| &arg.item) {
+            // A single angle turns around the Z axis, as with rotate()
+            Some(Value::Number(a)) => Value::values_to_vector3(&[
+                Value::Number(0.0),
+                Value::Number(0.0),
+                Value::Number(*a),
+            ])?,
+            Some(value) => value.to_vector3()?,
+            None => {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: "rotate_about requires an angle a".to_owned(),
+                    position: module_id.position.clone(),
+                });
+            }
+        };
+
+        let mut pivot = Vector3::ZERO;
+        if let Some(arg) = arguments.get("pt") {
+            pivot = arg.item.to_vector3()?;
+        }
+
+        if angles == Vector3::ZERO {
+            return Ok(child);
+        }
+        let rotation = Quaternion::from_euler(angles.x, angles.y, angles.z);
+        Ok(Arc::new(Rotate::new_about(child, rotation, pivot)))
+    }
+
+    /// Folds the children into boolean nodes. As in OpenSCAD, a difference
+    /// subtracts every other child from the first one.
+    fn create_csg(operation: CsgOperation, child_nodes: Vec<Arc<dyn Node>>) -> Vec<Arc<dyn Node>> {
+        let Some((first, rest)) = child_nodes.split_first() else {
+            return vec![];
+        };
+
+        let result = match operation {
+            CsgOperation::Union | CsgOperation::Intersection => {
+                Self::create_csg_tree(operation, &child_nodes)
+            }
+            CsgOperation::Difference if rest.is_empty() => first.clone(),
+            CsgOperation::Difference => Arc::new(Csg::new(
+                operation,
+                first.clone(),
+                Self::create_csg_tree(CsgOperation::Union, rest),
+            )),
+        };
+        vec![result]
+    }
+
+    /// Combines the nodes pairwise into a balanced tree, so a ray tests
+    /// log(n) levels of CSG nodes rather than n with a chain.
+    fn create_csg_tree(operation: CsgOperation, nodes: &[Arc<dyn Node>]) -> Arc<dyn Node> {
+        if let [node] = nodes {
+            return node.clone();
+        }
+        let (left, right) = nodes.split_at(nodes.len() / 2);
+        Arc::new(Csg::new(
+            operation,
+            Self::create_csg_tree(operation, left),
+            Self::create_csg_tree(operation, right),
+        ))
+    }
+
     fn create_scale(
         &mut self,
         arguments: &[CallArgumentWithPosition],
@@ -333,20 +764,92 @@ impl Interpreter {
         if child_nodes.is_empty() {
             todo!("should have children");
         }
-        let child = Arc::new(Group::from_list(&child_nodes));
 
         let arguments = self.convert_args(&["v"], arguments)?;
 
         if let Some(arg) = arguments.get("v") {
             let v = arg.item.to_vector3()?;
+
+            // A scaled sphere is an ellipsoid, which can compute exact normals itself
+            if let [child] = child_nodes.as_slice()
+                && let Some(ellipsoid) = Self::scale_to_ellipsoid(child, v)
+            {
+                return Ok(Arc::new(ellipsoid));
+            }
+
+            let child = Arc::new(Group::from_list(&child_nodes));
             return Ok(Arc::new(Scale::new(child, v.x, v.y, v.z)));
         }
 
         todo!("missing arg");
     }
 
+    fn create_animate(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<Arc<dyn Node>> {
+        if child_nodes.is_empty() {
+            todo!("should have children");
+        }
+        let mut child: Arc<dyn Node> = Arc::new(Group::from_list(&child_nodes));
+
+        let mut offset = Vector3::new(0.0, 0.0, 0.0);
+
+        let arguments = self.convert_args(&["v", "a"], arguments)?;
+
+        if let Some(arg) = arguments.get("v") {
+            offset = arg.item.to_vector3()?;
+        }
+
+        // Rotation happens about the origin before the move, same as rotate() inside translate()
+        if let Some(arg) = arguments.get("a") {
+            let a = arg.item.to_vector3()?;
+            child = Arc::new(Rotate::new_moving(
+                child,
+                Quaternion::IDENTITY,
+                Quaternion::from_euler(a.x, a.y, a.z),
+            ));
+        }
+
+        let animate = Translate::new_moving(child, Vector3::ZERO, offset);
+        Ok(Arc::new(animate))
+    }
+
+    fn scale_to_ellipsoid(node: &Arc<dyn Node>, v: Vector3) -> Option<Ellipsoid> {
+        // a flattened ellipsoid has a zero radius to divide by, the generic
+        // scale transform handles it
+        if [v.x, v.y, v.z].iter().any(|c| c.abs() <= 1e-9) {
+            return None;
+        }
+        let scale = |a: Vector3| Vector3::new(a.x * v.x, a.y * v.y, a.z * v.z);
+
+        if let Some(sphere) = node.as_any().downcast_ref::<Sphere>() {
+            let center = sphere.get_center(0.0);
+            // moving spheres keep the generic scale transform
+            if center != sphere.get_center(1.0) {
+                return None;
+            }
+            let radius = sphere.get_radius();
+            Some(Ellipsoid::new(
+                scale(center),
+                scale(Vector3::new(radius, radius, radius)),
+                sphere.material.clone(),
+            ))
+        } else {
+            node.as_any().downcast_ref::<Ellipsoid>().map(|ellipsoid| {
+                Ellipsoid::new(
+                    scale(*ellipsoid.get_center()),
+                    scale(*ellipsoid.get_radii()),
+                    ellipsoid.material.clone(),
+                )
+            })
+        }
+    }
+
     fn create_camera(
         &mut self,
+        module_id: &ModuleIdWithPosition,
         arguments: &[CallArgumentWithPosition],
         child_nodes: Vec<Arc<dyn Node>>,
     ) -> Result<()> {
@@ -368,6 +871,23 @@ impl Interpreter {
                 "focus_distance",
                 "background",
                 "aspect_ratio",
+                "lens_distortion",
+                "chromatic_aberration",
+                "environment",
+                "environment_intensity",
+                "environment_rotation",
+                "render_region",
+                "crop_window",
+                "projection",
+                "view_height",
+                "exposure",
+                "iso",
+                "f_number",
+                "shutter",
+                "firefly_clamp",
+                "min_pdf",
+                "caustic_photons",
+                "name",
             ],
             arguments,
         )?;
@@ -432,10 +952,544 @@ impl Interpreter {
         }
 
         if let Some(arg) = arguments.get("background") {
-            camera_builder.background = arg.item.to_color()?;
+            camera_builder.background = Arc::new(arg.item.to_color()?);
+        }
+
+        if let Some(arg) = arguments.get("environment") {
+            let position = &arg.position;
+            let filename = arg.item.to_unescaped_string()?;
+            self.record_asset(AssetKind::Image, &filename, position);
+            let image = self
+                .texture_registry
+                .get_or_load_image(&image_key(position, &filename), || {
+                    position.source.get_image(&filename)
+                })
+                .map_err(|err| Message {
+                    level: MessageLevel::Error,
+                    message: format!("failed to get environment \"{filename}\": {err}"),
+                    position: position.clone(),
+                })?;
+
+            let mut environment = EnvironmentMap::new(image);
+            if let Some(arg) = arguments.get("environment_intensity") {
+                environment = environment.with_intensity(arg.item.to_number()?);
+            }
+            if let Some(arg) = arguments.get("environment_rotation") {
+                environment = environment.with_rotation(arg.item.to_number()?);
+            }
+            camera_builder.background = Arc::new(environment);
+        }
+
+        if let Some(arg) = arguments.get("lens_distortion") {
+            camera_builder.lens_distortion = arg.item.to_number()?;
+        }
+
+        if let Some(arg) = arguments.get("chromatic_aberration") {
+            camera_builder.chromatic_aberration = arg.item.to_number()?;
+        }
+
+        if let Some(arg) = arguments.get("render_region") {
+            let region = match &arg.item {
+                Value::Vector { items } => values_to_numbers(items)?,
+                _ => vec![],
+            };
+            let [x, y, width, height] = region[..] else {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: "render_region must be [x, y, width, height]".to_owned(),
+                    position: arg.position.clone(),
+                });
+            };
+            camera_builder.render_region = Some(RenderRegion::new(
+                x.max(0.0) as u32,
+                y.max(0.0) as u32,
+                width.max(0.0) as u32,
+                height.max(0.0) as u32,
+            ));
+        }
+
+        if let Some(arg) = arguments.get("crop_window") {
+            let window = match &arg.item {
+                Value::Vector { items } => values_to_numbers(items)?,
+                _ => vec![],
+            };
+            let [xmin, xmax, ymin, ymax] = window[..] else {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: "crop_window must be [xmin, xmax, ymin, ymax]".to_owned(),
+                    position: arg.position.clone(),
+                });
+            };
+            camera_builder.crop_window = Some(CropWindow::new(xmin, xmax, ymin, ymax));
+        }
+
+        let settings = [
+            arguments.get("iso"),
+            arguments.get("f_number"),
+            arguments.get("shutter"),
+        ];
+        if let Some(arg) = arguments.get("exposure") {
+            if settings.iter().any(Option::is_some) {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: "exposure cannot be combined with iso, f_number or shutter".to_owned(),
+                    position: arg.position.clone(),
+                });
+            }
+            camera_builder.exposure = Exposure::Ev(arg.item.to_number()?);
+        } else if settings.iter().any(Option::is_some) {
+            // Zero or negative settings would give infinite or negative colors
+            let setting = |name: &str, default: f64| -> Result<f64> {
+                let Some(arg) = arguments.get(name) else {
+                    return Ok(default);
+                };
+                let value = arg.item.to_number()?;
+                if !value.is_finite() || value <= 0.0 {
+                    return Err(Message {
+                        level: MessageLevel::Error,
+                        message: format!("{name} must be a positive number"),
+                        position: arg.position.clone(),
+                    });
+                }
+                Ok(value)
+            };
+            camera_builder.exposure = Exposure::Camera {
+                iso: setting("iso", 100.0)?,
+                f_number: setting("f_number", 1.0)?,
+                shutter: setting("shutter", 1.0)?,
+            };
         }
 
-        self.camera = Some(Arc::new(camera_builder.build()));
+        if let Some(arg) = arguments.get("firefly_clamp") {
+            camera_builder.firefly_clamp = match arg.item {
+                Value::Boolean(false) => None,
+                _ => {
+                    let firefly_clamp = arg.item.to_number()?;
+                    if !firefly_clamp.is_finite() || firefly_clamp <= 0.0 {
+                        return Err(Message {
+                            level: MessageLevel::Error,
+                            message: "firefly_clamp must be a positive number or false".to_owned(),
+                            position: arg.position.clone(),
+                        });
+                    }
+                    Some(firefly_clamp)
+                }
+            };
+        }
+
+        if let Some(arg) = arguments.get("min_pdf") {
+            let min_pdf = arg.item.to_number()?;
+            if !min_pdf.is_finite() || min_pdf < 0.0 {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: "min_pdf must be a number of at least 0".to_owned(),
+                    position: arg.position.clone(),
+                });
+            }
+            camera_builder.min_pdf = min_pdf;
+        }
+
+        if let Some(arg) = arguments.get("caustic_photons") {
+            let caustic_photons = arg.item.to_number()?;
+            if caustic_photons > PhotonMap::MAX_PHOTONS as f64 {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: format!("caustic_photons must be at most {}", PhotonMap::MAX_PHOTONS),
+                    position: arg.position.clone(),
+                });
+            }
+            camera_builder.caustic_photons = caustic_photons.max(0.0) as u32;
+        }
+
+        if let Some(arg) = arguments.get("projection") {
+            match arg.item.to_unescaped_string()?.as_str() {
+                "perspective" => {}
+                "orthographic" => {
+                    // Without a view height, frame what the perspective camera
+                    // would show at the distance of look_at
+                    let view_height = if let Some(arg) = arguments.get("view_height") {
+                        arg.item.to_number()?
+                    } else {
+                        let distance = (camera_builder.look_at - camera_builder.look_from).length();
+                        2.0 * math::tan(camera_builder.vertical_fov.to_radians() / 2.0) * distance
+                    };
+                    camera_builder.projection = Projection::Orthographic { view_height };
+                }
+                other => {
+                    return Err(Message {
+                        level: MessageLevel::Error,
+                        message: format!(
+                            "unknown projection \"{other}\", expected \"perspective\" or \"orthographic\""
+                        ),
+                        position: arg.position.clone(),
+                    });
+                }
+            }
+        }
+
+        if let Some(arg) = arguments.get("name") {
+            let name = arg.item.to_unescaped_string()?;
+            if let Some(index) = self.named_cameras.iter().position(|(n, _)| *n == name) {
+                self.messages.push(Message {
+                    level: MessageLevel::Warning,
+                    message: format!(
+                        "camera \"{name}\" is defined more than once, the last one is used"
+                    ),
+                    position: module_id.position.clone(),
+                });
+                self.named_cameras[index].1 = camera_builder;
+            } else {
+                self.named_cameras.push((name, camera_builder));
+            }
+        } else {
+            if self.camera.is_some() {
+                self.messages.push(Message {
+                    level: MessageLevel::Warning,
+                    message: "camera is defined more than once, the last one is used, name cameras to keep several".to_owned(),
+                    position: module_id.position.clone(),
+                });
+            }
+            self.camera = Some(camera_builder);
+        }
+
+        Ok(())
+    }
+
+    /// Passes its children through, storing their bounding box in the variable
+    /// `name` as `[[xmin, ymin, zmin], [xmax, ymax, zmax]]`, or echoing it when
+    /// no name is given. Coordinates are rounded to thousandths.
+    fn create_bbox(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<Vec<Arc<dyn Node>>> {
+        let arguments = self.convert_args(&["name"], arguments)?;
+
+        let value = match child_nodes
+            .iter()
+            .map(|node| *node.bounding_box())
+            .reduce(|a, b| a.union(&b))
+        {
+            Some(bbox) => {
+                let x = bbox.axis_interval(Axis::X);
+                let y = bbox.axis_interval(Axis::Y);
+                let z = bbox.axis_interval(Axis::Z);
+                // Back to OpenSCAD coordinates, the x axis is flipped and z is up.
+                // Bounding boxes are padded a little for intersection tests, so
+                // round that away.
+                let round = |v: f64| Value::Number((v * 1000.0).round() / 1000.0 + 0.0);
+                let point = |x: f64, y: f64, z: f64| Value::Vector {
+                    items: vec![round(x), round(y), round(z)],
+                };
+                Value::Vector {
+                    items: vec![point(-x.max, z.min, y.min), point(-x.min, z.max, y.max)],
+                }
+            }
+            None => {
+                self.messages.push(Message {
+                    level: MessageLevel::Warning,
+                    message: "bbox has no children to measure".to_owned(),
+                    position: module_id.position.clone(),
+                });
+                Value::Undef
+            }
+        };
+
+        match arguments.get("name") {
+            Some(arg) => {
+                let name = arg.item.to_unescaped_string()?;
+                self.set_variable(&name, value);
+            }
+            None => self.messages.push(Message {
+                level: MessageLevel::Echo,
+                message: format!("bbox = {value}"),
+                position: module_id.position.clone(),
+            }),
+        }
+
+        Ok(child_nodes)
+    }
+
+    fn create_sky(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<()> {
+        if !child_nodes.is_empty() {
+            todo!("should not have children");
+        }
+
+        let arguments = self.convert_args(&["sun", "turbidity", "intensity"], arguments)?;
+
+        // [1, 1, 2] in OpenSCAD coordinates, high in the sky
+        let mut sun = Vector3::new(-1.0, 2.0, 1.0);
+        if let Some(arg) = arguments.get("sun") {
+            sun = arg.item.to_vector3()?;
+        }
+
+        let mut turbidity = 3.0;
+        if let Some(arg) = arguments.get("turbidity") {
+            turbidity = arg.item.to_number()?;
+        }
+
+        let mut sky = PreethamSky::new(sun, turbidity);
+        if let Some(arg) = arguments.get("intensity") {
+            sky = sky.with_intensity(arg.item.to_number()?);
+        }
+
+        self.background = Some(Arc::new(sky));
+
+        Ok(())
+    }
+
+    fn create_point_light(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<()> {
+        if !child_nodes.is_empty() {
+            todo!("should not have children");
+        }
+
+        let arguments = self.convert_args(&["position", "c", "intensity"], arguments)?;
+
+        let mut position = Vector3::ZERO;
+        if let Some(arg) = arguments.get("position") {
+            position = arg.item.to_vector3()?;
+        }
+
+        let intensity = Self::light_intensity(&arguments)?;
+        self.delta_lights
+            .push(Arc::new(PointLight::new(position, intensity)));
+
+        Ok(())
+    }
+
+    fn create_spot_light(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<()> {
+        if !child_nodes.is_empty() {
+            todo!("should not have children");
+        }
+
+        let arguments = self.convert_args(
+            &[
+                "position",
+                "target",
+                "c",
+                "intensity",
+                "angle",
+                "inner_angle",
+            ],
+            arguments,
+        )?;
+
+        let mut position = Vector3::ZERO;
+        if let Some(arg) = arguments.get("position") {
+            position = arg.item.to_vector3()?;
+        }
+
+        let mut target = Vector3::ZERO;
+        if let Some(arg) = arguments.get("target") {
+            target = arg.item.to_vector3()?;
+        }
+        // Both default to the origin, so a light given neither has no
+        // direction either
+        if (target - position).is_near_zero() {
+            let error_position = arguments
+                .get("target")
+                .or(arguments.get("position"))
+                .map_or(&module_id.position, |arg| &arg.position);
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "target must differ from position".to_owned(),
+                position: error_position.clone(),
+            });
+        }
+
+        let mut angle = SpotLight::DEFAULT_OUTER_ANGLE;
+        if let Some(arg) = arguments.get("angle") {
+            angle = arg.item.to_number()?;
+        }
+
+        let mut inner_angle = angle * 0.8;
+        if let Some(arg) = arguments.get("inner_angle") {
+            inner_angle = arg.item.to_number()?;
+            if inner_angle > angle {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: format!(
+                        "inner_angle must be at most angle {angle}, found {inner_angle}"
+                    ),
+                    position: arg.position.clone(),
+                });
+            }
+        }
+
+        let intensity = Self::light_intensity(&arguments)?;
+        self.delta_lights.push(Arc::new(
+            SpotLight::new(position, target - position, intensity).with_cone(inner_angle, angle),
+        ));
+
+        Ok(())
+    }
+
+    fn create_sun(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<()> {
+        if !child_nodes.is_empty() {
+            todo!("should not have children");
+        }
+
+        let arguments = self.convert_args(&["direction", "c", "intensity"], arguments)?;
+
+        // [1, 1, 2] in OpenSCAD coordinates, high in the sky
+        let mut direction = Vector3::new(-1.0, 2.0, 1.0);
+        if let Some(arg) = arguments.get("direction") {
+            direction = arg.item.to_vector3()?;
+        }
+
+        let irradiance = Self::light_intensity(&arguments)?;
+        self.delta_lights
+            .push(Arc::new(DirectionalLight::new(direction, irradiance)));
+
+        Ok(())
+    }
+
+    /// Returns the color `c` of a light times its `intensity`.
+    fn light_intensity(arguments: &HashMap<String, ValueWithPosition>) -> Result<Color> {
+        let mut color = Color::WHITE;
+        if let Some(arg) = arguments.get("c") {
+            color = arg.item.to_color()?;
+        }
+        if let Some(arg) = arguments.get("intensity") {
+            color = color * arg.item.to_number()?;
+        }
+        Ok(color)
+    }
+
+    fn create_sunlight(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+        child_nodes: Vec<Arc<dyn Node>>,
+    ) -> Result<()> {
+        if !child_nodes.is_empty() {
+            todo!("should not have children");
+        }
+
+        let arguments = self.convert_args(
+            &[
+                "lat",
+                "lon",
+                "date",
+                "time",
+                "timezone",
+                "turbidity",
+                "intensity",
+            ],
+            arguments,
+        )?;
+
+        let (Some(lat), Some(lon), Some(date), Some(time)) = (
+            arguments.get("lat"),
+            arguments.get("lon"),
+            arguments.get("date"),
+            arguments.get("time"),
+        ) else {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "sunlight requires lat, lon, date and time".to_owned(),
+                position: module_id.position.clone(),
+            });
+        };
+
+        let date_numbers = match &date.item {
+            Value::Vector { items } => values_to_numbers(items)?,
+            _ => vec![],
+        };
+        let [year, month, day] = date_numbers[..] else {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "date must be [year, month, day]".to_owned(),
+                position: date.position.clone(),
+            });
+        };
+        if !(1.0..=12.0).contains(&month) || !(1.0..=31.0).contains(&day) {
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: format!("invalid date [{year}, {month}, {day}]"),
+                position: date.position.clone(),
+            });
+        }
+
+        // Hours, or hours and minutes
+        let time_numbers = match &time.item {
+            Value::Vector { items } => values_to_numbers(items)?,
+            value => vec![value.to_number()?],
+        };
+        let hours = match time_numbers[..] {
+            [hours] => hours,
+            [hours, minutes] => hours + minutes / 60.0,
+            _ => {
+                return Err(Message {
+                    level: MessageLevel::Error,
+                    message: "time must be hours or [hours, minutes]".to_owned(),
+                    position: time.position.clone(),
+                });
+            }
+        };
+
+        let mut timezone = 0.0;
+        if let Some(arg) = arguments.get("timezone") {
+            timezone = arg.item.to_number()?;
+        }
+
+        let position = SolarPosition::new(
+            lat.item.to_number()?,
+            lon.item.to_number()?,
+            year as i32,
+            month as u32,
+            day as u32,
+            hours - timezone,
+        );
+        if position.elevation < 0.0 {
+            self.messages.push(Message {
+                level: MessageLevel::Warning,
+                message: format!(
+                    "the sun is {:.1} degrees below the horizon, the sky is lit as at sunrise",
+                    -position.elevation
+                ),
+                position: module_id.position.clone(),
+            });
+        }
+
+        // North is +y and east is +x in OpenSCAD coordinates
+        let (sin_azimuth, cos_azimuth) = math::sin_cos(position.azimuth.to_radians());
+        let (sin_elevation, cos_elevation) = math::sin_cos(position.elevation.to_radians());
+        let sun = Vector3::new(
+            -sin_azimuth * cos_elevation,
+            sin_elevation,
+            cos_azimuth * cos_elevation,
+        );
+
+        let mut turbidity = 3.0;
+        if let Some(arg) = arguments.get("turbidity") {
+            turbidity = arg.item.to_number()?;
+        }
+
+        let mut sky = PreethamSky::new(sun, turbidity);
+        if let Some(arg) = arguments.get("intensity") {
+            sky = sky.with_intensity(arg.item.to_number()?);
+        }
+
+        self.background = Some(Arc::new(sky));
 
         Ok(())
     }
@@ -490,7 +1544,39 @@ impl Interpreter {
         todo!("missing arg");
     }
 
-    fn create_lambertian(
+    /// Returns the material argument of `apply(m)` and `default_material(m)`,
+    /// usually a material stored in a variable.
+    fn material_argument(
+        &mut self,
+        module_id: &ModuleIdWithPosition,
+        arguments: &[CallArgumentWithPosition],
+    ) -> Result<Arc<dyn Material>> {
+        let arguments = self.convert_args(&["m"], arguments)?;
+
+        match arguments.get("m") {
+            Some(arg) => match &arg.item {
+                Value::Material(material) => Ok(material.clone()),
+                value => Err(Message {
+                    level: MessageLevel::Error,
+                    message: format!(
+                        "{} expects a material, e.g. {}(metal([0.9, 0.9, 0.9], 0.05)), found {value}",
+                        module_id.item, module_id.item
+                    ),
+                    position: arg.position.clone(),
+                }),
+            },
+            None => Err(Message {
+                level: MessageLevel::Error,
+                message: format!(
+                    "{} expects a material, e.g. {}(metal([0.9, 0.9, 0.9], 0.05))",
+                    module_id.item, module_id.item
+                ),
+                position: module_id.position.clone(),
+            }),
+        }
+    }
+
+    pub(super) fn create_lambertian(
         &mut self,
         arguments: &[CallArgumentWithPosition],
     ) -> Result<Arc<dyn Material>> {
@@ -509,21 +1595,29 @@ impl Interpreter {
         }
     }
 
-    fn create_dielectric(
+    pub(super) fn create_dielectric(
         &mut self,
         arguments: &[CallArgumentWithPosition],
     ) -> Result<Arc<dyn Material>> {
-        let arguments = self.convert_args(&["n"], arguments)?;
+        let arguments = self.convert_args(&["n", "c", "distance"], arguments)?;
 
-        if let Some(arg) = arguments.get("n") {
-            let refraction_index = arg.item.to_number()?;
-            Ok(Arc::new(Dielectric::new(refraction_index)))
-        } else {
+        let Some(arg) = arguments.get("n") else {
             todo!("missing arg");
+        };
+        let mut dielectric = Dielectric::new(arg.item.to_number()?);
+
+        if let Some(arg) = arguments.get("c") {
+            let mut distance = 1.0;
+            if let Some(arg) = arguments.get("distance") {
+                distance = arg.item.to_number()?;
+            }
+            dielectric = dielectric.with_absorption(arg.item.to_color()?, distance);
         }
+
+        Ok(Arc::new(dielectric))
     }
 
-    fn create_metal(
+    pub(super) fn create_metal(
         &mut self,
         arguments: &[CallArgumentWithPosition],
     ) -> Result<Arc<dyn Material>> {
@@ -543,18 +1637,149 @@ impl Interpreter {
         Ok(Arc::new(Metal::new(color, fuzz)))
     }
 
-    fn create_diffuse_light(
+    pub(super) fn create_diffuse_light(
         &mut self,
         arguments: &[CallArgumentWithPosition],
     ) -> Result<Arc<dyn Material>> {
-        let arguments = self.convert_args(&["c"], arguments)?;
+        let arguments = self.convert_args(&["c", "intensity", "temperature"], arguments)?;
 
-        let mut color = Color::WHITE;
+        let mut light = DiffuseLight::new_from_color(Color::WHITE);
 
         if let Some(arg) = arguments.get("c") {
-            color = arg.item.to_color()?;
+            light = match &arg.item {
+                Value::Texture(texture) => DiffuseLight::new(texture.clone()),
+                value => DiffuseLight::new_from_color(value.to_color()?),
+            };
+        }
+
+        if let Some(arg) = arguments.get("intensity") {
+            light = light.with_intensity(arg.item.to_number()?);
+        }
+
+        if let Some(arg) = arguments.get("temperature") {
+            light = light.with_temperature(arg.item.to_number()?);
+        }
+
+        Ok(Arc::new(light))
+    }
+
+    pub(super) fn create_sheen(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+    ) -> Result<Arc<dyn Material>> {
+        let arguments = self.convert_args(&["c", "sheen_color", "roughness"], arguments)?;
+
+        let mut material = Sheen::new_from_color(Color::WHITE);
+
+        if let Some(arg) = arguments.get("c") {
+            material = match &arg.item {
+                Value::Texture(texture) => Sheen::new(texture.clone()),
+                value => Sheen::new_from_color(value.to_color()?),
+            };
+        }
+
+        if let Some(arg) = arguments.get("sheen_color") {
+            material = material.with_sheen_color(arg.item.to_color()?);
+        }
+
+        if let Some(arg) = arguments.get("roughness") {
+            material = material.with_roughness(arg.item.to_number()?);
+        }
+
+        Ok(Arc::new(material))
+    }
+
+    pub(super) fn create_toon(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+    ) -> Result<Arc<dyn Material>> {
+        let arguments = self.convert_args(&["c", "bands", "outline"], arguments)?;
+
+        let mut material = Toon::new_from_color(Color::WHITE);
+
+        if let Some(arg) = arguments.get("c") {
+            material = match &arg.item {
+                Value::Texture(texture) => Toon::new(texture.clone()),
+                value => Toon::new_from_color(value.to_color()?),
+            };
+        }
+
+        if let Some(arg) = arguments.get("bands") {
+            material = material.with_bands(arg.item.to_u64()? as u32);
+        }
+
+        if let Some(arg) = arguments.get("outline") {
+            material = material.with_outline(arg.item.to_number()?);
+        }
+
+        Ok(Arc::new(material))
+    }
+
+    pub(super) fn create_pbr(
+        &mut self,
+        arguments: &[CallArgumentWithPosition],
+    ) -> Result<Arc<dyn Material>> {
+        let arguments = self.convert_args(
+            &[
+                "base_color",
+                "metallic",
+                "roughness",
+                "specular",
+                "transmission",
+                "ior",
+                "emission",
+                "sheen",
+                "sheen_tint",
+                "sheen_roughness",
+            ],
+            arguments,
+        )?;
+
+        let mut material = Principled::new_from_color(Color::WHITE);
+
+        if let Some(arg) = arguments.get("base_color") {
+            material = match &arg.item {
+                Value::Texture(texture) => Principled::new(texture.clone()),
+                value => Principled::new_from_color(value.to_color()?),
+            };
+        }
+
+        if let Some(arg) = arguments.get("metallic") {
+            material = material.with_metallic(arg.item.to_number()?);
+        }
+
+        if let Some(arg) = arguments.get("roughness") {
+            material = material.with_roughness(arg.item.to_number()?);
+        }
+
+        if let Some(arg) = arguments.get("specular") {
+            material = material.with_specular(arg.item.to_number()?);
+        }
+
+        if let Some(arg) = arguments.get("transmission") {
+            material = material.with_transmission(arg.item.to_number()?);
+        }
+
+        if let Some(arg) = arguments.get("ior") {
+            material = material.with_ior(arg.item.to_number()?);
+        }
+
+        if let Some(arg) = arguments.get("emission") {
+            material = material.with_emission(arg.item.to_color()?);
+        }
+
+        if let Some(arg) = arguments.get("sheen") {
+            material = material.with_sheen(arg.item.to_number()?);
+        }
+
+        if let Some(arg) = arguments.get("sheen_tint") {
+            material = material.with_sheen_tint(arg.item.to_color()?);
+        }
+
+        if let Some(arg) = arguments.get("sheen_roughness") {
+            material = material.with_sheen_roughness(arg.item.to_number()?);
         }
 
-        Ok(Arc::new(DiffuseLight::new_from_color(color)))
+        Ok(Arc::new(material))
     }
 }
@@ -3,12 +3,25 @@ mod tests {
     use std::sync::Arc;
 
     use caustic_core::{
-        object::{BoundingVolumeHierarchy, Disc},
+        Axis, Color, Image, Interval, Ray, RenderContext, RenderRegion, Vector3,
+        image::ImageError,
+        light::{GeometryLight, LightList, PointLight},
+        material::{Material, MaterialParameters},
+        object::{
+            BoundingVolumeHierarchy, ConeFrustum, ConvertedMesh, Csg, CsgOperation, Disc,
+            Ellipsoid, Node, Rotate, Scale, Sphere, Translate,
+        },
         random_new,
     };
 
     use crate::{
-        interpreter::{InterpreterResults, openscad_interpret},
+        MessageLevel,
+        interpreter::{
+            AssetKind, InterpreterLimits, InterpreterResults, openscad_interpret,
+            openscad_interpret_at_time, openscad_interpret_with_editable_materials,
+            openscad_interpret_with_limits,
+        },
+        library::LibraryPath,
         parser::openscad_parse,
         source::{Source, StringSource},
         tokenizer::openscad_tokenize,
@@ -60,6 +73,557 @@ mod tests {
         assert_eq!(disc.get_radius(), 20.0);
     }
 
+    #[test]
+    fn test_circle_ring() {
+        let results = interpret("circle(r=20, inner_r=10, angle=90);");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let disc = left.as_any().downcast_ref::<Disc>().unwrap();
+        assert_eq!(disc.get_inner_radius(), 10.0);
+        assert!((disc.get_sweep_angle() - 90.0).abs() < 1e-9);
+
+        let results = interpret("circle(r=20, inner_r=30);");
+        assert_eq!(results.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_pbr() {
+        let results =
+            interpret("pbr(base_color=[1, 0.78, 0.34], metallic=1, roughness=0.3) sphere(r=1);");
+        assert_eq!(results.messages.len(), 0);
+
+        let results = interpret("pbr([0.8, 0.1, 0.1], 0, 0.2, transmission=1, ior=1.5) cube(1);");
+        assert_eq!(results.messages.len(), 0);
+    }
+
+    #[test]
+    fn test_transformed_lights() {
+        let results = interpret(
+            "diffuse_light(4) { translate([0, 0, 10]) rotate([90, 0, 0]) sphere(r=1); scale(2) translate([5, 0, 0]) sphere(r=1); } sphere(r=5); difference() { diffuse_light(4) sphere(r=1); cube(1); }",
+        );
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let lights = scene_data.lights.expect("expected lights");
+        let list = lights.as_any().downcast_ref::<LightList>().unwrap();
+        assert_eq!(list.len(), 2);
+        let nodes: Vec<_> = list
+            .get_lights()
+            .iter()
+            .map(|light| {
+                light
+                    .as_any()
+                    .downcast_ref::<GeometryLight>()
+                    .unwrap()
+                    .get_node()
+            })
+            .collect();
+
+        let translate = nodes[0].as_any().downcast_ref::<Translate>().unwrap();
+        assert!(translate.get_object().as_any().is::<Rotate>());
+        assert!(nodes[1].as_any().is::<Scale>());
+
+        // Directions towards the moved light are sampled, not towards the origin
+        let ctx = RenderContext {
+            random: random_new(),
+        };
+        let origin = Vector3::new(20.0, 10.0, 0.0);
+        let direction = lights.sample_direction(&ctx, &origin);
+        assert!(lights.pdf_value(&ctx, &origin, &direction) > 0.0);
+    }
+
+    #[test]
+    fn test_sheen() {
+        let results = interpret(
+            "sheen([0.4, 0.05, 0.1], sheen_color=[1, 0.6, 0.7]) sphere(r=1); pbr(sheen=1, sheen_tint=[0.6, 0.7, 1]) cube(1);",
+        );
+        assert_eq!(results.messages.len(), 0);
+    }
+
+    #[test]
+    fn test_toon() {
+        let results = interpret("toon([0.9, 0.3, 0.2], bands=4, outline=0.3) sphere(r=1);");
+        assert_eq!(results.messages.len(), 0);
+    }
+
+    #[test]
+    fn test_material_variable() {
+        let results = interpret(
+            r#"
+            m = metal([0.9, 0.9, 0.9], 0.05);
+            light = diffuse_light(4);
+            apply(m) sphere(1);
+            apply(light) translate([0, 5, 0]) sphere(1);
+            echo(m == m, m == light);
+            "#,
+        );
+        assert_eq!(results.messages.len(), 1);
+        assert_eq!(results.messages[0].message, "true, false");
+        assert!(results.scene_data.unwrap().lights.is_some());
+
+        let results = interpret("apply([1, 0, 0]) sphere(1);");
+        assert_eq!(results.messages.len(), 1);
+        assert_eq!(results.messages[0].level, MessageLevel::Error);
+
+        let results = interpret("m = metal([0.9, 0.9, 0.9], 0.05); echo(m < 1);");
+        assert_eq!(results.messages.len(), 1);
+        assert_eq!(results.messages[0].level, MessageLevel::Error);
+        assert_eq!(
+            results.messages[0].message,
+            "operator < is not supported between a material and a number"
+        );
+    }
+
+    #[test]
+    fn test_operators_on_other_types() {
+        assert_output_trim(
+            r#"echo("a" < "b", "b" <= "a", true == true, undef == undef, "a" != "b", undef == 0);"#,
+            "true, false, true, true, true, false",
+        );
+
+        for source in [r#"echo("a" + 1);"#, "echo(-true);", "echo(true < false);"] {
+            let results = interpret(source);
+            assert_eq!(results.messages.len(), 1, "{source}");
+            assert_eq!(results.messages[0].level, MessageLevel::Error, "{source}");
+        }
+    }
+
+    #[test]
+    fn test_default_material() {
+        let results = interpret(
+            r#"
+            default_material(diffuse_light(4));
+            sphere(1);
+            "#,
+        );
+        assert_eq!(results.messages.len(), 0);
+        assert!(results.scene_data.unwrap().lights.is_some());
+
+        // Only the child of reset_material uses the default light
+        let results = interpret(
+            r#"
+            default_material(diffuse_light(4));
+            lambertian([0.5, 0.5, 0.5]) {
+                sphere(1);
+                reset_material() translate([5, 0, 0]) sphere(1);
+            }
+            reset_material();
+            sphere(1);
+            "#,
+        );
+        assert_eq!(results.messages.len(), 0);
+        let lights = results.scene_data.unwrap().lights.unwrap();
+        let list = lights.as_any().downcast_ref::<LightList>().unwrap();
+        assert_eq!(list.len(), 1);
+        let light = list.get_lights()[0]
+            .as_any()
+            .downcast_ref::<GeometryLight>()
+            .unwrap();
+        let x = light.get_node().bounding_box().axis_interval(Axis::X);
+        assert_eq!((x.min, x.max), (-6.0, -4.0));
+
+        let results = interpret("default_material(metal()) sphere(1);");
+        assert_eq!(results.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_light_temperature() {
+        let results = interpret("diffuse_light(4, temperature=3200) sphere(r=1);");
+        assert_eq!(results.messages.len(), 0);
+        assert!(results.scene_data.unwrap().lights.is_some());
+    }
+
+    #[test]
+    fn test_gradient() {
+        let results = interpret(
+            r#"
+            lambertian(t=gradient(stops=[[0, [1, 0.5, 0]], [1, [0, 0, 0.5]]], end=[0, 0, 10])) cube(10);
+            lambertian(t=gradient(type="radial", radius=5)) sphere(r=5);
+            "#,
+        );
+        assert_eq!(results.messages.len(), 0);
+
+        let results = interpret(r#"lambertian(t=gradient(type="conic")) cube(1);"#);
+        assert_eq!(results.messages.len(), 1);
+
+        for stops in [
+            "[[0, white]]",
+            r#"[[0, "red"]]"#,
+            r#"[["a", [1, 1, 1]]]"#,
+            "[[0, [1, 1]]]",
+            "[[0]]",
+            "[0, 1]",
+        ] {
+            let results = interpret(&format!("lambertian(t=gradient(stops={stops})) cube(1);"));
+            // An unknown variable such as white is also warned about
+            let error = results.messages.last().unwrap();
+            assert_eq!(error.level, MessageLevel::Error, "{stops}");
+            assert_eq!(
+                error.message,
+                "gradient stops must be a list of [position, color] pairs"
+            );
+        }
+    }
+
+    #[test]
+    fn test_coincident_objects_warning() {
+        let results = interpret("for (i = [0:99]) sphere(r=1);");
+        assert_eq!(results.messages.len(), 1);
+        assert_eq!(results.messages[0].level, MessageLevel::Warning);
+        assert!(results.messages[0].message.contains("same bounding box"));
+
+        let results = interpret("for (i = [0:99]) translate([i * 3, 0, 0]) sphere(r=1);");
+        assert_eq!(results.messages.len(), 0);
+    }
+
+    #[test]
+    fn test_unsupported_features() {
+        let results = interpret(
+            r#"
+            union() {
+                cube(10);
+                minkowski() { cube(5); sphere(1); }
+                translate([20, 0, 0]) sphere(2);
+            }
+            for (i = [0:3]) hull() cube(1);
+            echo(len([1, 2]));
+            "#,
+        );
+        let names: Vec<_> = results
+            .unsupported_features
+            .iter()
+            .map(|feature| (feature.name.as_str(), feature.positions.len()))
+            .collect();
+        assert_eq!(names, vec![("minkowski", 1), ("hull", 1), ("len", 1)]);
+
+        let warnings: Vec<_> = results
+            .messages
+            .iter()
+            .filter(|message| message.level == MessageLevel::Warning)
+            .collect();
+        assert_eq!(warnings.len(), 3);
+        assert!(warnings[0].message.contains("\"minkowski\""));
+        assert!(
+            results
+                .messages
+                .iter()
+                .any(|message| message.level == MessageLevel::Echo && message.message == "undef")
+        );
+
+        // The rest of the union is still rendered
+        let world = results.scene_data.unwrap().world;
+        assert!(world.bounding_box().axis_interval(Axis::X).min < -20.0);
+    }
+
+    #[test]
+    fn test_let_module() {
+        assert_output_trim("let(a=1, b=a * 2) echo(b);", "2");
+
+        // The variables are gone after the children
+        let results = interpret("let(a=1) sphere(r=a); echo(a);");
+        assert_eq!(results.messages.len(), 2);
+        assert_eq!(results.messages[0].level, MessageLevel::Warning);
+        assert_eq!(results.messages[1].message, "undef");
+    }
+
+    #[test]
+    fn test_deprecated_module_alias() {
+        let results =
+            interpret("for (i = [0:3]) assign(x=i * 3) translate([x, 0, 0]) sphere(r=1);");
+        assert_eq!(results.messages.len(), 1);
+        assert_eq!(results.messages[0].level, MessageLevel::Warning);
+        assert_eq!(
+            results.messages[0].message,
+            "\"assign\" is deprecated, use \"let\" instead"
+        );
+        let world = results.scene_data.unwrap().world;
+        assert!(world.bounding_box().axis_interval(Axis::X).min < -6.0);
+
+        // Aliases of unsupported modules are reported under the current name
+        let results = interpret(r#"dxf_linear_extrude("part.dxf");"#);
+        assert_eq!(results.messages.len(), 2);
+        assert_eq!(results.unsupported_features[0].name, "linear_extrude");
+    }
+
+    #[test]
+    fn test_voronoi() {
+        let results = interpret(
+            r#"
+            lambertian(t=voronoi(2)) cube(10);
+            lambertian(t=voronoi(0.5, feature="f2-f1", metric="manhattan", low=[0.1, 0.1, 0.1])) sphere(r=5);
+            "#,
+        );
+        assert_eq!(results.messages.len(), 0);
+
+        let results = interpret(r#"lambertian(t=voronoi(feature="f3")) cube(1);"#);
+        assert_eq!(results.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_triplanar() {
+        let results = interpret(
+            r#"
+            lambertian(t=triplanar(checker(scale=0.2), scale=2, sharpness=8)) cube(10);
+            lambertian(t=triplanar([1, 0, 0])) sphere(r=5);
+            "#,
+        );
+        assert_eq!(results.messages.len(), 0);
+
+        let results = interpret("lambertian(t=triplanar(scale=2)) cube(1);");
+        assert_eq!(results.messages.len(), 1);
+
+        let results = interpret("lambertian(t=triplanar([1, 0, 0], scale=0)) cube(1);");
+        assert_eq!(results.messages.len(), 1);
+        assert!(
+            results.messages[0]
+                .message
+                .contains("scale must be a positive number")
+        );
+    }
+
+    #[test]
+    fn test_colored_dielectric() {
+        let results = interpret("dielectric(1.5, c=[0.2, 0.8, 0.3], distance=2) sphere(r=1);");
+        assert_eq!(results.messages.len(), 0);
+    }
+
+    #[test]
+    fn test_cylinder_options() {
+        let results = interpret("cylinder(h=2, r1=2, r2=1, capped=false, smooth=false, $fn=6);");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let cylinder = left.as_any().downcast_ref::<ConeFrustum>().unwrap();
+        assert_eq!(cylinder.get_bottom_radius(), 2.0);
+        assert_eq!(cylinder.get_top_radius(), 1.0);
+        assert!(!cylinder.has_top_cap());
+        assert!(!cylinder.has_bottom_cap());
+        assert_eq!(cylinder.get_facets(), 6);
+
+        // $fa and $fs give 30 sides to a large cylinder
+        let results = interpret("cylinder(h=2, r=100, smooth=false);");
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let cylinder = left.as_any().downcast_ref::<ConeFrustum>().unwrap();
+        assert_eq!(cylinder.get_facets(), 30);
+    }
+
+    #[test]
+    fn test_rotate_about() {
+        let results = interpret("rotate_about(a=90, pt=[1, 2, 3]) cube(1);");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let rotate = left.as_any().downcast_ref::<Rotate>().unwrap();
+        assert_eq!(rotate.get_pivot(), Vector3::new(-1.0, 3.0, 2.0));
+    }
+
+    #[test]
+    fn test_moving_sphere() {
+        let results = interpret("moving_sphere(from=[0, 0, 0], to=[0, 0, 2], r=1);");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let sphere = left.as_any().downcast_ref::<Sphere>().unwrap();
+        assert_eq!(sphere.get_radius(), 1.0);
+        assert_eq!(sphere.get_center(0.0), Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(sphere.get_center(1.0), Vector3::new(0.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn test_animate() {
+        let results = interpret("animate([1, 0, 0]) sphere(r=1);");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let animate = left.as_any().downcast_ref::<Translate>().unwrap();
+        assert_eq!(animate.get_offset(0.0), Vector3::new(0.0, 0.0, 0.0));
+        assert_eq!(animate.get_offset(1.0), Vector3::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_animate_rotation() {
+        let results = interpret("animate(a=[0, 0, 90]) cube(1);");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let animate = left.as_any().downcast_ref::<Translate>().unwrap();
+        let rotate = animate.get_object();
+        let rotate = rotate.as_any().downcast_ref::<Rotate>().unwrap();
+        assert!(rotate.is_moving());
+        let (_axis, angle) = rotate.get_rotation_at(0.5).to_axis_angle();
+        assert!((angle - 45.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_scale_sphere_is_ellipsoid() {
+        let results = interpret("scale([2, 1, 3]) sphere(1);");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let ellipsoid = left.as_any().downcast_ref::<Ellipsoid>().unwrap();
+        assert_eq!(*ellipsoid.get_radii(), Vector3::new(2.0, 3.0, 1.0));
+    }
+
+    #[test]
+    fn test_scale_sphere_to_zero_keeps_scale() {
+        let results = interpret("scale([2, 0, 3]) sphere(1);");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        assert!(left.as_any().downcast_ref::<Scale>().is_some());
+    }
+
+    #[test]
+    fn test_scale_light_sphere_keeps_light() {
+        for code in [
+            "diffuse_light(4) scale(2) sphere(1);",
+            "diffuse_light(4) scale([2, 1, 3]) sphere(1);",
+        ] {
+            let results = interpret(code);
+            assert_eq!(results.messages.len(), 0);
+            let lights = results.scene_data.unwrap().lights.expect("expected lights");
+            let list = lights.as_any().downcast_ref::<LightList>().unwrap();
+            assert_eq!(list.len(), 1, "{code}");
+
+            // Directions sampled towards the light have a density and most
+            // of them find it
+            let ctx = RenderContext::new_seeded(1);
+            let origin = Vector3::new(0.0, -10.0, 0.0);
+            let mut hits = 0;
+            for _ in 0..100 {
+                let direction = lights.sample_direction(&ctx, &origin);
+                assert!(lights.pdf_value(&ctx, &origin, &direction) > 0.0);
+                if lights.emitted(&ctx, &origin, &direction).r > 0.0 {
+                    hits += 1;
+                }
+            }
+            assert!(hits > 50, "{code}: {hits}");
+        }
+    }
+
+    #[test]
+    fn test_nested_rotate_composes() {
+        let results = interpret("rotate([0, 0, 30]) rotate([0, 0, 60]) cube(1);");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let rotate = left.as_any().downcast_ref::<Rotate>().unwrap();
+        assert!(
+            rotate
+                .get_object()
+                .as_any()
+                .downcast_ref::<Rotate>()
+                .is_none()
+        );
+        let (_axis, angle) = rotate.get_rotation().to_axis_angle();
+        assert!((angle - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_difference() {
+        let results =
+            interpret("difference() { cube(10); sphere(7); translate([10, 0, 0]) sphere(2); }");
+        assert_eq!(results.messages.len(), 0);
+
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let csg = left.as_any().downcast_ref::<Csg>().unwrap();
+        assert_eq!(csg.get_operation(), CsgOperation::Difference);
+        let rest = csg.get_right();
+        let rest = rest.as_any().downcast_ref::<Csg>().unwrap();
+        assert_eq!(rest.get_operation(), CsgOperation::Union);
+    }
+
+    #[test]
+    fn test_union_is_balanced() {
+        let results = interpret("union() { for (i = [0:7]) translate([i * 3, 0, 0]) cube(1); }");
+        assert_eq!(results.messages.len(), 0);
+
+        fn depth(node: &Arc<dyn Node>) -> usize {
+            match node.as_any().downcast_ref::<Csg>() {
+                Some(csg) => 1 + depth(&csg.get_left()).max(depth(&csg.get_right())),
+                None => 0,
+            }
+        }
+        let scene_data = results.scene_data.unwrap();
+        let bvh = scene_data
+            .world
+            .as_any()
+            .downcast_ref::<BoundingVolumeHierarchy>()
+            .unwrap();
+        let left = bvh.get_left();
+        let csg = left.as_any().downcast_ref::<Csg>().unwrap();
+        assert_eq!(csg.get_operation(), CsgOperation::Union);
+        assert_eq!(depth(&csg.get_left()), 2);
+        assert_eq!(depth(&csg.get_right()), 2);
+    }
+
     // -- special variables ----------------------------
 
     #[test]
@@ -326,6 +890,24 @@ mod tests {
         assert_eq!(0, result.messages.len());
     }
 
+    #[test]
+    fn test_rands_seed() {
+        // std::mt19937 seeded with 42 starts with 1608637542, 3421126067
+        let expected = (1608637542.0 + 3421126067.0 * 4294967296.0) / 18446744073709551616.0;
+        assert_output_trim(
+            "echo(rands(0, 1, 1, 42)[0] == rands(0, 1, 1, 42)[0]);",
+            "true",
+        );
+        assert_output_trim(
+            &format!("echo(abs(rands(0, 1, 2, 42)[0] - {expected}) < 1e-12);"),
+            "true",
+        );
+        assert_output_trim(
+            "a = rands(0, 10, 3); b = rands(0, 10, 3); echo(a == b);",
+            "false",
+        );
+    }
+
     // -- function ----------------------------
 
     #[test]
@@ -565,4 +1147,569 @@ mod tests {
         assert_output_trim(r#"echo(cross([2, 1, -3], [4, 5]));"#, "undef");
         assert_output_trim(r#"echo(cross([2, 3, 4], "5"));"#, "undef");
     }
+
+    #[test]
+    fn test_asset_manifest() {
+        let result = interpret(
+            r#"
+            include <caustic.scad>;
+            lambertian(image("wood.png")) cube(1);
+            surface(file="terrain.png");
+            "#,
+        );
+        let assets: Vec<(AssetKind, &str)> = result
+            .assets
+            .iter()
+            .map(|asset| (asset.kind, asset.filename.as_str()))
+            .collect();
+        assert_eq!(
+            assets,
+            vec![
+                (AssetKind::Image, "wood.png"),
+                (AssetKind::Image, "terrain.png")
+            ]
+        );
+        // Both images are missing, each is reported at its call
+        assert_eq!(crate::find_missing_assets(&result.assets).len(), 2);
+    }
+
+    /// A source holding one converted mesh, a tetrahedron with a 2 unit long
+    /// edge along x
+    #[derive(Debug)]
+    struct MeshSource {
+        code: String,
+    }
+
+    impl Source for MeshSource {
+        fn get_filename(&self) -> &str {
+            "mesh"
+        }
+
+        fn get_code(&self) -> &str {
+            &self.code
+        }
+
+        fn get_image(&self, filename: &str) -> Result<Arc<dyn Image>, ImageError> {
+            Err(ImageError::NotFound(filename.to_owned()))
+        }
+
+        fn get_converted_mesh(&self, filename: &str) -> Result<Vec<u8>, String> {
+            if filename != "tetrahedron.stl" {
+                return Err(format!("\"{filename}\" not found"));
+            }
+            let mut data = ConvertedMesh::MAGIC.to_vec();
+            for count in [4u32, 4, 0] {
+                data.extend_from_slice(&count.to_le_bytes());
+            }
+            for v in [
+                0.0f64, 0.0, 0.0, 2.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0,
+            ] {
+                data.extend_from_slice(&v.to_le_bytes());
+            }
+            for i in [0u32, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3] {
+                data.extend_from_slice(&i.to_le_bytes());
+            }
+            Ok(data)
+        }
+
+        fn has_file(&self, filename: &str) -> bool {
+            filename == "tetrahedron.stl"
+        }
+
+        fn as_any(&self) -> &dyn std::any::Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_import() {
+        let source: Arc<Box<dyn Source>> = Arc::new(Box::new(MeshSource {
+            code: r#"import("tetrahedron.stl"); import(file="missing.stl");"#.to_owned(),
+        }));
+        let tokens = openscad_tokenize(source.clone()).tokens.unwrap();
+        let statements = openscad_parse(tokens, source).statements.unwrap();
+        let result = openscad_interpret(statements, random_new());
+
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(
+            result.messages[0].message,
+            "failed to import mesh \"missing.stl\": \"missing.stl\" not found"
+        );
+        let world = result.scene_data.unwrap().world;
+        // Mirrored like every other shape
+        let x = world.bounding_box().axis_interval(Axis::X);
+        assert!((x.min + 2.0).abs() < 1e-3 && x.max.abs() < 1e-3);
+
+        // The winding still faces out after mirroring
+        let ctx = RenderContext::new_seeded(1);
+        let up = Ray::new(Vector3::new(-0.2, 0.2, -5.0), Vector3::new(0.0, 0.0, 1.0));
+        let hit = world
+            .hit(&ctx, &up, Interval::new(0.001, f64::INFINITY))
+            .unwrap();
+        assert!(hit.front_face);
+        assert_eq!(hit.normal, Vector3::new(0.0, 0.0, -1.0));
+
+        let result = interpret(r#"import("part.stl");"#);
+        assert_eq!(
+            result.messages[0].message,
+            "failed to import mesh \"part.stl\": \"part.stl\" (meshes can only be imported in the web app)"
+        );
+    }
+
+    #[test]
+    fn test_surface_errors() {
+        for (code, message) in [
+            ("surface();", "surface requires a file"),
+            (
+                r#"surface(file="terrain.png") cube(1);"#,
+                "surface does not take children",
+            ),
+        ] {
+            let result = interpret(code);
+            assert_eq!(result.messages.len(), 1, "{code}");
+            assert_eq!(result.messages[0].level, MessageLevel::Error);
+            assert_eq!(result.messages[0].message, message);
+        }
+    }
+
+    #[test]
+    fn test_camera_environment() {
+        let result = interpret(r#"camera(environment="sky.hdr", environment_rotation=90);"#);
+        let assets: Vec<(AssetKind, &str)> = result
+            .assets
+            .iter()
+            .map(|asset| (asset.kind, asset.filename.as_str()))
+            .collect();
+        assert_eq!(assets, vec![(AssetKind::Image, "sky.hdr")]);
+        // The environment map is missing
+        assert_eq!(result.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_camera_render_region() {
+        let result = interpret("camera(render_region=[10, 20, 30, 40]);");
+        assert_eq!(result.messages.len(), 0);
+        let camera = result.scene_data.unwrap().camera;
+        assert_eq!(
+            camera.render_region(),
+            Some(RenderRegion::new(10, 20, 30, 40))
+        );
+        assert!(camera.is_rendered(10, 20));
+        assert!(!camera.is_rendered(0, 0));
+
+        let result = interpret("camera(render_region=[10, 20]);");
+        assert_eq!(result.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_camera_crop_window() {
+        let result = interpret("camera(image_width=200, crop_window=[0.5, 1, 0.25, 0.5]);");
+        assert_eq!(result.messages.len(), 0);
+        let camera = result.scene_data.unwrap().camera;
+        assert_eq!(
+            camera.render_region(),
+            Some(RenderRegion::new(100, 50, 100, 50))
+        );
+
+        let result = interpret("camera(crop_window=[0.5, 1]);");
+        assert_eq!(result.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_camera_exposure() {
+        let result = interpret("camera(exposure=-2);");
+        assert_eq!(result.messages.len(), 0);
+        assert_eq!(result.scene_data.unwrap().camera.exposure_scale(), 0.25);
+
+        let result = interpret("camera(iso=400, f_number=4);");
+        assert_eq!(result.messages.len(), 0);
+        assert_eq!(result.scene_data.unwrap().camera.exposure_scale(), 0.25);
+
+        let result = interpret("camera(exposure=1, shutter=0.5);");
+        assert_eq!(result.messages.len(), 1);
+
+        // The documented example is close to the default exposure
+        let result = interpret("camera(iso=200, f_number=1.4, shutter=1);");
+        let scale = result.scene_data.unwrap().camera.exposure_scale();
+        assert!((scale - 1.0).abs() < 0.05);
+
+        for (code, message) in [
+            ("camera(f_number=0);", "f_number must be a positive number"),
+            ("camera(shutter=-1);", "shutter must be a positive number"),
+            ("camera(iso=0, shutter=1);", "iso must be a positive number"),
+        ] {
+            let result = interpret(code);
+            assert_eq!(result.messages.len(), 1, "{code}");
+            assert_eq!(result.messages[0].message, message);
+        }
+    }
+
+    #[test]
+    fn test_camera_firefly_clamp() {
+        let result = interpret("camera();");
+        let camera = result.scene_data.unwrap().camera;
+        assert_eq!(camera.firefly_clamp(), Some(10.0));
+        assert_eq!(camera.min_pdf(), 0.05);
+
+        let result = interpret("camera(firefly_clamp=100, min_pdf=0.01);");
+        assert_eq!(result.messages.len(), 0);
+        let camera = result.scene_data.unwrap().camera;
+        assert_eq!(camera.firefly_clamp(), Some(100.0));
+        assert_eq!(camera.min_pdf(), 0.01);
+
+        let result = interpret("camera(firefly_clamp=false, min_pdf=0);");
+        assert_eq!(result.messages.len(), 0);
+        let camera = result.scene_data.unwrap().camera;
+        assert_eq!(camera.firefly_clamp(), None);
+        assert_eq!(camera.min_pdf(), 0.0);
+
+        for code in [
+            "camera(firefly_clamp=0);",
+            "camera(firefly_clamp=-1);",
+            "camera(firefly_clamp=0/0);",
+            "camera(firefly_clamp=1/0);",
+            "camera(min_pdf=-1);",
+            "camera(min_pdf=0/0);",
+        ] {
+            let result = interpret(code);
+            assert_eq!(result.messages.len(), 1, "{code}");
+            assert_eq!(result.messages[0].level, MessageLevel::Error, "{code}");
+        }
+    }
+
+    #[test]
+    fn test_camera_caustic_photons() {
+        let result = interpret(
+            "
+            camera(caustic_photons=20000);
+            point_light(position=[0, 0, 10], intensity=100);
+            dielectric(1.5) translate([0, 0, 3]) sphere(1);
+            translate([-5, -5, -1]) cube([10, 10, 1]);
+            ",
+        );
+        assert_eq!(result.messages.len(), 0);
+        let mut scene_data = result.scene_data.unwrap();
+        // Photons are traced when rendering, within its limits
+        assert!(scene_data.camera.caustics().is_none());
+        scene_data.trace_caustics(&RenderContext::new_seeded(1), 1000);
+        let caustics = scene_data.camera.caustics().cloned();
+        assert!(!caustics.unwrap().is_empty());
+
+        let result = interpret("camera(); dielectric(1.5) sphere(1);");
+        let mut scene_data = result.scene_data.unwrap();
+        scene_data.trace_caustics(&RenderContext::new_seeded(1), 1000);
+        assert!(scene_data.camera.caustics().is_none());
+
+        let result = interpret("camera(caustic_photons=1e12);");
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(
+            result.messages[0].message,
+            "caustic_photons must be at most 10000000"
+        );
+    }
+
+    #[test]
+    fn test_named_cameras() {
+        let result = interpret(
+            r#"
+            camera(name="front", image_width=100);
+            camera(name="top", image_width=200);
+            "#,
+        );
+        assert_eq!(result.messages.len(), 0);
+        let mut scene_data = result.scene_data.unwrap();
+        assert_eq!(scene_data.camera_names(), vec!["front", "top"]);
+        // The first named camera is the default
+        assert_eq!(scene_data.camera.image_width(), 100);
+        assert!(scene_data.select_camera("top"));
+        assert_eq!(scene_data.camera.image_width(), 200);
+        assert!(!scene_data.select_camera("side"));
+
+        // An unnamed camera stays the default, a second one is reported
+        let result =
+            interpret("camera(image_width=50); camera(name=\"top\"); camera(image_width=60);");
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.messages[0].level, MessageLevel::Warning);
+        assert_eq!(result.scene_data.unwrap().camera.image_width(), 60);
+    }
+
+    #[test]
+    fn test_camera_orthographic() {
+        let result = interpret(
+            r#"camera(projection="orthographic", view_height=20, look_from=[10, 0, 0], look_at=[0, 0, 0]);"#,
+        );
+        assert_eq!(result.messages.len(), 0);
+        let camera = result.scene_data.unwrap().camera;
+        let top = camera.get_pick_ray(0.5, 0.0);
+        let center = camera.get_pick_ray(0.5, 0.5);
+        assert_eq!(top.direction.unit(), center.direction.unit());
+        assert!(((top.origin - center.origin).length() - 10.0).abs() < 1e-9);
+
+        let result = interpret(r#"camera(projection="fisheye");"#);
+        assert_eq!(result.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_sky() {
+        let result = interpret("sky(sun=[1, 0, 0.1], turbidity=5); camera(background=[0, 0, 0]);");
+        assert_eq!(result.messages.len(), 0);
+
+        // The sky replaces the camera background, wherever it is declared
+        let camera = result.scene_data.unwrap().camera;
+        let color = camera.background().value(&Vector3::new(0.0, 1.0, 0.0));
+        assert!(color.b > 0.0);
+    }
+
+    #[test]
+    fn test_sunlight() {
+        // An October afternoon in Paris, the sun is in the south west
+        let result = interpret(
+            "sunlight(lat=48.86, lon=2.35, date=[2024, 10, 16], time=[16, 0], timezone=2);",
+        );
+        assert_eq!(result.messages.len(), 0);
+        let camera = result.scene_data.unwrap().camera;
+        let background = camera.background();
+        assert!(background.is_sampled());
+        // South west and north east, +y north and +x east in OpenSCAD
+        let south_west = background.value(&Vector3::new(1.0, 0.6, -1.0).unit());
+        let north_east = background.value(&Vector3::new(-1.0, 0.6, 1.0).unit());
+        assert!(south_west.luminance() > north_east.luminance());
+
+        let result = interpret("sunlight(lat=48.86, lon=2.35, date=[2024, 10, 16], time=23);");
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.messages[0].level, MessageLevel::Warning);
+
+        let result = interpret("sunlight(lat=48.86, lon=2.35, date=[2024, 13], time=12);");
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.messages[0].level, MessageLevel::Error);
+    }
+
+    #[test]
+    fn test_delta_lights() {
+        let result = interpret(
+            "point_light(position=[0, 0, 10], intensity=100);
+            spot_light(position=[0, 0, 10], target=[0, 0, 0], angle=20);
+            sun(direction=[0, 0, 1], c=[1, 0.9, 0.8], intensity=2);
+            cube(1);",
+        );
+        assert_eq!(result.messages.len(), 0);
+        let lights = result.scene_data.unwrap().lights.unwrap();
+        let list = lights.as_any().downcast_ref::<LightList>().unwrap();
+        assert_eq!(list.len(), 3);
+        assert!(!lights.has_area());
+        let point = list.get_lights()[0]
+            .as_any()
+            .downcast_ref::<PointLight>()
+            .unwrap();
+        assert_eq!(point.get_position(), Vector3::new(0.0, 10.0, 0.0));
+
+        // Every light reaches the origin, the sun from straight above
+        let ctx = RenderContext {
+            random: random_new(),
+        };
+        let mut samples = vec![];
+        lights.sample_delta(&ctx, &Vector3::ZERO, &mut |sample| samples.push(sample));
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0].irradiance, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(samples[2].direction, Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(samples[2].irradiance, Color::new(2.0, 1.8, 1.6));
+
+        let result = interpret("spot_light(position=[0, 0, 10], angle=20, inner_angle=30);");
+        assert_eq!(result.messages.len(), 1);
+        assert_eq!(result.messages[0].level, MessageLevel::Error);
+        for code in [
+            "spot_light();",
+            "spot_light(target=[0, 0, 0]);",
+            "spot_light(position=[1, 2, 3], target=[1, 2, 3]);",
+        ] {
+            let result = interpret(code);
+            assert_eq!(result.messages.len(), 1, "{code}");
+            assert_eq!(
+                result.messages[0].message,
+                "target must differ from position"
+            );
+        }
+    }
+
+    #[test]
+    fn test_editable_materials() {
+        let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(
+            "gold = metal([0.9, 0.8, 0.3], 0.1);
+            apply(gold) cube(1);
+            dielectric(1.5) sphere(1);
+            color([1, 0, 0]) cylinder(h=1, r=1);
+            cube(2);",
+        )));
+        let tokens = openscad_tokenize(source.clone()).tokens.unwrap();
+        let statements = openscad_parse(tokens, source).statements.unwrap();
+        let result = openscad_interpret_with_editable_materials(
+            statements,
+            random_new(),
+            LibraryPath::new(),
+        );
+        assert_eq!(result.messages.len(), 0);
+
+        // The default material is not created by the scene
+        let names: Vec<&str> = result.materials.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, vec!["metal", "dielectric", "color"]);
+        assert_eq!(result.materials[0].material.parameters().fuzz, Some(0.1));
+        assert_eq!(result.materials[1].material.parameters().ior, Some(1.5));
+
+        assert!(
+            result.materials[2]
+                .material
+                .set_parameters(&MaterialParameters {
+                    color: Some(Color::new(0.0, 0.0, 1.0)),
+                    ..Default::default()
+                })
+        );
+        assert_eq!(
+            result.materials[2].material.parameters().color,
+            Some(Color::new(0.0, 0.0, 1.0))
+        );
+
+        // Materials are only collected when asked for
+        assert!(
+            interpret("metal([1, 1, 1], 0) cube(1);")
+                .materials
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_bbox() {
+        assert_output_trim(
+            r#"
+            bbox("b") translate([1, 2, 3]) cube([10, 20, 30]);
+            echo(b);
+            echo(measure_distance(b[0], b[1]) == sqrt(1400));
+            "#,
+            "[[1, 2, 3], [11, 22, 33]]\ntrue",
+        );
+
+        let results = interpret("bbox() sphere(2);");
+        assert_eq!(results.messages.len(), 1);
+        assert_eq!(
+            results.messages[0].message,
+            "bbox = [[-2, -2, -2], [2, 2, 2]]"
+        );
+        // The children are still rendered
+        assert!(results.scene_data.is_some());
+    }
+
+    #[test]
+    fn test_keyframes() {
+        assert_output_trim(
+            "echo(keyframes([[0, [0, 0, 0]], [1, [0, 10, 0]]]));",
+            "[0, 0, 0]",
+        );
+        assert_output_trim(
+            "echo(keyframes([[0, 0], [0.5, 90], [1, 0]], t=0.75));",
+            "45",
+        );
+        assert_output_trim("echo(keyframes([[0, 0], [1, 10]], t=2));", "10");
+        assert_output_trim("echo(keyframes([[0, \"a\"], [1, \"b\"]], t=0.5));", "\"a\"");
+
+        let results = interpret("echo(keyframes([[1, 0], [0, 10]]));");
+        assert_eq!(results.messages[0].level, MessageLevel::Error);
+
+        // Tracks follow $t of the frame being rendered
+        let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(
+            "translate(keyframes([[0, [0, 0, 0]], [1, [10, 0, 0]]])) cube(1);",
+        )));
+        let tokens = openscad_tokenize(source.clone()).tokens.unwrap();
+        let statements = openscad_parse(tokens, source).statements.unwrap();
+        let results = openscad_interpret_at_time(statements, random_new(), LibraryPath::new(), 0.5);
+        assert_eq!(results.messages.len(), 0);
+        let x = results
+            .scene_data
+            .unwrap()
+            .world
+            .bounding_box()
+            .axis_interval(Axis::X);
+        // OpenSCAD +x is -x in the raytracer
+        assert!((x.min + 6.0).abs() < 0.01 && (x.max + 5.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_limits() {
+        let interpret_with_limits = |expr: &str| {
+            let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(expr)));
+            let tokens = openscad_tokenize(source.clone()).tokens.unwrap();
+            let statements = openscad_parse(tokens, source).statements.unwrap();
+            let limits = InterpreterLimits {
+                max_operations: Some(1000),
+                max_call_depth: Some(20),
+            };
+            openscad_interpret_with_limits(
+                statements,
+                random_new(),
+                LibraryPath::new(),
+                0.0,
+                limits,
+            )
+        };
+
+        let results = interpret_with_limits("for (i = [0:10]) translate([i, 0, 0]) cube(1);");
+        assert_eq!(results.messages.len(), 0);
+
+        // Empty loops count too, and later statements are not run
+        let results = interpret_with_limits("for (i = [0:1e9]) {} echo(1);");
+        assert_eq!(results.messages.len(), 1);
+        assert_eq!(results.messages[0].level, MessageLevel::Error);
+        assert!(results.messages[0].message.contains("1000 operations"));
+
+        let results = interpret_with_limits("function f(n) = f(n + 1); echo(f(0));");
+        assert_eq!(results.messages.len(), 1);
+        assert!(results.messages[0].message.contains("deeper than 20"));
+
+        let results = interpret_with_limits("function f(n) = n == 0 ? 0 : f(n - 1); echo(f(10));");
+        assert_eq!(results.messages[0].message, "0");
+    }
+
+    #[test]
+    fn test_measure_distance() {
+        assert_output_trim("echo(measure_distance([0, 0], [3, 4]));", "5");
+
+        let results = interpret("echo(measure_distance([0, 0], [1, 2, 3]));");
+        assert_eq!(results.messages.len(), 1);
+        assert_eq!(results.messages[0].level, MessageLevel::Error);
+    }
+
+    #[test]
+    fn test_library_shims() {
+        assert_output_trim("include <MCAD/units.scad>;\necho(2 * inch);", "50.8");
+
+        let results = interpret(
+            r#"
+            include <BOSL2/std.scad>;
+            up(2) cuboid([1, 2, 3], rounding=0.5);
+            zrot(45) cyl(h=2, d=1, anchor=BOTTOM);
+            "#,
+        );
+        let messages: Vec<&str> = results
+            .messages
+            .iter()
+            .map(|message| message.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "unsupported library feature: cuboid(rounding=...) is drawn with sharp edges",
+                "unsupported library feature: cyl(anchor=...) is ignored",
+            ]
+        );
+
+        // modules are only available once the library is included
+        let results = interpret("cuboid(1);");
+        assert_eq!(results.messages[0].message, "unknown identifier \"cuboid\"");
+
+        let results = interpret("include <MCAD/gears.scad>;");
+        assert!(
+            results.messages[0]
+                .message
+                .starts_with("unsupported library feature: \"MCAD/gears.scad\" is not available")
+        );
+        assert!(results.assets.is_empty());
+    }
 }
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+use crate::Message;
+use crate::docs::get_builtin_module_docs;
+use crate::language_server::{LanguageServerBackend, offset_to_position};
+use crate::parser::{
+    CallArgument, CallArgumentWithPosition, ModuleIdWithPosition, ParseResult, Statement,
+    StatementWithPosition,
+};
+
+impl LanguageServerBackend {
+    /// Returns the quick fixes and refactorings available for the selection
+    /// from `start` to `end`.
+    pub(super) fn handle_code_action(
+        &self,
+        uri: &Url,
+        code: &str,
+        parse_result: ParseResult,
+        start: usize,
+        end: usize,
+    ) -> Result<Option<CodeActionResponse>> {
+        let statements = parse_result.statements.unwrap_or_default();
+
+        let mut actions = vec![];
+        actions.extend(add_missing_semicolons(code, &parse_result.messages));
+        actions.extend(convert_positional_arguments(code, &statements, start));
+        if start < end {
+            actions.extend(wrap_in_translate(code, &statements, start, end));
+        }
+        actions.extend(insert_camera_template(&statements));
+
+        if actions.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            actions
+                .into_iter()
+                .map(|(title, kind, edits)| {
+                    CodeActionOrCommand::CodeAction(CodeAction {
+                        title,
+                        kind: Some(kind),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(HashMap::from([(uri.clone(), edits)])),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    })
+                })
+                .collect(),
+        ))
+    }
+}
+
+type Action = (String, CodeActionKind, Vec<TextEdit>);
+
+fn insert(code: &str, offset: usize, text: String) -> TextEdit {
+    let position = offset_to_position(code, offset);
+    TextEdit {
+        range: Range::new(position, position),
+        new_text: text,
+    }
+}
+
+/// Adds a ';' after the code before each "Expected Semicolon" parse error.
+fn add_missing_semicolons(code: &str, messages: &[Message]) -> Option<Action> {
+    let edits: Vec<TextEdit> = messages
+        .iter()
+        .filter(|message| message.message.starts_with("Expected Semicolon"))
+        .map(|message| {
+            // the error is reported at the next token, the semicolon belongs
+            // right after the previous one
+            let chars: Vec<char> = code.chars().take(message.position.start).collect();
+            let offset = chars
+                .iter()
+                .rposition(|ch| !ch.is_whitespace())
+                .map(|i| i + 1)
+                .unwrap_or(0);
+            insert(code, offset, ";".to_owned())
+        })
+        .collect();
+
+    if edits.is_empty() {
+        return None;
+    }
+    Some((
+        "Add missing semicolon".to_owned(),
+        CodeActionKind::QUICKFIX,
+        edits,
+    ))
+}
+
+/// Names the positional arguments of the builtin module call at `pos`, using
+/// the argument order from the module docs.
+fn convert_positional_arguments(
+    code: &str,
+    statements: &[StatementWithPosition],
+    pos: usize,
+) -> Option<Action> {
+    let (module_id, call_arguments) = find_module_instantiation(statements, pos)?;
+    let docs = get_builtin_module_docs(&module_id.item)?;
+
+    let edits: Vec<TextEdit> = call_arguments
+        .iter()
+        .enumerate()
+        .filter(|(_, argument)| matches!(argument.item, CallArgument::Expr { .. }))
+        .filter_map(|(i, argument)| {
+            let name = &docs.arguments.get(i)?.name;
+            Some(insert(code, argument.position.start, format!("{name}=")))
+        })
+        .collect();
+
+    if edits.is_empty() {
+        return None;
+    }
+    Some((
+        "Convert positional to named arguments".to_owned(),
+        CodeActionKind::REFACTOR_REWRITE,
+        edits,
+    ))
+}
+
+/// Finds the innermost module instantiation whose name or arguments contain `pos`.
+fn find_module_instantiation(
+    statements: &[StatementWithPosition],
+    pos: usize,
+) -> Option<(&ModuleIdWithPosition, &[CallArgumentWithPosition])> {
+    statements
+        .iter()
+        .filter(|statement| statement.position.contains_pos(pos))
+        .find_map(|statement| match &statement.item {
+            Statement::ModuleInstantiation {
+                module_id,
+                call_arguments,
+                child_statements,
+            } => find_module_instantiation(child_statements, pos).or_else(|| {
+                let in_call = module_id.position.contains_pos(pos)
+                    || call_arguments
+                        .iter()
+                        .any(|argument| argument.position.contains_pos(pos));
+                in_call.then_some((module_id, call_arguments.as_slice()))
+            }),
+            Statement::If {
+                expr: _,
+                true_statements,
+                false_statements,
+            } => find_module_instantiation(true_statements, pos)
+                .or_else(|| find_module_instantiation(false_statements, pos)),
+            _ => None,
+        })
+}
+
+/// Wraps the statements touched by the selection in a `translate()` block.
+fn wrap_in_translate(
+    code: &str,
+    statements: &[StatementWithPosition],
+    start: usize,
+    end: usize,
+) -> Option<Action> {
+    let (wrap_start, wrap_end) = find_wrap_range(code, statements, start, end)?;
+
+    let chars: Vec<char> = code.chars().collect();
+    let line_start = chars[..wrap_start]
+        .iter()
+        .rposition(|ch| *ch == '\n')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let indent: String = chars[line_start..wrap_start]
+        .iter()
+        .take_while(|ch| ch.is_whitespace())
+        .collect();
+
+    let body = chars[wrap_start..wrap_end]
+        .iter()
+        .collect::<String>()
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 {
+                format!("{indent}    {line}")
+            } else {
+                format!("    {line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some((
+        "Wrap in translate()".to_owned(),
+        CodeActionKind::REFACTOR,
+        vec![TextEdit {
+            range: Range::new(
+                offset_to_position(code, wrap_start),
+                offset_to_position(code, wrap_end),
+            ),
+            new_text: format!("translate([0, 0, 0]) {{\n{body}\n{indent}}}"),
+        }],
+    ))
+}
+
+/// Returns the start and end of the statements overlapping the selection,
+/// descending into a block when the selection is inside of it.
+fn find_wrap_range(
+    code: &str,
+    statements: &[StatementWithPosition],
+    start: usize,
+    end: usize,
+) -> Option<(usize, usize)> {
+    let selected: Vec<&StatementWithPosition> = statements
+        .iter()
+        .filter(|statement| {
+            statement.position.start < end
+                && start < statement.position.end
+                && !matches!(statement.item, Statement::Empty)
+        })
+        .collect();
+    let first = selected.first()?;
+    let last = selected.last()?;
+
+    if let Statement::ModuleInstantiation {
+        child_statements, ..
+    } = &first.item
+        && selected.len() == 1
+        && let (Some(first_child), Some(last_child)) =
+            (child_statements.first(), child_statements.last())
+        && first_child.position.start <= start
+        && end <= last_child.position.end
+    {
+        return find_wrap_range(code, child_statements, start, end);
+    }
+
+    // statements end at the start of the next token, leave the whitespace in between alone
+    let chars: Vec<char> = code.chars().take(last.position.end).collect();
+    let mut wrap_end = last.position.end;
+    while wrap_end > first.position.start && chars[wrap_end - 1].is_whitespace() {
+        wrap_end -= 1;
+    }
+    Some((first.position.start, wrap_end))
+}
+
+/// Offers a camera with every documented default when the file has none.
+fn insert_camera_template(statements: &[StatementWithPosition]) -> Option<Action> {
+    let has_camera = statements.iter().any(|statement| {
+        matches!(&statement.item, Statement::ModuleInstantiation { module_id, .. } if module_id.item == "camera")
+    });
+    if has_camera {
+        return None;
+    }
+
+    let docs = get_builtin_module_docs("camera")?;
+    let arguments = docs
+        .arguments
+        .iter()
+        .filter_map(|argument| {
+            let default = argument.default.as_ref()?;
+            Some(format!("    {}={default}", argument.name))
+        })
+        .collect::<Vec<_>>()
+        .join(",\n");
+
+    Some((
+        "Insert camera() template".to_owned(),
+        CodeActionKind::REFACTOR,
+        vec![TextEdit {
+            range: Range::new(Position::new(0, 0), Position::new(0, 0)),
+            new_text: format!("camera(\n{arguments}\n);\n\n"),
+        }],
+    ))
+}
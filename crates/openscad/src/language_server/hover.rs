@@ -1,20 +1,29 @@
+use std::collections::HashMap;
+
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::*;
 
 use crate::docs::get_builtin_module_docs;
 use crate::language_server::LanguageServerBackend;
 use crate::parser::{
-    CallArgumentWithPosition, ModuleIdWithPosition, Statement, StatementWithPosition,
+    CallArgument, CallArgumentWithPosition, DeclArgument, DeclArgumentWithPosition, Expr,
+    ExprWithPosition, ModuleIdWithPosition, Statement, StatementWithPosition,
 };
 
+/// Markdown hover text of user-defined functions, by name
+type UserDocs = HashMap<String, String>;
+
 impl LanguageServerBackend {
     pub(super) fn handle_hover(
         &self,
         statements: Vec<StatementWithPosition>,
         pos: usize,
     ) -> Result<Option<Hover>> {
+        let mut user_docs = UserDocs::new();
+        collect_user_docs(&statements, &mut user_docs);
+
         for statement in statements {
-            if let Some(result) = self.hover_statement(&statement, pos) {
+            if let Some(result) = self.hover_statement(&statement, pos, &user_docs) {
                 return Ok(Some(result));
             }
         }
@@ -22,25 +31,40 @@ impl LanguageServerBackend {
         Ok(None)
     }
 
-    fn hover_statement(&self, statement: &StatementWithPosition, pos: usize) -> Option<Hover> {
+    fn hover_statement(
+        &self,
+        statement: &StatementWithPosition,
+        pos: usize,
+        user_docs: &UserDocs,
+    ) -> Option<Hover> {
         if statement.position.contains_pos(pos) {
             match &statement.item {
                 Statement::Empty => None,
                 Statement::Assignment {
                     identifier: _,
-                    expr: _,
-                } => None,
-                Statement::Include { filename: _ } => None,
+                    expr,
+                } => hover_expr(expr, pos, user_docs),
+                Statement::Include { filename: _ } | Statement::Use { filename: _ } => None,
                 Statement::FunctionDecl {
-                    function_name: _,
+                    function_name,
                     arguments: _,
-                    expr: _,
-                } => None,
+                    expr,
+                    doc: _,
+                } => hover_expr(expr, pos, user_docs).or_else(|| {
+                    user_docs
+                        .get(function_name)
+                        .map(|docs| markdown_hover(docs))
+                }),
                 Statement::If {
-                    expr: _,
-                    true_statements: _,
-                    false_statements: _,
-                } => None,
+                    expr,
+                    true_statements,
+                    false_statements,
+                } => hover_expr(expr, pos, user_docs).or_else(|| {
+                    true_statements
+                        .iter()
+                        .chain(false_statements)
+                        .find_map(|statement| self.hover_statement(statement, pos, user_docs))
+                }),
                 Statement::ModuleInstantiation {
                     module_id,
                     call_arguments,
@@ -50,6 +74,7 @@ impl LanguageServerBackend {
                     module_id,
                     call_arguments,
                     child_statements,
+                    user_docs,
                 ),
             }
         } else {
@@ -61,31 +86,24 @@ impl LanguageServerBackend {
         &self,
         pos: usize,
         module_id: &ModuleIdWithPosition,
-        call_arguments: &Vec<CallArgumentWithPosition>,
-        child_statements: &Vec<StatementWithPosition>,
+        call_arguments: &[CallArgumentWithPosition],
+        child_statements: &[StatementWithPosition],
+        user_docs: &UserDocs,
     ) -> Option<Hover> {
         if module_id.position.contains_pos(pos) {
             let module_docs = get_builtin_module_docs(&module_id.item);
 
             if let Some(module_docs) = module_docs {
-                return Some(Hover {
-                    contents: HoverContents::Markup(MarkupContent {
-                        kind: MarkupKind::Markdown,
-                        value: module_docs.to_markdown(),
-                    }),
-                    range: None,
-                });
+                return Some(markdown_hover(&module_docs.to_markdown()));
             }
         }
 
-        for call_argument in call_arguments {
-            if call_argument.position.contains_pos(pos) {
-                // TODO
-            }
+        if let Some(result) = hover_call_arguments(call_arguments, pos, user_docs) {
+            return Some(result);
         }
 
         for child_statement in child_statements {
-            if let Some(result) = self.hover_statement(child_statement, pos) {
+            if let Some(result) = self.hover_statement(child_statement, pos, user_docs) {
                 return Some(result);
             }
         }
@@ -93,3 +111,134 @@ impl LanguageServerBackend {
         None
     }
 }
+
+fn markdown_hover(value: &str) -> Hover {
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: value.to_owned(),
+        }),
+        range: None,
+    }
+}
+
+/// Builds the hover text of every function declared in `statements`, from its
+/// signature and the doc comment written above it.
+fn collect_user_docs(statements: &[StatementWithPosition], user_docs: &mut UserDocs) {
+    for statement in statements {
+        match &statement.item {
+            Statement::FunctionDecl {
+                function_name,
+                arguments,
+                expr: _,
+                doc,
+            } => {
+                user_docs.insert(
+                    function_name.clone(),
+                    user_function_markdown(function_name, arguments, doc.as_deref()),
+                );
+            }
+            Statement::If {
+                expr: _,
+                true_statements,
+                false_statements,
+            } => {
+                collect_user_docs(true_statements, user_docs);
+                collect_user_docs(false_statements, user_docs);
+            }
+            Statement::ModuleInstantiation {
+                module_id: _,
+                call_arguments: _,
+                child_statements,
+            } => collect_user_docs(child_statements, user_docs),
+            Statement::Empty
+            | Statement::Assignment { .. }
+            | Statement::Include { .. }
+            | Statement::Use { .. } => {}
+        }
+    }
+}
+
+fn user_function_markdown(
+    function_name: &str,
+    arguments: &[DeclArgumentWithPosition],
+    doc: Option<&str>,
+) -> String {
+    let arguments = arguments
+        .iter()
+        .map(|argument| match &argument.item {
+            DeclArgument::WithDefault { identifier, .. } => format!("{identifier}=…"),
+            DeclArgument::Identifier { identifier } => identifier.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut result = format!("```\nfunction {function_name}({arguments})\n```");
+    if let Some(doc) = doc {
+        result += &format!("\n\n{doc}");
+    }
+    result
+}
+
+fn hover_call_arguments(
+    call_arguments: &[CallArgumentWithPosition],
+    pos: usize,
+    user_docs: &UserDocs,
+) -> Option<Hover> {
+    call_arguments
+        .iter()
+        .filter(|call_argument| call_argument.position.contains_pos(pos))
+        .find_map(|call_argument| match &call_argument.item {
+            CallArgument::NamedArgument {
+                identifier: _,
+                expr,
+            } => hover_expr(expr, pos, user_docs),
+            CallArgument::Expr { expr } => hover_expr(expr, pos, user_docs),
+        })
+}
+
+/// Finds the innermost call to a user-defined function at `pos`.
+fn hover_expr(expr: &ExprWithPosition, pos: usize, user_docs: &UserDocs) -> Option<Hover> {
+    if !expr.position.contains_pos(pos) {
+        return None;
+    }
+
+    match &expr.item {
+        Expr::True | Expr::False | Expr::Identifier { .. } | Expr::String(_) | Expr::Number(_) => {
+            None
+        }
+        Expr::FieldAccess { lhs, field: _ } => hover_expr(lhs, pos, user_docs),
+        Expr::Range {
+            start,
+            end,
+            increment,
+        } => hover_expr(start, pos, user_docs)
+            .or_else(|| hover_expr(end, pos, user_docs))
+            .or_else(|| {
+                increment
+                    .as_ref()
+                    .and_then(|increment| hover_expr(increment, pos, user_docs))
+            }),
+        Expr::Vector { items } => items
+            .iter()
+            .find_map(|item| hover_expr(item, pos, user_docs)),
+        Expr::Binary {
+            operator: _,
+            lhs,
+            rhs,
+        } => hover_expr(lhs, pos, user_docs).or_else(|| hover_expr(rhs, pos, user_docs)),
+        Expr::Unary { operator: _, rhs } => hover_expr(rhs, pos, user_docs),
+        Expr::Ternary {
+            condition,
+            true_expr,
+            false_expr,
+        } => hover_expr(condition, pos, user_docs)
+            .or_else(|| hover_expr(true_expr, pos, user_docs))
+            .or_else(|| hover_expr(false_expr, pos, user_docs)),
+        Expr::Index { lhs, index } => {
+            hover_expr(lhs, pos, user_docs).or_else(|| hover_expr(index, pos, user_docs))
+        }
+        Expr::FunctionCall { name, arguments } => hover_call_arguments(arguments, pos, user_docs)
+            .or_else(|| user_docs.get(name).map(|docs| markdown_hover(docs))),
+    }
+}
@@ -0,0 +1,157 @@
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::*;
+
+use crate::docs::get_builtin_module_docs;
+use crate::language_server::{LanguageServerBackend, offset_to_position};
+use crate::parser::{
+    CallArgument, CallArgumentWithPosition, Expr, ExprWithPosition, ModuleIdWithPosition,
+    Statement, StatementWithPosition, UnaryOperator,
+};
+
+impl LanguageServerBackend {
+    /// Returns the hints that start between `start` and `end`.
+    pub(super) fn handle_inlay_hint(
+        &self,
+        code: &str,
+        statements: Vec<StatementWithPosition>,
+        start: usize,
+        end: usize,
+    ) -> Result<Option<Vec<InlayHint>>> {
+        let mut hints = vec![];
+        collect_statement_hints(&statements, &mut hints);
+
+        Ok(Some(
+            hints
+                .into_iter()
+                .filter(|(offset, _)| (start..=end).contains(offset))
+                .map(|(offset, hint)| InlayHint {
+                    position: offset_to_position(code, offset),
+                    ..hint
+                })
+                .collect(),
+        ))
+    }
+}
+
+/// A hint and the offset it is shown at, the hint position is filled in from
+/// the offset once all hints are collected.
+type OffsetHint = (usize, InlayHint);
+
+fn hint(label: String, kind: InlayHintKind) -> InlayHint {
+    InlayHint {
+        position: Position::default(),
+        label: InlayHintLabel::String(label),
+        kind: Some(kind),
+        text_edits: None,
+        tooltip: None,
+        padding_left: Some(kind == InlayHintKind::TYPE),
+        padding_right: Some(kind == InlayHintKind::PARAMETER),
+        data: None,
+    }
+}
+
+fn collect_statement_hints(statements: &[StatementWithPosition], hints: &mut Vec<OffsetHint>) {
+    for statement in statements {
+        match &statement.item {
+            Statement::ModuleInstantiation {
+                module_id,
+                call_arguments,
+                child_statements,
+            } => {
+                collect_module_hints(module_id, call_arguments, hints);
+                collect_statement_hints(child_statements, hints);
+            }
+            Statement::If {
+                expr: _,
+                true_statements,
+                false_statements,
+            } => {
+                collect_statement_hints(true_statements, hints);
+                collect_statement_hints(false_statements, hints);
+            }
+            Statement::Empty
+            | Statement::Assignment { .. }
+            | Statement::Include { .. }
+            | Statement::Use { .. }
+            | Statement::FunctionDecl { .. } => {}
+        }
+    }
+}
+
+/// Names positional arguments of builtin modules and counts the iterations of
+/// `for` loops over constant ranges.
+fn collect_module_hints(
+    module_id: &ModuleIdWithPosition,
+    call_arguments: &[CallArgumentWithPosition],
+    hints: &mut Vec<OffsetHint>,
+) {
+    if module_id.item == "for" {
+        for argument in call_arguments {
+            if let CallArgument::NamedArgument {
+                identifier: _,
+                expr,
+            } = &argument.item
+                && let Some(count) = range_iteration_count(expr)
+            {
+                let label = if count == 1 {
+                    "1 iteration".to_owned()
+                } else {
+                    format!("{count} iterations")
+                };
+                hints.push((expr.position.end, hint(label, InlayHintKind::TYPE)));
+            }
+        }
+        return;
+    }
+
+    let Some(docs) = get_builtin_module_docs(&module_id.item) else {
+        return;
+    };
+    for (i, argument) in call_arguments.iter().enumerate() {
+        if let CallArgument::Expr { expr: _ } = &argument.item
+            && let Some(doc_argument) = docs.arguments.get(i)
+        {
+            hints.push((
+                argument.position.start,
+                hint(format!("{}:", doc_argument.name), InlayHintKind::PARAMETER),
+            ));
+        }
+    }
+}
+
+/// Number of times the interpreter runs a loop over the range, when the range
+/// is made of number literals. The end of the range is exclusive.
+fn range_iteration_count(expr: &ExprWithPosition) -> Option<u64> {
+    let Expr::Range {
+        start,
+        end,
+        increment,
+    } = &expr.item
+    else {
+        return None;
+    };
+
+    let start = literal_number(start)?;
+    let end = literal_number(end)?;
+    let increment = match increment {
+        Some(increment) => literal_number(increment)?,
+        None => 1.0,
+    };
+
+    let steps = (end - start) / increment;
+    if !steps.is_finite() || steps < 0.0 {
+        return None;
+    }
+    Some(steps.ceil() as u64)
+}
+
+fn literal_number(expr: &ExprWithPosition) -> Option<f64> {
+    match &expr.item {
+        Expr::Number(value) => Some(*value),
+        Expr::Unary {
+            operator: UnaryOperator::Minus,
+            rhs,
+        } => literal_number(rhs).map(|value| -value),
+        _ => None,
+    }
+}
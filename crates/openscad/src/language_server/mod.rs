@@ -1,44 +1,93 @@
+mod code_action;
 mod hover;
+mod inlay_hint;
+mod workspace;
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use caustic_core::utils::line_offset_to_position;
-use tower_lsp::LanguageServer;
 use tower_lsp::jsonrpc::{Error, ErrorCode, Result};
 use tower_lsp::lsp_types::*;
+use tower_lsp::{Client, LanguageServer};
 
-use crate::parser::{StatementWithPosition, openscad_parse};
+use crate::library::LibraryPath;
+use crate::parser::{ParseResult, StatementWithPosition, openscad_parse};
 use crate::source::{Source, StringSource};
-use crate::tokenizer::openscad_tokenize;
+use crate::tokenizer::openscad_tokenize_with_comments;
+
+use self::workspace::Document;
+
+/// Text of a document open in the editor.
+#[derive(Debug)]
+struct OpenDocument {
+    /// Version sent by the editor, which increases with each change
+    version: i32,
+    text: String,
+}
 
 #[derive(Debug)]
 pub struct LanguageServerBackend {
-    document_map: tokio::sync::RwLock<HashMap<Url, String>>,
+    /// Used to publish diagnostics, without one diagnostics are only computed on request
+    client: Option<Client>,
+    document_map: tokio::sync::RwLock<HashMap<Url, OpenDocument>>,
+    /// Documents parsed for diagnostics and workspace wide lookups, reused
+    /// until the document changes
+    parsed_documents: tokio::sync::RwLock<HashMap<Url, Arc<Document>>>,
+    /// Folders searched for included files and files to update on rename
+    workspace_folders: tokio::sync::RwLock<Vec<Url>>,
+    /// Searched for included files after the workspace folders
+    library_path: tokio::sync::RwLock<LibraryPath>,
 }
 
 impl LanguageServerBackend {
     pub fn new() -> Self {
         Self {
+            client: None,
             document_map: tokio::sync::RwLock::new(HashMap::new()),
+            parsed_documents: tokio::sync::RwLock::new(HashMap::new()),
+            workspace_folders: tokio::sync::RwLock::new(vec![]),
+            library_path: tokio::sync::RwLock::new(LibraryPath::from_env()),
         }
     }
 
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
     async fn parse_file(&self, url: &Url) -> Result<(String, Vec<StatementWithPosition>)> {
-        let document_map = self.document_map.read().await;
-        let text = match document_map.get(url) {
-            Some(content) => content,
-            None => {
-                return Err(Error {
-                    code: ErrorCode::InternalError,
-                    message: format!("File not found: {url}").into(),
-                    data: None,
-                });
-            } // Document not found
+        let (text, parse_result) = self.parse_document(url).await?;
+
+        let statements = parse_result.statements.ok_or_else(|| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to parse: {url}").into(),
+            data: None,
+        })?;
+
+        Ok((text, statements))
+    }
+
+    /// Parses the document, keeping the parse messages so partially parsed
+    /// documents can still be used. Documents that are not open are read from disk.
+    async fn parse_document(&self, url: &Url) -> Result<(String, ParseResult)> {
+        let Some(text) = self.load_text(url).await else {
+            return Err(Error {
+                code: ErrorCode::InternalError,
+                message: format!("File not found: {url}").into(),
+                data: None,
+            });
         };
 
+        let parse_result = Self::parse_text(url, &text)?;
+        Ok((text, parse_result))
+    }
+
+    /// Parses the text, turning a panic in the parser into an error so one
+    /// file the parser cannot handle does not bring the server down.
+    fn parse_text(url: &Url, text: &str) -> Result<ParseResult> {
         let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(text)));
-        let tokens = openscad_tokenize(source.clone())
+        let tokens = openscad_tokenize_with_comments(source.clone())
             .tokens
             .ok_or_else(|| Error {
                 code: ErrorCode::InternalError,
@@ -46,20 +95,77 @@ impl LanguageServerBackend {
                 data: None,
             })?;
 
-        let statements = openscad_parse(tokens, source)
-            .statements
-            .ok_or_else(|| Error {
-                code: ErrorCode::InternalError,
-                message: format!("Failed to parse: {url}").into(),
-                data: None,
-            })?;
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            openscad_parse(tokens, source)
+        }))
+        .map_err(|_| Error {
+            code: ErrorCode::InternalError,
+            message: format!("Failed to parse: {url}").into(),
+            data: None,
+        })
+    }
+
+    /// Publishes diagnostics for the document and the open documents including it.
+    async fn publish_diagnostics(&self, uri: Url) {
+        let Some(client) = &self.client else {
+            return;
+        };
 
-        Ok((text.to_owned(), statements))
+        let mut uris = vec![uri.clone()];
+        uris.extend(self.open_dependents(&uri).await);
+        for uri in uris {
+            let diagnostics = self.handle_diagnostics(&uri).await;
+            client.publish_diagnostics(uri, diagnostics, None).await;
+        }
+    }
+
+    /// Converts a line and character position in the document to an offset.
+    async fn document_offset(&self, uri: &Url, position: Position) -> Result<Option<usize>> {
+        let (code, _) = self.parse_document(uri).await?;
+        Ok(line_offset_to_position(
+            &code,
+            position.line as usize,
+            position.character as usize,
+        ))
+    }
+
+    /// Reads the `libraryPath` setting, a list of directories searched before
+    /// the ones in `OPENSCADPATH`. Settings may be nested in a `caustic` section.
+    async fn apply_settings(&self, settings: &LSPAny) {
+        let settings = settings.get("caustic").unwrap_or(settings);
+        let Some(directories) = settings.get("libraryPath").and_then(|v| v.as_array()) else {
+            return;
+        };
+
+        let library_path = directories
+            .iter()
+            .filter_map(|directory| directory.as_str())
+            .fold(LibraryPath::new(), |library_path, directory| {
+                library_path.with_directory(directory)
+            })
+            .with_library_path(LibraryPath::from_env());
+        *self.library_path.write().await = library_path;
+    }
+
+    #[cfg(test)]
+    pub async fn with_workspace_folder(self, uri: Url) -> Self {
+        self.workspace_folders.write().await.push(uri);
+        self
     }
 
     #[cfg(test)]
     pub async fn with_document(self, uri: Url, text: &str) -> Self {
-        self.document_map.write().await.insert(uri, text.to_owned());
+        {
+            let mut document_map = self.document_map.write().await;
+            let version = document_map.get(&uri).map_or(0, |open| open.version + 1);
+            document_map.insert(
+                uri,
+                OpenDocument {
+                    version,
+                    text: text.to_owned(),
+                },
+            );
+        }
         self
     }
 }
@@ -72,13 +178,32 @@ impl Default for LanguageServerBackend {
 
 #[tower_lsp::async_trait]
 impl LanguageServer for LanguageServerBackend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        if let Some(folders) = params.workspace_folders {
+            *self.workspace_folders.write().await =
+                folders.into_iter().map(|folder| folder.uri).collect();
+        }
+        if let Some(options) = &params.initialization_options {
+            self.apply_settings(options).await;
+        }
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
                     TextDocumentSyncKind::FULL,
                 )),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                definition_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Left(true)),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -91,17 +216,60 @@ impl LanguageServer for LanguageServerBackend {
 
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let uri = params.text_document.uri;
-        let text = params.text_document.text;
-        self.document_map.write().await.insert(uri, text);
+        let open = OpenDocument {
+            version: params.text_document.version,
+            text: params.text_document.text,
+        };
+        self.document_map.write().await.insert(uri.clone(), open);
+        self.publish_diagnostics(uri).await;
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri;
         if let Some(change) = params.content_changes.into_iter().next() {
-            self.document_map.write().await.insert(uri, change.text);
+            let open = OpenDocument {
+                version: params.text_document.version,
+                text: change.text,
+            };
+            self.document_map.write().await.insert(uri.clone(), open);
+            self.publish_diagnostics(uri).await;
+        }
+    }
+
+    async fn did_close(&self, params: DidCloseTextDocumentParams) {
+        // includes of other documents now see the file on disk
+        let uri = params.text_document.uri;
+        self.document_map.write().await.remove(&uri);
+        self.parsed_documents.write().await.remove(&uri);
+        if let Some(client) = &self.client {
+            client.publish_diagnostics(uri.clone(), vec![], None).await;
+        }
+        for dependent in self.open_dependents(&uri).await {
+            self.publish_diagnostics(dependent).await;
         }
     }
 
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        self.apply_settings(&params.settings).await;
+
+        let open: Vec<Url> = self.document_map.read().await.keys().cloned().collect();
+        for uri in open {
+            self.publish_diagnostics(uri).await;
+        }
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        let mut folders = self.workspace_folders.write().await;
+        folders.retain(|folder| {
+            !params
+                .event
+                .removed
+                .iter()
+                .any(|removed| removed.uri == *folder)
+        });
+        folders.extend(params.event.added.into_iter().map(|folder| folder.uri));
+    }
+
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let uri = &params.text_document_position_params.text_document.uri;
         let line = params.text_document_position_params.position.line as usize;
@@ -116,6 +284,82 @@ impl LanguageServer for LanguageServerBackend {
 
         self.handle_hover(statements, pos)
     }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+        let (code, parse_result) = self.parse_document(uri).await?;
+        let Some(start) = line_offset_to_position(
+            &code,
+            params.range.start.line as usize,
+            params.range.start.character as usize,
+        ) else {
+            return Ok(None);
+        };
+        let end = line_offset_to_position(
+            &code,
+            params.range.end.line as usize,
+            params.range.end.character as usize,
+        )
+        .unwrap_or(start);
+
+        self.handle_code_action(uri, &code, parse_result, start, end)
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        let (code, statements) = self.parse_file(&params.text_document.uri).await?;
+        let start = line_offset_to_position(
+            &code,
+            params.range.start.line as usize,
+            params.range.start.character as usize,
+        )
+        .unwrap_or(0);
+        let end = line_offset_to_position(
+            &code,
+            params.range.end.line as usize,
+            params.range.end.character as usize,
+        )
+        .unwrap_or(code.chars().count());
+
+        self.handle_inlay_hint(&code, statements, start, end)
+    }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let Some(pos) = self.document_offset(uri, position).await? else {
+            return Ok(None);
+        };
+
+        self.handle_goto_definition(uri, pos).await
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        let Some(pos) = self.document_offset(uri, position).await? else {
+            return Ok(None);
+        };
+
+        self.handle_rename(uri, pos, &params.new_name).await
+    }
+}
+
+/// Converts an offset in `code` to a line and character position.
+fn offset_to_position(code: &str, offset: usize) -> Position {
+    let mut line = 0;
+    let mut character = 0;
+    for ch in code.chars().take(offset) {
+        if ch == '\n' {
+            line += 1;
+            character = 0;
+        } else {
+            character += 1;
+        }
+    }
+    Position::new(line, character)
 }
 
 #[cfg(test)]
@@ -150,10 +394,380 @@ pub mod test {
                 assert_eq!(kind, MarkupKind::Markdown);
                 assert_eq!(
                     value,
-                    "**Description:** Creates a circle at the origin. All parameters, except r, must be named.\n\n### Arguments:\n- `r` circle radius. r name is the only one optional with circle.\n- `d` circle diameter.\n\n### Examples:\n```\ncircle(10);\ncircle(r=10);\ncircle(d=20);\n```"
+                    "**Description:** Creates a circle at the origin. All parameters, except r, must be named.\n\n### Arguments:\n- `r` circle radius. r name is the only one optional with circle.\n- `d` circle diameter.\n- `inner_r` radius of a hole in the middle, making a ring.Default: 0\n- `angle` degrees of the circle to keep, starting at +X and turning towards +Y.Default: 360\n\n### Examples:\n```\ncircle(10);\ncircle(r=10);\ncircle(d=20);\ncircle(r=10, inner_r=6, angle=270);\n```"
                 );
             }
             _ => panic!("Expected scalar string"),
         }
     }
+
+    #[tokio::test]
+    async fn test_hover_user_function_docs() {
+        let backend = LanguageServerBackend::new()
+            .with_document(
+                Url::parse("file:///test.scad").unwrap(),
+                "// Area of a rectangle.\nfunction area(w, h) = w * h;\nx = area(2, 3);",
+            )
+            .await;
+
+        let params = HoverParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::parse("file:///test.scad").unwrap(),
+                },
+                position: Position::new(2, 5),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let hover = backend.hover(params).await.unwrap().unwrap();
+
+        match hover.contents {
+            HoverContents::Markup(MarkupContent { kind, value }) => {
+                assert_eq!(kind, MarkupKind::Markdown);
+                assert_eq!(
+                    value,
+                    "```\nfunction area(w, h)\n```\n\nArea of a rectangle."
+                );
+            }
+            _ => panic!("Expected scalar string"),
+        }
+    }
+
+    async fn code_action_titles(text: &str, range: Range) -> Vec<(String, Vec<TextEdit>)> {
+        let uri = Url::parse("file:///test.scad").unwrap();
+        let backend = LanguageServerBackend::new()
+            .with_document(uri.clone(), text)
+            .await;
+
+        let params = CodeActionParams {
+            text_document: TextDocumentIdentifier { uri: uri.clone() },
+            range,
+            context: CodeActionContext::default(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        backend
+            .code_action(params)
+            .await
+            .unwrap()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => {
+                    let mut changes = action.edit.unwrap().changes.unwrap();
+                    (action.title, changes.remove(&uri).unwrap())
+                }
+                CodeActionOrCommand::Command(_) => panic!("Expected code action"),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_code_actions() {
+        let cursor = Range::new(Position::new(1, 2), Position::new(1, 2));
+        let actions = code_action_titles("camera();\nx = 1\ncircle(5);", cursor).await;
+        assert_eq!(
+            actions,
+            vec![(
+                "Add missing semicolon".to_owned(),
+                vec![TextEdit {
+                    range: Range::new(Position::new(1, 5), Position::new(1, 5)),
+                    new_text: ";".to_owned(),
+                }]
+            ),]
+        );
+
+        let cursor = Range::new(Position::new(1, 2), Position::new(1, 2));
+        let actions = code_action_titles("camera();\ncircle(5);", cursor).await;
+        assert_eq!(
+            actions,
+            vec![(
+                "Convert positional to named arguments".to_owned(),
+                vec![TextEdit {
+                    range: Range::new(Position::new(1, 7), Position::new(1, 7)),
+                    new_text: "r=".to_owned(),
+                }]
+            )]
+        );
+
+        let selection = Range::new(Position::new(1, 0), Position::new(1, 5));
+        let actions = code_action_titles("camera();\ncircle(r=5);\n", selection).await;
+        assert_eq!(
+            actions,
+            vec![(
+                "Wrap in translate()".to_owned(),
+                vec![TextEdit {
+                    range: Range::new(Position::new(1, 0), Position::new(1, 12)),
+                    new_text: "translate([0, 0, 0]) {\n    circle(r=5);\n}".to_owned(),
+                }]
+            )]
+        );
+
+        let cursor = Range::new(Position::new(0, 0), Position::new(0, 0));
+        let actions = code_action_titles("sphere(r=1);", cursor).await;
+        let titles: Vec<&str> = actions.iter().map(|(title, _)| title.as_str()).collect();
+        assert_eq!(titles, ["Insert camera() template"]);
+        assert!(
+            actions[0].1[0]
+                .new_text
+                .starts_with("camera(\n    aspect_ratio=1.0,\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inlay_hints() {
+        let uri = Url::parse("file:///test.scad").unwrap();
+        let backend = LanguageServerBackend::new()
+            .with_document(
+                uri.clone(),
+                "for (i = [0:2:9]) {\n    translate([i, 0, 0]) cube(1, center=true);\n}",
+            )
+            .await;
+
+        let params = InlayHintParams {
+            text_document: TextDocumentIdentifier { uri },
+            range: Range::new(Position::new(0, 0), Position::new(3, 0)),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let hints: Vec<(Position, String)> = backend
+            .inlay_hint(params)
+            .await
+            .unwrap()
+            .unwrap()
+            .into_iter()
+            .map(|hint| match hint.label {
+                InlayHintLabel::String(label) => (hint.position, label),
+                InlayHintLabel::LabelParts(_) => panic!("Expected string label"),
+            })
+            .collect();
+        assert_eq!(
+            hints,
+            vec![
+                (Position::new(0, 16), "5 iterations".to_owned()),
+                (Position::new(1, 14), "v:".to_owned()),
+                (Position::new(1, 30), "size:".to_owned()),
+            ]
+        );
+    }
+
+    async fn workspace_backend() -> LanguageServerBackend {
+        LanguageServerBackend::new()
+            .with_document(
+                Url::parse("file:///project/lib/shapes.scad").unwrap(),
+                "size = 2;\nfunction area(w, h) = w * h;\n",
+            )
+            .await
+            .with_document(
+                Url::parse("file:///project/main.scad").unwrap(),
+                "use <lib/shapes.scad>\nx = area(size, 3);\ny = area(x, 1);\n",
+            )
+            .await
+            .with_document(
+                Url::parse("file:///project/other.scad").unwrap(),
+                "include <shapes.scad>\nfunction f(size) = area(size, size);\n",
+            )
+            .await
+            .with_workspace_folder(Url::parse("file:///project/lib").unwrap())
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_workspace_goto_definition() {
+        let backend = workspace_backend().await;
+        let main = Url::parse("file:///project/main.scad").unwrap();
+
+        let params = GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: main },
+                position: Position::new(1, 5),
+            },
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+
+        let definition = backend.goto_definition(params).await.unwrap();
+        assert_eq!(
+            definition,
+            Some(GotoDefinitionResponse::Scalar(Location::new(
+                Url::parse("file:///project/lib/shapes.scad").unwrap(),
+                Range::new(Position::new(1, 9), Position::new(1, 13)),
+            )))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_workspace_rename() {
+        let backend = workspace_backend().await;
+
+        let params = RenameParams {
+            text_document_position: TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier {
+                    uri: Url::parse("file:///project/main.scad").unwrap(),
+                },
+                position: Position::new(1, 10),
+            },
+            new_name: "length".to_owned(),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+
+        let changes = backend
+            .rename(params)
+            .await
+            .unwrap()
+            .unwrap()
+            .changes
+            .unwrap();
+        let mut changed: Vec<(String, Vec<Range>)> = changes
+            .into_iter()
+            .map(|(uri, edits)| {
+                assert!(edits.iter().all(|edit| edit.new_text == "length"));
+                (
+                    uri.to_string(),
+                    edits.into_iter().map(|edit| edit.range).collect(),
+                )
+            })
+            .collect();
+        changed.sort_by(|a, b| a.0.cmp(&b.0));
+
+        // `size` in other.scad is a function parameter and stays as is
+        assert_eq!(
+            changed,
+            vec![
+                (
+                    "file:///project/lib/shapes.scad".to_owned(),
+                    vec![Range::new(Position::new(0, 0), Position::new(0, 4))]
+                ),
+                (
+                    "file:///project/main.scad".to_owned(),
+                    vec![Range::new(Position::new(1, 9), Position::new(1, 13))]
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_workspace_diagnostics() {
+        let backend = workspace_backend()
+            .await
+            .with_document(
+                Url::parse("file:///project/broken.scad").unwrap(),
+                "include <caustic.scad>\ninclude <missing.scad>\nuse <bad.scad>\n",
+            )
+            .await
+            .with_document(
+                Url::parse("file:///project/bad.scad").unwrap(),
+                "x = 1\ny = 2;",
+            )
+            .await;
+
+        let diagnostics = backend
+            .handle_diagnostics(&Url::parse("file:///project/main.scad").unwrap())
+            .await;
+        assert_eq!(diagnostics, vec![]);
+
+        let diagnostics = backend
+            .handle_diagnostics(&Url::parse("file:///project/broken.scad").unwrap())
+            .await;
+        let summary: Vec<(Range, String)> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.range, diagnostic.message.clone()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Range::new(Position::new(1, 0), Position::new(1, 22)),
+                    "Cannot find \"missing.scad\"".to_owned()
+                ),
+                (
+                    Range::new(Position::new(2, 0), Position::new(2, 14)),
+                    "\"bad.scad\" has 1 error(s)".to_owned()
+                ),
+            ]
+        );
+        let related = diagnostics[1].related_information.as_ref().unwrap();
+        assert_eq!(
+            related[0].location.uri,
+            Url::parse("file:///project/bad.scad").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_syntax_diagnostics() {
+        let uri = Url::parse("file:///project/main.scad").unwrap();
+        let backend = LanguageServerBackend::new()
+            .with_document(
+                uri.clone(),
+                "module wheel() { cylinder(r=1, h=1); }\nwheel();\n",
+            )
+            .await;
+
+        let diagnostics = backend.handle_diagnostics(&uri).await;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "module declarations are not supported"
+        );
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+    }
+
+    #[tokio::test]
+    async fn test_documents_are_parsed_once_per_version() {
+        let uri = Url::parse("file:///project/main.scad").unwrap();
+        let backend = LanguageServerBackend::new()
+            .with_document(uri.clone(), "x = 1;")
+            .await;
+
+        let first = backend.load_document(&uri).await.unwrap();
+        let second = backend.load_document(&uri).await.unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let backend = backend.with_document(uri.clone(), "x = 1\n").await;
+        let changed = backend.load_document(&uri).await.unwrap();
+        assert!(!Arc::ptr_eq(&first, &changed));
+        assert_eq!(backend.handle_diagnostics(&uri).await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_library_diagnostics() {
+        let library = std::env::temp_dir().join("caustic-lsp-library-path");
+        std::fs::create_dir_all(&library).unwrap();
+        std::fs::write(library.join("parts.scad"), "function part() = 1;\n").unwrap();
+
+        let uri = Url::parse("file:///project/main.scad").unwrap();
+        let backend = LanguageServerBackend::new()
+            .with_document(
+                uri.clone(),
+                "include <BOSL2/std.scad>\ninclude <MCAD/gears.scad>\ninclude <parts.scad>\n",
+            )
+            .await;
+
+        let messages = |diagnostics: Vec<Diagnostic>| -> Vec<String> {
+            diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.message)
+                .collect()
+        };
+        let diagnostics = messages(backend.handle_diagnostics(&uri).await);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].starts_with("unsupported library feature: \"MCAD/gears.scad\""));
+        assert_eq!(diagnostics[1], "Cannot find \"parts.scad\"");
+
+        backend
+            .did_change_configuration(DidChangeConfigurationParams {
+                settings: LSPAny::from_iter([(
+                    "caustic",
+                    LSPAny::from_iter([(
+                        "libraryPath",
+                        LSPAny::from(vec![library.to_string_lossy().to_string()]),
+                    )]),
+                )]),
+            })
+            .await;
+        let diagnostics = messages(backend.handle_diagnostics(&uri).await);
+        assert_eq!(diagnostics.len(), 1);
+    }
 }
@@ -0,0 +1,690 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::*;
+
+use crate::language_server::{LanguageServerBackend, offset_to_position};
+use crate::library::{
+    LibraryPath, find_library_shim, is_known_library, unsupported_library_message,
+};
+use crate::parser::{
+    CallArgument, CallArgumentWithPosition, DeclArgument, Expr, ExprWithPosition, Statement,
+    StatementWithPosition,
+};
+use crate::{Message, MessageLevel};
+
+/// Library provided by the interpreter, it never exists on disk
+const BUILTIN_LIBRARY: &str = "caustic.scad";
+
+/// A document open in the editor or read from disk, with what the workspace
+/// features need from its parse. Parse results hold sources that cannot be
+/// sent between threads, so they are dropped once parsed.
+#[derive(Debug)]
+pub(super) struct Document {
+    uri: Url,
+    /// Editor version of an open document, `None` when read from disk
+    version: Option<i32>,
+    code: String,
+    /// The file name, start and end of each top level `include` and `use`
+    includes: Vec<(String, usize, usize)>,
+    names: Vec<Name>,
+    /// Parse messages, or a single error when the document could not be parsed
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Document {
+    fn parse(uri: &Url, version: Option<i32>, code: String) -> Self {
+        let (statements, diagnostics) = match LanguageServerBackend::parse_text(uri, &code) {
+            Ok(parse_result) => {
+                let diagnostics = parse_result
+                    .messages
+                    .iter()
+                    .map(|message| Diagnostic {
+                        range: message_range(&code, message),
+                        severity: Some(message_severity(message)),
+                        message: message.message.clone(),
+                        ..Default::default()
+                    })
+                    .collect();
+                (parse_result.statements.unwrap_or_default(), diagnostics)
+            }
+            Err(err) => (
+                vec![],
+                vec![Diagnostic {
+                    range: Range::default(),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    message: err.message.into_owned(),
+                    ..Default::default()
+                }],
+            ),
+        };
+
+        let includes = statements
+            .iter()
+            .filter_map(|statement| match &statement.item {
+                Statement::Include { filename } | Statement::Use { filename } => Some((
+                    filename.clone(),
+                    statement.position.start,
+                    statement.position.end,
+                )),
+                _ => None,
+            })
+            .collect();
+        let mut names = vec![];
+        collect_statement_names(&statements, &HashSet::new(), &mut names);
+
+        Self {
+            uri: uri.clone(),
+            version,
+            code,
+            includes,
+            names,
+            diagnostics,
+        }
+    }
+
+    /// Returns whether the document was parsed from this version of the file.
+    fn is_current(&self, version: Option<i32>, code: &str) -> bool {
+        // files on disk have no version, so their text is compared instead
+        self.version == version && (version.is_some() || self.code == code)
+    }
+
+    fn name_at(&self, pos: usize) -> Option<&Name> {
+        self.names
+            .iter()
+            .find(|name| (name.start..name.end).contains(&pos))
+    }
+
+    /// Errors in the document, with their range.
+    fn errors(&self) -> Vec<(Range, String)> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Some(DiagnosticSeverity::ERROR))
+            .map(|diagnostic| (diagnostic.range, diagnostic.message.clone()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NameKind {
+    Function,
+    Variable,
+}
+
+/// A function or variable name, where it is declared or where it is used.
+#[derive(Debug, Clone)]
+struct Name {
+    name: String,
+    kind: NameKind,
+    start: usize,
+    end: usize,
+    declaration: bool,
+}
+
+impl Name {
+    fn new(name: &str, kind: NameKind, start: usize, declaration: bool) -> Self {
+        Self {
+            name: name.to_owned(),
+            kind,
+            start,
+            end: start + name.chars().count(),
+            declaration,
+        }
+    }
+
+    fn same_symbol(&self, other: &Name) -> bool {
+        self.name == other.name && self.kind == other.kind
+    }
+
+    fn range(&self, code: &str) -> Range {
+        Range::new(
+            offset_to_position(code, self.start),
+            offset_to_position(code, self.end),
+        )
+    }
+}
+
+impl LanguageServerBackend {
+    /// Returns the text of an open document, falling back to the file on disk.
+    pub(super) async fn load_text(&self, uri: &Url) -> Option<String> {
+        if let Some(open) = self.document_map.read().await.get(uri) {
+            return Some(open.text.clone());
+        }
+        read_file(uri)
+    }
+
+    /// Returns the parsed document, parsing it again only when it changed
+    /// since it was last parsed.
+    pub(super) async fn load_document(&self, uri: &Url) -> Option<Arc<Document>> {
+        let open = self
+            .document_map
+            .read()
+            .await
+            .get(uri)
+            .map(|open| (open.version, open.text.clone()));
+        let (version, code) = match open {
+            Some((version, code)) => (Some(version), code),
+            None => (None, read_file(uri)?),
+        };
+
+        if let Some(document) = self.parsed_documents.read().await.get(uri)
+            && document.is_current(version, &code)
+        {
+            return Some(document.clone());
+        }
+
+        let document = Arc::new(Document::parse(uri, version, code));
+        self.parsed_documents
+            .write()
+            .await
+            .insert(uri.clone(), document.clone());
+        Some(document)
+    }
+
+    /// Finds the file an `include` or `use` in `from` refers to, relative to
+    /// the including file first, then to each workspace folder and last in
+    /// the library path.
+    async fn resolve_include(&self, from: &Url, filename: &str) -> Option<Url> {
+        let mut candidates = vec![from.join(filename).ok()];
+        for folder in self.workspace_folders.read().await.iter() {
+            candidates.push(directory_url(folder).join(filename).ok());
+        }
+        candidates.push(library_url(&*self.library_path.read().await, filename));
+
+        for candidate in candidates.into_iter().flatten() {
+            if self.load_text(&candidate).await.is_some() {
+                return Some(candidate);
+            }
+        }
+        None
+    }
+
+    /// Returns the document followed by every file it includes or uses,
+    /// directly or through other files.
+    async fn load_with_includes(&self, uri: &Url) -> Vec<Arc<Document>> {
+        let mut documents = vec![];
+        let mut seen = HashSet::from([uri.clone()]);
+        let mut queue = VecDeque::from([uri.clone()]);
+
+        while let Some(uri) = queue.pop_front() {
+            let Some(document) = self.load_document(&uri).await else {
+                continue;
+            };
+            for (filename, _, _) in &document.includes {
+                if let Some(include_uri) = self.resolve_include(&uri, filename).await
+                    && seen.insert(include_uri.clone())
+                {
+                    queue.push_back(include_uri);
+                }
+            }
+            documents.push(document);
+        }
+
+        documents
+    }
+
+    /// Every document the server knows of, open in the editor or a `.scad`
+    /// file inside a workspace folder.
+    async fn workspace_uris(&self) -> Vec<Url> {
+        let mut uris: Vec<Url> = self.document_map.read().await.keys().cloned().collect();
+        for folder in self.workspace_folders.read().await.iter() {
+            for uri in scad_files(folder) {
+                if !uris.contains(&uri) {
+                    uris.push(uri);
+                }
+            }
+        }
+        uris
+    }
+
+    /// Open documents that include or use `uri`, directly or through other files.
+    pub(super) async fn open_dependents(&self, uri: &Url) -> Vec<Url> {
+        let open: Vec<Url> = self.document_map.read().await.keys().cloned().collect();
+        let mut dependents = vec![];
+        for other in open {
+            if other != *uri
+                && self
+                    .load_with_includes(&other)
+                    .await
+                    .iter()
+                    .any(|document| document.uri == *uri)
+            {
+                dependents.push(other);
+            }
+        }
+        dependents
+    }
+
+    /// Parse errors of the document, plus a diagnostic on each `include` or
+    /// `use` whose file is missing or has errors of its own.
+    pub(super) async fn handle_diagnostics(&self, uri: &Url) -> Vec<Diagnostic> {
+        let Some(document) = self.load_document(uri).await else {
+            return vec![];
+        };
+        let code = &document.code;
+
+        let mut diagnostics = document.diagnostics.clone();
+
+        for (filename, start, end) in &document.includes {
+            let (start, end) = (*start, *end);
+            // the interpreter provides these itself
+            if filename.ends_with(BUILTIN_LIBRARY) || find_library_shim(filename).is_some() {
+                continue;
+            }
+            let range = Range::new(
+                offset_to_position(code, start),
+                offset_to_position(code, trim_end(code, start, end)),
+            );
+
+            if is_known_library(filename) {
+                diagnostics.push(Diagnostic {
+                    range,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    message: unsupported_library_message(filename),
+                    ..Default::default()
+                });
+                continue;
+            }
+
+            let Some(include_uri) = self.resolve_include(uri, filename).await else {
+                diagnostics.push(Diagnostic {
+                    range,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    message: format!("Cannot find \"{filename}\""),
+                    ..Default::default()
+                });
+                continue;
+            };
+
+            let Some(included) = self.load_document(&include_uri).await else {
+                continue;
+            };
+            let errors: Vec<DiagnosticRelatedInformation> = included
+                .errors()
+                .into_iter()
+                .map(|(range, message)| DiagnosticRelatedInformation {
+                    location: Location::new(include_uri.clone(), range),
+                    message,
+                })
+                .collect();
+            if !errors.is_empty() {
+                diagnostics.push(Diagnostic {
+                    range,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    message: format!("\"{filename}\" has {} error(s)", errors.len()),
+                    related_information: Some(errors),
+                    ..Default::default()
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Finds where the function or variable at `pos` is declared, in the
+    /// document or in the files it includes.
+    pub(super) async fn handle_goto_definition(
+        &self,
+        uri: &Url,
+        pos: usize,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let documents = self.load_with_includes(uri).await;
+        let Some(name) = documents
+            .first()
+            .and_then(|document| document.name_at(pos).cloned())
+        else {
+            return Ok(None);
+        };
+
+        let mut locations: Vec<Location> = documents
+            .iter()
+            .flat_map(|document| {
+                document
+                    .names
+                    .iter()
+                    .filter(|other| other.declaration && other.same_symbol(&name))
+                    .map(|other| Location::new(document.uri.clone(), other.range(&document.code)))
+            })
+            .collect();
+
+        Ok(match locations.len() {
+            0 => None,
+            1 => Some(GotoDefinitionResponse::Scalar(locations.remove(0))),
+            _ => Some(GotoDefinitionResponse::Array(locations)),
+        })
+    }
+
+    /// Renames the function or variable at `pos` in the files declaring it
+    /// and in every workspace file that includes one of them.
+    pub(super) async fn handle_rename(
+        &self,
+        uri: &Url,
+        pos: usize,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>> {
+        if !is_identifier(new_name) {
+            return Err(Error::invalid_params(format!(
+                "\"{new_name}\" is not a valid identifier"
+            )));
+        }
+
+        let documents = self.load_with_includes(uri).await;
+        let Some(name) = documents
+            .first()
+            .and_then(|document| document.name_at(pos).cloned())
+        else {
+            return Ok(None);
+        };
+
+        let declared_in: Vec<Url> = documents
+            .iter()
+            .filter(|document| {
+                document
+                    .names
+                    .iter()
+                    .any(|other| other.declaration && other.same_symbol(&name))
+            })
+            .map(|document| document.uri.clone())
+            .collect();
+        if declared_in.is_empty() {
+            return Err(Error::invalid_params(format!(
+                "\"{}\" is not declared in the workspace",
+                name.name
+            )));
+        }
+
+        let mut changes = HashMap::new();
+        for workspace_uri in self.workspace_uris().await {
+            let documents = self.load_with_includes(&workspace_uri).await;
+            if !documents
+                .iter()
+                .any(|document| declared_in.contains(&document.uri))
+            {
+                continue;
+            }
+            let Some(document) = documents.first() else {
+                continue;
+            };
+
+            let edits: Vec<TextEdit> = document
+                .names
+                .iter()
+                .filter(|other| other.same_symbol(&name))
+                .map(|other| TextEdit {
+                    range: other.range(&document.code),
+                    new_text: new_name.to_owned(),
+                })
+                .collect();
+            if !edits.is_empty() {
+                changes.insert(workspace_uri, edits);
+            }
+        }
+
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..Default::default()
+        }))
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_file(uri: &Url) -> Option<String> {
+    std::fs::read_to_string(uri.to_file_path().ok()?).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_file(_uri: &Url) -> Option<String> {
+    None
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn library_url(library_path: &LibraryPath, filename: &str) -> Option<Url> {
+    Url::from_file_path(library_path.find(filename)?).ok()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn library_url(_library_path: &LibraryPath, _filename: &str) -> Option<Url> {
+    None
+}
+
+/// Every `.scad` file below the folder, skipping hidden directories.
+#[cfg(not(target_arch = "wasm32"))]
+fn scad_files(folder: &Url) -> Vec<Url> {
+    fn walk(dir: &std::path::Path, files: &mut Vec<Url>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if path.is_dir() && !hidden {
+                walk(&path, files);
+            } else if path.extension().is_some_and(|ext| ext == "scad")
+                && let Ok(uri) = Url::from_file_path(&path)
+            {
+                files.push(uri);
+            }
+        }
+    }
+
+    let mut files = vec![];
+    if let Ok(path) = folder.to_file_path() {
+        walk(&path, &mut files);
+    }
+    files
+}
+
+#[cfg(target_arch = "wasm32")]
+fn scad_files(_folder: &Url) -> Vec<Url> {
+    vec![]
+}
+
+/// Workspace folder URLs usually lack the trailing slash `Url::join` needs to
+/// resolve inside them.
+fn directory_url(folder: &Url) -> Url {
+    let mut folder = folder.clone();
+    if !folder.path().ends_with('/') {
+        let path = format!("{}/", folder.path());
+        folder.set_path(&path);
+    }
+    folder
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|ch| ch.is_alphabetic() || ch == '_' || ch == '$')
+        && chars.all(|ch| ch.is_alphanumeric() || ch == '_')
+}
+
+fn message_range(code: &str, message: &Message) -> Range {
+    Range::new(
+        offset_to_position(code, message.position.start),
+        offset_to_position(code, message.position.end),
+    )
+}
+
+fn message_severity(message: &Message) -> DiagnosticSeverity {
+    match message.level {
+        MessageLevel::Error => DiagnosticSeverity::ERROR,
+        MessageLevel::Warning => DiagnosticSeverity::WARNING,
+        MessageLevel::Echo => DiagnosticSeverity::INFORMATION,
+    }
+}
+
+/// Statements end at the start of the next token, drops the whitespace in between.
+fn trim_end(code: &str, start: usize, end: usize) -> usize {
+    let chars: Vec<char> = code.chars().take(end).collect();
+    let mut end = end.min(chars.len());
+    while end > start && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+    end
+}
+
+/// Collects function and variable names, skipping variables shadowed by
+/// function parameters and loop variables.
+fn collect_statement_names(
+    statements: &[StatementWithPosition],
+    shadowed: &HashSet<String>,
+    names: &mut Vec<Name>,
+) {
+    for statement in statements {
+        match &statement.item {
+            Statement::Assignment { identifier, expr } => {
+                if !shadowed.contains(identifier) {
+                    names.push(Name::new(
+                        identifier,
+                        NameKind::Variable,
+                        statement.position.start,
+                        true,
+                    ));
+                }
+                collect_expr_names(expr, shadowed, names);
+            }
+            Statement::FunctionDecl {
+                function_name,
+                arguments,
+                expr,
+                doc: _,
+            } => {
+                names.push(Name::new(
+                    function_name,
+                    NameKind::Function,
+                    statement.position.start,
+                    true,
+                ));
+                let mut shadowed = shadowed.clone();
+                for argument in arguments {
+                    match &argument.item {
+                        DeclArgument::WithDefault {
+                            identifier,
+                            default_expr,
+                        } => {
+                            collect_expr_names(default_expr, &shadowed, names);
+                            shadowed.insert(identifier.clone());
+                        }
+                        DeclArgument::Identifier { identifier } => {
+                            shadowed.insert(identifier.clone());
+                        }
+                    }
+                }
+                collect_expr_names(expr, &shadowed, names);
+            }
+            Statement::If {
+                expr,
+                true_statements,
+                false_statements,
+            } => {
+                collect_expr_names(expr, shadowed, names);
+                collect_statement_names(true_statements, shadowed, names);
+                collect_statement_names(false_statements, shadowed, names);
+            }
+            Statement::ModuleInstantiation {
+                module_id,
+                call_arguments,
+                child_statements,
+            } => {
+                collect_call_argument_names(call_arguments, shadowed, names);
+                if module_id.item == "for" {
+                    let mut shadowed = shadowed.clone();
+                    for argument in call_arguments {
+                        if let CallArgument::NamedArgument {
+                            identifier,
+                            expr: _,
+                        } = &argument.item
+                        {
+                            shadowed.insert(identifier.clone());
+                        }
+                    }
+                    collect_statement_names(child_statements, &shadowed, names);
+                } else {
+                    collect_statement_names(child_statements, shadowed, names);
+                }
+            }
+            Statement::Empty | Statement::Include { .. } | Statement::Use { .. } => {}
+        }
+    }
+}
+
+fn collect_call_argument_names(
+    call_arguments: &[CallArgumentWithPosition],
+    shadowed: &HashSet<String>,
+    names: &mut Vec<Name>,
+) {
+    for argument in call_arguments {
+        match &argument.item {
+            CallArgument::NamedArgument {
+                identifier: _,
+                expr,
+            } => collect_expr_names(expr, shadowed, names),
+            CallArgument::Expr { expr } => collect_expr_names(expr, shadowed, names),
+        }
+    }
+}
+
+fn collect_expr_names(expr: &ExprWithPosition, shadowed: &HashSet<String>, names: &mut Vec<Name>) {
+    match &expr.item {
+        Expr::True | Expr::False | Expr::String(_) | Expr::Number(_) => {}
+        Expr::Identifier { name } => {
+            if !shadowed.contains(name) {
+                names.push(Name::new(
+                    name,
+                    NameKind::Variable,
+                    expr.position.start,
+                    false,
+                ));
+            }
+        }
+        Expr::FieldAccess { lhs, field: _ } => collect_expr_names(lhs, shadowed, names),
+        Expr::Range {
+            start,
+            end,
+            increment,
+        } => {
+            collect_expr_names(start, shadowed, names);
+            collect_expr_names(end, shadowed, names);
+            if let Some(increment) = increment {
+                collect_expr_names(increment, shadowed, names);
+            }
+        }
+        Expr::Vector { items } => {
+            for item in items {
+                collect_expr_names(item, shadowed, names);
+            }
+        }
+        Expr::Binary {
+            operator: _,
+            lhs,
+            rhs,
+        } => {
+            collect_expr_names(lhs, shadowed, names);
+            collect_expr_names(rhs, shadowed, names);
+        }
+        Expr::Unary { operator: _, rhs } => collect_expr_names(rhs, shadowed, names),
+        Expr::Ternary {
+            condition,
+            true_expr,
+            false_expr,
+        } => {
+            collect_expr_names(condition, shadowed, names);
+            collect_expr_names(true_expr, shadowed, names);
+            collect_expr_names(false_expr, shadowed, names);
+        }
+        Expr::Index { lhs, index } => {
+            collect_expr_names(lhs, shadowed, names);
+            collect_expr_names(index, shadowed, names);
+        }
+        Expr::FunctionCall { name, arguments } => {
+            names.push(Name::new(
+                name,
+                NameKind::Function,
+                expr.position.start,
+                false,
+            ));
+            collect_call_argument_names(arguments, shadowed, names);
+        }
+    }
+}
@@ -1,7 +1,9 @@
+pub mod animation;
 pub mod docs;
 pub mod docs_builtin;
 pub mod interpreter;
 pub mod language_server;
+pub mod library;
 pub mod parser;
 pub mod source;
 pub mod tokenizer;
@@ -14,7 +16,14 @@ use caustic_core::{Random, SceneData};
 
 use crate::source::Source;
 use crate::{
-    interpreter::openscad_interpret, parser::openscad_parse, tokenizer::openscad_tokenize,
+    interpreter::{
+        AssetKind, AssetReference, InterpreterLimits, InterpreterResults, SceneMaterial,
+        UnsupportedFeature, openscad_interpret_with_editable_materials,
+        openscad_interpret_with_limits,
+    },
+    library::LibraryPath,
+    parser::{StatementWithPosition, openscad_parse},
+    tokenizer::openscad_tokenize,
 };
 
 #[derive(Debug, Clone)]
@@ -83,9 +92,94 @@ pub type Result<T> = core::result::Result<T, Message>;
 pub struct OpenscadResults {
     pub scene_data: Option<SceneData>,
     pub messages: Vec<Message>,
+    pub assets: Vec<AssetReference>,
+    /// OpenSCAD features the scene uses which were skipped, each also reported
+    /// by a warning
+    pub unsupported_features: Vec<UnsupportedFeature>,
+    /// Materials the scene created, only when run with
+    /// [`run_openscad_with_editable_materials`]
+    pub materials: Vec<SceneMaterial>,
 }
 
+/// Returns an error message for every asset that does not exist.
+pub fn find_missing_assets(assets: &[AssetReference]) -> Vec<Message> {
+    assets
+        .iter()
+        .filter(|asset| !asset.position.source.has_file(&asset.filename))
+        .map(|asset| {
+            let kind = match asset.kind {
+                AssetKind::Image => "image",
+                AssetKind::Include => "include",
+                AssetKind::Mesh => "mesh",
+            };
+            Message {
+                level: MessageLevel::Error,
+                message: format!("missing {kind} \"{}\"", asset.filename),
+                position: asset.position.clone(),
+            }
+        })
+        .collect()
+}
+
+/// Runs a scene, finding library files in the directories listed in `OPENSCADPATH`.
 pub fn run_openscad(source: Arc<Box<dyn Source>>, random: Arc<dyn Random>) -> OpenscadResults {
+    run_openscad_with_library_path(source, random, LibraryPath::from_env())
+}
+
+pub fn run_openscad_with_library_path(
+    source: Arc<Box<dyn Source>>,
+    random: Arc<dyn Random>,
+    library_path: LibraryPath,
+) -> OpenscadResults {
+    run_openscad_at_time(source, random, library_path, 0.0)
+}
+
+/// Runs a scene with `$t` set to `time`, to render a frame of an animation,
+/// see [`animation::frame_time`].
+pub fn run_openscad_at_time(
+    source: Arc<Box<dyn Source>>,
+    random: Arc<dyn Random>,
+    library_path: LibraryPath,
+    time: f64,
+) -> OpenscadResults {
+    run_openscad_with_limits(
+        source,
+        random,
+        library_path,
+        time,
+        InterpreterLimits::default(),
+    )
+}
+
+/// Runs a scene within `limits`, for scenes from untrusted users.
+pub fn run_openscad_with_limits(
+    source: Arc<Box<dyn Source>>,
+    random: Arc<dyn Random>,
+    library_path: LibraryPath,
+    time: f64,
+    limits: InterpreterLimits,
+) -> OpenscadResults {
+    run_openscad_with_interpreter(source, |statements| {
+        openscad_interpret_with_limits(statements, random, library_path, time, limits)
+    })
+}
+
+/// Runs a scene with every material it creates made editable, so a user
+/// interface can change them without running the scene again.
+pub fn run_openscad_with_editable_materials(
+    source: Arc<Box<dyn Source>>,
+    random: Arc<dyn Random>,
+) -> OpenscadResults {
+    run_openscad_with_interpreter(source, |statements| {
+        openscad_interpret_with_editable_materials(statements, random, LibraryPath::from_env())
+    })
+}
+
+/// Tokenizes and parses a scene, then runs `interpret` on its statements.
+fn run_openscad_with_interpreter(
+    source: Arc<Box<dyn Source>>,
+    interpret: impl FnOnce(Vec<StatementWithPosition>) -> InterpreterResults,
+) -> OpenscadResults {
     let mut messages: Vec<Message> = vec![];
 
     let mut tokenize_results = openscad_tokenize(source.clone());
@@ -96,6 +190,9 @@ pub fn run_openscad(source: Arc<Box<dyn Source>>, random: Arc<dyn Random>) -> Op
         return OpenscadResults {
             scene_data: None,
             messages,
+            assets: vec![],
+            unsupported_features: vec![],
+            materials: vec![],
         };
     };
 
@@ -107,22 +204,58 @@ pub fn run_openscad(source: Arc<Box<dyn Source>>, random: Arc<dyn Random>) -> Op
         return OpenscadResults {
             scene_data: None,
             messages,
+            assets: vec![],
+            unsupported_features: vec![],
+            materials: vec![],
         };
     };
 
-    let mut interpret_results = openscad_interpret(statements, random);
+    let mut interpret_results = interpret(statements);
     messages.append(&mut interpret_results.messages);
+    let assets = interpret_results.assets;
+    let unsupported_features = interpret_results.unsupported_features;
+    let materials = interpret_results.materials;
+
+    // Report every missing file at once rather than rendering an incomplete scene.
+    // Files that failed to load during interpretation already have an error.
+    let missing_assets = find_missing_assets(&assets);
+    if !missing_assets.is_empty() {
+        for missing in missing_assets {
+            let reported = messages.iter().any(|message| {
+                message.level == MessageLevel::Error
+                    && message.position.start == missing.position.start
+                    && message.position.end == missing.position.end
+            });
+            if !reported {
+                messages.push(missing);
+            }
+        }
+        return OpenscadResults {
+            scene_data: None,
+            messages,
+            assets,
+            unsupported_features,
+            materials: vec![],
+        };
+    }
+
     let scene_data = if let Some(scene_data) = interpret_results.scene_data {
         scene_data
     } else {
         return OpenscadResults {
             scene_data: None,
             messages,
+            assets,
+            unsupported_features,
+            materials: vec![],
         };
     };
 
     OpenscadResults {
         scene_data: Some(scene_data),
         messages,
+        assets,
+        unsupported_features,
+        materials,
     }
 }
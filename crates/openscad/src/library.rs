@@ -0,0 +1,190 @@
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::source::Source;
+
+/// Environment variable listing library directories, the same one OpenSCAD reads.
+pub const LIBRARY_PATH_ENV: &str = "OPENSCADPATH";
+
+/// Directories searched for files given to `include` or `use` that are not
+/// next to the file including them.
+///
+/// # Examples
+///
+/// ```
+/// use caustic_openscad::library::LibraryPath;
+///
+/// let library_path = LibraryPath::new()
+///     .with_directory("/usr/share/openscad/libraries")
+///     .with_directory("/home/me/scad");
+/// assert_eq!(library_path.get_directories().len(), 2);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LibraryPath {
+    directories: Vec<PathBuf>,
+}
+
+impl LibraryPath {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the directories listed in `OPENSCADPATH`, separated like `PATH`.
+    pub fn from_env() -> Self {
+        let directories = env::var_os(LIBRARY_PATH_ENV)
+            .map(|paths| env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        Self { directories }
+    }
+
+    /// Adds a directory, searched after the ones already added.
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directories.push(directory.into());
+        self
+    }
+
+    /// Adds the directories of `other`, searched after the ones already added.
+    pub fn with_library_path(mut self, other: LibraryPath) -> Self {
+        self.directories.extend(other.directories);
+        self
+    }
+
+    pub fn get_directories(&self) -> &[PathBuf] {
+        &self.directories
+    }
+
+    /// Returns the path of `filename` in the first directory containing it.
+    pub fn find(&self, filename: &str) -> Option<PathBuf> {
+        self.directories
+            .iter()
+            .map(|directory| directory.join(filename))
+            .find(|path| path.is_file())
+    }
+
+    /// Loads `filename` from the first directory containing it.
+    pub fn load(&self, filename: &str) -> Option<Arc<Box<dyn Source>>> {
+        load_file(&self.find(filename)?)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_file(path: &Path) -> Option<Arc<Box<dyn Source>>> {
+    let source = crate::source::FileSource::new(path).ok()?;
+    Some(Arc::new(Box::new(source)))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_file(_path: &Path) -> Option<Arc<Box<dyn Source>>> {
+    None
+}
+
+/// A widely used OpenSCAD library file the interpreter provides itself, so
+/// scenes written for OpenSCAD render without the library installed.
+#[derive(Debug)]
+pub struct LibraryShim {
+    /// Name used to include the library, e.g. "BOSL2/std.scad"
+    pub filename: &'static str,
+    /// Modules the shim implements
+    pub modules: &'static [&'static str],
+    /// Constants the library defines
+    pub variables: &'static [(&'static str, LibraryValue)],
+}
+
+/// Value of a constant defined by a library shim.
+#[derive(Debug, Clone, Copy)]
+pub enum LibraryValue {
+    Number(f64),
+    Vector([f64; 3]),
+}
+
+/// Libraries recognized by name, including them without a shim reports an
+/// unsupported library feature instead of a missing file.
+pub const KNOWN_LIBRARIES: &[&str] = &["MCAD/", "BOSL/", "BOSL2/"];
+
+pub const LIBRARY_SHIMS: &[LibraryShim] = &[
+    LibraryShim {
+        filename: "MCAD/units.scad",
+        modules: &[],
+        variables: &[
+            ("mm", LibraryValue::Number(1.0)),
+            ("cm", LibraryValue::Number(10.0)),
+            ("dm", LibraryValue::Number(100.0)),
+            ("m", LibraryValue::Number(1000.0)),
+            ("inch", LibraryValue::Number(25.4)),
+            ("X", LibraryValue::Vector([1.0, 0.0, 0.0])),
+            ("Y", LibraryValue::Vector([0.0, 1.0, 0.0])),
+            ("Z", LibraryValue::Vector([0.0, 0.0, 1.0])),
+            ("M3", LibraryValue::Number(3.0)),
+            ("M4", LibraryValue::Number(4.0)),
+            ("M5", LibraryValue::Number(5.0)),
+            ("M6", LibraryValue::Number(6.0)),
+            ("M8", LibraryValue::Number(8.0)),
+            ("epsilon", LibraryValue::Number(0.01)),
+        ],
+    },
+    LibraryShim {
+        filename: "MCAD/boxes.scad",
+        modules: &["roundedBox"],
+        variables: &[],
+    },
+    LibraryShim {
+        filename: "BOSL2/std.scad",
+        modules: &[
+            "cuboid", "cyl", "spheroid", "up", "down", "left", "right", "fwd", "back", "xrot",
+            "yrot", "zrot",
+        ],
+        variables: &[],
+    },
+];
+
+/// Returns the shim for an included library file, matching the end of the
+/// path so "lib/BOSL2/std.scad" finds "BOSL2/std.scad".
+pub fn find_library_shim(filename: &str) -> Option<&'static LibraryShim> {
+    let filename = filename.replace('\\', "/");
+    LIBRARY_SHIMS.iter().find(|shim| {
+        filename == shim.filename || filename.ends_with(&format!("/{}", shim.filename))
+    })
+}
+
+/// Returns true if the file belongs to a library known by name.
+pub fn is_known_library(filename: &str) -> bool {
+    let filename = filename.replace('\\', "/");
+    KNOWN_LIBRARIES
+        .iter()
+        .any(|library| filename.starts_with(library) || filename.contains(&format!("/{library}")))
+}
+
+/// Message for an include of a known library without a shim.
+pub fn unsupported_library_message(filename: &str) -> String {
+    let supported = LIBRARY_SHIMS
+        .iter()
+        .map(|shim| shim.filename)
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("unsupported library feature: \"{filename}\" is not available, supported: {supported}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_library_shim() {
+        assert_eq!(
+            find_library_shim("BOSL2/std.scad").map(|shim| shim.filename),
+            Some("BOSL2/std.scad")
+        );
+        assert_eq!(
+            find_library_shim("libraries\\MCAD\\units.scad").map(|shim| shim.filename),
+            Some("MCAD/units.scad")
+        );
+        assert!(find_library_shim("MCAD/gears.scad").is_none());
+        assert!(find_library_shim("my_std.scad").is_none());
+
+        assert!(is_known_library("MCAD/gears.scad"));
+        assert!(!is_known_library("parts/gears.scad"));
+    }
+}
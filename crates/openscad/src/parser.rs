@@ -18,13 +18,16 @@ pub enum Statement {
     },
     /// "include" <include_file>
     Include { filename: String },
-    // TODO "use" <include_file>
+    /// "use" <include_file>
+    Use { filename: String },
     // TODO "module" <identifier> '(' <arguments_decl> <optional_commas> ')' <statement>
     // "function" <identifier> '(' <arguments_decl> <optional_commas> ')' '=' <expr> ';'
     FunctionDecl {
         function_name: String,
         arguments: Vec<DeclArgumentWithPosition>,
         expr: ExprWithPosition,
+        /// Comment on the lines directly before the declaration
+        doc: Option<String>,
     },
 
     // TODO '!' <module_instantiation>
@@ -244,6 +247,34 @@ impl Parser {
         }
     }
 
+    /// Returns the comment directly before the current token if it starts on a
+    /// line of its own. Comments are only in the token stream when tokenized with
+    /// [`crate::tokenizer::openscad_tokenize_with_comments`].
+    fn doc_comment(&self) -> Option<String> {
+        let comment = self.tokens.get(self.pos.checked_sub(1)?)?;
+        let Token::Comment(text) = &comment.item else {
+            return None;
+        };
+
+        // a trailing comment on the line of the previous statement is not documentation
+        if let Some(previous) = self.pos.checked_sub(2).and_then(|i| self.tokens.get(i)) {
+            let start = previous.position.end;
+            let end = comment.position.start;
+            let on_own_line = self
+                .source
+                .get_code()
+                .chars()
+                .skip(start)
+                .take(end.saturating_sub(start))
+                .any(|ch| ch == '\n');
+            if !on_own_line {
+                return None;
+            }
+        }
+
+        Some(text.clone())
+    }
+
     fn get_current_pos(&mut self) -> Result<Position> {
         match self.current() {
             Some(current) => Ok(current.position.clone()),
@@ -370,15 +401,22 @@ impl Parser {
 
         // TODO '{' <inner_input> '}'
 
-        // TODO "use" <include_file>
         // "include" <include_file>
+        // "use" <include_file>
         if let Some(tok) = self.current()
-            && let Token::Include { filename } = &tok.item
+            && let Some(statement) = match &tok.item {
+                Token::Include { filename } => Some(Statement::Include {
+                    filename: filename.to_owned(),
+                }),
+                Token::Use { filename } => Some(Statement::Use {
+                    filename: filename.to_owned(),
+                }),
+                _ => None,
+            }
         {
-            let filename = filename.to_owned();
             self.advance();
             return Ok(StatementWithPosition::new(
-                Statement::Include { filename },
+                statement,
                 Position {
                     start: pos.start,
                     end: self.current_token_start(),
@@ -389,11 +427,12 @@ impl Parser {
 
         if let Some(identifier) = self.current_matches_identifier() {
             if identifier == "function" {
+                let doc = self.doc_comment();
                 self.advance(); // function
-                return self.parse_function_decl();
+                return self.parse_function_decl(doc);
             } else if identifier == "module" {
                 // TODO "module" <identifier> '(' <arguments_decl> <optional_commas> ')' <statement>
-                todo!("module decl")
+                return self.skip_module_decl();
             }
         }
 
@@ -511,7 +550,11 @@ impl Parser {
             ));
         }
 
-        todo!("parse_module_id: {:?}", self.current());
+        Err(Message {
+            level: MessageLevel::Error,
+            message: "Expected for or identifier, found EOF".to_owned(),
+            position: pos,
+        })
     }
 
     /// <call_arguments> ::=
@@ -680,7 +723,11 @@ impl Parser {
         let token = if let Some(token) = self.current() {
             token.clone()
         } else {
-            todo!("error missing token");
+            return Err(Message {
+                level: MessageLevel::Error,
+                message: "Expected expression, found EOF".to_owned(),
+                position: pos,
+            });
         };
 
         let mut lhs: ExprWithPosition = match &token.item {
@@ -704,7 +751,13 @@ impl Parser {
 
                     if !found_comma && self.current_matches(Token::Colon) {
                         if expressions.is_empty() {
-                            todo!("add error since leading colon is not allowed");
+                            let position = self.get_current_pos()?;
+                            self.advance();
+                            return Err(Message {
+                                level: MessageLevel::Error,
+                                message: "Expected expression before ':'".to_owned(),
+                                position,
+                            });
                         }
                         found_colon = true;
                         self.expect(Token::Colon)?;
@@ -727,7 +780,18 @@ impl Parser {
                         let end_expr = Box::new(expressions.remove(0));
                         (start_expr, end_expr, increment_expr)
                     } else {
-                        todo!("add error since colon statements must be 2 or 3 expressions");
+                        return Err(Message {
+                            level: MessageLevel::Error,
+                            message: format!(
+                                "Expected 2 or 3 expressions in range, found {}",
+                                expressions.len()
+                            ),
+                            position: Position {
+                                start: pos.start,
+                                end: self.current_token_start(),
+                                source: pos.source,
+                            },
+                        });
                     };
 
                     ExprWithPosition::new(
@@ -929,7 +993,7 @@ impl Parser {
                         },
                     );
                 } else {
-                    todo!("expected identifier");
+                    self.expect_identifier()?;
                 }
             } else {
                 break;
@@ -946,9 +1010,17 @@ impl Parser {
             if tok.item == Token::Eof {
                 break;
             }
+            let start = self.pos;
             match self.parse_statement() {
                 Ok(stmt) => statements.push(stmt),
-                Err(err) => self.messages.push(err),
+                Err(err) => {
+                    self.messages.push(err);
+                    // skip the token the statement failed on, so a statement
+                    // which fails without consuming anything is not retried forever
+                    if self.pos == start {
+                        self.advance();
+                    }
+                }
             }
         }
 
@@ -958,18 +1030,36 @@ impl Parser {
         }
     }
 
+    /// "module" <identifier> '(' <arguments_decl> <optional_commas> ')' <statement>
+    ///
+    /// Module declarations are not supported yet, so the declaration is parsed
+    /// to find where it ends and reported as an error.
+    fn skip_module_decl(&mut self) -> Result<StatementWithPosition> {
+        let pos = self.get_current_pos()?;
+
+        self.advance(); // module
+        self.expect_identifier()?;
+        self.parse_decl_arguments()?;
+        self.parse_child_statements()?;
+
+        Err(Message {
+            level: MessageLevel::Error,
+            message: "module declarations are not supported".to_owned(),
+            position: Position {
+                start: pos.start,
+                end: self.current_token_start(),
+                source: pos.source,
+            },
+        })
+    }
+
     /// <assignment> ::=
     ///   <identifier> '=' <expr> ';'
     fn parse_assignment(&mut self) -> Result<StatementWithPosition> {
         let pos = self.get_current_pos()?;
 
         // <identifier>
-        let identifier = if let Some(identifier) = self.current_matches_identifier() {
-            self.advance();
-            identifier
-        } else {
-            todo!("expected identifier")
-        };
+        let identifier = self.expect_identifier()?;
 
         // '='
         self.expect(Token::Equals)?;
@@ -1015,7 +1105,7 @@ impl Parser {
     }
 
     /// <identifier> '(' <arguments_decl> <optional_commas> ')' '=' <expr> ';'
-    fn parse_function_decl(&mut self) -> Result<StatementWithPosition> {
+    fn parse_function_decl(&mut self, doc: Option<String>) -> Result<StatementWithPosition> {
         let pos = self.get_current_pos()?;
 
         let function_name = self.expect_identifier()?;
@@ -1033,6 +1123,7 @@ impl Parser {
                 function_name,
                 arguments,
                 expr,
+                doc,
             },
             Position {
                 start: pos.start,
@@ -1081,7 +1172,19 @@ impl Parser {
         };
 
         if self.current_matches(Token::Equals) {
-            todo!("decl arg with default");
+            self.advance();
+            let default_expr = self.parse_expr()?;
+            Ok(Some(DeclArgumentWithPosition::new(
+                DeclArgument::WithDefault {
+                    identifier,
+                    default_expr,
+                },
+                Position {
+                    start: pos.start,
+                    end: self.current_token_start(),
+                    source: pos.source,
+                },
+            )))
         } else {
             Ok(Some(DeclArgumentWithPosition::new(
                 DeclArgument::Identifier { identifier },
@@ -1145,7 +1248,7 @@ mod tests {
 
     use crate::{
         source::{Source, StringSource},
-        tokenizer::openscad_tokenize,
+        tokenizer::{openscad_tokenize, openscad_tokenize_with_comments},
     };
 
     use super::*;
@@ -1336,6 +1439,21 @@ mod tests {
         assert_eq!(1, result.statements.unwrap().len());
     }
 
+    #[test]
+    fn test_use() {
+        let source: Arc<Box<dyn Source>> =
+            Arc::new(Box::new(StringSource::new("use <shapes.scad>\nx = 1;")));
+        let result = parse(source);
+        assert_eq!(Vec::<Message>::new(), result.messages);
+        let statements = result.statements.unwrap();
+        assert_eq!(
+            statements[0].item,
+            Statement::Use {
+                filename: "shapes.scad".to_owned()
+            }
+        );
+    }
+
     #[test]
     fn test_function_call() {
         let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(
@@ -1435,4 +1553,61 @@ mod tests {
         assert_eq!(0, result.messages.len());
         assert_eq!(1, result.statements.unwrap().len());
     }
+
+    #[test]
+    fn test_function_doc_comment() {
+        let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(
+            r#"
+            x = 1; // not documentation
+            function a() = 1;
+
+            /**
+             * Area of a rectangle.
+             */
+            function area(w, h) = w * h;
+            "#,
+        )));
+        let tokens = openscad_tokenize_with_comments(source.clone())
+            .tokens
+            .unwrap();
+        let result = openscad_parse(tokens, source);
+        assert_eq!(Vec::<Message>::new(), result.messages);
+
+        let docs: Vec<Option<String>> = result
+            .statements
+            .unwrap()
+            .into_iter()
+            .filter_map(|statement| match statement.item {
+                Statement::FunctionDecl { doc, .. } => Some(doc),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(docs, [None, Some("Area of a rectangle.".to_owned())]);
+    }
+
+    #[test]
+    fn test_module_decl_is_an_error() {
+        let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(
+            "module wheel(r, w = 2) { cylinder(r=r, h=w); }\ncube(1);",
+        )));
+        let result = parse(source);
+        assert_eq!(1, result.messages.len());
+        assert_eq!(
+            "module declarations are not supported",
+            result.messages[0].message
+        );
+        assert_eq!(0, result.messages[0].position.start);
+        assert_eq!(47, result.messages[0].position.end);
+        // parsing carries on after the declaration
+        assert_eq!(1, result.statements.unwrap().len());
+    }
+
+    #[test]
+    fn test_invalid_ranges_are_errors() {
+        for code in ["x = [:1];", "x = [1:2:3:4];", "x = a.;", "x = "] {
+            let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(code)));
+            let result = parse(source);
+            assert!(!result.messages.is_empty(), "{code}");
+        }
+    }
 }
@@ -17,6 +17,8 @@ pub struct FileSource {
     filename: String,
     filename_path: PathBuf,
     code: String,
+    /// Directory the files read by the scene must be in, canonicalized
+    root: Option<PathBuf>,
 }
 
 impl FileSource {
@@ -27,8 +29,40 @@ impl FileSource {
             filename,
             filename_path: filename_path.to_owned(),
             code,
+            root: None,
         })
     }
+
+    /// Reads a scene which may only include, import and read images from
+    /// files within `root`, for scenes uploaded by users to a server. Files
+    /// given with absolute paths, or leaving `root` with `..` or symbolic
+    /// links, are treated as missing.
+    pub fn new_within(filename_path: &Path, root: &Path) -> std::io::Result<Self> {
+        let root = root.canonicalize()?;
+        if !filename_path.canonicalize()?.starts_with(&root) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("{filename_path:?} is outside of {root:?}"),
+            ));
+        }
+        Ok(Self {
+            root: Some(root),
+            ..Self::new(filename_path)?
+        })
+    }
+
+    /// Returns the path of `filename` relative to this source, or `None` when
+    /// it is outside of the root directory.
+    fn resolve(&self, filename: &str) -> Option<PathBuf> {
+        let path = self.filename_path.parent()?.join(filename);
+        match &self.root {
+            Some(root) => path
+                .canonicalize()
+                .ok()
+                .filter(|path| path.starts_with(root)),
+            None => Some(path),
+        }
+    }
 }
 
 impl Source for FileSource {
@@ -49,18 +83,66 @@ impl Source for FileSource {
     }
 
     fn get_image(&self, filename: &str) -> Result<Arc<dyn Image>, ImageError> {
-        let dir = self
-            .filename_path
-            .parent()
-            .ok_or(ImageError::Other(format!(
-                "source file \"{:?}\" has no parent",
-                self.filename_path
-            )))?;
-        let image_filename = dir.join(filename);
+        let image_filename = self.resolve(filename).ok_or(ImageError::Other(format!(
+            "image \"{filename}\" not found next to \"{:?}\"",
+            self.filename_path
+        )))?;
         ImageImage::load_file(image_filename)
     }
 
+    fn has_file(&self, filename: &str) -> bool {
+        self.resolve(filename).is_some_and(|path| path.is_file())
+    }
+
+    fn get_source(&self, filename: &str) -> Option<Arc<Box<dyn Source>>> {
+        let path = self.resolve(filename)?;
+        let source = FileSource {
+            root: self.root.clone(),
+            ..FileSource::new(&path).ok()?
+        };
+        Some(Arc::new(Box::new(source)))
+    }
+
     fn get_filename(&self) -> &str {
         &self.filename
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::FileSource;
+    use crate::source::Source;
+
+    #[test]
+    fn new_within_keeps_files_in_root() {
+        let dir = std::env::temp_dir().join("caustic-file-source-within");
+        let project = dir.join("project");
+        fs::create_dir_all(project.join("parts")).unwrap();
+        fs::write(dir.join("secret.scad"), "secret = 1;\n").unwrap();
+        fs::write(project.join("main.scad"), "include <parts/part.scad>\n").unwrap();
+        fs::write(project.join("parts/part.scad"), "part = 1;\n").unwrap();
+
+        let source = FileSource::new_within(&project.join("main.scad"), &project).unwrap();
+        assert!(source.has_file("parts/part.scad"));
+        assert!(source.get_source("parts/part.scad").is_some());
+        assert!(!source.has_file("../secret.scad"));
+        assert!(source.get_source("../secret.scad").is_none());
+        let absolute = dir.join("secret.scad");
+        assert!(!source.has_file(absolute.to_str().unwrap()));
+        assert!(source.get_image("../secret.scad").is_err());
+
+        // Sources included from within the root are confined too
+        let part = source.get_source("parts/part.scad").unwrap();
+        assert!(!part.has_file("../../secret.scad"));
+        assert!(part.has_file("../main.scad"));
+
+        assert!(FileSource::new_within(&dir.join("secret.scad"), &project).is_err());
+
+        // Without a root, files anywhere can be read
+        let source = FileSource::new(&project.join("main.scad")).unwrap();
+        assert!(source.has_file("../secret.scad"));
+        assert!(source.has_file(absolute.to_str().unwrap()));
+    }
+}
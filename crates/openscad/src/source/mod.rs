@@ -12,6 +12,19 @@ pub trait Source: Debug {
     fn get_filename(&self) -> &str;
     fn get_code(&self) -> &str;
     fn get_image(&self, filename: &str) -> Result<Arc<dyn Image>, ImageError>;
+    /// Returns a mesh file relative to this source as converted by the web
+    /// app, see [`caustic_core::object::ConvertedMesh`].
+    fn get_converted_mesh(&self, filename: &str) -> Result<Vec<u8>, String> {
+        Err(format!(
+            "\"{filename}\" (meshes can only be imported in the web app)"
+        ))
+    }
+    /// Returns true if `filename`, relative to this source, can be loaded.
+    fn has_file(&self, filename: &str) -> bool;
+    /// Loads another source file, relative to this source.
+    fn get_source(&self, _filename: &str) -> Option<Arc<Box<dyn Source>>> {
+        None
+    }
     fn as_any(&self) -> &dyn Any;
 
     fn equals(&self, other: &dyn Source) -> bool {
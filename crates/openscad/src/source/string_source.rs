@@ -27,7 +27,13 @@ impl Source for StringSource {
     }
 
     fn get_image(&self, filename: &str) -> Result<Arc<dyn Image>, ImageError> {
-        todo!("get_image {filename}")
+        Err(ImageError::NotFound(format!(
+            "\"{filename}\" (string sources cannot load images)"
+        )))
+    }
+
+    fn has_file(&self, _filename: &str) -> bool {
+        false
     }
 
     fn get_filename(&self) -> &str {
@@ -108,6 +108,8 @@ struct Tokenizer {
     input: Vec<char>,
     pos: usize,
     source: Arc<Box<dyn Source>>,
+    /// Include comment tokens in the results
+    emit_comments: bool,
 }
 
 impl Tokenizer {
@@ -116,15 +118,25 @@ impl Tokenizer {
             input: source.get_code().chars().collect(),
             pos: 0,
             source: source.clone(),
+            emit_comments: false,
         }
     }
 
+    pub fn with_comments(mut self) -> Self {
+        self.emit_comments = true;
+        self
+    }
+
     pub fn tokenize(mut self) -> TokenizerResults {
         let mut tokens = Vec::new();
         loop {
             match self.next() {
                 Ok(token) => match token {
-                    Some(token) => tokens.push(token),
+                    Some(token) => {
+                        if self.emit_comments || !matches!(token.item, Token::Comment(_)) {
+                            tokens.push(token);
+                        }
+                    }
                     None => break,
                 },
                 Err(err) => {
@@ -227,6 +239,9 @@ impl Tokenizer {
         ident
     }
 
+    /// Reads a number such as `42`, `4.2`, `.5`, `1.` or `1.5e-3`. Only ASCII
+    /// digits and `.` are accepted, so the result does not depend on the locale.
+    /// An `e` that is not followed by exponent digits is left for the next token.
     fn try_read_number(&mut self) -> Option<f64> {
         let mut result = String::new();
         let mut offset = 0;
@@ -255,26 +270,33 @@ impl Tokenizer {
         if let Some(ch) = self.peek(offset)
             && (ch == 'e' || ch == 'E')
         {
-            result.push(ch);
-            offset += 1;
+            let mut exponent = String::from(ch);
+            let mut exponent_offset = offset + 1;
 
             // +/-
-            if let Some(ch) = self.peek(offset)
+            if let Some(ch) = self.peek(exponent_offset)
                 && (ch == '+' || ch == '-')
             {
-                result.push(ch);
-                offset += 1;
+                exponent.push(ch);
+                exponent_offset += 1;
             }
 
             // number
-            while let Some(ch) = self.peek(offset) {
+            let mut found_exponent = false;
+            while let Some(ch) = self.peek(exponent_offset) {
                 if ch.is_ascii_digit() {
-                    result.push(ch);
-                    offset += 1;
+                    exponent.push(ch);
+                    exponent_offset += 1;
+                    found_exponent = true;
                 } else {
                     break;
                 }
             }
+
+            if found_exponent {
+                result += &exponent;
+                offset = exponent_offset;
+            }
         }
 
         match result.parse() {
@@ -332,8 +354,15 @@ impl Tokenizer {
                 Token::Semicolon
             }
             Some('.') => {
-                self.advance();
-                Token::Period
+                if let Some(ch) = self.peek(1)
+                    && ch.is_ascii_digit()
+                    && let Some(number) = self.try_read_number()
+                {
+                    Token::Number(number)
+                } else {
+                    self.advance();
+                    Token::Period
+                }
             }
             Some('+') => {
                 self.advance();
@@ -608,13 +637,18 @@ impl Tokenizer {
                 result += &format!("{}", ch);
             }
         }
+        // "/**" doc comments start with an extra asterisk
         let result = result
+            .trim_start_matches('*')
             .trim()
             .lines()
-            .map(|line| line.trim().trim_start_matches("* "))
+            .map(|line| match line.trim() {
+                "*" => "",
+                line => line.trim_start_matches("* "),
+            })
             .collect::<Vec<_>>()
             .join("\n");
-        Token::Comment(result)
+        Token::Comment(result.trim().to_string())
     }
 
     fn read_line_comment(&mut self) -> Token {
@@ -641,11 +675,20 @@ impl Tokenizer {
     }
 }
 
+/// Tokenizes the source, dropping comments.
 pub fn openscad_tokenize(source: Arc<Box<dyn Source>>) -> TokenizerResults {
     let tokenizer = Tokenizer::new(source);
     tokenizer.tokenize()
 }
 
+/// Tokenizes the source, keeping comments as [`Token::Comment`] tokens with
+/// their positions. The parser attaches a comment directly before a declaration
+/// to it as documentation, and tools that rewrite the source can keep them.
+pub fn openscad_tokenize_with_comments(source: Arc<Box<dyn Source>>) -> TokenizerResults {
+    let tokenizer = Tokenizer::new(source).with_comments();
+    tokenizer.tokenize()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::source::StringSource;
@@ -653,13 +696,13 @@ mod tests {
     use super::*;
 
     fn assert_tokens_with_pos(source: Arc<Box<dyn Source>>, expected: &[TokenWithPosition]) {
-        let found = openscad_tokenize(source);
+        let found = openscad_tokenize_with_comments(source);
         assert_eq!(found.tokens.unwrap(), expected);
     }
 
     fn assert_tokens(input: &str, expected: &[Token]) {
         let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(input)));
-        let found = openscad_tokenize(source);
+        let found = openscad_tokenize_with_comments(source);
         let found_without_pos: Vec<Token> = found
             .tokens
             .unwrap()
@@ -673,7 +716,7 @@ mod tests {
         let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(input)));
         assert_tokens_with_pos(
             source.clone(),
-            &vec![
+            &[
                 TokenWithPosition::new(
                     token,
                     Position {
@@ -694,6 +737,69 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_number_edge_cases() {
+        assert_token_with_pos("0", Token::Number(0.0), 0, 1);
+        assert_token_with_pos("007", Token::Number(7.0), 0, 3);
+        assert_token_with_pos(".5", Token::Number(0.5), 0, 2);
+        assert_token_with_pos("1.", Token::Number(1.0), 0, 2);
+        assert_token_with_pos("1.e3", Token::Number(1000.0), 0, 4);
+        assert_token_with_pos(".5e-2", Token::Number(0.005), 0, 5);
+        assert_token_with_pos("1e3", Token::Number(1000.0), 0, 3);
+        assert_token_with_pos("1E+3", Token::Number(1000.0), 0, 4);
+        assert_token_with_pos("1e-0", Token::Number(1.0), 0, 4);
+
+        // an exponent without digits is not part of the number
+        assert_tokens(
+            "1e",
+            &[
+                Token::Number(1.0),
+                Token::Identifier("e".to_owned()),
+                Token::Eof,
+            ],
+        );
+        assert_tokens(
+            "2e+",
+            &[
+                Token::Number(2.0),
+                Token::Identifier("e".to_owned()),
+                Token::Plus,
+                Token::Eof,
+            ],
+        );
+
+        // only one decimal point per number
+        assert_tokens(
+            "1.5.3",
+            &[Token::Number(1.5), Token::Number(0.3), Token::Eof],
+        );
+
+        // a period not followed by a digit is member access
+        assert_tokens(
+            "v.x",
+            &[
+                Token::Identifier("v".to_owned()),
+                Token::Period,
+                Token::Identifier("x".to_owned()),
+                Token::Eof,
+            ],
+        );
+        assert_tokens("-.5", &[Token::Minus, Token::Number(0.5), Token::Eof]);
+        assert_tokens(
+            "[0:.5:1]",
+            &[
+                Token::LeftBracket,
+                Token::Number(0.0),
+                Token::Colon,
+                Token::Number(0.5),
+                Token::Colon,
+                Token::Number(1.0),
+                Token::RightBracket,
+                Token::Eof,
+            ],
+        );
+    }
+
     #[test]
     fn test_re_number() {
         assert_token_with_pos("1", Token::Number(1.0), 0, 1);
@@ -705,7 +811,7 @@ mod tests {
         let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new("42.34a")));
         assert_tokens_with_pos(
             source.clone(),
-            &vec![
+            &[
                 TokenWithPosition::new(
                     Token::Number(42.34),
                     Position {
@@ -742,7 +848,7 @@ mod tests {
         let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new("cube(")));
         assert_tokens_with_pos(
             source.clone(),
-            &vec![
+            &[
                 TokenWithPosition::new(
                     Token::Identifier("cube".to_string()),
                     Position {
@@ -776,7 +882,7 @@ mod tests {
         let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new("cube(10);")));
         assert_tokens_with_pos(
             source.clone(),
-            &vec![
+            &[
                 TokenWithPosition {
                     item: Token::Identifier("cube".to_string()),
                     position: Position {
@@ -833,7 +939,7 @@ mod tests {
     fn test_cube_vector() {
         assert_tokens(
             "cube([20,30,50]);",
-            &vec![
+            &[
                 Token::Identifier("cube".to_string()),
                 Token::LeftParen,
                 Token::LeftBracket,
@@ -854,7 +960,7 @@ mod tests {
     fn test_cube_named_parameter() {
         assert_tokens(
             "cube(size=20);",
-            &vec![
+            &[
                 Token::Identifier("cube".to_string()),
                 Token::LeftParen,
                 Token::Identifier("size".to_string()),
@@ -871,7 +977,7 @@ mod tests {
     fn test_set_fa() {
         assert_tokens(
             "$fa = 1;",
-            &vec![
+            &[
                 Token::Identifier("$fa".to_string()),
                 Token::Equals,
                 Token::Number(1.0),
@@ -885,7 +991,7 @@ mod tests {
     fn test_include() {
         assert_tokens(
             "include <test.scad>",
-            &vec![
+            &[
                 Token::Include {
                     filename: "test.scad".to_owned(),
                 },
@@ -898,7 +1004,7 @@ mod tests {
     fn test_string() {
         assert_tokens(
             r#" "Test \"quotes\"" "#,
-            &vec![Token::String("Test \"quotes\"".to_owned()), Token::Eof],
+            &[Token::String("Test \"quotes\"".to_owned()), Token::Eof],
         );
     }
 
@@ -906,7 +1012,7 @@ mod tests {
     fn test_block_comment() {
         assert_tokens(
             "/* this is a multi-line\nblock comment */",
-            &vec![
+            &[
                 Token::Comment("this is a multi-line\nblock comment".to_owned()),
                 Token::Eof,
             ],
@@ -917,29 +1023,60 @@ mod tests {
     fn test_block_comment_leading_asterisk() {
         assert_tokens(
             "/* this is a multi-line\n * block comment */",
-            &vec![
+            &[
                 Token::Comment("this is a multi-line\nblock comment".to_owned()),
                 Token::Eof,
             ],
         );
     }
 
+    #[test]
+    fn test_block_doc_comment() {
+        assert_tokens(
+            "/**\n * Area of a rectangle.\n *\n * Width times height.\n */",
+            &[
+                Token::Comment("Area of a rectangle.\n\nWidth times height.".to_owned()),
+                Token::Eof,
+            ],
+        );
+    }
+
     #[test]
     fn test_line_comment() {
         assert_tokens(
             "// this is a line comment\n",
-            &vec![
+            &[
                 Token::Comment("this is a line comment".to_owned()),
                 Token::Eof,
             ],
         );
     }
 
+    #[test]
+    fn test_comments_dropped_by_default() {
+        let source: Arc<Box<dyn Source>> =
+            Arc::new(Box::new(StringSource::new("// comment\na /* b */;")));
+        let found: Vec<Token> = openscad_tokenize(source)
+            .tokens
+            .unwrap()
+            .into_iter()
+            .map(|tok| tok.item)
+            .collect();
+        assert_eq!(
+            found,
+            [
+                Token::Identifier("a".to_owned()),
+                Token::Semicolon,
+                Token::Eof
+            ]
+        );
+    }
+
     #[test]
     fn test_line_comment_combine() {
         assert_tokens(
             "  // this is a line comment\n  // next line\n",
-            &vec![
+            &[
                 Token::Comment("this is a line comment\nnext line".to_owned()),
                 Token::Eof,
             ],
@@ -1,6 +1,6 @@
 use std::{fmt::Display, sync::Arc};
 
-use caustic_core::{Color, Vector3, texture::Texture};
+use caustic_core::{Color, Vector3, material::Material, texture::Texture};
 
 use crate::WithPosition;
 
@@ -20,6 +20,9 @@ pub enum Value {
     },
     Boolean(bool),
     Texture(Arc<dyn Texture>),
+    /// A material created by calling a material module as a function, e.g.
+    /// `metal([0.9, 0.9, 0.9], 0.05)`, applied with `apply()`
+    Material(Arc<dyn Material>),
     Range {
         start: Box<Value>,
         end: Box<Value>,
@@ -32,6 +35,21 @@ pub enum Value {
 }
 
 impl Value {
+    /// Returns the name of the value's type, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Number(_) => "number",
+            Value::String(_) => "string",
+            Value::Vector { .. } => "vector",
+            Value::Boolean(_) => "boolean",
+            Value::Texture(_) => "texture",
+            Value::Material(_) => "material",
+            Value::Range { .. } => "range",
+            Value::Undef => "undef",
+            Value::FunctionRef { .. } => "function",
+        }
+    }
+
     pub fn to_number(&self) -> Result<f64> {
         match self {
             Value::Number(value) => Ok(*value),
@@ -137,6 +155,7 @@ impl Value {
             Value::Vector { items } => todo!("is_truthy {items:?}"),
             Value::Boolean(b) => *b,
             Value::Texture(texture) => todo!("is_truthy {texture:?}"),
+            Value::Material(_) => true,
             Value::Range {
                 start,
                 end,
@@ -153,11 +172,7 @@ impl Value {
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Value::Number(number) => {
-                let formatted = format!("{number:.6}");
-                let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
-                write!(f, "{formatted}")
-            }
+            Value::Number(number) => write!(f, "{}", format_number(*number)),
             Value::String(str) => write!(f, "{str:?}"),
             Value::Vector { items } => {
                 let mut output = String::new();
@@ -173,6 +188,7 @@ impl Display for Value {
             }
             Value::Boolean(b) => write!(f, "{b}"),
             Value::Texture(texture) => todo!("texture {texture:?}"),
+            Value::Material(_) => write!(f, "material"),
             Value::Range {
                 start,
                 end,
@@ -184,6 +200,26 @@ impl Display for Value {
     }
 }
 
+/// Formats a number the way `echo` prints it, with at most 6 decimals and no
+/// trailing zeros. Rust formatting always uses `.` as the decimal separator, so
+/// the output does not depend on the locale.
+pub fn format_number(number: f64) -> String {
+    if number.is_nan() {
+        return "nan".to_owned();
+    }
+    if number.is_infinite() {
+        return if number > 0.0 { "inf" } else { "-inf" }.to_owned();
+    }
+    let formatted = format!("{number:.6}");
+    let formatted = formatted.trim_end_matches('0').trim_end_matches('.');
+    // Values that round to zero, including -0, print as 0
+    if formatted == "-0" {
+        "0".to_owned()
+    } else {
+        formatted.to_owned()
+    }
+}
+
 pub fn values_to_numbers(items: &[Value]) -> Result<Vec<f64>> {
     items.iter().map(|i| i.to_number()).collect()
 }
@@ -192,6 +228,20 @@ pub fn values_to_numbers(items: &[Value]) -> Result<Vec<f64>> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_format_number() {
+        assert_eq!(format_number(1.0), "1");
+        assert_eq!(format_number(0.5), "0.5");
+        assert_eq!(format_number(-2.25), "-2.25");
+        assert_eq!(format_number(1.0 / 3.0), "0.333333");
+        assert_eq!(format_number(1e6), "1000000");
+        assert_eq!(format_number(-0.0), "0");
+        assert_eq!(format_number(-1e-9), "0");
+        assert_eq!(format_number(f64::NAN), "nan");
+        assert_eq!(format_number(f64::INFINITY), "inf");
+        assert_eq!(format_number(f64::NEG_INFINITY), "-inf");
+    }
+
     #[test]
     fn test_display_string() {
         let v = Value::String("Test\nLine 2".to_owned());
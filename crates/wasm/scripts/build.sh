@@ -4,13 +4,15 @@ set -euo pipefail
 SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 PROJECT_DIR="${SCRIPT_DIR}/.."
 WEBAPP_DIR="${SCRIPT_DIR}/../../../webapp"
+WIDGET_DIR="${SCRIPT_DIR}/../../../target/widget"
+BENCH_DIR="${SCRIPT_DIR}/../../../target/wasm-bench"
 
 # Parse build mode argument
 BUILD_MODE="${1:-all}"
 
-if [[ ! "$BUILD_MODE" =~ ^(debug|release|all)$ ]]; then
-    echo "Error: Invalid argument. Use 'debug', 'release', or omit for both."
-    echo "Usage: $0 [debug|release]"
+if [[ ! "$BUILD_MODE" =~ ^(debug|release|all|widget|bench)$ ]]; then
+    echo "Error: Invalid argument. Use 'debug', 'release', 'widget', 'bench', or omit for debug and release."
+    echo "Usage: $0 [debug|release|widget|bench]"
     exit 1
 fi
 
@@ -52,6 +54,28 @@ echo "Running wasm-pack build…"
         mkdir -p "${WEBAPP_DIR}/frontend/src/wasm/debug"
         cp pkg/caustic_wasm* "${WEBAPP_DIR}/frontend/src/wasm/debug"
     fi
+
+    # Standalone bundle for third-party pages embedding CausticViewer
+    if [[ "$BUILD_MODE" == "widget" ]]; then
+        echo ""
+        echo "building widget..."
+        rm -rf "${WIDGET_DIR}"
+        wasm-pack build --target web --release --no-pack --out-dir "${WIDGET_DIR}"
+    fi
+
+    # Scalar and SIMD128 builds side by side with the page comparing them,
+    # serve the directory over HTTP to open it
+    if [[ "$BUILD_MODE" == "bench" ]]; then
+        echo ""
+        echo "building benchmark..."
+        rm -rf "${BENCH_DIR}"
+        wasm-pack build --target web --release --no-pack --out-dir "${BENCH_DIR}/scalar"
+        RUSTFLAGS="-C target-feature=+simd128" \
+            wasm-pack build --target web --release --no-pack --out-dir "${BENCH_DIR}/simd" \
+            -- --features simd128
+        cp bench/index.html "${BENCH_DIR}"
+        echo "open ${BENCH_DIR}/index.html through a local server, e.g. python3 -m http.server -d ${BENCH_DIR}"
+    fi
 )
 
 echo "Build complete!"
@@ -0,0 +1,77 @@
+use caustic_core::{Color as CoreColor, ProgressiveRenderer, RenderContext, TransferFunction};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::LOADED_SCENE_DATA;
+
+/// Seed of the benchmark renders, so every build renders the same samples
+const BENCHMARK_SEED: u64 = 1;
+
+/// Time taken by [`benchmark`] and the image it rendered.
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    /// Whether this build uses the SIMD128 math kernels
+    pub simd: bool,
+    pub ms: f64,
+    pub pixels_per_second: f64,
+    pub samples_per_second: f64,
+    /// Hash of the rendered pixels, equal for builds rendering identical
+    /// images
+    pub checksum: String,
+}
+
+/// Returns whether this build uses the SIMD128 math kernels, built with the
+/// `simd128` feature and `-C target-feature=+simd128`.
+#[wasm_bindgen]
+pub fn has_simd() -> bool {
+    cfg!(all(feature = "simd128", target_feature = "simd128"))
+}
+
+/// Renders `passes` passes of the loaded scene with a fixed seed and returns
+/// how long it took. Run by the benchmark page in scalar and SIMD builds of
+/// the same scene, whose checksums should match.
+#[wasm_bindgen]
+pub fn benchmark(passes: u32) -> Result<BenchmarkResult, JsValue> {
+    LOADED_SCENE_DATA.with(|data| {
+        let data = data.borrow();
+        let Some(scene_data) = data.as_ref() else {
+            return Err(JsValue::from_str("Scene data not loaded"));
+        };
+        let ctx = RenderContext::new_seeded(BENCHMARK_SEED);
+        let mut renderer = ProgressiveRenderer::new(scene_data);
+        let passes = passes.max(1);
+
+        let start = js_sys::Date::now();
+        for _ in 0..passes {
+            renderer.add_sample_pass(&ctx);
+        }
+        let ms = (js_sys::Date::now() - start).max(1.0);
+
+        let pixels = renderer.pixel_count() as f64 * passes as f64;
+        let samples = pixels * scene_data.camera.samples_per_pixel() as f64;
+        Ok(BenchmarkResult {
+            simd: has_simd(),
+            ms,
+            pixels_per_second: pixels * 1000.0 / ms,
+            samples_per_second: samples * 1000.0 / ms,
+            checksum: format!(
+                "{:016x}",
+                checksum(&renderer.current_image(TransferFunction::Linear))
+            ),
+        })
+    })
+}
+
+/// FNV-1a hash of the little-endian bytes of the colors.
+fn checksum(colors: &[CoreColor]) -> u64 {
+    colors
+        .iter()
+        .flat_map(|color| [color.r, color.g, color.b])
+        .flat_map(|value| value.to_bits().to_le_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
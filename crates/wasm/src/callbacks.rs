@@ -0,0 +1,96 @@
+use std::cell::RefCell;
+
+use js_sys::Function;
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::prelude::*;
+
+use crate::types::message::WasmMessage;
+
+/// Callbacks registered by the JavaScript host, called as a scene loads and
+/// renders so the host does not have to poll for progress.
+#[derive(Default)]
+struct RenderCallbacks {
+    progress: Option<Function>,
+    tile: Option<Function>,
+    log: Option<Function>,
+}
+
+thread_local! {
+static RENDER_CALLBACKS: RefCell<RenderCallbacks> = RefCell::new(RenderCallbacks::default());
+}
+
+/// Pixels rendered since the tile callback was last called.
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct TileRect {
+    pub xmin: u32,
+    pub xmax: u32,
+    pub ymin: u32,
+    pub ymax: u32,
+}
+
+/// Registers the function called with the fraction of the render completed,
+/// or removes it when undefined.
+#[wasm_bindgen]
+pub fn set_progress_callback(
+    #[wasm_bindgen(unchecked_param_type = "((progress: number) => void) | undefined")]
+    callback: Option<Function>,
+) {
+    RENDER_CALLBACKS.with(|callbacks| callbacks.borrow_mut().progress = callback);
+}
+
+/// Registers the function called with the rectangle of every rendered tile,
+/// or removes it when undefined.
+#[wasm_bindgen]
+pub fn set_tile_callback(
+    #[wasm_bindgen(unchecked_param_type = "((tile: TileRect) => void) | undefined")]
+    callback: Option<Function>,
+) {
+    RENDER_CALLBACKS.with(|callbacks| callbacks.borrow_mut().tile = callback);
+}
+
+/// Registers the function called with every echo, warning and error of a
+/// loaded scene, or removes it when undefined.
+#[wasm_bindgen]
+pub fn set_log_callback(
+    #[wasm_bindgen(unchecked_param_type = "((message: WasmMessage) => void) | undefined")]
+    callback: Option<Function>,
+) {
+    RENDER_CALLBACKS.with(|callbacks| callbacks.borrow_mut().log = callback);
+}
+
+/// Calls a callback, reporting exceptions to the console rather than to the
+/// render that triggered it. The callback is cloned out of the registry first
+/// so it may register other callbacks.
+fn call(select: impl Fn(&RenderCallbacks) -> Option<Function>, arg: Result<JsValue, JsValue>) {
+    let Some(callback) = RENDER_CALLBACKS.with(|callbacks| select(&callbacks.borrow())) else {
+        return;
+    };
+    let result = arg.and_then(|arg| callback.call1(&JsValue::NULL, &arg));
+    if let Err(err) = result {
+        web_sys::console::error_2(&JsValue::from_str("render callback failed"), &err);
+    }
+}
+
+pub(crate) fn emit_progress(progress: f64) {
+    call(
+        |callbacks| callbacks.progress.clone(),
+        Ok(JsValue::from_f64(progress)),
+    );
+}
+
+pub(crate) fn emit_tile(tile: TileRect) {
+    call(
+        |callbacks| callbacks.tile.clone(),
+        serde_wasm_bindgen::to_value(&tile).map_err(JsValue::from),
+    );
+}
+
+pub(crate) fn emit_log(message: &WasmMessage) {
+    call(
+        |callbacks| callbacks.log.clone(),
+        serde_wasm_bindgen::to_value(message).map_err(JsValue::from),
+    );
+}
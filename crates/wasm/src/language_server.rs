@@ -15,7 +15,8 @@ pub struct WasmLspServer {
 impl WasmLspServer {
     #[wasm_bindgen(constructor)]
     pub fn new(output_callback: js_sys::Function) -> Self {
-        let (service, mut messages) = LspService::new(|_| LanguageServerBackend::new());
+        let (service, mut messages) =
+            LspService::new(|client| LanguageServerBackend::new().with_client(client));
 
         // Handle Outgoing (Rust -> JS)
         wasm_bindgen_futures::spawn_local(async move {
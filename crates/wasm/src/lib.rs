@@ -1,25 +1,45 @@
 #![allow(clippy::vec_init_then_push)]
 
+pub mod benchmark;
+pub mod callbacks;
 pub mod language_server;
 pub mod types;
+pub mod viewer;
 
 use std::{any::Any, cell::RefCell, fmt::Debug, sync::Arc};
 
 use caustic_core::{
-    Color as CoreColor, Image, RenderContext, SceneData, image::ImageError, random_new,
+    CameraBuilder, Color as CoreColor, CropWindow as CoreCropWindow, GuideKind as CoreGuideKind,
+    GuideLine as CoreGuideLine, Guides, Image, ProgressiveRenderer, RenderContext, SceneData,
+    TransferFunction,
+    image::{ImageError, ImageImage},
+    material::{Material, MaterialParameters as CoreMaterialParameters},
+    random_new,
+    render::{
+        PhotonMap, Tile, TileOrder as CoreTileOrder, TileScheduler, estimate_tile_costs,
+        find_tiles_showing,
+    },
 };
-use caustic_openscad::{run_openscad, source::Source};
-use js_sys::Uint8ClampedArray;
+use caustic_openscad::{
+    interpreter::SceneMaterial, run_openscad, run_openscad_with_editable_materials, source::Source,
+};
+use js_sys::{Uint8Array, Uint8ClampedArray};
 use serde::{Deserialize, Serialize};
 use tsify::Tsify;
 use wasm_bindgen::prelude::*;
 
-use crate::types::message::WasmMessage;
+use crate::{
+    callbacks::{TileRect, emit_log, emit_progress, emit_tile},
+    types::{message::WasmMessage, position::WasmPosition},
+};
 
 pub use language_server::WasmLspServer;
+pub use viewer::CausticViewer;
 
 thread_local! {
 static LOADED_SCENE_DATA: RefCell<Option<SceneData>> = const { RefCell::new(None) };
+static LOADED_MATERIALS: RefCell<Vec<SceneMaterial>> = const { RefCell::new(Vec::new()) };
+static COOPERATIVE_RENDER: RefCell<Option<CooperativeRender>> = const { RefCell::new(None) };
 }
 
 #[wasm_bindgen(typescript_custom_section)]
@@ -28,6 +48,11 @@ export interface WasmSource {
     get_filename(): string;
     get_code(): string;
     get_image(filename: string): WasmImage;
+    /** Returns the encoded contents of an image file, decoded by the raytracer. */
+    get_image_bytes(filename: string): Uint8Array | undefined;
+    /** Returns a mesh file as converted by the server, for `import()`. */
+    get_mesh_bytes(filename: string): Uint8Array | undefined;
+    has_file(filename: string): boolean;
 }
 "#;
 
@@ -43,6 +68,15 @@ extern "C" {
 
     #[wasm_bindgen(method, catch)]
     pub fn get_image(this: &WasmSource, filename: &str) -> Result<WasmImage, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn get_image_bytes(this: &WasmSource, filename: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn get_mesh_bytes(this: &WasmSource, filename: &str) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn has_file(this: &WasmSource, filename: &str) -> Result<bool, JsValue>;
 }
 
 // Add this wrapper struct
@@ -88,6 +122,17 @@ impl Source for WasmSourceAdapter {
     }
 
     fn get_image(&self, filename: &str) -> Result<Arc<dyn Image>, ImageError> {
+        // Prefer decoding the file ourselves, which supports formats the browser
+        // cannot draw such as HDR and keeps the full range of their pixels
+        let bytes = self.wasm_source.get_image_bytes(filename).map_err(|err| {
+            ImageError::Other(format!(
+                "getting image bytes from JavaScript failed: {err:?}"
+            ))
+        })?;
+        if !bytes.is_undefined() && !bytes.is_null() {
+            return ImageImage::load_from_memory(&Uint8Array::new(&bytes).to_vec());
+        }
+
         let image = self.wasm_source.get_image(filename).map_err(|err| {
             ImageError::Other(format!("getting image from JavaScript failed: {err:?}"))
         })?;
@@ -97,6 +142,21 @@ impl Source for WasmSourceAdapter {
         Ok(Arc::new(image_adapter))
     }
 
+    fn get_converted_mesh(&self, filename: &str) -> Result<Vec<u8>, String> {
+        let bytes = self
+            .wasm_source
+            .get_mesh_bytes(filename)
+            .map_err(|err| format!("getting mesh bytes from JavaScript failed: {err:?}"))?;
+        if bytes.is_undefined() || bytes.is_null() {
+            return Err(format!("\"{filename}\" is not a mesh"));
+        }
+        Ok(Uint8Array::new(&bytes).to_vec())
+    }
+
+    fn has_file(&self, filename: &str) -> bool {
+        self.wasm_source.has_file(filename).unwrap_or(false)
+    }
+
     fn get_filename(&self) -> &str {
         &self.filename
     }
@@ -180,16 +240,32 @@ impl Debug for WasmImageAdapter {
     }
 }
 
+/// Interprets the scene and keeps it loaded for rendering. With
+/// `editable_materials` its materials can be changed afterwards with
+/// [`set_material_parameters`], at the cost of a lock taken by every ray
+/// hitting them, so scenes are only loaded that way for material editing.
 #[wasm_bindgen]
-pub fn load_openscad(wasm_source: WasmSource) -> Result<LoadResults, JsValue> {
+pub fn load_openscad(
+    wasm_source: WasmSource,
+    editable_materials: bool,
+) -> Result<LoadResults, JsValue> {
     let source: Arc<Box<dyn Source>> = Arc::new(Box::new(WasmSourceAdapter::new(wasm_source)?));
     let random = random_new();
-    let results = run_openscad(source, random);
-    let messages = results.messages.iter().map(|m| m.into()).collect();
+    let results = if editable_materials {
+        run_openscad_with_editable_materials(source, random.clone())
+    } else {
+        run_openscad(source, random.clone())
+    };
+    let messages: Vec<WasmMessage> = results.messages.iter().map(|m| m.into()).collect();
+    for message in &messages {
+        emit_log(message);
+    }
 
     let loaded = match results.scene_data {
-        Some(scene_data) => {
+        Some(mut scene_data) => {
+            scene_data.trace_caustics(&RenderContext { random }, PhotonMap::MAX_PHOTONS);
             LOADED_SCENE_DATA.with(|data| *data.borrow_mut() = Some(scene_data));
+            LOADED_MATERIALS.with(|materials| *materials.borrow_mut() = results.materials);
             true
         }
         None => false,
@@ -204,16 +280,321 @@ pub fn get_camera_info() -> Result<CameraInfo, JsValue> {
         if let Some(scene_data) = data.borrow().as_ref() {
             let width = scene_data.camera.image_width();
             let height = scene_data.camera.image_height();
-            Ok(CameraInfo { width, height })
+            let samples_per_pixel = scene_data.camera.samples_per_pixel();
+            Ok(CameraInfo {
+                width,
+                height,
+                samples_per_pixel,
+            })
         } else {
             Err(JsValue::from_str("Scene data not loaded"))
         }
     })
 }
 
+/// Returns the names of the loaded scene's cameras in alphabetical order.
 #[wasm_bindgen]
-pub fn render(xmin: u32, xmax: u32, ymin: u32, ymax: u32) -> Result<Vec<Color>, JsValue> {
+pub fn get_camera_names() -> Result<Vec<String>, JsValue> {
+    LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow().as_ref() {
+            Ok(scene_data
+                .camera_names()
+                .into_iter()
+                .map(str::to_owned)
+                .collect())
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
+    })
+}
+
+/// Renders the loaded scene's camera with the given name instead of its
+/// default camera. Overscan and crop windows apply to the selected camera,
+/// so they are set afterwards.
+#[wasm_bindgen]
+pub fn set_camera(name: &str) -> Result<(), JsValue> {
+    LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow_mut().as_mut() {
+            if scene_data.select_camera(name) {
+                Ok(())
+            } else {
+                Err(JsValue::from_str(&format!("no camera named \"{name}\"")))
+            }
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
+    })
+}
+
+/// Orbits the loaded scene's camera around the point it looks at, `yaw`
+/// degrees around its up direction and `pitch` degrees towards it, without
+/// interpreting the scene again. Overscan and crop windows are dropped, so
+/// they are set afterwards.
+#[wasm_bindgen]
+pub fn orbit_camera(yaw: f64, pitch: f64) -> Result<(), JsValue> {
+    update_camera(|camera_builder| camera_builder.orbit(yaw, pitch))
+}
+
+/// Moves the loaded scene's camera towards the point it looks at, multiplying
+/// their distance by `factor`. Overscan and crop windows are dropped.
+#[wasm_bindgen]
+pub fn dolly_camera(factor: f64) -> Result<(), JsValue> {
+    update_camera(|camera_builder| camera_builder.dolly(factor))
+}
+
+/// Moves the loaded scene's camera across the view by `right` and `up` times
+/// the height of the view. Overscan and crop windows are dropped.
+#[wasm_bindgen]
+pub fn pan_camera(right: f64, up: f64) -> Result<(), JsValue> {
+    update_camera(|camera_builder| camera_builder.pan(right, up))
+}
+
+/// Rebuilds the loaded scene's camera from its settings after `update`,
+/// keeping the caustics traced when the scene was loaded.
+fn update_camera(update: impl FnOnce(&mut CameraBuilder)) -> Result<(), JsValue> {
+    LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow_mut().as_mut() {
+            let mut camera_builder = scene_data.camera.builder().clone();
+            update(&mut camera_builder);
+            let caustics = scene_data.camera.caustics().cloned();
+            scene_data.camera = Arc::new(camera_builder.build().with_caustics(caustics));
+            Ok(())
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
+    })
+}
+
+/// Renders the loaded scene with the settings of a project's render preset,
+/// each unset setting keeping the scene's own. Overscan and crop windows are
+/// dropped, so they are set afterwards.
+#[wasm_bindgen]
+pub fn set_render_settings(settings: RenderSettings) -> Result<(), JsValue> {
+    update_camera(|camera_builder| settings.apply(camera_builder))
+}
+
+/// Renders a margin of `overscan` times the image size around the shot, or
+/// removes it when 0. The camera info changes to the size of the overscanned
+/// image.
+#[wasm_bindgen]
+pub fn set_overscan(overscan: f64) -> Result<(), JsValue> {
+    LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow_mut().as_mut() {
+            scene_data.camera = Arc::new(scene_data.camera.with_overscan(overscan));
+            Ok(())
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
+    })
+}
+
+/// Renders only the part of the shot in the window, given as fractions of the
+/// image size, at the scene's full sample count.
+#[wasm_bindgen]
+pub fn set_crop_window(crop_window: CropWindow) -> Result<(), JsValue> {
+    LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow_mut().as_mut() {
+            scene_data.camera = Arc::new(
+                scene_data
+                    .camera
+                    .with_crop_window(Some(CoreCropWindow::from(crop_window))),
+            );
+            Ok(())
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
+    })
+}
+
+/// Returns the materials created by the loaded scene, in the order they were
+/// created, with the parameters which can be edited. Empty unless the scene
+/// was loaded with editable materials.
+///
+/// A material's id is its index in this list, which only holds until the
+/// scene is loaded again.
+#[wasm_bindgen]
+pub fn get_materials() -> Result<Vec<MaterialInfo>, JsValue> {
+    LOADED_MATERIALS.with(|materials| {
+        Ok(materials
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(id, scene_material)| {
+                let parameters = scene_material.material.parameters();
+                MaterialInfo {
+                    id: id as u32,
+                    name: scene_material.name.clone(),
+                    position: (&scene_material.position).into(),
+                    parameters: MaterialParameters {
+                        color: parameters.color.map(LinearColor::from),
+                        fuzz: parameters.fuzz,
+                        ior: parameters.ior,
+                    },
+                }
+            })
+            .collect())
+    })
+}
+
+/// Changes the given parameters of the loaded scene's material `id`, from
+/// [`get_materials`], without interpreting the scene again. Pixels rendered
+/// before keep the old material, [`get_material_tiles`] finds the tiles to
+/// render again.
+#[wasm_bindgen]
+pub fn set_material_parameters(id: u32, parameters: MaterialParameters) -> Result<(), JsValue> {
+    LOADED_MATERIALS.with(|materials| {
+        let materials = materials.borrow();
+        let Some(scene_material) = materials.get(id as usize) else {
+            if materials.is_empty() {
+                return Err(JsValue::from_str(
+                    "scene was not loaded with editable materials",
+                ));
+            }
+            return Err(JsValue::from_str(&format!("no material with id {id}")));
+        };
+        let parameters = CoreMaterialParameters {
+            color: parameters
+                .color
+                .map(|color| CoreColor::new(color.r as f64, color.g as f64, color.b as f64)),
+            fuzz: parameters.fuzz,
+            ior: parameters.ior,
+        };
+        if scene_material.material.set_parameters(&parameters) {
+            Ok(())
+        } else {
+            Err(JsValue::from_str(&format!(
+                "material {id} ({}) cannot be edited",
+                scene_material.name
+            )))
+        }
+    })
+}
+
+/// Returns the indexes of the tiles showing the loaded scene's material `id`,
+/// found with one ray per `block_size` by `block_size` pixels, the tiles to
+/// render again after changing it. Reflections of the material and shadows
+/// cast by objects made of it are not found.
+#[wasm_bindgen]
+pub fn get_material_tiles(
+    id: u32,
+    tiles: Vec<TileRect>,
+    block_size: u32,
+) -> Result<Vec<u32>, JsValue> {
+    let material = LOADED_MATERIALS.with(|materials| {
+        materials
+            .borrow()
+            .get(id as usize)
+            .map(|scene_material| scene_material.material.clone())
+            .ok_or_else(|| JsValue::from_str(&format!("no material with id {id}")))
+    })?;
+    LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow().as_ref() {
+            let ctx = RenderContext {
+                random: random_new(),
+            };
+            let tiles: Vec<Tile> = tiles
+                .into_iter()
+                .map(|tile| Tile {
+                    xmin: tile.xmin,
+                    xmax: tile.xmax,
+                    ymin: tile.ymin,
+                    ymax: tile.ymax,
+                })
+                .collect();
+            let material = Arc::as_ptr(&material) as *const ();
+            let showing = find_tiles_showing(
+                &ctx,
+                &scene_data.camera,
+                &*scene_data.world,
+                &tiles,
+                block_size,
+                |hit| Arc::as_ptr(&hit.material) as *const () == material,
+            );
+            Ok(showing
+                .into_iter()
+                .enumerate()
+                .filter_map(|(index, shows)| shows.then_some(index as u32))
+                .collect())
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
+    })
+}
+
+/// Returns the composition guides to draw over the rendered image, kept out of
+/// the rendered pixels.
+#[wasm_bindgen]
+pub fn get_guides(rule_of_thirds: bool, safe_areas: bool) -> Result<Vec<GuideLine>, JsValue> {
     LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow().as_ref() {
+            let guides = Guides {
+                rule_of_thirds,
+                safe_areas,
+            };
+            Ok(guides
+                .lines(&scene_data.camera)
+                .into_iter()
+                .map(GuideLine::from)
+                .collect())
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
+    })
+}
+
+/// Returns the blocks of a `width` by `height` image in the order they should
+/// be rendered.
+#[wasm_bindgen]
+pub fn get_tiles(width: u32, height: u32, tile_size: u32, order: TileOrder) -> Vec<TileRect> {
+    TileScheduler::new(width, height)
+        .with_tile_size(tile_size)
+        .with_order(order.into())
+        .tiles()
+        .into_iter()
+        .map(|tile| TileRect {
+            xmin: tile.xmin,
+            xmax: tile.xmax,
+            ymin: tile.ymin,
+            ymax: tile.ymax,
+        })
+        .collect()
+}
+
+/// Estimates the relative cost of rendering each block of the loaded scene
+/// from one ray per `block_size` by `block_size` pixels, to render the
+/// hardest blocks first and weigh progress by the work left.
+#[wasm_bindgen]
+pub fn get_tile_costs(tiles: Vec<TileRect>, block_size: u32) -> Result<Vec<f64>, JsValue> {
+    LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow().as_ref() {
+            let ctx = RenderContext {
+                random: random_new(),
+            };
+            let tiles: Vec<Tile> = tiles
+                .into_iter()
+                .map(|tile| Tile {
+                    xmin: tile.xmin,
+                    xmax: tile.xmax,
+                    ymin: tile.ymin,
+                    ymax: tile.ymax,
+                })
+                .collect();
+            Ok(estimate_tile_costs(
+                &ctx,
+                &scene_data.camera,
+                &*scene_data.world,
+                &tiles,
+                block_size,
+            ))
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
+    })
+}
+
+#[wasm_bindgen]
+pub fn render(xmin: u32, xmax: u32, ymin: u32, ymax: u32) -> Result<Vec<Color>, JsValue> {
+    let results = LOADED_SCENE_DATA.with(|data| {
         if let Some(scene_data) = data.borrow().as_ref() {
             let ctx = Arc::new(RenderContext {
                 random: random_new(),
@@ -238,9 +619,159 @@ pub fn render(xmin: u32, xmax: u32, ymin: u32, ymax: u32) -> Result<Vec<Color>,
         } else {
             Err(JsValue::from_str("Scene data not loaded"))
         }
+    })?;
+    emit_tile(TileRect {
+        xmin,
+        xmax,
+        ymin,
+        ymax,
+    });
+    Ok(results)
+}
+
+/// Renders a block of pixels with one pass of samples and returns linear colors,
+/// for the caller to accumulate over several passes.
+#[wasm_bindgen]
+pub fn render_linear(
+    xmin: u32,
+    xmax: u32,
+    ymin: u32,
+    ymax: u32,
+) -> Result<Vec<LinearColor>, JsValue> {
+    let results = LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow().as_ref() {
+            let ctx = Arc::new(RenderContext {
+                random: random_new(),
+            });
+            let mut results: Vec<LinearColor> = vec![];
+
+            for y in ymin..ymax {
+                for x in xmin..xmax {
+                    let pixel_color = scene_data.camera.render_linear(
+                        &ctx,
+                        x,
+                        y,
+                        &*scene_data.world,
+                        scene_data.lights.clone(),
+                    );
+                    results.push(LinearColor::from(pixel_color));
+                }
+            }
+
+            Ok(results)
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
+    })?;
+    emit_tile(TileRect {
+        xmin,
+        xmax,
+        ymin,
+        ymax,
+    });
+    Ok(results)
+}
+
+/// A render of the loaded scene spread over calls to [`render_for`], for pages
+/// which cannot render in workers. Pixels are rendered row by row, one pass of
+/// samples at a time, and accumulated until every pass is done.
+struct CooperativeRender {
+    ctx: RenderContext,
+    renderer: ProgressiveRenderer,
+    passes: u32,
+}
+
+/// Starts a render of the loaded scene taking `passes` passes of the camera's
+/// samples per pixel, driven by calls to [`render_for`].
+#[wasm_bindgen]
+pub fn start_cooperative_render(passes: u32) -> Result<(), JsValue> {
+    LOADED_SCENE_DATA.with(|data| {
+        if let Some(scene_data) = data.borrow().as_ref() {
+            let render = CooperativeRender {
+                ctx: RenderContext {
+                    random: random_new(),
+                },
+                renderer: ProgressiveRenderer::new(scene_data),
+                passes: passes.max(1),
+            };
+            COOPERATIVE_RENDER.with(|cooperative| *cooperative.borrow_mut() = Some(render));
+            Ok(())
+        } else {
+            Err(JsValue::from_str("Scene data not loaded"))
+        }
     })
 }
 
+/// Renders pixels of the render started by [`start_cooperative_render`] until
+/// `ms` milliseconds have passed, so the page stays responsive between calls,
+/// and returns the rows that changed. A call renders at least one pixel and
+/// stops at the end of a pass.
+#[wasm_bindgen]
+pub fn render_for(ms: f64) -> Result<CooperativeRenderResult, JsValue> {
+    let start = js_sys::Date::now();
+    let result = COOPERATIVE_RENDER.with(|cooperative| {
+        let mut cooperative = cooperative.borrow_mut();
+        let Some(render) = cooperative.as_mut() else {
+            return Err(JsValue::from_str("Cooperative render not started"));
+        };
+        let CooperativeRender {
+            ctx,
+            renderer,
+            passes,
+        } = render;
+
+        let pass = renderer.passes();
+        let first_pixel = renderer.next_pixel();
+        let mut last_pixel = first_pixel;
+        if pass < *passes {
+            while renderer.passes() == pass {
+                renderer.render_next_pixel(ctx);
+                last_pixel += 1;
+                if js_sys::Date::now() - start >= ms {
+                    break;
+                }
+            }
+        }
+
+        let width = renderer.width();
+        let (ymin, ymax) = if last_pixel > first_pixel {
+            (first_pixel / width, last_pixel.div_ceil(width))
+        } else {
+            (0, 0)
+        };
+        let data = renderer
+            .rows(ymin, ymax, TransferFunction::Srgb)
+            .into_iter()
+            .map(Color::from)
+            .collect();
+
+        let pixel_count = renderer.pixel_count() as f64;
+        let rendered = renderer.passes() as f64 * pixel_count + renderer.next_pixel() as f64;
+        Ok(CooperativeRenderResult {
+            xmin: 0,
+            xmax: width,
+            ymin,
+            ymax,
+            data,
+            pass,
+            progress: rendered / (*passes as f64 * pixel_count).max(1.0),
+            done: renderer.passes() >= *passes,
+        })
+    })?;
+    // Called once the scene is no longer borrowed, so callbacks may start
+    // another render
+    if result.ymax > result.ymin {
+        emit_tile(TileRect {
+            xmin: result.xmin,
+            xmax: result.xmax,
+            ymin: result.ymin,
+            ymax: result.ymax,
+        });
+    }
+    emit_progress(result.progress);
+    Ok(result)
+}
+
 #[derive(Tsify, Serialize, Deserialize)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 #[serde(rename_all = "camelCase")]
@@ -249,12 +780,122 @@ pub struct LoadResults {
     pub loaded: bool,
 }
 
+/// Rows rendered by a call to [`render_for`], with their averaged colors.
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct CooperativeRenderResult {
+    pub xmin: u32,
+    pub xmax: u32,
+    pub ymin: u32,
+    pub ymax: u32,
+    pub data: Vec<Color>,
+    /// Pass the rows were rendered in, starting at 0
+    pub pass: u32,
+    /// Fraction of every pass rendered so far
+    pub progress: f64,
+    pub done: bool,
+}
+
 #[derive(Tsify, Serialize, Deserialize)]
 #[tsify(into_wasm_abi, from_wasm_abi)]
 #[serde(rename_all = "camelCase")]
 pub struct CameraInfo {
     pub width: u32,
     pub height: u32,
+    pub samples_per_pixel: u32,
+}
+
+/// A material of the loaded scene, see [`get_materials`].
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterialInfo {
+    /// Id given to [`set_material_parameters`]
+    pub id: u32,
+    /// Module or function which created the material, such as "metal"
+    pub name: String,
+    pub position: WasmPosition,
+    /// Parameters the material has, the others are missing
+    pub parameters: MaterialParameters,
+}
+
+/// Editable parameters of a material, each optional.
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct MaterialParameters {
+    /// Color of untextured materials
+    #[tsify(optional)]
+    pub color: Option<LinearColor>,
+    /// Fuzz of metals or roughness of PBR materials
+    #[tsify(optional)]
+    pub fuzz: Option<f64>,
+    /// Index of refraction
+    #[tsify(optional)]
+    pub ior: Option<f64>,
+}
+
+/// Camera settings of a project's render preset, unset values keep the
+/// scene's settings.
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderSettings {
+    #[tsify(optional)]
+    pub image_width: Option<u32>,
+    #[tsify(optional)]
+    pub image_height: Option<u32>,
+    #[tsify(optional)]
+    pub samples_per_pixel: Option<u32>,
+    #[tsify(optional)]
+    pub max_depth: Option<u32>,
+}
+
+impl RenderSettings {
+    fn apply(&self, camera_builder: &mut CameraBuilder) {
+        match (self.image_width, self.image_height) {
+            (Some(width), Some(height)) => {
+                camera_builder.image_width = width.max(1);
+                camera_builder.aspect_ratio = width.max(1) as f64 / height.max(1) as f64;
+            }
+            (Some(width), None) => camera_builder.image_width = width.max(1),
+            // The height follows from the width and the scene's aspect ratio
+            (None, Some(height)) => {
+                camera_builder.image_width =
+                    ((height as f64 * camera_builder.aspect_ratio).round() as u32).max(1)
+            }
+            (None, None) => {}
+        }
+        if let Some(samples_per_pixel) = self.samples_per_pixel {
+            camera_builder.samples_per_pixel = samples_per_pixel.max(1);
+        }
+        if let Some(max_depth) = self.max_depth {
+            camera_builder.max_depth = max_depth;
+        }
+    }
+}
+
+/// Part of the image as fractions of its width and height from the top left.
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct CropWindow {
+    pub xmin: f64,
+    pub xmax: f64,
+    pub ymin: f64,
+    pub ymax: f64,
+}
+
+impl From<CropWindow> for CoreCropWindow {
+    fn from(crop_window: CropWindow) -> Self {
+        CoreCropWindow::new(
+            crop_window.xmin,
+            crop_window.xmax,
+            crop_window.ymin,
+            crop_window.ymax,
+        )
+    }
 }
 
 #[derive(Tsify, Serialize, Deserialize)]
@@ -276,6 +917,85 @@ impl Color {
     }
 }
 
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct LinearColor {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+}
+
+impl LinearColor {
+    pub fn from(color: CoreColor) -> Self {
+        LinearColor {
+            r: color.r as f32,
+            g: color.g as f32,
+            b: color.b as f32,
+        }
+    }
+}
+
+/// Order blocks are rendered in.
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub enum TileOrder {
+    Scanline,
+    Spiral,
+    Hilbert,
+}
+
+impl From<TileOrder> for CoreTileOrder {
+    fn from(order: TileOrder) -> Self {
+        match order {
+            TileOrder::Scanline => CoreTileOrder::Scanline,
+            TileOrder::Spiral => CoreTileOrder::Spiral,
+            TileOrder::Hilbert => CoreTileOrder::Hilbert,
+        }
+    }
+}
+
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub enum GuideKind {
+    Frame,
+    Thirds,
+    ActionSafe,
+    TitleSafe,
+}
+
+#[derive(Tsify, Serialize, Deserialize)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct GuideLine {
+    pub x0: u32,
+    pub y0: u32,
+    pub x1: u32,
+    pub y1: u32,
+    pub kind: GuideKind,
+    pub color: Color,
+}
+
+impl GuideLine {
+    pub fn from(line: CoreGuideLine) -> Self {
+        GuideLine {
+            x0: line.x0,
+            y0: line.y0,
+            x1: line.x1,
+            y1: line.y1,
+            kind: match line.kind {
+                CoreGuideKind::Frame => GuideKind::Frame,
+                CoreGuideKind::Thirds => GuideKind::Thirds,
+                CoreGuideKind::ActionSafe => GuideKind::ActionSafe,
+                CoreGuideKind::TitleSafe => GuideKind::TitleSafe,
+            },
+            color: Color::from(line.kind.color()),
+        }
+    }
+}
+
 // Initialize WASM module
 #[wasm_bindgen(start)]
 pub fn main() {
@@ -0,0 +1,360 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+    sync::Arc,
+};
+
+use caustic_core::{
+    Camera, ProgressiveRenderer, RenderContext, SceneData, TransferFunction, random_new,
+};
+use caustic_openscad::{
+    MessageLevel, run_openscad,
+    source::{Source, StringSource},
+};
+use serde::{Deserialize, Serialize};
+use tsify::Tsify;
+use wasm_bindgen::{Clamped, prelude::*};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData, ResizeObserver};
+
+/// Passes of the camera's samples per pixel rendered when not given
+const DEFAULT_PASSES: u32 = 16;
+
+/// Milliseconds rendered per animation frame when not given
+const DEFAULT_FRAME_BUDGET_MS: f64 = 12.0;
+
+/// Options of [`CausticViewer::attach`], every one optional.
+#[derive(Tsify, Serialize, Deserialize, Default)]
+#[tsify(into_wasm_abi, from_wasm_abi)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewerOptions {
+    /// Passes of the camera's samples per pixel to render, 16 by default
+    #[tsify(optional)]
+    pub passes: Option<u32>,
+    /// Milliseconds rendered per animation frame, 12 by default so the page
+    /// stays responsive
+    #[tsify(optional)]
+    pub frame_budget_ms: Option<f64>,
+    /// Name of the scene's camera to render instead of its default camera
+    #[tsify(optional)]
+    pub camera: Option<String>,
+    /// Fill the width of the canvas's parent, rendering at that size in device
+    /// pixels up to the scene's resolution, and restart when it changes. True
+    /// by default.
+    #[tsify(optional)]
+    pub auto_resize: Option<bool>,
+}
+
+/// An OpenSCAD scene rendered progressively into a canvas, so pages can embed
+/// an interactive render with a few lines of JavaScript:
+///
+/// ```js
+/// import init, { CausticViewer } from "caustic_wasm.js";
+///
+/// await init();
+/// const viewer = CausticViewer.attach(canvas, source, { passes: 32 });
+/// ```
+///
+/// Rendering runs in animation frames on the page's thread, refining the
+/// whole image one pass at a time, and stops when the viewer is detached.
+#[wasm_bindgen]
+pub struct CausticViewer {
+    state: Rc<RefCell<ViewerState>>,
+    resize_observer: Option<ResizeObserver>,
+    /// Kept alive while the resize observer may call it
+    _on_resize: Option<Closure<dyn FnMut()>>,
+}
+
+struct ViewerState {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    passes: u32,
+    frame_budget_ms: f64,
+    camera: Option<String>,
+    auto_resize: bool,
+    /// Loaded scene, its camera sized to the canvas
+    scene: SceneData,
+    /// Camera of the scene at the resolution it was written for
+    full_camera: Arc<Camera>,
+    ctx: RenderContext,
+    renderer: ProgressiveRenderer,
+    /// Called on every animation frame while rendering
+    frame: Option<Closure<dyn FnMut()>>,
+    frame_handle: Option<i32>,
+}
+
+#[wasm_bindgen]
+impl CausticViewer {
+    /// Loads `scad_source` and starts rendering it into `canvas`. Fails with
+    /// the scene's errors when it cannot be loaded.
+    pub fn attach(
+        canvas: HtmlCanvasElement,
+        scad_source: &str,
+        options: Option<ViewerOptions>,
+    ) -> Result<CausticViewer, JsValue> {
+        let options = options.unwrap_or_default();
+        let context = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("canvas has no 2d context"))?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+        let scene = load_scene(scad_source, options.camera.as_deref())?;
+
+        let state = Rc::new(RefCell::new(ViewerState {
+            renderer: ProgressiveRenderer::new(&scene),
+            full_camera: scene.camera.clone(),
+            scene,
+            canvas,
+            context,
+            passes: options.passes.unwrap_or(DEFAULT_PASSES).max(1),
+            frame_budget_ms: options.frame_budget_ms.unwrap_or(DEFAULT_FRAME_BUDGET_MS),
+            camera: options.camera,
+            auto_resize: options.auto_resize.unwrap_or(true),
+            ctx: RenderContext {
+                random: random_new(),
+            },
+            frame: None,
+            frame_handle: None,
+        }));
+
+        let weak = Rc::downgrade(&state);
+        state.borrow_mut().frame = Some(Closure::new(move || render_frame(&weak)));
+
+        let auto_resize = state.borrow().auto_resize;
+        let (resize_observer, on_resize) = if auto_resize {
+            let weak = Rc::downgrade(&state);
+            let on_resize = Closure::<dyn FnMut()>::new(move || {
+                if let Some(state) = weak.upgrade() {
+                    fit_canvas(&state, false);
+                }
+            });
+            let resize_observer = ResizeObserver::new(on_resize.as_ref().unchecked_ref())?;
+            // The canvas follows its parent, so its own size changes with every
+            // render size and would feed back into it
+            match state.borrow().canvas.parent_element() {
+                Some(parent) => resize_observer.observe(&parent),
+                None => resize_observer.observe(&state.borrow().canvas),
+            }
+            (Some(resize_observer), Some(on_resize))
+        } else {
+            (None, None)
+        };
+
+        fit_canvas(&state, true);
+        Ok(CausticViewer {
+            state,
+            resize_observer,
+            _on_resize: on_resize,
+        })
+    }
+
+    /// Replaces the rendered scene with `scad_source`, keeping the current one
+    /// when it cannot be loaded.
+    pub fn set_source(&self, scad_source: &str) -> Result<(), JsValue> {
+        let camera = self.state.borrow().camera.clone();
+        let scene = load_scene(scad_source, camera.as_deref())?;
+        {
+            let mut state = self.state.borrow_mut();
+            state.full_camera = scene.camera.clone();
+            state.scene = scene;
+        }
+        fit_canvas(&self.state, true);
+        Ok(())
+    }
+
+    /// Sizes the render to the canvas again, for viewers attached without
+    /// `autoResize`.
+    pub fn resize(&self) {
+        fit_canvas(&self.state, false);
+    }
+
+    /// Returns the fraction of every pass rendered so far.
+    pub fn progress(&self) -> f64 {
+        let state = self.state.borrow();
+        let pixel_count = state.renderer.pixel_count() as f64;
+        let rendered =
+            state.renderer.passes() as f64 * pixel_count + state.renderer.next_pixel() as f64;
+        (rendered / (state.passes as f64 * pixel_count).max(1.0)).min(1.0)
+    }
+
+    /// Stops rendering and watching the canvas, leaving the last image drawn.
+    pub fn detach(&self) {
+        if let Some(resize_observer) = &self.resize_observer {
+            resize_observer.disconnect();
+        }
+        let mut state = self.state.borrow_mut();
+        if let (Some(handle), Some(window)) = (state.frame_handle.take(), web_sys::window()) {
+            let _ = window.cancel_animation_frame(handle);
+        }
+        state.frame = None;
+    }
+}
+
+impl Drop for CausticViewer {
+    fn drop(&mut self) {
+        self.detach();
+    }
+}
+
+fn load_scene(scad_source: &str, camera: Option<&str>) -> Result<SceneData, JsValue> {
+    let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(scad_source)));
+    let results = run_openscad(source, random_new());
+    let Some(mut scene) = results.scene_data else {
+        let errors: Vec<String> = results
+            .messages
+            .iter()
+            .filter(|m| m.level == MessageLevel::Error)
+            .map(|m| format!("{}: {}", m.position, m.message))
+            .collect();
+        return Err(JsValue::from_str(&errors.join("\n")));
+    };
+    if let Some(name) = camera
+        && !scene.select_camera(name)
+    {
+        return Err(JsValue::from_str(&format!("no camera named \"{name}\"")));
+    }
+    Ok(scene)
+}
+
+/// Sizes the render to the width of the canvas's parent, in device pixels and
+/// at most the scene's resolution, and renders it again from the first pass
+/// when its size changed or `restart` is set.
+///
+/// The width comes from the parent's CSS box rather than the canvas's, as a
+/// canvas without a CSS size is shown at its pixel width, which would grow by
+/// the device pixel ratio on every resize.
+fn fit_canvas(state: &Rc<RefCell<ViewerState>>, restart: bool) {
+    let mut state = state.borrow_mut();
+    let full_width = state.full_camera.image_width();
+    let width = if state.auto_resize {
+        let shown_width = state
+            .canvas
+            .parent_element()
+            .map_or_else(
+                || state.canvas.client_width(),
+                |parent| parent.client_width(),
+            )
+            .max(0);
+        if shown_width > 0 {
+            let _ = state
+                .canvas
+                .style()
+                .set_property("width", &format!("{shown_width}px"));
+        }
+        render_width(
+            shown_width as f64,
+            web_sys::window().map_or(1.0, |window| window.device_pixel_ratio()),
+            full_width,
+        )
+    } else {
+        full_width
+    };
+    if !restart && width == state.scene.camera.image_width() {
+        return;
+    }
+
+    let camera = if width == full_width {
+        state.full_camera.clone()
+    } else {
+        Arc::new(state.full_camera.with_image_width(width))
+    };
+    state.canvas.set_width(camera.image_width());
+    state.canvas.set_height(camera.image_height());
+    state.scene.camera = camera;
+    state.renderer = ProgressiveRenderer::new(&state.scene);
+    request_frame(&mut state);
+}
+
+/// Returns the width to render a canvas shown `shown_width` CSS pixels wide
+/// at, in device pixels and at most the scene's `full_width`. A canvas which
+/// is not shown renders at the scene's resolution.
+fn render_width(shown_width: f64, device_pixel_ratio: f64, full_width: u32) -> u32 {
+    let width = (shown_width * device_pixel_ratio).round();
+    if width >= 1.0 {
+        (width as u32).min(full_width)
+    } else {
+        full_width
+    }
+}
+
+fn request_frame(state: &mut ViewerState) {
+    if state.frame_handle.is_some() {
+        return;
+    }
+    let (Some(frame), Some(window)) = (&state.frame, web_sys::window()) else {
+        return;
+    };
+    state.frame_handle = window
+        .request_animation_frame(frame.as_ref().unchecked_ref())
+        .ok();
+}
+
+/// Renders pixels for the frame budget, draws the rows that changed and asks
+/// for another frame until every pass is done.
+fn render_frame(state: &Weak<RefCell<ViewerState>>) {
+    let Some(state) = state.upgrade() else {
+        return;
+    };
+    let mut state = state.borrow_mut();
+    state.frame_handle = None;
+    let ViewerState {
+        renderer,
+        ctx,
+        passes,
+        frame_budget_ms,
+        ..
+    } = &mut *state;
+    if renderer.passes() >= *passes {
+        return;
+    }
+
+    let start = js_sys::Date::now();
+    let pass = renderer.passes();
+    let first_pixel = renderer.next_pixel();
+    let mut last_pixel = first_pixel;
+    while renderer.passes() == pass {
+        renderer.render_next_pixel(ctx);
+        last_pixel += 1;
+        if js_sys::Date::now() - start >= *frame_budget_ms {
+            break;
+        }
+    }
+
+    let width = renderer.width();
+    let ymin = first_pixel / width;
+    let ymax = last_pixel.div_ceil(width);
+    let data: Vec<u8> = renderer
+        .rows(ymin, ymax, TransferFunction::Srgb)
+        .into_iter()
+        .flat_map(|color| {
+            let color = color.clamp(0.0, 0.999);
+            [
+                (color.r * 256.0) as u8,
+                (color.g * 256.0) as u8,
+                (color.b * 256.0) as u8,
+                255,
+            ]
+        })
+        .collect();
+    if let Ok(image_data) =
+        ImageData::new_with_u8_clamped_array_and_sh(Clamped(&data), width, ymax - ymin)
+    {
+        let _ = state.context.put_image_data(&image_data, 0.0, ymin as f64);
+    }
+
+    request_frame(&mut state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render_width;
+
+    #[test]
+    fn render_width_follows_the_shown_width() {
+        assert_eq!(render_width(400.0, 1.0, 1920), 400);
+        assert_eq!(render_width(400.0, 2.0, 1920), 800);
+        assert_eq!(render_width(333.0, 1.5, 1920), 500);
+        // Never above the scene's resolution
+        assert_eq!(render_width(1500.0, 2.0, 1920), 1920);
+        // Hidden canvases render at the scene's resolution
+        assert_eq!(render_width(0.0, 2.0, 1920), 1920);
+    }
+}
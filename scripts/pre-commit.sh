@@ -34,6 +34,9 @@ cargo build -p caustic-wasm --target wasm32-unknown-unknown
 banner "cargo test"
 cargo test --workspace --exclude caustic-wasm
 
+banner "wasm test"
+(cd "${SCRIPT_DIR}/../crates/core" && wasm-pack test --node -- --lib)
+
 banner "wasm-pack"
 cd "${SCRIPT_DIR}/../crates/wasm"
 ./scripts/build.sh
@@ -46,8 +49,8 @@ cd "${SCRIPT_DIR}/../webapp/frontend"
 banner "nvm install"
 nvm install
 
-banner "generate api"
-./scripts/generate-api.sh
+banner "check api"
+./scripts/check-api.sh
 
 banner "npm format"
 npm run format
@@ -0,0 +1,15 @@
+
+CREATE TABLE caustic_render_preset (
+    project_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    image_width INTEGER,
+    image_height INTEGER,
+    samples_per_pixel INTEGER,
+    max_depth INTEGER,
+    integrator TEXT NOT NULL,
+    denoise INTEGER NOT NULL,
+    created TEXT NOT NULL,
+    last_modified TEXT NOT NULL,
+    FOREIGN KEY (project_id) REFERENCES caustic_project(project_id),
+    PRIMARY KEY (project_id, name)
+);
@@ -0,0 +1,24 @@
+
+CREATE TABLE caustic_gallery_item (
+    gallery_id TEXT PRIMARY KEY,
+    project_id TEXT NOT NULL,
+    owner_user_id TEXT NOT NULL,
+    title TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    hidden INTEGER NOT NULL,
+    created TEXT NOT NULL,
+    FOREIGN KEY (project_id) REFERENCES caustic_project(project_id),
+    FOREIGN KEY (owner_user_id) REFERENCES caustic_user(user_id)
+);
+
+CREATE INDEX caustic_gallery_item_created ON caustic_gallery_item(hidden, created);
+
+CREATE TABLE caustic_gallery_report (
+    gallery_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    created TEXT NOT NULL,
+    FOREIGN KEY (gallery_id) REFERENCES caustic_gallery_item(gallery_id),
+    FOREIGN KEY (user_id) REFERENCES caustic_user(user_id),
+    PRIMARY KEY (gallery_id, user_id)
+);
@@ -0,0 +1,14 @@
+CREATE TABLE caustic_asset_upload (
+    upload_id TEXT PRIMARY KEY,
+    project_id TEXT NOT NULL,
+    filename TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    sha256 TEXT NOT NULL,
+    received_size INTEGER NOT NULL,
+    created TEXT NOT NULL,
+    last_modified TEXT NOT NULL,
+    FOREIGN KEY (project_id) REFERENCES caustic_project(project_id)
+);
+
+CREATE INDEX caustic_asset_upload_project_id ON caustic_asset_upload(project_id);
@@ -12,13 +12,31 @@ use clap::Parser;
 use env_logger::Env;
 use utoipa::openapi::{InfoBuilder, LicenseBuilder, OpenApi, Paths};
 
-use std::sync::Arc;
+use std::{net::SocketAddr, sync::Arc};
 
 use log::info;
+use routes::asset_upload_routes::{
+    __path_complete_asset_upload, __path_get_asset_upload, __path_put_asset_upload_part,
+    __path_start_asset_upload, complete_asset_upload, get_asset_upload, put_asset_upload_part,
+    start_asset_upload,
+};
+use routes::gallery_routes::{
+    __path_get_gallery, __path_get_gallery_item, __path_get_gallery_item_image,
+    __path_moderate_gallery_item, __path_publish_gallery_item, __path_report_gallery_item,
+    __path_unpublish_gallery_item, get_gallery, get_gallery_item, get_gallery_item_image,
+    moderate_gallery_item, publish_gallery_item, report_gallery_item, unpublish_gallery_item,
+};
+use routes::preview_routes::{__path_try_scene, try_scene};
 use routes::project_routes::{
     __path_copy_project, __path_create_project, __path_delete_project, __path_get_project,
-    __path_get_project_file, __path_get_projects, copy_project, create_project, delete_project,
-    get_project, get_project_file, get_projects,
+    __path_get_project_file, __path_get_project_mesh, __path_get_projects, __path_pick_project,
+    copy_project, create_project, delete_project, get_project, get_project_file, get_project_mesh,
+    get_projects, pick_project,
+};
+use routes::render_preset_routes::{
+    __path_delete_render_preset, __path_get_render_preset, __path_get_render_presets,
+    __path_put_render_preset, delete_render_preset, get_render_preset, get_render_presets,
+    put_render_preset,
 };
 use routes::user_routes::{
     __path_get_user_me, __path_google_token_verify, get_user_me, google_token_verify,
@@ -29,6 +47,8 @@ use utoipa_swagger_ui::SwaggerUi;
 
 use crate::state::AppState;
 
+pub const GALLERY_TAG: &str = "gallery";
+pub const PREVIEW_TAG: &str = "preview";
 pub const PROJECT_TAG: &str = "project";
 pub const USER_TAG: &str = "user";
 
@@ -74,7 +94,11 @@ async fn main() -> Result<()> {
 
     let listener = tokio::net::TcpListener::bind(&bind).await?;
     println!("listening http://{bind}");
-    axum::serve(listener, router).await?;
+    axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await?;
     Ok(())
 }
 
@@ -100,9 +124,27 @@ fn build_api_router() -> OpenApiRouter<Arc<AppState>> {
         .routes(routes!(get_project))
         .routes(routes!(get_projects))
         .routes(routes!(get_project_file))
+        .routes(routes!(get_project_mesh))
         .routes(routes!(create_project))
         .routes(routes!(copy_project))
         .routes(routes!(delete_project))
+        .routes(routes!(pick_project))
+        .routes(routes!(start_asset_upload))
+        .routes(routes!(get_asset_upload))
+        .routes(routes!(put_asset_upload_part))
+        .routes(routes!(complete_asset_upload))
+        .routes(routes!(get_render_presets))
+        .routes(routes!(
+            get_render_preset,
+            put_render_preset,
+            delete_render_preset
+        ))
+        .routes(routes!(get_gallery, publish_gallery_item))
+        .routes(routes!(get_gallery_item, unpublish_gallery_item))
+        .routes(routes!(get_gallery_item_image))
+        .routes(routes!(report_gallery_item))
+        .routes(routes!(moderate_gallery_item))
+        .routes(routes!(try_scene))
         .layer(middleware::from_fn(access_logs))
 }
 
@@ -0,0 +1,347 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result, anyhow};
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{Stream, StreamExt};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::prelude::FromRow;
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom},
+};
+use utoipa::ToSchema;
+
+use crate::repository::DbPool;
+
+/// Size of the buffer used to hash a completed upload
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// A project file being uploaded in parts, so large assets such as HDRIs
+/// survive dropped connections. Received bytes are kept in a part file until
+/// the upload completes.
+#[derive(ToSchema, Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetUpload {
+    pub id: String,
+    pub project_id: String,
+    pub filename: String,
+    pub content_type: String,
+    /// Size of the whole file in bytes
+    pub size: u64,
+    /// Hex encoded SHA-256 of the whole file
+    pub sha256: String,
+    /// Bytes received so far, the offset of the next part
+    pub received_size: u64,
+    #[schema(value_type = String)]
+    pub last_modified: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+struct AssetUploadRow {
+    upload_id: String,
+    project_id: String,
+    filename: String,
+    content_type: String,
+    size: i64,
+    sha256: String,
+    received_size: i64,
+    last_modified: String,
+}
+
+impl AssetUploadRow {
+    fn into_asset_upload(self) -> Result<AssetUpload> {
+        Ok(AssetUpload {
+            id: self.upload_id,
+            project_id: self.project_id,
+            filename: self.filename,
+            content_type: self.content_type,
+            size: self.size.try_into()?,
+            sha256: self.sha256,
+            received_size: self.received_size.try_into()?,
+            last_modified: self.last_modified.parse()?,
+        })
+    }
+}
+
+/// Bytes of a part written by [`AssetUploadRepository::write_part`].
+pub struct WrittenPart {
+    pub size: u64,
+    /// Hex encoded SHA-256 of the part
+    pub sha256: String,
+}
+
+pub struct AssetUploadRepository {
+    db_pool: DbPool,
+    uploads_path: PathBuf,
+}
+
+impl AssetUploadRepository {
+    pub fn new(db_pool: DbPool, data_path: &Path) -> Self {
+        Self {
+            db_pool,
+            uploads_path: data_path.join("uploads"),
+        }
+    }
+
+    pub async fn find_by_upload_id(&self, upload_id: &str) -> Result<Option<AssetUpload>> {
+        let row = sqlx::query_as::<_, AssetUploadRow>(
+            r#"
+            SELECT
+                upload_id,
+                project_id,
+                filename,
+                content_type,
+                size,
+                sha256,
+                received_size,
+                last_modified
+            FROM caustic_asset_upload
+            WHERE upload_id = ?
+            "#,
+        )
+        .bind(upload_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .context("Failed to read asset upload (by upload id)")?;
+
+        row.map(AssetUploadRow::into_asset_upload).transpose()
+    }
+
+    /// Returns an unfinished upload of the same file, so starting an upload
+    /// again resumes it rather than sending every part again.
+    pub async fn find_unfinished(
+        &self,
+        project_id: &str,
+        filename: &str,
+        sha256: &str,
+    ) -> Result<Option<AssetUpload>> {
+        let row = sqlx::query_as::<_, AssetUploadRow>(
+            r#"
+            SELECT
+                upload_id,
+                project_id,
+                filename,
+                content_type,
+                size,
+                sha256,
+                received_size,
+                last_modified
+            FROM caustic_asset_upload
+            WHERE project_id = ? AND filename = ? AND sha256 = ?
+            ORDER BY last_modified DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(project_id)
+        .bind(filename)
+        .bind(sha256)
+        .fetch_optional(&self.db_pool)
+        .await
+        .context("Failed to read asset upload (by file)")?;
+
+        row.map(AssetUploadRow::into_asset_upload).transpose()
+    }
+
+    pub async fn insert(&self, upload: &AssetUpload, created: &DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO caustic_asset_upload (
+                upload_id,
+                project_id,
+                filename,
+                content_type,
+                size,
+                sha256,
+                received_size,
+                created,
+                last_modified
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(&upload.id)
+        .bind(&upload.project_id)
+        .bind(&upload.filename)
+        .bind(&upload.content_type)
+        .bind(i64::try_from(upload.size)?)
+        .bind(&upload.sha256)
+        .bind(i64::try_from(upload.received_size)?)
+        .bind(created)
+        .bind(upload.last_modified)
+        .execute(&self.db_pool)
+        .await
+        .context("Failed to insert asset upload")?;
+        Ok(())
+    }
+
+    pub async fn update_received_size(
+        &self,
+        upload_id: &str,
+        received_size: u64,
+        last_modified: &DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE caustic_asset_upload SET received_size = ?, last_modified = ? WHERE upload_id = ?",
+        )
+        .bind(i64::try_from(received_size)?)
+        .bind(last_modified)
+        .bind(upload_id)
+        .execute(&self.db_pool)
+        .await
+        .context("Failed to update asset upload")?;
+        Ok(())
+    }
+
+    fn part_path(&self, upload_id: &str) -> PathBuf {
+        self.uploads_path.join(format!("{upload_id}.part"))
+    }
+
+    /// Writes a part of an upload starting at `offset`, dropping anything
+    /// received after it first so a failed part can be sent again. The part
+    /// is streamed to disk, at most `max_size` bytes are accepted.
+    pub async fn write_part<S, E>(
+        &self,
+        upload_id: &str,
+        offset: u64,
+        max_size: u64,
+        mut stream: S,
+    ) -> Result<WrittenPart>
+    where
+        S: Stream<Item = std::result::Result<Bytes, E>> + Unpin,
+        E: Into<axum::BoxError>,
+    {
+        fs::create_dir_all(&self.uploads_path)
+            .await
+            .with_context(|| {
+                format!(
+                    "saving file {:?} (could not create path)",
+                    self.uploads_path
+                )
+            })?;
+        let path = self.part_path(upload_id);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&path)
+            .await
+            .with_context(|| format!("opening file {path:?}"))?;
+        file.set_len(offset).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+
+        let mut hasher = Sha256::new();
+        let mut size = 0;
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|err| anyhow!("reading part: {}", err.into()))?;
+            size += chunk.len() as u64;
+            if size > max_size {
+                file.set_len(offset).await?;
+                return Err(anyhow!("part larger than {max_size} bytes"));
+            }
+            hasher.update(&chunk);
+            file.write_all(&chunk)
+                .await
+                .with_context(|| format!("saving file {path:?}"))?;
+        }
+        file.flush().await?;
+
+        Ok(WrittenPart {
+            size,
+            sha256: hex::encode(hasher.finalize()),
+        })
+    }
+
+    /// Drops the bytes received after `size`, to undo a part that failed its
+    /// checksum.
+    pub async fn truncate_part(&self, upload_id: &str, size: u64) -> Result<()> {
+        let path = self.part_path(upload_id);
+        let file = OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .await
+            .with_context(|| format!("opening file {path:?}"))?;
+        file.set_len(size).await?;
+        Ok(())
+    }
+
+    /// Returns the hex encoded SHA-256 of the bytes received, reading them in
+    /// small pieces.
+    pub async fn hash_received(&self, upload_id: &str) -> Result<String> {
+        let path = self.part_path(upload_id);
+        let mut file = match File::open(&path).await {
+            Ok(file) => file,
+            // Nothing was received for empty files
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Ok(hex::encode(Sha256::digest([])));
+            }
+            Err(err) => return Err(err).with_context(|| format!("opening file {path:?}")),
+        };
+
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0; HASH_BUFFER_SIZE];
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .await
+                .with_context(|| format!("reading file {path:?}"))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+        Ok(hex::encode(hasher.finalize()))
+    }
+
+    /// Moves the received bytes of a completed upload to `destination` and
+    /// forgets the upload.
+    pub async fn complete(&self, upload_id: &str, destination: &Path) -> Result<()> {
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("saving file {parent:?} (could not create path)"))?;
+        }
+        let path = self.part_path(upload_id);
+        if fs::try_exists(&path).await? {
+            fs::rename(&path, destination)
+                .await
+                .with_context(|| format!("moving file {path:?} to {destination:?}"))?;
+        } else {
+            File::create(destination)
+                .await
+                .with_context(|| format!("saving file {destination:?}"))?;
+        }
+        self.delete(upload_id).await
+    }
+
+    pub async fn delete(&self, upload_id: &str) -> Result<()> {
+        let path = self.part_path(upload_id);
+        if fs::try_exists(&path).await? {
+            fs::remove_file(&path)
+                .await
+                .with_context(|| format!("Failed to delete file {path:?}"))?;
+        }
+
+        sqlx::query("DELETE FROM caustic_asset_upload WHERE upload_id = ?")
+            .bind(upload_id)
+            .execute(&self.db_pool)
+            .await
+            .context("Failed to delete asset upload")?;
+        Ok(())
+    }
+
+    pub async fn delete_by_project_id(&self, project_id: &str) -> Result<()> {
+        let upload_ids: Vec<(String,)> =
+            sqlx::query_as("SELECT upload_id FROM caustic_asset_upload WHERE project_id = ?")
+                .bind(project_id)
+                .fetch_all(&self.db_pool)
+                .await
+                .context("Failed to read asset uploads (by project id)")?;
+        for (upload_id,) in upload_ids {
+            self.delete(&upload_id).await?;
+        }
+        Ok(())
+    }
+}
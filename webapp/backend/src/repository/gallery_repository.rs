@@ -0,0 +1,273 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+use crate::repository::DbPool;
+
+/// A render published to the public gallery, linking back to the project
+/// holding its scene.
+#[derive(ToSchema, Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GalleryItem {
+    pub id: String,
+    pub project_id: String,
+    pub owner_user_id: String,
+    pub title: String,
+    /// Content type of the rendered image
+    pub content_type: String,
+    /// Hidden items were taken down by moderation and are only visible to
+    /// their owner and moderators
+    pub hidden: bool,
+    #[schema(value_type = String)]
+    pub created: DateTime<Utc>,
+}
+
+#[derive(Debug, FromRow)]
+struct GalleryItemRow {
+    gallery_id: String,
+    project_id: String,
+    owner_user_id: String,
+    title: String,
+    content_type: String,
+    hidden: bool,
+    created: String,
+}
+
+impl GalleryItemRow {
+    fn into_gallery_item(self) -> Result<GalleryItem> {
+        Ok(GalleryItem {
+            id: self.gallery_id,
+            project_id: self.project_id,
+            owner_user_id: self.owner_user_id,
+            title: self.title,
+            content_type: self.content_type,
+            hidden: self.hidden,
+            created: self.created.parse()?,
+        })
+    }
+}
+
+pub struct GalleryRepository {
+    db_pool: DbPool,
+    data_path: PathBuf,
+}
+
+impl GalleryRepository {
+    pub fn new(db_pool: DbPool, data_path: &Path) -> Self {
+        Self {
+            db_pool,
+            data_path: data_path.join("gallery"),
+        }
+    }
+
+    /// Returns visible items, newest first.
+    pub async fn find_visible(&self, offset: u32, limit: u32) -> Result<Vec<GalleryItem>> {
+        let rows = sqlx::query_as::<_, GalleryItemRow>(
+            r#"
+            SELECT
+                gallery_id,
+                project_id,
+                owner_user_id,
+                title,
+                content_type,
+                hidden,
+                created
+            FROM caustic_gallery_item
+            WHERE hidden = 0
+            ORDER BY created DESC, gallery_id
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.db_pool)
+        .await
+        .context("Failed to read gallery items")?;
+
+        rows.into_iter()
+            .map(GalleryItemRow::into_gallery_item)
+            .collect()
+    }
+
+    pub async fn count_visible(&self) -> Result<u32> {
+        let (count,): (u32,) =
+            sqlx::query_as("SELECT COUNT(*) FROM caustic_gallery_item WHERE hidden = 0")
+                .fetch_one(&self.db_pool)
+                .await
+                .context("Failed to count gallery items")?;
+        Ok(count)
+    }
+
+    pub async fn find_by_gallery_id(&self, gallery_id: &str) -> Result<Option<GalleryItem>> {
+        let row = sqlx::query_as::<_, GalleryItemRow>(
+            r#"
+            SELECT
+                gallery_id,
+                project_id,
+                owner_user_id,
+                title,
+                content_type,
+                hidden,
+                created
+            FROM caustic_gallery_item
+            WHERE gallery_id = ?
+            "#,
+        )
+        .bind(gallery_id)
+        .fetch_optional(&self.db_pool)
+        .await
+        .context("Failed to read gallery item (by gallery id)")?;
+
+        row.map(GalleryItemRow::into_gallery_item).transpose()
+    }
+
+    /// Returns whether a project has a visible gallery item, which makes its
+    /// scene readable by everyone.
+    pub async fn is_project_published(&self, project_id: &str) -> Result<bool> {
+        let (count,): (u32,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM caustic_gallery_item WHERE project_id = ? AND hidden = 0",
+        )
+        .bind(project_id)
+        .fetch_one(&self.db_pool)
+        .await
+        .context("Failed to count gallery items (by project id)")?;
+        Ok(count > 0)
+    }
+
+    fn image_path(&self, gallery_id: &str) -> PathBuf {
+        self.data_path.join(gallery_id)
+    }
+
+    pub async fn load_image(&self, gallery_id: &str) -> Result<Option<Vec<u8>>> {
+        let path = self.image_path(gallery_id);
+        if path.exists() {
+            let contents = fs::read(&path).with_context(|| format!("loading file {path:?}"))?;
+            Ok(Some(contents))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn insert(&self, item: &GalleryItem, image: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.data_path)
+            .with_context(|| format!("saving file {:?} (could not create path)", self.data_path))?;
+        let path = self.image_path(&item.id);
+        fs::write(&path, image).with_context(|| format!("saving file {path:?}"))?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO caustic_gallery_item (
+                gallery_id,
+                project_id,
+                owner_user_id,
+                title,
+                content_type,
+                hidden,
+                created
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+        )
+        .bind(&item.id)
+        .bind(&item.project_id)
+        .bind(&item.owner_user_id)
+        .bind(&item.title)
+        .bind(&item.content_type)
+        .bind(item.hidden)
+        .bind(item.created)
+        .execute(&self.db_pool)
+        .await
+        .context("Failed to insert gallery item")?;
+        Ok(())
+    }
+
+    pub async fn set_hidden(&self, gallery_id: &str, hidden: bool) -> Result<()> {
+        sqlx::query("UPDATE caustic_gallery_item SET hidden = ? WHERE gallery_id = ?")
+            .bind(hidden)
+            .bind(gallery_id)
+            .execute(&self.db_pool)
+            .await
+            .context("Failed to update gallery item")?;
+        Ok(())
+    }
+
+    /// Records a report of an item, at most one per user, and returns the
+    /// number of users who reported it.
+    pub async fn insert_report(
+        &self,
+        gallery_id: &str,
+        user_id: &str,
+        reason: &str,
+        created: &DateTime<Utc>,
+    ) -> Result<u32> {
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO caustic_gallery_report (
+                gallery_id,
+                user_id,
+                reason,
+                created
+            ) VALUES (?, ?, ?, ?)"#,
+        )
+        .bind(gallery_id)
+        .bind(user_id)
+        .bind(reason)
+        .bind(created)
+        .execute(&self.db_pool)
+        .await
+        .context("Failed to insert gallery report")?;
+
+        let (count,): (u32,) =
+            sqlx::query_as("SELECT COUNT(*) FROM caustic_gallery_report WHERE gallery_id = ?")
+                .bind(gallery_id)
+                .fetch_one(&self.db_pool)
+                .await
+                .context("Failed to count gallery reports")?;
+        Ok(count)
+    }
+
+    pub async fn delete_reports(&self, gallery_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM caustic_gallery_report WHERE gallery_id = ?")
+            .bind(gallery_id)
+            .execute(&self.db_pool)
+            .await
+            .context("Failed to delete gallery reports")?;
+        Ok(())
+    }
+
+    pub async fn delete(&self, gallery_id: &str) -> Result<()> {
+        let path = self.image_path(gallery_id);
+        if fs::exists(&path)? {
+            fs::remove_file(&path).with_context(|| format!("Failed to delete file {path:?}"))?;
+        }
+
+        self.delete_reports(gallery_id).await?;
+
+        sqlx::query("DELETE FROM caustic_gallery_item WHERE gallery_id = ?")
+            .bind(gallery_id)
+            .execute(&self.db_pool)
+            .await
+            .context("Failed to delete gallery item")?;
+
+        Ok(())
+    }
+
+    pub async fn delete_by_project_id(&self, project_id: &str) -> Result<()> {
+        let rows: Vec<(String,)> =
+            sqlx::query_as("SELECT gallery_id FROM caustic_gallery_item WHERE project_id = ?")
+                .bind(project_id)
+                .fetch_all(&self.db_pool)
+                .await
+                .context("Failed to read gallery items (by project id)")?;
+
+        for (gallery_id,) in rows {
+            self.delete(&gallery_id).await?;
+        }
+        Ok(())
+    }
+}
@@ -2,7 +2,10 @@ use anyhow::Result;
 use sqlx::{Pool, Sqlite, SqlitePool, migrate::Migrator, sqlite::SqliteConnectOptions};
 use std::{path::Path, str::FromStr};
 
+pub mod asset_upload_repository;
+pub mod gallery_repository;
 pub mod project_repository;
+pub mod render_preset_repository;
 pub mod user_repository;
 
 pub type DbPool = Pool<Sqlite>;
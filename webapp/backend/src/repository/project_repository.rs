@@ -119,12 +119,21 @@ impl ProjectRepository {
         }
     }
 
+    /// Returns the directory holding the files of a project.
+    pub fn project_dir(&self, project_id: &str) -> PathBuf {
+        self.data_path.join(project_id)
+    }
+
+    pub fn project_file_path(&self, project_id: &str, filename: &str) -> PathBuf {
+        self.project_dir(project_id).join(filename)
+    }
+
     pub async fn load_project_file_data(
         &self,
         project_id: &str,
         filename: &str,
     ) -> Result<Option<Vec<u8>>> {
-        let path = self.data_path.join(project_id).join(filename);
+        let path = self.project_file_path(project_id, filename);
         if path.exists() {
             let contents = fs::read(&path).with_context(|| format!("loading file {path:?}"))?;
             Ok(Some(contents))
@@ -198,6 +207,52 @@ impl ProjectRepository {
         Ok(())
     }
 
+    /// Records a file whose data was already written to
+    /// [`ProjectRepository::project_file_path`], such as a completed upload.
+    /// New files are sorted after the existing ones.
+    pub async fn insert_or_update_project_file_entry(
+        &self,
+        project_id: &str,
+        filename: &str,
+        content_type: &str,
+        now: &DateTime<Utc>,
+    ) -> Result<ProjectFile> {
+        let (sort,): (u32,) = sqlx::query_as(
+            r#"
+            INSERT INTO caustic_project_file (
+                project_id,
+                filename,
+                content_type,
+                sort,
+                created,
+                last_modified
+            ) VALUES (
+                ?, ?, ?,
+                (SELECT COALESCE(MAX(sort), 0) + 1 FROM caustic_project_file WHERE project_id = ?),
+                ?, ?
+            )
+            ON CONFLICT (project_id, filename) DO UPDATE SET
+                content_type = excluded.content_type,
+                last_modified = excluded.last_modified
+            RETURNING sort"#,
+        )
+        .bind(project_id)
+        .bind(filename)
+        .bind(content_type)
+        .bind(project_id)
+        .bind(now)
+        .bind(now)
+        .fetch_one(&self.db_pool)
+        .await
+        .context("Failed to insert or update project file entry")?;
+
+        Ok(ProjectFile {
+            filename: filename.to_owned(),
+            content_type: content_type.to_owned(),
+            sort,
+        })
+    }
+
     pub async fn delete_project(&self, project_id: &str) -> Result<()> {
         let project_path = self.data_path.join(project_id);
         if fs::exists(&project_path)? {
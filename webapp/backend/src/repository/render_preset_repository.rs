@@ -0,0 +1,206 @@
+use anyhow::{Context, Result, anyhow};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::prelude::FromRow;
+use utoipa::ToSchema;
+
+use crate::repository::DbPool;
+
+/// Named render settings of a project, such as "preview" and "final", applied
+/// over the camera of the scene. Unset values keep the scene's own settings.
+#[derive(ToSchema, Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderPreset {
+    pub name: String,
+    pub image_width: Option<u32>,
+    pub image_height: Option<u32>,
+    pub samples_per_pixel: Option<u32>,
+    pub max_depth: Option<u32>,
+    pub integrator: RenderPresetIntegrator,
+    pub denoise: bool,
+    #[schema(value_type = String)]
+    pub last_modified: DateTime<Utc>,
+}
+
+#[derive(ToSchema, Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum RenderPresetIntegrator {
+    /// Path tracing with light sampling
+    #[default]
+    Path,
+}
+
+impl RenderPresetIntegrator {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RenderPresetIntegrator::Path => "path",
+        }
+    }
+
+    fn parse(value: &str) -> Result<Self> {
+        match value {
+            "path" => Ok(RenderPresetIntegrator::Path),
+            other => Err(anyhow!("unknown integrator \"{other}\"")),
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct RenderPresetRow {
+    name: String,
+    image_width: Option<u32>,
+    image_height: Option<u32>,
+    samples_per_pixel: Option<u32>,
+    max_depth: Option<u32>,
+    integrator: String,
+    denoise: bool,
+    last_modified: String,
+}
+
+impl RenderPresetRow {
+    fn into_render_preset(self) -> Result<RenderPreset> {
+        Ok(RenderPreset {
+            name: self.name,
+            image_width: self.image_width,
+            image_height: self.image_height,
+            samples_per_pixel: self.samples_per_pixel,
+            max_depth: self.max_depth,
+            integrator: RenderPresetIntegrator::parse(&self.integrator)?,
+            denoise: self.denoise,
+            last_modified: self.last_modified.parse()?,
+        })
+    }
+}
+
+pub struct RenderPresetRepository {
+    db_pool: DbPool,
+}
+
+impl RenderPresetRepository {
+    pub fn new(db_pool: DbPool) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn find_by_project_id(&self, project_id: &str) -> Result<Vec<RenderPreset>> {
+        let rows = sqlx::query_as::<_, RenderPresetRow>(
+            r#"
+            SELECT
+                name,
+                image_width,
+                image_height,
+                samples_per_pixel,
+                max_depth,
+                integrator,
+                denoise,
+                last_modified
+            FROM caustic_render_preset
+            WHERE project_id = ?
+            ORDER BY name
+            "#,
+        )
+        .bind(project_id)
+        .fetch_all(&self.db_pool)
+        .await
+        .context("Failed to read render presets (by project id)")?;
+
+        rows.into_iter()
+            .map(RenderPresetRow::into_render_preset)
+            .collect()
+    }
+
+    pub async fn find_by_project_id_and_name(
+        &self,
+        project_id: &str,
+        name: &str,
+    ) -> Result<Option<RenderPreset>> {
+        let row = sqlx::query_as::<_, RenderPresetRow>(
+            r#"
+            SELECT
+                name,
+                image_width,
+                image_height,
+                samples_per_pixel,
+                max_depth,
+                integrator,
+                denoise,
+                last_modified
+            FROM caustic_render_preset
+            WHERE project_id = ? AND name = ?
+            "#,
+        )
+        .bind(project_id)
+        .bind(name)
+        .fetch_optional(&self.db_pool)
+        .await
+        .context("Failed to read render preset (by project id and name)")?;
+
+        row.map(RenderPresetRow::into_render_preset).transpose()
+    }
+
+    pub async fn insert_or_update(
+        &self,
+        project_id: &str,
+        preset: &RenderPreset,
+        created: &DateTime<Utc>,
+    ) -> Result<()> {
+        // Keep the creation time of a preset being updated
+        sqlx::query(
+            r#"
+            INSERT INTO caustic_render_preset (
+                project_id,
+                name,
+                image_width,
+                image_height,
+                samples_per_pixel,
+                max_depth,
+                integrator,
+                denoise,
+                created,
+                last_modified
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (project_id, name) DO UPDATE SET
+                image_width = excluded.image_width,
+                image_height = excluded.image_height,
+                samples_per_pixel = excluded.samples_per_pixel,
+                max_depth = excluded.max_depth,
+                integrator = excluded.integrator,
+                denoise = excluded.denoise,
+                last_modified = excluded.last_modified"#,
+        )
+        .bind(project_id)
+        .bind(&preset.name)
+        .bind(preset.image_width)
+        .bind(preset.image_height)
+        .bind(preset.samples_per_pixel)
+        .bind(preset.max_depth)
+        .bind(preset.integrator.as_str())
+        .bind(preset.denoise)
+        .bind(created)
+        .bind(preset.last_modified)
+        .execute(&self.db_pool)
+        .await
+        .context("Failed to insert or update render preset")?;
+        Ok(())
+    }
+
+    /// Returns whether a preset was deleted.
+    pub async fn delete(&self, project_id: &str, name: &str) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM caustic_render_preset WHERE project_id = ? AND name = ?")
+                .bind(project_id)
+                .bind(name)
+                .execute(&self.db_pool)
+                .await
+                .context("Failed to delete render preset")?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn delete_by_project_id(&self, project_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM caustic_render_preset WHERE project_id = ?")
+            .bind(project_id)
+            .execute(&self.db_pool)
+            .await
+            .context("Failed to delete render presets")?;
+        Ok(())
+    }
+}
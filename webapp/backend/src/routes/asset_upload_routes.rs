@@ -0,0 +1,721 @@
+use std::sync::Arc;
+
+use axum::{body::Body, extract::State};
+use chrono::Utc;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::routes::extract::{Json, Path, Query};
+use crate::{
+    PROJECT_TAG,
+    repository::{asset_upload_repository::AssetUpload, project_repository::ProjectFile},
+    routes::{error::ApiError, project_routes::assert_load_project_owner, user_routes::AuthUser},
+    services::mesh_service::{MeshResult, MeshService},
+    state::AppState,
+};
+
+/// Largest part accepted, clients send parts of at most this size
+pub const UPLOAD_PART_SIZE: u64 = 8 * 1024 * 1024;
+
+/// Largest file accepted
+const MAX_UPLOAD_SIZE: u64 = 512 * 1024 * 1024;
+
+const MAX_FILENAME_LENGTH: usize = 255;
+
+#[derive(ToSchema, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartAssetUploadRequest {
+    filename: String,
+    content_type: String,
+    /// Size of the whole file in bytes
+    size: u64,
+    /// Hex encoded SHA-256 of the whole file
+    sha256: String,
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetUploadResponse {
+    pub upload: AssetUpload,
+    /// Largest part accepted in bytes
+    pub part_size: u64,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct PutAssetUploadPartQuery {
+    /// Offset of the part in the file, the received size of the upload or
+    /// less to send parts again
+    offset: u64,
+    /// Hex encoded SHA-256 of the part
+    sha256: String,
+}
+
+fn validate_filename(filename: &str) -> Result<(), ApiError> {
+    let valid = !filename.trim().is_empty()
+        && filename.len() <= MAX_FILENAME_LENGTH
+        && filename != "."
+        && filename != ".."
+        && !filename.contains(['/', '\\', '\0']);
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::bad_request("invalid filename").with_details(filename))
+    }
+}
+
+fn is_sha256(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+async fn assert_load_upload(
+    state: &AppState,
+    project_id: &str,
+    upload_id: &str,
+) -> Result<AssetUpload, ApiError> {
+    let upload = state
+        .asset_upload_repository
+        .find_by_upload_id(upload_id)
+        .await
+        .map_err(|err| {
+            error!("failed to load upload (upload id: {upload_id}): {err:?}");
+            ApiError::internal_server_error("failed to load upload")
+        })?;
+    match upload {
+        Some(upload) if upload.project_id == project_id => Ok(upload),
+        _ => Err(ApiError::not_found("upload not found").with_details(upload_id)),
+    }
+}
+
+/// Starts uploading a project file in parts, or resumes the unfinished upload
+/// of the same file.
+#[utoipa::path(
+    post,
+    path = "/api/v1/project/{project_id}/upload",
+    responses(
+        (status = OK, body = AssetUploadResponse),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn start_asset_upload(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(project_id): Path<String>,
+    Json(payload): Json<StartAssetUploadRequest>,
+) -> Result<Json<AssetUploadResponse>, ApiError> {
+    validate_filename(&payload.filename)?;
+    if payload.size > MAX_UPLOAD_SIZE {
+        return Err(ApiError::bad_request(format!(
+            "files must be at most {MAX_UPLOAD_SIZE} bytes"
+        )));
+    }
+    let sha256 = payload.sha256.to_ascii_lowercase();
+    if !is_sha256(&sha256) {
+        return Err(ApiError::bad_request("sha256 must be 64 hex digits").with_details(sha256));
+    }
+
+    assert_load_project_owner(&state.project_service, &project_id, &Some(user)).await?;
+
+    let existing = state
+        .asset_upload_repository
+        .find_unfinished(&project_id, &payload.filename, &sha256)
+        .await
+        .map_err(|err| {
+            error!("failed to load upload: {err:?}");
+            ApiError::internal_server_error("failed to load upload")
+        })?;
+    if let Some(upload) = existing {
+        info!(
+            "resuming upload (upload id: {}, received: {}/{})",
+            upload.id, upload.received_size, upload.size
+        );
+        return Ok(Json(AssetUploadResponse {
+            upload,
+            part_size: UPLOAD_PART_SIZE,
+        }));
+    }
+
+    let now = Utc::now();
+    let upload = AssetUpload {
+        id: Uuid::new_v4().to_string(),
+        project_id,
+        filename: payload.filename,
+        content_type: payload.content_type,
+        size: payload.size,
+        sha256,
+        received_size: 0,
+        last_modified: now,
+    };
+    info!(
+        "starting upload (upload id: {}, project id: {}, filename: {}, size: {})",
+        upload.id, upload.project_id, upload.filename, upload.size
+    );
+    state
+        .asset_upload_repository
+        .insert(&upload, &now)
+        .await
+        .map_err(|err| {
+            error!("failed to save upload: {err:?}");
+            ApiError::internal_server_error("failed to save upload")
+        })?;
+
+    Ok(Json(AssetUploadResponse {
+        upload,
+        part_size: UPLOAD_PART_SIZE,
+    }))
+}
+
+/// Returns how much of an upload was received, to resume it.
+#[utoipa::path(
+    get,
+    path = "/api/v1/project/{project_id}/upload/{upload_id}",
+    responses(
+        (status = OK, body = AssetUploadResponse),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn get_asset_upload(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((project_id, upload_id)): Path<(String, String)>,
+) -> Result<Json<AssetUploadResponse>, ApiError> {
+    assert_load_project_owner(&state.project_service, &project_id, &Some(user)).await?;
+    let upload = assert_load_upload(&state, &project_id, &upload_id).await?;
+    Ok(Json(AssetUploadResponse {
+        upload,
+        part_size: UPLOAD_PART_SIZE,
+    }))
+}
+
+/// Receives a part of an upload, sent as the request body. The part is
+/// written to disk as it arrives and dropped if its checksum does not match.
+#[utoipa::path(
+    put,
+    path = "/api/v1/project/{project_id}/upload/{upload_id}/part",
+    params(PutAssetUploadPartQuery),
+    request_body(content = Vec<u8>, content_type = "application/octet-stream"),
+    responses(
+        (status = OK, body = AssetUploadResponse),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn put_asset_upload_part(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((project_id, upload_id)): Path<(String, String)>,
+    Query(query): Query<PutAssetUploadPartQuery>,
+    body: Body,
+) -> Result<Json<AssetUploadResponse>, ApiError> {
+    assert_load_project_owner(&state.project_service, &project_id, &Some(user)).await?;
+    let mut upload = assert_load_upload(&state, &project_id, &upload_id).await?;
+
+    if query.offset > upload.received_size {
+        return Err(
+            ApiError::bad_request("part starts after the bytes received").with_details(format!(
+                "offset {}, received {}",
+                query.offset, upload.received_size
+            )),
+        );
+    }
+    let max_size = UPLOAD_PART_SIZE.min(upload.size - query.offset);
+
+    let part = state
+        .asset_upload_repository
+        .write_part(&upload_id, query.offset, max_size, body.into_data_stream())
+        .await
+        .map_err(|err| {
+            error!("failed to write upload part (upload id: {upload_id}): {err:?}");
+            ApiError::bad_request("failed to receive part").with_details(err.to_string())
+        })?;
+
+    if !part.sha256.eq_ignore_ascii_case(&query.sha256) {
+        state
+            .asset_upload_repository
+            .truncate_part(&upload_id, query.offset)
+            .await
+            .map_err(|err| {
+                error!("failed to drop upload part (upload id: {upload_id}): {err:?}");
+                ApiError::internal_server_error("failed to drop part")
+            })?;
+        return Err(
+            ApiError::bad_request("part checksum does not match, send it again").with_details(
+                format!("expected {}, received {}", query.sha256, part.sha256),
+            ),
+        );
+    }
+
+    let now = Utc::now();
+    upload.received_size = query.offset + part.size;
+    upload.last_modified = now;
+    state
+        .asset_upload_repository
+        .update_received_size(&upload_id, upload.received_size, &now)
+        .await
+        .map_err(|err| {
+            error!("failed to save upload: {err:?}");
+            ApiError::internal_server_error("failed to save upload")
+        })?;
+
+    Ok(Json(AssetUploadResponse {
+        upload,
+        part_size: UPLOAD_PART_SIZE,
+    }))
+}
+
+/// Checks the whole file of a fully received upload against its checksum and
+/// adds it to the project, replacing a file of the same name.
+#[utoipa::path(
+    post,
+    path = "/api/v1/project/{project_id}/upload/{upload_id}/complete",
+    responses(
+        (status = OK, body = ProjectFile),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn complete_asset_upload(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((project_id, upload_id)): Path<(String, String)>,
+) -> Result<Json<ProjectFile>, ApiError> {
+    assert_load_project_owner(&state.project_service, &project_id, &Some(user)).await?;
+    let upload = assert_load_upload(&state, &project_id, &upload_id).await?;
+
+    if upload.received_size != upload.size {
+        return Err(
+            ApiError::bad_request("upload is missing parts").with_details(format!(
+                "received {} of {} bytes",
+                upload.received_size, upload.size
+            )),
+        );
+    }
+
+    let sha256 = state
+        .asset_upload_repository
+        .hash_received(&upload_id)
+        .await
+        .map_err(|err| {
+            error!("failed to hash upload (upload id: {upload_id}): {err:?}");
+            ApiError::internal_server_error("failed to check upload")
+        })?;
+    if sha256 != upload.sha256 {
+        // The parts cannot be told apart, start over
+        state
+            .asset_upload_repository
+            .delete(&upload_id)
+            .await
+            .map_err(|err| {
+                error!("failed to delete upload (upload id: {upload_id}): {err:?}");
+                ApiError::internal_server_error("failed to delete upload")
+            })?;
+        return Err(
+            ApiError::bad_request("file checksum does not match, upload it again")
+                .with_details(format!("expected {}, received {sha256}", upload.sha256)),
+        );
+    }
+
+    let destination = state
+        .project_repository
+        .project_file_path(&project_id, &upload.filename);
+    state
+        .asset_upload_repository
+        .complete(&upload_id, &destination)
+        .await
+        .map_err(|err| {
+            error!("failed to store upload (upload id: {upload_id}): {err:?}");
+            ApiError::internal_server_error("failed to store upload")
+        })?;
+
+    let file = state
+        .project_repository
+        .insert_or_update_project_file_entry(
+            &project_id,
+            &upload.filename,
+            &upload.content_type,
+            &Utc::now(),
+        )
+        .await
+        .map_err(|err| {
+            error!("failed to save project file: {err:?}");
+            ApiError::internal_server_error("failed to save project file")
+        })?;
+    info!(
+        "completed upload (upload id: {upload_id}, project id: {project_id}, filename: {})",
+        file.filename
+    );
+
+    if MeshService::is_mesh(&file.filename) {
+        // Convert now so renders importing the mesh do not wait for it
+        let mesh_service = state.mesh_service.clone();
+        let filename = file.filename.clone();
+        tokio::spawn(async move {
+            match mesh_service.get_or_convert(&destination, &filename).await {
+                Ok(MeshResult::Converted(_)) => {}
+                Ok(MeshResult::Invalid(reason)) => warn!(
+                    "uploaded mesh can not be rendered (project id: {project_id}, filename: {filename}): {reason}"
+                ),
+                Err(err) => error!(
+                    "failed to convert mesh (project id: {project_id}, filename: {filename}): {err:?}"
+                ),
+            }
+        });
+    }
+
+    Ok(Json(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use axum::{body::Body, extract::State};
+    use chrono::Utc;
+    use sha2::{Digest, Sha256};
+
+    use super::{
+        AssetUploadResponse, MAX_UPLOAD_SIZE, PutAssetUploadPartQuery, StartAssetUploadRequest,
+        complete_asset_upload, put_asset_upload_part, start_asset_upload,
+    };
+    use crate::{
+        repository::user_repository::UserData,
+        routes::{
+            error::{ApiError, ApiErrorCode},
+            extract::{Json, Path, Query},
+            project_routes::delete_project,
+            user_routes::AuthUser,
+        },
+        state::{AppState, AppStateSettings},
+    };
+
+    const DATA: &[u8] = b"0123456789";
+
+    fn sha256(data: &[u8]) -> String {
+        hex::encode(Sha256::digest(data))
+    }
+
+    /// Returns a state with an empty database and data directory, a signed
+    /// in user and a project they own.
+    async fn setup(name: &str) -> (Arc<AppState>, AuthUser, String) {
+        let data_path = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&data_path);
+        fs::create_dir_all(&data_path).unwrap();
+        let state = AppState::new_with_settings(AppStateSettings {
+            google_client_id: String::new(),
+            google_client_secret: String::new(),
+            google_redirect_url: String::new(),
+            jwt_secret: "secret".to_owned(),
+            bind: String::new(),
+            jwt_expire_duration_hours: 1,
+            sqlite_connection_string: format!("sqlite://{}", data_path.join("db.sqlite").display()),
+            data_path,
+            gallery_moderator_user_ids: vec![],
+            trust_forwarded_for: false,
+            forwarded_for_hops: 1,
+        })
+        .await
+        .unwrap();
+        let state = Arc::new(state);
+
+        let user = AuthUser {
+            user_id: "uploader".to_owned(),
+            email: "uploader@example.com".to_owned(),
+            name: "Uploader".to_owned(),
+            picture: None,
+        };
+        state
+            .user_repository
+            .create(&UserData {
+                user_id: user.user_id.clone(),
+                email: user.email.clone(),
+                projects: vec![],
+                created: Utc::now(),
+            })
+            .await
+            .unwrap();
+        let project_id = "uploads".to_owned();
+        let now = Utc::now();
+        state
+            .project_repository
+            .insert_or_update_project(&project_id, "uploads", &user.user_id, &now, &now)
+            .await
+            .unwrap();
+        (state, user, project_id)
+    }
+
+    async fn start(
+        state: &Arc<AppState>,
+        user: &AuthUser,
+        project_id: &str,
+        size: u64,
+    ) -> Result<AssetUploadResponse, ApiError> {
+        start_asset_upload(
+            State(state.clone()),
+            user.clone(),
+            Path(project_id.to_owned()),
+            Json(StartAssetUploadRequest {
+                filename: "data.bin".to_owned(),
+                content_type: "application/octet-stream".to_owned(),
+                size,
+                sha256: sha256(DATA),
+            }),
+        )
+        .await
+        .map(|Json(response)| response)
+    }
+
+    async fn put(
+        state: &Arc<AppState>,
+        user: &AuthUser,
+        project_id: &str,
+        upload_id: &str,
+        offset: u64,
+        part: &[u8],
+        sha256: String,
+    ) -> Result<AssetUploadResponse, ApiError> {
+        put_asset_upload_part(
+            State(state.clone()),
+            user.clone(),
+            Path((project_id.to_owned(), upload_id.to_owned())),
+            Query(PutAssetUploadPartQuery { offset, sha256 }),
+            Body::from(part.to_vec()),
+        )
+        .await
+        .map(|Json(response)| response)
+    }
+
+    #[tokio::test]
+    async fn resumes_upload_at_received_offset() {
+        let (state, user, project_id) = setup("caustic-upload-resume").await;
+        let upload = start(&state, &user, &project_id, DATA.len() as u64)
+            .await
+            .unwrap()
+            .upload;
+        let received = put(
+            &state,
+            &user,
+            &project_id,
+            &upload.id,
+            0,
+            &DATA[..4],
+            sha256(&DATA[..4]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(received.upload.received_size, 4);
+
+        // Starting the same file again resumes the upload
+        let resumed = start(&state, &user, &project_id, DATA.len() as u64)
+            .await
+            .unwrap()
+            .upload;
+        assert_eq!(resumed.id, upload.id);
+        assert_eq!(resumed.received_size, 4);
+
+        // A part sent again from an earlier offset replaces what followed it
+        put(
+            &state,
+            &user,
+            &project_id,
+            &upload.id,
+            2,
+            &DATA[2..6],
+            sha256(&DATA[2..6]),
+        )
+        .await
+        .unwrap();
+        put(
+            &state,
+            &user,
+            &project_id,
+            &upload.id,
+            6,
+            &DATA[6..],
+            sha256(&DATA[6..]),
+        )
+        .await
+        .unwrap();
+        let Json(file) = complete_asset_upload(
+            State(state.clone()),
+            user.clone(),
+            Path((project_id.clone(), upload.id.clone())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(file.filename, "data.bin");
+        let path = state
+            .project_repository
+            .project_file_path(&project_id, "data.bin");
+        assert_eq!(fs::read(path).unwrap(), DATA);
+    }
+
+    #[tokio::test]
+    async fn rejects_part_after_received_bytes() {
+        let (state, user, project_id) = setup("caustic-upload-gap").await;
+        let upload = start(&state, &user, &project_id, DATA.len() as u64)
+            .await
+            .unwrap()
+            .upload;
+        let err = put(
+            &state,
+            &user,
+            &project_id,
+            &upload.id,
+            4,
+            &DATA[4..],
+            sha256(&DATA[4..]),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::BadRequest);
+        let upload = state
+            .asset_upload_repository
+            .find_by_upload_id(&upload.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(upload.received_size, 0);
+    }
+
+    #[tokio::test]
+    async fn rejects_uploads_over_max_size() {
+        let (state, user, project_id) = setup("caustic-upload-max-size").await;
+        let err = start(&state, &user, &project_id, MAX_UPLOAD_SIZE + 1)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::BadRequest);
+
+        // Parts running past the size of the file are dropped
+        let upload = start(&state, &user, &project_id, 4).await.unwrap().upload;
+        let err = put(
+            &state,
+            &user,
+            &project_id,
+            &upload.id,
+            0,
+            DATA,
+            sha256(DATA),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::BadRequest);
+        let upload = state
+            .asset_upload_repository
+            .find_by_upload_id(&upload.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(upload.received_size, 0);
+        let part = state
+            .settings
+            .data_path
+            .join("uploads")
+            .join(format!("{}.part", upload.id));
+        assert_eq!(fs::metadata(part).unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn drops_part_with_wrong_checksum() {
+        let (state, user, project_id) = setup("caustic-upload-checksum").await;
+        let upload = start(&state, &user, &project_id, DATA.len() as u64)
+            .await
+            .unwrap()
+            .upload;
+        put(
+            &state,
+            &user,
+            &project_id,
+            &upload.id,
+            0,
+            &DATA[..4],
+            sha256(&DATA[..4]),
+        )
+        .await
+        .unwrap();
+
+        let err = put(
+            &state,
+            &user,
+            &project_id,
+            &upload.id,
+            4,
+            &DATA[4..],
+            sha256(b"other"),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::BadRequest);
+        let part = state
+            .settings
+            .data_path
+            .join("uploads")
+            .join(format!("{}.part", upload.id));
+        assert_eq!(fs::metadata(&part).unwrap().len(), 4);
+        let upload = state
+            .asset_upload_repository
+            .find_by_upload_id(&upload.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(upload.received_size, 4);
+    }
+
+    #[tokio::test]
+    async fn delete_project_removes_uploads() {
+        let (state, user, project_id) = setup("caustic-upload-delete").await;
+        let upload = start(&state, &user, &project_id, DATA.len() as u64)
+            .await
+            .unwrap()
+            .upload;
+        put(
+            &state,
+            &user,
+            &project_id,
+            &upload.id,
+            0,
+            &DATA[..4],
+            sha256(&DATA[..4]),
+        )
+        .await
+        .unwrap();
+        let part = state
+            .settings
+            .data_path
+            .join("uploads")
+            .join(format!("{}.part", upload.id));
+        assert!(part.exists());
+
+        delete_project(
+            State(state.clone()),
+            user.clone(),
+            Json(serde_json::from_value(serde_json::json!({ "projectId": project_id })).unwrap()),
+        )
+        .await
+        .unwrap();
+        assert!(!part.exists());
+        assert!(
+            state
+                .asset_upload_repository
+                .find_by_upload_id(&upload.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use axum::{
+    Json,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(ToSchema, Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApiErrorCode {
+    BadRequest,
+    Unauthorized,
+    NotFound,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    UnprocessableEntity,
+    TooManyRequests,
+    InternalServerError,
+}
+
+impl ApiErrorCode {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ApiErrorCode::BadRequest => StatusCode::BAD_REQUEST,
+            ApiErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiErrorCode::NotFound => StatusCode::NOT_FOUND,
+            ApiErrorCode::PayloadTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            ApiErrorCode::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            ApiErrorCode::UnprocessableEntity => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiErrorCode::TooManyRequests => StatusCode::TOO_MANY_REQUESTS,
+            ApiErrorCode::InternalServerError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    /// Returns the code of `status`, falling back to `BadRequest` for other
+    /// client errors and `InternalServerError` for the rest.
+    pub fn from_status_code(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => ApiErrorCode::Unauthorized,
+            StatusCode::NOT_FOUND => ApiErrorCode::NotFound,
+            StatusCode::PAYLOAD_TOO_LARGE => ApiErrorCode::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ApiErrorCode::UnsupportedMediaType,
+            StatusCode::UNPROCESSABLE_ENTITY => ApiErrorCode::UnprocessableEntity,
+            StatusCode::TOO_MANY_REQUESTS => ApiErrorCode::TooManyRequests,
+            status if status.is_client_error() => ApiErrorCode::BadRequest,
+            _ => ApiErrorCode::InternalServerError,
+        }
+    }
+}
+
+/// JSON body returned by every route on failure.
+#[derive(ToSchema, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiError {
+    pub code: ApiErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+impl ApiError {
+    pub fn new(code: ApiErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl Into<String>) -> Self {
+        self.details = Some(details.into());
+        self
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(ApiErrorCode::BadRequest, message)
+    }
+
+    pub fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(ApiErrorCode::Unauthorized, message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ApiErrorCode::NotFound, message)
+    }
+
+    pub fn too_many_requests(message: impl Into<String>) -> Self {
+        Self::new(ApiErrorCode::TooManyRequests, message)
+    }
+
+    pub fn internal_server_error(message: impl Into<String>) -> Self {
+        Self::new(ApiErrorCode::InternalServerError, message)
+    }
+
+    pub fn status_code(&self) -> StatusCode {
+        self.code.status_code()
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status_code(), Json(self)).into_response()
+    }
+}
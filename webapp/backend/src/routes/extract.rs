@@ -0,0 +1,93 @@
+//! Extractors wrapping axum's, which reject malformed requests with an
+//! [`ApiError`] body instead of axum's plain text.
+
+use axum::{
+    extract::{
+        FromRequest, FromRequestParts,
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+    },
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::routes::error::{ApiError, ApiErrorCode};
+
+/// JSON request or response body.
+#[derive(Debug, FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiError))]
+pub struct Json<T>(pub T);
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+/// Parameters taken from the request path.
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+pub struct Path<T>(pub T);
+
+/// Parameters taken from the query string.
+#[derive(Debug, FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiError))]
+pub struct Query<T>(pub T);
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::new(
+            ApiErrorCode::from_status_code(rejection.status()),
+            "invalid JSON body",
+        )
+        .with_details(rejection.body_text())
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(rejection: PathRejection) -> Self {
+        ApiError::new(
+            ApiErrorCode::from_status_code(rejection.status()),
+            "invalid path parameters",
+        )
+        .with_details(rejection.body_text())
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(rejection: QueryRejection) -> Self {
+        ApiError::new(
+            ApiErrorCode::from_status_code(rejection.status()),
+            "invalid query parameters",
+        )
+        .with_details(rejection.body_text())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, http::Request, routing::post};
+    use tower::ServiceExt;
+
+    use super::Json;
+
+    #[tokio::test]
+    async fn rejects_invalid_json_with_api_error() {
+        let app = Router::new().route(
+            "/",
+            post(|Json(value): Json<serde_json::Value>| async move { Json(value) }),
+        );
+        let request = Request::post("/")
+            .header("content-type", "application/json")
+            .body(Body::from("{"))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), 400);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "BAD_REQUEST");
+        assert_eq!(body["message"], "invalid JSON body");
+    }
+}
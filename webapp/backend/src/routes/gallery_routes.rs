@@ -0,0 +1,445 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{HeaderMap, header},
+    response::Response,
+};
+use chrono::Utc;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::routes::extract::{Json, Path, Query};
+use crate::{
+    GALLERY_TAG,
+    repository::gallery_repository::GalleryItem,
+    routes::{
+        error::ApiError,
+        project_routes::{assert_load_project_owner, assert_load_user_data},
+        user_routes::{AuthUser, MaybeAuthUser},
+    },
+    state::AppState,
+};
+
+const MAX_TITLE_LENGTH: usize = 100;
+const MAX_REASON_LENGTH: usize = 1000;
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+/// Number of users reporting an item after which it is hidden until a
+/// moderator restores it
+const REPORTS_TO_HIDE: u32 = 3;
+const IMAGE_CONTENT_TYPES: [&str; 3] = ["image/png", "image/jpeg", "image/webp"];
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct GetGalleryQuery {
+    /// Page to return, starting at 0
+    page: Option<u32>,
+    /// Items per page, at most 100
+    page_size: Option<u32>,
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetGalleryResponse {
+    pub items: Vec<GalleryItem>,
+    pub page: u32,
+    pub page_size: u32,
+    /// Number of visible items over all pages
+    pub total: u32,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct PublishGalleryItemQuery {
+    /// Project holding the scene of the render
+    project_id: String,
+    title: String,
+}
+
+#[derive(ToSchema, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportGalleryItemRequest {
+    reason: String,
+}
+
+#[derive(ToSchema, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModerateGalleryItemRequest {
+    hidden: bool,
+}
+
+fn is_moderator(state: &AppState, user: &AuthUser) -> bool {
+    state
+        .settings
+        .gallery_moderator_user_ids
+        .contains(&user.user_id)
+}
+
+/// Loads an item, treating hidden items as missing for everyone but their
+/// owner and moderators.
+async fn assert_load_gallery_item(
+    state: &AppState,
+    gallery_id: &str,
+    user: &Option<AuthUser>,
+) -> Result<GalleryItem, ApiError> {
+    let item = state
+        .gallery_repository
+        .find_by_gallery_id(gallery_id)
+        .await
+        .map_err(|err| {
+            error!("failed to load gallery item (gallery id: {gallery_id}): {err:?}");
+            ApiError::internal_server_error("failed to load gallery item")
+        })?;
+
+    match item {
+        Some(item)
+            if !item.hidden
+                || user.as_ref().is_some_and(|user| {
+                    item.owner_user_id == user.user_id || is_moderator(state, user)
+                }) =>
+        {
+            Ok(item)
+        }
+        _ => Err(ApiError::not_found("gallery item not found").with_details(gallery_id)),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/gallery",
+    params(GetGalleryQuery),
+    responses(
+        (status = OK, body = GetGalleryResponse),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = GALLERY_TAG
+)]
+pub async fn get_gallery(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetGalleryQuery>,
+) -> Result<Json<GetGalleryResponse>, ApiError> {
+    let page = query.page.unwrap_or(0);
+    let page_size = query
+        .page_size
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .clamp(1, MAX_PAGE_SIZE);
+
+    let items = state
+        .gallery_repository
+        .find_visible(page.saturating_mul(page_size), page_size)
+        .await
+        .map_err(|err| {
+            error!("failed to load gallery items: {err:?}");
+            ApiError::internal_server_error("failed to load gallery")
+        })?;
+    let total = state
+        .gallery_repository
+        .count_visible()
+        .await
+        .map_err(|err| {
+            error!("failed to count gallery items: {err:?}");
+            ApiError::internal_server_error("failed to load gallery")
+        })?;
+
+    Ok(Json(GetGalleryResponse {
+        items,
+        page,
+        page_size,
+        total,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/gallery/{gallery_id}",
+    responses(
+        (status = OK, body = GalleryItem),
+        (status = NOT_FOUND, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = GALLERY_TAG
+)]
+pub async fn get_gallery_item(
+    State(state): State<Arc<AppState>>,
+    user: MaybeAuthUser,
+    Path(gallery_id): Path<String>,
+) -> Result<Json<GalleryItem>, ApiError> {
+    assert_load_gallery_item(&state, &gallery_id, &user.user)
+        .await
+        .map(Json)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/gallery/{gallery_id}/image",
+    responses(
+        (status = OK, content_type = "image/*"),
+        (status = NOT_FOUND, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = GALLERY_TAG
+)]
+pub async fn get_gallery_item_image(
+    State(state): State<Arc<AppState>>,
+    user: MaybeAuthUser,
+    Path(gallery_id): Path<String>,
+) -> Result<Response, ApiError> {
+    let item = assert_load_gallery_item(&state, &gallery_id, &user.user).await?;
+
+    let image = state
+        .gallery_repository
+        .load_image(&gallery_id)
+        .await
+        .map_err(|err| {
+            error!("failed to load gallery image: {err:?}");
+            ApiError::internal_server_error("failed to load gallery image")
+        })?;
+
+    if let Some(image) = image {
+        let mut response = Response::new(Body::from(image));
+        response.headers_mut().insert(
+            header::CONTENT_TYPE,
+            item.content_type
+                .parse()
+                .unwrap_or_else(|_| "application/octet-stream".parse().unwrap()),
+        );
+        Ok(response)
+    } else {
+        warn!("found gallery item but missing image data (gallery_id: {gallery_id})");
+        Err(ApiError::not_found("gallery image data not found").with_details(gallery_id))
+    }
+}
+
+/// Publishes a render, sent as the request body with its image content type.
+#[utoipa::path(
+    post,
+    path = "/api/v1/gallery",
+    params(PublishGalleryItemQuery),
+    request_body(content = Vec<u8>, content_type = "image/png"),
+    responses(
+        (status = OK, body = GalleryItem),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = GALLERY_TAG
+)]
+pub async fn publish_gallery_item(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Query(query): Query<PublishGalleryItemQuery>,
+    headers: HeaderMap,
+    image: Bytes,
+) -> Result<Json<GalleryItem>, ApiError> {
+    info!(
+        "publishing gallery item (project id: {}, user_id: {})",
+        query.project_id, user.user_id
+    );
+
+    let title = query.title.trim();
+    if title.is_empty() || title.len() > MAX_TITLE_LENGTH {
+        return Err(ApiError::bad_request(format!(
+            "title must be 1 to {MAX_TITLE_LENGTH} characters"
+        )));
+    }
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    if !IMAGE_CONTENT_TYPES.contains(&content_type) {
+        return Err(
+            ApiError::bad_request("render must be a PNG, JPEG or WebP image")
+                .with_details(content_type),
+        );
+    }
+    if image.is_empty() {
+        return Err(ApiError::bad_request("render image is empty"));
+    }
+
+    let user_data = assert_load_user_data(&state.user_repository, &user).await?;
+    assert_load_project_owner(&state.project_service, &query.project_id, &Some(user)).await?;
+
+    let item = GalleryItem {
+        id: Uuid::new_v4().to_string(),
+        project_id: query.project_id,
+        owner_user_id: user_data.user_id,
+        title: title.to_owned(),
+        content_type: content_type.to_owned(),
+        hidden: false,
+        created: Utc::now(),
+    };
+    state
+        .gallery_repository
+        .insert(&item, &image)
+        .await
+        .map_err(|err| {
+            error!("failed to save gallery item: {err:?}");
+            ApiError::internal_server_error("failed to save gallery item")
+        })?;
+
+    Ok(Json(item))
+}
+
+/// Removes an item from the gallery, by its owner or a moderator.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/gallery/{gallery_id}",
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = GALLERY_TAG
+)]
+pub async fn unpublish_gallery_item(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(gallery_id): Path<String>,
+) -> Result<(), ApiError> {
+    info!(
+        "unpublishing gallery item (gallery id: {gallery_id}, user_id: {})",
+        user.user_id
+    );
+
+    let item = assert_load_gallery_item(&state, &gallery_id, &Some(user.clone())).await?;
+    if item.owner_user_id != user.user_id && !is_moderator(&state, &user) {
+        return Err(
+            ApiError::unauthorized("only the owner may unpublish a gallery item")
+                .with_details(gallery_id),
+        );
+    }
+
+    state
+        .gallery_repository
+        .delete(&gallery_id)
+        .await
+        .map_err(|err| {
+            error!("failed to delete gallery item: {err:?}");
+            ApiError::internal_server_error("failed to unpublish gallery item")
+        })
+}
+
+/// Reports an item for moderation. Items reported by enough users are hidden
+/// until a moderator restores them.
+#[utoipa::path(
+    post,
+    path = "/api/v1/gallery/{gallery_id}/report",
+    responses(
+        (status = OK),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = GALLERY_TAG
+)]
+pub async fn report_gallery_item(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(gallery_id): Path<String>,
+    Json(payload): Json<ReportGalleryItemRequest>,
+) -> Result<(), ApiError> {
+    if payload.reason.len() > MAX_REASON_LENGTH {
+        return Err(ApiError::bad_request(format!(
+            "reason must be at most {MAX_REASON_LENGTH} characters"
+        )));
+    }
+
+    let user_data = assert_load_user_data(&state.user_repository, &user).await?;
+    let item = assert_load_gallery_item(&state, &gallery_id, &Some(user)).await?;
+
+    let reports = state
+        .gallery_repository
+        .insert_report(
+            &gallery_id,
+            &user_data.user_id,
+            &payload.reason,
+            &Utc::now(),
+        )
+        .await
+        .map_err(|err| {
+            error!("failed to save gallery report: {err:?}");
+            ApiError::internal_server_error("failed to report gallery item")
+        })?;
+    warn!(
+        "gallery item reported (gallery id: {gallery_id}, user_id: {}, reports: {reports}): {}",
+        user_data.user_id, payload.reason
+    );
+
+    if reports >= REPORTS_TO_HIDE && !item.hidden {
+        info!("hiding reported gallery item (gallery id: {gallery_id})");
+        state
+            .gallery_repository
+            .set_hidden(&gallery_id, true)
+            .await
+            .map_err(|err| {
+                error!("failed to hide gallery item: {err:?}");
+                ApiError::internal_server_error("failed to report gallery item")
+            })?;
+    }
+
+    Ok(())
+}
+
+/// Hides or restores an item, by a moderator. Restoring an item dismisses its
+/// reports.
+#[utoipa::path(
+    post,
+    path = "/api/v1/gallery/{gallery_id}/moderate",
+    responses(
+        (status = OK, body = GalleryItem),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = GALLERY_TAG
+)]
+pub async fn moderate_gallery_item(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path(gallery_id): Path<String>,
+    Json(payload): Json<ModerateGalleryItemRequest>,
+) -> Result<Json<GalleryItem>, ApiError> {
+    if !is_moderator(&state, &user) {
+        return Err(ApiError::unauthorized(
+            "only moderators may moderate the gallery",
+        ));
+    }
+
+    info!(
+        "moderating gallery item (gallery id: {gallery_id}, hidden: {}, user_id: {})",
+        payload.hidden, user.user_id
+    );
+
+    let mut item = assert_load_gallery_item(&state, &gallery_id, &Some(user)).await?;
+    state
+        .gallery_repository
+        .set_hidden(&gallery_id, payload.hidden)
+        .await
+        .map_err(|err| {
+            error!("failed to update gallery item: {err:?}");
+            ApiError::internal_server_error("failed to moderate gallery item")
+        })?;
+    if !payload.hidden {
+        state
+            .gallery_repository
+            .delete_reports(&gallery_id)
+            .await
+            .map_err(|err| {
+                error!("failed to dismiss gallery reports: {err:?}");
+                ApiError::internal_server_error("failed to moderate gallery item")
+            })?;
+    }
+    item.hidden = payload.hidden;
+
+    Ok(Json(item))
+}
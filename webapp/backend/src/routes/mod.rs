@@ -1,2 +1,8 @@
+pub mod asset_upload_routes;
+pub mod error;
+pub mod extract;
+pub mod gallery_routes;
+pub mod preview_routes;
 pub mod project_routes;
+pub mod render_preset_routes;
 pub mod user_routes;
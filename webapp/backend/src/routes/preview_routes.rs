@@ -0,0 +1,130 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, header},
+    response::Response,
+};
+use log::{error, info};
+
+use crate::{
+    PREVIEW_TAG,
+    routes::error::ApiError,
+    services::preview_service::{MAX_PREVIEW_SOURCE_SIZE, PreviewResult},
+    state::AppState,
+};
+
+/// Renders a small preview of an OpenSCAD scene sent as the request body,
+/// without signing in, so visitors can try the ray tracer. Previews are
+/// rate limited by client address and rendered within strict limits.
+#[utoipa::path(
+    post,
+    path = "/api/v1/try",
+    request_body(content = String, content_type = "text/plain"),
+    responses(
+        (status = OK, content_type = "image/png"),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = TOO_MANY_REQUESTS, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PREVIEW_TAG
+)]
+pub async fn try_scene(
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    if body.len() > MAX_PREVIEW_SOURCE_SIZE {
+        return Err(
+            ApiError::bad_request("scene too large").with_details(format!(
+                "scenes are limited to {MAX_PREVIEW_SOURCE_SIZE} bytes"
+            )),
+        );
+    }
+    let code = String::from_utf8(body.to_vec())
+        .map_err(|_| ApiError::bad_request("scene is not valid UTF-8"))?;
+
+    let client = client_address(&state, &headers, address);
+    let result = state
+        .preview_service
+        .render(client, code)
+        .await
+        .map_err(|err| {
+            error!("failed to render preview: {err:?}");
+            ApiError::internal_server_error("failed to render preview")
+        })?;
+
+    match result {
+        PreviewResult::Rendered(png) => {
+            let mut response = Response::new(Body::from(png));
+            response
+                .headers_mut()
+                .insert(header::CONTENT_TYPE, "image/png".parse().unwrap());
+            Ok(response)
+        }
+        PreviewResult::InvalidScene(errors) => {
+            Err(ApiError::bad_request("failed to interpret scene").with_details(errors.join("\n")))
+        }
+        PreviewResult::TimedOut => Err(ApiError::bad_request(
+            "scene took too long to render, sign in to render it in full",
+        )),
+        PreviewResult::Busy => Err(ApiError::too_many_requests(
+            "too many previews are rendering, try again shortly",
+        )),
+        PreviewResult::RateLimited => {
+            info!("preview rate limit reached (client: {client})");
+            Err(ApiError::too_many_requests(
+                "too many previews, sign in to keep rendering",
+            ))
+        }
+    }
+}
+
+/// Returns the address of the client, taken from `X-Forwarded-For` when the
+/// server runs behind trusted proxies.
+fn client_address(state: &AppState, headers: &HeaderMap, address: SocketAddr) -> IpAddr {
+    if state.settings.trust_forwarded_for
+        && let Some(forwarded) = headers
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| forwarded_client(value, state.settings.forwarded_for_hops))
+    {
+        return forwarded;
+    }
+    address.ip()
+}
+
+/// Returns the address `hops` entries from the right of an `X-Forwarded-For`
+/// value, the one appended by the outermost trusted proxy. Entries further
+/// left are sent by the client, which can make them up.
+fn forwarded_client(forwarded_for: &str, hops: usize) -> Option<IpAddr> {
+    let entries: Vec<&str> = forwarded_for.split(',').collect();
+    let index = entries.len().checked_sub(hops.max(1))?;
+    entries[index].trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::IpAddr;
+
+    use super::forwarded_client;
+
+    #[test]
+    fn forwarded_client_ignores_client_written_entries() {
+        let ip = |value: &str| Some(value.parse::<IpAddr>().unwrap());
+        // The client sent a made up address, the proxy appended the real one
+        assert_eq!(forwarded_client("1.2.3.4, 5.6.7.8", 1), ip("5.6.7.8"));
+        assert_eq!(
+            forwarded_client("1.2.3.4, 5.6.7.8, 10.0.0.1", 2),
+            ip("5.6.7.8")
+        );
+        assert_eq!(forwarded_client("5.6.7.8", 1), ip("5.6.7.8"));
+        assert_eq!(forwarded_client("5.6.7.8", 2), None);
+        assert_eq!(forwarded_client("1.2.3.4, not an address", 1), None);
+    }
+}
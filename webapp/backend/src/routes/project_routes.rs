@@ -1,27 +1,33 @@
 use std::sync::Arc;
 
 use axum::{
-    Json,
     body::Body,
-    extract::{Path, State},
+    extract::State,
     http::{HeaderValue, header},
     response::Response,
 };
 use chrono::Utc;
 use log::{error, info, warn};
-use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::routes::extract::{Json, Path};
 use crate::{
     PROJECT_TAG,
     repository::{
         project_repository::{CONTENT_TYPE_OPENSCAD, Project, ProjectFile},
         user_repository::{UserData, UserDataProject, UserRepository},
     },
-    routes::user_routes::{AuthUser, MaybeAuthUser},
-    services::project_service::{LoadProjectResult, ProjectService},
+    routes::{
+        error::ApiError,
+        user_routes::{AuthUser, MaybeAuthUser},
+    },
+    services::{
+        mesh_service::MeshResult,
+        project_service::{LoadProjectResult, ProjectService},
+        scene_service::PickResult,
+    },
     state::AppState,
 };
 
@@ -43,57 +49,87 @@ pub struct DeleteProjectRequest {
     project_id: String,
 }
 
+#[derive(ToSchema, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickRequest {
+    /// Normalized horizontal screen coordinate, 0.0 (left) to 1.0 (right)
+    x: f64,
+    /// Normalized vertical screen coordinate, 0.0 (top) to 1.0 (bottom)
+    y: f64,
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickResponse {
+    pub hit: bool,
+    /// Distance from the camera to the hit point
+    pub distance: Option<f64>,
+    /// Hit point in scene coordinates
+    pub point: Option<[f64; 3]>,
+    /// Surface normal at the hit point in scene coordinates
+    pub normal: Option<[f64; 3]>,
+    /// Type of the object that was hit
+    pub object: Option<String>,
+}
+
 #[derive(ToSchema, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetProjectsResponse {
     pub projects: Vec<UserDataProject>,
 }
 
-async fn assert_load_project(
+pub(crate) async fn assert_load_project(
     project_service: &ProjectService,
     project_id: &str,
     user: &Option<AuthUser>,
-) -> Result<Project, StatusCode> {
+) -> Result<Project, ApiError> {
     match project_service.load_project(project_id, user).await {
         Ok(project) => match project {
             LoadProjectResult::Project(project) => Ok(project),
-            LoadProjectResult::NotFound => Err(StatusCode::NOT_FOUND),
-            LoadProjectResult::AccessDenied => Err(StatusCode::UNAUTHORIZED),
+            LoadProjectResult::NotFound => {
+                Err(ApiError::not_found("project not found").with_details(project_id))
+            }
+            LoadProjectResult::AccessDenied => {
+                Err(ApiError::unauthorized("access to project denied").with_details(project_id))
+            }
         },
         Err(err) => {
             error!("failed to load project (project id: {project_id}): {err:?}");
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            Err(ApiError::internal_server_error("failed to load project"))
         }
     }
 }
 
-async fn assert_load_project_owner(
+pub(crate) async fn assert_load_project_owner(
     project_service: &ProjectService,
     project_id: &str,
     user: &Option<AuthUser>,
-) -> Result<Project, StatusCode> {
+) -> Result<Project, ApiError> {
     let project = assert_load_project(project_service, project_id, user).await?;
     if let Some(user) = user
         && project.owner_user_id == user.user_id
     {
         Ok(project)
     } else {
-        Err(StatusCode::UNAUTHORIZED)
+        Err(
+            ApiError::unauthorized("only the project owner may modify the project")
+                .with_details(project_id),
+        )
     }
 }
 
-async fn assert_load_user_data(
+pub(crate) async fn assert_load_user_data(
     user_repository: &UserRepository,
     user: &AuthUser,
-) -> Result<UserData, StatusCode> {
+) -> Result<UserData, ApiError> {
     user_repository
         .find_by_user_id(&user.user_id)
         .await
         .map_err(|err| {
             error!("failed to load user: {err:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::internal_server_error("failed to load user")
         })?
-        .ok_or(StatusCode::UNAUTHORIZED)
+        .ok_or_else(|| ApiError::unauthorized("user not found"))
 }
 
 #[utoipa::path(
@@ -101,15 +137,15 @@ async fn assert_load_user_data(
     path = "/api/v1/project",
     responses(
         (status = OK, body = GetProjectsResponse),
-        (status = UNAUTHORIZED),
-        (status = INTERNAL_SERVER_ERROR)
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
     ),
     tag = PROJECT_TAG
 )]
 pub async fn get_projects(
     State(state): State<Arc<AppState>>,
     user: MaybeAuthUser,
-) -> Result<Json<GetProjectsResponse>, StatusCode> {
+) -> Result<Json<GetProjectsResponse>, ApiError> {
     let mut projects: Vec<UserDataProject> = vec![];
 
     if let Some(user) = user.user {
@@ -119,7 +155,7 @@ pub async fn get_projects(
             .await
             .map_err(|err| {
                 error!("failed to load user data: {err:?}");
-                StatusCode::INTERNAL_SERVER_ERROR
+                ApiError::internal_server_error("failed to load user data")
             })?;
 
         if let Some(user_data) = user_data {
@@ -135,7 +171,7 @@ pub async fn get_projects(
         .await
         .map_err(|err| {
             error!("failed to load example projects: {err:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::internal_server_error("failed to load example projects")
         })?;
     for project in example_projects {
         projects.push(UserDataProject {
@@ -156,9 +192,9 @@ pub async fn get_projects(
     path = "/api/v1/project/{project_id}",
     responses(
         (status = OK, body = Project),
-        (status = NOT_FOUND),
-        (status = UNAUTHORIZED),
-        (status = INTERNAL_SERVER_ERROR)
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
     ),
     tag = PROJECT_TAG
 )]
@@ -166,7 +202,7 @@ pub async fn get_project(
     State(state): State<Arc<AppState>>,
     user: MaybeAuthUser,
     Path(project_id): Path<String>,
-) -> Result<Json<Project>, StatusCode> {
+) -> Result<Json<Project>, ApiError> {
     match assert_load_project(&state.project_service, &project_id, &user.user).await {
         Ok(project) => Ok(Json(project)),
         Err(err) => Err(err),
@@ -178,8 +214,9 @@ pub async fn get_project(
     path = "/api/v1/project/{project_id}/file/{filename}",
     responses(
         (status = OK, content_type = "application/octet-stream"),
-        (status = UNAUTHORIZED),
-        (status = INTERNAL_SERVER_ERROR)
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
     ),
     tag = PROJECT_TAG
 )]
@@ -187,13 +224,13 @@ pub async fn get_project_file(
     State(state): State<Arc<AppState>>,
     user: MaybeAuthUser,
     Path((project_id, filename)): Path<(String, String)>,
-) -> Result<Response, StatusCode> {
+) -> Result<Response, ApiError> {
     let project = assert_load_project(&state.project_service, &project_id, &user.user).await?;
     let project_file = project.files.iter().find(|f| f.filename == filename);
     let project_file = if let Some(project_file) = project_file {
         project_file
     } else {
-        return Err(StatusCode::NOT_FOUND);
+        return Err(ApiError::not_found("project file not found").with_details(filename));
     };
 
     let file_data = state
@@ -202,7 +239,7 @@ pub async fn get_project_file(
         .await
         .map_err(|err| {
             error!("failed to load project file: {err:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::internal_server_error("failed to load project file")
         })?;
 
     if let Some(file_data) = file_data {
@@ -213,7 +250,7 @@ pub async fn get_project_file(
             HeaderValue::from_str(&format!("attachment; filename=\"{}\"", filename)).map_err(
                 |err| {
                     error!("failed to parse header value: {err:?}");
-                    StatusCode::INTERNAL_SERVER_ERROR
+                    ApiError::internal_server_error("failed to build response headers")
                 },
             )?,
         );
@@ -231,7 +268,61 @@ pub async fn get_project_file(
         warn!(
             "found project file but missing file data (project_id: {project_id}, filename: {filename})"
         );
-        Err(StatusCode::NOT_FOUND)
+        Err(ApiError::not_found("project file data not found").with_details(filename))
+    }
+}
+
+/// Returns a mesh file of the project converted for rendering, see
+/// [`crate::services::mesh_service::MeshService`] for the format. Meshes are
+/// converted in the background once uploaded, and here if they were not yet.
+#[utoipa::path(
+    get,
+    path = "/api/v1/project/{project_id}/mesh/{filename}",
+    responses(
+        (status = OK, content_type = "application/octet-stream"),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn get_project_mesh(
+    State(state): State<Arc<AppState>>,
+    user: MaybeAuthUser,
+    Path((project_id, filename)): Path<(String, String)>,
+) -> Result<Response, ApiError> {
+    let project = assert_load_project(&state.project_service, &project_id, &user.user).await?;
+    if !project.files.iter().any(|f| f.filename == filename) {
+        return Err(ApiError::not_found("project file not found").with_details(filename));
+    }
+
+    let path = state
+        .project_repository
+        .project_file_path(&project_id, &filename);
+    let result = state
+        .mesh_service
+        .get_or_convert(&path, &filename)
+        .await
+        .map_err(|err| {
+            error!(
+                "failed to convert mesh (project_id: {project_id}, filename: {filename}): {err:?}"
+            );
+            ApiError::internal_server_error("failed to convert mesh")
+        })?;
+
+    match result {
+        MeshResult::Converted(mesh) => {
+            let mut response = Response::new(Body::from(mesh));
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/octet-stream"),
+            );
+            Ok(response)
+        }
+        MeshResult::Invalid(reason) => {
+            Err(ApiError::bad_request("failed to convert mesh").with_details(reason))
+        }
     }
 }
 
@@ -240,8 +331,8 @@ pub async fn get_project_file(
     path = "/api/v1/project",
     responses(
         (status = OK, body = Project),
-        (status = UNAUTHORIZED),
-        (status = INTERNAL_SERVER_ERROR)
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
     ),
     tag = PROJECT_TAG
 )]
@@ -249,7 +340,7 @@ pub async fn create_project(
     State(state): State<Arc<AppState>>,
     user: AuthUser,
     Json(payload): Json<CreateProjectRequest>,
-) -> Result<Json<Project>, StatusCode> {
+) -> Result<Json<Project>, ApiError> {
     let now = Utc::now();
 
     info!(
@@ -281,7 +372,7 @@ pub async fn create_project(
         .await
         .map_err(|err| {
             error!("failed to save project: {err:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::internal_server_error("failed to save project")
         })?;
 
     // create project file
@@ -304,7 +395,7 @@ pub async fn create_project(
         .await
         .map_err(|err| {
             error!("failed to save project file: {err:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::internal_server_error("failed to save project file")
         })?;
     project.files.push(file);
 
@@ -316,8 +407,9 @@ pub async fn create_project(
     path = "/api/v1/project/copy",
     responses(
         (status = OK, body = Project),
-        (status = UNAUTHORIZED),
-        (status = INTERNAL_SERVER_ERROR)
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
     ),
     tag = PROJECT_TAG
 )]
@@ -325,7 +417,7 @@ pub async fn delete_project(
     State(state): State<Arc<AppState>>,
     user: AuthUser,
     Json(payload): Json<DeleteProjectRequest>,
-) -> Result<(), StatusCode> {
+) -> Result<(), ApiError> {
     info!(
         "deleting project (project id: {}, user_id: {})",
         payload.project_id, user.user_id
@@ -334,13 +426,40 @@ pub async fn delete_project(
     assert_load_user_data(&state.user_repository, &user).await?;
     assert_load_project_owner(&state.project_service, &payload.project_id, &Some(user)).await?;
 
+    state
+        .gallery_repository
+        .delete_by_project_id(&payload.project_id)
+        .await
+        .map_err(|err| {
+            error!("failed to delete project gallery items: {err:?}");
+            ApiError::internal_server_error("failed to delete project")
+        })?;
+
+    state
+        .asset_upload_repository
+        .delete_by_project_id(&payload.project_id)
+        .await
+        .map_err(|err| {
+            error!("failed to delete project uploads: {err:?}");
+            ApiError::internal_server_error("failed to delete project")
+        })?;
+
+    state
+        .render_preset_repository
+        .delete_by_project_id(&payload.project_id)
+        .await
+        .map_err(|err| {
+            error!("failed to delete project render presets: {err:?}");
+            ApiError::internal_server_error("failed to delete project")
+        })?;
+
     state
         .project_repository
         .delete_project(&payload.project_id)
         .await
         .map_err(|err| {
             error!("failed to delete project files: {err:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::internal_server_error("failed to delete project")
         })?;
 
     Ok(())
@@ -351,8 +470,9 @@ pub async fn delete_project(
     path = "/api/v1/project/copy",
     responses(
         (status = OK, body = Project),
-        (status = UNAUTHORIZED),
-        (status = INTERNAL_SERVER_ERROR)
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
     ),
     tag = PROJECT_TAG
 )]
@@ -360,7 +480,7 @@ pub async fn copy_project(
     State(state): State<Arc<AppState>>,
     user: AuthUser,
     Json(payload): Json<CopyProjectRequest>,
-) -> Result<Json<Project>, StatusCode> {
+) -> Result<Json<Project>, ApiError> {
     let now = Utc::now();
 
     info!(
@@ -395,7 +515,7 @@ pub async fn copy_project(
         .await
         .map_err(|err| {
             error!("failed to load user: {err:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::internal_server_error("failed to save project")
         })?;
 
     for file in &existing_project.files {
@@ -408,7 +528,7 @@ pub async fn copy_project(
                     "failed to read existing project file (project_id: {}, filename: {}): {err:?}",
                     existing_project.id, file.filename
                 );
-                StatusCode::INTERNAL_SERVER_ERROR
+                ApiError::internal_server_error("failed to read existing project file")
             })?;
         let data = if let Some(data) = data {
             data
@@ -417,7 +537,10 @@ pub async fn copy_project(
                 "missing existing project file data (project_id: {}, filename: {})",
                 existing_project.id, file.filename
             );
-            return Err(StatusCode::INTERNAL_SERVER_ERROR);
+            return Err(
+                ApiError::internal_server_error("missing existing project file data")
+                    .with_details(file.filename.clone()),
+            );
         };
 
         state
@@ -436,7 +559,7 @@ pub async fn copy_project(
                     "failed to copy file (project_id: {}, filename: {}): {err:?}",
                     existing_project.id, file.filename
                 );
-                StatusCode::INTERNAL_SERVER_ERROR
+                ApiError::internal_server_error("failed to copy project file")
             })?;
         new_project.files.push(ProjectFile {
             filename: file.filename.to_owned(),
@@ -445,5 +568,92 @@ pub async fn copy_project(
         });
     }
 
+    let presets = state
+        .render_preset_repository
+        .find_by_project_id(&existing_project.id)
+        .await
+        .map_err(|err| {
+            error!(
+                "failed to read existing render presets (project_id: {}): {err:?}",
+                existing_project.id
+            );
+            ApiError::internal_server_error("failed to read existing render presets")
+        })?;
+    for preset in presets {
+        state
+            .render_preset_repository
+            .insert_or_update(&new_project.id, &preset, &now)
+            .await
+            .map_err(|err| {
+                error!(
+                    "failed to copy render preset (project_id: {}, name: {}): {err:?}",
+                    existing_project.id, preset.name
+                );
+                ApiError::internal_server_error("failed to copy render preset")
+            })?;
+    }
+
     Ok(Json(new_project))
 }
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/project/{project_id}/pick",
+    responses(
+        (status = OK, body = PickResponse),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn pick_project(
+    State(state): State<Arc<AppState>>,
+    user: MaybeAuthUser,
+    Path(project_id): Path<String>,
+    Json(payload): Json<PickRequest>,
+) -> Result<Json<PickResponse>, ApiError> {
+    let unit_interval = 0.0..=1.0;
+    if !unit_interval.contains(&payload.x) || !unit_interval.contains(&payload.y) {
+        return Err(ApiError::bad_request(
+            "pick coordinates must be between 0.0 and 1.0",
+        ));
+    }
+
+    let project = assert_load_project(&state.project_service, &project_id, &user.user).await?;
+
+    let result = state
+        .scene_service
+        .pick(&project, payload.x, payload.y)
+        .await
+        .map_err(|err| {
+            error!("failed to pick (project id: {project_id}): {err:?}");
+            ApiError::internal_server_error("failed to pick")
+        })?;
+
+    match result {
+        PickResult::Hit {
+            distance,
+            point,
+            normal,
+            object,
+        } => Ok(Json(PickResponse {
+            hit: true,
+            distance: Some(distance),
+            point: Some([point.x, point.y, point.z]),
+            normal: Some([normal.x, normal.y, normal.z]),
+            object: Some(object),
+        })),
+        PickResult::Miss => Ok(Json(PickResponse {
+            hit: false,
+            distance: None,
+            point: None,
+            normal: None,
+            object: None,
+        })),
+        PickResult::InvalidScene(errors) => {
+            Err(ApiError::bad_request("failed to interpret scene").with_details(errors.join("\n")))
+        }
+    }
+}
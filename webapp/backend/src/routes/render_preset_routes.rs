@@ -0,0 +1,503 @@
+use std::sync::Arc;
+
+use axum::extract::State;
+use chrono::Utc;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::routes::extract::{Json, Path};
+use crate::{
+    PROJECT_TAG,
+    repository::render_preset_repository::{RenderPreset, RenderPresetIntegrator},
+    routes::{
+        error::ApiError,
+        project_routes::{assert_load_project, assert_load_project_owner},
+        user_routes::{AuthUser, MaybeAuthUser},
+    },
+    state::AppState,
+};
+
+const MAX_PRESET_NAME_LENGTH: usize = 64;
+
+/// Most presets a project may have
+const MAX_PRESETS_PER_PROJECT: usize = 32;
+
+#[derive(ToSchema, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PutRenderPresetRequest {
+    /// Rendered image width in pixels, unset to keep the scene's camera setting
+    image_width: Option<u32>,
+    /// Rendered image height in pixels, unset to keep the scene's camera setting
+    image_height: Option<u32>,
+    /// Samples per pixel, unset to keep the scene's camera setting
+    samples_per_pixel: Option<u32>,
+    /// Maximum number of ray bounces, unset to keep the scene's camera setting
+    max_depth: Option<u32>,
+    #[serde(default)]
+    integrator: RenderPresetIntegrator,
+    #[serde(default)]
+    denoise: bool,
+}
+
+#[derive(ToSchema, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRenderPresetsResponse {
+    pub presets: Vec<RenderPreset>,
+}
+
+fn validate_render_preset(name: &str, payload: &PutRenderPresetRequest) -> Result<(), ApiError> {
+    if name.trim().is_empty() || name.len() > MAX_PRESET_NAME_LENGTH {
+        return Err(ApiError::bad_request(format!(
+            "preset name must be 1 to {MAX_PRESET_NAME_LENGTH} characters"
+        ))
+        .with_details(name));
+    }
+
+    for (field, value) in [
+        ("imageWidth", payload.image_width),
+        ("imageHeight", payload.image_height),
+        ("samplesPerPixel", payload.samples_per_pixel),
+        ("maxDepth", payload.max_depth),
+    ] {
+        if value == Some(0) {
+            return Err(
+                ApiError::bad_request("preset values must be greater than 0").with_details(field),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/project/{project_id}/preset",
+    responses(
+        (status = OK, body = GetRenderPresetsResponse),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn get_render_presets(
+    State(state): State<Arc<AppState>>,
+    user: MaybeAuthUser,
+    Path(project_id): Path<String>,
+) -> Result<Json<GetRenderPresetsResponse>, ApiError> {
+    assert_load_project(&state.project_service, &project_id, &user.user).await?;
+
+    let presets = state
+        .render_preset_repository
+        .find_by_project_id(&project_id)
+        .await
+        .map_err(|err| {
+            error!("failed to load render presets (project id: {project_id}): {err:?}");
+            ApiError::internal_server_error("failed to load render presets")
+        })?;
+
+    Ok(Json(GetRenderPresetsResponse { presets }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/project/{project_id}/preset/{name}",
+    responses(
+        (status = OK, body = RenderPreset),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn get_render_preset(
+    State(state): State<Arc<AppState>>,
+    user: MaybeAuthUser,
+    Path((project_id, name)): Path<(String, String)>,
+) -> Result<Json<RenderPreset>, ApiError> {
+    assert_load_project(&state.project_service, &project_id, &user.user).await?;
+
+    state
+        .render_preset_repository
+        .find_by_project_id_and_name(&project_id, &name)
+        .await
+        .map_err(|err| {
+            error!(
+                "failed to load render preset (project id: {project_id}, name: {name}): {err:?}"
+            );
+            ApiError::internal_server_error("failed to load render preset")
+        })?
+        .map(Json)
+        .ok_or_else(|| ApiError::not_found("render preset not found").with_details(name))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/project/{project_id}/preset/{name}",
+    responses(
+        (status = OK, body = RenderPreset),
+        (status = BAD_REQUEST, body = ApiError),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn put_render_preset(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((project_id, name)): Path<(String, String)>,
+    Json(payload): Json<PutRenderPresetRequest>,
+) -> Result<Json<RenderPreset>, ApiError> {
+    let now = Utc::now();
+
+    info!(
+        "saving render preset (project id: {project_id}, name: {name}, user_id: {})",
+        user.user_id
+    );
+
+    validate_render_preset(&name, &payload)?;
+    assert_load_project_owner(&state.project_service, &project_id, &Some(user)).await?;
+
+    let presets = state
+        .render_preset_repository
+        .find_by_project_id(&project_id)
+        .await
+        .map_err(|err| {
+            error!("failed to load render presets (project id: {project_id}): {err:?}");
+            ApiError::internal_server_error("failed to load render presets")
+        })?;
+    if presets.len() >= MAX_PRESETS_PER_PROJECT && !presets.iter().any(|p| p.name == name) {
+        return Err(ApiError::bad_request(format!(
+            "projects may have at most {MAX_PRESETS_PER_PROJECT} render presets"
+        ))
+        .with_details(name));
+    }
+
+    let preset = RenderPreset {
+        name,
+        image_width: payload.image_width,
+        image_height: payload.image_height,
+        samples_per_pixel: payload.samples_per_pixel,
+        max_depth: payload.max_depth,
+        integrator: payload.integrator,
+        denoise: payload.denoise,
+        last_modified: now,
+    };
+    state
+        .render_preset_repository
+        .insert_or_update(&project_id, &preset, &now)
+        .await
+        .map_err(|err| {
+            error!("failed to save render preset (project id: {project_id}): {err:?}");
+            ApiError::internal_server_error("failed to save render preset")
+        })?;
+
+    Ok(Json(preset))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/project/{project_id}/preset/{name}",
+    responses(
+        (status = OK),
+        (status = NOT_FOUND, body = ApiError),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)
+    ),
+    tag = PROJECT_TAG
+)]
+pub async fn delete_render_preset(
+    State(state): State<Arc<AppState>>,
+    user: AuthUser,
+    Path((project_id, name)): Path<(String, String)>,
+) -> Result<(), ApiError> {
+    info!(
+        "deleting render preset (project id: {project_id}, name: {name}, user_id: {})",
+        user.user_id
+    );
+
+    assert_load_project_owner(&state.project_service, &project_id, &Some(user)).await?;
+
+    let deleted = state
+        .render_preset_repository
+        .delete(&project_id, &name)
+        .await
+        .map_err(|err| {
+            error!("failed to delete render preset (project id: {project_id}): {err:?}");
+            ApiError::internal_server_error("failed to delete render preset")
+        })?;
+
+    if deleted {
+        Ok(())
+    } else {
+        Err(ApiError::not_found("render preset not found").with_details(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use axum::extract::State;
+    use chrono::Utc;
+    use serde_json::json;
+
+    use super::{
+        MAX_PRESETS_PER_PROJECT, PutRenderPresetRequest, delete_render_preset, get_render_preset,
+        get_render_presets, put_render_preset,
+    };
+    use crate::{
+        repository::{render_preset_repository::RenderPreset, user_repository::UserData},
+        routes::{
+            error::{ApiError, ApiErrorCode},
+            extract::{Json, Path},
+            user_routes::{AuthUser, MaybeAuthUser},
+        },
+        state::{AppState, AppStateSettings},
+    };
+
+    fn user(user_id: &str) -> AuthUser {
+        AuthUser {
+            user_id: user_id.to_owned(),
+            email: format!("{user_id}@example.com"),
+            name: user_id.to_owned(),
+            picture: None,
+        }
+    }
+
+    /// Returns a state with an empty database and data directory, a signed
+    /// in user and a project they own.
+    async fn setup(name: &str) -> (Arc<AppState>, AuthUser, String) {
+        let data_path = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&data_path);
+        fs::create_dir_all(&data_path).unwrap();
+        let state = AppState::new_with_settings(AppStateSettings {
+            google_client_id: String::new(),
+            google_client_secret: String::new(),
+            google_redirect_url: String::new(),
+            jwt_secret: "secret".to_owned(),
+            bind: String::new(),
+            jwt_expire_duration_hours: 1,
+            sqlite_connection_string: format!("sqlite://{}", data_path.join("db.sqlite").display()),
+            data_path,
+            gallery_moderator_user_ids: vec![],
+            trust_forwarded_for: false,
+            forwarded_for_hops: 1,
+        })
+        .await
+        .unwrap();
+        let state = Arc::new(state);
+
+        let owner = user("owner");
+        state
+            .user_repository
+            .create(&UserData {
+                user_id: owner.user_id.clone(),
+                email: owner.email.clone(),
+                projects: vec![],
+                created: Utc::now(),
+            })
+            .await
+            .unwrap();
+        let project_id = "presets".to_owned();
+        let now = Utc::now();
+        state
+            .project_repository
+            .insert_or_update_project(&project_id, "presets", &owner.user_id, &now, &now)
+            .await
+            .unwrap();
+        (state, owner, project_id)
+    }
+
+    async fn put(
+        state: &Arc<AppState>,
+        user: &AuthUser,
+        project_id: &str,
+        name: &str,
+        payload: serde_json::Value,
+    ) -> Result<RenderPreset, ApiError> {
+        let payload: PutRenderPresetRequest = serde_json::from_value(payload).unwrap();
+        put_render_preset(
+            State(state.clone()),
+            user.clone(),
+            Path((project_id.to_owned(), name.to_owned())),
+            Json(payload),
+        )
+        .await
+        .map(|Json(preset)| preset)
+    }
+
+    async fn get(
+        state: &Arc<AppState>,
+        user: Option<&AuthUser>,
+        project_id: &str,
+        name: &str,
+    ) -> Result<RenderPreset, ApiError> {
+        get_render_preset(
+            State(state.clone()),
+            MaybeAuthUser {
+                user: user.cloned(),
+            },
+            Path((project_id.to_owned(), name.to_owned())),
+        )
+        .await
+        .map(|Json(preset)| preset)
+    }
+
+    #[tokio::test]
+    async fn creates_updates_and_deletes_presets() {
+        let (state, owner, project_id) = setup("caustic-presets-crud").await;
+
+        let preset = put(
+            &state,
+            &owner,
+            &project_id,
+            "final",
+            json!({ "imageWidth": 1920, "samplesPerPixel": 500, "denoise": true }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(preset.image_width, Some(1920));
+        put(
+            &state,
+            &owner,
+            &project_id,
+            "draft",
+            json!({ "maxDepth": 4 }),
+        )
+        .await
+        .unwrap();
+
+        // Saving under the same name updates the preset
+        put(
+            &state,
+            &owner,
+            &project_id,
+            "final",
+            json!({ "imageWidth": 3840 }),
+        )
+        .await
+        .unwrap();
+        let preset = get(&state, Some(&owner), &project_id, "final")
+            .await
+            .unwrap();
+        assert_eq!(preset.image_width, Some(3840));
+        assert_eq!(preset.samples_per_pixel, None);
+        assert!(!preset.denoise);
+
+        let Json(presets) = get_render_presets(
+            State(state.clone()),
+            MaybeAuthUser {
+                user: Some(owner.clone()),
+            },
+            Path(project_id.clone()),
+        )
+        .await
+        .unwrap();
+        let names: Vec<&str> = presets.presets.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["draft", "final"]);
+
+        delete_render_preset(
+            State(state.clone()),
+            owner.clone(),
+            Path((project_id.clone(), "final".to_owned())),
+        )
+        .await
+        .unwrap();
+        let err = get(&state, Some(&owner), &project_id, "final")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::NotFound);
+        let err = delete_render_preset(
+            State(state.clone()),
+            owner.clone(),
+            Path((project_id.clone(), "final".to_owned())),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::NotFound);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_presets() {
+        let (state, owner, project_id) = setup("caustic-presets-invalid").await;
+
+        let err = put(&state, &owner, &project_id, " ", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::BadRequest);
+        let err = put(
+            &state,
+            &owner,
+            &project_id,
+            "zero",
+            json!({ "samplesPerPixel": 0 }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::BadRequest);
+
+        for i in 0..MAX_PRESETS_PER_PROJECT {
+            put(
+                &state,
+                &owner,
+                &project_id,
+                &format!("preset {i}"),
+                json!({}),
+            )
+            .await
+            .unwrap();
+        }
+        let err = put(&state, &owner, &project_id, "one too many", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::BadRequest);
+        // Existing presets can still be updated
+        put(
+            &state,
+            &owner,
+            &project_id,
+            "preset 0",
+            json!({ "maxDepth": 8 }),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_the_owner_may_change_presets() {
+        let (state, owner, project_id) = setup("caustic-presets-authorization").await;
+        put(&state, &owner, &project_id, "final", json!({}))
+            .await
+            .unwrap();
+
+        let other = user("other");
+        let err = put(&state, &other, &project_id, "final", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::Unauthorized);
+        let err = delete_render_preset(
+            State(state.clone()),
+            other.clone(),
+            Path((project_id.clone(), "final".to_owned())),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::Unauthorized);
+
+        // Presets of unpublished projects are private
+        let err = get(&state, Some(&other), &project_id, "final")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::Unauthorized);
+        let err = get(&state, None, &project_id, "final").await.unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::Unauthorized);
+
+        let err = put(&state, &owner, "missing", "final", json!({}))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ApiErrorCode::NotFound);
+    }
+}
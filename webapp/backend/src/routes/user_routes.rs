@@ -1,14 +1,15 @@
 use std::sync::Arc;
 
-use axum::{Json, extract::State};
+use axum::extract::State;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::routes::error::ApiError;
+use crate::routes::extract::Json;
 use crate::utils::google::google_verify_token;
 use crate::{AppState, USER_TAG};
 
 use axum::extract::FromRequestParts;
-use reqwest::StatusCode;
 
 use axum::http::request::Parts;
 use jsonwebtoken::{DecodingKey, Validation, decode};
@@ -58,7 +59,7 @@ impl AuthUser {
 }
 
 impl FromRequestParts<Arc<AppState>> for AuthUser {
-    type Rejection = StatusCode;
+    type Rejection = ApiError;
 
     async fn from_request_parts(
         parts: &mut Parts,
@@ -69,11 +70,13 @@ impl FromRequestParts<Arc<AppState>> for AuthUser {
             .headers
             .get("Authorization")
             .and_then(|h| h.to_str().ok())
-            .ok_or(StatusCode::UNAUTHORIZED)?;
+            .ok_or_else(|| ApiError::unauthorized("missing Authorization header"))?;
 
         // Check for Bearer token
         if !auth_header.starts_with("Bearer ") {
-            return Err(StatusCode::UNAUTHORIZED);
+            return Err(ApiError::unauthorized(
+                "Authorization header must be a Bearer token",
+            ));
         }
 
         let token = &auth_header[7..];
@@ -84,7 +87,7 @@ impl FromRequestParts<Arc<AppState>> for AuthUser {
             &DecodingKey::from_secret(state.settings.jwt_secret.as_bytes()),
             &Validation::default(),
         )
-        .map_err(|_| StatusCode::UNAUTHORIZED)?;
+        .map_err(|_| ApiError::unauthorized("invalid or expired token"))?;
 
         Ok(AuthUser::from_claims(&token_data.claims))
     }
@@ -167,17 +170,17 @@ fn generate_jwt(claims: &Claims, secret: &str) -> Result<String, jsonwebtoken::e
     path = "/api/v1/user/google/verify",
     responses(
         (status = OK, body = AuthResponse),
-        (status = UNAUTHORIZED),
-        (status = INTERNAL_SERVER_ERROR)),
+        (status = UNAUTHORIZED, body = ApiError),
+        (status = INTERNAL_SERVER_ERROR, body = ApiError)),
     tag = USER_TAG
 )]
 pub async fn google_token_verify(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<GoogleVerifyRequest>,
-) -> Result<Json<AuthResponse>, StatusCode> {
+) -> Result<Json<AuthResponse>, ApiError> {
     let info = google_verify_token(&payload.token).await.map_err(|err| {
         error!("failed to verify token: {err:?}");
-        StatusCode::UNAUTHORIZED
+        ApiError::unauthorized("failed to verify token")
     })?;
 
     let exp_duration = chrono::Duration::hours(state.settings.jwt_expire_duration_hours as i64);
@@ -195,7 +198,7 @@ pub async fn google_token_verify(
 
     let jwt_token = generate_jwt(&claims, &state.settings.jwt_secret).map_err(|err| {
         error!("failed to generate jwt: {err:?}");
-        StatusCode::INTERNAL_SERVER_ERROR
+        ApiError::internal_server_error("failed to generate token")
     })?;
 
     let auth_user = AuthUser::from_claims(&claims);
@@ -205,7 +208,7 @@ pub async fn google_token_verify(
         .await
         .map_err(|err| {
             error!("failed to load or create user: {err:?}");
-            StatusCode::INTERNAL_SERVER_ERROR
+            ApiError::internal_server_error("failed to load or create user")
         })?;
 
     Ok(Json(AuthResponse {
@@ -0,0 +1,713 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use caustic_core::{Vector3, object::ConvertedMesh};
+use log::info;
+use sha2::{Digest, Sha256};
+use tokio::{fs, sync::Semaphore};
+
+/// Most triangles in a leaf of the bounding volume hierarchy
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+/// Most triangles accepted in an uploaded mesh
+const MAX_TRIANGLES: usize = 20_000_000;
+
+/// Meshes converted at once, others wait for their turn
+const MAX_CONCURRENT_CONVERSIONS: usize = 2;
+
+pub enum MeshResult {
+    /// The converted mesh, see [`MeshService`] for the format
+    Converted(Vec<u8>),
+    /// The file is not a mesh which can be rendered, contains the reason
+    Invalid(String),
+}
+
+/// Converts uploaded STL and OBJ meshes to a binary format which loads
+/// without any processing: vertices are welded, degenerate triangles dropped,
+/// windings made consistent and facing out, and a bounding volume hierarchy
+/// built. Converted meshes are cached by the SHA-256 of the uploaded file, so
+/// a mesh is processed once however many projects and renders use it. Scenes
+/// load them with `import()`, through [`ConvertedMesh`].
+///
+/// The format is little endian:
+///
+/// - magic `CAUMESH1`
+/// - vertex count, triangle count and node count as `u32`
+/// - vertices as three `f64`, in the coordinates of the uploaded file
+/// - triangles as three `u32` vertex indices, counter clockwise seen from
+///   outside, ordered so each leaf node covers a contiguous range
+/// - nodes as a minimum and maximum corner of three `f64` each, then `first`
+///   and `count` as `u32`. Leaves have a nonzero `count` and cover triangles
+///   `first..first + count`; other nodes have their first child right after
+///   them and their second child at `first`.
+pub struct MeshService {
+    cache_path: PathBuf,
+    conversions: Semaphore,
+}
+
+impl MeshService {
+    pub fn new(data_path: &Path) -> Self {
+        Self {
+            cache_path: data_path.join("mesh_cache"),
+            conversions: Semaphore::new(MAX_CONCURRENT_CONVERSIONS),
+        }
+    }
+
+    /// Returns true if `filename` is a mesh format which can be converted.
+    pub fn is_mesh(filename: &str) -> bool {
+        mesh_format(filename).is_some()
+    }
+
+    fn cached_mesh_path(&self, sha256: &str) -> PathBuf {
+        self.cache_path.join(format!("{sha256}.mesh"))
+    }
+
+    /// Returns the converted mesh of the file at `path`, converting it unless a
+    /// file with the same contents was converted before.
+    pub async fn get_or_convert(&self, path: &Path, filename: &str) -> Result<MeshResult> {
+        let Some(format) = mesh_format(filename) else {
+            return Ok(MeshResult::Invalid(format!(
+                "{filename} is not an STL or OBJ file"
+            )));
+        };
+        let data = fs::read(path)
+            .await
+            .with_context(|| format!("loading file {path:?}"))?;
+        let sha256 = hex::encode(Sha256::digest(&data));
+
+        let cached_path = self.cached_mesh_path(&sha256);
+        if let Some(mesh) = read_cached_mesh(&cached_path).await? {
+            return Ok(MeshResult::Converted(mesh));
+        }
+
+        let _permit = self.conversions.acquire().await?;
+        // The same file may have been converted while waiting
+        if let Some(mesh) = read_cached_mesh(&cached_path).await? {
+            return Ok(MeshResult::Converted(mesh));
+        }
+        let filename = filename.to_owned();
+        let converted = tokio::task::spawn_blocking(move || {
+            let mesh = match format {
+                MeshFormat::Stl => parse_stl(&data),
+                MeshFormat::Obj => parse_obj(&data),
+            }?;
+            let (mesh, report) = repair(mesh)?;
+            info!(
+                "converted mesh {filename} (sha256: {sha256}, triangles: {}, dropped: {}, flipped: {})",
+                mesh.triangles.len(),
+                report.dropped,
+                report.flipped
+            );
+            Ok::<_, String>(encode(&build_hierarchy(mesh)))
+        })
+        .await?;
+        let mesh = match converted {
+            Ok(mesh) => mesh,
+            Err(reason) => return Ok(MeshResult::Invalid(reason)),
+        };
+
+        fs::create_dir_all(&self.cache_path)
+            .await
+            .with_context(|| {
+                format!("saving file {:?} (could not create path)", self.cache_path)
+            })?;
+        // Written under another name first so a crash never leaves half a mesh
+        let partial_path = cached_path.with_extension("mesh.partial");
+        fs::write(&partial_path, &mesh)
+            .await
+            .with_context(|| format!("saving file {partial_path:?}"))?;
+        fs::rename(&partial_path, &cached_path)
+            .await
+            .with_context(|| format!("moving file {partial_path:?} to {cached_path:?}"))?;
+
+        Ok(MeshResult::Converted(mesh))
+    }
+}
+
+async fn read_cached_mesh(cached_path: &Path) -> Result<Option<Vec<u8>>> {
+    if !fs::try_exists(cached_path).await? {
+        return Ok(None);
+    }
+    let mesh = fs::read(cached_path)
+        .await
+        .with_context(|| format!("loading file {cached_path:?}"))?;
+    Ok(Some(mesh))
+}
+
+#[derive(Debug, Clone, Copy)]
+enum MeshFormat {
+    Stl,
+    Obj,
+}
+
+fn mesh_format(filename: &str) -> Option<MeshFormat> {
+    let extension = Path::new(filename).extension()?.to_str()?;
+    if extension.eq_ignore_ascii_case("stl") {
+        Some(MeshFormat::Stl)
+    } else if extension.eq_ignore_ascii_case("obj") {
+        Some(MeshFormat::Obj)
+    } else {
+        None
+    }
+}
+
+#[derive(Default)]
+struct Mesh {
+    vertices: Vec<Vector3>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl Mesh {
+    fn push_vertex(&mut self, vertex: Vector3) -> Result<(), String> {
+        if [vertex.x, vertex.y, vertex.z]
+            .iter()
+            .any(|v| !v.is_finite())
+        {
+            return Err(format!(
+                "vertex {} is not a finite number",
+                self.vertices.len()
+            ));
+        }
+        self.vertices.push(vertex);
+        Ok(())
+    }
+
+    fn push_triangle(&mut self, triangle: [u32; 3]) -> Result<(), String> {
+        if self.triangles.len() >= MAX_TRIANGLES {
+            return Err(format!("meshes may have at most {MAX_TRIANGLES} triangles"));
+        }
+        self.triangles.push(triangle);
+        Ok(())
+    }
+}
+
+fn parse_stl(data: &[u8]) -> Result<Mesh, String> {
+    // ASCII files start with "solid", but so do the headers of some binary
+    // files, whose size gives them away
+    let binary_size = data
+        .get(80..84)
+        .map(|count| 84 + 50 * u32::from_le_bytes(count.try_into().unwrap()) as usize);
+    if binary_size == Some(data.len()) || !data.starts_with(b"solid") {
+        parse_binary_stl(data)
+    } else {
+        parse_ascii_stl(data)
+    }
+}
+
+fn parse_binary_stl(data: &[u8]) -> Result<Mesh, String> {
+    let count = data
+        .get(80..84)
+        .map(|count| u32::from_le_bytes(count.try_into().unwrap()) as usize)
+        .ok_or("STL file is too short")?;
+    if data.len() < 84 + 50 * count {
+        return Err(format!("STL file is too short for {count} triangles"));
+    }
+
+    let mut mesh = Mesh::default();
+    for facet in data[84..84 + 50 * count].chunks_exact(50) {
+        // Skip the normal, it is computed from the winding
+        let mut corners = [0; 3];
+        for (corner, vertex) in corners.iter_mut().zip(facet[12..48].chunks_exact(12)) {
+            let coordinate =
+                |i: usize| f32::from_le_bytes(vertex[i * 4..i * 4 + 4].try_into().unwrap()) as f64;
+            *corner = mesh.vertices.len() as u32;
+            mesh.push_vertex(Vector3::new(coordinate(0), coordinate(1), coordinate(2)))?;
+        }
+        mesh.push_triangle(corners)?;
+    }
+    Ok(mesh)
+}
+
+fn parse_ascii_stl(data: &[u8]) -> Result<Mesh, String> {
+    let text = std::str::from_utf8(data).map_err(|_| "STL file is not valid text")?;
+    let mut mesh = Mesh::default();
+    let mut corners = Vec::with_capacity(3);
+    for (line_number, line) in text.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("vertex") => {
+                let vertex = parse_vertex(words)
+                    .ok_or_else(|| format!("line {}: invalid vertex", line_number + 1))?;
+                corners.push(mesh.vertices.len() as u32);
+                mesh.push_vertex(vertex)?;
+            }
+            Some("endloop") => {
+                if corners.len() != 3 {
+                    return Err(format!(
+                        "line {}: facets must have 3 vertices, found {}",
+                        line_number + 1,
+                        corners.len()
+                    ));
+                }
+                mesh.push_triangle([corners[0], corners[1], corners[2]])?;
+                corners.clear();
+            }
+            _ => {}
+        }
+    }
+    Ok(mesh)
+}
+
+fn parse_obj(data: &[u8]) -> Result<Mesh, String> {
+    let text = String::from_utf8_lossy(data);
+    let mut mesh = Mesh::default();
+    for (line_number, line) in text.lines().enumerate() {
+        let error = |message: &str| format!("line {}: {message}", line_number + 1);
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("v") => {
+                let vertex = parse_vertex(words).ok_or_else(|| error("invalid vertex"))?;
+                mesh.push_vertex(vertex)?;
+            }
+            Some("f") => {
+                // Faces refer to vertices from 1, or back from the last one
+                // read when negative, and may carry texture and normal indices
+                let corners = words
+                    .map(|word| {
+                        let index: i64 = word.split('/').next()?.parse().ok()?;
+                        let count = mesh.vertices.len() as i64;
+                        let index = if index < 0 { count + index } else { index - 1 };
+                        (0..count).contains(&index).then_some(index as u32)
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(|| error("face refers to a missing vertex"))?;
+                if corners.len() < 3 {
+                    return Err(error("faces must have at least 3 vertices"));
+                }
+                for i in 1..corners.len() - 1 {
+                    mesh.push_triangle([corners[0], corners[i], corners[i + 1]])?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(mesh)
+}
+
+fn parse_vertex<'a>(mut words: impl Iterator<Item = &'a str>) -> Option<Vector3> {
+    let mut coordinate = || words.next()?.parse().ok();
+    Some(Vector3::new(coordinate()?, coordinate()?, coordinate()?))
+}
+
+struct RepairReport {
+    /// Degenerate triangles left out
+    dropped: usize,
+    /// Triangles whose winding was reversed
+    flipped: usize,
+}
+
+/// Welds vertices at the same position, drops degenerate triangles and makes
+/// the winding of each connected part consistent and facing out.
+fn repair(mesh: Mesh) -> Result<(Mesh, RepairReport), String> {
+    // Weld, STL files repeat every vertex for each triangle using it
+    let mut welded = Mesh::default();
+    let mut vertex_ids = HashMap::new();
+    let remap: Vec<u32> = mesh
+        .vertices
+        .iter()
+        .map(|vertex| {
+            // Adding 0.0 turns -0.0 into 0.0 so they weld
+            let key = [vertex.x, vertex.y, vertex.z].map(|v| (v + 0.0).to_bits());
+            *vertex_ids.entry(key).or_insert_with(|| {
+                welded.vertices.push(*vertex);
+                welded.vertices.len() as u32 - 1
+            })
+        })
+        .collect();
+
+    let mut dropped = 0;
+    for triangle in &mesh.triangles {
+        let triangle = triangle.map(|i| remap[i as usize]);
+        let [a, b, c] = triangle.map(|i| welded.vertices[i as usize]);
+        if triangle[0] == triangle[1]
+            || triangle[1] == triangle[2]
+            || triangle[0] == triangle[2]
+            || (b - a).cross(&(c - a)).length_squared() == 0.0
+        {
+            dropped += 1;
+            continue;
+        }
+        welded.triangles.push(triangle);
+    }
+    if welded.triangles.is_empty() {
+        return Err("mesh has no triangles with an area".to_owned());
+    }
+
+    let flipped = orient(&mut welded);
+    Ok((welded, RepairReport { dropped, flipped }))
+}
+
+/// Reverses triangles so neighbors sharing an edge wind the same way, then
+/// reverses whole parts enclosing a negative volume so they face out. Returns
+/// the number of triangles reversed.
+fn orient(mesh: &mut Mesh) -> usize {
+    // Triangles using each edge, and whether they use it from the lower to
+    // the higher vertex index
+    let mut edges: HashMap<(u32, u32), Vec<(usize, bool)>> = HashMap::new();
+    for (index, triangle) in mesh.triangles.iter().enumerate() {
+        for (from, to) in triangle_edges(triangle) {
+            edges
+                .entry((from.min(to), from.max(to)))
+                .or_default()
+                .push((index, from < to));
+        }
+    }
+
+    let mut reverse = vec![false; mesh.triangles.len()];
+    let mut visited = vec![false; mesh.triangles.len()];
+    for start in 0..mesh.triangles.len() {
+        if visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut part = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(index) = queue.pop_front() {
+            for (from, to) in triangle_edges(&mesh.triangles[index]) {
+                let forward = (from < to) != reverse[index];
+                for &(neighbor, neighbor_forward) in &edges[&(from.min(to), from.max(to))] {
+                    if visited[neighbor] {
+                        continue;
+                    }
+                    visited[neighbor] = true;
+                    // Consistent neighbors use a shared edge in opposite directions
+                    reverse[neighbor] = neighbor_forward == forward;
+                    part.push(neighbor);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+
+        let volume: f64 = part
+            .iter()
+            .map(|&index| {
+                let [a, b, c] = mesh.triangles[index].map(|i| mesh.vertices[i as usize]);
+                let volume = a.dot(&b.cross(&c));
+                if reverse[index] { -volume } else { volume }
+            })
+            .sum();
+        if volume < 0.0 {
+            for &index in &part {
+                reverse[index] = !reverse[index];
+            }
+        }
+    }
+
+    let mut flipped = 0;
+    for (triangle, reverse) in mesh.triangles.iter_mut().zip(reverse) {
+        if reverse {
+            triangle.swap(1, 2);
+            flipped += 1;
+        }
+    }
+    flipped
+}
+
+fn triangle_edges(triangle: &[u32; 3]) -> [(u32, u32); 3] {
+    [
+        (triangle[0], triangle[1]),
+        (triangle[1], triangle[2]),
+        (triangle[2], triangle[0]),
+    ]
+}
+
+struct HierarchyNode {
+    min: [f64; 3],
+    max: [f64; 3],
+    first: u32,
+    count: u32,
+}
+
+struct MeshHierarchy {
+    mesh: Mesh,
+    nodes: Vec<HierarchyNode>,
+}
+
+/// Builds a bounding volume hierarchy over the triangles, splitting at the
+/// median centroid along the longest axis, and reorders the triangles to
+/// match its leaves.
+fn build_hierarchy(mut mesh: Mesh) -> MeshHierarchy {
+    let bounds: Vec<([f64; 3], [f64; 3])> = mesh
+        .triangles
+        .iter()
+        .map(|triangle| {
+            let corners = triangle.map(|i| {
+                let vertex = mesh.vertices[i as usize];
+                [vertex.x, vertex.y, vertex.z]
+            });
+            let min = std::array::from_fn(|axis| {
+                corners
+                    .iter()
+                    .map(|c| c[axis])
+                    .fold(f64::INFINITY, f64::min)
+            });
+            let max = std::array::from_fn(|axis| {
+                corners
+                    .iter()
+                    .map(|c| c[axis])
+                    .fold(f64::NEG_INFINITY, f64::max)
+            });
+            (min, max)
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..mesh.triangles.len()).collect();
+    let mut nodes = vec![];
+    build_node(&bounds, &mut order, 0, &mut nodes);
+
+    mesh.triangles = order.iter().map(|&i| mesh.triangles[i]).collect();
+    MeshHierarchy { mesh, nodes }
+}
+
+fn build_node(
+    bounds: &[([f64; 3], [f64; 3])],
+    order: &mut [usize],
+    first: usize,
+    nodes: &mut Vec<HierarchyNode>,
+) {
+    let mut min = [f64::INFINITY; 3];
+    let mut max = [f64::NEG_INFINITY; 3];
+    for &index in order.iter() {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(bounds[index].0[axis]);
+            max[axis] = max[axis].max(bounds[index].1[axis]);
+        }
+    }
+
+    let node = nodes.len();
+    nodes.push(HierarchyNode {
+        min,
+        max,
+        first: first as u32,
+        count: order.len() as u32,
+    });
+    if order.len() <= MAX_LEAF_TRIANGLES {
+        return;
+    }
+
+    let centroid = |index: usize, axis: usize| bounds[index].0[axis] + bounds[index].1[axis];
+    let axis = (0..3)
+        .max_by(|a, b| (max[*a] - min[*a]).total_cmp(&(max[*b] - min[*b])))
+        .unwrap();
+    let middle = order.len() / 2;
+    order.select_nth_unstable_by(middle, |a, b| {
+        centroid(*a, axis).total_cmp(&centroid(*b, axis))
+    });
+
+    let (left, right) = order.split_at_mut(middle);
+    build_node(bounds, left, first, nodes);
+    nodes[node].first = nodes.len() as u32;
+    nodes[node].count = 0;
+    build_node(bounds, right, first + middle, nodes);
+}
+
+fn encode(hierarchy: &MeshHierarchy) -> Vec<u8> {
+    let MeshHierarchy { mesh, nodes } = hierarchy;
+    let mut data = Vec::with_capacity(
+        ConvertedMesh::MAGIC.len()
+            + 12
+            + mesh.vertices.len() * 24
+            + mesh.triangles.len() * 12
+            + nodes.len() * 56,
+    );
+    data.extend_from_slice(ConvertedMesh::MAGIC);
+    for count in [mesh.vertices.len(), mesh.triangles.len(), nodes.len()] {
+        data.extend_from_slice(&(count as u32).to_le_bytes());
+    }
+    for vertex in &mesh.vertices {
+        for v in [vertex.x, vertex.y, vertex.z] {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+    }
+    for triangle in &mesh.triangles {
+        for i in triangle {
+            data.extend_from_slice(&i.to_le_bytes());
+        }
+    }
+    for node in nodes {
+        for v in node.min.iter().chain(&node.max) {
+            data.extend_from_slice(&v.to_le_bytes());
+        }
+        data.extend_from_slice(&node.first.to_le_bytes());
+        data.extend_from_slice(&node.count.to_le_bytes());
+    }
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use caustic_core::{Vector3, object::ConvertedMesh};
+
+    use super::{Mesh, build_hierarchy, encode, parse_obj, parse_stl, repair};
+
+    /// A cube from 0 to 1 as 12 triangles of 3 vertices each, the two
+    /// triangles of each side wound opposite ways
+    fn cube_stl_facets() -> Vec<[Vector3; 3]> {
+        let corner =
+            |i: usize| Vector3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64);
+        [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ]
+        .iter()
+        .flat_map(|[a, b, c, d]| {
+            [
+                [corner(*a), corner(*b), corner(*c)],
+                [corner(*a), corner(*d), corner(*c)],
+            ]
+        })
+        .collect()
+    }
+
+    fn binary_stl(facets: &[[Vector3; 3]]) -> Vec<u8> {
+        let mut data = vec![0; 80];
+        data.extend_from_slice(&(facets.len() as u32).to_le_bytes());
+        for facet in facets {
+            data.extend_from_slice(&[0; 12]);
+            for vertex in facet {
+                for v in [vertex.x, vertex.y, vertex.z] {
+                    data.extend_from_slice(&(v as f32).to_le_bytes());
+                }
+            }
+            data.extend_from_slice(&[0; 2]);
+        }
+        data
+    }
+
+    fn volume(mesh: &Mesh) -> f64 {
+        mesh.triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|i| mesh.vertices[i as usize]);
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn parses_binary_and_ascii_stl() {
+        let facets = cube_stl_facets();
+        let binary = parse_stl(&binary_stl(&facets)).unwrap();
+        assert_eq!(binary.vertices.len(), 36);
+        assert_eq!(binary.triangles.len(), 12);
+
+        let mut text = "solid cube\n".to_owned();
+        for facet in &facets {
+            text += "facet normal 0 0 0\nouter loop\n";
+            for v in facet {
+                text += &format!("vertex {} {} {}\n", v.x, v.y, v.z);
+            }
+            text += "endloop\nendfacet\n";
+        }
+        text += "endsolid cube\n";
+        let ascii = parse_stl(text.as_bytes()).unwrap();
+        assert_eq!(ascii.vertices, binary.vertices);
+        assert_eq!(ascii.triangles, binary.triangles);
+
+        // A binary header starting with "solid" is told apart by its size
+        let mut data = binary_stl(&facets);
+        data[..5].copy_from_slice(b"solid");
+        assert_eq!(parse_stl(&data).unwrap().triangles.len(), 12);
+    }
+
+    #[test]
+    fn rejects_invalid_stl() {
+        let mut data = binary_stl(&cube_stl_facets());
+        data.truncate(data.len() - 1);
+        assert_eq!(
+            parse_stl(&data).err().unwrap(),
+            "STL file is too short for 12 triangles"
+        );
+        assert_eq!(
+            parse_stl(b"solid x\nouter loop\nvertex 0 0 0\nendloop\n")
+                .err()
+                .unwrap(),
+            "line 4: facets must have 3 vertices, found 1"
+        );
+        assert_eq!(
+            parse_stl(b"solid x\nvertex 0 nan 0\n").err().unwrap(),
+            "vertex 0 is not a finite number"
+        );
+    }
+
+    #[test]
+    fn parses_obj() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nvt 0 0\nf 1/1 2/1 3/1 4/1\nf -4 -2 -1\n";
+        let mesh = parse_obj(obj).unwrap();
+        assert_eq!(mesh.vertices.len(), 4);
+        // The quad is split into a fan, negative indices count back
+        assert_eq!(mesh.triangles, vec![[0, 1, 2], [0, 2, 3], [0, 2, 3]]);
+
+        assert_eq!(
+            parse_obj(b"v 0 0 0\nf 1 2 3\n").err().unwrap(),
+            "line 2: face refers to a missing vertex"
+        );
+        assert_eq!(
+            parse_obj(b"v 0 0 0\nv 1 0 0\nf 1 2\n").err().unwrap(),
+            "line 3: faces must have at least 3 vertices"
+        );
+    }
+
+    #[test]
+    fn repair_welds_and_orients_the_mesh() {
+        let mut facets = cube_stl_facets();
+        // A degenerate triangle, and the whole cube inside out
+        facets.push([Vector3::ZERO, Vector3::ZERO, Vector3::new(1.0, 0.0, 0.0)]);
+        let mut mesh = parse_stl(&binary_stl(&facets)).unwrap();
+        for triangle in &mut mesh.triangles {
+            triangle.swap(1, 2);
+        }
+
+        let (mesh, report) = repair(mesh).unwrap();
+        assert_eq!(mesh.vertices.len(), 8);
+        assert_eq!(mesh.triangles.len(), 12);
+        assert_eq!(report.dropped, 1);
+        assert!(report.flipped > 0);
+        assert!((volume(&mesh) - 1.0).abs() < 1e-12);
+
+        // Every edge is used once in each direction
+        let mut edges: Vec<(u32, u32)> = mesh
+            .triangles
+            .iter()
+            .flat_map(|[a, b, c]| [(*a, *b), (*b, *c), (*c, *a)])
+            .collect();
+        edges.sort();
+        edges.dedup();
+        assert_eq!(edges.len(), 36);
+
+        let degenerate = Mesh {
+            vertices: vec![Vector3::ZERO, Vector3::new(1.0, 0.0, 0.0)],
+            triangles: vec![[0, 1, 1]],
+        };
+        assert_eq!(
+            repair(degenerate).err().unwrap(),
+            "mesh has no triangles with an area"
+        );
+    }
+
+    #[test]
+    fn converted_meshes_decode() {
+        let (mesh, _) = repair(parse_stl(&binary_stl(&cube_stl_facets())).unwrap()).unwrap();
+        let data = encode(&build_hierarchy(mesh));
+
+        let decoded = ConvertedMesh::decode(&data).unwrap();
+        assert_eq!(decoded.vertices.len(), 8);
+        assert_eq!(decoded.triangles.len(), 12);
+        let volume: f64 = decoded
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|i| decoded.vertices[i]);
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum();
+        assert!((volume - 1.0).abs() < 1e-12);
+    }
+}
@@ -1,2 +1,5 @@
+pub mod mesh_service;
+pub mod preview_service;
 pub mod project_service;
+pub mod scene_service;
 pub mod user_service;
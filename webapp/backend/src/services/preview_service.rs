@@ -0,0 +1,198 @@
+use std::{
+    io::Cursor,
+    net::IpAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use caustic_core::{RenderContext, random_new};
+use caustic_openscad::{
+    MessageLevel,
+    interpreter::InterpreterLimits,
+    library::LibraryPath,
+    run_openscad_with_limits,
+    source::{Source, StringSource},
+};
+use image::{ImageFormat, RgbImage};
+use tokio::sync::Semaphore;
+
+use crate::utils::rate_limiter::RateLimiter;
+
+/// Largest scene accepted, in bytes
+pub const MAX_PREVIEW_SOURCE_SIZE: usize = 16 * 1024;
+
+/// Longest side of a preview in pixels
+const PREVIEW_SIZE: u32 = 160;
+
+/// Most samples per pixel of a preview
+const PREVIEW_SAMPLES_PER_PIXEL: u32 = 16;
+
+/// Most bounces of a path in a preview
+const PREVIEW_MAX_DEPTH: u32 = 8;
+
+/// Most photons traced for the caustics of a preview
+const PREVIEW_CAUSTIC_PHOTONS: u32 = 20_000;
+
+/// Time after which a preview stops rendering
+const PREVIEW_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Previews rendered at once, others are turned away
+const MAX_CONCURRENT_PREVIEWS: usize = 2;
+
+/// Previews each client may request per `RATE_LIMIT_WINDOW`
+const RATE_LIMIT_REQUESTS: u32 = 10;
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Sandbox for scenes from anonymous users, so their work is bounded
+pub const PREVIEW_LIMITS: InterpreterLimits = InterpreterLimits {
+    max_operations: Some(1_000_000),
+    max_call_depth: Some(100),
+};
+
+pub enum PreviewResult {
+    /// PNG of the render
+    Rendered(Vec<u8>),
+    /// The scene could not be interpreted, contains the error messages
+    InvalidScene(Vec<String>),
+    /// Rendering took longer than allowed
+    TimedOut,
+    /// Too many previews are rendering
+    Busy,
+    /// The client requested too many previews recently
+    RateLimited,
+}
+
+/// Renders small previews of scenes for visitors without an account, within
+/// strict limits: no files besides the scene, bounded interpretation, a
+/// small image with few samples and bounces, and a time limit.
+pub struct PreviewService {
+    rate_limiter: RateLimiter,
+    renders: Arc<Semaphore>,
+}
+
+impl Default for PreviewService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PreviewService {
+    pub fn new() -> Self {
+        Self {
+            rate_limiter: RateLimiter::new(RATE_LIMIT_REQUESTS, RATE_LIMIT_WINDOW),
+            renders: Arc::new(Semaphore::new(MAX_CONCURRENT_PREVIEWS)),
+        }
+    }
+
+    /// Renders a preview requested by `client`. Only previews which start
+    /// rendering count towards the client's rate limit, not those turned away
+    /// because too many are rendering.
+    pub async fn render(&self, client: IpAddr, code: String) -> Result<PreviewResult> {
+        let Ok(permit) = self.renders.clone().try_acquire_owned() else {
+            return Ok(PreviewResult::Busy);
+        };
+        if !self.rate_limiter.check(client) {
+            return Ok(PreviewResult::RateLimited);
+        }
+
+        tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            let deadline = Instant::now() + PREVIEW_TIMEOUT;
+
+            // Without a library path or files next to the scene, only the
+            // scene itself is read
+            let source: Arc<Box<dyn Source>> = Arc::new(Box::new(StringSource::new(&code)));
+            let random = random_new();
+            let results = run_openscad_with_limits(
+                source,
+                random.clone(),
+                LibraryPath::new(),
+                0.0,
+                PREVIEW_LIMITS,
+            );
+            let Some(mut scene_data) = results.scene_data else {
+                let errors = results
+                    .messages
+                    .iter()
+                    .filter(|m| m.level == MessageLevel::Error)
+                    .map(|m| format!("{}: {}", m.position, m.message))
+                    .collect();
+                return Ok(PreviewResult::InvalidScene(errors));
+            };
+
+            let ctx = RenderContext { random };
+            // The photon map is traced for the preview's size, within its
+            // time limit
+            scene_data.camera = Arc::new(
+                scene_data
+                    .camera
+                    .with_max_depth(scene_data.camera.max_depth().min(PREVIEW_MAX_DEPTH)),
+            );
+            scene_data.cameras.clear();
+            scene_data.trace_caustics(&ctx, PREVIEW_CAUSTIC_PHOTONS);
+            if Instant::now() > deadline {
+                return Ok(PreviewResult::TimedOut);
+            }
+
+            let camera = &scene_data.camera;
+            let camera = camera
+                .with_image_width(preview_width(camera.image_width(), camera.image_height()))
+                .with_samples_per_pixel(camera.samples_per_pixel().min(PREVIEW_SAMPLES_PER_PIXEL))
+                .with_max_depth(camera.max_depth().min(PREVIEW_MAX_DEPTH));
+
+            let (width, height) = (camera.image_width(), camera.image_height());
+            let mut pixels = Vec::with_capacity(width as usize * height as usize * 3);
+            for y in 0..height {
+                for x in 0..width {
+                    // Checked for every pixel, as a single row of a slow
+                    // scene can take far longer than the time limit
+                    if Instant::now() > deadline {
+                        return Ok(PreviewResult::TimedOut);
+                    }
+                    let color = camera
+                        .render(&ctx, x, y, &*scene_data.world, scene_data.lights.clone())
+                        .clamp(0.0, 0.999);
+                    for value in [color.r, color.g, color.b] {
+                        pixels.push((value * 256.0) as u8);
+                    }
+                }
+            }
+
+            let image = RgbImage::from_raw(width, height, pixels)
+                .context("preview pixels do not match its size")?;
+            let mut png = Cursor::new(vec![]);
+            image
+                .write_to(&mut png, ImageFormat::Png)
+                .context("failed to encode preview")?;
+            Ok(PreviewResult::Rendered(png.into_inner()))
+        })
+        .await?
+    }
+}
+
+/// Returns the width of the preview of an image, scaled so its longest side
+/// is at most [`PREVIEW_SIZE`].
+fn preview_width(image_width: u32, image_height: u32) -> u32 {
+    let longest = image_width.max(image_height);
+    if longest <= PREVIEW_SIZE {
+        return image_width;
+    }
+    // In u64, as the scene's width times the preview size can overflow u32
+    (image_width as u64 * PREVIEW_SIZE as u64 / longest as u64).max(1) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PREVIEW_SIZE, preview_width};
+
+    #[test]
+    fn preview_width_keeps_the_aspect_ratio() {
+        assert_eq!(preview_width(100, 50), 100);
+        assert_eq!(preview_width(1600, 900), PREVIEW_SIZE);
+        assert_eq!(preview_width(900, 1600), 90);
+        assert_eq!(preview_width(u32::MAX, u32::MAX / 2), PREVIEW_SIZE);
+        assert_eq!(preview_width(1, u32::MAX), 1);
+    }
+}
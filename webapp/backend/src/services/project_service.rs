@@ -3,7 +3,10 @@ use std::sync::Arc;
 use anyhow::Result;
 
 use crate::{
-    repository::project_repository::{Project, ProjectRepository},
+    repository::{
+        gallery_repository::GalleryRepository,
+        project_repository::{Project, ProjectRepository},
+    },
     routes::user_routes::AuthUser,
 };
 
@@ -17,11 +20,18 @@ pub enum LoadProjectResult {
 
 pub struct ProjectService {
     project_repository: Arc<ProjectRepository>,
+    gallery_repository: Arc<GalleryRepository>,
 }
 
 impl ProjectService {
-    pub fn new(project_repository: Arc<ProjectRepository>) -> Self {
-        Self { project_repository }
+    pub fn new(
+        project_repository: Arc<ProjectRepository>,
+        gallery_repository: Arc<GalleryRepository>,
+    ) -> Self {
+        Self {
+            project_repository,
+            gallery_repository,
+        }
     }
 
     pub async fn load_project(
@@ -41,6 +51,13 @@ impl ProjectService {
                     && project.owner_user_id == user.user_id
                 {
                     Ok(LoadProjectResult::Project(project))
+                } else if self
+                    .gallery_repository
+                    .is_project_published(project_id)
+                    .await?
+                {
+                    // published to the gallery, readable by everyone
+                    Ok(LoadProjectResult::Project(project))
                 } else {
                     Ok(LoadProjectResult::AccessDenied)
                 }
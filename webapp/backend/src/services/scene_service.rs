@@ -0,0 +1,198 @@
+use std::{path::Path, sync::Arc};
+
+use anyhow::{Result, anyhow};
+use caustic_core::{
+    Interval, Node, Ray, RenderContext, Vector3,
+    object::{BoundingVolumeHierarchy, Group, HitRecord},
+    random_new,
+};
+use caustic_openscad::{
+    MessageLevel,
+    library::LibraryPath,
+    run_openscad_with_limits,
+    source::{FileSource, Source},
+};
+
+use crate::{
+    repository::project_repository::{CONTENT_TYPE_OPENSCAD, Project, ProjectRepository},
+    services::preview_service::PREVIEW_LIMITS,
+};
+
+pub enum PickResult {
+    Hit {
+        distance: f64,
+        point: Vector3,
+        normal: Vector3,
+        object: String,
+    },
+    Miss,
+    /// The scene could not be interpreted, contains the error messages
+    InvalidScene(Vec<String>),
+}
+
+pub struct SceneService {
+    project_repository: Arc<ProjectRepository>,
+}
+
+impl SceneService {
+    pub fn new(project_repository: Arc<ProjectRepository>) -> Self {
+        Self { project_repository }
+    }
+
+    /// Interprets the project's main OpenSCAD file and casts a single ray through
+    /// the normalized screen coordinates `(s, t)`, where `(0, 0)` is the top left
+    /// of the image and `(1, 1)` the bottom right.
+    pub async fn pick(&self, project: &Project, s: f64, t: f64) -> Result<PickResult> {
+        let main_file = project
+            .files
+            .iter()
+            .filter(|f| f.content_type == CONTENT_TYPE_OPENSCAD)
+            .min_by_key(|f| f.sort)
+            .ok_or_else(|| anyhow!("project {} has no OpenSCAD file", project.id))?;
+        let dir = self.project_repository.project_dir(&project.id);
+        let path = dir.join(&main_file.filename);
+
+        tokio::task::spawn_blocking(move || pick_file(&path, &dir, s, t)).await?
+    }
+}
+
+/// Interprets the scene at `path`, which may only read files within `dir`,
+/// within the limits of anonymous previews, as projects come from any user,
+/// and casts the pick ray through `(s, t)`.
+fn pick_file(path: &Path, dir: &Path, s: f64, t: f64) -> Result<PickResult> {
+    let source: Arc<Box<dyn Source>> = Arc::new(Box::new(FileSource::new_within(path, dir)?));
+    let random = random_new();
+    let results = run_openscad_with_limits(
+        source,
+        random.clone(),
+        LibraryPath::new(),
+        0.0,
+        PREVIEW_LIMITS,
+    );
+
+    let scene_data = if let Some(scene_data) = results.scene_data {
+        scene_data
+    } else {
+        let errors = results
+            .messages
+            .iter()
+            .filter(|m| m.level == MessageLevel::Error)
+            .map(|m| format!("{}: {}", m.position, m.message))
+            .collect();
+        return Ok(PickResult::InvalidScene(errors));
+    };
+
+    let ctx = RenderContext { random };
+    let ray = scene_data.camera.get_pick_ray(s, t);
+    let ray_t = Interval::new(0.001, f64::INFINITY);
+    Ok(match pick_node(&ctx, &scene_data.world, &ray, ray_t) {
+        Some((hit, object)) => PickResult::Hit {
+            distance: hit.t * ray.direction.length(),
+            point: hit.pt,
+            normal: hit.normal,
+            object,
+        },
+        None => PickResult::Miss,
+    })
+}
+
+/// Finds the closest hit below `node`, descending through bounding volume
+/// hierarchies and groups so the name of the leaf node that was hit can be reported.
+fn pick_node(
+    ctx: &RenderContext,
+    node: &Arc<dyn Node>,
+    ray: &Ray,
+    ray_t: Interval,
+) -> Option<(HitRecord, String)> {
+    if let Some(bvh) = node.as_any().downcast_ref::<BoundingVolumeHierarchy>() {
+        if !bvh.bounding_box().hit(ray, ray_t) {
+            return None;
+        }
+        let hit_left = pick_node(ctx, &bvh.get_left(), ray, ray_t);
+        let t = hit_left.as_ref().map(|(hit, _)| hit.t).unwrap_or(ray_t.max);
+        let hit_right = pick_node(ctx, &bvh.get_right(), ray, Interval::new(ray_t.min, t));
+        return hit_right.or(hit_left);
+    }
+
+    if let Some(group) = node.as_any().downcast_ref::<Group>() {
+        let mut ray_t = ray_t;
+        let mut closest_hit = None;
+        for child in group.get_nodes() {
+            if let Some(hit) = pick_node(ctx, child, ray, ray_t) {
+                ray_t.max = hit.0.t;
+                closest_hit = Some(hit);
+            }
+        }
+        return closest_hit;
+    }
+
+    node.hit(ctx, ray, ray_t)
+        .map(|hit| (hit, node.type_name().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::{PickResult, pick_file};
+
+    fn write_project(name: &str, code: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("main.scad"), code).unwrap();
+        dir
+    }
+
+    #[test]
+    fn pick_reports_hit_object() {
+        let dir = write_project(
+            "caustic-pick-hit",
+            "camera(look_from=[0, -10, 0], look_at=[0, 0, 0]);\nsphere(r=1);\n",
+        );
+        let PickResult::Hit {
+            distance, object, ..
+        } = pick_file(&dir.join("main.scad"), &dir, 0.5, 0.5).unwrap()
+        else {
+            panic!("expected a hit");
+        };
+        assert_eq!(object, "Sphere");
+        assert!((distance - 9.0).abs() < 1e-6, "distance {distance}");
+
+        assert!(matches!(
+            pick_file(&dir.join("main.scad"), &dir, 0.0, 0.0).unwrap(),
+            PickResult::Miss
+        ));
+    }
+
+    #[test]
+    fn pick_stops_endless_scenes() {
+        let dir = write_project(
+            "caustic-pick-endless",
+            "camera(look_from=[0, -10, 0], look_at=[0, 0, 0]);\n\
+             for (i = [0:1e12]) {}\n\
+             sphere(r=1);\n",
+        );
+        // The loop is stopped and the statements after it are not run
+        assert!(matches!(
+            pick_file(&dir.join("main.scad"), &dir, 0.5, 0.5).unwrap(),
+            PickResult::Miss
+        ));
+    }
+
+    #[test]
+    fn pick_does_not_include_files_outside_project() {
+        let dir = write_project(
+            "caustic-pick-outside",
+            "include <../caustic-pick-secret.scad>\n",
+        );
+        fs::write(
+            std::env::temp_dir().join("caustic-pick-secret.scad"),
+            "camera(look_from=[0, -10, 0], look_at=[0, 0, 0]);\nsphere(r=1);\n",
+        )
+        .unwrap();
+        assert!(!matches!(
+            pick_file(&dir.join("main.scad"), &dir, 0.5, 0.5).unwrap(),
+            PickResult::Hit { .. }
+        ));
+    }
+}
@@ -2,9 +2,14 @@ use std::{path::PathBuf, sync::Arc};
 
 use crate::{
     repository::{
-        create_db_pool, project_repository::ProjectRepository, user_repository::UserRepository,
+        asset_upload_repository::AssetUploadRepository, create_db_pool,
+        gallery_repository::GalleryRepository, project_repository::ProjectRepository,
+        render_preset_repository::RenderPresetRepository, user_repository::UserRepository,
+    },
+    services::{
+        mesh_service::MeshService, preview_service::PreviewService,
+        project_service::ProjectService, scene_service::SceneService, user_service::UserService,
     },
-    services::{project_service::ProjectService, user_service::UserService},
 };
 use anyhow::Result;
 use dotenvy;
@@ -22,14 +27,32 @@ pub struct AppStateSettings {
     pub jwt_expire_duration_hours: u32,
     pub sqlite_connection_string: String,
     pub data_path: PathBuf,
+    /// Users allowed to hide, restore and remove any gallery item
+    #[serde(default)]
+    pub gallery_moderator_user_ids: Vec<String>,
+    /// Take client addresses from `X-Forwarded-For`, only when behind a proxy
+    /// which sets it, as clients could otherwise dodge rate limits
+    #[serde(default)]
+    pub trust_forwarded_for: bool,
+    /// Proxies in front of the server which append to `X-Forwarded-For`, the
+    /// client address is taken this many entries from the right, as entries
+    /// further left are written by the client
+    #[serde(default = "default_forwarded_for_hops")]
+    pub forwarded_for_hops: usize,
 }
 
 #[derive(Clone)]
 pub struct AppState {
     pub settings: Arc<AppStateSettings>,
+    pub asset_upload_repository: Arc<AssetUploadRepository>,
+    pub gallery_repository: Arc<GalleryRepository>,
     pub project_repository: Arc<ProjectRepository>,
+    pub render_preset_repository: Arc<RenderPresetRepository>,
     pub user_repository: Arc<UserRepository>,
+    pub mesh_service: Arc<MeshService>,
+    pub preview_service: Arc<PreviewService>,
     pub project_service: Arc<ProjectService>,
+    pub scene_service: Arc<SceneService>,
     pub user_service: Arc<UserService>,
 }
 
@@ -37,6 +60,10 @@ fn default_bind() -> String {
     "0.0.0.0:8080".to_string()
 }
 
+fn default_forwarded_for_hops() -> usize {
+    1
+}
+
 fn default_jwt_expire_duration_hours() -> u32 {
     30 * 24 // 30 days
 }
@@ -45,24 +72,53 @@ impl AppState {
     pub async fn new() -> Result<AppState> {
         dotenvy::dotenv().ok();
 
-        let settings = Arc::new(envy::prefixed("RAYTRACE_").from_env::<AppStateSettings>()?);
+        let settings = envy::prefixed("RAYTRACE_").from_env::<AppStateSettings>()?;
+        AppState::new_with_settings(settings).await
+    }
+
+    /// Connects to the database of `settings` and creates the repositories
+    /// and services.
+    pub async fn new_with_settings(settings: AppStateSettings) -> Result<AppState> {
+        let settings = Arc::new(settings);
 
         let db_pool = create_db_pool(&settings.sqlite_connection_string).await?;
 
         let project_repository =
             Arc::new(ProjectRepository::new(db_pool.clone(), &settings.data_path));
+        let gallery_repository =
+            Arc::new(GalleryRepository::new(db_pool.clone(), &settings.data_path));
+        let asset_upload_repository = Arc::new(AssetUploadRepository::new(
+            db_pool.clone(),
+            &settings.data_path,
+        ));
+        let render_preset_repository = Arc::new(RenderPresetRepository::new(db_pool.clone()));
         let user_repository = Arc::new(UserRepository::new(db_pool));
 
         let user_service = Arc::new(UserService::new(user_repository.clone()));
 
-        let project_service = Arc::new(ProjectService::new(project_repository.clone()));
+        let project_service = Arc::new(ProjectService::new(
+            project_repository.clone(),
+            gallery_repository.clone(),
+        ));
+
+        let scene_service = Arc::new(SceneService::new(project_repository.clone()));
+
+        let mesh_service = Arc::new(MeshService::new(&settings.data_path));
+
+        let preview_service = Arc::new(PreviewService::new());
 
         Ok(AppState {
             settings,
+            asset_upload_repository,
+            gallery_repository,
             project_repository,
+            render_preset_repository,
             user_repository,
             user_service,
+            mesh_service,
+            preview_service,
             project_service,
+            scene_service,
         })
     }
 }
@@ -3,6 +3,7 @@ use anyhow::{Result, anyhow};
 use crate::repository::project_repository::CONTENT_TYPE_OPENSCAD;
 
 pub mod google;
+pub mod rate_limiter;
 
 pub fn mime_type_from_path(path: &str) -> Result<String> {
     let guess = mime_guess::from_path(path).first();
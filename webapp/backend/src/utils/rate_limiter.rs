@@ -0,0 +1,40 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Limits how many requests each client may make in a window of time, kept
+/// in memory so the counts start over when the server restarts.
+pub struct RateLimiter {
+    max_requests: u32,
+    window: Duration,
+    /// Start of the current window and requests made in it, by client
+    requests: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: u32, window: Duration) -> Self {
+        Self {
+            max_requests,
+            window,
+            requests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts a request from `client`, returning false without counting it
+    /// once the client made `max_requests` in the current window.
+    pub fn check(&self, client: IpAddr) -> bool {
+        let now = Instant::now();
+        let mut requests = self.requests.lock().unwrap();
+        requests.retain(|_, (start, _)| now.duration_since(*start) < self.window);
+
+        let (_, count) = requests.entry(client).or_insert((now, 0));
+        if *count >= self.max_requests {
+            return false;
+        }
+        *count += 1;
+        true
+    }
+}
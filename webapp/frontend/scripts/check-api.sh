@@ -0,0 +1,21 @@
+#!/bin/bash
+set -e
+
+# Generates the TypeScript client and checks that it exposes the typed error
+# body of the API and that the frontend still compiles against it.
+
+SCRIPT_DIR=$(dirname "$(readlink -f "$0")")
+cd "${SCRIPT_DIR}/.."
+
+./scripts/generate-api.sh
+
+for model in ApiError ApiErrorCode; do
+    if [ ! -f "./src/api/models/${model}.ts" ]; then
+        echo "generated client is missing the ${model} model"
+        exit 1
+    fi
+done
+
+npx tsc -b
+
+echo "api check complete!"
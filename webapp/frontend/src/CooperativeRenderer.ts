@@ -0,0 +1,54 @@
+import type { RenderCallbackFn, RenderOptions } from './RenderWorkerPool';
+import { renderFor, startCooperativeRender } from './wasm';
+
+/** Time spent rendering per animation frame, leaving the rest of the frame to the UI */
+const FRAME_BUDGET_MS = 8;
+
+/**
+ * Renders the scene loaded on the main thread a slice at a time between
+ * animation frames, for browsers without workers. Emits the same events as
+ * the worker pool, the first pass as the preview and later passes as
+ * refinements.
+ */
+export class CooperativeRenderer {
+    private renderId = 0;
+
+    public render(options: RenderOptions): void {
+        const renderId = ++this.renderId;
+        const callback: RenderCallbackFn = options.callback;
+        startCooperativeRender(1 + options.refinementPasses);
+        callback({
+            type: 'init',
+            blockSize: options.blockSize,
+            blockCount: options.height,
+            startTime: new Date(),
+        });
+
+        const step = (): void => {
+            if (renderId !== this.renderId) {
+                // replaced by a newer render
+                return;
+            }
+            const result = renderFor(FRAME_BUDGET_MS);
+            const { pass, progress, done, ...block } = result;
+            if (pass === 0) {
+                callback({
+                    ...block,
+                    type: 'renderResult',
+                    progress: Math.min(1, progress * (1 + options.refinementPasses)),
+                });
+            } else {
+                callback({
+                    ...block,
+                    type: 'refineResult',
+                    samplesPerPixel: options.samplesPerPixel * (pass + 1),
+                    refineProgress: (progress * (1 + options.refinementPasses) - 1) / options.refinementPasses,
+                });
+            }
+            if (!done) {
+                requestAnimationFrame(step);
+            }
+        };
+        requestAnimationFrame(step);
+    }
+}
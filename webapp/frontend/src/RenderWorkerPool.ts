@@ -9,6 +9,8 @@ import type {
     TextWorkingFile,
     WorkingFile,
 } from './types';
+import { AccumulationBuffer } from './utils/accumulationBuffer';
+import { getTiles, type CropWindow, type RenderSettings } from './wasm';
 import RenderWorker from './workers/renderWorker?worker';
 
 export interface RenderEventInit {
@@ -23,13 +25,23 @@ export interface RenderEventRenderResult extends RenderResult {
     progress: number;
 }
 
-export type RenderEvent = RenderEventInit | RenderEventRenderResult;
+/** A block rendered again after the preview completed, with its new sample count. */
+export interface RenderEventRefineResult extends RenderResult {
+    type: 'refineResult';
+    samplesPerPixel: number;
+    refineProgress: number;
+}
+
+export type RenderEvent = RenderEventInit | RenderEventRenderResult | RenderEventRefineResult;
 
 export type RenderCallbackFn = (event: RenderEvent) => unknown;
 
 export interface RenderOptions extends Required<StateRenderOptions> {
     width: number;
     height: number;
+    samplesPerPixel: number;
+    /** Settings of the selected render preset, applied by every worker */
+    renderSettings: RenderSettings | null;
     callback: RenderCallbackFn;
 }
 
@@ -39,6 +51,19 @@ export class RenderWorkerPool {
     private callback?: RenderCallbackFn;
     private blockCount = 0;
     private receivedBlockCount = 0;
+    private renderId = 0;
+    private buffer?: AccumulationBuffer;
+    private samplesPerPixel = 1;
+    private refinementPasses = 0;
+    private camera: string | null = null;
+    private renderSettings: RenderSettings | null = null;
+    private overscan = 0;
+    private cropWindow: CropWindow | null = null;
+    private refinementBlockCount = 0;
+    private receivedRefinementBlockCount = 0;
+    private blocks: RenderRequestWork[] = [];
+    /** Refinement blocks sent to a worker but not yet received */
+    private pending: RenderRequestWork[] = [];
 
     private ensureWorkerCount(threadCount: number): void {
         if (this.workers.length < threadCount) {
@@ -66,13 +91,40 @@ export class RenderWorkerPool {
     }
 
     private handleWorkerDataResponse(response: RenderResponseData): void {
-        this.receivedBlockCount++;
-        const progress = this.receivedBlockCount / this.blockCount;
-        this.callback?.({
-            ...response,
-            type: 'renderResult',
-            progress,
-        });
+        if (response.renderId !== this.renderId || !this.buffer) {
+            // left over from a previous render
+            return;
+        }
+
+        const result = this.buffer.add(response, this.samplesPerPixel);
+        if (this.receivedBlockCount < this.blockCount) {
+            this.receivedBlockCount++;
+            const progress = this.receivedBlockCount / this.blockCount;
+            this.callback?.({
+                ...result,
+                type: 'renderResult',
+                progress,
+            });
+            if (this.receivedBlockCount === this.blockCount) {
+                // Every worker is idle once the preview is complete, start refining with all of them
+                this.workers.forEach((_, workerId) => {
+                    this.sendMoreWork(workerId);
+                });
+                return;
+            }
+        } else {
+            const pendingIndex = this.pending.findIndex((p) => p.xmin === result.xmin && p.ymin === result.ymin);
+            if (pendingIndex >= 0) {
+                this.pending.splice(pendingIndex, 1);
+            }
+            this.receivedRefinementBlockCount++;
+            this.callback?.({
+                ...result,
+                type: 'refineResult',
+                samplesPerPixel: this.buffer.getTileSamples(result.xmin, result.ymin),
+                refineProgress: this.receivedRefinementBlockCount / this.refinementBlockCount,
+            });
+        }
         this.sendMoreWork(response.workerId);
     }
 
@@ -87,7 +139,51 @@ export class RenderWorkerPool {
         const work = this.work.pop();
         if (work) {
             this.workers[workerId].postMessage(work);
+            return;
         }
+
+        // The preview is queued in full up front, once it has been received the
+        // remaining refinement budget is spent while the page is idle
+        const renderId = this.renderId;
+        whenIdle(() => {
+            if (renderId !== this.renderId) {
+                return;
+            }
+            const refinement = this.nextRefinementWork();
+            if (refinement) {
+                this.workers[workerId].postMessage(refinement);
+            }
+        });
+    }
+
+    /** Returns the least refined block that is still under the sample budget. */
+    private nextRefinementWork(): RenderRequestWork | undefined {
+        const buffer = this.buffer;
+        if (!buffer || this.receivedBlockCount < this.blockCount) {
+            return undefined;
+        }
+        const targetSamples = this.samplesPerPixel * (1 + this.refinementPasses);
+        if (buffer.getMinTileSamples() >= targetSamples) {
+            return undefined;
+        }
+
+        let best: { block: RenderRequestWork; samples: number } | undefined;
+        for (const block of this.blocks) {
+            const samples = buffer.getTileSamples(block.xmin, block.ymin) + this.pendingSamples(block);
+            if (samples < targetSamples && (!best || samples < best.samples)) {
+                best = { block, samples };
+            }
+        }
+        if (!best) {
+            return undefined;
+        }
+        this.pending.push(best.block);
+        return best.block;
+    }
+
+    /** Samples already requested for the block but not yet received. */
+    private pendingSamples(block: RenderRequestWork): number {
+        return this.pending.filter((p) => p === block).length * this.samplesPerPixel;
     }
 
     private handleWorkerError(workerId: number, err: ErrorEvent): void {
@@ -95,12 +191,22 @@ export class RenderWorkerPool {
     }
 
     public render(threadCount: number, main: TextWorkingFile, files: WorkingFile[], options: RenderOptions): void {
+        this.renderId++;
         this.callback = options.callback;
+        this.buffer = new AccumulationBuffer(options.width, options.height, options.blockSize);
+        this.samplesPerPixel = Math.max(1, options.samplesPerPixel);
+        this.refinementPasses = options.refinementPasses;
+        this.camera = options.camera;
+        this.renderSettings = options.renderSettings;
+        this.overscan = options.overscan;
+        this.cropWindow = options.cropWindow;
         this.ensureWorkerCount(threadCount);
         this.populateWorkQueue(options);
 
         this.blockCount = this.work.length;
         this.receivedBlockCount = 0;
+        this.refinementBlockCount = this.blockCount * this.refinementPasses;
+        this.receivedRefinementBlockCount = 0;
         this.callback?.({
             type: 'init',
             blockSize: options.blockSize,
@@ -112,20 +218,15 @@ export class RenderWorkerPool {
     }
 
     private populateWorkQueue(options: RenderOptions): void {
-        const { blockSize, width, height } = options;
-
-        const work: RenderRequestWork[] = [];
-        for (let y = 0; y < height; y += blockSize) {
-            for (let x = 0; x < width; x += blockSize) {
-                work.push({
-                    type: 'work',
-                    xmin: x,
-                    xmax: Math.min(width, x + blockSize),
-                    ymin: y,
-                    ymax: Math.min(height, y + blockSize),
-                });
-            }
-        }
+        const { blockSize, tileOrder, width, height } = options;
+
+        const work: RenderRequestWork[] = getTiles(width, height, blockSize, tileOrder).map((tile) => ({
+            type: 'work',
+            renderId: this.renderId,
+            ...tile,
+        }));
+        this.blocks = [...work];
+        this.pending = [];
         this.work = work.reverse();
         console.log(`work queue initialized with ${work.length} blocks`);
     }
@@ -137,8 +238,21 @@ export class RenderWorkerPool {
                 workerId: i,
                 main,
                 files,
+                camera: this.camera,
+                renderSettings: this.renderSettings,
+                overscan: this.overscan,
+                cropWindow: this.cropWindow,
             };
             this.workers[i].postMessage(message);
         }
     }
 }
+
+/** Runs `fn` when the browser is idle, so refinement never competes with the UI. */
+function whenIdle(fn: () => void): void {
+    if (typeof requestIdleCallback !== 'undefined') {
+        requestIdleCallback(fn);
+    } else {
+        setTimeout(fn, 0);
+    }
+}
@@ -1,13 +1,19 @@
-import React, { useImperativeHandle, type JSX } from 'react';
+import React, { useEffect, useImperativeHandle, type JSX } from 'react';
 import { MiniMap, TransformComponent, TransformWrapper, type ReactZoomPanPinchHandlers } from 'react-zoom-pan-pinch';
 import classes from './Render.module.scss';
 import { Button, Tooltip } from '@mantine/core';
-import { ZoomIn as ZoomInIcon, ZoomOut as ZoomOutIcon, X as ResetZoomIcon } from 'react-bootstrap-icons';
+import {
+    ZoomIn as ZoomInIcon,
+    ZoomOut as ZoomOutIcon,
+    X as ResetZoomIcon,
+    Grid3x3 as GuidesIcon,
+} from 'react-bootstrap-icons';
 import * as _ from 'radash';
 import { RenderProgress, type RenderProgressProps } from './RenderProgress';
 import { useSignal, useSignalEffect } from '@preact/signals-react';
 import { useSignalRef } from '@preact/signals-react/utils';
-import { renderEmpty } from '../utils/canvas';
+import { renderEmpty, renderGuides } from '../utils/canvas';
+import type { GuideLine } from '../wasm';
 
 export const DEFAULT_BLOCK_SIZE = 128;
 
@@ -23,12 +29,16 @@ export interface CanvasViewerProps {
     blockSize?: number;
     width: number;
     height: number;
+    /** Drawn on an overlay above the image, frame guides are always shown and the others can be toggled */
+    guides?: GuideLine[];
 }
 
-export const CanvasViewer = ({ ref, progress, blockSize, width, height }: CanvasViewerProps): JSX.Element => {
+export const CanvasViewer = ({ ref, progress, blockSize, width, height, guides }: CanvasViewerProps): JSX.Element => {
     const canvasRef = useSignalRef<HTMLCanvasElement | null>(null);
     const canvasMiniRef = useSignalRef<HTMLCanvasElement | null>(null);
+    const canvasGuidesRef = useSignalRef<HTMLCanvasElement | null>(null);
     const showMinimap = useSignal(false);
+    const showGuides = useSignal(false);
 
     const getCanvasCtx = (
         canvasRef: React.RefObject<HTMLCanvasElement | null>
@@ -70,6 +80,20 @@ export const CanvasViewer = ({ ref, progress, blockSize, width, height }: Canvas
         });
     });
 
+    // the guides live on their own canvas so they never mix with rendered pixels
+    const _showGuides = showGuides.value;
+    useEffect(() => {
+        const ctx = getCanvasCtx(canvasGuidesRef);
+        if (ctx) {
+            renderGuides(ctx, (guides ?? []).filter((guide) => _showGuides || guide.kind === 'frame'));
+        }
+    }, [guides, _showGuides, width, height]);
+
+    const hasToggleableGuides = (guides ?? []).some((guide) => guide.kind !== 'frame');
+    const handleGuidesClick = (): void => {
+        showGuides.value = !showGuides.value;
+    };
+
     const handleOnZoom = (): void => {
         const canvas = canvasRef.current;
         if (!canvas) {
@@ -107,9 +131,21 @@ export const CanvasViewer = ({ ref, progress, blockSize, width, height }: Canvas
                                 <canvas ref={canvasMiniRef} width={width} height={height} />
                             </MiniMap>
                         </div>
-                        <Controls {...utils} />
+                        <Controls
+                            {...utils}
+                            onGuidesClick={hasToggleableGuides ? handleGuidesClick : undefined}
+                            showGuides={_showGuides}
+                        />
                         <TransformComponent>
-                            <canvas className={classes.canvas} ref={canvasRef} width={width} height={height} />
+                            <div className={classes.canvasStack}>
+                                <canvas className={classes.canvas} ref={canvasRef} width={width} height={height} />
+                                <canvas
+                                    className={classes.guides}
+                                    ref={canvasGuidesRef}
+                                    width={width}
+                                    height={height}
+                                />
+                            </div>
                         </TransformComponent>
                     </React.Fragment>
                 )}
@@ -119,7 +155,12 @@ export const CanvasViewer = ({ ref, progress, blockSize, width, height }: Canvas
     );
 };
 
-function Controls(options: ReactZoomPanPinchHandlers): JSX.Element {
+interface ControlsProps extends ReactZoomPanPinchHandlers {
+    onGuidesClick?: () => void;
+    showGuides: boolean;
+}
+
+function Controls(options: ControlsProps): JSX.Element {
     const handleZoomInClick = (): void => {
         options.zoomIn();
     };
@@ -149,6 +190,13 @@ function Controls(options: ReactZoomPanPinchHandlers): JSX.Element {
                     <ResetZoomIcon />
                 </Button>
             </Tooltip>
+            {options.onGuidesClick && (
+                <Tooltip label={options.showGuides ? 'Hide Guides' : 'Show Guides'}>
+                    <Button onClick={options.onGuidesClick} variant={options.showGuides ? 'filled' : 'light'}>
+                        <GuidesIcon />
+                    </Button>
+                </Tooltip>
+            )}
         </div>
     );
 }
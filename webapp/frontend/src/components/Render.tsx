@@ -47,6 +47,11 @@ export function Render(): JSX.Element {
                 _canvasViewerRef.current?.render((ctx) => {
                     renderDrawEvent(ctx, event);
                 });
+            } else if (event.type === 'refineResult') {
+                // patch the refined block over the preview
+                _canvasViewerRef.current?.render((ctx) => {
+                    renderDrawEvent(ctx, event);
+                });
             }
         });
 
@@ -64,6 +69,7 @@ export function Render(): JSX.Element {
             width={projectStore.cameraInfo.value?.width ?? 500}
             height={projectStore.cameraInfo.value?.height ?? 500}
             blockSize={projectStore.renderOptions.value.blockSize}
+            guides={projectStore.guides.value}
         />
     );
 }
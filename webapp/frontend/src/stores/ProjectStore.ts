@@ -1,10 +1,27 @@
-import { getCameraInfo, initWasm, loadOpenscad, Source, type CameraInfo, type WasmMessage } from '../wasm';
-import { RenderWorkerPool, type RenderCallbackFn } from '../RenderWorkerPool';
-import type { ImageWorkingFile, TextWorkingFile, WorkingFile } from '../types';
+import {
+    getCameraInfo,
+    getCameraNames,
+    getGuides,
+    initWasm,
+    loadOpenscad,
+    setCamera,
+    setCropWindow,
+    setOverscan,
+    setRenderSettings,
+    Source,
+    type CameraInfo,
+    type GuideLine,
+    type RenderSettings,
+    type WasmMessage,
+} from '../wasm';
+import { CooperativeRenderer } from '../CooperativeRenderer';
+import { RenderWorkerPool, type RenderCallbackFn, type RenderEvent } from '../RenderWorkerPool';
+import type { ImageWorkingFile, MeshWorkingFile, TextWorkingFile, WorkingFile } from '../types';
 import { type Project } from '../api';
 import { computed, signal } from '@preact/signals-react';
 import {
     CONTENT_TYPE_OPENSCAD,
+    DEFAULT_REFINEMENT_PASSES,
     DEFAULT_RENDER_BLOCK_SIZE,
     EXAMPLE_CAR_ID,
     projectsStore,
@@ -18,7 +35,11 @@ import type { WasmPosition } from '../wasm/release/caustic_wasm';
 import type { editor } from 'monaco-editor';
 import * as R from 'radash';
 
+/** Files converted by the server for `import()`, see `MeshService::is_mesh` */
+const MESH_FILENAME = /\.(stl|obj)$/i;
+
 const renderWorkerPool = new RenderWorkerPool();
+const cooperativeRenderer = new CooperativeRenderer();
 
 export class ProjectStore {
     private readonly drawEventListeners = new Set<RenderCallbackFn>();
@@ -26,9 +47,21 @@ export class ProjectStore {
 
     public readonly files = signal<WorkingFile[]>([]);
     public readonly cameraInfo = signal<CameraInfo | undefined>(undefined);
+    /** Names of the cameras the scene defines, to choose which one to render */
+    public readonly cameraNames = signal<string[]>([]);
+    /** Guides drawn over the render, outside of the rendered pixels */
+    public readonly guides = signal<GuideLine[]>([]);
     public readonly renderOptions = signal<Required<RenderOptions>>({
         blockSize: DEFAULT_RENDER_BLOCK_SIZE,
+        tileOrder: 'scanline',
         threadCount: typeof navigator !== 'undefined' ? (navigator.hardwareConcurrency ?? 4) : 4,
+        refinementPasses: DEFAULT_REFINEMENT_PASSES,
+        camera: null,
+        preset: null,
+        overscan: 0,
+        cropWindow: null,
+        ruleOfThirds: true,
+        safeAreas: true,
     });
     public readonly selectedTab = signal<string | undefined>(undefined);
 
@@ -107,20 +140,63 @@ export class ProjectStore {
             throw err;
         }
 
+        const { threadCount, camera, preset, overscan, cropWindow, ruleOfThirds, safeAreas } = this.renderOptions.value;
+        this.cameraNames.value = getCameraNames();
+        // a camera removed from the scene falls back to the default camera
+        const selectedCamera = camera && this.cameraNames.value.includes(camera) ? camera : null;
+        if (selectedCamera) {
+            setCamera(selectedCamera);
+        }
+        const renderSettings = preset ? await this.loadRenderSettings(preset) : null;
+        if (renderSettings) {
+            setRenderSettings(renderSettings);
+        }
+        setOverscan(overscan);
+        if (cropWindow) {
+            setCropWindow(cropWindow);
+        }
         const cameraInfo = getCameraInfo();
-        const { threadCount } = this.renderOptions.value;
         console.log(`Begin render ${cameraInfo.width}x${cameraInfo.height}`);
         this.cameraInfo.value = cameraInfo;
+        this.guides.value = getGuides(ruleOfThirds, safeAreas);
 
-        renderWorkerPool.render(threadCount, main, this.files.value, {
+        const options = {
             ...cameraInfo,
             ...this.renderOptions.value,
-            callback: (event) => {
+            camera: selectedCamera,
+            renderSettings,
+            callback: (event: RenderEvent): void => {
                 for (const listener of this.drawEventListeners) {
                     listener(event);
                 }
             },
-        });
+        };
+        if (typeof Worker === 'undefined') {
+            // without workers render the scene loaded above between frames
+            cooperativeRenderer.render(options);
+        } else {
+            renderWorkerPool.render(threadCount, main, this.files.value, options);
+        }
+    }
+
+    /** Returns the camera settings of the project's render preset, or null if the preset was removed. */
+    private async loadRenderSettings(name: string): Promise<RenderSettings | null> {
+        const projectId = this._project.value?.id;
+        if (!projectId) {
+            return null;
+        }
+        try {
+            const preset = await rayTracerApi.project.getRenderPreset({ projectId, name });
+            return {
+                imageWidth: preset.imageWidth ?? undefined,
+                imageHeight: preset.imageHeight ?? undefined,
+                samplesPerPixel: preset.samplesPerPixel ?? undefined,
+                maxDepth: preset.maxDepth ?? undefined,
+            };
+        } catch (err) {
+            console.warn(`could not load render preset, using the scene's settings (name: ${name})`, err);
+            return null;
+        }
     }
 
     private async loadProjectFiles(project: Project): Promise<WorkingFile[]> {
@@ -128,6 +204,18 @@ export class ProjectStore {
             project.files.map(async (f) => {
                 console.log(`getting project file (projectId: ${project.id}, filename: ${f.filename})`);
 
+                if (MESH_FILENAME.test(f.filename)) {
+                    // scenes import the mesh as converted by the server
+                    const response = (
+                        await rayTracerApi.project.getProjectMeshRaw({ projectId: project.id, filename: f.filename })
+                    ).raw;
+                    return {
+                        ...f,
+                        type: 'mesh',
+                        bytes: new Uint8Array(await response.arrayBuffer()),
+                    } satisfies MeshWorkingFile;
+                }
+
                 const response = (
                     await rayTracerApi.project.getProjectFileRaw({ projectId: project.id, filename: f.filename })
                 ).raw;
@@ -142,7 +230,12 @@ export class ProjectStore {
                     } satisfies TextWorkingFile;
                 } else {
                     const contents = await response.blob();
-                    const imageData = await getImageDataFromBlob(contents);
+                    const bytes = new Uint8Array(await contents.arrayBuffer());
+                    // the browser can't draw every format the raytracer reads (e.g. HDR), those have no preview
+                    const imageData = await getImageDataFromBlob(contents).catch((err: unknown) => {
+                        console.warn(`could not preview image (filename: ${f.filename})`, err);
+                        return { width: 0, height: 0, data: new Uint8ClampedArray() };
+                    });
 
                     return {
                         ...f,
@@ -150,6 +243,7 @@ export class ProjectStore {
                         width: imageData.width,
                         height: imageData.height,
                         pixels: imageData.data,
+                        bytes,
                     } satisfies ImageWorkingFile;
                 }
             })
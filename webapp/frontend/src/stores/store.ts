@@ -9,6 +9,7 @@ import {
 import { ProjectsStore } from './ProjectsStore';
 import { UserStore } from './UserStore';
 import { ProjectStore } from './ProjectStore';
+import type { CropWindow, TileOrder } from '../wasm';
 
 export class RayTracerApi {
     private config = new Configuration();
@@ -48,7 +49,23 @@ export type UnsubscribeFn = () => void;
 
 export interface RenderOptions {
     blockSize?: number;
+    /** Order blocks are rendered in */
+    tileOrder?: TileOrder;
     threadCount?: number;
+    /** Extra passes over every block rendered in idle time after the preview completes, 0 to disable */
+    refinementPasses?: number;
+    /** Name of the scene camera to render, null for the scene's default camera */
+    camera?: string | null;
+    /** Name of the project's render preset to render with, null for the scene's own settings */
+    preset?: string | null;
+    /** Margin rendered around the shot, as a fraction of the image size, 0 to disable */
+    overscan?: number;
+    /** Part of the shot to render, as fractions of the image size, null to render all of it */
+    cropWindow?: CropWindow | null;
+    /** Show rule of thirds guides when guides are shown */
+    ruleOfThirds?: boolean;
+    /** Show action and title safe area guides when guides are shown */
+    safeAreas?: boolean;
 }
 
 export interface StoreProject extends Project {
@@ -57,6 +74,7 @@ export interface StoreProject extends Project {
 
 export const CONTENT_TYPE_OPENSCAD = 'application/x-openscad';
 export const DEFAULT_RENDER_BLOCK_SIZE = 50;
+export const DEFAULT_REFINEMENT_PASSES = 3;
 export const EXAMPLE_CAR_ID = 'cad84577-c808-41a9-8d77-25a4626fe65f';
 
 export const projectStore = new ProjectStore();
@@ -1,5 +1,5 @@
 import type { ProjectFile } from './api';
-import type { Color } from './wasm';
+import type { Color, CropWindow, LinearColor, RenderSettings } from './wasm';
 
 export interface RenderResult {
     xmin: number;
@@ -9,15 +9,28 @@ export interface RenderResult {
     data: Color[];
 }
 
+export interface LinearRenderResult {
+    xmin: number;
+    xmax: number;
+    ymin: number;
+    ymax: number;
+    data: LinearColor[];
+}
+
 export interface RenderRequestInit {
     type: 'init';
     workerId: number;
     main: TextWorkingFile;
     files: WorkingFile[];
+    camera: string | null;
+    renderSettings: RenderSettings | null;
+    overscan: number;
+    cropWindow: CropWindow | null;
 }
 
 export interface RenderRequestWork {
     type: 'work';
+    renderId: number;
     xmin: number;
     xmax: number;
     ymin: number;
@@ -31,9 +44,10 @@ export interface RenderResponseInit {
     workerId: number;
 }
 
-export interface RenderResponseData extends RenderResult {
+export interface RenderResponseData extends LinearRenderResult {
     type: 'data';
     workerId: number;
+    renderId: number;
 }
 
 export type RenderResponse = RenderResponseInit | RenderResponseData;
@@ -49,6 +63,14 @@ export interface ImageWorkingFile extends ProjectFile {
     width: number;
     height: number;
     pixels: ImageDataArray;
+    /** Encoded file contents, decoded by the raytracer */
+    bytes: Uint8Array;
+}
+
+export interface MeshWorkingFile extends ProjectFile {
+    type: 'mesh';
+    /** Mesh converted by the server, see `MeshService` */
+    bytes: Uint8Array;
 }
 
-export type WorkingFile = TextWorkingFile | ImageWorkingFile;
+export type WorkingFile = TextWorkingFile | ImageWorkingFile | MeshWorkingFile;
@@ -0,0 +1,82 @@
+import type { LinearRenderResult, RenderResult } from '../types';
+import type { Color } from '../wasm';
+
+/**
+ * Averages passes of linear colors per pixel and tracks how many samples each
+ * block (tile) of the image has received, so tiles can be refined independently.
+ */
+export class AccumulationBuffer {
+    private readonly sums: Float64Array;
+    private readonly tileSamples: Uint32Array;
+    private readonly tileColumns: number;
+
+    public constructor(
+        public readonly width: number,
+        public readonly height: number,
+        public readonly blockSize: number
+    ) {
+        this.sums = new Float64Array(width * height * 3);
+        this.tileColumns = Math.ceil(width / blockSize);
+        this.tileSamples = new Uint32Array(this.tileColumns * Math.ceil(height / blockSize));
+    }
+
+    /**
+     * Adds a pass of `samples` samples per pixel to the block and returns the
+     * averaged, gamma corrected colors of the block for display.
+     */
+    public add(result: LinearRenderResult, samples: number): RenderResult {
+        const { xmin, xmax, ymin, ymax, data } = result;
+        const tile = this.tileIndex(xmin, ymin);
+        const previousSamples = this.tileSamples[tile];
+        const totalSamples = previousSamples + samples;
+        this.tileSamples[tile] = totalSamples;
+
+        const colors: Color[] = [];
+        let i = 0;
+        for (let y = ymin; y < ymax; y++) {
+            for (let x = xmin; x < xmax; x++) {
+                const { r, g, b } = data[i++];
+                const offset = (y * this.width + x) * 3;
+                // sums hold the color times the sample count, so passes with
+                // different sample counts are weighted correctly
+                this.sums[offset] += r * samples;
+                this.sums[offset + 1] += g * samples;
+                this.sums[offset + 2] += b * samples;
+                colors.push({
+                    r: toDisplay(this.sums[offset] / totalSamples),
+                    g: toDisplay(this.sums[offset + 1] / totalSamples),
+                    b: toDisplay(this.sums[offset + 2] / totalSamples),
+                });
+            }
+        }
+
+        return { xmin, xmax, ymin, ymax, data: colors };
+    }
+
+    /** Returns the number of samples per pixel accumulated in the block containing (x, y). */
+    public getTileSamples(x: number, y: number): number {
+        return this.tileSamples[this.tileIndex(x, y)];
+    }
+
+    /** Returns the sample count of the least refined block. */
+    public getMinTileSamples(): number {
+        return this.tileSamples.reduce((min, samples) => Math.min(min, samples), Number.MAX_SAFE_INTEGER);
+    }
+
+    private tileIndex(x: number, y: number): number {
+        const column = Math.floor(x / this.blockSize);
+        const row = Math.floor(y / this.blockSize);
+        return row * this.tileColumns + column;
+    }
+}
+
+/** Encodes a linear color component with the sRGB transfer function, matching the core renderer, and converts it to 0-255. */
+function toDisplay(v: number): number {
+    let encoded = 0;
+    if (v > 0.0031308) {
+        encoded = 1.055 * Math.pow(v, 1 / 2.4) - 0.055;
+    } else if (v > 0) {
+        encoded = 12.92 * v;
+    }
+    return Math.floor(Math.min(encoded, 0.999) * 255);
+}
@@ -1,4 +1,5 @@
 import type { ImageWorkingFile } from '../types';
+import type { GuideLine } from '../wasm';
 
 export function renderEmpty(ctx: CanvasRenderingContext2D, blockSize: number): void {
     for (let row = 0; ; row++) {
@@ -18,6 +19,15 @@ export function renderEmpty(ctx: CanvasRenderingContext2D, blockSize: number): v
     }
 }
 
+/** Clears the overlay and draws the guide lines, which cover the pixels between their ends. */
+export function renderGuides(ctx: CanvasRenderingContext2D, guides: GuideLine[]): void {
+    ctx.clearRect(0, 0, ctx.canvas.width, ctx.canvas.height);
+    for (const { x0, y0, x1, y1, color } of guides) {
+        ctx.fillStyle = `rgb(${color.r},${color.g},${color.b})`;
+        ctx.fillRect(x0, y0, x1 - x0 + 1, y1 - y0 + 1);
+    }
+}
+
 export interface ImageData {
     width: number;
     height: number;
@@ -2,33 +2,179 @@ import type { ImageWorkingFile, TextWorkingFile, WorkingFile } from './types.js'
 import type {
     CameraInfo,
     Color,
+    CooperativeRenderResult,
+    CropWindow,
+    GuideLine,
     InitOutput,
+    LinearColor,
     LoadResults,
+    MaterialInfo,
+    MaterialParameters,
+    RenderSettings,
+    TileOrder,
+    TileRect,
     WasmImage,
     WasmSource,
     WasmMessage,
 } from './wasm/debug/caustic_wasm';
-import init, { load_openscad, get_camera_info, render } from './wasm/debug/caustic_wasm.js';
+import init, {
+    dolly_camera,
+    load_openscad,
+    get_camera_info,
+    get_camera_names,
+    get_guides,
+    get_material_tiles,
+    get_materials,
+    get_tile_costs,
+    get_tiles,
+    orbit_camera,
+    pan_camera,
+    render,
+    render_linear,
+    render_for,
+    set_camera,
+    set_crop_window,
+    set_log_callback,
+    set_material_parameters,
+    set_overscan,
+    set_render_settings,
+    set_progress_callback,
+    set_tile_callback,
+    start_cooperative_render,
+} from './wasm/debug/caustic_wasm.js';
 export { WasmLspServer } from './wasm/debug/caustic_wasm.js';
 
-export type { CameraInfo, Color, WasmMessage };
+export type {
+    CameraInfo,
+    Color,
+    CooperativeRenderResult,
+    CropWindow,
+    GuideLine,
+    LinearColor,
+    MaterialInfo,
+    MaterialParameters,
+    RenderSettings,
+    TileOrder,
+    TileRect,
+    WasmMessage,
+};
 
 export function initWasm(): Promise<InitOutput> {
     return init();
 }
 
-export function loadOpenscad(source: Source): LoadResults {
-    return load_openscad(source);
+/** Loads the scene, with `editableMaterials` its materials can be changed afterwards at some cost to render speed. */
+export function loadOpenscad(source: Source, editableMaterials = false): LoadResults {
+    return load_openscad(source, editableMaterials);
 }
 
 export function getCameraInfo(): CameraInfo {
     return get_camera_info();
 }
 
+/** Returns the names of the loaded scene's cameras in alphabetical order. */
+export function getCameraNames(): string[] {
+    return get_camera_names();
+}
+
+/** Renders the named camera of the loaded scene instead of its default camera, call before setting overscan or a crop window. */
+export function setCamera(name: string): void {
+    set_camera(name);
+}
+
+/** Orbits the loaded scene's camera around the point it looks at, in degrees, without loading the scene again. Set overscan and crop windows afterwards. */
+export function orbitCamera(yaw: number, pitch: number): void {
+    orbit_camera(yaw, pitch);
+}
+
+/** Moves the loaded scene's camera towards the point it looks at, multiplying their distance by `factor`. */
+export function dollyCamera(factor: number): void {
+    dolly_camera(factor);
+}
+
+/** Moves the loaded scene's camera across the view by `right` and `up` times the height of the view. */
+export function panCamera(right: number, up: number): void {
+    pan_camera(right, up);
+}
+
+/** Renders the loaded scene with the settings of a render preset, unset settings keep the scene's. */
+export function setRenderSettings(settings: RenderSettings): void {
+    set_render_settings(settings);
+}
+
+/** Renders a margin of `overscan` times the image size around the shot of the loaded scene. */
+export function setOverscan(overscan: number): void {
+    set_overscan(overscan);
+}
+
+/** Renders only the part of the shot of the loaded scene in the window, given as fractions of the image size. */
+export function setCropWindow(cropWindow: CropWindow): void {
+    set_crop_window(cropWindow);
+}
+
+/** Returns the materials created by the loaded scene with their editable parameters, empty unless it was loaded with editable materials. */
+export function getMaterials(): MaterialInfo[] {
+    return get_materials();
+}
+
+/** Changes parameters of a material of the loaded scene without loading it again, render the tiles from `getMaterialTiles` again afterwards. */
+export function setMaterialParameters(id: number, parameters: MaterialParameters): void {
+    set_material_parameters(id, parameters);
+}
+
+/** Returns the indexes of the tiles showing a material of the loaded scene, casting one ray per `blockSize` square of pixels. */
+export function getMaterialTiles(id: number, tiles: TileRect[], blockSize: number): number[] {
+    return Array.from(get_material_tiles(id, tiles, blockSize));
+}
+
+/** Returns the composition guides of the loaded scene, the frame is outlined when there is overscan. */
+export function getGuides(ruleOfThirds: boolean, safeAreas: boolean): GuideLine[] {
+    return get_guides(ruleOfThirds, safeAreas);
+}
+
+/** Returns the blocks of a `width` by `height` image in the order they should be rendered. */
+export function getTiles(width: number, height: number, tileSize: number, order: TileOrder): TileRect[] {
+    return get_tiles(width, height, tileSize, order);
+}
+
+/** Estimates the relative cost of rendering each block of the loaded scene, casting one ray per `blockSize` square of pixels. */
+export function getTileCosts(tiles: TileRect[], blockSize: number): number[] {
+    return Array.from(get_tile_costs(tiles, blockSize));
+}
+
 export function renderBlock(xmin: number, xmax: number, ymin: number, ymax: number): Color[] {
     return render(xmin, xmax, ymin, ymax);
 }
 
+export function renderBlockLinear(xmin: number, xmax: number, ymin: number, ymax: number): LinearColor[] {
+    return render_linear(xmin, xmax, ymin, ymax);
+}
+
+/** Starts rendering the loaded scene on this thread, `passes` times the camera's samples per pixel. */
+export function startCooperativeRender(passes: number): void {
+    start_cooperative_render(passes);
+}
+
+/** Renders the cooperative render for about `ms` milliseconds and returns the rows that changed. */
+export function renderFor(ms: number): CooperativeRenderResult {
+    return render_for(ms);
+}
+
+/** Calls `callback` with the fraction of a cooperative render completed after every slice, or stops when undefined. */
+export function setProgressCallback(callback: ((progress: number) => void) | undefined): void {
+    set_progress_callback(callback);
+}
+
+/** Calls `callback` with the rectangle of every block or slice rendered on this thread, or stops when undefined. */
+export function setTileCallback(callback: ((tile: TileRect) => void) | undefined): void {
+    set_tile_callback(callback);
+}
+
+/** Calls `callback` with every message of a loaded scene, or stops when undefined. */
+export function setLogCallback(callback: ((message: WasmMessage) => void) | undefined): void {
+    set_log_callback(callback);
+}
+
 export class Source implements WasmSource {
     public constructor(
         private readonly main: TextWorkingFile,
@@ -53,6 +199,20 @@ export class Source implements WasmSource {
         }
         return new Image(file);
     }
+
+    public get_image_bytes(filename: string): Uint8Array | undefined {
+        const file = this.files.find((f) => f.filename === filename);
+        return file?.type === 'image' ? file.bytes : undefined;
+    }
+
+    public get_mesh_bytes(filename: string): Uint8Array | undefined {
+        const file = this.files.find((f) => f.filename === filename);
+        return file?.type === 'mesh' ? file.bytes : undefined;
+    }
+
+    public has_file(filename: string): boolean {
+        return this.files.some((f) => f.filename === filename);
+    }
 }
 
 export class Image implements WasmImage {
@@ -5,7 +5,16 @@ import type {
     RenderResponseData,
     RenderResponseInit,
 } from '../types';
-import { initWasm, loadOpenscad, renderBlock, Source } from '../wasm';
+import {
+    initWasm,
+    loadOpenscad,
+    renderBlockLinear,
+    setCamera,
+    setCropWindow,
+    setOverscan,
+    setRenderSettings,
+    Source,
+} from '../wasm';
 
 let workerId = -1;
 
@@ -27,19 +36,30 @@ async function init(data: RenderRequestInit): Promise<void> {
     console.log(`[${workerId}] initializing worker`);
     await initWasm();
     loadOpenscad(new Source(data.main, data.files));
+    if (data.camera) {
+        setCamera(data.camera);
+    }
+    if (data.renderSettings) {
+        setRenderSettings(data.renderSettings);
+    }
+    setOverscan(data.overscan);
+    if (data.cropWindow) {
+        setCropWindow(data.cropWindow);
+    }
 
     const resultsMessage: RenderResponseInit = { type: 'init', workerId };
     self.postMessage(resultsMessage);
 }
 
 function work(data: RenderRequestWork): void {
-    const { xmin, xmax, ymin, ymax } = data;
+    const { renderId, xmin, xmax, ymin, ymax } = data;
 
-    const results = renderBlock(xmin, xmax, ymin, ymax);
+    const results = renderBlockLinear(xmin, xmax, ymin, ymax);
 
     const resultsMessage: RenderResponseData = {
         type: 'data',
         workerId,
+        renderId,
         xmin,
         xmax,
         ymin,